@@ -0,0 +1,74 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+// Measures the throughput of the lock-free GPIO level methods. When the `mock`
+// feature is enabled and the memory-mapped GPIO registers aren't available, the
+// benchmark falls back to a simulated GPIO peripheral.
+//
+// Run with `cargo bench`. The pin number can be changed through the RPPAL_BENCH_PIN
+// environment variable (default: 23).
+
+use std::env;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use rppal::gpio::Gpio;
+
+const DEFAULT_PIN: u8 = 23;
+
+fn gpio() -> Gpio {
+    match Gpio::new() {
+        Ok(gpio) => gpio,
+        #[cfg(feature = "mock")]
+        Err(_) => Gpio::with_mock(&rppal::gpio::MockGpio::new()).expect("mock GPIO unavailable"),
+        #[cfg(not(feature = "mock"))]
+        Err(e) => panic!("GPIO unavailable ({}), try --features mock", e),
+    }
+}
+
+fn bench_gpio(c: &mut Criterion) {
+    let pin = env::var("RPPAL_BENCH_PIN")
+        .ok()
+        .and_then(|pin| pin.parse().ok())
+        .unwrap_or(DEFAULT_PIN);
+
+    let gpio = gpio();
+
+    let mut output = gpio.get(pin).expect("pin unavailable").into_output();
+    c.bench_function("toggle", |b| {
+        b.iter(|| {
+            output.set_high();
+            output.set_low();
+        })
+    });
+    c.bench_function("toggle_fast", |b| {
+        b.iter(|| {
+            output.set_high_fast();
+            output.set_low_fast();
+        })
+    });
+    drop(output);
+
+    let input = gpio.get(pin).expect("pin unavailable").into_input();
+    c.bench_function("is_high", |b| b.iter(|| black_box(input.is_high())));
+}
+
+criterion_group!(benches, bench_gpio);
+criterion_main!(benches);
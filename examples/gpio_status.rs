@@ -100,7 +100,7 @@ fn format_pin(
     mode: impl fmt::Display,
     level: impl fmt::Display,
 ) {
-    if pin % 2 != 0 {
+    if pin % 2 == 1 {
         buf.push_str(&format!(
             "| {:>4} | {:<5} | {:>1} | {:>2} |",
             gpio, mode, level, pin
@@ -167,8 +167,12 @@ fn main() {
         | Model::RaspberryPi3APlus
         | Model::RaspberryPi3B
         | Model::RaspberryPi3BPlus
+        | Model::RaspberryPi4B
+        | Model::RaspberryPi400
+        | Model::RaspberryPi5
         | Model::RaspberryPiZero
-        | Model::RaspberryPiZeroW => print_header(MAX_PINS_LONG),
+        | Model::RaspberryPiZeroW
+        | Model::RaspberryPiZero2W => print_header(MAX_PINS_LONG),
         model => {
             eprintln!("Error: No GPIO header information available for {}", model);
             exit(1);
@@ -0,0 +1,72 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Drivers for external analog-to-digital converters.
+//!
+//! The Raspberry Pi doesn't have any analog inputs. Analog signals can be measured
+//! with an external ADC instead, connected through either SPI or I2C.
+//!
+//! * [`Mcp300x`] supports the 10-bit MCP3004 (4 channels) and MCP3008 (8 channels),
+//!   connected through SPI.
+//! * [`Ads1x15`] supports the 12-bit ADS1015 and 16-bit ADS1115 (4 channels), with
+//!   a programmable gain amplifier and configurable sample rate, connected
+//!   through I2C.
+//!
+//! Both drivers support single-ended measurements, where a channel is measured
+//! relative to ground, and differential measurements between two channels.
+//!
+//! [`Mcp300x`]: struct.Mcp300x.html
+//! [`Ads1x15`]: struct.Ads1x15.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::i2c;
+use crate::spi;
+
+mod ads1x15;
+mod mcp300x;
+
+pub use self::ads1x15::{Ads1x15, Ads1x15Model, Gain};
+pub use self::mcp300x::{Mcp300x, Mcp300xModel};
+
+quick_error! {
+/// Errors that can occur when accessing an ADC.
+    #[derive(Debug)]
+    pub enum Error {
+/// SPI error.
+        Spi(err: spi::Error) { description(err.description()) from() }
+/// I2C error.
+        I2c(err: i2c::Error) { description(err.description()) from() }
+/// Invalid channel or channel combination.
+///
+/// The channel doesn't exist on the selected model, or the channels can't be
+/// combined for a differential measurement.
+        InvalidChannel(channel: u8) { description("invalid channel") }
+/// Timeout.
+///
+/// The conversion didn't complete in time.
+        Timeout { description("conversion didn't complete in time") }
+    }
+}
+
+/// Result type returned from methods that can have `adc::Error`s.
+pub type Result<T> = result::Result<T, Error>;
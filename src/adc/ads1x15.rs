@@ -0,0 +1,261 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::adc::{Error, Result};
+use crate::i2c::I2c;
+
+const REG_CONVERSION: u8 = 0x00;
+const REG_CONFIG: u8 = 0x01;
+
+// Start a single conversion when written, conversion complete when read
+const CONFIG_OS: u16 = 0x8000;
+const CONFIG_MODE_SINGLE_SHOT: u16 = 0x0100;
+// Comparator disabled, ALERT/RDY pin in high impedance
+const CONFIG_COMP_DISABLE: u16 = 0x0003;
+
+const SAMPLE_RATES_ADS1015: [u32; 8] = [128, 250, 490, 920, 1600, 2400, 3300, 3300];
+const SAMPLE_RATES_ADS1115: [u32; 8] = [8, 16, 32, 64, 128, 250, 475, 860];
+
+/// ADS1x15 models.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Ads1x15Model {
+    /// ADS1015. 12-bit resolution, up to 3300 samples per second.
+    Ads1015,
+    /// ADS1115. 16-bit resolution, up to 860 samples per second.
+    Ads1115,
+}
+
+impl Ads1x15Model {
+    fn sample_rates(self) -> &'static [u32; 8] {
+        match self {
+            Ads1x15Model::Ads1015 => &SAMPLE_RATES_ADS1015,
+            Ads1x15Model::Ads1115 => &SAMPLE_RATES_ADS1115,
+        }
+    }
+}
+
+/// Programmable gain amplifier settings.
+///
+/// Each setting is named after its full-scale range. Regardless of the selected
+/// range, the input voltage must stay between GND - 0.3 V and VDD + 0.3 V.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Gain {
+    /// ±6.144 V.
+    Fsr6_144V = 0,
+    /// ±4.096 V.
+    Fsr4_096V = 1,
+    /// ±2.048 V.
+    Fsr2_048V = 2,
+    /// ±1.024 V.
+    Fsr1_024V = 3,
+    /// ±0.512 V.
+    Fsr0_512V = 4,
+    /// ±0.256 V.
+    Fsr0_256V = 5,
+}
+
+impl Gain {
+    /// Returns the full-scale range in volts (V).
+    pub fn full_scale_range(self) -> f64 {
+        match self {
+            Gain::Fsr6_144V => 6.144,
+            Gain::Fsr4_096V => 4.096,
+            Gain::Fsr2_048V => 2.048,
+            Gain::Fsr1_024V => 1.024,
+            Gain::Fsr0_512V => 0.512,
+            Gain::Fsr0_256V => 0.256,
+        }
+    }
+}
+
+/// ADS1015 and ADS1115 ADC driver.
+///
+/// Conversions are started in single-shot mode, after which `Ads1x15` waits for the
+/// conversion to complete. The ADC powers down in between conversions.
+///
+/// The ADDR pin selects the slave address. Connecting it to GND, VDD, SDA or
+/// SCL results in address 0x48, 0x49, 0x4A or 0x4B respectively.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::adc::{Ads1x15, Ads1x15Model, Gain};
+/// use rppal::i2c::I2c;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut adc = Ads1x15::new(I2c::new()?, Ads1x15Model::Ads1115, 0x48)?;
+/// adc.set_gain(Gain::Fsr4_096V);
+/// adc.set_sample_rate(128);
+///
+/// println!("{:.4} V", adc.read_voltage(0)?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Ads1x15 {
+    i2c: I2c,
+    model: Ads1x15Model,
+    gain: Gain,
+    data_rate: u8,
+}
+
+impl Ads1x15 {
+    /// Constructs a new `Ads1x15` for the device at the specified slave address.
+    ///
+    /// The gain defaults to [`Gain::Fsr2_048V`], and the sample rate defaults to
+    /// 1600 samples per second (ADS1015) or 128 samples per second (ADS1115).
+    ///
+    /// [`Gain::Fsr2_048V`]: enum.Gain.html#variant.Fsr2_048V
+    pub fn new(mut i2c: I2c, model: Ads1x15Model, address: u16) -> Result<Ads1x15> {
+        i2c.set_slave_address(address)?;
+
+        Ok(Ads1x15 {
+            i2c,
+            model,
+            gain: Gain::Fsr2_048V,
+            data_rate: 4,
+        })
+    }
+
+    /// Returns the model.
+    pub fn model(&self) -> Ads1x15Model {
+        self.model
+    }
+
+    /// Returns the gain.
+    pub fn gain(&self) -> Gain {
+        self.gain
+    }
+
+    /// Sets the gain, which is applied to subsequent conversions.
+    pub fn set_gain(&mut self, gain: Gain) {
+        self.gain = gain;
+    }
+
+    /// Returns the sample rate in samples per second.
+    pub fn sample_rate(&self) -> u32 {
+        self.model.sample_rates()[self.data_rate as usize]
+    }
+
+    /// Sets the sample rate in samples per second, which is applied to subsequent
+    /// conversions.
+    ///
+    /// The closest rate supported by the model is selected. Lower sample rates
+    /// result in less noise.
+    pub fn set_sample_rate(&mut self, samples_per_second: u32) {
+        let rates = self.model.sample_rates();
+
+        self.data_rate = (0..rates.len())
+            .min_by_key(|&idx| (i64::from(rates[idx]) - i64::from(samples_per_second)).abs())
+            .unwrap_or(0) as u8;
+    }
+
+    /// Measures the voltage on `channel` (0-3) relative to ground, and returns the
+    /// raw value.
+    ///
+    /// ADS1015 results are left-aligned to 16 bits, with the lower 4 bits set to
+    /// 0, so values from both models can be scaled the same way.
+    pub fn read(&mut self, channel: u8) -> Result<i16> {
+        if channel > 3 {
+            return Err(Error::InvalidChannel(channel));
+        }
+
+        self.convert(0b100 | u16::from(channel))
+    }
+
+    /// Measures the voltage on `positive` relative to `negative`, and returns the
+    /// raw value.
+    ///
+    /// Supported combinations are 0 and 1, 0 and 3, 1 and 3, and 2 and 3.
+    pub fn read_differential(&mut self, positive: u8, negative: u8) -> Result<i16> {
+        let mux = match (positive, negative) {
+            (0, 1) => 0b000,
+            (0, 3) => 0b001,
+            (1, 3) => 0b010,
+            (2, 3) => 0b011,
+            _ => return Err(Error::InvalidChannel(negative)),
+        };
+
+        self.convert(mux)
+    }
+
+    /// Measures the voltage on `channel` (0-3) relative to ground, and returns
+    /// the voltage in volts (V) based on the configured gain.
+    pub fn read_voltage(&mut self, channel: u8) -> Result<f64> {
+        let value = self.read(channel)?;
+
+        Ok(self.to_voltage(value))
+    }
+
+    /// Measures the voltage on `positive` relative to `negative`, and returns
+    /// the voltage in volts (V) based on the configured gain.
+    pub fn read_differential_voltage(&mut self, positive: u8, negative: u8) -> Result<f64> {
+        let value = self.read_differential(positive, negative)?;
+
+        Ok(self.to_voltage(value))
+    }
+
+    fn to_voltage(&self, value: i16) -> f64 {
+        f64::from(value) * self.gain.full_scale_range() / 32768.0
+    }
+
+    fn convert(&mut self, mux: u16) -> Result<i16> {
+        let config = CONFIG_OS
+            | (mux << 12)
+            | ((self.gain as u16) << 9)
+            | CONFIG_MODE_SINGLE_SHOT
+            | (u16::from(self.data_rate) << 5)
+            | CONFIG_COMP_DISABLE;
+
+        self.i2c
+            .write(&[REG_CONFIG, (config >> 8) as u8, config as u8])?;
+
+        // Wait for the expected conversion time, and then poll the OS bit, since
+        // the internal oscillator is only accurate to within 10%
+        let conversion_time = Duration::from_micros(1_000_000 / u64::from(self.sample_rate()));
+        sleep(conversion_time);
+
+        let deadline = Instant::now() + conversion_time * 2 + Duration::from_millis(1);
+        while self.read_register(REG_CONFIG)? & CONFIG_OS == 0 {
+            if Instant::now() > deadline {
+                return Err(Error::Timeout);
+            }
+
+            sleep(Duration::from_micros(100));
+        }
+
+        Ok(self.read_register(REG_CONVERSION)? as i16)
+    }
+
+    fn read_register(&self, register: u8) -> Result<u16> {
+        let mut buffer = [0u8; 2];
+        self.i2c.write_read(&[register], &mut buffer)?;
+
+        Ok((u16::from(buffer[0]) << 8) | u16::from(buffer[1]))
+    }
+
+    /// Consumes the `Ads1x15`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+}
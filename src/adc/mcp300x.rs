@@ -0,0 +1,136 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::adc::{Error, Result};
+use crate::spi::Spi;
+
+/// MCP300x models.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Mcp300xModel {
+    /// MCP3004. 4 single-ended or 2 differential channels.
+    Mcp3004,
+    /// MCP3008. 8 single-ended or 4 differential channels.
+    Mcp3008,
+}
+
+impl Mcp300xModel {
+    /// Returns the number of single-ended channels.
+    pub fn channels(self) -> u8 {
+        match self {
+            Mcp300xModel::Mcp3004 => 4,
+            Mcp300xModel::Mcp3008 => 8,
+        }
+    }
+}
+
+/// MCP3004 and MCP3008 10-bit ADC driver.
+///
+/// The maximum SPI clock speed is 1.35 MHz when the ADC is powered at 2.7 V,
+/// and 3.6 MHz at 5 V. The ADC supports SPI [`Mode0`] and [`Mode3`].
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::adc::{Mcp300x, Mcp300xModel};
+/// use rppal::spi::{Bus, Mode, SlaveSelect, Spi};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let spi = Spi::new(Bus::Spi0, SlaveSelect::Ss0, 1_000_000, Mode::Mode0)?;
+/// let adc = Mcp300x::new(spi, Mcp300xModel::Mcp3008);
+///
+/// println!("{:.3} V", adc.read_voltage(0, 3.3)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Mode0`]: ../spi/enum.Mode.html#variant.Mode0
+/// [`Mode3`]: ../spi/enum.Mode.html#variant.Mode3
+#[derive(Debug)]
+pub struct Mcp300x {
+    spi: Spi,
+    model: Mcp300xModel,
+}
+
+impl Mcp300x {
+    /// Maximum value returned by a conversion.
+    pub const MAX_VALUE: u16 = 1023;
+
+    /// Constructs a new `Mcp300x`.
+    pub fn new(spi: Spi, model: Mcp300xModel) -> Mcp300x {
+        Mcp300x { spi, model }
+    }
+
+    /// Returns the model.
+    pub fn model(&self) -> Mcp300xModel {
+        self.model
+    }
+
+    /// Measures the voltage on `channel` relative to ground, and returns the
+    /// raw value between 0 and 1023.
+    pub fn read(&self, channel: u8) -> Result<u16> {
+        if channel >= self.model.channels() {
+            return Err(Error::InvalidChannel(channel));
+        }
+
+        self.convert(true, channel)
+    }
+
+    /// Measures the voltage on `positive` relative to `negative`, and returns the
+    /// raw value between 0 and 1023.
+    ///
+    /// The channels have to be part of the same pair (0 and 1, 2 and 3, etc.),
+    /// in either order. Negative voltages are returned as 0.
+    pub fn read_differential(&self, positive: u8, negative: u8) -> Result<u16> {
+        if positive >= self.model.channels() || positive / 2 != negative / 2 || positive == negative
+        {
+            return Err(Error::InvalidChannel(negative));
+        }
+
+        // The channel bits select the pair, and whether the odd channel is the
+        // positive input
+        self.convert(false, positive)
+    }
+
+    /// Measures the voltage on `channel` relative to ground, and returns the
+    /// voltage based on the reference voltage on the ADC's VREF pin.
+    pub fn read_voltage(&self, channel: u8, vref: f64) -> Result<f64> {
+        Ok(f64::from(self.read(channel)?) * vref / f64::from(Mcp300x::MAX_VALUE + 1))
+    }
+
+    fn convert(&self, single_ended: bool, channel: u8) -> Result<u16> {
+        // Start bit, followed by the single-ended/differential bit and the channel
+        // bits. The result is clocked out in the last 10 bits.
+        let write_buffer = [
+            0x01,
+            (if single_ended { 0x80 } else { 0x00 }) | ((channel & 0x07) << 4),
+            0x00,
+        ];
+        let mut read_buffer = [0u8; 3];
+
+        self.spi.transfer(&mut read_buffer, &write_buffer)?;
+
+        Ok((u16::from(read_buffer[1] & 0x03) << 8) | u16::from(read_buffer[2]))
+    }
+
+    /// Consumes the `Mcp300x`, and returns the underlying `Spi` instance.
+    pub fn into_spi(self) -> Spi {
+        self.spi
+    }
+}
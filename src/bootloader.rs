@@ -0,0 +1,105 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Serial bootloader helpers for reflashing attached microcontrollers.
+//!
+//! Most microcontroller development boards are programmed through a serial
+//! bootloader, which is started by toggling the reset and boot strapping pins in
+//! a specific sequence. On a PC, USB-to-serial adapters handle this through their
+//! DTR and RTS lines. When a microcontroller is connected directly to the
+//! Raspberry Pi's UART, the same sequences can be generated with GPIO pins.
+//!
+//! * [`Stk500`] resets an AVR-based board, like the Arduino Uno, into its
+//!   bootloader (Optiboot or compatible), and programs and verifies the flash
+//!   memory using the STK500v1 protocol.
+//! * [`EspBootloader`] emulates esptool's DTR/RTS reset sequence on the EN and
+//!   GPIO0 pins of an ESP8266 or ESP32, and synchronizes with the ROM
+//!   bootloader. After entering the bootloader, the [`Uart`] can be released
+//!   for use by other tools, like esptool with `--before no_reset`.
+//!
+//! Many 5 V boards need a level shifter on the UART and reset lines.
+//!
+//! [`Stk500`]: struct.Stk500.html
+//! [`EspBootloader`]: struct.EspBootloader.html
+//! [`Uart`]: ../uart/struct.Uart.html
+
+use std::result;
+use std::time::{Duration, Instant};
+
+use quick_error::quick_error;
+
+use crate::uart::{self, Uart};
+
+mod esp;
+mod stk500;
+
+pub use self::esp::EspBootloader;
+pub use self::stk500::Stk500;
+
+quick_error! {
+/// Errors that can occur when communicating with a bootloader.
+    #[derive(Debug)]
+    pub enum Error {
+/// UART error.
+        Uart(err: uart::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Timeout.
+///
+/// The bootloader didn't respond in time.
+        Timeout { description("bootloader didn't respond in time") }
+/// Not in sync.
+///
+/// The bootloader didn't acknowledge the command with the expected sync byte.
+        NotInSync { description("bootloader not in sync") }
+/// Invalid response.
+///
+/// The response doesn't match the command, or has an unexpected length.
+        InvalidResponse { description("invalid response") }
+/// Command failed.
+///
+/// The bootloader rejected the command. Contains the error code returned by
+/// the bootloader, or 0 if the protocol doesn't report one.
+        Failed(code: u8) { description("command failed") }
+/// Verification failed.
+///
+/// The data read back from the flash memory doesn't match the data that was
+/// written. Contains the address of the first mismatch.
+        VerifyFailed(address: u32) { description("verification failed") }
+    }
+}
+
+/// Result type returned from methods that can have `bootloader::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+// Fills buffer with incoming data, or returns Error::Timeout if not enough data
+// arrived before the timeout elapsed
+fn read_exact(uart: &mut Uart, buffer: &mut [u8], timeout: Duration) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    let mut received = 0;
+
+    while received < buffer.len() {
+        received += uart.read(&mut buffer[received..])?;
+
+        if received < buffer.len() && Instant::now() > deadline {
+            return Err(Error::Timeout);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,306 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::bootloader::{Error, Result};
+use crate::gpio::OutputPin;
+use crate::uart::{Queue, Uart};
+
+// SLIP framing
+const SLIP_END: u8 = 0xc0;
+const SLIP_ESC: u8 = 0xdb;
+const SLIP_ESC_END: u8 = 0xdc;
+const SLIP_ESC_ESC: u8 = 0xdd;
+
+const DIRECTION_REQUEST: u8 = 0x00;
+const DIRECTION_RESPONSE: u8 = 0x01;
+
+const CMD_SYNC: u8 = 0x08;
+const CMD_READ_REG: u8 = 0x0a;
+
+// Response header: direction, command, size (2 bytes), value (4 bytes)
+const RESPONSE_HEADER_SIZE: usize = 8;
+
+// Matches the timing of esptool's classic reset sequence
+const RESET_DELAY: Duration = Duration::from_millis(100);
+const BOOT_DELAY: Duration = Duration::from_millis(50);
+const SYNC_ATTEMPTS: u32 = 7;
+const SYNC_TIMEOUT: Duration = Duration::from_millis(100);
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// ESP8266 and ESP32 ROM bootloader helper.
+///
+/// The ESP8266 and ESP32 enter their ROM bootloader when GPIO0 is held low
+/// while the chip is released from reset through its EN (CHIP_PU) pin. On
+/// development boards, esptool toggles both pins through the DTR and RTS lines
+/// of the USB-to-serial adapter. `EspBootloader` performs the same sequence on
+/// two [`OutputPin`]s, and synchronizes with the ROM bootloader over the [`Uart`].
+///
+/// `EspBootloader` only implements the commands needed to verify the connection.
+/// To flash firmware, call [`enter_bootloader`], release the `Uart` with
+/// [`into_parts`], and run esptool with the `--before no_reset` option.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::bootloader::EspBootloader;
+/// use rppal::gpio::Gpio;
+/// use rppal::uart::{Device, Parity, Uart};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gpio = Gpio::new()?;
+/// let uart = Uart::new(Device::Uart0, 115_200, Parity::None, 8, 1)?;
+/// let mut esp = EspBootloader::new(
+///     uart,
+///     gpio.get(17).unwrap().into_output_high(),
+///     gpio.get(27).unwrap().into_output_high(),
+/// )?;
+///
+/// esp.connect()?;
+/// println!("Chip magic: {:#010x}", esp.read_register(0x4000_1000)?);
+///
+/// esp.reset();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`OutputPin`]: ../gpio/struct.OutputPin.html
+/// [`Uart`]: ../uart/struct.Uart.html
+/// [`enter_bootloader`]: #method.enter_bootloader
+/// [`into_parts`]: #method.into_parts
+#[derive(Debug)]
+pub struct EspBootloader {
+    uart: Uart,
+    enable: OutputPin,
+    boot: OutputPin,
+    timeout: Duration,
+}
+
+impl EspBootloader {
+    /// Constructs a new `EspBootloader`.
+    ///
+    /// `enable` is connected to the EN (CHIP_PU) pin, and `boot` to GPIO0. Both
+    /// pins are set high, which lets the chip run normally. The read mode of `uart`
+    /// is changed to wait up to 100 ms for incoming data.
+    pub fn new(
+        mut uart: Uart,
+        mut enable: OutputPin,
+        mut boot: OutputPin,
+    ) -> Result<EspBootloader> {
+        uart.set_read_mode(0, Duration::from_millis(100))?;
+        enable.set_high();
+        boot.set_high();
+
+        Ok(EspBootloader {
+            uart,
+            enable,
+            boot,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Returns the response timeout.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Sets the response timeout.
+    ///
+    /// By default, the timeout is set to 3 seconds.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Resets the chip into its ROM bootloader.
+    ///
+    /// GPIO0 is held low while EN is released, and is released afterwards.
+    pub fn enter_bootloader(&mut self) {
+        self.boot.set_high();
+        self.enable.set_low();
+        sleep(RESET_DELAY);
+
+        self.boot.set_low();
+        self.enable.set_high();
+        sleep(BOOT_DELAY);
+
+        self.boot.set_high();
+    }
+
+    /// Resets the chip, and lets it boot normally.
+    pub fn reset(&mut self) {
+        self.boot.set_high();
+        self.enable.set_low();
+        sleep(RESET_DELAY);
+
+        self.enable.set_high();
+    }
+
+    /// Resets the chip into its ROM bootloader, and synchronizes with it.
+    ///
+    /// Returns [`Error::Timeout`] if the bootloader didn't respond.
+    ///
+    /// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+    pub fn connect(&mut self) -> Result<()> {
+        self.enter_bootloader();
+
+        for _ in 0..SYNC_ATTEMPTS {
+            self.uart.flush(Queue::Input)?;
+
+            match self.sync_with_timeout(SYNC_TIMEOUT) {
+                Ok(()) => return Ok(()),
+                Err(Error::Timeout) | Err(Error::InvalidResponse) => (),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    /// Sends a SYNC command, which the bootloader also uses to detect the baud
+    /// rate.
+    pub fn sync(&mut self) -> Result<()> {
+        self.sync_with_timeout(self.timeout)
+    }
+
+    /// Reads a 32-bit register from the chip's address space.
+    ///
+    /// The value at address 0x40001000 identifies the chip family.
+    pub fn read_register(&mut self, address: u32) -> Result<u32> {
+        self.command(CMD_READ_REG, &address.to_le_bytes(), self.timeout)
+    }
+
+    /// Consumes the `EspBootloader`, and returns the underlying `Uart`, and the
+    /// EN and GPIO0 pins.
+    pub fn into_parts(self) -> (Uart, OutputPin, OutputPin) {
+        (self.uart, self.enable, self.boot)
+    }
+
+    fn sync_with_timeout(&mut self, timeout: Duration) -> Result<()> {
+        let mut data = vec![0x07, 0x07, 0x12, 0x20];
+        data.resize(36, 0x55);
+
+        self.command(CMD_SYNC, &data, timeout)?;
+
+        // The bootloader sends several additional responses to a SYNC command
+        sleep(SYNC_TIMEOUT);
+        self.uart.flush(Queue::Input)?;
+
+        Ok(())
+    }
+
+    // Sends a command packet, and returns the value field of the response.
+    // Responses to other commands are skipped.
+    fn command(&mut self, command: u8, data: &[u8], timeout: Duration) -> Result<u32> {
+        let mut packet = Vec::with_capacity(data.len() + 8);
+        packet.push(DIRECTION_REQUEST);
+        packet.push(command);
+        packet.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        // The checksum is only used by data transfer commands
+        packet.extend_from_slice(&[0u8; 4]);
+        packet.extend_from_slice(data);
+
+        self.uart.write(&slip_encode(&packet))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let response = self.read_frame(deadline)?;
+
+            if response.len() < RESPONSE_HEADER_SIZE
+                || response[0] != DIRECTION_RESPONSE
+                || response[1] != command
+            {
+                continue;
+            }
+
+            let size = u16::from_le_bytes([response[2], response[3]]) as usize;
+            let value = u32::from_le_bytes([response[4], response[5], response[6], response[7]]);
+            let status = &response[RESPONSE_HEADER_SIZE..];
+
+            // The ESP8266 returns 2 status bytes, and the ESP32 returns 4
+            if status.len() != size || size < 2 {
+                return Err(Error::InvalidResponse);
+            }
+
+            if status[0] != 0 {
+                return Err(Error::Failed(status[1]));
+            }
+
+            return Ok(value);
+        }
+    }
+
+    // Reads and decodes a single SLIP frame
+    fn read_frame(&mut self, deadline: Instant) -> Result<Vec<u8>> {
+        let mut frame = Vec::new();
+        let mut in_frame = false;
+        let mut escaped = false;
+        let mut byte = [0u8; 1];
+
+        loop {
+            if self.uart.read(&mut byte)? == 0 {
+                if Instant::now() > deadline {
+                    return Err(Error::Timeout);
+                }
+
+                continue;
+            }
+
+            match (byte[0], escaped) {
+                (SLIP_END, _) if in_frame && !frame.is_empty() => return Ok(frame),
+                (SLIP_END, _) => {
+                    in_frame = true;
+                    frame.clear();
+                }
+                _ if !in_frame => (),
+                (SLIP_ESC, false) => escaped = true,
+                (SLIP_ESC_END, true) => {
+                    frame.push(SLIP_END);
+                    escaped = false;
+                }
+                (SLIP_ESC_ESC, true) => {
+                    frame.push(SLIP_ESC);
+                    escaped = false;
+                }
+                (_, true) => return Err(Error::InvalidResponse),
+                (value, false) => frame.push(value),
+            }
+        }
+    }
+}
+
+fn slip_encode(packet: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(packet.len() + 2);
+    frame.push(SLIP_END);
+
+    for &byte in packet {
+        match byte {
+            SLIP_END => frame.extend_from_slice(&[SLIP_ESC, SLIP_ESC_END]),
+            SLIP_ESC => frame.extend_from_slice(&[SLIP_ESC, SLIP_ESC_ESC]),
+            _ => frame.push(byte),
+        }
+    }
+
+    frame.push(SLIP_END);
+
+    frame
+}
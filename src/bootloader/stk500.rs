@@ -0,0 +1,313 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::bootloader::{read_exact, Error, Result};
+use crate::gpio::OutputPin;
+use crate::uart::{Queue, Uart};
+
+const STK_OK: u8 = 0x10;
+const STK_FAILED: u8 = 0x11;
+const STK_INSYNC: u8 = 0x14;
+const CRC_EOP: u8 = 0x20;
+
+const CMD_GET_SYNC: u8 = 0x30;
+const CMD_GET_PARAMETER: u8 = 0x41;
+const CMD_ENTER_PROGMODE: u8 = 0x50;
+const CMD_LEAVE_PROGMODE: u8 = 0x51;
+const CMD_LOAD_ADDRESS: u8 = 0x55;
+const CMD_PROG_PAGE: u8 = 0x64;
+const CMD_READ_PAGE: u8 = 0x74;
+const CMD_READ_SIGN: u8 = 0x75;
+
+const PARAM_SW_MAJOR: u8 = 0x82;
+const PARAM_SW_MINOR: u8 = 0x83;
+
+const MEMORY_FLASH: u8 = b'F';
+
+const RESET_PULSE: Duration = Duration::from_millis(10);
+// Time until the bootloader is ready to receive commands
+const RESET_DELAY: Duration = Duration::from_millis(50);
+const SYNC_ATTEMPTS: u32 = 10;
+const SYNC_TIMEOUT: Duration = Duration::from_millis(100);
+
+const DEFAULT_PAGE_SIZE: usize = 128;
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// STK500v1 bootloader client for AVR microcontrollers.
+///
+/// `Stk500` communicates with the Optiboot bootloader used by the Arduino Uno,
+/// Nano and Pro Mini, and other bootloaders that implement the same subset of
+/// the STK500v1 protocol. The bootloader is started by pulsing the
+/// microcontroller's active-low RESET pin, which is connected to an [`OutputPin`].
+///
+/// The flash memory is written in pages. The page size defaults to 128 bytes,
+/// which matches the ATmega328P. Addresses are specified in bytes.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::bootloader::Stk500;
+/// use rppal::gpio::Gpio;
+/// use rppal::uart::{Device, Parity, Uart};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let uart = Uart::new(Device::Uart0, 115_200, Parity::None, 8, 1)?;
+/// let reset = Gpio::new()?.get(17).unwrap().into_output_high();
+/// let mut stk500 = Stk500::new(uart, reset)?;
+///
+/// let firmware = std::fs::read("firmware.bin")?;
+/// stk500.flash(&firmware)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`OutputPin`]: ../gpio/struct.OutputPin.html
+#[derive(Debug)]
+pub struct Stk500 {
+    uart: Uart,
+    reset: OutputPin,
+    page_size: usize,
+    timeout: Duration,
+}
+
+impl Stk500 {
+    /// Constructs a new `Stk500`.
+    ///
+    /// The read mode of `uart` is changed to wait up to 100 ms for incoming data,
+    /// and `reset` is set high, which releases the microcontroller's RESET pin.
+    pub fn new(mut uart: Uart, mut reset: OutputPin) -> Result<Stk500> {
+        uart.set_read_mode(0, Duration::from_millis(100))?;
+        reset.set_high();
+
+        Ok(Stk500 {
+            uart,
+            reset,
+            page_size: DEFAULT_PAGE_SIZE,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Returns the flash page size in bytes.
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Sets the flash page size in bytes.
+    ///
+    /// By default, the page size is set to 128 bytes. Values below 2 are
+    /// clamped.
+    pub fn set_page_size(&mut self, page_size: usize) {
+        self.page_size = page_size.max(2);
+    }
+
+    /// Returns the response timeout.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Sets the response timeout.
+    ///
+    /// By default, the timeout is set to 500 ms.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Resets the microcontroller, and synchronizes with the bootloader.
+    ///
+    /// Returns [`Error::Timeout`] if the bootloader didn't respond.
+    ///
+    /// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+    pub fn reset(&mut self) -> Result<()> {
+        self.reset.set_low();
+        sleep(RESET_PULSE);
+        self.reset.set_high();
+        sleep(RESET_DELAY);
+
+        self.uart.flush(Queue::Input)?;
+
+        for _ in 0..SYNC_ATTEMPTS {
+            match self.command_with_timeout(&[CMD_GET_SYNC], &mut [], SYNC_TIMEOUT) {
+                Ok(()) => return Ok(()),
+                Err(Error::Timeout) | Err(Error::NotInSync) => {
+                    self.uart.flush(Queue::Input)?;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    /// Checks if the bootloader is still in sync.
+    pub fn sync(&mut self) -> Result<()> {
+        self.command(&[CMD_GET_SYNC], &mut [])
+    }
+
+    /// Returns the bootloader's major and minor version number.
+    pub fn version(&mut self) -> Result<(u8, u8)> {
+        let mut major = [0u8; 1];
+        let mut minor = [0u8; 1];
+
+        self.command(&[CMD_GET_PARAMETER, PARAM_SW_MAJOR], &mut major)?;
+        self.command(&[CMD_GET_PARAMETER, PARAM_SW_MINOR], &mut minor)?;
+
+        Ok((major[0], minor[0]))
+    }
+
+    /// Returns the microcontroller's 3-byte device signature.
+    ///
+    /// The ATmega328P returns `[0x1e, 0x95, 0x0f]`.
+    pub fn signature(&mut self) -> Result<[u8; 3]> {
+        let mut signature = [0u8; 3];
+        self.command(&[CMD_READ_SIGN], &mut signature)?;
+
+        Ok(signature)
+    }
+
+    /// Enters programming mode.
+    pub fn enter_programming_mode(&mut self) -> Result<()> {
+        self.command(&[CMD_ENTER_PROGMODE], &mut [])
+    }
+
+    /// Leaves programming mode.
+    ///
+    /// Most bootloaders start the application afterwards.
+    pub fn leave_programming_mode(&mut self) -> Result<()> {
+        self.command(&[CMD_LEAVE_PROGMODE], &mut [])
+    }
+
+    /// Fills `buffer` with data read from the flash memory, starting at the
+    /// specified address.
+    ///
+    /// `address` needs to be even.
+    pub fn read_flash(&mut self, address: u32, buffer: &mut [u8]) -> Result<()> {
+        let page_size = self.page_size;
+
+        for (index, chunk) in buffer.chunks_mut(page_size).enumerate() {
+            self.load_address(address + (index * page_size) as u32)?;
+
+            let len = chunk.len() as u16;
+            self.command(
+                &[CMD_READ_PAGE, (len >> 8) as u8, len as u8, MEMORY_FLASH],
+                chunk,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `data` to the flash memory, starting at the specified address.
+    ///
+    /// `address` needs to be aligned to the page size. The bootloader erases
+    /// each page before it's written, so the last page is padded with 0xFF.
+    pub fn write_flash(&mut self, address: u32, data: &[u8]) -> Result<()> {
+        let page_size = self.page_size;
+        let mut request = Vec::with_capacity(page_size + 4);
+
+        for (index, chunk) in data.chunks(page_size).enumerate() {
+            self.load_address(address + (index * page_size) as u32)?;
+
+            let len = page_size as u16;
+            request.clear();
+            request.extend_from_slice(&[CMD_PROG_PAGE, (len >> 8) as u8, len as u8, MEMORY_FLASH]);
+            request.extend_from_slice(chunk);
+            request.resize(page_size + 4, 0xff);
+
+            self.command(&request, &mut [])?;
+        }
+
+        Ok(())
+    }
+
+    /// Resets the microcontroller, writes `data` to the start of the flash memory,
+    /// verifies it, and starts the application.
+    ///
+    /// Returns [`Error::VerifyFailed`] if the data read back from the flash memory
+    /// doesn't match.
+    ///
+    /// [`Error::VerifyFailed`]: enum.Error.html#variant.VerifyFailed
+    pub fn flash(&mut self, data: &[u8]) -> Result<()> {
+        self.reset()?;
+        self.enter_programming_mode()?;
+        self.write_flash(0, data)?;
+
+        let mut buffer = vec![0u8; data.len()];
+        self.read_flash(0, &mut buffer)?;
+
+        if let Some(index) = data.iter().zip(&buffer).position(|(a, b)| a != b) {
+            return Err(Error::VerifyFailed(index as u32));
+        }
+
+        self.leave_programming_mode()
+    }
+
+    /// Consumes the `Stk500`, and returns the underlying `Uart` and reset pin.
+    pub fn into_parts(self) -> (Uart, OutputPin) {
+        (self.uart, self.reset)
+    }
+
+    // The bootloader expects flash addresses in 16-bit words
+    fn load_address(&mut self, address: u32) -> Result<()> {
+        let word_address = (address / 2) as u16;
+
+        self.command(
+            &[
+                CMD_LOAD_ADDRESS,
+                word_address as u8,
+                (word_address >> 8) as u8,
+            ],
+            &mut [],
+        )
+    }
+
+    fn command(&mut self, request: &[u8], response: &mut [u8]) -> Result<()> {
+        self.command_with_timeout(request, response, self.timeout)
+    }
+
+    // Sends request followed by CRC_EOP, and expects STK_INSYNC, the response
+    // data, and STK_OK
+    fn command_with_timeout(
+        &mut self,
+        request: &[u8],
+        response: &mut [u8],
+        timeout: Duration,
+    ) -> Result<()> {
+        self.uart.write(request)?;
+        self.uart.write(&[CRC_EOP])?;
+
+        let mut status = [0u8; 1];
+        read_exact(&mut self.uart, &mut status, timeout)?;
+        if status[0] != STK_INSYNC {
+            return Err(Error::NotInSync);
+        }
+
+        read_exact(&mut self.uart, response, timeout)?;
+
+        read_exact(&mut self.uart, &mut status, timeout)?;
+        match status[0] {
+            STK_OK => Ok(()),
+            STK_FAILED => Err(Error::Failed(0)),
+            _ => Err(Error::InvalidResponse),
+        }
+    }
+}
@@ -0,0 +1,364 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Exposure triggering and strobe synchronization for externally triggered cameras.
+//!
+//! Machine vision cameras, and Raspberry Pi camera modules with an external
+//! trigger input (such as the Global Shutter Camera's XTR pin), start an
+//! exposure when their trigger input is pulsed. [`Trigger`] generates accurately
+//! timed trigger pulses on any available GPIO pin, either on demand through
+//! [`fire`], or periodically at a fixed frame rate on a background thread
+//! through [`start`].
+//!
+//! Many cameras also output a frame-sync or strobe signal while the sensor is
+//! exposing, which is used to synchronize external lighting. A strobe input
+//! configured through [`set_strobe`] is timestamped by the kernel when an edge
+//! occurs, and can be read with [`poll_strobe`].
+//!
+//! Pulses are timed by the CPU. The pulse width is accurate to within a few
+//! microseconds, but the start of a pulse can be delayed when the thread is
+//! preempted. Triggering from a background thread is more accurate when the
+//! application runs with superuser privileges, which enables real-time
+//! scheduling.
+//!
+//! Trigger timestamps are based on `CLOCK_MONOTONIC`. Strobe timestamps are set
+//! by the kernel, and are only based on the same clock on Linux 5.7 or later.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rppal::camera::Trigger;
+//! use rppal::gpio::{self, Gpio};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let gpio = Gpio::new()?;
+//! let mut trigger = Trigger::new(
+//!     gpio.get(17).unwrap().into_output(),
+//!     Duration::from_micros(100),
+//! );
+//! trigger.set_strobe(gpio.get(27).unwrap().into_input(), gpio::Trigger::RisingEdge)?;
+//!
+//! if let Some(latency) = trigger.fire_and_wait(Some(Duration::from_millis(100)))? {
+//!     println!("Exposure started {} µs after the trigger", latency.as_micros());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Trigger`]: struct.Trigger.html
+//! [`fire`]: struct.Trigger.html#method.fire
+//! [`start`]: struct.Trigger.html#method.start
+//! [`set_strobe`]: struct.Trigger.html#method.set_strobe
+//! [`poll_strobe`]: struct.Trigger.html#method.poll_strobe
+
+use std::result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use quick_error::quick_error;
+
+use crate::delay::{delay_until_ns, duration_to_ns, get_time_ns};
+use crate::gpio::soft_pwm::set_realtime_priority;
+use crate::gpio::{self, InputPin, Level, OutputPin};
+
+quick_error! {
+/// Errors that can occur when triggering a camera.
+    #[derive(Debug)]
+    pub enum Error {
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// No strobe input is configured.
+///
+/// Use [`Trigger::set_strobe`] to configure a strobe input first.
+///
+/// [`Trigger::set_strobe`]: struct.Trigger.html#method.set_strobe
+        StrobeNotConfigured { description("no strobe input configured") }
+/// Periodic triggering is active.
+///
+/// Individual trigger pulses can't be generated while the trigger is running
+/// periodically. Use [`Trigger::stop`] to stop periodic triggering first.
+///
+/// [`Trigger::stop`]: struct.Trigger.html#method.stop
+        Running { description("periodic triggering is active") }
+/// Background thread panicked.
+        ThreadPanic { description("trigger thread panicked") }
+    }
+}
+
+/// Result type returned from methods that can have `camera::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+// Output pin and pulse configuration, shared with the background thread.
+#[derive(Debug)]
+struct Output {
+    pin: OutputPin,
+    active_level: Level,
+    pulse_width_ns: i64,
+}
+
+impl Output {
+    // Generates a single pulse, and returns the time the leading edge was set.
+    fn pulse(&mut self) -> i64 {
+        let start_ns = get_time_ns();
+        self.pin.write(self.active_level);
+        delay_until_ns(start_ns + self.pulse_width_ns);
+        self.pin.write(inactive_level(self.active_level));
+
+        start_ns
+    }
+}
+
+fn inactive_level(active_level: Level) -> Level {
+    match active_level {
+        Level::Low => Level::High,
+        Level::High => Level::Low,
+    }
+}
+
+/// Camera exposure trigger with an optional strobe input.
+///
+/// The trigger output is set to its inactive level when `Trigger` is
+/// constructed. By default, trigger pulses are active-high.
+///
+/// More information can be found in the [module documentation].
+///
+/// [module documentation]: index.html
+#[derive(Debug)]
+pub struct Trigger {
+    output: Arc<Mutex<Output>>,
+    strobe: Option<InputPin>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Trigger {
+    /// Constructs a new `Trigger` that pulses `pin` for the specified duration.
+    pub fn new(mut pin: OutputPin, pulse_width: Duration) -> Trigger {
+        pin.set_low();
+
+        Trigger {
+            output: Arc::new(Mutex::new(Output {
+                pin,
+                active_level: Level::High,
+                pulse_width_ns: duration_to_ns(pulse_width),
+            })),
+            strobe: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            thread: None,
+        }
+    }
+
+    /// Returns the logic level the trigger output is set to during a pulse.
+    pub fn active_level(&self) -> Level {
+        self.output().active_level
+    }
+
+    /// Sets the logic level the trigger output is set to during a pulse.
+    ///
+    /// The output is immediately set to the opposite, inactive level. If a pulse
+    /// is active, `set_active_level` waits until it has completed.
+    pub fn set_active_level(&mut self, level: Level) {
+        let mut output = self.output();
+
+        output.active_level = level;
+        output.pin.write(inactive_level(level));
+    }
+
+    /// Returns the trigger pulse width.
+    pub fn pulse_width(&self) -> Duration {
+        Duration::from_nanos(self.output().pulse_width_ns as u64)
+    }
+
+    /// Sets the trigger pulse width.
+    ///
+    /// While the trigger is running periodically, the new pulse width is used
+    /// starting with the next pulse.
+    pub fn set_pulse_width(&mut self, pulse_width: Duration) {
+        self.output().pulse_width_ns = duration_to_ns(pulse_width);
+    }
+
+    /// Generates a single trigger pulse, and returns the time the pulse started.
+    ///
+    /// `fire` blocks until the pulse has completed. The timestamp is based on
+    /// `CLOCK_MONOTONIC`.
+    ///
+    /// If the trigger is running periodically, `fire` returns [`Error::Running`].
+    ///
+    /// [`Error::Running`]: enum.Error.html#variant.Running
+    pub fn fire(&mut self) -> Result<Duration> {
+        if self.is_running() {
+            return Err(Error::Running);
+        }
+
+        let start_ns = self.output().pulse();
+
+        Ok(Duration::from_nanos(start_ns as u64))
+    }
+
+    /// Generates a single trigger pulse, and blocks until the strobe input
+    /// reports the start of the exposure, or until `timeout` expires.
+    ///
+    /// Any strobe events that occurred before the pulse are discarded. Returns
+    /// the time between the start of the trigger pulse and the strobe edge, or
+    /// `None` if `timeout` expired. `timeout` can be set to `None` to wait
+    /// indefinitely.
+    ///
+    /// The latency is only accurate on Linux 5.7 or later, where strobe events
+    /// are timestamped using `CLOCK_MONOTONIC`.
+    ///
+    /// If no strobe input is configured, `fire_and_wait` returns
+    /// [`Error::StrobeNotConfigured`].
+    ///
+    /// [`Error::StrobeNotConfigured`]: enum.Error.html#variant.StrobeNotConfigured
+    pub fn fire_and_wait(&mut self, timeout: Option<Duration>) -> Result<Option<Duration>> {
+        if self.strobe.is_none() {
+            return Err(Error::StrobeNotConfigured);
+        }
+
+        // Discard any stale events
+        while self.poll(true, Some(Duration::from_millis(0)))?.is_some() {}
+
+        let start = self.fire()?;
+
+        Ok(self
+            .poll(false, timeout)?
+            .map(|strobe| strobe.checked_sub(start).unwrap_or_default()))
+    }
+
+    /// Starts generating trigger pulses at a fixed `period` on a background
+    /// thread.
+    ///
+    /// Pulses are scheduled relative to the first pulse, so any delays don't
+    /// accumulate over time. If the trigger is already running, it's restarted
+    /// with the new period.
+    ///
+    /// The thread is assigned real-time scheduling when the application runs
+    /// with superuser privileges.
+    pub fn start(&mut self, period: Duration) -> Result<()> {
+        self.stop()?;
+
+        let output = self.output.clone();
+        let stop = self.stop.clone();
+        let period_ns = duration_to_ns(period).max(1);
+
+        self.thread = Some(thread::spawn(move || {
+            set_realtime_priority();
+
+            let mut start_ns = get_time_ns();
+            loop {
+                delay_until_ns(start_ns);
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                output.lock().unwrap_or_else(|err| err.into_inner()).pulse();
+
+                start_ns += period_ns;
+            }
+        }));
+
+        Ok(())
+    }
+
+    /// Stops generating periodic trigger pulses.
+    ///
+    /// `stop` blocks until the background thread has exited, which can take up
+    /// to a full period.
+    pub fn stop(&mut self) -> Result<()> {
+        if let Some(thread) = self.thread.take() {
+            self.stop.store(true, Ordering::SeqCst);
+            let result = thread.join();
+            self.stop.store(false, Ordering::SeqCst);
+
+            if result.is_err() {
+                return Err(Error::ThreadPanic);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the trigger is running periodically.
+    pub fn is_running(&self) -> bool {
+        self.thread.is_some()
+    }
+
+    /// Configures `pin` as the strobe input, and timestamps any edges that match
+    /// `trigger`.
+    ///
+    /// Any previously configured strobe input is released.
+    pub fn set_strobe(&mut self, mut pin: InputPin, trigger: gpio::Trigger) -> Result<()> {
+        pin.set_interrupt(trigger)?;
+        self.strobe = Some(pin);
+
+        Ok(())
+    }
+
+    /// Removes the strobe input, and returns the pin with its interrupt cleared.
+    pub fn clear_strobe(&mut self) -> Result<Option<InputPin>> {
+        match self.strobe.take() {
+            Some(mut pin) => {
+                pin.clear_interrupt()?;
+
+                Ok(Some(pin))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Blocks until the next strobe edge occurs, or until `timeout` expires, and
+    /// returns the kernel timestamp of the edge.
+    ///
+    /// Edges that occurred since the previous call are returned immediately, in
+    /// order. `timeout` can be set to `None` to wait indefinitely, in which case
+    /// `poll_strobe` never returns `None`.
+    ///
+    /// If no strobe input is configured, `poll_strobe` returns
+    /// [`Error::StrobeNotConfigured`].
+    ///
+    /// [`Error::StrobeNotConfigured`]: enum.Error.html#variant.StrobeNotConfigured
+    pub fn poll_strobe(&mut self, timeout: Option<Duration>) -> Result<Option<Duration>> {
+        self.poll(false, timeout)
+    }
+
+    fn poll(&mut self, reset: bool, timeout: Option<Duration>) -> Result<Option<Duration>> {
+        let strobe = self.strobe.as_mut().ok_or(Error::StrobeNotConfigured)?;
+
+        Ok(strobe
+            .poll_interrupt(reset, timeout)?
+            .map(|event| event.timestamp()))
+    }
+
+    // A poisoned lock only means the background thread panicked, which doesn't
+    // leave the output in an inconsistent state.
+    fn output(&self) -> std::sync::MutexGuard<'_, Output> {
+        self.output.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Drop for Trigger {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
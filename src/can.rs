@@ -0,0 +1,272 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Interface for CAN bus controllers.
+//!
+//! RPPAL provides two ways to access a CAN bus.
+//!
+//! [`SocketCan`] uses the Linux kernel's SocketCAN subsystem, which handles
+//! buffering, bit timing and error recovery in kernel space. An MCP2515-based
+//! CAN HAT can be made available as `can0` by adding
+//! `dtoverlay=mcp2515-can0,oscillator=16000000,interrupt=25` to
+//! `/boot/config.txt`. The interface needs to be configured and brought up
+//! before it can be used, for instance by running
+//! `sudo ip link set can0 up type can bitrate 500000`.
+//!
+//! [`Mcp2515`] talks to an MCP2515 CAN controller directly through [`Spi`],
+//! without requiring the kernel driver. Incoming frames can be detected by
+//! polling the controller, or by connecting its INT output to a GPIO pin and
+//! configuring it with [`Mcp2515::set_interrupt_pin`].
+//!
+//! Both backends send and receive [`CanFrame`]s, and support hardware
+//! acceptance [`Filter`]s to limit the frames that are received.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::can::{CanFrame, Id, SocketCan};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut can = SocketCan::new("can0")?;
+//!
+//! can.send(&CanFrame::new(Id::Standard(0x123), &[0x01, 0x02, 0x03])?)?;
+//!
+//! let frame = can.receive()?;
+//! println!("{:?}: {:?}", frame.id(), frame.data());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`SocketCan`]: struct.SocketCan.html
+//! [`Mcp2515`]: struct.Mcp2515.html
+//! [`Mcp2515::set_interrupt_pin`]: struct.Mcp2515.html#method.set_interrupt_pin
+//! [`Spi`]: ../spi/struct.Spi.html
+//! [`CanFrame`]: struct.CanFrame.html
+//! [`Filter`]: struct.Filter.html
+
+use std::io;
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::gpio;
+use crate::spi;
+
+mod mcp2515;
+mod socketcan;
+
+pub use self::mcp2515::{Mcp2515, Mode};
+pub use self::socketcan::SocketCan;
+
+quick_error! {
+/// Errors that can occur when accessing a CAN bus.
+    #[derive(Debug)]
+    pub enum Error {
+/// IO error.
+        Io(err: io::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// SPI error.
+        Spi(err: spi::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Invalid identifier.
+///
+/// Standard identifiers are limited to 11 bits, and extended identifiers to
+/// 29 bits.
+        InvalidId { description("invalid identifier") }
+/// Invalid data length.
+///
+/// A classic CAN frame can contain up to 8 bytes of data.
+        InvalidLength { description("invalid data length") }
+/// Invalid bit rate.
+///
+/// The requested bit rate can't be configured with the specified oscillator
+/// frequency.
+        InvalidBitrate { description("invalid bit rate") }
+/// Invalid filter configuration.
+///
+/// The number or type of filters isn't supported by the controller.
+        InvalidFilter { description("invalid filter configuration") }
+/// Timeout.
+///
+/// No frame was received, or the controller didn't switch modes, in time.
+        Timeout { description("operation timed out") }
+/// All transmit buffers are full.
+        Busy { description("all transmit buffers are full") }
+    }
+}
+
+/// Result type returned from methods that can have `can::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+const SFF_MAX: u16 = 0x7ff;
+const EFF_MAX: u32 = 0x1fff_ffff;
+
+/// CAN identifier.
+///
+/// `Standard` identifiers are 11 bits, and `Extended` identifiers are 29 bits.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Id {
+    Standard(u16),
+    Extended(u32),
+}
+
+impl Id {
+    fn is_valid(self) -> bool {
+        match self {
+            Id::Standard(id) => id <= SFF_MAX,
+            Id::Extended(id) => id <= EFF_MAX,
+        }
+    }
+}
+
+/// Classic CAN data or remote frame.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct CanFrame {
+    id: Id,
+    remote: bool,
+    len: u8,
+    data: [u8; 8],
+}
+
+impl CanFrame {
+    /// Constructs a new data frame.
+    ///
+    /// `data` can contain up to 8 bytes.
+    pub fn new(id: Id, data: &[u8]) -> Result<CanFrame> {
+        if !id.is_valid() {
+            return Err(Error::InvalidId);
+        }
+
+        if data.len() > 8 {
+            return Err(Error::InvalidLength);
+        }
+
+        let mut buffer = [0u8; 8];
+        buffer[..data.len()].copy_from_slice(data);
+
+        Ok(CanFrame {
+            id,
+            remote: false,
+            len: data.len() as u8,
+            data: buffer,
+        })
+    }
+
+    /// Constructs a new remote frame, requesting `len` bytes of data.
+    ///
+    /// `len` can be a value between 0 and 8.
+    pub fn remote(id: Id, len: usize) -> Result<CanFrame> {
+        if !id.is_valid() {
+            return Err(Error::InvalidId);
+        }
+
+        if len > 8 {
+            return Err(Error::InvalidLength);
+        }
+
+        Ok(CanFrame {
+            id,
+            remote: true,
+            len: len as u8,
+            data: [0u8; 8],
+        })
+    }
+
+    /// Returns the identifier.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Returns the data.
+    ///
+    /// Remote frames don't contain any data, and always return an empty slice.
+    pub fn data(&self) -> &[u8] {
+        if self.remote {
+            &[]
+        } else {
+            &self.data[..self.len as usize]
+        }
+    }
+
+    /// Returns the data length code.
+    ///
+    /// For data frames, this is the number of data bytes. For remote frames,
+    /// this is the number of requested data bytes.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Returns `true` if the data length code is 0.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if this is a remote frame.
+    pub fn is_remote(&self) -> bool {
+        self.remote
+    }
+}
+
+/// Acceptance filter.
+///
+/// An incoming frame is accepted when all identifier bits that are set in
+/// `mask` match the corresponding bits in `id`. A `Filter` only matches frames
+/// of its own identifier type.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Filter {
+    id: Id,
+    mask: u32,
+}
+
+impl Filter {
+    /// Constructs a new `Filter` for 11-bit standard identifiers.
+    pub fn standard(id: u16, mask: u16) -> Result<Filter> {
+        if id > SFF_MAX || mask > SFF_MAX {
+            return Err(Error::InvalidFilter);
+        }
+
+        Ok(Filter {
+            id: Id::Standard(id),
+            mask: u32::from(mask),
+        })
+    }
+
+    /// Constructs a new `Filter` for 29-bit extended identifiers.
+    pub fn extended(id: u32, mask: u32) -> Result<Filter> {
+        if id > EFF_MAX || mask > EFF_MAX {
+            return Err(Error::InvalidFilter);
+        }
+
+        Ok(Filter {
+            id: Id::Extended(id),
+            mask,
+        })
+    }
+
+    /// Returns the identifier.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Returns the mask.
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+}
@@ -0,0 +1,502 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::can::{CanFrame, Error, Filter, Id, Result};
+use crate::gpio::{InputPin, Level, Trigger};
+use crate::spi::Spi;
+
+// SPI instructions
+const INSTR_RESET: u8 = 0xc0;
+const INSTR_READ: u8 = 0x03;
+const INSTR_WRITE: u8 = 0x02;
+const INSTR_BIT_MODIFY: u8 = 0x05;
+const INSTR_READ_STATUS: u8 = 0xa0;
+const INSTR_LOAD_TX: u8 = 0x40;
+const INSTR_RTS: u8 = 0x80;
+const INSTR_READ_RX: u8 = 0x90;
+
+// Registers
+const REG_CANSTAT: u8 = 0x0e;
+const REG_CANCTRL: u8 = 0x0f;
+const REG_CNF3: u8 = 0x28;
+const REG_CANINTE: u8 = 0x2b;
+const REG_CANINTF: u8 = 0x2c;
+const REG_RXB0CTRL: u8 = 0x60;
+const REG_RXB1CTRL: u8 = 0x70;
+const REG_RXF: [u8; 6] = [0x00, 0x04, 0x08, 0x10, 0x14, 0x18];
+const REG_RXM: [u8; 2] = [0x20, 0x24];
+
+const CANINTF_RX0IF: u8 = 0x01;
+const CANINTF_RX1IF: u8 = 0x02;
+const RXBCTRL_RXM_OFF: u8 = 0x60;
+const RXB0CTRL_BUKT: u8 = 0x04;
+const SIDL_EXIDE: u8 = 0x08;
+const SIDL_SRR: u8 = 0x10;
+const DLC_RTR: u8 = 0x40;
+const CNF2_BTLMODE: u8 = 0x80;
+
+const MODE_MASK: u8 = 0xe0;
+const MODE_TIMEOUT: Duration = Duration::from_millis(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// MCP2515 operating modes.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Mode {
+    /// Sends and receives frames.
+    Normal = 0b000,
+    /// Low-power mode.
+    Sleep = 0b001,
+    /// Sent frames are received internally, without being sent on the bus.
+    Loopback = 0b010,
+    /// Receives frames without acknowledging them or sending error frames.
+    ListenOnly = 0b011,
+    /// Required to change the bit timing and acceptance filters.
+    Configuration = 0b100,
+}
+
+impl Mode {
+    fn from_bits(bits: u8) -> Mode {
+        match bits & 0b111 {
+            0b000 => Mode::Normal,
+            0b001 => Mode::Sleep,
+            0b010 => Mode::Loopback,
+            0b011 => Mode::ListenOnly,
+            _ => Mode::Configuration,
+        }
+    }
+}
+
+/// Microchip MCP2515 CAN controller.
+///
+/// `Mcp2515` talks to the controller directly through [`Spi`], and doesn't
+/// require the kernel's mcp251x driver. Make sure the `mcp2515-can0` overlay
+/// isn't enabled, since the kernel driver would otherwise claim the device.
+///
+/// The MCP2515 supports SPI modes 0 and 3, at clock speeds up to 10 MHz.
+///
+/// The controller's INT output is driven low while a received frame is
+/// waiting to be read. If INT is connected to a GPIO pin, configure it with
+/// [`set_interrupt_pin`] so [`receive`] can wait for an interrupt instead of
+/// polling the controller.
+///
+/// [`Spi`]: ../spi/struct.Spi.html
+/// [`set_interrupt_pin`]: #method.set_interrupt_pin
+/// [`receive`]: #method.receive
+#[derive(Debug)]
+pub struct Mcp2515 {
+    spi: Spi,
+    mode: Mode,
+    interrupt_pin: Option<InputPin>,
+}
+
+impl Mcp2515 {
+    /// Constructs a new `Mcp2515`.
+    ///
+    /// Resets the controller, configures the bit timing for the specified
+    /// `bitrate` based on the oscillator frequency `oscillator` (typically
+    /// 8 MHz or 16 MHz on CAN HATs), accepts all incoming frames, and switches
+    /// to [`Mode::Normal`]. Both values are specified in herz (Hz).
+    ///
+    /// [`Mode::Normal`]: enum.Mode.html#variant.Normal
+    pub fn new(spi: Spi, oscillator: u32, bitrate: u32) -> Result<Mcp2515> {
+        let cnf = bit_timing(oscillator, bitrate).ok_or(Error::InvalidBitrate)?;
+
+        let mut mcp2515 = Mcp2515 {
+            spi,
+            mode: Mode::Configuration,
+            interrupt_pin: None,
+        };
+
+        mcp2515.spi.write(&[INSTR_RESET])?;
+        // The oscillator needs some time to stabilize after a reset
+        thread::sleep(Duration::from_millis(5));
+        mcp2515.set_mode(Mode::Configuration)?;
+
+        // CNF3, CNF2 and CNF1 are located at consecutive addresses
+        mcp2515.write_registers(REG_CNF3, &[cnf.2, cnf.1, cnf.0])?;
+
+        // Enable rollover from RXB0 to RXB1, and disable all filters
+        mcp2515.write_register(REG_RXB0CTRL, RXBCTRL_RXM_OFF | RXB0CTRL_BUKT)?;
+        mcp2515.write_register(REG_RXB1CTRL, RXBCTRL_RXM_OFF)?;
+
+        // Drive INT low while a frame is waiting in either receive buffer
+        mcp2515.write_register(REG_CANINTE, CANINTF_RX0IF | CANINTF_RX1IF)?;
+        mcp2515.write_register(REG_CANINTF, 0)?;
+
+        mcp2515.set_mode(Mode::Normal)?;
+
+        Ok(mcp2515)
+    }
+
+    /// Returns the operating mode.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Sets the operating mode.
+    ///
+    /// Returns `Err(Error::Timeout)` if the controller didn't switch modes in
+    /// time, which happens when switching to [`Mode::Configuration`] or
+    /// [`Mode::Sleep`] while a frame is being sent.
+    ///
+    /// [`Mode::Configuration`]: enum.Mode.html#variant.Configuration
+    /// [`Mode::Sleep`]: enum.Mode.html#variant.Sleep
+    pub fn set_mode(&mut self, mode: Mode) -> Result<()> {
+        self.modify_register(REG_CANCTRL, MODE_MASK, (mode as u8) << 5)?;
+
+        let deadline = Instant::now() + MODE_TIMEOUT;
+        loop {
+            if Mode::from_bits(self.read_register(REG_CANSTAT)? >> 5) == mode {
+                self.mode = mode;
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout);
+            }
+
+            thread::sleep(Duration::from_micros(100));
+        }
+    }
+
+    /// Configures the acceptance filters.
+    ///
+    /// The MCP2515 has two receive buffers, each with its own mask, so up to 2
+    /// filters with different masks are supported. A frame is received when it
+    /// matches any of the filters. Filters for standard identifiers only match
+    /// standard frames, and filters for extended identifiers only match extended
+    /// frames.
+    ///
+    /// Returns `Err(Error::InvalidFilter)` if `filters` is empty or contains more
+    /// than 2 filters. Use [`clear_filters`] to receive all frames.
+    ///
+    /// [`clear_filters`]: #method.clear_filters
+    pub fn set_filters(&mut self, filters: &[Filter]) -> Result<()> {
+        if filters.is_empty() || filters.len() > 2 {
+            return Err(Error::InvalidFilter);
+        }
+
+        // RXB0 uses RXM0 together with RXF0 and RXF1, and RXB1 uses RXM1
+        // together with RXF2 to RXF5. Unused filter registers are filled with
+        // duplicates. Because frames roll over from RXB0 to RXB1, with a single
+        // filter RXB1 is configured with the same filter to avoid dropping frames
+        // that are accepted by RXB0.
+        let rxb0 = filters[0];
+        let rxb1 = *filters.get(1).unwrap_or(&filters[0]);
+
+        self.with_configuration_mode(|mcp2515| {
+            mcp2515.write_registers(REG_RXM[0], &encode_mask(&rxb0))?;
+            mcp2515.write_registers(REG_RXM[1], &encode_mask(&rxb1))?;
+
+            for (index, &address) in REG_RXF.iter().enumerate() {
+                let filter = if index < 2 { &rxb0 } else { &rxb1 };
+                mcp2515.write_registers(address, &encode_id(filter.id()))?;
+            }
+
+            mcp2515.write_register(REG_RXB0CTRL, RXB0CTRL_BUKT)?;
+            mcp2515.write_register(REG_RXB1CTRL, 0)
+        })
+    }
+
+    /// Removes all acceptance filters, so every incoming frame is received.
+    pub fn clear_filters(&mut self) -> Result<()> {
+        self.with_configuration_mode(|mcp2515| {
+            mcp2515.write_register(REG_RXB0CTRL, RXBCTRL_RXM_OFF | RXB0CTRL_BUKT)?;
+            mcp2515.write_register(REG_RXB1CTRL, RXBCTRL_RXM_OFF)
+        })
+    }
+
+    /// Queues a frame for transmission.
+    ///
+    /// The MCP2515 has 3 transmit buffers. Returns `Err(Error::Busy)` if all
+    /// buffers are still waiting to be sent.
+    pub fn send(&mut self, frame: &CanFrame) -> Result<()> {
+        let status = self.read_status()?;
+
+        // TXREQ bits for TXB0, TXB1 and TXB2
+        let buffer = [0x04u8, 0x10, 0x40]
+            .iter()
+            .position(|&txreq| status & txreq == 0)
+            .ok_or(Error::Busy)? as u8;
+
+        let mut buffer_data = [0u8; 14];
+        buffer_data[0] = INSTR_LOAD_TX | (buffer << 1);
+        buffer_data[1..5].copy_from_slice(&encode_id(frame.id()));
+        buffer_data[5] = frame.len() as u8 | if frame.is_remote() { DLC_RTR } else { 0 };
+        buffer_data[6..6 + frame.data().len()].copy_from_slice(frame.data());
+
+        self.spi.write(&buffer_data[..6 + frame.data().len()])?;
+        self.spi.write(&[INSTR_RTS | (1 << buffer)])?;
+
+        Ok(())
+    }
+
+    /// Returns a received frame, or `None` if both receive buffers are empty.
+    pub fn try_receive(&mut self) -> Result<Option<CanFrame>> {
+        let status = self.read_status()?;
+
+        let buffer = if status & CANINTF_RX0IF != 0 {
+            0
+        } else if status & CANINTF_RX1IF != 0 {
+            1
+        } else {
+            return Ok(None);
+        };
+
+        // READ RX BUFFER clears the buffer's RXnIF flag when Slave Select is
+        // set to inactive.
+        let mut read_buffer = [0u8; 14];
+        let mut write_buffer = [0u8; 14];
+        write_buffer[0] = INSTR_READ_RX | (buffer << 2);
+        self.spi.transfer(&mut read_buffer, &write_buffer)?;
+
+        let regs = &read_buffer[1..];
+        let (sidh, sidl, eid8, eid0, dlc) = (regs[0], regs[1], regs[2], regs[3], regs[4]);
+        let len = usize::from(dlc & 0x0f).min(8);
+
+        let (id, remote) = if sidl & SIDL_EXIDE != 0 {
+            let id = (u32::from(sidh) << 21)
+                | (u32::from(sidl >> 5) << 18)
+                | (u32::from(sidl & 0x03) << 16)
+                | (u32::from(eid8) << 8)
+                | u32::from(eid0);
+
+            (Id::Extended(id), dlc & DLC_RTR != 0)
+        } else {
+            let id = (u16::from(sidh) << 3) | u16::from(sidl >> 5);
+
+            (Id::Standard(id), sidl & SIDL_SRR != 0)
+        };
+
+        if remote {
+            CanFrame::remote(id, len).map(Some)
+        } else {
+            CanFrame::new(id, &regs[5..5 + len]).map(Some)
+        }
+    }
+
+    /// Blocks until a frame is received.
+    pub fn receive(&mut self) -> Result<CanFrame> {
+        self.receive_deadline(None)
+    }
+
+    /// Blocks until a frame is received, or the timeout expires.
+    ///
+    /// Returns `Err(Error::Timeout)` if no frame was received in time.
+    pub fn receive_timeout(&mut self, timeout: Duration) -> Result<CanFrame> {
+        self.receive_deadline(Some(Instant::now() + timeout))
+    }
+
+    /// Configures the GPIO pin connected to the controller's INT output.
+    ///
+    /// While an interrupt pin is configured, [`receive`] and [`receive_timeout`]
+    /// wait for INT to go low instead of polling the controller every
+    /// millisecond.
+    ///
+    /// Returns the previously configured pin, if any.
+    ///
+    /// [`receive`]: #method.receive
+    /// [`receive_timeout`]: #method.receive_timeout
+    pub fn set_interrupt_pin(&mut self, mut pin: InputPin) -> Result<Option<InputPin>> {
+        pin.set_interrupt(Trigger::FallingEdge)?;
+
+        let previous = self.clear_interrupt_pin()?;
+        self.interrupt_pin = Some(pin);
+
+        Ok(previous)
+    }
+
+    /// Removes the configured interrupt pin.
+    ///
+    /// Returns the previously configured pin, if any.
+    pub fn clear_interrupt_pin(&mut self) -> Result<Option<InputPin>> {
+        match self.interrupt_pin.take() {
+            Some(mut pin) => {
+                pin.clear_interrupt()?;
+                Ok(Some(pin))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Consumes the `Mcp2515`, and returns the underlying `Spi`.
+    ///
+    /// The controller is switched to [`Mode::Configuration`], so it stops
+    /// participating in bus traffic.
+    ///
+    /// [`Mode::Configuration`]: enum.Mode.html#variant.Configuration
+    pub fn into_spi(mut self) -> Spi {
+        let _ = self.set_mode(Mode::Configuration);
+
+        self.spi
+    }
+
+    fn receive_deadline(&mut self, deadline: Option<Instant>) -> Result<CanFrame> {
+        loop {
+            if let Some(frame) = self.try_receive()? {
+                return Ok(frame);
+            }
+
+            let remaining = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(Error::Timeout);
+                    }
+
+                    Some(deadline - now)
+                }
+                None => None,
+            };
+
+            match self.interrupt_pin {
+                // INT stays low while a frame is waiting, so only wait for the
+                // next falling edge if it's currently high.
+                Some(ref mut pin) => {
+                    if pin.read() == Level::High {
+                        pin.poll_interrupt(false, remaining)?;
+                    }
+                }
+                None => thread::sleep(remaining.map_or(POLL_INTERVAL, |r| r.min(POLL_INTERVAL))),
+            }
+        }
+    }
+
+    fn with_configuration_mode<F>(&mut self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Mcp2515) -> Result<()>,
+    {
+        let mode = self.mode;
+
+        self.set_mode(Mode::Configuration)?;
+        let result = f(self);
+        self.set_mode(mode)?;
+
+        result
+    }
+
+    fn read_status(&mut self) -> Result<u8> {
+        let mut read_buffer = [0u8; 2];
+        self.spi
+            .transfer(&mut read_buffer, &[INSTR_READ_STATUS, 0])?;
+
+        Ok(read_buffer[1])
+    }
+
+    fn read_register(&mut self, address: u8) -> Result<u8> {
+        let mut read_buffer = [0u8; 3];
+        self.spi
+            .transfer(&mut read_buffer, &[INSTR_READ, address, 0])?;
+
+        Ok(read_buffer[2])
+    }
+
+    fn write_register(&mut self, address: u8, value: u8) -> Result<()> {
+        self.write_registers(address, &[value])
+    }
+
+    fn write_registers(&mut self, address: u8, values: &[u8]) -> Result<()> {
+        let mut write_buffer = Vec::with_capacity(values.len() + 2);
+        write_buffer.push(INSTR_WRITE);
+        write_buffer.push(address);
+        write_buffer.extend_from_slice(values);
+
+        self.spi.write(&write_buffer)?;
+
+        Ok(())
+    }
+
+    fn modify_register(&mut self, address: u8, mask: u8, value: u8) -> Result<()> {
+        self.spi.write(&[INSTR_BIT_MODIFY, address, mask, value])?;
+
+        Ok(())
+    }
+}
+
+// Encodes an identifier into the SIDH, SIDL, EID8 and EID0 register layout.
+fn encode_id(id: Id) -> [u8; 4] {
+    match id {
+        Id::Standard(id) => [(id >> 3) as u8, ((id & 0x07) << 5) as u8, 0, 0],
+        Id::Extended(id) => [
+            (id >> 21) as u8,
+            ((((id >> 18) & 0x07) << 5) as u8) | SIDL_EXIDE | ((id >> 16) & 0x03) as u8,
+            (id >> 8) as u8,
+            id as u8,
+        ],
+    }
+}
+
+// Encodes a filter mask into the RXMnSIDH, RXMnSIDL, RXMnEID8 and RXMnEID0
+// register layout. For standard identifiers, the extended identifier bits of
+// the mask would be compared against the first two data bytes, so those are
+// left at 0.
+fn encode_mask(filter: &Filter) -> [u8; 4] {
+    match filter.id() {
+        Id::Standard(_) => encode_id(Id::Standard(filter.mask() as u16)),
+        Id::Extended(_) => {
+            let mut mask = encode_id(Id::Extended(filter.mask()));
+            mask[1] &= !SIDL_EXIDE;
+            mask
+        }
+    }
+}
+
+// Calculates the CNF1, CNF2 and CNF3 register values for the specified
+// oscillator frequency and bit rate, with a sample point of roughly 75%.
+//
+// Each bit consists of 8 to 25 time quanta (TQ), with TQ = 2 * (BRP + 1) / Fosc.
+// A bit is divided into a synchronization segment of 1 TQ, a propagation
+// segment, phase segment 1 and phase segment 2, each of which can be between
+// 1 and 8 TQ, although phase segment 2 needs to be at least 2 TQ.
+fn bit_timing(oscillator: u32, bitrate: u32) -> Option<(u8, u8, u8)> {
+    if bitrate == 0 {
+        return None;
+    }
+
+    // Prefer more time quanta per bit for a more accurate sample point
+    for tq in (8..=25u32).rev() {
+        let divisor = 2 * tq * bitrate;
+        let brp = oscillator / divisor;
+        if brp * divisor != oscillator || !(1..=64).contains(&brp) {
+            continue;
+        }
+
+        let ps2 = ((tq + 2) / 4).clamp(2, 8);
+        let remaining = tq - 1 - ps2;
+        let prop_seg = (remaining / 2).clamp(1, 8);
+        let ps1 = remaining - prop_seg;
+
+        if !(1..=8).contains(&ps1) {
+            continue;
+        }
+
+        // SJW = 1 TQ
+        let cnf1 = (brp - 1) as u8;
+        let cnf2 = CNF2_BTLMODE | (((ps1 - 1) as u8) << 3) | (prop_seg - 1) as u8;
+        let cnf3 = (ps2 - 1) as u8;
+
+        return Some((cnf1, cnf2, cnf3));
+    }
+
+    None
+}
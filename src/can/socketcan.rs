@@ -0,0 +1,250 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::{Duration, Instant};
+
+use libc::{self, c_int, c_void};
+
+use crate::can::{CanFrame, Error, Filter, Id, Result};
+
+/// Provides access to a CAN interface through the Linux kernel's SocketCAN
+/// subsystem.
+///
+/// The interface has to be configured and brought up before it can be used,
+/// for instance by running `sudo ip link set can0 up type can bitrate 500000`.
+/// The bit rate is managed by the kernel, and can't be changed through
+/// `SocketCan`.
+#[derive(Debug)]
+pub struct SocketCan {
+    fd: RawFd,
+    interface: String,
+}
+
+impl SocketCan {
+    /// Constructs a new `SocketCan` bound to the specified network interface,
+    /// for instance `can0`.
+    ///
+    /// All incoming frames are received until filters are configured with
+    /// [`set_filters`].
+    ///
+    /// [`set_filters`]: #method.set_filters
+    pub fn new(interface: &str) -> Result<SocketCan> {
+        let name = CString::new(interface)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid interface name"))?;
+
+        let ifindex = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if ifindex == 0 {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+
+        let fd = parse_retval!(unsafe {
+            libc::socket(
+                libc::PF_CAN,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::CAN_RAW,
+            )
+        })?;
+
+        // Construct SocketCan first, so the socket is closed if bind() fails
+        let socket_can = SocketCan {
+            fd,
+            interface: interface.to_owned(),
+        };
+
+        let mut addr: libc::sockaddr_can = unsafe { mem::zeroed() };
+        addr.can_family = libc::AF_CAN as libc::sa_family_t;
+        addr.can_ifindex = ifindex as c_int;
+
+        parse_retval!(unsafe {
+            libc::bind(
+                fd,
+                &addr as *const libc::sockaddr_can as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_can>() as libc::socklen_t,
+            )
+        })?;
+
+        Ok(socket_can)
+    }
+
+    /// Returns the name of the network interface.
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    /// Sends a frame.
+    ///
+    /// `send` blocks while the kernel's transmit queue is full.
+    pub fn send(&mut self, frame: &CanFrame) -> Result<()> {
+        let mut raw: libc::can_frame = unsafe { mem::zeroed() };
+
+        raw.can_id = match frame.id() {
+            Id::Standard(id) => u32::from(id),
+            Id::Extended(id) => id | libc::CAN_EFF_FLAG,
+        };
+
+        if frame.is_remote() {
+            raw.can_id |= libc::CAN_RTR_FLAG;
+        }
+
+        raw.can_dlc = frame.len() as u8;
+        raw.data[..frame.data().len()].copy_from_slice(frame.data());
+
+        parse_retval!(unsafe {
+            libc::write(
+                self.fd,
+                &raw as *const libc::can_frame as *const c_void,
+                mem::size_of::<libc::can_frame>(),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// Blocks until a frame is received.
+    ///
+    /// Error frames are skipped.
+    pub fn receive(&mut self) -> Result<CanFrame> {
+        loop {
+            if let Some(frame) = self.read_frame()? {
+                return Ok(frame);
+            }
+        }
+    }
+
+    /// Blocks until a frame is received, or the timeout expires.
+    ///
+    /// Returns `Err(Error::Timeout)` if no frame was received in time. Error
+    /// frames are skipped.
+    pub fn receive_timeout(&mut self, timeout: Duration) -> Result<CanFrame> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let mut pfd = libc::pollfd {
+                fd: self.fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+
+            let timeout_ms = remaining.as_millis().min(c_int::MAX as u128) as c_int;
+            if parse_retval!(unsafe { libc::poll(&mut pfd, 1, timeout_ms) })? == 0 {
+                return Err(Error::Timeout);
+            }
+
+            if let Some(frame) = self.read_frame()? {
+                return Ok(frame);
+            }
+        }
+    }
+
+    /// Configures the acceptance filters.
+    ///
+    /// A frame is received when it matches any of the filters. An empty slice
+    /// blocks all incoming frames. SocketCAN doesn't limit the number of
+    /// filters.
+    pub fn set_filters(&mut self, filters: &[Filter]) -> Result<()> {
+        let raw_filters: Vec<libc::can_filter> = filters
+            .iter()
+            .map(|filter| match filter.id() {
+                Id::Standard(id) => libc::can_filter {
+                    can_id: u32::from(id),
+                    can_mask: filter.mask() | libc::CAN_EFF_FLAG | libc::CAN_RTR_FLAG,
+                },
+                Id::Extended(id) => libc::can_filter {
+                    can_id: id | libc::CAN_EFF_FLAG,
+                    can_mask: filter.mask() | libc::CAN_EFF_FLAG | libc::CAN_RTR_FLAG,
+                },
+            })
+            .collect();
+
+        self.set_raw_filters(&raw_filters)
+    }
+
+    /// Removes all acceptance filters, so every incoming frame is received.
+    pub fn clear_filters(&mut self) -> Result<()> {
+        self.set_raw_filters(&[libc::can_filter {
+            can_id: 0,
+            can_mask: 0,
+        }])
+    }
+
+    fn set_raw_filters(&mut self, filters: &[libc::can_filter]) -> Result<()> {
+        parse_retval!(unsafe {
+            libc::setsockopt(
+                self.fd,
+                libc::SOL_CAN_RAW,
+                libc::CAN_RAW_FILTER,
+                filters.as_ptr() as *const c_void,
+                mem::size_of_val(filters) as libc::socklen_t,
+            )
+        })?;
+
+        Ok(())
+    }
+
+    // Reads a single frame. Returns None for error frames.
+    fn read_frame(&mut self) -> Result<Option<CanFrame>> {
+        let mut raw: libc::can_frame = unsafe { mem::zeroed() };
+
+        parse_retval!(unsafe {
+            libc::read(
+                self.fd,
+                &mut raw as *mut libc::can_frame as *mut c_void,
+                mem::size_of::<libc::can_frame>(),
+            )
+        })?;
+
+        if raw.can_id & libc::CAN_ERR_FLAG != 0 {
+            return Ok(None);
+        }
+
+        let id = if raw.can_id & libc::CAN_EFF_FLAG != 0 {
+            Id::Extended(raw.can_id & libc::CAN_EFF_MASK)
+        } else {
+            Id::Standard((raw.can_id & libc::CAN_SFF_MASK) as u16)
+        };
+
+        let len = (raw.can_dlc as usize).min(8);
+
+        if raw.can_id & libc::CAN_RTR_FLAG != 0 {
+            CanFrame::remote(id, len).map(Some)
+        } else {
+            CanFrame::new(id, &raw.data[..len]).map(Some)
+        }
+    }
+}
+
+impl AsRawFd for SocketCan {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl Drop for SocketCan {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
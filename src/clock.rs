@@ -0,0 +1,507 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Interface for the general-purpose clock generators.
+//!
+//! The BCM283x and BCM2711 SoCs contain three general-purpose clock generators,
+//! GPCLK0-2, which can output a stable square wave with a 50% duty cycle on a GPIO
+//! pin, without any CPU involvement. Typical applications include master clocks for
+//! camera modules and audio codecs, driving piezo buzzers, and frequency references.
+//!
+//! [`Gpclk`] routes a clock generator to its GPIO pin, and computes the divisor
+//! needed to reach the requested frequency based on the available clock sources.
+//! The oscillator (19.2 MHz on the BCM283x, 54 MHz on the BCM2711) and PLLD
+//! (500 MHz on the BCM283x, 750 MHz on the BCM2711) are used as clock sources.
+//! Their actual frequency is read from `/sys/kernel/debug/clk` when debugfs is
+//! mounted. The clock source is selected to produce the smallest frequency error,
+//! preferring integer divisors, since fractional divisors introduce jitter.
+//!
+//! Each clock generator has a 12-bit integer divisor, limiting the lowest available
+//! frequency to roughly 4.7 kHz on the BCM283x, and 13.2 kHz on the BCM2711. Use
+//! [`Pwm`] for lower frequencies.
+//!
+//! ## Pins
+//!
+//! By default, [`Gpclk`] uses the following pins:
+//!
+//! * GPCLK0: GPIO 4 (physical pin 7)
+//! * GPCLK1: GPIO 5 (physical pin 29)
+//! * GPCLK2: GPIO 6 (physical pin 31)
+//!
+//! GPCLK0 can also be routed to GPIO 20, 32 and 34, and GPCLK1 to GPIO 21, 42 and 44
+//! using [`Gpclk::with_pin`], depending on which pins are available on your model.
+//! GPCLK1 is used internally for the Ethernet controller on some models, and GPCLK2
+//! may be used by the firmware for audio, so GPCLK0 is the safest choice.
+//!
+//! ## Permissions
+//!
+//! The clock manager registers are only accessible through `/dev/mem`, which requires
+//! superuser privileges. The Raspberry Pi 5 isn't supported, because its GPIO pins are
+//! controlled by the RP1 I/O controller.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::clock::{Channel, Gpclk};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Output a 24 MHz camera master clock on GPIO 4
+//! let gpclk = Gpclk::new(Channel::Gpclk0, 24_000_000.0)?;
+//!
+//! println!("Running at {} Hz", gpclk.frequency());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Gpclk`]: struct.Gpclk.html
+//! [`Gpclk::with_pin`]: struct.Gpclk.html#method.with_pin
+//! [`Pwm`]: ../pwm/struct.Pwm.html
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+use std::result;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use libc;
+use quick_error::quick_error;
+
+use crate::gpio::{self, AltPin, Function, Gpio};
+use crate::system::{DeviceInfo, SoC};
+
+// Clock manager offset from the peripheral base address
+const CM_OFFSET: u64 = 0x10_1000;
+const CM_MEM_SIZE: usize = 4096;
+
+// GPCLK0 control register. Each clock generator uses a CTL/DIV register pair.
+const CM_GP0CTL: usize = 0x70 / std::mem::size_of::<u32>();
+
+const CM_PASSWD: u32 = 0x5a << 24;
+const CTL_BUSY: u32 = 1 << 7;
+const CTL_KILL: u32 = 1 << 5;
+const CTL_ENAB: u32 = 1 << 4;
+const CTL_MASH_SHIFT: u32 = 9;
+
+const SRC_OSCILLATOR: u32 = 1;
+const SRC_PLLD: u32 = 6;
+
+const DIVI_MAX: u32 = 0xfff;
+const DIVF_MAX: u32 = 0xfff;
+
+const BUSY_TIMEOUT: Duration = Duration::from_millis(10);
+
+quick_error! {
+/// Errors that can occur when accessing the general-purpose clock generators.
+    #[derive(Debug)]
+    pub enum Error {
+/// IO error.
+        Io(err: io::Error) { description(err.description()) from() }
+/// Permission denied when opening `/dev/mem` for read/write access.
+///
+/// The clock manager registers can only be accessed through `/dev/mem`, which
+/// requires superuser privileges.
+        PermissionDenied { description("/dev/mem insufficient permissions") }
+/// Unknown model.
+///
+/// The Raspberry Pi model or SoC can't be identified, which is needed to
+/// determine the clock manager's memory address and its source clocks.
+        UnknownModel { description("unknown Raspberry Pi model") }
+/// Unsupported SoC.
+///
+/// The general-purpose clock generators aren't supported on the BCM2712
+/// (Raspberry Pi 5).
+        UnsupportedSoC(soc: SoC) { description("clock generators aren't supported on this SoC") }
+/// GPIO error.
+///
+/// The GPIO pin couldn't be retrieved, or doesn't support the selected clock generator.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Invalid frequency.
+///
+/// The requested frequency can't be reached with any of the available clock sources.
+        InvalidFrequency(frequency: f64) { description("frequency out of range") }
+/// Clock generator is busy.
+///
+/// The clock generator didn't stop within the expected time.
+        Busy { description("clock generator is busy") }
+    }
+}
+
+/// Result type returned from methods that can have `clock::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// General-purpose clock generators.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Channel {
+    Gpclk0 = 0,
+    Gpclk1 = 1,
+    Gpclk2 = 2,
+}
+
+impl Channel {
+    fn default_pin(self) -> u8 {
+        4 + self as u8
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GPCLK{}", *self as u8)
+    }
+}
+
+// Clock manager register access
+pub(crate) struct ClockManager {
+    mem_ptr: *mut u32,
+}
+
+impl fmt::Debug for ClockManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClockManager")
+            .field("mem_ptr", &self.mem_ptr)
+            .finish()
+    }
+}
+
+impl ClockManager {
+    pub(crate) fn open(device_info: &DeviceInfo) -> Result<ClockManager> {
+        let mem_file = match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_SYNC)
+            .open("/dev/mem")
+        {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                return Err(Error::PermissionDenied)
+            }
+            Err(e) => return Err(Error::Io(e)),
+        };
+
+        let mem_ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                CM_MEM_SIZE,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                mem_file.as_raw_fd(),
+                (device_info.peripheral_base() + CM_OFFSET) as libc::off_t,
+            )
+        };
+
+        if mem_ptr == libc::MAP_FAILED {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+
+        Ok(ClockManager {
+            mem_ptr: mem_ptr as *mut u32,
+        })
+    }
+
+    #[inline]
+    fn read(&self, offset: usize) -> u32 {
+        unsafe { ptr::read_volatile(self.mem_ptr.add(offset)) }
+    }
+
+    #[inline]
+    fn write(&self, offset: usize, value: u32) {
+        unsafe {
+            ptr::write_volatile(self.mem_ptr.add(offset), CM_PASSWD | value);
+        }
+    }
+
+    // Stops the clock generator with the specified control register at the
+    // end of the current clock cycle.
+    pub(crate) fn stop(&self, ctl: usize) -> Result<()> {
+        self.write(ctl, self.read(ctl) & !(CM_PASSWD | CTL_ENAB));
+
+        let start = Instant::now();
+        while self.read(ctl) & CTL_BUSY > 0 {
+            if start.elapsed() > BUSY_TIMEOUT {
+                // Force the generator to stop, which might cause a glitch
+                self.write(ctl, (self.read(ctl) & !CM_PASSWD) | CTL_KILL);
+                sleep(Duration::from_micros(10));
+
+                if self.read(ctl) & CTL_BUSY > 0 {
+                    return Err(Error::Busy);
+                }
+
+                self.write(ctl, 0);
+                break;
+            }
+
+            sleep(Duration::from_micros(10));
+        }
+
+        Ok(())
+    }
+
+    // Configuring the source, divisor or MASH while the generator is running
+    // causes glitches, so the generator is stopped first. The divisor register
+    // directly follows the control register.
+    pub(crate) fn start(&self, ctl: usize, divisor: &Divisor) -> Result<()> {
+        self.stop(ctl)?;
+
+        self.write(ctl + 1, (divisor.divi << 12) | divisor.divf);
+        self.write(ctl, (divisor.mash() << CTL_MASH_SHIFT) | divisor.source);
+        self.write(
+            ctl,
+            (divisor.mash() << CTL_MASH_SHIFT) | divisor.source | CTL_ENAB,
+        );
+
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn is_enabled(&self, ctl: usize) -> bool {
+        self.read(ctl) & CTL_ENAB > 0
+    }
+}
+
+impl Drop for ClockManager {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.mem_ptr as *mut libc::c_void, CM_MEM_SIZE);
+        }
+    }
+}
+
+// Reads the measured frequency of a clock from debugfs, if available
+fn measured_rate(clock: &str) -> Option<f64> {
+    fs::read_to_string(format!("/sys/kernel/debug/clk/{}/clk_rate", clock))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+// Clock source, and the integer and fractional parts of the divisor
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Divisor {
+    source: u32,
+    source_frequency: f64,
+    divi: u32,
+    divf: u32,
+}
+
+impl Divisor {
+    pub(crate) fn frequency(&self) -> f64 {
+        self.source_frequency / (f64::from(self.divi) + f64::from(self.divf) / 4096.0)
+    }
+
+    pub(crate) fn source_frequency(&self) -> f64 {
+        self.source_frequency
+    }
+
+    fn mash(&self) -> u32 {
+        if self.divf > 0 {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+// Returns the clock sources available to the clock generators, and their
+// frequency in hertz (Hz)
+pub(crate) fn clock_sources(device_info: &DeviceInfo) -> Result<[(u32, f64); 2]> {
+    let (oscillator, plld) = match device_info.soc() {
+        SoC::Bcm2712 => return Err(Error::UnsupportedSoC(SoC::Bcm2712)),
+        SoC::Bcm2711 => (54_000_000.0, 750_000_000.0),
+        _ => (19_200_000.0, 500_000_000.0),
+    };
+
+    Ok([
+        (SRC_OSCILLATOR, measured_rate("osc").unwrap_or(oscillator)),
+        (SRC_PLLD, measured_rate("plld_per").unwrap_or(plld)),
+    ])
+}
+
+pub(crate) fn calculate_divisor(sources: &[(u32, f64)], frequency: f64) -> Option<Divisor> {
+    let mut best: Option<(bool, f64, Divisor)> = None;
+
+    for &(source, source_frequency) in sources {
+        let divisor = source_frequency / frequency;
+        let mut divi = divisor.trunc() as u32;
+        let mut divf = ((divisor - divisor.trunc()) * 4096.0).round() as u32;
+        if divf > DIVF_MAX {
+            divi += 1;
+            divf = 0;
+        }
+
+        // MASH 1 requires an integer divisor of at least 2
+        let min_divi = if divf > 0 { 2 } else { 1 };
+        if divi < min_divi || divi > DIVI_MAX {
+            continue;
+        }
+
+        let candidate = Divisor {
+            source,
+            source_frequency,
+            divi,
+            divf,
+        };
+
+        let integer = divf == 0;
+        let error = (candidate.frequency() - frequency).abs();
+
+        let better = match best {
+            None => true,
+            Some((best_integer, best_error, _)) => {
+                (integer && !best_integer) || (integer == best_integer && error < best_error)
+            }
+        };
+
+        if better {
+            best = Some((integer, error, candidate));
+        }
+    }
+
+    best.map(|(_, _, divisor)| divisor)
+}
+
+/// Provides access to a general-purpose clock generator.
+///
+/// The clock generator is stopped, and the GPIO pin is reset to its original
+/// mode, when `Gpclk` goes out of scope.
+#[derive(Debug)]
+pub struct Gpclk {
+    channel: Channel,
+    manager: ClockManager,
+    sources: [(u32, f64); 2],
+    divisor: Divisor,
+    pin: AltPin,
+}
+
+impl Gpclk {
+    /// Constructs a new `Gpclk`, and starts outputting the specified frequency
+    /// in hertz (Hz) on the default GPIO pin for `channel`.
+    ///
+    /// The actual frequency might differ slightly from the requested frequency,
+    /// and can be retrieved with [`frequency`].
+    ///
+    /// [`frequency`]: #method.frequency
+    pub fn new(channel: Channel, frequency: f64) -> Result<Gpclk> {
+        Gpclk::with_pin(channel, channel.default_pin(), frequency)
+    }
+
+    /// Constructs a new `Gpclk`, and starts outputting the specified frequency
+    /// in hertz (Hz) on the specified BCM GPIO pin.
+    ///
+    /// Returns [`Error::Gpio`] wrapping [`gpio::Error::FunctionNotAvailable`] if
+    /// `channel` can't be routed to `pin`.
+    ///
+    /// [`Error::Gpio`]: enum.Error.html#variant.Gpio
+    /// [`gpio::Error::FunctionNotAvailable`]: ../gpio/enum.Error.html#variant.FunctionNotAvailable
+    pub fn with_pin(channel: Channel, pin: u8, frequency: f64) -> Result<Gpclk> {
+        let device_info = DeviceInfo::new().map_err(|_| Error::UnknownModel)?;
+        let sources = clock_sources(&device_info)?;
+
+        let divisor =
+            calculate_divisor(&sources, frequency).ok_or(Error::InvalidFrequency(frequency))?;
+
+        let manager = ClockManager::open(&device_info)?;
+
+        let pin = Gpio::new()?
+            .get(pin)
+            .ok_or(gpio::Error::PinNotAvailable(pin))?
+            .into_alt_function(Function::Gpclk(channel as u8))?;
+
+        let gpclk = Gpclk {
+            channel,
+            manager,
+            sources,
+            divisor,
+            pin,
+        };
+
+        gpclk.start()?;
+
+        Ok(gpclk)
+    }
+
+    /// Returns the clock generator's channel.
+    pub fn channel(&self) -> Channel {
+        self.channel
+    }
+
+    /// Returns the BCM GPIO pin number the clock is routed to.
+    pub fn pin(&self) -> u8 {
+        self.pin.pin()
+    }
+
+    /// Returns the actual output frequency in hertz (Hz).
+    pub fn frequency(&self) -> f64 {
+        self.divisor.frequency()
+    }
+
+    /// Returns the frequency of the selected clock source in hertz (Hz).
+    pub fn source_frequency(&self) -> f64 {
+        self.divisor.source_frequency()
+    }
+
+    /// Changes the output frequency to the specified value in hertz (Hz).
+    ///
+    /// The clock generator is briefly stopped while the divisor is updated.
+    pub fn set_frequency(&mut self, frequency: f64) -> Result<()> {
+        self.divisor = calculate_divisor(&self.sources, frequency)
+            .ok_or(Error::InvalidFrequency(frequency))?;
+
+        self.start()
+    }
+
+    /// Returns `true` if the clock generator is running.
+    pub fn is_enabled(&self) -> bool {
+        self.manager.is_enabled(self.ctl())
+    }
+
+    /// Starts the clock generator at the current frequency.
+    pub fn enable(&self) -> Result<()> {
+        self.start()
+    }
+
+    /// Stops the clock generator.
+    ///
+    /// The output is stopped at the end of the current clock cycle, and the pin
+    /// remains low.
+    pub fn disable(&self) -> Result<()> {
+        self.manager.stop(self.ctl())
+    }
+
+    fn ctl(&self) -> usize {
+        CM_GP0CTL + 2 * self.channel as usize
+    }
+
+    fn start(&self) -> Result<()> {
+        self.manager.start(self.ctl(), &self.divisor)
+    }
+}
+
+impl Drop for Gpclk {
+    fn drop(&mut self) {
+        let _ = self.disable();
+    }
+}
+
+// Required because of the raw pointer to our memory-mapped registers
+unsafe impl Send for Gpclk {}
@@ -0,0 +1,311 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Declarative GPIO pin configuration.
+//!
+//! Products like HATs often define their wiring in a configuration file rather
+//! than in code. A [`Config`] describes the mode, built-in pull-up/pull-down
+//! resistor state, initial logic level and interrupt trigger for any number of
+//! pins, and can be applied in a single step with [`Config::apply`].
+//!
+//! When the optional `serde` feature is enabled, [`Config`] and [`PinConfig`]
+//! implement `Serialize` and `Deserialize`, which allows them to be loaded from
+//! any format supported by serde, such as TOML or JSON. Fields that are omitted
+//! keep their default values.
+//!
+//! ```toml
+//! [[pins]]
+//! pin = 17
+//! mode = "Output"
+//! level = "High"
+//!
+//! [[pins]]
+//! pin = 27
+//! mode = "Input"
+//! pull = "PullUp"
+//! trigger = "FallingEdge"
+//! ```
+//!
+//! ## Applying a configuration
+//!
+//! [`Config::apply`] validates the entire configuration before any pins are
+//! retrieved, and then retrieves all pins at once. If a pin number is invalid,
+//! already in use or listed more than once, no pins are modified. If
+//! configuring a pin fails after that point, all pins that were configured so
+//! far go out of scope, and are reset to their original state as described in
+//! the [`gpio`] module documentation.
+//!
+//! ```no_run
+//! use rppal::config::{Config, PinConfig};
+//! use rppal::gpio::{Gpio, Level, Mode, PullUpDown, Trigger};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut config = Config::new();
+//! config.pins.push(PinConfig::new(17, Mode::Output).with_level(Level::High));
+//! config.pins.push(
+//!     PinConfig::new(27, Mode::Input)
+//!         .with_pull(PullUpDown::PullUp)
+//!         .with_trigger(Trigger::FallingEdge),
+//! );
+//!
+//! let mut pins = config.apply(&Gpio::new()?)?;
+//!
+//! if let Some(mut led) = pins.remove(&17).and_then(|pin| pin.into_output()) {
+//!     led.set_low();
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Config`]: struct.Config.html
+//! [`Config::apply`]: struct.Config.html#method.apply
+//! [`PinConfig`]: struct.PinConfig.html
+//! [`gpio`]: ../gpio/index.html#pins
+
+use std::collections::BTreeMap;
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::gpio::{self, AltPin, Gpio, InputPin, Level, Mode, OutputPin, PullUpDown, Trigger};
+
+quick_error! {
+/// Errors that can occur when applying a pin configuration.
+    #[derive(Debug)]
+    pub enum Error {
+/// GPIO error.
+///
+/// A pin couldn't be retrieved or configured.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Invalid initial level.
+///
+/// An initial logic level was specified for a pin that isn't configured as an output.
+        InvalidLevel(pin: u8) { description("initial level is only supported for output pins") }
+/// Invalid interrupt trigger.
+///
+/// An interrupt trigger was specified for a pin that isn't configured as an input.
+        InvalidTrigger(pin: u8) { description("interrupt trigger is only supported for input pins") }
+    }
+}
+
+/// Result type returned from methods that can have `config::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// Configuration for a single GPIO pin.
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PinConfig {
+    /// BCM GPIO pin number.
+    pub pin: u8,
+    /// Pin mode.
+    pub mode: Mode,
+    /// Built-in pull-up/pull-down resistor state. If `None`, the current state
+    /// is left unchanged for output and alternate function pins, and disabled
+    /// for input pins.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pull: Option<PullUpDown>,
+    /// Initial logic level. Only supported for output pins. If `None`, the
+    /// current output level is left unchanged.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub level: Option<Level>,
+    /// Synchronous interrupt trigger. Only supported for input pins.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub trigger: Option<Trigger>,
+}
+
+impl PinConfig {
+    /// Constructs a new `PinConfig` for the specified BCM GPIO pin number and mode.
+    pub fn new(pin: u8, mode: Mode) -> PinConfig {
+        PinConfig {
+            pin,
+            mode,
+            pull: None,
+            level: None,
+            trigger: None,
+        }
+    }
+
+    /// Sets the built-in pull-up/pull-down resistor state.
+    pub fn with_pull(mut self, pull: PullUpDown) -> PinConfig {
+        self.pull = Some(pull);
+        self
+    }
+
+    /// Sets the initial logic level.
+    pub fn with_level(mut self, level: Level) -> PinConfig {
+        self.level = Some(level);
+        self
+    }
+
+    /// Sets the synchronous interrupt trigger.
+    pub fn with_trigger(mut self, trigger: Trigger) -> PinConfig {
+        self.trigger = Some(trigger);
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.level.is_some() && self.mode != Mode::Output {
+            return Err(Error::InvalidLevel(self.pin));
+        }
+
+        if self.trigger.is_some() && self.mode != Mode::Input {
+            return Err(Error::InvalidTrigger(self.pin));
+        }
+
+        Ok(())
+    }
+
+    fn configure(&self, pin: gpio::Pin) -> Result<ConfiguredPin> {
+        match self.mode {
+            Mode::Input => {
+                let mut pin = match self.pull.unwrap_or(PullUpDown::Off) {
+                    PullUpDown::Off => pin.into_input(),
+                    PullUpDown::PullDown => pin.into_input_pulldown(),
+                    PullUpDown::PullUp => pin.into_input_pullup(),
+                };
+
+                if let Some(trigger) = self.trigger {
+                    pin.set_interrupt(trigger)?;
+                }
+
+                Ok(ConfiguredPin::Input(pin))
+            }
+            Mode::Output => {
+                let mut pin = match self.level {
+                    Some(Level::Low) => pin.into_output_low(),
+                    Some(Level::High) => pin.into_output_high(),
+                    None => pin.into_output(),
+                };
+
+                if let Some(pull) = self.pull {
+                    pin.set_pullupdown(pull);
+                }
+
+                Ok(ConfiguredPin::Output(pin))
+            }
+            mode => {
+                let mut pin = pin.into_alt(mode);
+
+                if let Some(pull) = self.pull {
+                    pin.set_pullupdown(pull);
+                }
+
+                Ok(ConfiguredPin::Alt(pin))
+            }
+        }
+    }
+}
+
+/// Configuration for a set of GPIO pins.
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Config {
+    /// Pin configurations, applied in order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pins: Vec<PinConfig>,
+}
+
+impl Config {
+    /// Constructs a new, empty `Config`.
+    pub fn new() -> Config {
+        Config::default()
+    }
+
+    /// Applies the configuration, and returns the configured pins, indexed by
+    /// their BCM GPIO pin number.
+    ///
+    /// The configuration is validated before any pins are retrieved. More
+    /// information on error handling can be found in the [module-level
+    /// documentation].
+    ///
+    /// [module-level documentation]: index.html#applying-a-configuration
+    pub fn apply(&self, gpio: &Gpio) -> Result<BTreeMap<u8, ConfiguredPin>> {
+        for pin_config in &self.pins {
+            pin_config.validate()?;
+        }
+
+        let pin_numbers: Vec<u8> = self.pins.iter().map(|pin_config| pin_config.pin).collect();
+        let pins = gpio.get_multiple(&pin_numbers)?;
+
+        let mut configured = BTreeMap::new();
+        for (pin_config, pin) in self.pins.iter().zip(pins) {
+            // Dropping configured resets any pins we've configured so far
+            configured.insert(pin_config.pin, pin_config.configure(pin)?);
+        }
+
+        Ok(configured)
+    }
+}
+
+/// A GPIO pin configured by [`Config::apply`].
+///
+/// [`Config::apply`]: struct.Config.html#method.apply
+#[derive(Debug)]
+pub enum ConfiguredPin {
+    /// Pin configured as input.
+    Input(InputPin),
+    /// Pin configured as output.
+    Output(OutputPin),
+    /// Pin configured for an alternate function.
+    Alt(AltPin),
+}
+
+impl ConfiguredPin {
+    /// Returns the GPIO pin number.
+    ///
+    /// Pins are addressed by their BCM numbers, rather than their physical location.
+    pub fn pin(&self) -> u8 {
+        match *self {
+            ConfiguredPin::Input(ref pin) => pin.pin(),
+            ConfiguredPin::Output(ref pin) => pin.pin(),
+            ConfiguredPin::Alt(ref pin) => pin.pin(),
+        }
+    }
+
+    /// Returns the [`InputPin`] if the pin is configured as input.
+    ///
+    /// [`InputPin`]: ../gpio/struct.InputPin.html
+    pub fn into_input(self) -> Option<InputPin> {
+        match self {
+            ConfiguredPin::Input(pin) => Some(pin),
+            _ => None,
+        }
+    }
+
+    /// Returns the [`OutputPin`] if the pin is configured as output.
+    ///
+    /// [`OutputPin`]: ../gpio/struct.OutputPin.html
+    pub fn into_output(self) -> Option<OutputPin> {
+        match self {
+            ConfiguredPin::Output(pin) => Some(pin),
+            _ => None,
+        }
+    }
+
+    /// Returns the [`AltPin`] if the pin is configured for an alternate function.
+    ///
+    /// [`AltPin`]: ../gpio/struct.AltPin.html
+    pub fn into_alt(self) -> Option<AltPin> {
+        match self {
+            ConfiguredPin::Alt(pin) => Some(pin),
+            _ => None,
+        }
+    }
+}
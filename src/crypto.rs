@@ -0,0 +1,92 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Drivers for cryptographic coprocessors.
+//!
+//! * [`Atecc`] supports the Microchip ATECC508A and ATECC608 secure elements,
+//!   connected through I2C, which are included on many industrial Raspberry Pi
+//!   HATs. It generates random numbers, calculates SHA-256 digests, and signs
+//!   and verifies pre-hashed messages using ECDSA with the NIST P-256 curve. Private
+//!   keys never leave the device.
+//!
+//! The ATECC508A and ATECC608 go to sleep when they haven't been addressed for a
+//! while, and need to be woken up by holding SDA low for at least 60 µs, which
+//! [`Atecc`] takes care of before each command. This only works reliably if the
+//! I2C bus is configured for a clock speed of 100 kHz or less, which can be
+//! set by adding `dtparam=i2c_arm_baudrate=100000` to `/boot/config.txt`.
+//!
+//! Configuring and locking the device's configuration and data zones isn't
+//! supported. Use a dedicated provisioning tool before using the ATECC for
+//! signing operations.
+//!
+//! [`Atecc`]: struct.Atecc.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::i2c;
+
+mod atecc;
+
+pub use self::atecc::{Atecc, AteccModel};
+
+quick_error! {
+/// Errors that can occur when accessing a cryptographic coprocessor.
+    #[derive(Debug)]
+    pub enum Error {
+/// I2C error.
+        I2c(err: i2c::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Unknown device.
+///
+/// The device's revision number doesn't match any of the supported models.
+/// Contains the revision number that was read instead.
+        UnknownDevice(revision: u32) { description("unknown device") }
+/// Wake-up failed.
+///
+/// The device didn't respond with the expected wake-up token. This usually means
+/// the I2C bus is configured for a clock speed above 100 kHz.
+        WakeFailed { description("device failed to wake up") }
+/// Timeout.
+///
+/// The device didn't respond within the command's maximum execution time.
+        Timeout { description("device didn't respond in time") }
+/// CRC mismatch.
+        Crc { description("CRC mismatch") }
+/// Invalid response.
+///
+/// The response doesn't have the expected length.
+        InvalidResponse { description("invalid response") }
+/// Command failed.
+///
+/// The device returned an error status. Contains the status code, which is one of
+/// 0x01 (checkmac or verify miscompare), 0x03 (parse error), 0x05 (ECC fault),
+/// 0x07 (self test error), 0x08 (health test error), 0x0F (execution error),
+/// 0xEE (watchdog about to expire) or 0xFF (communication error).
+        Status(code: u8) { description("command failed") }
+/// Invalid key slot.
+///
+/// Key slots range from 0 to 15.
+        InvalidSlot(slot: u8) { description("invalid key slot") }
+    }
+}
+
+/// Result type returned from methods that can have `crypto::Error`s.
+pub type Result<T> = result::Result<T, Error>;
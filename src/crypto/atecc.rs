@@ -0,0 +1,431 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::crypto::{Error, Result};
+use crate::i2c::I2c;
+
+// Word addresses
+const WORD_SLEEP: u8 = 0x01;
+const WORD_IDLE: u8 = 0x02;
+const WORD_COMMAND: u8 = 0x03;
+
+const OPCODE_READ: u8 = 0x02;
+const OPCODE_NONCE: u8 = 0x16;
+const OPCODE_RANDOM: u8 = 0x1b;
+const OPCODE_INFO: u8 = 0x30;
+const OPCODE_GENKEY: u8 = 0x40;
+const OPCODE_SIGN: u8 = 0x41;
+const OPCODE_VERIFY: u8 = 0x45;
+const OPCODE_SHA: u8 = 0x47;
+
+const NONCE_MODE_PASSTHROUGH: u8 = 0x03;
+const SHA_MODE_START: u8 = 0x00;
+const SHA_MODE_UPDATE: u8 = 0x01;
+const SHA_MODE_END: u8 = 0x02;
+const SIGN_MODE_EXTERNAL: u8 = 0x80;
+const VERIFY_MODE_EXTERNAL: u8 = 0x02;
+const VERIFY_KEY_P256: u16 = 0x0004;
+const READ_ZONE_CONFIG_32: u8 = 0x80;
+
+const STATUS_SUCCESS: u8 = 0x00;
+const STATUS_MISCOMPARE: u8 = 0x01;
+
+// Count, status 0x11 (after wake) and CRC
+const WAKE_TOKEN: [u8; 4] = [0x04, 0x11, 0x33, 0x43];
+
+// SDA needs to be high for 1.5 ms after the wake pulse (tWHI)
+const WAKE_DELAY: Duration = Duration::from_micros(1500);
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+// Count, up to 64 bytes of data or a 64-byte signature and public key, and CRC
+const RESPONSE_MAX: usize = 75;
+
+const SHA_BLOCK_SIZE: usize = 64;
+
+/// Supported ATECC models.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum AteccModel {
+    /// ATECC508A.
+    Atecc508a,
+    /// ATECC608A or ATECC608B.
+    Atecc608,
+}
+
+/// ATECC508A and ATECC608 secure element driver.
+///
+/// The ATECC508A and ATECC608 store up to 16 keys in separate slots, and
+/// perform cryptographic operations without exposing private keys. Signing and
+/// verification use ECDSA with the NIST P-256 curve on a 32-byte message digest,
+/// which can be calculated by the device itself with [`sha256`].
+///
+/// Signatures and public keys are 64 bytes long. Signatures consist of the R and S
+/// integers, and public keys consist of the X and Y coordinates, all stored as
+/// 32-byte big-endian values.
+///
+/// By default, the ATECC uses slave address 0x60. Most HATs don't change the
+/// default address, but some devices are provisioned with a different one.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::crypto::Atecc;
+/// use rppal::i2c::I2c;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut atecc = Atecc::new(I2c::new()?, 0x60)?;
+///
+/// let digest = atecc.sha256(b"Hello, world!")?;
+/// let signature = atecc.sign(0, &digest)?;
+/// let public_key = atecc.public_key(0)?;
+///
+/// assert!(atecc.verify(&digest, &signature, &public_key)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`sha256`]: #method.sha256
+#[derive(Debug)]
+pub struct Atecc {
+    i2c: I2c,
+    model: AteccModel,
+}
+
+impl Atecc {
+    /// Constructs a new `Atecc` for the device at the specified slave address.
+    ///
+    /// The device's model is detected by reading its revision number. Returns
+    /// [`Error::UnknownDevice`] if the revision number doesn't match a supported
+    /// model.
+    ///
+    /// [`Error::UnknownDevice`]: enum.Error.html#variant.UnknownDevice
+    pub fn new(mut i2c: I2c, address: u16) -> Result<Atecc> {
+        i2c.set_slave_address(address)?;
+
+        let mut atecc = Atecc {
+            i2c,
+            model: AteccModel::Atecc608,
+        };
+
+        let revision = atecc.revision()?;
+        atecc.model = match revision[2] {
+            0x50 => AteccModel::Atecc508a,
+            0x60 => AteccModel::Atecc608,
+            _ => return Err(Error::UnknownDevice(u32::from_be_bytes(revision))),
+        };
+
+        Ok(atecc)
+    }
+
+    /// Returns the detected model.
+    pub fn model(&self) -> AteccModel {
+        self.model
+    }
+
+    /// Returns the device's revision number.
+    pub fn revision(&mut self) -> Result<[u8; 4]> {
+        let mut revision = [0u8; 4];
+        self.execute(OPCODE_INFO, 0, 0, &[], 5, &mut revision)?;
+
+        Ok(revision)
+    }
+
+    /// Returns the device's unique 9-byte serial number.
+    pub fn serial_number(&mut self) -> Result<[u8; 9]> {
+        let mut config = [0u8; 32];
+        self.execute(OPCODE_READ, READ_ZONE_CONFIG_32, 0, &[], 5, &mut config)?;
+
+        let mut serial_number = [0u8; 9];
+        serial_number[..4].copy_from_slice(&config[..4]);
+        serial_number[4..].copy_from_slice(&config[8..13]);
+
+        Ok(serial_number)
+    }
+
+    /// Returns 32 random bytes generated by the device's hardware random number
+    /// generator.
+    ///
+    /// Until the configuration zone has been locked, the device returns a fixed
+    /// test pattern instead of random data.
+    pub fn random(&mut self) -> Result<[u8; 32]> {
+        let mut random = [0u8; 32];
+        self.execute(OPCODE_RANDOM, 0, 0, &[0u8; 20], 25, &mut random)?;
+
+        Ok(random)
+    }
+
+    /// Calculates the SHA-256 digest of `data`.
+    ///
+    /// The data is transferred to the device in 64-byte blocks.
+    pub fn sha256(&mut self, data: &[u8]) -> Result<[u8; 32]> {
+        self.execute(OPCODE_SHA, SHA_MODE_START, 0, &[], 45, &mut [])?;
+
+        let mut blocks = data.chunks_exact(SHA_BLOCK_SIZE);
+        for block in &mut blocks {
+            self.execute(OPCODE_SHA, SHA_MODE_UPDATE, 0, block, 45, &mut [])?;
+        }
+
+        let remainder = blocks.remainder();
+        let mut digest = [0u8; 32];
+        self.execute(
+            OPCODE_SHA,
+            SHA_MODE_END,
+            remainder.len() as u16,
+            remainder,
+            45,
+            &mut digest,
+        )?;
+
+        Ok(digest)
+    }
+
+    /// Returns the public key for the private key stored in the specified slot.
+    ///
+    /// `slot` must be between 0 and 15, and contain a P-256 private key.
+    pub fn public_key(&mut self, slot: u8) -> Result<[u8; 64]> {
+        check_slot(slot)?;
+
+        let mut public_key = [0u8; 64];
+        self.execute(OPCODE_GENKEY, 0, u16::from(slot), &[], 115, &mut public_key)?;
+
+        Ok(public_key)
+    }
+
+    /// Signs a 32-byte message digest with the private key stored in the specified
+    /// slot, and returns the signature.
+    ///
+    /// `slot` must be between 0 and 15, and contain a P-256 private key. The
+    /// configuration and data zones need to be locked.
+    pub fn sign(&mut self, slot: u8, digest: &[u8; 32]) -> Result<[u8; 64]> {
+        check_slot(slot)?;
+
+        self.load_digest(digest)?;
+
+        let mut signature = [0u8; 64];
+        self.execute(
+            OPCODE_SIGN,
+            SIGN_MODE_EXTERNAL,
+            u16::from(slot),
+            &[],
+            115,
+            &mut signature,
+        )?;
+
+        Ok(signature)
+    }
+
+    /// Verifies a signature for a 32-byte message digest against an external
+    /// public key.
+    ///
+    /// Returns `Ok(true)` if the signature is valid, or `Ok(false)` if it isn't.
+    pub fn verify(
+        &mut self,
+        digest: &[u8; 32],
+        signature: &[u8; 64],
+        public_key: &[u8; 64],
+    ) -> Result<bool> {
+        self.load_digest(digest)?;
+
+        let mut data = [0u8; 128];
+        data[..64].copy_from_slice(signature);
+        data[64..].copy_from_slice(public_key);
+
+        match self.execute(
+            OPCODE_VERIFY,
+            VERIFY_MODE_EXTERNAL,
+            VERIFY_KEY_P256,
+            &data,
+            105,
+            &mut [],
+        ) {
+            Ok(()) => Ok(true),
+            Err(Error::Status(STATUS_MISCOMPARE)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Puts the device in sleep mode.
+    ///
+    /// The device's volatile state is cleared, and the power consumption drops
+    /// below 150 nA. The device automatically wakes up when it's addressed again.
+    pub fn sleep(&mut self) -> Result<()> {
+        self.wake()?;
+        self.i2c.write(&[WORD_SLEEP])?;
+
+        Ok(())
+    }
+
+    /// Consumes the `Atecc`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+
+    // Loads a message digest into the device's TempKey register, which is used
+    // by the Sign and Verify commands
+    fn load_digest(&mut self, digest: &[u8; 32]) -> Result<()> {
+        self.execute(OPCODE_NONCE, NONCE_MODE_PASSTHROUGH, 0, digest, 20, &mut [])
+    }
+
+    // Wakes up the device, sends a command, and reads the response into buffer.
+    // If buffer is empty, the response is expected to only contain a status code.
+    // The device is put in idle mode afterwards, which preserves its volatile
+    // state while resetting the watchdog timer, so multi-command sequences like
+    // SHA-256 calculations and TempKey-based signing keep working.
+    fn execute(
+        &mut self,
+        opcode: u8,
+        param1: u8,
+        param2: u16,
+        data: &[u8],
+        execution_time_ms: u64,
+        buffer: &mut [u8],
+    ) -> Result<()> {
+        self.wake()?;
+
+        let result = self
+            .send_command(opcode, param1, param2, data)
+            .and_then(|_| self.read_response(Duration::from_millis(execution_time_ms), buffer));
+
+        // Make sure the device doesn't stay awake if the command failed
+        let _ = self.i2c.write(&[WORD_IDLE]);
+
+        result
+    }
+
+    fn wake(&mut self) -> Result<()> {
+        // Addressing the general call address holds SDA low for 8 clock cycles,
+        // which is at least 60 µs (tWLO) at 100 kHz. Nothing is expected to
+        // acknowledge the address, so any errors are ignored.
+        let _ = self.i2c.write_general_call(&[0x00]);
+
+        sleep(WAKE_DELAY);
+
+        let mut token = [0u8; 4];
+        if self.i2c.read(&mut token).is_err() {
+            return Err(Error::WakeFailed);
+        }
+
+        if token == WAKE_TOKEN {
+            Ok(())
+        } else if token[0] == 4 && crc(&token[..2]) == [token[2], token[3]] {
+            // ATECC608 self test or health test failure
+            Err(Error::Status(token[1]))
+        } else {
+            Err(Error::WakeFailed)
+        }
+    }
+
+    fn send_command(&mut self, opcode: u8, param1: u8, param2: u16, data: &[u8]) -> Result<()> {
+        let mut packet = Vec::with_capacity(data.len() + 8);
+        packet.push(WORD_COMMAND);
+        packet.push((data.len() + 7) as u8);
+        packet.push(opcode);
+        packet.push(param1);
+        packet.extend_from_slice(&param2.to_le_bytes());
+        packet.extend_from_slice(data);
+
+        let crc = crc(&packet[1..]);
+        packet.extend_from_slice(&crc);
+
+        self.i2c.write(&packet)?;
+
+        Ok(())
+    }
+
+    fn read_response(&mut self, execution_time: Duration, buffer: &mut [u8]) -> Result<()> {
+        let start = Instant::now();
+        let mut response = [0u8; RESPONSE_MAX];
+
+        // The device doesn't acknowledge its address until the command has been
+        // executed, so keep polling until the maximum execution time has passed.
+        loop {
+            sleep(POLL_INTERVAL);
+
+            if self.i2c.read(&mut response[..1]).is_ok() {
+                break;
+            }
+
+            if start.elapsed() > execution_time {
+                return Err(Error::Timeout);
+            }
+        }
+
+        let count = response[0] as usize;
+        if !(4..=RESPONSE_MAX).contains(&count) {
+            return Err(Error::InvalidResponse);
+        }
+
+        self.i2c.read(&mut response[1..count])?;
+
+        if crc(&response[..count - 2]) != [response[count - 2], response[count - 1]] {
+            return Err(Error::Crc);
+        }
+
+        let payload = &response[1..count - 2];
+
+        // A single byte contains a status code
+        if payload.len() == 1 && buffer.len() != 1 {
+            return match payload[0] {
+                STATUS_SUCCESS if buffer.is_empty() => Ok(()),
+                STATUS_SUCCESS => Err(Error::InvalidResponse),
+                code => Err(Error::Status(code)),
+            };
+        }
+
+        if payload.len() != buffer.len() {
+            return Err(Error::InvalidResponse);
+        }
+
+        buffer.copy_from_slice(payload);
+
+        Ok(())
+    }
+}
+
+fn check_slot(slot: u8) -> Result<()> {
+    if slot > 15 {
+        Err(Error::InvalidSlot(slot))
+    } else {
+        Ok(())
+    }
+}
+
+// CRC-16 with polynomial 0x8005, processing each byte LSB first, returned in
+// little-endian byte order
+fn crc(data: &[u8]) -> [u8; 2] {
+    let mut crc: u16 = 0;
+
+    for byte in data {
+        for bit in 0..8 {
+            let data_bit = (byte >> bit) & 1;
+            let crc_bit = (crc >> 15) as u8;
+
+            crc <<= 1;
+
+            if data_bit != crc_bit {
+                crc ^= 0x8005;
+            }
+        }
+    }
+
+    crc.to_le_bytes()
+}
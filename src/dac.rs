@@ -0,0 +1,75 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Drivers for external digital-to-analog converters.
+//!
+//! The Raspberry Pi doesn't have any analog outputs. Analog voltages can be
+//! generated with an external DAC instead, connected through either I2C or SPI.
+//!
+//! * [`Mcp4725`] supports the single-channel 12-bit MCP4725, connected through I2C,
+//!   which can store its output value and power-down mode in EEPROM.
+//! * [`Mcp4922`] supports the dual-channel 12-bit MCP4922, connected through SPI.
+//!
+//! Besides writing raw values, both drivers can scale a voltage based on the
+//! reference voltage, which complements the voltage readings provided by the
+//! [`adc`] module.
+//!
+//! [`Mcp4725`]: struct.Mcp4725.html
+//! [`Mcp4922`]: struct.Mcp4922.html
+//! [`adc`]: ../adc/index.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::i2c;
+use crate::spi;
+
+mod mcp4725;
+mod mcp4922;
+
+pub use self::mcp4725::{Mcp4725, PowerDown};
+pub use self::mcp4922::{Gain, Mcp4922};
+
+quick_error! {
+/// Errors that can occur when accessing a DAC.
+    #[derive(Debug)]
+    pub enum Error {
+/// SPI error.
+        Spi(err: spi::Error) { description(err.description()) from() }
+/// I2C error.
+        I2c(err: i2c::Error) { description(err.description()) from() }
+/// Invalid channel.
+        InvalidChannel(channel: u8) { description("invalid channel") }
+    }
+}
+
+/// Result type returned from methods that can have `dac::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+// Converts a voltage into a 12-bit value, based on the voltage that corresponds
+// to the full-scale output
+pub(crate) fn voltage_to_value(voltage: f64, full_scale: f64) -> u16 {
+    if full_scale <= 0.0 {
+        return 0;
+    }
+
+    (voltage / full_scale * 4096.0).round().clamp(0.0, 4095.0) as u16
+}
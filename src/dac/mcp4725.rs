@@ -0,0 +1,170 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::dac::{voltage_to_value, Result};
+use crate::i2c::I2c;
+
+// Write DAC register and EEPROM
+const CMD_WRITE_EEPROM: u8 = 0x60;
+// The EEPROM write cycle takes at most 50 ms
+const EEPROM_WRITE_TIME: Duration = Duration::from_millis(50);
+// Ready bit in the first status byte, which is cleared during an EEPROM write
+const STATUS_READY: u8 = 0x80;
+
+/// Power-down modes.
+///
+/// In power-down mode, the output amplifier is disabled, and the output is
+/// connected to ground through a resistor.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum PowerDown {
+    /// Normal operation.
+    Disabled = 0,
+    /// Output pulled down through a 1 kΩ resistor.
+    Pulldown1k = 1,
+    /// Output pulled down through a 100 kΩ resistor.
+    Pulldown100k = 2,
+    /// Output pulled down through a 500 kΩ resistor.
+    Pulldown500k = 3,
+}
+
+/// MCP4725 12-bit DAC driver.
+///
+/// The output voltage ranges from 0 V to VDD, which is also used as the reference
+/// voltage. The A0 pin selects between two slave addresses, which differ based on
+/// the part's address option (0x60 and 0x61 for the common A0 option).
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::dac::Mcp4725;
+/// use rppal::i2c::I2c;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut dac = Mcp4725::new(I2c::new()?, 0x60, 3.3)?;
+///
+/// dac.write_voltage(1.65)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Mcp4725 {
+    i2c: I2c,
+    vdd: f64,
+    value: u16,
+    power_down: PowerDown,
+}
+
+impl Mcp4725 {
+    /// Constructs a new `Mcp4725` for the device at the specified slave address.
+    ///
+    /// `vdd` is the supply voltage in volts (V), which is used to scale voltages.
+    /// The current output value and power-down mode are left unchanged until the
+    /// first write.
+    pub fn new(mut i2c: I2c, address: u16, vdd: f64) -> Result<Mcp4725> {
+        i2c.set_slave_address(address)?;
+
+        Ok(Mcp4725 {
+            i2c,
+            vdd,
+            value: 0,
+            power_down: PowerDown::Disabled,
+        })
+    }
+
+    /// Returns the supply voltage in volts (V).
+    pub fn vdd(&self) -> f64 {
+        self.vdd
+    }
+
+    /// Returns the last value written to the DAC.
+    pub fn value(&self) -> u16 {
+        self.value
+    }
+
+    /// Returns the last power-down mode written to the DAC.
+    pub fn power_down(&self) -> PowerDown {
+        self.power_down
+    }
+
+    /// Sets the output to `value` (0-4095), and disables power-down mode.
+    pub fn write(&mut self, value: u16) -> Result<()> {
+        self.write_fast(value & 0x0fff, PowerDown::Disabled)
+    }
+
+    /// Sets the output to the 12-bit value closest to `voltage`, and disables
+    /// power-down mode.
+    pub fn write_voltage(&mut self, voltage: f64) -> Result<()> {
+        self.write(voltage_to_value(voltage, self.vdd))
+    }
+
+    /// Configures the power-down mode, while keeping the current output value.
+    pub fn set_power_down(&mut self, power_down: PowerDown) -> Result<()> {
+        self.write_fast(self.value, power_down)
+    }
+
+    /// Sets the output to `value` (0-4095), and stores both the value and the
+    /// power-down mode in EEPROM, which is loaded when the DAC is powered on.
+    ///
+    /// `write_eeprom` blocks until the EEPROM write cycle has completed, which
+    /// takes up to 50 ms.
+    pub fn write_eeprom(&mut self, value: u16, power_down: PowerDown) -> Result<()> {
+        let value = value & 0x0fff;
+
+        self.i2c.write(&[
+            CMD_WRITE_EEPROM | ((power_down as u8) << 1),
+            (value >> 4) as u8,
+            (value << 4) as u8,
+        ])?;
+
+        self.value = value;
+        self.power_down = power_down;
+
+        // Poll the ready bit, and give up after the maximum write time
+        let start = Instant::now();
+        let mut status = [0u8; 1];
+        while start.elapsed() < EEPROM_WRITE_TIME {
+            sleep(Duration::from_millis(5));
+
+            if self.i2c.read(&mut status).is_ok() && status[0] & STATUS_READY > 0 {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_fast(&mut self, value: u16, power_down: PowerDown) -> Result<()> {
+        self.i2c
+            .write(&[((power_down as u8) << 4) | (value >> 8) as u8, value as u8])?;
+
+        self.value = value;
+        self.power_down = power_down;
+
+        Ok(())
+    }
+
+    /// Consumes the `Mcp4725`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::dac::{voltage_to_value, Error, Result};
+use crate::spi::Spi;
+
+const CONFIG_CHANNEL_B: u16 = 0x8000;
+const CONFIG_BUFFERED: u16 = 0x4000;
+// Output gain is 1x when set, and 2x when cleared
+const CONFIG_GAIN_1X: u16 = 0x2000;
+// Output is active when set, and shut down when cleared
+const CONFIG_ACTIVE: u16 = 0x1000;
+
+/// Output gain.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Gain {
+    /// The output ranges from 0 V to VREF.
+    X1,
+    /// The output ranges from 0 V to 2 × VREF, limited by VDD.
+    X2,
+}
+
+/// MCP4922 dual 12-bit DAC driver.
+///
+/// Channel 0 refers to output A (VOUTA), and channel 1 to output B (VOUTB). Each
+/// channel has its own reference voltage input. The outputs are updated as soon
+/// as a value is written if the LDAC pin is tied to ground. The maximum SPI clock
+/// speed is 20 MHz. The DAC supports SPI [`Mode0`] and [`Mode3`].
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::dac::Mcp4922;
+/// use rppal::spi::{Bus, Mode, SlaveSelect, Spi};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let spi = Spi::new(Bus::Spi0, SlaveSelect::Ss1, 10_000_000, Mode::Mode0)?;
+/// let mut dac = Mcp4922::new(spi, 3.3);
+///
+/// dac.write_voltage(0, 1.0)?;
+/// dac.write(1, 4095)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Mode0`]: ../spi/enum.Mode.html#variant.Mode0
+/// [`Mode3`]: ../spi/enum.Mode.html#variant.Mode3
+#[derive(Debug)]
+pub struct Mcp4922 {
+    spi: Spi,
+    vref: [f64; 2],
+    gain: [Gain; 2],
+    buffered: [bool; 2],
+}
+
+impl Mcp4922 {
+    /// Constructs a new `Mcp4922`.
+    ///
+    /// `vref` is the reference voltage in volts (V) for both channels, which is used
+    /// to scale voltages. The gain defaults to [`Gain::X1`], and the reference
+    /// voltage inputs are unbuffered.
+    ///
+    /// [`Gain::X1`]: enum.Gain.html#variant.X1
+    pub fn new(spi: Spi, vref: f64) -> Mcp4922 {
+        Mcp4922 {
+            spi,
+            vref: [vref; 2],
+            gain: [Gain::X1; 2],
+            buffered: [false; 2],
+        }
+    }
+
+    /// Returns the reference voltage in volts (V) for the specified channel.
+    pub fn vref(&self, channel: u8) -> Result<f64> {
+        Ok(self.vref[Mcp4922::index(channel)?])
+    }
+
+    /// Sets the reference voltage in volts (V) for the specified channel.
+    pub fn set_vref(&mut self, channel: u8, vref: f64) -> Result<()> {
+        self.vref[Mcp4922::index(channel)?] = vref;
+
+        Ok(())
+    }
+
+    /// Returns the output gain for the specified channel.
+    pub fn gain(&self, channel: u8) -> Result<Gain> {
+        Ok(self.gain[Mcp4922::index(channel)?])
+    }
+
+    /// Sets the output gain for the specified channel, which is applied on the
+    /// next write.
+    pub fn set_gain(&mut self, channel: u8, gain: Gain) -> Result<()> {
+        self.gain[Mcp4922::index(channel)?] = gain;
+
+        Ok(())
+    }
+
+    /// Returns `true` if the reference voltage input of the specified channel
+    /// is buffered.
+    pub fn buffered(&self, channel: u8) -> Result<bool> {
+        Ok(self.buffered[Mcp4922::index(channel)?])
+    }
+
+    /// Enables or disables the reference voltage input buffer for the specified
+    /// channel, which is applied on the next write.
+    ///
+    /// A buffered input has a higher input impedance, but limits the reference
+    /// voltage to between 0.04 V and VDD - 0.04 V.
+    pub fn set_buffered(&mut self, channel: u8, buffered: bool) -> Result<()> {
+        self.buffered[Mcp4922::index(channel)?] = buffered;
+
+        Ok(())
+    }
+
+    /// Sets the output of the specified channel to `value` (0-4095).
+    pub fn write(&mut self, channel: u8, value: u16) -> Result<()> {
+        let index = Mcp4922::index(channel)?;
+
+        self.send(index, CONFIG_ACTIVE | (value & 0x0fff))
+    }
+
+    /// Sets the output of the specified channel to the 12-bit value closest to
+    /// `voltage`, based on the channel's reference voltage and gain.
+    pub fn write_voltage(&mut self, channel: u8, voltage: f64) -> Result<()> {
+        let index = Mcp4922::index(channel)?;
+
+        let full_scale = match self.gain[index] {
+            Gain::X1 => self.vref[index],
+            Gain::X2 => self.vref[index] * 2.0,
+        };
+
+        self.write(channel, voltage_to_value(voltage, full_scale))
+    }
+
+    /// Shuts down the output of the specified channel, which disconnects the
+    /// output amplifier, and connects the output to ground through a 500 kΩ resistor.
+    ///
+    /// The output is enabled again on the next write.
+    pub fn shutdown(&mut self, channel: u8) -> Result<()> {
+        let index = Mcp4922::index(channel)?;
+
+        self.send(index, 0)
+    }
+
+    fn index(channel: u8) -> Result<usize> {
+        if channel > 1 {
+            return Err(Error::InvalidChannel(channel));
+        }
+
+        Ok(channel as usize)
+    }
+
+    fn send(&mut self, index: usize, data: u16) -> Result<()> {
+        let mut word = data;
+
+        if index == 1 {
+            word |= CONFIG_CHANNEL_B;
+        }
+
+        if self.buffered[index] {
+            word |= CONFIG_BUFFERED;
+        }
+
+        if self.gain[index] == Gain::X1 {
+            word |= CONFIG_GAIN_1X;
+        }
+
+        self.spi.write(&[(word >> 8) as u8, word as u8])?;
+
+        Ok(())
+    }
+
+    /// Consumes the `Mcp4922`, and returns the underlying `Spi` instance.
+    pub fn into_spi(self) -> Spi {
+        self.spi
+    }
+}
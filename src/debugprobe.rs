@@ -0,0 +1,119 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Bit-banged SWD and JTAG debug probes.
+//!
+//! RPPAL can act as a debug probe for microcontrollers connected to the GPIO
+//! header, which is commonly used to flash and test firmware in CI rigs.
+//!
+//! * [`Swd`] implements the ARM Serial Wire Debug protocol, including Debug Port
+//!   and Access Port register transfers and word-sized memory access through a
+//!   MEM-AP.
+//! * [`Jtag`] implements the IEEE 1149.1 TAP state machine sequences, including
+//!   instruction and data register scans.
+//!
+//! Both probes can use any available GPIO pins, and are clocked by busy waiting
+//! on the current thread. Because SWD and JTAG are synchronous protocols, the
+//! thread getting interrupted only slows down the transfer, and doesn't corrupt
+//! data.
+//!
+//! [`Swd`]: struct.Swd.html
+//! [`Jtag`]: struct.Jtag.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::delay::delay_ns;
+
+mod jtag;
+mod swd;
+
+pub use self::jtag::Jtag;
+pub use self::swd::Swd;
+
+quick_error! {
+/// Errors that can occur when accessing a debug target.
+    #[derive(Debug)]
+    pub enum Error {
+/// No response.
+///
+/// The target didn't drive SWDIO during the acknowledge phase. This usually
+/// means the target isn't connected or powered, or the debug port hasn't been
+/// switched to SWD.
+        NoResponse { description("target didn't respond") }
+/// WAIT response.
+///
+/// The target kept responding with WAIT after the maximum number of retries.
+        Wait { description("target is busy") }
+/// FAULT response.
+///
+/// A sticky error flag is set on the target, and needs to be cleared before
+/// any further AP transfers are accepted.
+        Fault { description("target responded with a fault") }
+/// Protocol error.
+///
+/// The target responded with an invalid acknowledge value. Contains the value
+/// that was received.
+        Protocol(ack: u8) { description("invalid acknowledge value") }
+/// Parity error.
+        Parity { description("parity error") }
+/// Timeout.
+///
+/// The target didn't acknowledge the debug and system power-up request in time.
+        Timeout { description("target didn't power up in time") }
+    }
+}
+
+/// Result type returned from methods that can have `debugprobe::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+// Generates the bit clock by busy waiting for half a period after every edge
+#[derive(Debug, Copy, Clone)]
+struct Clock {
+    frequency: f64,
+    half_period_ns: u64,
+}
+
+impl Clock {
+    fn new(frequency: f64) -> Clock {
+        let half_period_ns = if frequency > 0.0 {
+            (500_000_000.0 / frequency) as u64
+        } else {
+            0
+        };
+
+        Clock {
+            frequency,
+            half_period_ns,
+        }
+    }
+
+    fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    #[inline]
+    fn wait(&self) {
+        if self.half_period_ns > 0 {
+            delay_ns(self.half_period_ns);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::debugprobe::Clock;
+use crate::gpio::{IoPin, Level, Mode};
+
+const DEFAULT_FREQUENCY: f64 = 1_000_000.0;
+
+/// Bit-banged JTAG probe.
+///
+/// `Jtag` drives TCK, TMS and TDI, and reads TDO, on any available GPIO pins. It
+/// moves the Test Access Port (TAP) state machine through the reset, instruction
+/// register (IR) and data register (DR) scan sequences. Device-specific operations
+/// can be implemented on top of [`shift_ir`] and [`shift_dr`].
+///
+/// All methods expect the TAP to be in the Run-Test/Idle state, and return it to
+/// Run-Test/Idle afterwards. Call [`reset`] first to get the TAP into a known
+/// state.
+///
+/// The pins are passed in as [`IoPin`]s, which are reset to their original modes
+/// when `Jtag` goes out of scope, unless [`reset_on_drop`] was set to `false`.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::debugprobe::Jtag;
+/// use rppal::gpio::{Gpio, Mode};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gpio = Gpio::new()?;
+/// let mut jtag = Jtag::new(
+///     gpio.get(25).unwrap().into_io(Mode::Output),
+///     gpio.get(24).unwrap().into_io(Mode::Output),
+///     gpio.get(23).unwrap().into_io(Mode::Output),
+///     gpio.get(22).unwrap().into_io(Mode::Input),
+/// );
+///
+/// println!("IDCODE: {:#010x}", jtag.read_idcode());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`shift_ir`]: #method.shift_ir
+/// [`shift_dr`]: #method.shift_dr
+/// [`reset`]: #method.reset
+/// [`IoPin`]: ../gpio/struct.IoPin.html
+/// [`reset_on_drop`]: ../gpio/struct.IoPin.html#method.set_reset_on_drop
+#[derive(Debug)]
+pub struct Jtag {
+    tck: IoPin,
+    tms: IoPin,
+    tdi: IoPin,
+    tdo: IoPin,
+    clock: Clock,
+}
+
+impl Jtag {
+    /// Constructs a new `Jtag`.
+    ///
+    /// TCK, TMS and TDI are configured as outputs, and TDO as an input. The clock
+    /// frequency defaults to 1 MHz.
+    pub fn new(mut tck: IoPin, mut tms: IoPin, mut tdi: IoPin, mut tdo: IoPin) -> Jtag {
+        tck.set_low();
+        tck.set_mode(Mode::Output);
+        tms.set_high();
+        tms.set_mode(Mode::Output);
+        tdi.set_low();
+        tdi.set_mode(Mode::Output);
+        tdo.set_mode(Mode::Input);
+
+        Jtag {
+            tck,
+            tms,
+            tdi,
+            tdo,
+            clock: Clock::new(DEFAULT_FREQUENCY),
+        }
+    }
+
+    /// Consumes the `Jtag`, and returns the TCK, TMS, TDI and TDO pins.
+    pub fn into_pins(self) -> (IoPin, IoPin, IoPin, IoPin) {
+        (self.tck, self.tms, self.tdi, self.tdo)
+    }
+
+    /// Returns the clock frequency in herz (Hz).
+    pub fn frequency(&self) -> f64 {
+        self.clock.frequency()
+    }
+
+    /// Sets the clock frequency in herz (Hz).
+    ///
+    /// The actual frequency may be lower, depending on how fast the GPIO pins can be
+    /// toggled. Set `frequency` to 0.0 to toggle the pins as fast as possible.
+    pub fn set_frequency(&mut self, frequency: f64) {
+        self.clock = Clock::new(frequency);
+    }
+
+    /// Moves the TAP to the Test-Logic-Reset state by holding TMS high for 5 clock
+    /// cycles, and then to Run-Test/Idle.
+    ///
+    /// After a reset, the IDCODE instruction is selected on devices that support it.
+    pub fn reset(&mut self) {
+        for _ in 0..5 {
+            self.clock_bit(true, false);
+        }
+
+        self.clock_bit(false, false);
+    }
+
+    /// Sends the specified number of clock cycles in the Run-Test/Idle state.
+    pub fn idle(&mut self, cycles: u32) {
+        for _ in 0..cycles {
+            self.clock_bit(false, false);
+        }
+    }
+
+    /// Shifts up to 64 bits into the instruction register, least significant bit
+    /// first, and returns the bits that were shifted out.
+    ///
+    /// Nothing is shifted if `len` is 0.
+    pub fn shift_ir(&mut self, value: u64, len: u8) -> u64 {
+        if len == 0 {
+            return 0;
+        }
+
+        // Select-DR-Scan, Select-IR-Scan, Capture-IR, Shift-IR
+        self.clock_bit(true, false);
+        self.clock_bit(true, false);
+        self.clock_bit(false, false);
+        self.clock_bit(false, false);
+
+        self.shift(value, len)
+    }
+
+    /// Shifts up to 64 bits into the currently selected data register, least
+    /// significant bit first, and returns the bits that were shifted out.
+    ///
+    /// Nothing is shifted if `len` is 0.
+    pub fn shift_dr(&mut self, value: u64, len: u8) -> u64 {
+        if len == 0 {
+            return 0;
+        }
+
+        // Select-DR-Scan, Capture-DR, Shift-DR
+        self.clock_bit(true, false);
+        self.clock_bit(false, false);
+        self.clock_bit(false, false);
+
+        self.shift(value, len)
+    }
+
+    /// Resets the TAP, and returns the 32-bit IDCODE of the first device in the
+    /// scan chain.
+    ///
+    /// Devices that don't support the IDCODE instruction select their 1-bit BYPASS
+    /// register instead, in which case the least significant bit is 0.
+    pub fn read_idcode(&mut self) -> u32 {
+        self.reset();
+
+        self.shift_dr(0, 32) as u32
+    }
+
+    // Shifts len bits in the Shift-IR or Shift-DR state. TMS is set high on the
+    // last bit to move to Exit1, followed by Update and Run-Test/Idle.
+    fn shift(&mut self, value: u64, len: u8) -> u64 {
+        let len = len.min(64);
+        let mut captured = 0;
+
+        for bit in 0..len {
+            if self.clock_bit(bit == len - 1, (value >> bit) & 1 > 0) {
+                captured |= 1 << bit;
+            }
+        }
+
+        // Update-IR/DR, Run-Test/Idle
+        self.clock_bit(true, false);
+        self.clock_bit(false, false);
+
+        captured
+    }
+
+    // TMS and TDI are sampled by the target on the rising edge of TCK. TDO changes
+    // on the falling edge, so it's sampled right before the rising edge.
+    #[inline]
+    fn clock_bit(&mut self, tms: bool, tdi: bool) -> bool {
+        self.tck.set_low();
+        self.tms.write(if tms { Level::High } else { Level::Low });
+        self.tdi.write(if tdi { Level::High } else { Level::Low });
+        self.clock.wait();
+        let tdo = self.tdo.read() == Level::High;
+        self.tck.set_high();
+        self.clock.wait();
+
+        tdo
+    }
+}
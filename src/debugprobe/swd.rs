@@ -0,0 +1,432 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::debugprobe::{Clock, Error, Result};
+use crate::gpio::{IoPin, Level, Mode};
+
+// Sequence that switches a SWJ-DP from JTAG to SWD, sent LSB first
+const JTAG_TO_SWD: u64 = 0xe79e;
+
+const ACK_OK: u8 = 0b001;
+const ACK_WAIT: u8 = 0b010;
+const ACK_FAULT: u8 = 0b100;
+
+// Debug Port register addresses
+const DP_DPIDR: u8 = 0x00;
+const DP_ABORT: u8 = 0x00;
+const DP_CTRL_STAT: u8 = 0x04;
+const DP_SELECT: u8 = 0x08;
+const DP_RDBUFF: u8 = 0x0c;
+
+// Clear the STICKYORUN, STICKYCMP, STICKYERR and WDATAERR flags
+const ABORT_CLEAR_ERRORS: u32 = 0x1e;
+
+const CTRL_STAT_CDBGPWRUPREQ: u32 = 1 << 28;
+const CTRL_STAT_CDBGPWRUPACK: u32 = 1 << 29;
+const CTRL_STAT_CSYSPWRUPREQ: u32 = 1 << 30;
+const CTRL_STAT_CSYSPWRUPACK: u32 = 1 << 31;
+
+// MEM-AP register addresses
+const AP_CSW: u8 = 0x00;
+const AP_TAR: u8 = 0x04;
+const AP_DRW: u8 = 0x0c;
+
+// 32-bit transfers, no address increment, privileged debug access
+const CSW_WORD: u32 = 0x2300_0002;
+
+const POWER_UP_RETRIES: u32 = 100;
+const DEFAULT_WAIT_RETRIES: u32 = 100;
+const DEFAULT_FREQUENCY: f64 = 1_000_000.0;
+
+/// Bit-banged Serial Wire Debug (SWD) probe.
+///
+/// SWD is the two-wire debug interface found on ARM Cortex-M microcontrollers.
+/// `Swd` drives SWCLK and SWDIO on any available GPIO pins, and implements the
+/// line reset and JTAG-to-SWD switching sequences, Debug Port (DP) and Access Port
+/// (AP) register transfers, and word-sized memory access through a MEM-AP. Halting
+/// the core and programming flash can be implemented on top of the memory access
+/// methods, by writing to the target's debug and flash controller registers.
+///
+/// SWDIO is switched between output and input mode for every turnaround, and
+/// should be connected through a 100 Ω series resistor. Most targets include a
+/// pull-up resistor on SWDIO.
+///
+/// Transfers that are answered with a WAIT response are automatically retried.
+/// After a FAULT response, the sticky error flags need to be cleared with
+/// [`clear_errors`] before the DP accepts new AP transfers.
+///
+/// Both pins are passed in as [`IoPin`]s. When `Swd` goes out of scope, they're
+/// reset to their original modes, unless [`reset_on_drop`] was set to `false`.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::debugprobe::Swd;
+/// use rppal::gpio::{Gpio, Mode};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gpio = Gpio::new()?;
+/// let mut swd = Swd::new(
+///     gpio.get(25).unwrap().into_io(Mode::Output),
+///     gpio.get(24).unwrap().into_io(Mode::Output),
+/// );
+///
+/// println!("DPIDR: {:#010x}", swd.connect()?);
+///
+/// // Halt the core by writing to the Debug Halting Control and Status Register
+/// swd.write_memory(0, 0xe000_edf0, 0xa05f_0003)?;
+/// println!("Reset vector: {:#010x}", swd.read_memory(0, 0x0000_0004)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`clear_errors`]: #method.clear_errors
+/// [`IoPin`]: ../gpio/struct.IoPin.html
+/// [`reset_on_drop`]: ../gpio/struct.IoPin.html#method.set_reset_on_drop
+#[derive(Debug)]
+pub struct Swd {
+    swclk: IoPin,
+    swdio: IoPin,
+    clock: Clock,
+    wait_retries: u32,
+    select: Option<u32>,
+}
+
+impl Swd {
+    /// Constructs a new `Swd`.
+    ///
+    /// Both pins are configured as outputs. The clock frequency defaults to 1 MHz.
+    pub fn new(mut swclk: IoPin, mut swdio: IoPin) -> Swd {
+        swclk.set_low();
+        swclk.set_mode(Mode::Output);
+        swdio.set_high();
+        swdio.set_mode(Mode::Output);
+
+        Swd {
+            swclk,
+            swdio,
+            clock: Clock::new(DEFAULT_FREQUENCY),
+            wait_retries: DEFAULT_WAIT_RETRIES,
+            select: None,
+        }
+    }
+
+    /// Consumes the `Swd`, and returns the SWCLK and SWDIO pins.
+    pub fn into_pins(self) -> (IoPin, IoPin) {
+        (self.swclk, self.swdio)
+    }
+
+    /// Returns the clock frequency in herz (Hz).
+    pub fn frequency(&self) -> f64 {
+        self.clock.frequency()
+    }
+
+    /// Sets the clock frequency in herz (Hz).
+    ///
+    /// The actual frequency may be lower, depending on how fast the GPIO pins can be
+    /// toggled. Set `frequency` to 0.0 to toggle the pins as fast as possible.
+    pub fn set_frequency(&mut self, frequency: f64) {
+        self.clock = Clock::new(frequency);
+    }
+
+    /// Returns the maximum number of times a transfer is retried after a WAIT
+    /// response.
+    pub fn wait_retries(&self) -> u32 {
+        self.wait_retries
+    }
+
+    /// Sets the maximum number of times a transfer is retried after a WAIT
+    /// response.
+    ///
+    /// By default, `wait_retries` is set to 100.
+    pub fn set_wait_retries(&mut self, retries: u32) {
+        self.wait_retries = retries;
+    }
+
+    /// Switches the target's debug port to SWD, reads its identification
+    /// register (DPIDR), clears any sticky errors, and powers up the debug and
+    /// system domains.
+    ///
+    /// Returns the value of DPIDR. Returns [`Error::NoResponse`] if no target
+    /// responded, or [`Error::Timeout`] if the target didn't acknowledge the
+    /// power-up request.
+    ///
+    /// [`Error::NoResponse`]: enum.Error.html#variant.NoResponse
+    /// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+    pub fn connect(&mut self) -> Result<u32> {
+        self.line_reset();
+        self.write_bits(JTAG_TO_SWD, 16);
+        self.line_reset();
+        self.idle(2);
+
+        // DPIDR must be read directly after the line reset
+        let dpidr = self.read_dp(DP_DPIDR)?;
+
+        self.clear_errors()?;
+        self.select = None;
+
+        self.write_dp(
+            DP_CTRL_STAT,
+            CTRL_STAT_CDBGPWRUPREQ | CTRL_STAT_CSYSPWRUPREQ,
+        )?;
+
+        for _ in 0..POWER_UP_RETRIES {
+            let ack = CTRL_STAT_CDBGPWRUPACK | CTRL_STAT_CSYSPWRUPACK;
+            if self.read_dp(DP_CTRL_STAT)? & ack == ack {
+                return Ok(dpidr);
+            }
+
+            sleep(Duration::from_millis(1));
+        }
+
+        Err(Error::Timeout)
+    }
+
+    /// Clears the sticky error flags by writing to the DP's ABORT register.
+    pub fn clear_errors(&mut self) -> Result<()> {
+        self.write_dp(DP_ABORT, ABORT_CLEAR_ERRORS)
+    }
+
+    /// Reads a Debug Port register.
+    ///
+    /// `address` selects one of the registers at 0x0, 0x4, 0x8 or 0xC. Bits 0-1 are
+    /// ignored.
+    pub fn read_dp(&mut self, address: u8) -> Result<u32> {
+        self.transfer(false, true, address, 0)
+    }
+
+    /// Writes to a Debug Port register.
+    ///
+    /// `address` selects one of the registers at 0x0, 0x4, 0x8 or 0xC. Bits 0-1 are
+    /// ignored.
+    pub fn write_dp(&mut self, address: u8, value: u32) -> Result<()> {
+        self.transfer(false, false, address, value)?;
+
+        if address & 0x0c == DP_SELECT {
+            self.select = Some(value);
+        }
+
+        Ok(())
+    }
+
+    /// Reads a register from the specified Access Port.
+    ///
+    /// The DP's SELECT register is updated if needed. Because AP reads are posted,
+    /// the result is retrieved from the DP's RDBUFF register.
+    pub fn read_ap(&mut self, ap: u8, address: u8) -> Result<u32> {
+        self.select_ap(ap, address)?;
+
+        self.transfer(true, true, address, 0)?;
+        self.read_dp(DP_RDBUFF)
+    }
+
+    /// Writes to a register of the specified Access Port.
+    ///
+    /// The DP's SELECT register is updated if needed.
+    pub fn write_ap(&mut self, ap: u8, address: u8, value: u32) -> Result<()> {
+        self.select_ap(ap, address)?;
+
+        self.transfer(true, false, address, value)?;
+
+        Ok(())
+    }
+
+    /// Reads a 32-bit word from the target's memory through the specified MEM-AP.
+    ///
+    /// `address` needs to be word-aligned.
+    pub fn read_memory(&mut self, ap: u8, address: u32) -> Result<u32> {
+        self.write_ap(ap, AP_CSW, CSW_WORD)?;
+        self.write_ap(ap, AP_TAR, address)?;
+
+        self.read_ap(ap, AP_DRW)
+    }
+
+    /// Writes a 32-bit word to the target's memory through the specified MEM-AP.
+    ///
+    /// `address` needs to be word-aligned.
+    pub fn write_memory(&mut self, ap: u8, address: u32, value: u32) -> Result<()> {
+        self.write_ap(ap, AP_CSW, CSW_WORD)?;
+        self.write_ap(ap, AP_TAR, address)?;
+        self.write_ap(ap, AP_DRW, value)?;
+
+        // Make sure the write has completed
+        self.read_dp(DP_RDBUFF)?;
+
+        Ok(())
+    }
+
+    /// Sends a line reset, which consists of at least 50 clock cycles with SWDIO
+    /// high.
+    pub fn line_reset(&mut self) {
+        self.write_bits(u64::MAX, 56);
+    }
+
+    /// Sends the specified number of idle cycles, with SWDIO low.
+    pub fn idle(&mut self, cycles: u32) {
+        for _ in 0..cycles {
+            self.write_bit(false);
+        }
+    }
+
+    /// Writes up to 64 bits, least significant bit first.
+    pub fn write_bits(&mut self, value: u64, count: u8) {
+        for bit in 0..count.min(64) {
+            self.write_bit((value >> bit) & 1 > 0);
+        }
+    }
+
+    /// Reads up to 64 bits, least significant bit first.
+    ///
+    /// SWDIO is switched to input mode for the duration of the read, without a
+    /// turnaround cycle.
+    pub fn read_bits(&mut self, count: u8) -> u64 {
+        self.swdio.set_mode(Mode::Input);
+
+        let value = (0..count.min(64)).fold(0, |value, bit| {
+            if self.read_bit() {
+                value | (1 << bit)
+            } else {
+                value
+            }
+        });
+
+        self.swdio.set_mode(Mode::Output);
+
+        value
+    }
+
+    fn select_ap(&mut self, ap: u8, address: u8) -> Result<()> {
+        let select = (u32::from(ap) << 24) | u32::from(address & 0xf0);
+
+        if self.select != Some(select) {
+            self.write_dp(DP_SELECT, select)?;
+        }
+
+        Ok(())
+    }
+
+    // Sends a request packet, and handles the ACK and data phases. WAIT
+    // responses are retried up to wait_retries times.
+    fn transfer(&mut self, ap: bool, read: bool, address: u8, value: u32) -> Result<u32> {
+        let a2 = address & 0x04 > 0;
+        let a3 = address & 0x08 > 0;
+        let parity = ap ^ read ^ a2 ^ a3;
+
+        // Start, APnDP, RnW, A[2:3], parity, stop, park
+        let request = 1
+            | (u64::from(ap) << 1)
+            | (u64::from(read) << 2)
+            | (u64::from(a2) << 3)
+            | (u64::from(a3) << 4)
+            | (u64::from(parity) << 5)
+            | (1 << 7);
+
+        let mut retries = 0;
+        loop {
+            self.write_bits(request, 8);
+
+            // Turnaround, after which the target drives SWDIO
+            self.swdio.set_mode(Mode::Input);
+            self.read_bit();
+
+            let ack = (0..3).fold(0, |ack, bit| {
+                if self.read_bit() {
+                    ack | (1 << bit)
+                } else {
+                    ack
+                }
+            });
+
+            if ack != ACK_OK {
+                // Turnaround, after which the host drives SWDIO again
+                self.read_bit();
+                self.swdio.set_mode(Mode::Output);
+                self.idle(2);
+
+                match ack {
+                    ACK_WAIT if retries < self.wait_retries => {
+                        retries += 1;
+                        continue;
+                    }
+                    ACK_WAIT => return Err(Error::Wait),
+                    ACK_FAULT => return Err(Error::Fault),
+                    0b111 => return Err(Error::NoResponse),
+                    ack => return Err(Error::Protocol(ack)),
+                }
+            }
+
+            if read {
+                let mut data = 0u32;
+                for bit in 0..32 {
+                    if self.read_bit() {
+                        data |= 1 << bit;
+                    }
+                }
+
+                let parity = self.read_bit();
+
+                self.read_bit();
+                self.swdio.set_mode(Mode::Output);
+                self.idle(8);
+
+                if parity != (data.count_ones() % 2 == 1) {
+                    return Err(Error::Parity);
+                }
+
+                return Ok(data);
+            }
+
+            self.read_bit();
+            self.swdio.set_mode(Mode::Output);
+
+            self.write_bits(u64::from(value), 32);
+            self.write_bit(value.count_ones() % 2 == 1);
+            self.idle(8);
+
+            return Ok(0);
+        }
+    }
+
+    // The target samples SWDIO on the rising edge of SWCLK
+    #[inline]
+    fn write_bit(&mut self, bit: bool) {
+        self.swclk.set_low();
+        self.swdio.write(if bit { Level::High } else { Level::Low });
+        self.clock.wait();
+        self.swclk.set_high();
+        self.clock.wait();
+    }
+
+    // The target changes SWDIO on the rising edge of SWCLK, so it's sampled
+    // before the next rising edge
+    #[inline]
+    fn read_bit(&mut self) -> bool {
+        self.swclk.set_low();
+        self.clock.wait();
+        let bit = self.swdio.read() == Level::High;
+        self.swclk.set_high();
+        self.clock.wait();
+
+        bit
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Precision busy-wait delays.
+//!
+//! [`delay_us`] and [`delay_ns`] block the current thread for the specified
+//! duration, with much less jitter than `thread::sleep`. This is needed when
+//! bit-banging protocols that rely on short, accurately timed pulses, such as
+//! DHT sensors, 1-Wire devices or WS2812 LEDs.
+//!
+//! Delays of 100 µs or less are handled by spinning in a busy-wait loop. Longer
+//! delays sleep using `clock_nanosleep` until the final ~100 µs, which frees up
+//! the CPU core, and busy-wait for the remaining time, because the Linux
+//! scheduler doesn't reliably wake up a sleeping thread with that level of
+//! accuracy.
+//!
+//! The first delay calibrates the overhead of reading the monotonic clock
+//! against the SoC's free-running [`SystemTimer`], which lets the busy-wait loop
+//! stop at the right moment, rather than one clock read too late. When the
+//! system timer is accessible, [`delay_us`] also uses it directly for the final
+//! busy-wait. Accessing the system timer requires superuser privileges. Without
+//! them, both the calibration and the busy-wait fall back to `CLOCK_MONOTONIC`.
+//!
+//! Delays can still take longer than requested when the thread is preempted.
+//! Running the application with real-time scheduling improves accuracy.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::delay::{delay_ns, delay_us};
+//!
+//! delay_us(18);
+//! delay_ns(500);
+//! ```
+//!
+//! [`delay_us`]: fn.delay_us.html
+//! [`delay_ns`]: fn.delay_ns.html
+//! [`SystemTimer`]: ../system/struct.SystemTimer.html
+
+use std::convert::TryFrom;
+use std::hint;
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use libc;
+
+use crate::system::SystemTimer;
+
+// Busy-wait for the final part of every delay, since the scheduler doesn't
+// reliably wake up a sleeping thread with sub-100 µs accuracy.
+const BUSYWAIT_THRESHOLD: i64 = 100_000;
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+// Number of clock reads used to calibrate the clock read overhead
+const CALIBRATION_ITERATIONS: u64 = 10_000;
+
+#[derive(Debug)]
+struct Calibration {
+    // System timer, if we have permission to access /dev/mem
+    system_timer: Option<SystemTimer>,
+    // Average duration of a single get_time_ns() call in nanoseconds
+    clock_overhead_ns: i64,
+}
+
+impl Calibration {
+    fn new() -> Calibration {
+        let system_timer = SystemTimer::new().ok();
+
+        let clock_overhead_ns = if let Some(ref system_timer) = system_timer {
+            let start = system_timer.ticks();
+            for _ in 0..CALIBRATION_ITERATIONS {
+                hint::black_box(get_time_ns());
+            }
+            let elapsed_us = system_timer.ticks() - start;
+
+            (elapsed_us * 1000 / CALIBRATION_ITERATIONS) as i64
+        } else {
+            let start_ns = get_time_ns();
+            for _ in 0..CALIBRATION_ITERATIONS {
+                hint::black_box(get_time_ns());
+            }
+
+            (get_time_ns() - start_ns) / CALIBRATION_ITERATIONS as i64
+        };
+
+        Calibration {
+            system_timer,
+            clock_overhead_ns,
+        }
+    }
+}
+
+lazy_static! {
+    static ref CALIBRATION: Calibration = Calibration::new();
+}
+
+// Sleeps using clock_nanosleep() until BUSYWAIT_THRESHOLD before deadline_ns,
+// if there's enough time remaining.
+fn sleep_until_ns(deadline_ns: i64) {
+    let sleep_until_ns = deadline_ns - BUSYWAIT_THRESHOLD;
+
+    if sleep_until_ns <= get_time_ns() {
+        return;
+    }
+
+    // The conversions are needed on 32-bit targets, where time_t and c_long are i32.
+    let ts = libc::timespec {
+        tv_sec: (sleep_until_ns / NANOS_PER_SEC) as libc::time_t,
+        tv_nsec: (sleep_until_ns % NANOS_PER_SEC) as libc::c_long,
+    };
+
+    // clock_nanosleep() returns EINTR when it's interrupted by a signal
+    // handler. Since we're using an absolute deadline, we can simply retry.
+    while unsafe {
+        libc::clock_nanosleep(
+            libc::CLOCK_MONOTONIC,
+            libc::TIMER_ABSTIME,
+            &ts,
+            std::ptr::null_mut(),
+        )
+    } == libc::EINTR
+    {}
+}
+
+// Blocks until get_time_ns() reaches deadline_ns.
+pub(crate) fn delay_until_ns(deadline_ns: i64) {
+    sleep_until_ns(deadline_ns);
+
+    // Stop as soon as the next clock read would end past the deadline
+    let deadline_ns = deadline_ns - CALIBRATION.clock_overhead_ns;
+    while get_time_ns() < deadline_ns {
+        hint::spin_loop();
+    }
+}
+
+/// Blocks the current thread for at least `ns` nanoseconds (ns).
+///
+/// The accuracy of very short delays is limited by the time it takes to read
+/// the monotonic clock, which is usually somewhere between 50 ns and 1 µs,
+/// depending on the Raspberry Pi model.
+pub fn delay_ns(ns: u64) {
+    let start_ns = get_time_ns();
+    let ns = i64::try_from(ns).unwrap_or(i64::MAX);
+
+    delay_until_ns(start_ns.saturating_add(ns));
+}
+
+// Blocks until the system timer's counter reaches ticks.
+pub(crate) fn delay_until_ticks(system_timer: &SystemTimer, ticks: u64) {
+    let remaining_us = ticks.saturating_sub(system_timer.ticks());
+    let remaining_ns = i64::try_from(remaining_us.saturating_mul(1000)).unwrap_or(i64::MAX);
+
+    sleep_until_ns(get_time_ns().saturating_add(remaining_ns));
+    while system_timer.ticks() < ticks {
+        hint::spin_loop();
+    }
+}
+
+/// Blocks the current thread for at least `us` microseconds (µs).
+///
+/// When the system timer is accessible, the final busy-wait spins on the
+/// system timer's counter instead of the monotonic clock.
+pub fn delay_us(us: u64) {
+    if let Some(ref system_timer) = CALIBRATION.system_timer {
+        // Add a tick, because we could be near the end of the current one
+        let deadline = system_timer.ticks().saturating_add(us).saturating_add(1);
+
+        delay_until_ticks(system_timer, deadline);
+    } else {
+        delay_ns(us.saturating_mul(1000));
+    }
+}
+
+#[inline(always)]
+pub(crate) fn duration_to_ns(duration: Duration) -> i64 {
+    (duration.as_secs() as i64)
+        .saturating_mul(1_000_000_000)
+        .saturating_add(i64::from(duration.subsec_nanos()))
+}
+
+// Required because Instant::now() is too expensive for our busy wait loops.
+// The conversions are needed on 32-bit targets, where time_t and c_long are i32.
+#[inline(always)]
+#[allow(clippy::useless_conversion)]
+pub(crate) fn get_time_ns() -> i64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts);
+    }
+
+    (i64::from(ts.tv_sec) * 1_000_000_000) + i64::from(ts.tv_nsec)
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Drivers for displays.
+//!
+//! * [`Ssd1306`] supports 128x64 and 128x32 monochrome OLED displays based on the
+//!   SSD1306 or SH1106 controller, connected through I2C or SPI.
+//! * [`Tft`] supports 16-bit color TFT displays based on the ST7735R or ILI9341
+//!   controller, connected through SPI.
+//!
+//! The drivers keep a framebuffer in memory. Drawing operations only change the
+//! framebuffer, after which `flush` sends it to the display.
+//!
+//! ## embedded-graphics
+//!
+//! When the `graphics` feature is enabled, the drivers implement the `DrawTarget`
+//! trait from [`embedded-graphics-core`], which allows text, shapes and images to
+//! be drawn with the [`embedded-graphics`] crate.
+//!
+//! [`Ssd1306`]: struct.Ssd1306.html
+//! [`Tft`]: struct.Tft.html
+//! [`embedded-graphics-core`]: https://crates.io/crates/embedded-graphics-core
+//! [`embedded-graphics`]: https://crates.io/crates/embedded-graphics
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::gpio;
+use crate::i2c;
+use crate::spi;
+
+#[cfg(feature = "graphics")]
+mod graphics;
+mod interface;
+mod ssd1306;
+mod tft;
+
+pub use self::ssd1306::{Controller, Size, Ssd1306};
+pub use self::tft::{Rotation, Tft, TftModel};
+
+quick_error! {
+/// Errors that can occur when accessing a display.
+    #[derive(Debug)]
+    pub enum Error {
+/// I2C error.
+        I2c(err: i2c::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// SPI error.
+        Spi(err: spi::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+    }
+}
+
+/// Result type returned from methods that can have `display::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// Converts an 8-bit per channel RGB color to RGB565.
+///
+/// The lower bits of each channel are discarded.
+pub fn rgb565(red: u8, green: u8, blue: u8) -> u16 {
+    (u16::from(red & 0xf8) << 8) | (u16::from(green & 0xfc) << 3) | (u16::from(blue) >> 3)
+}
@@ -0,0 +1,64 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::display::Result;
+use crate::gpio::OutputPin;
+use crate::spi::Spi;
+
+// 4-wire SPI, where the D/C pin selects whether the transferred bytes contain
+// commands (low) or data (high)
+#[derive(Debug)]
+pub(crate) struct SpiInterface {
+    spi: Spi,
+    dc: OutputPin,
+}
+
+impl SpiInterface {
+    pub(crate) fn new(spi: Spi, dc: OutputPin) -> SpiInterface {
+        SpiInterface { spi, dc }
+    }
+
+    pub(crate) fn command(&mut self, command: &[u8]) -> Result<()> {
+        self.dc.set_low();
+        self.spi.write(command)?;
+
+        Ok(())
+    }
+
+    pub(crate) fn data(&mut self, data: &[u8]) -> Result<()> {
+        self.dc.set_high();
+        self.spi.write(data)?;
+
+        Ok(())
+    }
+}
+
+// Resets the display controller by pulling its active-low RST pin low
+pub(crate) fn hardware_reset(rst: &mut OutputPin) {
+    rst.set_high();
+    sleep(Duration::from_millis(1));
+    rst.set_low();
+    sleep(Duration::from_millis(10));
+    rst.set_high();
+    sleep(Duration::from_millis(120));
+}
@@ -0,0 +1,415 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::display::interface::{self, SpiInterface};
+use crate::display::Result;
+use crate::gpio::OutputPin;
+use crate::i2c::I2c;
+use crate::spi::Spi;
+
+// I2C control bytes, with the Co bit cleared, so all following bytes are
+// interpreted as either commands or data
+const I2C_COMMAND: u8 = 0x00;
+const I2C_DATA: u8 = 0x40;
+// Maximum number of bytes sent in a single I2C write, excluding the control byte
+const I2C_CHUNK_SIZE: usize = 32;
+
+const CMD_DISPLAY_OFF: u8 = 0xae;
+const CMD_DISPLAY_ON: u8 = 0xaf;
+const CMD_SET_CLOCK_DIV: u8 = 0xd5;
+const CMD_SET_MULTIPLEX: u8 = 0xa8;
+const CMD_SET_DISPLAY_OFFSET: u8 = 0xd3;
+const CMD_SET_START_LINE: u8 = 0x40;
+const CMD_CHARGE_PUMP: u8 = 0x8d;
+const CMD_SH1106_DC_DC: u8 = 0xad;
+const CMD_MEMORY_MODE: u8 = 0x20;
+const CMD_SEG_REMAP: u8 = 0xa0;
+const CMD_COM_SCAN_INC: u8 = 0xc0;
+const CMD_COM_SCAN_DEC: u8 = 0xc8;
+const CMD_SET_COM_PINS: u8 = 0xda;
+const CMD_SET_CONTRAST: u8 = 0x81;
+const CMD_SET_PRECHARGE: u8 = 0xd9;
+const CMD_SET_VCOM_DESELECT: u8 = 0xdb;
+const CMD_DISPLAY_RAM: u8 = 0xa4;
+const CMD_NORMAL: u8 = 0xa6;
+const CMD_INVERT: u8 = 0xa7;
+const CMD_DEACTIVATE_SCROLL: u8 = 0x2e;
+const CMD_SET_PAGE: u8 = 0xb0;
+const CMD_SET_COLUMN_LOW: u8 = 0x00;
+const CMD_SET_COLUMN_HIGH: u8 = 0x10;
+
+/// Display controllers.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Controller {
+    /// Solomon Systech SSD1306.
+    Ssd1306,
+    /// Sino Wealth SH1106.
+    ///
+    /// The SH1106 has 132 columns of display RAM, of which the center 128 columns
+    /// are visible.
+    Sh1106,
+}
+
+impl Controller {
+    fn column_offset(self) -> u8 {
+        match self {
+            Controller::Ssd1306 => 0,
+            Controller::Sh1106 => 2,
+        }
+    }
+}
+
+/// Display sizes in pixels.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Size {
+    /// 128x64 pixels.
+    Size128x64,
+    /// 128x32 pixels.
+    Size128x32,
+}
+
+impl Size {
+    /// Returns the width in pixels.
+    pub fn width(self) -> u32 {
+        128
+    }
+
+    /// Returns the height in pixels.
+    pub fn height(self) -> u32 {
+        match self {
+            Size::Size128x64 => 64,
+            Size::Size128x32 => 32,
+        }
+    }
+
+    fn com_pins(self) -> u8 {
+        match self {
+            Size::Size128x64 => 0x12,
+            Size::Size128x32 => 0x02,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Interface {
+    I2c(I2c),
+    // OutputPin is fairly large, so keep it out of the enum
+    Spi(Box<SpiInterface>),
+}
+
+impl Interface {
+    fn command(&mut self, command: &[u8]) -> Result<()> {
+        match self {
+            Interface::I2c(i2c) => i2c_write(i2c, I2C_COMMAND, command),
+            Interface::Spi(spi) => spi.command(command),
+        }
+    }
+
+    fn data(&mut self, data: &[u8]) -> Result<()> {
+        match self {
+            Interface::I2c(i2c) => i2c_write(i2c, I2C_DATA, data),
+            Interface::Spi(spi) => spi.data(data),
+        }
+    }
+}
+
+fn i2c_write(i2c: &mut I2c, control: u8, buffer: &[u8]) -> Result<()> {
+    let mut frame = [0u8; I2C_CHUNK_SIZE + 1];
+    frame[0] = control;
+
+    for chunk in buffer.chunks(I2C_CHUNK_SIZE) {
+        frame[1..=chunk.len()].copy_from_slice(chunk);
+        i2c.write(&frame[..=chunk.len()])?;
+    }
+
+    Ok(())
+}
+
+/// SSD1306 and SH1106 monochrome OLED display driver.
+///
+/// `Ssd1306` keeps a framebuffer with one bit per pixel. [`set_pixel`], [`clear`]
+/// and the optional `embedded-graphics` integration update the framebuffer, after
+/// which [`flush`] sends it to the display. The origin (0, 0) is located in the
+/// top left corner.
+///
+/// Most I2C modules use slave address 0x3C, or 0x3D if the SA0 pin is pulled
+/// high. SPI modules need an additional GPIO pin connected to D/C, and optionally
+/// a pin connected to RES. The maximum SPI clock speed is 10 MHz. The display
+/// uses SPI [`Mode0`].
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::display::{Controller, Size, Ssd1306};
+/// use rppal::i2c::I2c;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut display = Ssd1306::with_i2c(I2c::new()?, 0x3c, Controller::Ssd1306, Size::Size128x64)?;
+///
+/// for x in 0..128 {
+///     display.set_pixel(x, 32, true);
+/// }
+///
+/// display.flush()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`set_pixel`]: #method.set_pixel
+/// [`clear`]: #method.clear
+/// [`flush`]: #method.flush
+/// [`Mode0`]: ../spi/enum.Mode.html#variant.Mode0
+#[derive(Debug)]
+pub struct Ssd1306 {
+    interface: Interface,
+    controller: Controller,
+    size: Size,
+    buffer: Vec<u8>,
+}
+
+impl Ssd1306 {
+    /// Constructs a new `Ssd1306` for the display at the specified I2C slave address,
+    /// and initializes the display.
+    pub fn with_i2c(
+        mut i2c: I2c,
+        address: u16,
+        controller: Controller,
+        size: Size,
+    ) -> Result<Ssd1306> {
+        i2c.set_slave_address(address)?;
+
+        Ssd1306::new(Interface::I2c(i2c), controller, size)
+    }
+
+    /// Constructs a new `Ssd1306` for a display connected through SPI, and
+    /// initializes the display.
+    ///
+    /// `dc` is connected to the display's D/C pin. If `rst` is specified, the
+    /// display is reset through its RES pin first.
+    pub fn with_spi(
+        spi: Spi,
+        dc: OutputPin,
+        rst: Option<OutputPin>,
+        controller: Controller,
+        size: Size,
+    ) -> Result<Ssd1306> {
+        if let Some(mut rst) = rst {
+            interface::hardware_reset(&mut rst);
+
+            // Keep RES high after rst goes out of scope
+            rst.set_reset_on_drop(false);
+        }
+
+        Ssd1306::new(
+            Interface::Spi(Box::new(SpiInterface::new(spi, dc))),
+            controller,
+            size,
+        )
+    }
+
+    fn new(interface: Interface, controller: Controller, size: Size) -> Result<Ssd1306> {
+        let mut ssd1306 = Ssd1306 {
+            interface,
+            controller,
+            size,
+            buffer: vec![0; (size.width() * size.height() / 8) as usize],
+        };
+
+        ssd1306.init()?;
+        ssd1306.flush()?;
+        ssd1306.interface.command(&[CMD_DISPLAY_ON])?;
+
+        Ok(ssd1306)
+    }
+
+    fn init(&mut self) -> Result<()> {
+        self.interface.command(&[
+            CMD_DISPLAY_OFF,
+            CMD_SET_CLOCK_DIV,
+            0x80,
+            CMD_SET_MULTIPLEX,
+            (self.size.height() - 1) as u8,
+            CMD_SET_DISPLAY_OFFSET,
+            0x00,
+            CMD_SET_START_LINE,
+        ])?;
+
+        match self.controller {
+            // Enable the internal charge pump, and use page addressing, which
+            // is the only mode supported by the SH1106
+            Controller::Ssd1306 => {
+                self.interface
+                    .command(&[CMD_CHARGE_PUMP, 0x14, CMD_MEMORY_MODE, 0x02])?
+            }
+            // Enable the internal DC-DC converter
+            Controller::Sh1106 => self.interface.command(&[CMD_SH1106_DC_DC, 0x8b])?,
+        }
+
+        self.interface.command(&[
+            CMD_SEG_REMAP | 0x01,
+            CMD_COM_SCAN_DEC,
+            CMD_SET_COM_PINS,
+            self.size.com_pins(),
+            CMD_SET_CONTRAST,
+            0xcf,
+            CMD_SET_PRECHARGE,
+            0xf1,
+            CMD_SET_VCOM_DESELECT,
+            0x40,
+            CMD_DISPLAY_RAM,
+            CMD_NORMAL,
+            CMD_DEACTIVATE_SCROLL,
+        ])
+    }
+
+    /// Returns the display controller.
+    pub fn controller(&self) -> Controller {
+        self.controller
+    }
+
+    /// Returns the display size.
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the width in pixels.
+    pub fn width(&self) -> u32 {
+        self.size.width()
+    }
+
+    /// Returns the height in pixels.
+    pub fn height(&self) -> u32 {
+        self.size.height()
+    }
+
+    /// Turns the pixel at the specified location on or off in the framebuffer.
+    ///
+    /// Locations outside of the display area are ignored.
+    pub fn set_pixel(&mut self, x: u32, y: u32, on: bool) {
+        if x >= self.width() || y >= self.height() {
+            return;
+        }
+
+        let index = ((y / 8) * self.width() + x) as usize;
+        let mask = 1 << (y % 8);
+
+        if on {
+            self.buffer[index] |= mask;
+        } else {
+            self.buffer[index] &= !mask;
+        }
+    }
+
+    /// Returns `true` if the pixel at the specified location is turned on in
+    /// the framebuffer.
+    ///
+    /// Returns `false` for locations outside of the display area.
+    pub fn pixel(&self, x: u32, y: u32) -> bool {
+        if x >= self.width() || y >= self.height() {
+            return false;
+        }
+
+        self.buffer[((y / 8) * self.width() + x) as usize] & (1 << (y % 8)) != 0
+    }
+
+    /// Turns all pixels on or off in the framebuffer.
+    pub fn fill(&mut self, on: bool) {
+        let value = if on { 0xff } else { 0x00 };
+
+        for byte in &mut self.buffer {
+            *byte = value;
+        }
+    }
+
+    /// Turns all pixels off in the framebuffer.
+    pub fn clear(&mut self) {
+        self.fill(false);
+    }
+
+    /// Returns a reference to the framebuffer.
+    ///
+    /// The framebuffer is organized in pages of 8 rows. Each byte contains a
+    /// vertical column of 8 pixels, with the least significant bit at the top.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Returns a mutable reference to the framebuffer.
+    ///
+    /// More information on the layout can be found at [`buffer`].
+    ///
+    /// [`buffer`]: #method.buffer
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+
+    /// Sends the framebuffer to the display.
+    pub fn flush(&mut self) -> Result<()> {
+        let width = self.width() as usize;
+        let column = self.controller.column_offset();
+
+        for page in 0..(self.height() / 8) as usize {
+            self.interface.command(&[
+                CMD_SET_PAGE | page as u8,
+                CMD_SET_COLUMN_LOW | (column & 0x0f),
+                CMD_SET_COLUMN_HIGH | (column >> 4),
+            ])?;
+
+            self.interface
+                .data(&self.buffer[page * width..(page + 1) * width])?;
+        }
+
+        Ok(())
+    }
+
+    /// Turns the display on or off.
+    ///
+    /// The display RAM is kept while the display is turned off.
+    pub fn set_display_on(&mut self, on: bool) -> Result<()> {
+        self.interface
+            .command(&[if on { CMD_DISPLAY_ON } else { CMD_DISPLAY_OFF }])
+    }
+
+    /// Sets the contrast (0-255).
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<()> {
+        self.interface.command(&[CMD_SET_CONTRAST, contrast])
+    }
+
+    /// Inverts the display, which turns on pixels that are off in the framebuffer,
+    /// and vice versa.
+    pub fn set_inverted(&mut self, inverted: bool) -> Result<()> {
+        self.interface
+            .command(&[if inverted { CMD_INVERT } else { CMD_NORMAL }])
+    }
+
+    /// Rotates the display by 180 degrees.
+    ///
+    /// Only affects data written by the next [`flush`].
+    ///
+    /// [`flush`]: #method.flush
+    pub fn set_flipped(&mut self, flipped: bool) -> Result<()> {
+        if flipped {
+            self.interface.command(&[CMD_SEG_REMAP, CMD_COM_SCAN_INC])
+        } else {
+            self.interface
+                .command(&[CMD_SEG_REMAP | 0x01, CMD_COM_SCAN_DEC])
+        }
+    }
+}
@@ -0,0 +1,431 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::display::interface::{self, SpiInterface};
+use crate::display::Result;
+use crate::gpio::OutputPin;
+use crate::spi::Spi;
+
+const CMD_SWRESET: u8 = 0x01;
+const CMD_SLPOUT: u8 = 0x11;
+const CMD_NORON: u8 = 0x13;
+const CMD_INVOFF: u8 = 0x20;
+const CMD_INVON: u8 = 0x21;
+const CMD_DISPOFF: u8 = 0x28;
+const CMD_DISPON: u8 = 0x29;
+const CMD_CASET: u8 = 0x2a;
+const CMD_RASET: u8 = 0x2b;
+const CMD_RAMWR: u8 = 0x2c;
+const CMD_MADCTL: u8 = 0x36;
+const CMD_COLMOD: u8 = 0x3a;
+
+const MADCTL_MY: u8 = 0x80;
+const MADCTL_MX: u8 = 0x40;
+const MADCTL_MV: u8 = 0x20;
+const MADCTL_BGR: u8 = 0x08;
+
+// 16 bits per pixel (RGB565)
+const COLMOD_16BIT: u8 = 0x55;
+
+// Controller-specific initialization sequences, consisting of a command, its
+// parameters, and the delay in milliseconds after the command
+const ST7735R_INIT: &[(u8, &[u8], u64)] = &[
+    (0xb1, &[0x01, 0x2c, 0x2d], 0),
+    (0xb2, &[0x01, 0x2c, 0x2d], 0),
+    (0xb3, &[0x01, 0x2c, 0x2d, 0x01, 0x2c, 0x2d], 0),
+    (0xb4, &[0x07], 0),
+    (0xc0, &[0xa2, 0x02, 0x84], 0),
+    (0xc1, &[0xc5], 0),
+    (0xc2, &[0x0a, 0x00], 0),
+    (0xc3, &[0x8a, 0x2a], 0),
+    (0xc4, &[0x8a, 0xee], 0),
+    (0xc5, &[0x0e], 0),
+    (
+        0xe0,
+        &[
+            0x02, 0x1c, 0x07, 0x12, 0x37, 0x32, 0x29, 0x2d, 0x29, 0x25, 0x2b, 0x39, 0x00, 0x01,
+            0x03, 0x10,
+        ],
+        0,
+    ),
+    (
+        0xe1,
+        &[
+            0x03, 0x1d, 0x07, 0x06, 0x2e, 0x2c, 0x29, 0x2d, 0x2e, 0x2e, 0x37, 0x3f, 0x00, 0x00,
+            0x02, 0x10,
+        ],
+        0,
+    ),
+    (CMD_NORON, &[], 10),
+];
+
+const ILI9341_INIT: &[(u8, &[u8], u64)] = &[
+    (0xcf, &[0x00, 0xc1, 0x30], 0),
+    (0xed, &[0x64, 0x03, 0x12, 0x81], 0),
+    (0xe8, &[0x85, 0x00, 0x78], 0),
+    (0xcb, &[0x39, 0x2c, 0x00, 0x34, 0x02], 0),
+    (0xf7, &[0x20], 0),
+    (0xea, &[0x00, 0x00], 0),
+    (0xc0, &[0x23], 0),
+    (0xc1, &[0x10], 0),
+    (0xc5, &[0x3e, 0x28], 0),
+    (0xc7, &[0x86], 0),
+    (0xb1, &[0x00, 0x18], 0),
+    (0xb6, &[0x08, 0x82, 0x27], 0),
+    (0xf2, &[0x00], 0),
+    (0x26, &[0x01], 0),
+    (
+        0xe0,
+        &[
+            0x0f, 0x31, 0x2b, 0x0c, 0x0e, 0x08, 0x4e, 0xf1, 0x37, 0x07, 0x10, 0x03, 0x0e, 0x09,
+            0x00,
+        ],
+        0,
+    ),
+    (
+        0xe1,
+        &[
+            0x00, 0x0e, 0x14, 0x03, 0x11, 0x07, 0x31, 0xc1, 0x48, 0x08, 0x0f, 0x0c, 0x31, 0x36,
+            0x0f,
+        ],
+        0,
+    ),
+];
+
+/// TFT display controllers.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum TftModel {
+    /// Sitronix ST7735R, with a 128x160 pixel panel.
+    St7735R,
+    /// Ilitek ILI9341, with a 240x320 pixel panel.
+    Ili9341,
+}
+
+impl TftModel {
+    /// Returns the panel's width in pixels, in its native portrait orientation.
+    pub fn width(self) -> u32 {
+        match self {
+            TftModel::St7735R => 128,
+            TftModel::Ili9341 => 240,
+        }
+    }
+
+    /// Returns the panel's height in pixels, in its native portrait orientation.
+    pub fn height(self) -> u32 {
+        match self {
+            TftModel::St7735R => 160,
+            TftModel::Ili9341 => 320,
+        }
+    }
+
+    fn init_sequence(self) -> &'static [(u8, &'static [u8], u64)] {
+        match self {
+            TftModel::St7735R => ST7735R_INIT,
+            TftModel::Ili9341 => ILI9341_INIT,
+        }
+    }
+
+    fn madctl(self, rotation: Rotation) -> u8 {
+        match (self, rotation) {
+            (TftModel::St7735R, Rotation::Deg0) => MADCTL_MX | MADCTL_MY,
+            (TftModel::St7735R, Rotation::Deg90) => MADCTL_MY | MADCTL_MV,
+            (TftModel::St7735R, Rotation::Deg180) => 0,
+            (TftModel::St7735R, Rotation::Deg270) => MADCTL_MX | MADCTL_MV,
+            (TftModel::Ili9341, Rotation::Deg0) => MADCTL_MX | MADCTL_BGR,
+            (TftModel::Ili9341, Rotation::Deg90) => MADCTL_MV | MADCTL_BGR,
+            (TftModel::Ili9341, Rotation::Deg180) => MADCTL_MY | MADCTL_BGR,
+            (TftModel::Ili9341, Rotation::Deg270) => MADCTL_MX | MADCTL_MY | MADCTL_MV | MADCTL_BGR,
+        }
+    }
+}
+
+/// Display rotations, clockwise relative to the panel's native portrait orientation.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Rotation {
+    /// Portrait.
+    Deg0,
+    /// Landscape.
+    Deg90,
+    /// Portrait, upside down.
+    Deg180,
+    /// Landscape, upside down.
+    Deg270,
+}
+
+/// ST7735R and ILI9341 TFT display driver.
+///
+/// `Tft` keeps a framebuffer with a 16-bit RGB565 color for each pixel, which can
+/// be created with [`rgb565`]. [`set_pixel`], [`fill`] and the optional
+/// `embedded-graphics` integration update the framebuffer, after which [`flush`]
+/// sends it to the display. The origin (0, 0) is located in the top left corner
+/// for the current rotation.
+///
+/// The display is connected through SPI, with an additional GPIO pin connected
+/// to D/C (sometimes labeled RS or A0), and optionally a pin connected to RST.
+/// Large transfers are split into chunks that fit within spidev's buffer size
+/// limit. The ST7735R supports SPI clock speeds up to 15 MHz, and the ILI9341 up to
+/// 10 MHz, although both usually work at higher speeds. The displays use SPI
+/// [`Mode0`].
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::display::{rgb565, Rotation, Tft, TftModel};
+/// use rppal::gpio::Gpio;
+/// use rppal::spi::{Bus, Mode, SlaveSelect, Spi};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gpio = Gpio::new()?;
+/// let dc = gpio.get(24).unwrap().into_output();
+/// let rst = gpio.get(25).unwrap().into_output();
+/// let spi = Spi::new(Bus::Spi0, SlaveSelect::Ss0, 32_000_000, Mode::Mode0)?;
+///
+/// let mut tft = Tft::new(spi, dc, Some(rst), TftModel::Ili9341)?;
+/// tft.set_rotation(Rotation::Deg90)?;
+/// tft.fill(rgb565(0, 0, 255));
+/// tft.flush()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`rgb565`]: fn.rgb565.html
+/// [`set_pixel`]: #method.set_pixel
+/// [`fill`]: #method.fill
+/// [`flush`]: #method.flush
+/// [`Mode0`]: ../spi/enum.Mode.html#variant.Mode0
+#[derive(Debug)]
+pub struct Tft {
+    interface: SpiInterface,
+    model: TftModel,
+    rotation: Rotation,
+    offset: (u16, u16),
+    buffer: Vec<u16>,
+}
+
+impl Tft {
+    /// Constructs a new `Tft`, and initializes the display.
+    ///
+    /// `dc` is connected to the display's D/C pin. If `rst` is specified, the
+    /// display is reset through its RST pin first. Otherwise, a software reset
+    /// is performed.
+    pub fn new(spi: Spi, dc: OutputPin, rst: Option<OutputPin>, model: TftModel) -> Result<Tft> {
+        let mut tft = Tft {
+            interface: SpiInterface::new(spi, dc),
+            model,
+            rotation: Rotation::Deg0,
+            offset: (0, 0),
+            buffer: vec![0; (model.width() * model.height()) as usize],
+        };
+
+        if let Some(mut rst) = rst {
+            interface::hardware_reset(&mut rst);
+
+            // Keep RST high after rst goes out of scope
+            rst.set_reset_on_drop(false);
+        } else {
+            tft.interface.command(&[CMD_SWRESET])?;
+            sleep(Duration::from_millis(150));
+        }
+
+        tft.interface.command(&[CMD_SLPOUT])?;
+        sleep(Duration::from_millis(120));
+
+        for &(command, parameters, delay) in model.init_sequence() {
+            tft.command(command, parameters)?;
+
+            if delay > 0 {
+                sleep(Duration::from_millis(delay));
+            }
+        }
+
+        tft.command(CMD_COLMOD, &[COLMOD_16BIT])?;
+        tft.command(CMD_MADCTL, &[model.madctl(Rotation::Deg0)])?;
+        tft.command(CMD_INVOFF, &[])?;
+
+        tft.flush()?;
+        tft.command(CMD_DISPON, &[])?;
+
+        Ok(tft)
+    }
+
+    /// Returns the display controller.
+    pub fn model(&self) -> TftModel {
+        self.model
+    }
+
+    /// Returns the width in pixels for the current rotation.
+    pub fn width(&self) -> u32 {
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => self.model.width(),
+            Rotation::Deg90 | Rotation::Deg270 => self.model.height(),
+        }
+    }
+
+    /// Returns the height in pixels for the current rotation.
+    pub fn height(&self) -> u32 {
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => self.model.height(),
+            Rotation::Deg90 | Rotation::Deg270 => self.model.width(),
+        }
+    }
+
+    /// Returns the rotation.
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Sets the rotation.
+    ///
+    /// Changing between portrait and landscape swaps the width and height. The
+    /// framebuffer is cleared, and needs to be redrawn.
+    pub fn set_rotation(&mut self, rotation: Rotation) -> Result<()> {
+        self.command(CMD_MADCTL, &[self.model.madctl(rotation)])?;
+
+        self.rotation = rotation;
+        self.clear();
+
+        Ok(())
+    }
+
+    /// Sets the column and row offset in the controller's display RAM where the
+    /// visible area starts, for the current rotation.
+    ///
+    /// Some panels don't use all of the controller's display RAM. For instance,
+    /// ST7735R modules with a green tab often need an offset of (2, 1). By
+    /// default, the offset is set to (0, 0).
+    pub fn set_offset(&mut self, column: u16, row: u16) {
+        self.offset = (column, row);
+    }
+
+    /// Sets the pixel at the specified location to an RGB565 `color` in the
+    /// framebuffer.
+    ///
+    /// Locations outside of the display area are ignored.
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: u16) {
+        if x >= self.width() || y >= self.height() {
+            return;
+        }
+
+        let index = (y * self.width() + x) as usize;
+        self.buffer[index] = color;
+    }
+
+    /// Returns the RGB565 color of the pixel at the specified location in the
+    /// framebuffer.
+    ///
+    /// Returns `None` for locations outside of the display area.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<u16> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+
+        Some(self.buffer[(y * self.width() + x) as usize])
+    }
+
+    /// Sets all pixels to an RGB565 `color` in the framebuffer.
+    pub fn fill(&mut self, color: u16) {
+        for pixel in &mut self.buffer {
+            *pixel = color;
+        }
+    }
+
+    /// Sets all pixels to black in the framebuffer.
+    pub fn clear(&mut self) {
+        self.fill(0);
+    }
+
+    /// Returns a reference to the framebuffer.
+    ///
+    /// The framebuffer contains an RGB565 color for each pixel, one row at a time,
+    /// starting with the top left corner for the current rotation.
+    pub fn buffer(&self) -> &[u16] {
+        &self.buffer
+    }
+
+    /// Returns a mutable reference to the framebuffer.
+    ///
+    /// More information on the layout can be found at [`buffer`].
+    ///
+    /// [`buffer`]: #method.buffer
+    pub fn buffer_mut(&mut self) -> &mut [u16] {
+        &mut self.buffer
+    }
+
+    /// Sends the framebuffer to the display.
+    pub fn flush(&mut self) -> Result<()> {
+        let x_end = self.offset.0 + self.width() as u16 - 1;
+        let y_end = self.offset.1 + self.height() as u16 - 1;
+
+        self.command(
+            CMD_CASET,
+            &[
+                (self.offset.0 >> 8) as u8,
+                self.offset.0 as u8,
+                (x_end >> 8) as u8,
+                x_end as u8,
+            ],
+        )?;
+        self.command(
+            CMD_RASET,
+            &[
+                (self.offset.1 >> 8) as u8,
+                self.offset.1 as u8,
+                (y_end >> 8) as u8,
+                y_end as u8,
+            ],
+        )?;
+
+        // The controller expects the most significant byte first
+        let mut data = Vec::with_capacity(self.buffer.len() * 2);
+        for pixel in &self.buffer {
+            data.push((pixel >> 8) as u8);
+            data.push(*pixel as u8);
+        }
+
+        self.interface.command(&[CMD_RAMWR])?;
+        self.interface.data(&data)
+    }
+
+    /// Turns the display on or off.
+    ///
+    /// The display RAM is kept while the display is turned off. The backlight is
+    /// usually controlled through a separate pin.
+    pub fn set_display_on(&mut self, on: bool) -> Result<()> {
+        self.command(if on { CMD_DISPON } else { CMD_DISPOFF }, &[])
+    }
+
+    /// Inverts the colors of the display.
+    pub fn set_inverted(&mut self, inverted: bool) -> Result<()> {
+        self.command(if inverted { CMD_INVON } else { CMD_INVOFF }, &[])
+    }
+
+    fn command(&mut self, command: u8, parameters: &[u8]) -> Result<()> {
+        self.interface.command(&[command])?;
+
+        if !parameters.is_empty() {
+            self.interface.data(parameters)?;
+        }
+
+        Ok(())
+    }
+}
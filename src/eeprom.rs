@@ -0,0 +1,83 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Drivers for I2C EEPROMs.
+//!
+//! * [`At24cxx`] supports the Microchip AT24Cxx series and compatible serial
+//!   EEPROMs, connected through I2C. It handles the device's page size, the
+//!   memory address width, and waits for write cycles to complete through ACK
+//!   polling.
+//!
+//! [`HatEeprom`] reads and writes the HAT ID EEPROM format, which is used by
+//! Raspberry Pi HATs to identify themselves and configure their GPIO pins at boot.
+//!
+//! [`At24cxx`]: struct.At24cxx.html
+//! [`HatEeprom`]: struct.HatEeprom.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::i2c;
+
+mod at24cxx;
+mod hat;
+
+pub use self::at24cxx::{At24cxx, At24cxxModel};
+pub use self::hat::{GpioMap, HatEeprom, HatPin, VendorInfo};
+
+quick_error! {
+/// Errors that can occur when accessing an EEPROM.
+    #[derive(Debug)]
+    pub enum Error {
+/// I2C error.
+        I2c(err: i2c::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Address out of range.
+///
+/// The read or write operation would extend past the end of the EEPROM.
+        OutOfRange { description("address out of range") }
+/// Timeout.
+///
+/// The EEPROM didn't finish its write cycle in time. This usually means the
+/// EEPROM is write-protected.
+        Timeout { description("write cycle didn't finish in time") }
+/// Invalid HAT EEPROM signature.
+///
+/// The data doesn't start with the HAT ID EEPROM signature.
+        InvalidSignature { description("invalid HAT EEPROM signature") }
+/// Invalid HAT EEPROM format.
+///
+/// The header or one of the atoms has an invalid length.
+        InvalidFormat { description("invalid HAT EEPROM format") }
+/// CRC mismatch.
+        Crc { description("CRC mismatch") }
+/// String too long.
+///
+/// The vendor and product strings can contain a maximum of 255 bytes each.
+        StringTooLong { description("string too long") }
+/// Invalid GPIO pin number.
+///
+/// The HAT GPIO map only covers BCM GPIO 0-27.
+        InvalidPin(pin: u8) { description("invalid GPIO pin number") }
+    }
+}
+
+/// Result type returned from methods that can have `eeprom::Error`s.
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,287 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::eeprom::{Error, Result};
+use crate::i2c::I2c;
+
+// Maximum write cycle time. Most devices need 5 ms, some up to 10 ms.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(20);
+const POLL_INTERVAL: Duration = Duration::from_micros(500);
+
+// Keep reads well below the I2C_RDWR limit of 8192 bytes per message
+const READ_CHUNK_SIZE: usize = 4096;
+
+/// Supported AT24Cxx EEPROM models.
+///
+/// Compatible devices from other manufacturers, like the ON Semiconductor CAT24Cxx
+/// and the ST M24Cxx series, can be used with the model that has the same
+/// capacity and page size.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum At24cxxModel {
+    /// AT24C01, 128 bytes.
+    At24c01,
+    /// AT24C02, 256 bytes.
+    At24c02,
+    /// AT24C04, 512 bytes.
+    At24c04,
+    /// AT24C08, 1 KiB.
+    At24c08,
+    /// AT24C16, 2 KiB.
+    At24c16,
+    /// AT24C32, 4 KiB.
+    At24c32,
+    /// AT24C64, 8 KiB.
+    At24c64,
+    /// AT24C128, 16 KiB.
+    At24c128,
+    /// AT24C256, 32 KiB.
+    At24c256,
+    /// AT24C512, 64 KiB.
+    At24c512,
+    /// AT24CM01, 128 KiB.
+    At24cm01,
+    /// AT24CM02, 256 KiB.
+    At24cm02,
+}
+
+impl At24cxxModel {
+    /// Returns the capacity in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            At24cxxModel::At24c01 => 128,
+            At24cxxModel::At24c02 => 256,
+            At24cxxModel::At24c04 => 512,
+            At24cxxModel::At24c08 => 1024,
+            At24cxxModel::At24c16 => 2048,
+            At24cxxModel::At24c32 => 4096,
+            At24cxxModel::At24c64 => 8192,
+            At24cxxModel::At24c128 => 16384,
+            At24cxxModel::At24c256 => 32768,
+            At24cxxModel::At24c512 => 65536,
+            At24cxxModel::At24cm01 => 131_072,
+            At24cxxModel::At24cm02 => 262_144,
+        }
+    }
+
+    /// Returns the page size in bytes.
+    ///
+    /// A single write operation can't cross a page boundary.
+    pub fn page_size(self) -> usize {
+        match self {
+            At24cxxModel::At24c01 | At24cxxModel::At24c02 => 8,
+            At24cxxModel::At24c04 | At24cxxModel::At24c08 | At24cxxModel::At24c16 => 16,
+            At24cxxModel::At24c32 | At24cxxModel::At24c64 => 32,
+            At24cxxModel::At24c128 | At24cxxModel::At24c256 => 64,
+            At24cxxModel::At24c512 => 128,
+            At24cxxModel::At24cm01 | At24cxxModel::At24cm02 => 256,
+        }
+    }
+
+    // Number of memory address bytes sent after the slave address. Any
+    // remaining high address bits are included in the slave address.
+    fn address_bytes(self) -> usize {
+        if self.size() <= 2048 {
+            1
+        } else {
+            2
+        }
+    }
+}
+
+/// AT24Cxx I2C EEPROM driver.
+///
+/// `At24cxx` splits writes at page boundaries, and waits for the EEPROM to
+/// finish its internal write cycle by polling for an acknowledgement (ACK
+/// polling), so data of any length can be written to any address.
+///
+/// The A0, A1 and A2 pins select one of 8 slave addresses between 0x50 and 0x57.
+/// Smaller devices (AT24C04, AT24C08, AT24C16) and the AT24CMxx series use some
+/// of those address bits for the upper bits of the memory address, and occupy
+/// multiple consecutive slave addresses. In that case, `address` should be set to
+/// the lowest one.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::eeprom::{At24cxx, At24cxxModel};
+/// use rppal::i2c::I2c;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut eeprom = At24cxx::new(I2c::new()?, 0x50, At24cxxModel::At24c32)?;
+///
+/// eeprom.write(0x0100, b"Hello, world!")?;
+///
+/// let mut buffer = [0u8; 13];
+/// eeprom.read(0x0100, &mut buffer)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct At24cxx {
+    i2c: I2c,
+    address: u16,
+    model: At24cxxModel,
+}
+
+impl At24cxx {
+    /// Constructs a new `At24cxx` for the device at the specified slave address.
+    pub fn new(mut i2c: I2c, address: u16, model: At24cxxModel) -> Result<At24cxx> {
+        i2c.set_slave_address(address)?;
+
+        Ok(At24cxx {
+            i2c,
+            address,
+            model,
+        })
+    }
+
+    /// Returns the model selected when the `At24cxx` was constructed.
+    pub fn model(&self) -> At24cxxModel {
+        self.model
+    }
+
+    /// Returns the capacity in bytes.
+    pub fn size(&self) -> usize {
+        self.model.size()
+    }
+
+    /// Fills `buffer` with data read from the EEPROM, starting at the specified
+    /// memory address.
+    ///
+    /// Returns [`Error::OutOfRange`] if the read would extend past the end of the
+    /// EEPROM.
+    ///
+    /// [`Error::OutOfRange`]: enum.Error.html#variant.OutOfRange
+    pub fn read(&mut self, address: usize, buffer: &mut [u8]) -> Result<()> {
+        self.check_range(address, buffer.len())?;
+
+        let block_size = self.block_size();
+        let mut offset = 0;
+
+        while offset < buffer.len() {
+            let memory_address = address + offset;
+            let len = (buffer.len() - offset)
+                .min(block_size - (memory_address % block_size))
+                .min(READ_CHUNK_SIZE);
+
+            let (address_buffer, address_len) = self.select(memory_address)?;
+            self.i2c.write_read(
+                &address_buffer[..address_len],
+                &mut buffer[offset..offset + len],
+            )?;
+
+            offset += len;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `data` to the EEPROM, starting at the specified memory address.
+    ///
+    /// The data is split at page boundaries, and `write` blocks until the EEPROM
+    /// has finished writing each page. Returns [`Error::OutOfRange`] if the write
+    /// would extend past the end of the EEPROM, or [`Error::Timeout`] if the
+    /// EEPROM doesn't finish its write cycle in time, which usually means it's
+    /// write-protected.
+    ///
+    /// [`Error::OutOfRange`]: enum.Error.html#variant.OutOfRange
+    /// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+    pub fn write(&mut self, address: usize, data: &[u8]) -> Result<()> {
+        self.check_range(address, data.len())?;
+
+        let page_size = self.model.page_size();
+        let mut buffer = Vec::with_capacity(page_size + 2);
+        let mut offset = 0;
+
+        while offset < data.len() {
+            let memory_address = address + offset;
+            let len = (data.len() - offset).min(page_size - (memory_address % page_size));
+
+            let (address_buffer, address_len) = self.select(memory_address)?;
+
+            buffer.clear();
+            buffer.extend_from_slice(&address_buffer[..address_len]);
+            buffer.extend_from_slice(&data[offset..offset + len]);
+
+            self.i2c.write(&buffer)?;
+            self.wait_for_write(&address_buffer[..address_len])?;
+
+            offset += len;
+        }
+
+        Ok(())
+    }
+
+    /// Consumes the `At24cxx`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+
+    fn check_range(&self, address: usize, len: usize) -> Result<()> {
+        match address.checked_add(len) {
+            Some(end) if end <= self.model.size() => Ok(()),
+            _ => Err(Error::OutOfRange),
+        }
+    }
+
+    // Range of memory addresses that can be reached without changing the slave
+    // address
+    fn block_size(&self) -> usize {
+        1 << (8 * self.model.address_bytes())
+    }
+
+    // Selects the slave address for the specified memory address, and returns the
+    // memory address bytes that should be sent after it
+    fn select(&mut self, memory_address: usize) -> Result<([u8; 2], usize)> {
+        let address_bytes = self.model.address_bytes();
+        let slave_address = self.address + (memory_address >> (8 * address_bytes)) as u16;
+
+        if self.i2c.slave_address() != slave_address {
+            self.i2c.set_slave_address(slave_address)?;
+        }
+
+        if address_bytes == 1 {
+            Ok(([memory_address as u8, 0], 1))
+        } else {
+            Ok(([(memory_address >> 8) as u8, memory_address as u8], 2))
+        }
+    }
+
+    // The EEPROM doesn't acknowledge its address during the internal write cycle.
+    // Sending only the memory address doesn't start a new write cycle.
+    fn wait_for_write(&mut self, address_buffer: &[u8]) -> Result<()> {
+        let start = Instant::now();
+
+        loop {
+            sleep(POLL_INTERVAL);
+
+            if self.i2c.write(address_buffer).is_ok() {
+                return Ok(());
+            }
+
+            if start.elapsed() > WRITE_TIMEOUT {
+                return Err(Error::Timeout);
+            }
+        }
+    }
+}
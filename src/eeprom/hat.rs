@@ -0,0 +1,592 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::eeprom::{At24cxx, Error, Result};
+use crate::gpio::{Mode, PullUpDown};
+
+const SIGNATURE: [u8; 4] = *b"R-Pi";
+const FORMAT_VERSION: u8 = 0x01;
+
+const HEADER_SIZE: usize = 12;
+const ATOM_HEADER_SIZE: usize = 8;
+const CRC_SIZE: usize = 2;
+
+const ATOM_VENDOR_INFO: u16 = 0x0001;
+const ATOM_GPIO_MAP: u16 = 0x0002;
+const ATOM_DEVICE_TREE: u16 = 0x0003;
+const ATOM_CUSTOM_DATA: u16 = 0x0004;
+
+const VENDOR_INFO_SIZE: usize = 22;
+const GPIO_MAP_SIZE: usize = 30;
+const GPIO_MAP_PINS: usize = 28;
+
+const PIN_USED: u8 = 0x80;
+
+/// Vendor information stored in a HAT ID EEPROM.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct VendorInfo {
+    uuid: [u8; 16],
+    product_id: u16,
+    product_version: u16,
+    vendor: String,
+    product: String,
+}
+
+impl VendorInfo {
+    /// Constructs a new `VendorInfo`.
+    ///
+    /// `uuid` should be unique for every produced board. `vendor` and `product`
+    /// can contain a maximum of 255 bytes each. Returns [`Error::StringTooLong`]
+    /// if either of them exceeds that limit.
+    ///
+    /// [`Error::StringTooLong`]: enum.Error.html#variant.StringTooLong
+    pub fn new(
+        uuid: [u8; 16],
+        product_id: u16,
+        product_version: u16,
+        vendor: &str,
+        product: &str,
+    ) -> Result<VendorInfo> {
+        if vendor.len() > 255 || product.len() > 255 {
+            return Err(Error::StringTooLong);
+        }
+
+        Ok(VendorInfo {
+            uuid,
+            product_id,
+            product_version,
+            vendor: vendor.to_owned(),
+            product: product.to_owned(),
+        })
+    }
+
+    /// Returns the board's UUID.
+    pub fn uuid(&self) -> [u8; 16] {
+        self.uuid
+    }
+
+    /// Returns the product ID.
+    pub fn product_id(&self) -> u16 {
+        self.product_id
+    }
+
+    /// Returns the product version.
+    pub fn product_version(&self) -> u16 {
+        self.product_version
+    }
+
+    /// Returns the vendor name.
+    pub fn vendor(&self) -> &str {
+        &self.vendor
+    }
+
+    /// Returns the product name.
+    pub fn product(&self) -> &str {
+        &self.product
+    }
+
+    fn parse(data: &[u8]) -> Result<VendorInfo> {
+        if data.len() < VENDOR_INFO_SIZE {
+            return Err(Error::InvalidFormat);
+        }
+
+        let vendor_len = data[20] as usize;
+        let product_len = data[21] as usize;
+        if data.len() < VENDOR_INFO_SIZE + vendor_len + product_len {
+            return Err(Error::InvalidFormat);
+        }
+
+        let mut uuid = [0u8; 16];
+        uuid.copy_from_slice(&data[..16]);
+
+        let vendor = &data[VENDOR_INFO_SIZE..VENDOR_INFO_SIZE + vendor_len];
+        let product = &data[VENDOR_INFO_SIZE + vendor_len..][..product_len];
+
+        Ok(VendorInfo {
+            uuid,
+            product_id: u16::from_le_bytes([data[16], data[17]]),
+            product_version: u16::from_le_bytes([data[18], data[19]]),
+            vendor: String::from_utf8_lossy(vendor).into_owned(),
+            product: String::from_utf8_lossy(product).into_owned(),
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data =
+            Vec::with_capacity(VENDOR_INFO_SIZE + self.vendor.len() + self.product.len());
+        data.extend_from_slice(&self.uuid);
+        data.extend_from_slice(&self.product_id.to_le_bytes());
+        data.extend_from_slice(&self.product_version.to_le_bytes());
+        data.push(self.vendor.len() as u8);
+        data.push(self.product.len() as u8);
+        data.extend_from_slice(self.vendor.as_bytes());
+        data.extend_from_slice(self.product.as_bytes());
+
+        data
+    }
+}
+
+/// GPIO pin configuration stored in a HAT ID EEPROM.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct HatPin {
+    mode: Mode,
+    pull_up_down: Option<PullUpDown>,
+}
+
+impl HatPin {
+    /// Constructs a new `HatPin`.
+    ///
+    /// If `pull_up_down` is set to `None`, the pin's built-in pull-up/pull-down
+    /// resistor is left at its default setting.
+    pub fn new(mode: Mode, pull_up_down: Option<PullUpDown>) -> HatPin {
+        HatPin { mode, pull_up_down }
+    }
+
+    /// Returns the pin's mode.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Returns the pin's pull-up/pull-down resistor configuration, or `None`
+    /// if it's left at its default setting.
+    pub fn pull_up_down(&self) -> Option<PullUpDown> {
+        self.pull_up_down
+    }
+
+    fn parse(value: u8) -> Option<HatPin> {
+        if value & PIN_USED == 0 {
+            return None;
+        }
+
+        let mode = match value & 0b111 {
+            0b000 => Mode::Input,
+            0b001 => Mode::Output,
+            0b100 => Mode::Alt0,
+            0b101 => Mode::Alt1,
+            0b110 => Mode::Alt2,
+            0b111 => Mode::Alt3,
+            0b011 => Mode::Alt4,
+            _ => Mode::Alt5,
+        };
+
+        let pull_up_down = match (value >> 5) & 0b11 {
+            0 => None,
+            1 => Some(PullUpDown::PullUp),
+            2 => Some(PullUpDown::PullDown),
+            _ => Some(PullUpDown::Off),
+        };
+
+        Some(HatPin { mode, pull_up_down })
+    }
+
+    fn to_byte(self) -> u8 {
+        let pull_up_down = match self.pull_up_down {
+            None => 0,
+            Some(PullUpDown::PullUp) => 1,
+            Some(PullUpDown::PullDown) => 2,
+            Some(PullUpDown::Off) => 3,
+        };
+
+        PIN_USED | (pull_up_down << 5) | self.mode as u8
+    }
+}
+
+/// GPIO bank configuration and pin map stored in a HAT ID EEPROM.
+///
+/// The firmware applies the configuration at boot, before the kernel is loaded.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct GpioMap {
+    bank_drive: u8,
+    power: u8,
+    pins: [u8; GPIO_MAP_PINS],
+}
+
+impl GpioMap {
+    /// Constructs a new `GpioMap` with default bank settings, and all pins
+    /// marked as unused.
+    pub fn new() -> GpioMap {
+        GpioMap::default()
+    }
+
+    /// Returns the bank's drive strength.
+    ///
+    /// 0 leaves the drive strength at its default setting. 1-8 select a drive
+    /// strength of 2-16 mA in 2 mA increments.
+    pub fn drive(&self) -> u8 {
+        self.bank_drive & 0x0f
+    }
+
+    /// Sets the bank's drive strength.
+    ///
+    /// Values above 8 are clamped.
+    pub fn set_drive(&mut self, drive: u8) {
+        self.bank_drive = (self.bank_drive & 0xf0) | drive.min(8);
+    }
+
+    /// Returns the bank's slew rate setting.
+    ///
+    /// 0 leaves the slew rate at its default setting, 1 limits the slew rate,
+    /// and 2 doesn't limit the slew rate.
+    pub fn slew(&self) -> u8 {
+        (self.bank_drive >> 4) & 0b11
+    }
+
+    /// Sets the bank's slew rate setting.
+    ///
+    /// Values above 2 are clamped.
+    pub fn set_slew(&mut self, slew: u8) {
+        self.bank_drive = (self.bank_drive & 0xcf) | (slew.min(2) << 4);
+    }
+
+    /// Returns the bank's input hysteresis setting.
+    ///
+    /// 0 leaves the hysteresis at its default setting, 1 disables hysteresis,
+    /// and 2 enables hysteresis.
+    pub fn hysteresis(&self) -> u8 {
+        self.bank_drive >> 6
+    }
+
+    /// Sets the bank's input hysteresis setting.
+    ///
+    /// Values above 2 are clamped.
+    pub fn set_hysteresis(&mut self, hysteresis: u8) {
+        self.bank_drive = (self.bank_drive & 0x3f) | (hysteresis.min(2) << 6);
+    }
+
+    /// Returns the back-powering setting.
+    ///
+    /// 0 indicates the HAT doesn't back-power the Raspberry Pi. 1 and 2 indicate
+    /// the HAT back-powers the Raspberry Pi, and can supply a minimum of 1.3 A
+    /// and 2 A respectively.
+    pub fn back_power(&self) -> u8 {
+        self.power & 0b11
+    }
+
+    /// Sets the back-powering setting.
+    ///
+    /// Values above 2 are clamped.
+    pub fn set_back_power(&mut self, back_power: u8) {
+        self.power = (self.power & 0xfc) | back_power.min(2);
+    }
+
+    /// Returns the configuration for the specified pin, or `None` if the pin
+    /// isn't used by the HAT.
+    ///
+    /// Pins are addressed by their BCM numbers, and range from 0 to 27.
+    pub fn pin(&self, pin: u8) -> Option<HatPin> {
+        self.pins
+            .get(pin as usize)
+            .and_then(|&value| HatPin::parse(value))
+    }
+
+    /// Sets the configuration for the specified pin.
+    ///
+    /// Set `config` to `None` to mark the pin as unused. Returns
+    /// [`Error::InvalidPin`] if `pin` is greater than 27.
+    ///
+    /// [`Error::InvalidPin`]: enum.Error.html#variant.InvalidPin
+    pub fn set_pin(&mut self, pin: u8, config: Option<HatPin>) -> Result<()> {
+        let value = self
+            .pins
+            .get_mut(pin as usize)
+            .ok_or(Error::InvalidPin(pin))?;
+
+        *value = config.map_or(0, HatPin::to_byte);
+
+        Ok(())
+    }
+
+    fn parse(data: &[u8]) -> Result<GpioMap> {
+        if data.len() < GPIO_MAP_SIZE {
+            return Err(Error::InvalidFormat);
+        }
+
+        let mut pins = [0u8; GPIO_MAP_PINS];
+        pins.copy_from_slice(&data[2..GPIO_MAP_SIZE]);
+
+        Ok(GpioMap {
+            bank_drive: data[0],
+            power: data[1],
+            pins,
+        })
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(GPIO_MAP_SIZE);
+        data.push(self.bank_drive);
+        data.push(self.power);
+        data.extend_from_slice(&self.pins);
+
+        data
+    }
+}
+
+/// Contents of a HAT ID EEPROM.
+///
+/// Raspberry Pi HATs include an EEPROM connected to the ID_SD and ID_SC pins
+/// (BCM GPIO 0 and 1), which identifies the board and configures its GPIO pins.
+/// The firmware reads the EEPROM at boot, and loads the device tree overlay it
+/// contains.
+///
+/// `HatEeprom` parses and generates the EEPROM format defined in the HAT
+/// specification, consisting of a header followed by a vendor info atom, a GPIO
+/// map atom, and optionally a device tree overlay and any number of custom data
+/// atoms. Each atom is protected by a CRC.
+///
+/// The ID EEPROM is connected to I2C bus 0, which is reserved for the firmware.
+/// To access it from Linux, add `dtparam=i2c_vc=on` to `/boot/config.txt`. Most
+/// HAT EEPROMs are 24C32-compatible devices at slave address 0x50, and have a
+/// write-protect pin that needs to be pulled low before they can be written to.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::eeprom::{At24cxx, At24cxxModel, HatEeprom};
+/// use rppal::i2c::I2c;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut eeprom = At24cxx::new(I2c::with_bus(0)?, 0x50, At24cxxModel::At24c32)?;
+/// let hat = HatEeprom::read(&mut eeprom)?;
+///
+/// if let Some(vendor_info) = hat.vendor_info() {
+///     println!("{} {}", vendor_info.vendor(), vendor_info.product());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct HatEeprom {
+    vendor_info: Option<VendorInfo>,
+    gpio_map: Option<GpioMap>,
+    device_tree: Option<Vec<u8>>,
+    custom_data: Vec<Vec<u8>>,
+}
+
+impl HatEeprom {
+    /// Constructs a new, empty `HatEeprom`.
+    pub fn new() -> HatEeprom {
+        HatEeprom::default()
+    }
+
+    /// Reads and parses the contents of a HAT ID EEPROM.
+    ///
+    /// Returns [`Error::InvalidSignature`] if the EEPROM doesn't start with the
+    /// HAT signature, [`Error::Crc`] if one of the atoms is corrupted, or
+    /// [`Error::InvalidFormat`] if the data is otherwise malformed.
+    ///
+    /// [`Error::InvalidSignature`]: enum.Error.html#variant.InvalidSignature
+    /// [`Error::Crc`]: enum.Error.html#variant.Crc
+    /// [`Error::InvalidFormat`]: enum.Error.html#variant.InvalidFormat
+    pub fn read(eeprom: &mut At24cxx) -> Result<HatEeprom> {
+        let mut header = [0u8; HEADER_SIZE];
+        eeprom.read(0, &mut header)?;
+
+        if header[..4] != SIGNATURE {
+            return Err(Error::InvalidSignature);
+        }
+
+        let len = u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as usize;
+        if len < HEADER_SIZE || len > eeprom.size() {
+            return Err(Error::InvalidFormat);
+        }
+
+        let mut data = vec![0u8; len];
+        eeprom.read(0, &mut data)?;
+
+        HatEeprom::parse(&data)
+    }
+
+    /// Writes the contents to a HAT ID EEPROM.
+    ///
+    /// Returns [`Error::OutOfRange`] if the contents don't fit in the EEPROM.
+    ///
+    /// [`Error::OutOfRange`]: enum.Error.html#variant.OutOfRange
+    pub fn write(&self, eeprom: &mut At24cxx) -> Result<()> {
+        eeprom.write(0, &self.to_bytes())
+    }
+
+    /// Parses the contents of a HAT ID EEPROM.
+    ///
+    /// Unknown atom types are skipped.
+    pub fn parse(data: &[u8]) -> Result<HatEeprom> {
+        if data.len() < HEADER_SIZE {
+            return Err(Error::InvalidFormat);
+        }
+
+        if data[..4] != SIGNATURE {
+            return Err(Error::InvalidSignature);
+        }
+
+        let atoms = u16::from_le_bytes([data[6], data[7]]);
+        let len = u32::from_le_bytes([data[8], data[9], data[10], data[11]]) as usize;
+        if len < HEADER_SIZE || len > data.len() {
+            return Err(Error::InvalidFormat);
+        }
+
+        let data = &data[..len];
+        let mut hat = HatEeprom::new();
+        let mut offset = HEADER_SIZE;
+
+        for _ in 0..atoms {
+            if data.len() < offset + ATOM_HEADER_SIZE {
+                return Err(Error::InvalidFormat);
+            }
+
+            let atom = &data[offset..];
+            let atom_type = u16::from_le_bytes([atom[0], atom[1]]);
+            let atom_len = u32::from_le_bytes([atom[4], atom[5], atom[6], atom[7]]) as usize;
+
+            if atom_len < CRC_SIZE || atom.len() - ATOM_HEADER_SIZE < atom_len {
+                return Err(Error::InvalidFormat);
+            }
+
+            let crc_offset = ATOM_HEADER_SIZE + atom_len - CRC_SIZE;
+            if crc(&atom[..crc_offset])
+                != u16::from_le_bytes([atom[crc_offset], atom[crc_offset + 1]])
+            {
+                return Err(Error::Crc);
+            }
+
+            let payload = &atom[ATOM_HEADER_SIZE..crc_offset];
+            match atom_type {
+                ATOM_VENDOR_INFO => hat.vendor_info = Some(VendorInfo::parse(payload)?),
+                ATOM_GPIO_MAP => hat.gpio_map = Some(GpioMap::parse(payload)?),
+                ATOM_DEVICE_TREE => hat.device_tree = Some(payload.to_vec()),
+                ATOM_CUSTOM_DATA => hat.custom_data.push(payload.to_vec()),
+                _ => (),
+            }
+
+            offset += ATOM_HEADER_SIZE + atom_len;
+        }
+
+        Ok(hat)
+    }
+
+    /// Returns the contents in the HAT ID EEPROM format.
+    ///
+    /// The HAT specification requires both a vendor info and a GPIO map atom. Atoms
+    /// that haven't been set are left out.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut atoms: Vec<(u16, &[u8])> = Vec::new();
+
+        let vendor_info = self.vendor_info.as_ref().map(VendorInfo::to_bytes);
+        if let Some(ref data) = vendor_info {
+            atoms.push((ATOM_VENDOR_INFO, data));
+        }
+
+        let gpio_map = self.gpio_map.map(GpioMap::to_bytes);
+        if let Some(ref data) = gpio_map {
+            atoms.push((ATOM_GPIO_MAP, data));
+        }
+
+        if let Some(ref data) = self.device_tree {
+            atoms.push((ATOM_DEVICE_TREE, data));
+        }
+
+        for data in &self.custom_data {
+            atoms.push((ATOM_CUSTOM_DATA, data));
+        }
+
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(&SIGNATURE);
+        buffer.push(FORMAT_VERSION);
+        buffer.push(0);
+        buffer.extend_from_slice(&(atoms.len() as u16).to_le_bytes());
+        // Total length, updated below
+        buffer.extend_from_slice(&[0u8; 4]);
+
+        for (index, (atom_type, data)) in atoms.iter().enumerate() {
+            let start = buffer.len();
+
+            buffer.extend_from_slice(&atom_type.to_le_bytes());
+            buffer.extend_from_slice(&(index as u16).to_le_bytes());
+            buffer.extend_from_slice(&((data.len() + CRC_SIZE) as u32).to_le_bytes());
+            buffer.extend_from_slice(data);
+
+            let crc = crc(&buffer[start..]);
+            buffer.extend_from_slice(&crc.to_le_bytes());
+        }
+
+        let len = (buffer.len() as u32).to_le_bytes();
+        buffer[8..HEADER_SIZE].copy_from_slice(&len);
+
+        buffer
+    }
+
+    /// Returns the vendor info.
+    pub fn vendor_info(&self) -> Option<&VendorInfo> {
+        self.vendor_info.as_ref()
+    }
+
+    /// Sets the vendor info.
+    pub fn set_vendor_info(&mut self, vendor_info: Option<VendorInfo>) {
+        self.vendor_info = vendor_info;
+    }
+
+    /// Returns the GPIO map.
+    pub fn gpio_map(&self) -> Option<&GpioMap> {
+        self.gpio_map.as_ref()
+    }
+
+    /// Sets the GPIO map.
+    pub fn set_gpio_map(&mut self, gpio_map: Option<GpioMap>) {
+        self.gpio_map = gpio_map;
+    }
+
+    /// Returns the compiled device tree overlay (`.dtbo`).
+    pub fn device_tree(&self) -> Option<&[u8]> {
+        self.device_tree.as_deref()
+    }
+
+    /// Sets the compiled device tree overlay (`.dtbo`).
+    pub fn set_device_tree(&mut self, device_tree: Option<Vec<u8>>) {
+        self.device_tree = device_tree;
+    }
+
+    /// Returns the custom data atoms.
+    pub fn custom_data(&self) -> &[Vec<u8>] {
+        &self.custom_data
+    }
+
+    /// Sets the custom data atoms.
+    pub fn set_custom_data(&mut self, custom_data: Vec<Vec<u8>>) {
+        self.custom_data = custom_data;
+    }
+}
+
+// CRC-16 with polynomial 0x8005 in reflected form (CRC-16/ARC), as used by the
+// eepmake tool
+fn crc(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+
+    for &byte in data {
+        crc ^= u16::from(byte);
+
+        for _ in 0..8 {
+            if crc & 1 > 0 {
+                crc = (crc >> 1) ^ 0xa001;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+
+    crc
+}
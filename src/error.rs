@@ -0,0 +1,67 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::{expander, gpio, i2c, pwm, spi, uart};
+
+quick_error! {
+/// Errors that can occur when accessing any of the supported peripherals.
+///
+/// `Error` wraps the module-specific error types, and implements `From` for each
+/// of them, which allows the `?` operator to be used with methods from different
+/// modules within a single function.
+///
+/// ```no_run
+/// use rppal::gpio::Gpio;
+/// use rppal::i2c::I2c;
+/// use rppal::Result;
+///
+/// fn setup() -> Result<()> {
+///     let mut pin = Gpio::new()?.get(23).unwrap().into_output();
+///     let mut i2c = I2c::new()?;
+///
+///     pin.set_high();
+///     i2c.set_slave_address(0x20)?;
+///
+///     Ok(())
+/// }
+/// ```
+    #[derive(Debug)]
+    pub enum Error {
+/// GPIO expander error.
+        Expander(err: expander::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// I2C error.
+        I2c(err: i2c::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// PWM error.
+        Pwm(err: pwm::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// SPI error.
+        Spi(err: spi::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// UART error.
+        Uart(err: uart::Error) { description(err.description()) display("{}", err) cause(err) from() }
+    }
+}
+
+/// Result type returned from functions that can have `rppal::Error`s.
+pub type Result<T> = result::Result<T, Error>;
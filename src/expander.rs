@@ -0,0 +1,375 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Interface for I2C GPIO expanders.
+//!
+//! GPIO expanders add additional digital I/O pins through the I2C bus. RPPAL
+//! supports the 16-bit MCP23017 and the 8-bit PCF8574 (and PCF8574A). Their pins are
+//! exposed through [`Pin`], [`InputPin`] and [`OutputPin`], which mirror their
+//! counterparts in the [`gpio`] module. Unlike the SoC's GPIO pins, every operation
+//! involves an I2C transfer, so all methods return a `Result`.
+//!
+//! ## Interrupts
+//!
+//! Both expanders have an active-low interrupt output (INT), which signals a change
+//! on any of the input pins that have interrupts enabled. When INT is connected to
+//! one of the Raspberry Pi's GPIO pins, and that pin is passed to
+//! [`Expander::set_interrupt_pin`], [`InputPin::poll_interrupt`] blocks until an
+//! interrupt is triggered, similar to [`gpio::InputPin::poll_interrupt`]. The
+//! MCP23017's INTA and INTB outputs are mirrored, so connecting either of them
+//! is sufficient.
+//!
+//! Multiple pins can be polled from different threads at the same time. Only one
+//! of those threads waits for INT, and queues the events for all pins.
+//!
+//! The MCP23017 latches the pin levels at the time of the interrupt, which are
+//! used to determine the type of edge. The PCF8574 doesn't latch its inputs, so
+//! the pin levels are read after the interrupt was detected, which means very
+//! short pulses might be missed.
+//!
+//! ## Embedded HAL
+//!
+//! When the `hal` feature is enabled, [`InputPin`] and [`OutputPin`] implement the
+//! same `embedded-hal` digital traits as the SoC's GPIO pins, which lets drivers
+//! use either interchangeably.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::expander::{Expander, Model};
+//! use rppal::gpio::{Gpio, Trigger};
+//! use rppal::i2c::I2c;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let expander = Expander::new(I2c::new()?, Model::Mcp23017, 0x20)?;
+//! expander.set_interrupt_pin(Gpio::new()?.get(17).unwrap().into_input())?;
+//!
+//! let mut led = expander.get(0)?.into_output_low()?;
+//! let mut button = expander.get(8)?.into_input_pullup()?;
+//! button.set_interrupt(Trigger::FallingEdge)?;
+//!
+//! loop {
+//!     button.poll_interrupt(None)?;
+//!     led.toggle()?;
+//! }
+//! # }
+//! ```
+//!
+//! [`Pin`]: struct.Pin.html
+//! [`InputPin`]: struct.InputPin.html
+//! [`OutputPin`]: struct.OutputPin.html
+//! [`gpio`]: ../gpio/index.html
+//! [`Expander::set_interrupt_pin`]: struct.Expander.html#method.set_interrupt_pin
+//! [`InputPin::poll_interrupt`]: struct.InputPin.html#method.poll_interrupt
+//! [`gpio::InputPin::poll_interrupt`]: ../gpio/struct.InputPin.html#method.poll_interrupt
+
+use std::fmt;
+use std::result;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use quick_error::quick_error;
+
+use crate::gpio;
+use crate::i2c::{self, I2c};
+
+mod chip;
+#[cfg(feature = "hal")]
+mod hal;
+mod pin;
+
+pub use self::pin::{InputPin, OutputPin, Pin};
+
+use self::chip::Chip;
+
+// Maximum time a single wait for INT blocks, which prevents set_interrupt_pin()
+// and clear_interrupt_pin() from getting stuck behind a poll without a timeout
+const INTERRUPT_SLICE: Duration = Duration::from_millis(100);
+
+quick_error! {
+/// Errors that can occur when accessing a GPIO expander.
+    #[derive(Debug)]
+    pub enum Error {
+/// I2C error.
+        I2c(err: i2c::Error) { description(err.description()) from() }
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Invalid pin number.
+///
+/// The MCP23017 has 16 pins (0-7 for GPA0-GPA7, 8-15 for GPB0-GPB7), and the
+/// PCF8574 has 8 pins (0-7 for P0-P7).
+        InvalidPin(pin: u8) { description("invalid pin number") }
+/// Pin is already in use.
+        PinNotAvailable(pin: u8) { description("pin is already in use") }
+/// Interrupt pin not configured.
+///
+/// Polling for interrupts requires the expander's INT output to be connected
+/// to a GPIO pin, which is configured with [`Expander::set_interrupt_pin`].
+///
+/// [`Expander::set_interrupt_pin`]: struct.Expander.html#method.set_interrupt_pin
+        InterruptPinNotConfigured { description("interrupt pin not configured") }
+    }
+}
+
+/// Result type returned from methods that can have `expander::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// Supported GPIO expander models.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Model {
+    /// Microchip MCP23017. 16 pins, configurable pull-ups, default address 0x20-0x27.
+    Mcp23017,
+    /// NXP/TI PCF8574. 8 quasi-bidirectional pins with weak pull-ups, default address
+    /// 0x20-0x27, or 0x38-0x3F for the PCF8574A.
+    Pcf8574,
+}
+
+impl Model {
+    /// Returns the number of pins.
+    pub fn pins(self) -> u8 {
+        match self {
+            Model::Mcp23017 => 16,
+            Model::Pcf8574 => 8,
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Model::Mcp23017 => write!(f, "MCP23017"),
+            Model::Pcf8574 => write!(f, "PCF8574"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct InterruptState {
+    pin: Option<gpio::InputPin>,
+    // Set while a thread is waiting for INT. The pin is taken out of the state in
+    // the meantime, so the lock isn't held while blocking.
+    polling: bool,
+    // Number of threads waiting to replace or remove the pin, which take priority
+    // over starting a new wait for INT
+    pending_changes: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct ExpanderState {
+    chip: Mutex<Chip>,
+    // Kept separate from chip, so other pins can be accessed while we're waiting
+    // for an interrupt
+    interrupt: Mutex<InterruptState>,
+    // Notified whenever the thread that's waiting for INT is done
+    interrupt_done: Condvar,
+}
+
+/// Provides access to the pins of an I2C GPIO expander.
+///
+/// `Expander` can be cloned cheaply, and shared between threads. All clones, as
+/// well as any pins retrieved through [`get`], refer to the same device.
+///
+/// When the device is initialized, all pins are configured as inputs with
+/// interrupts and pull-ups disabled.
+///
+/// [`get`]: #method.get
+#[derive(Clone, Debug)]
+pub struct Expander {
+    state: Arc<ExpanderState>,
+    model: Model,
+}
+
+impl Expander {
+    /// Constructs a new `Expander` for the device at the specified slave address.
+    pub fn new(mut i2c: I2c, model: Model, address: u16) -> Result<Expander> {
+        i2c.set_slave_address(address)?;
+
+        Ok(Expander {
+            state: Arc::new(ExpanderState {
+                chip: Mutex::new(Chip::new(i2c, model)?),
+                interrupt: Mutex::new(InterruptState::default()),
+                interrupt_done: Condvar::new(),
+            }),
+            model,
+        })
+    }
+
+    /// Returns the expander model.
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// Returns a [`Pin`] for the specified pin number.
+    ///
+    /// Retrieving a pin that's already in use returns [`Error::PinNotAvailable`].
+    /// The pin becomes available again when the `Pin`, or the `InputPin` or
+    /// `OutputPin` it was converted into, goes out of scope.
+    ///
+    /// [`Pin`]: struct.Pin.html
+    /// [`Error::PinNotAvailable`]: enum.Error.html#variant.PinNotAvailable
+    pub fn get(&self, pin: u8) -> Result<Pin> {
+        self.state.chip.lock().unwrap().take(pin)?;
+
+        Ok(Pin::new(pin, self.state.clone()))
+    }
+
+    /// Reads the logic levels of all pins, where bit 0 contains the level of pin 0.
+    pub fn read_levels(&self) -> Result<u16> {
+        self.state.chip.lock().unwrap().read_levels()
+    }
+
+    /// Configures the GPIO pin connected to the expander's INT output.
+    ///
+    /// The pin's built-in pull-up resistor is enabled, since INT is an open-drain
+    /// output on the PCF8574, and configured as open-drain on the MCP23017.
+    ///
+    /// If another thread is currently waiting for an interrupt, the pin is
+    /// replaced once that wait ends, which takes at most 100 ms.
+    pub fn set_interrupt_pin(&self, mut pin: gpio::InputPin) -> Result<()> {
+        pin.set_pullupdown(gpio::PullUpDown::PullUp);
+        pin.set_interrupt(gpio::Trigger::FallingEdge)?;
+
+        self.state
+            .change_interrupt(|interrupt| interrupt.pin = Some(pin));
+
+        Ok(())
+    }
+
+    /// Removes the interrupt pin, and returns it.
+    ///
+    /// Any threads that are blocked in [`InputPin::poll_interrupt`] return
+    /// [`Error::InterruptPinNotConfigured`] within 100 ms.
+    ///
+    /// [`InputPin::poll_interrupt`]: struct.InputPin.html#method.poll_interrupt
+    /// [`Error::InterruptPinNotConfigured`]: enum.Error.html#variant.InterruptPinNotConfigured
+    pub fn clear_interrupt_pin(&self) -> Result<Option<gpio::InputPin>> {
+        if let Some(mut pin) = self
+            .state
+            .change_interrupt(|interrupt| interrupt.pin.take())
+        {
+            pin.clear_interrupt()?;
+            pin.set_pullupdown(gpio::PullUpDown::Off);
+
+            return Ok(Some(pin));
+        }
+
+        Ok(None)
+    }
+}
+
+impl ExpanderState {
+    // Modifies the interrupt state once no other thread is waiting for INT
+    fn change_interrupt<T>(&self, f: impl FnOnce(&mut InterruptState) -> T) -> T {
+        let mut interrupt = self.interrupt.lock().unwrap();
+        interrupt.pending_changes += 1;
+        while interrupt.polling {
+            interrupt = self.interrupt_done.wait(interrupt).unwrap();
+        }
+
+        let result = f(&mut interrupt);
+        interrupt.pending_changes -= 1;
+        drop(interrupt);
+        self.interrupt_done.notify_all();
+
+        result
+    }
+
+    // Blocks until an event is queued for the specified pin, servicing the
+    // expander's interrupts for all pins in the meantime
+    pub(crate) fn poll_interrupt(
+        &self,
+        pin: u8,
+        timeout: Option<Duration>,
+    ) -> Result<Option<gpio::Event>> {
+        let start = Instant::now();
+
+        loop {
+            if let Some(event) = self.chip.lock().unwrap().pop_event(pin) {
+                return Ok(Some(event));
+            }
+
+            let remaining = match timeout {
+                Some(timeout) => match timeout.checked_sub(start.elapsed()) {
+                    Some(remaining) => Some(remaining),
+                    None => return Ok(None),
+                },
+                None => None,
+            };
+
+            let mut interrupt = self.interrupt.lock().unwrap();
+            if interrupt.polling || interrupt.pending_changes > 0 {
+                // Another thread is already waiting for INT, and services the
+                // interrupt for all pins, or the pin is about to be changed. Check
+                // our queue again once that's done.
+                let interrupt = match remaining {
+                    Some(remaining) => {
+                        self.interrupt_done
+                            .wait_timeout(interrupt, remaining)
+                            .unwrap()
+                            .0
+                    }
+                    None => self.interrupt_done.wait(interrupt).unwrap(),
+                };
+                drop(interrupt);
+
+                continue;
+            }
+
+            let mut interrupt_pin = match interrupt.pin.take() {
+                Some(interrupt_pin) => interrupt_pin,
+                None => return Err(Error::InterruptPinNotConfigured),
+            };
+            interrupt.polling = true;
+            drop(interrupt);
+
+            let slice =
+                remaining.map_or(INTERRUPT_SLICE, |remaining| remaining.min(INTERRUPT_SLICE));
+            let result = self.service_interrupt(&mut interrupt_pin, slice);
+
+            let mut interrupt = self.interrupt.lock().unwrap();
+            interrupt.pin = Some(interrupt_pin);
+            interrupt.polling = false;
+            drop(interrupt);
+            self.interrupt_done.notify_all();
+
+            result?;
+        }
+    }
+
+    // Waits up to timeout for INT to be asserted, and services the interrupt
+    fn service_interrupt(
+        &self,
+        interrupt_pin: &mut gpio::InputPin,
+        timeout: Duration,
+    ) -> Result<()> {
+        // INT stays asserted until the interrupt is serviced, so check the
+        // current level first in case we missed the edge
+        let timestamp = if interrupt_pin.read() == gpio::Level::Low {
+            None
+        } else {
+            match interrupt_pin.poll_interrupt(false, Some(timeout))? {
+                Some(event) => Some(event.timestamp()),
+                None => return Ok(()),
+            }
+        };
+
+        self.chip.lock().unwrap().service_interrupt(timestamp)
+    }
+}
@@ -0,0 +1,280 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::delay::get_time_ns;
+use crate::expander::{Error, Model, Result};
+use crate::gpio::{Event, Level, Trigger};
+use crate::i2c::I2c;
+
+// MCP23017 registers, with IOCON.BANK = 0, where the port B register immediately
+// follows its port A counterpart
+const MCP_IODIR: u8 = 0x00;
+const MCP_GPINTEN: u8 = 0x04;
+const MCP_INTCON: u8 = 0x08;
+const MCP_IOCON: u8 = 0x0a;
+const MCP_GPPU: u8 = 0x0c;
+const MCP_INTF: u8 = 0x0e;
+const MCP_INTCAP: u8 = 0x10;
+const MCP_GPIO: u8 = 0x12;
+const MCP_OLAT: u8 = 0x14;
+
+// Mirror INTA and INTB, and configure them as open-drain outputs
+const MCP_IOCON_MIRROR: u8 = 0x40;
+const MCP_IOCON_ODR: u8 = 0x04;
+
+// Maximum number of queued interrupt events per pin
+const QUEUE_CAPACITY: usize = 16;
+
+// Register cache and interrupt state for a single expander. Bit n of every
+// mask refers to pin n.
+#[derive(Debug)]
+pub(crate) struct Chip {
+    i2c: I2c,
+    model: Model,
+    taken: u16,
+    inputs: u16,
+    pullups: u16,
+    outputs: u16,
+    interrupts: u16,
+    levels: u16,
+    triggers: [Trigger; 16],
+    events: Vec<VecDeque<Event>>,
+}
+
+impl Chip {
+    pub(crate) fn new(i2c: I2c, model: Model) -> Result<Chip> {
+        let mut chip = Chip {
+            i2c,
+            model,
+            taken: 0,
+            inputs: 0xffff,
+            pullups: 0,
+            outputs: 0,
+            interrupts: 0,
+            levels: 0,
+            triggers: [Trigger::Disabled; 16],
+            events: (0..16).map(|_| VecDeque::new()).collect(),
+        };
+
+        match model {
+            Model::Mcp23017 => {
+                chip.write_register(MCP_IOCON, MCP_IOCON_MIRROR | MCP_IOCON_ODR)?;
+                chip.write_register16(MCP_IODIR, chip.inputs)?;
+                chip.write_register16(MCP_GPPU, chip.pullups)?;
+                chip.write_register16(MCP_GPINTEN, chip.interrupts)?;
+                // Compare against the previous value, which triggers on both edges
+                chip.write_register16(MCP_INTCON, 0)?;
+                chip.write_register16(MCP_OLAT, chip.outputs)?;
+            }
+            Model::Pcf8574 => {
+                chip.write_port()?;
+                chip.levels = chip.read_levels()?;
+            }
+        }
+
+        Ok(chip)
+    }
+
+    pub(crate) fn take(&mut self, pin: u8) -> Result<()> {
+        if pin >= self.model.pins() {
+            return Err(Error::InvalidPin(pin));
+        }
+
+        if self.taken & (1 << pin) > 0 {
+            return Err(Error::PinNotAvailable(pin));
+        }
+
+        self.taken |= 1 << pin;
+
+        Ok(())
+    }
+
+    // Reconfigures the pin as an input with pull-ups and interrupts disabled
+    pub(crate) fn release(&mut self, pin: u8) -> Result<()> {
+        self.taken &= !(1 << pin);
+
+        self.set_interrupt(pin, Trigger::Disabled)?;
+        self.set_mode(pin, true, false)
+    }
+
+    pub(crate) fn set_mode(&mut self, pin: u8, input: bool, pullup: bool) -> Result<()> {
+        let mask = 1 << pin;
+
+        self.inputs = if input {
+            self.inputs | mask
+        } else {
+            self.inputs & !mask
+        };
+
+        self.pullups = if pullup {
+            self.pullups | mask
+        } else {
+            self.pullups & !mask
+        };
+
+        match self.model {
+            Model::Mcp23017 => {
+                self.write_register16(MCP_GPPU, self.pullups)?;
+                self.write_register16(MCP_IODIR, self.inputs)
+            }
+            Model::Pcf8574 => self.write_port(),
+        }
+    }
+
+    pub(crate) fn write(&mut self, pin: u8, level: Level) -> Result<()> {
+        self.outputs = match level {
+            Level::High => self.outputs | (1 << pin),
+            Level::Low => self.outputs & !(1 << pin),
+        };
+
+        match self.model {
+            Model::Mcp23017 => self.write_register16(MCP_OLAT, self.outputs),
+            Model::Pcf8574 => self.write_port(),
+        }
+    }
+
+    pub(crate) fn output_level(&self, pin: u8) -> Level {
+        if self.outputs & (1 << pin) > 0 {
+            Level::High
+        } else {
+            Level::Low
+        }
+    }
+
+    pub(crate) fn read_levels(&mut self) -> Result<u16> {
+        match self.model {
+            Model::Mcp23017 => self.read_register16(MCP_GPIO),
+            Model::Pcf8574 => {
+                let mut buffer = [0u8; 1];
+                self.i2c.read(&mut buffer)?;
+
+                Ok(u16::from(buffer[0]))
+            }
+        }
+    }
+
+    pub(crate) fn set_interrupt(&mut self, pin: u8, trigger: Trigger) -> Result<()> {
+        let mask = 1 << pin;
+
+        self.triggers[pin as usize] = trigger;
+        self.events[pin as usize].clear();
+
+        self.interrupts = if trigger == Trigger::Disabled {
+            self.interrupts & !mask
+        } else {
+            self.interrupts | mask
+        };
+
+        match self.model {
+            Model::Mcp23017 => {
+                self.write_register16(MCP_GPINTEN, self.interrupts)?;
+                // Clear any pending interrupts
+                self.read_register16(MCP_INTCAP)?;
+            }
+            Model::Pcf8574 => {
+                self.levels = self.read_levels()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn pop_event(&mut self, pin: u8) -> Option<Event> {
+        self.events[pin as usize].pop_front()
+    }
+
+    // Reads the interrupt flags and captured levels, which clears the interrupt,
+    // and queues an event for every pin that changed to a level that matches
+    // its trigger
+    pub(crate) fn service_interrupt(&mut self, timestamp: Option<Duration>) -> Result<()> {
+        let timestamp = timestamp.unwrap_or_else(|| Duration::from_nanos(get_time_ns() as u64));
+
+        let (changed, levels) = match self.model {
+            Model::Mcp23017 => {
+                let flags = self.read_register16(MCP_INTF)?;
+                let levels = self.read_register16(MCP_INTCAP)?;
+
+                (flags, levels)
+            }
+            Model::Pcf8574 => {
+                let levels = self.read_levels()?;
+                let changed = (levels ^ self.levels) & self.inputs;
+                self.levels = levels;
+
+                (changed, levels)
+            }
+        };
+
+        for pin in 0..self.model.pins() {
+            let mask = 1 << pin;
+            if (changed & self.interrupts & mask) == 0 {
+                continue;
+            }
+
+            let trigger = if levels & mask > 0 {
+                Trigger::RisingEdge
+            } else {
+                Trigger::FallingEdge
+            };
+
+            let configured = self.triggers[pin as usize];
+            if configured == Trigger::Both || configured == trigger {
+                let events = &mut self.events[pin as usize];
+                if events.len() < QUEUE_CAPACITY {
+                    events.push_back(Event::new(pin, trigger, timestamp));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // PCF8574 pins are quasi-bidirectional. Writing a 1 enables a weak pull-up,
+    // which allows the pin to be used as an input.
+    fn write_port(&mut self) -> Result<()> {
+        let port = (self.outputs & !self.inputs) | self.inputs;
+        self.i2c.write(&[port as u8])?;
+
+        Ok(())
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) -> Result<()> {
+        self.i2c.write(&[register, value])?;
+
+        Ok(())
+    }
+
+    fn write_register16(&mut self, register: u8, value: u16) -> Result<()> {
+        self.i2c
+            .write(&[register, value as u8, (value >> 8) as u8])?;
+
+        Ok(())
+    }
+
+    fn read_register16(&mut self, register: u8) -> Result<u16> {
+        let mut buffer = [0u8; 2];
+        self.i2c.write_read(&[register], &mut buffer)?;
+
+        Ok(u16::from(buffer[0]) | (u16::from(buffer[1]) << 8))
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use embedded_hal::digital::v2;
+
+use crate::expander::{Error, InputPin, OutputPin};
+
+impl v2::InputPin for InputPin {
+    type Error = Error;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        InputPin::is_high(self)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        InputPin::is_low(self)
+    }
+}
+
+impl v2::OutputPin for OutputPin {
+    type Error = Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        OutputPin::set_low(self)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        OutputPin::set_high(self)
+    }
+}
+
+impl v2::StatefulOutputPin for OutputPin {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(OutputPin::is_set_high(self))
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(OutputPin::is_set_low(self))
+    }
+}
+
+impl v2::ToggleableOutputPin for OutputPin {
+    type Error = Error;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        OutputPin::toggle(self)
+    }
+}
@@ -0,0 +1,308 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::expander::{ExpanderState, Result};
+use crate::gpio::{Event, Level, Trigger};
+
+/// Unconfigured expander pin.
+///
+/// `Pin`s are retrieved through [`Expander::get`], and can be converted into an
+/// [`InputPin`] or [`OutputPin`].
+///
+/// [`Expander::get`]: struct.Expander.html#method.get
+/// [`InputPin`]: struct.InputPin.html
+/// [`OutputPin`]: struct.OutputPin.html
+#[derive(Debug)]
+pub struct Pin {
+    pin: u8,
+    state: Arc<ExpanderState>,
+}
+
+impl Pin {
+    pub(crate) fn new(pin: u8, state: Arc<ExpanderState>) -> Pin {
+        Pin { pin, state }
+    }
+
+    /// Returns the pin number.
+    pub fn pin(&self) -> u8 {
+        self.pin
+    }
+
+    /// Reads the pin's logic level.
+    pub fn read(&self) -> Result<Level> {
+        let levels = self.state.chip.lock().unwrap().read_levels()?;
+
+        Ok(if levels & (1 << self.pin) > 0 {
+            Level::High
+        } else {
+            Level::Low
+        })
+    }
+
+    /// Consumes the `Pin`, and returns an [`InputPin`], with its pull-up disabled.
+    ///
+    /// On the PCF8574, inputs always have a weak pull-up enabled.
+    ///
+    /// [`InputPin`]: struct.InputPin.html
+    pub fn into_input(self) -> Result<InputPin> {
+        self.state
+            .chip
+            .lock()
+            .unwrap()
+            .set_mode(self.pin, true, false)?;
+
+        Ok(InputPin { pin: self })
+    }
+
+    /// Consumes the `Pin`, and returns an [`InputPin`], with its pull-up enabled.
+    ///
+    /// [`InputPin`]: struct.InputPin.html
+    pub fn into_input_pullup(self) -> Result<InputPin> {
+        self.state
+            .chip
+            .lock()
+            .unwrap()
+            .set_mode(self.pin, true, true)?;
+
+        Ok(InputPin { pin: self })
+    }
+
+    /// Consumes the `Pin`, and returns an [`OutputPin`], without changing its
+    /// output latch.
+    ///
+    /// The PCF8574 can only sink a meaningful amount of current. When the
+    /// output is set high, it's pulled up by a weak current source.
+    ///
+    /// [`OutputPin`]: struct.OutputPin.html
+    pub fn into_output(self) -> Result<OutputPin> {
+        self.state
+            .chip
+            .lock()
+            .unwrap()
+            .set_mode(self.pin, false, false)?;
+
+        Ok(OutputPin { pin: self })
+    }
+
+    /// Consumes the `Pin`, and returns an [`OutputPin`] that's set low.
+    ///
+    /// [`OutputPin`]: struct.OutputPin.html
+    pub fn into_output_low(self) -> Result<OutputPin> {
+        self.state
+            .chip
+            .lock()
+            .unwrap()
+            .write(self.pin, Level::Low)?;
+
+        self.into_output()
+    }
+
+    /// Consumes the `Pin`, and returns an [`OutputPin`] that's set high.
+    ///
+    /// [`OutputPin`]: struct.OutputPin.html
+    pub fn into_output_high(self) -> Result<OutputPin> {
+        self.state
+            .chip
+            .lock()
+            .unwrap()
+            .write(self.pin, Level::High)?;
+
+        self.into_output()
+    }
+}
+
+impl Pin {
+    // Reconfigures the pin as an input with its pull-up and interrupt disabled,
+    // the same way it's reset when it's released.
+    fn reset(&self) -> Result<()> {
+        let mut chip = self.state.chip.lock().unwrap();
+
+        chip.set_interrupt(self.pin, Trigger::Disabled)?;
+        chip.set_mode(self.pin, true, false)
+    }
+}
+
+impl Drop for Pin {
+    fn drop(&mut self) {
+        if let Ok(mut chip) = self.state.chip.lock() {
+            let _ = chip.release(self.pin);
+        }
+    }
+}
+
+/// Expander pin configured as input.
+///
+/// The pin is reconfigured as an input with its pull-up and interrupt disabled,
+/// and becomes available again, when `InputPin` goes out of scope.
+#[derive(Debug)]
+pub struct InputPin {
+    pin: Pin,
+}
+
+impl InputPin {
+    /// Returns the pin number.
+    pub fn pin(&self) -> u8 {
+        self.pin.pin
+    }
+
+    /// Reads the pin's logic level.
+    pub fn read(&self) -> Result<Level> {
+        self.pin.read()
+    }
+
+    /// Returns `true` if the pin's logic level is low.
+    pub fn is_low(&self) -> Result<bool> {
+        Ok(self.read()? == Level::Low)
+    }
+
+    /// Returns `true` if the pin's logic level is high.
+    pub fn is_high(&self) -> Result<bool> {
+        Ok(self.read()? == Level::High)
+    }
+
+    /// Enables interrupt-on-change for the pin.
+    ///
+    /// Events for this pin are retrieved with [`poll_interrupt`]. Any pending
+    /// events are discarded.
+    ///
+    /// [`poll_interrupt`]: #method.poll_interrupt
+    pub fn set_interrupt(&mut self, trigger: Trigger) -> Result<()> {
+        self.pin
+            .state
+            .chip
+            .lock()
+            .unwrap()
+            .set_interrupt(self.pin.pin, trigger)
+    }
+
+    /// Disables interrupt-on-change for the pin.
+    pub fn clear_interrupt(&mut self) -> Result<()> {
+        self.set_interrupt(Trigger::Disabled)
+    }
+
+    /// Blocks until an interrupt is triggered on this pin, or a timeout occurs.
+    ///
+    /// Requires the expander's INT output to be connected to a GPIO pin, which
+    /// is configured with [`Expander::set_interrupt_pin`]. Interrupts for other
+    /// expander pins that occur while polling are queued for those pins.
+    ///
+    /// `timeout` can be set to `None` to wait indefinitely. Returns `Ok(None)`
+    /// if a timeout occurred. The [`Event`] contains the expander pin number,
+    /// and the kernel timestamp of the INT pin's falling edge. If the interrupt
+    /// was already pending when `poll_interrupt` was called, the current time is
+    /// used instead.
+    ///
+    /// [`Expander::set_interrupt_pin`]: struct.Expander.html#method.set_interrupt_pin
+    /// [`Event`]: ../gpio/struct.Event.html
+    pub fn poll_interrupt(&mut self, timeout: Option<Duration>) -> Result<Option<Event>> {
+        self.pin.state.poll_interrupt(self.pin.pin, timeout)
+    }
+
+    /// Consumes the `InputPin`, and returns the underlying [`Pin`] without
+    /// releasing it.
+    ///
+    /// The pin's pull-up and interrupt are disabled, so it can be reconfigured
+    /// without calling [`Expander::get`] again.
+    ///
+    /// [`Pin`]: struct.Pin.html
+    /// [`Expander::get`]: struct.Expander.html#method.get
+    pub fn into_pin(self) -> Result<Pin> {
+        self.pin.reset()?;
+
+        Ok(self.pin)
+    }
+}
+
+/// Expander pin configured as output.
+///
+/// The pin is reconfigured as an input, and becomes available again, when
+/// `OutputPin` goes out of scope.
+#[derive(Debug)]
+pub struct OutputPin {
+    pin: Pin,
+}
+
+impl OutputPin {
+    /// Returns the pin number.
+    pub fn pin(&self) -> u8 {
+        self.pin.pin
+    }
+
+    /// Returns `true` if the pin's output latch is set low.
+    pub fn is_set_low(&self) -> bool {
+        self.pin
+            .state
+            .chip
+            .lock()
+            .unwrap()
+            .output_level(self.pin.pin)
+            == Level::Low
+    }
+
+    /// Returns `true` if the pin's output latch is set high.
+    pub fn is_set_high(&self) -> bool {
+        !self.is_set_low()
+    }
+
+    /// Sets the pin's output level.
+    pub fn write(&mut self, level: Level) -> Result<()> {
+        self.pin
+            .state
+            .chip
+            .lock()
+            .unwrap()
+            .write(self.pin.pin, level)
+    }
+
+    /// Sets the pin's output level to low.
+    pub fn set_low(&mut self) -> Result<()> {
+        self.write(Level::Low)
+    }
+
+    /// Sets the pin's output level to high.
+    pub fn set_high(&mut self) -> Result<()> {
+        self.write(Level::High)
+    }
+
+    /// Toggles the pin's output level between low and high.
+    pub fn toggle(&mut self) -> Result<()> {
+        if self.is_set_low() {
+            self.set_high()
+        } else {
+            self.set_low()
+        }
+    }
+    /// Consumes the `OutputPin`, and returns the underlying [`Pin`] without
+    /// releasing it.
+    ///
+    /// The pin is switched back to an input with its pull-up disabled, so it
+    /// can be reconfigured without calling [`Expander::get`] again.
+    ///
+    /// [`Pin`]: struct.Pin.html
+    /// [`Expander::get`]: struct.Expander.html#method.get
+    pub fn into_pin(self) -> Result<Pin> {
+        self.pin.reset()?;
+
+        Ok(self.pin)
+    }
+}
@@ -0,0 +1,514 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Temperature-controlled cooling fans.
+//!
+//! [`Fan`] periodically reads the SoC temperature through [`Health`], and adjusts
+//! the speed of a cooling fan on a background thread. The fan can be driven by a
+//! hardware PWM channel, by software-based PWM on any available GPIO pin, or
+//! simply switched on and off through a GPIO pin.
+//!
+//! Two control strategies are available:
+//!
+//! * [`Control::Hysteresis`] runs the fan at full speed once the temperature
+//!   reaches the `on` threshold, and switches it off after it falls to the `off`
+//!   threshold. This works with any fan, including fans without a PWM input.
+//! * [`Control::Pid`] continuously adjusts the fan speed to keep the temperature
+//!   at the `target`, which results in less noise from PWM-controlled fans.
+//!
+//! Most fans stall below a certain duty cycle. [`Fan::set_min_duty_cycle`]
+//! sets the lowest duty cycle the fan is driven at while it's running.
+//!
+//! Fans draw more current than a GPIO pin can supply, so they need to be
+//! connected through a transistor or MOSFET, unless they have a separate PWM
+//! input, such as 4-pin PC fans and the official Raspberry Pi 4 Case Fan.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::fan::{Control, Fan};
+//! use rppal::pwm::{Channel, Pwm};
+//!
+//! # fn main() -> rppal::fan::Result<()> {
+//! // Keep the SoC at 55 °C
+//! let fan = Fan::new(
+//!     Pwm::new(Channel::Pwm0)?,
+//!     Control::Pid {
+//!         target: 55.0,
+//!         kp: 0.1,
+//!         ki: 0.01,
+//!         kd: 0.0,
+//!     },
+//! )?;
+//!
+//! // ...
+//!
+//! println!(
+//!     "{:.1} °C, fan at {:.0}%",
+//!     fan.temperature().unwrap_or(0.0),
+//!     fan.duty_cycle() * 100.0
+//! );
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Fan`]: struct.Fan.html
+//! [`Health`]: ../system/struct.Health.html
+//! [`Control::Hysteresis`]: enum.Control.html#variant.Hysteresis
+//! [`Control::Pid`]: enum.Control.html#variant.Pid
+//! [`Fan::set_min_duty_cycle`]: struct.Fan.html#method.set_min_duty_cycle
+
+use std::result;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use quick_error::quick_error;
+
+use crate::gpio::{self, OutputPin};
+use crate::pwm::{self, Pwm};
+use crate::system::{self, Health};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_PWM_FREQUENCY: f64 = 25_000.0;
+const DEFAULT_SOFT_PWM_FREQUENCY: f64 = 100.0;
+// With on/off drive, the fan is switched on at or above this duty cycle
+const ON_OFF_THRESHOLD: f64 = 0.5;
+
+quick_error! {
+/// Errors that can occur when controlling a fan.
+    #[derive(Debug)]
+    pub enum Error {
+/// PWM error.
+        Pwm(err: pwm::Error) { description(err.description()) from() }
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Error reading the SoC temperature.
+        System(err: system::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Invalid control parameters.
+///
+/// The `off` threshold of [`Control::Hysteresis`] needs to be lower than the
+/// `on` threshold, the gains of [`Control::Pid`] can't be negative, and all
+/// values need to be finite.
+///
+/// [`Control::Hysteresis`]: enum.Control.html#variant.Hysteresis
+/// [`Control::Pid`]: enum.Control.html#variant.Pid
+        InvalidControl { description("invalid control parameters") }
+    }
+}
+
+/// Result type returned from methods that can have `fan::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// Fan control strategies.
+///
+/// All temperatures are specified in degrees Celsius (°C).
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Control {
+    /// Runs the fan at full speed when the temperature reaches `on`, and switches
+    /// it off when the temperature falls to `off`.
+    Hysteresis { on: f64, off: f64 },
+    /// Adjusts the fan speed to keep the temperature at `target`.
+    ///
+    /// The duty cycle is calculated from the difference between the measured
+    /// and the target temperature, multiplied by the proportional gain `kp`,
+    /// the integral gain `ki` (per second) and the derivative gain `kd` (in
+    /// seconds). For instance, with `kp` set to `0.1`, the fan runs at full
+    /// speed when the temperature is 10 °C above the target.
+    Pid {
+        target: f64,
+        kp: f64,
+        ki: f64,
+        kd: f64,
+    },
+}
+
+impl Control {
+    fn validate(self) -> Result<Control> {
+        let valid = match self {
+            Control::Hysteresis { on, off } => on.is_finite() && off.is_finite() && off < on,
+            Control::Pid { target, kp, ki, kd } => {
+                target.is_finite()
+                    && [kp, ki, kd]
+                        .iter()
+                        .all(|gain| gain.is_finite() && *gain >= 0.0)
+            }
+        };
+
+        if valid {
+            Ok(self)
+        } else {
+            Err(Error::InvalidControl)
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Output {
+    Pwm(Pwm),
+    Soft(OutputPin),
+    OnOff(OutputPin),
+}
+
+impl Output {
+    fn set(&mut self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        match self {
+            Output::Pwm(pwm) => {
+                pwm.set_frequency(frequency, duty_cycle)?;
+                pwm.enable()?;
+            }
+            Output::Soft(pin) if duty_cycle <= 0.0 => {
+                pin.clear_pwm()?;
+                pin.set_low();
+            }
+            Output::Soft(pin) if duty_cycle >= 1.0 => {
+                pin.clear_pwm()?;
+                pin.set_high();
+            }
+            Output::Soft(pin) => pin.set_pwm_frequency(frequency, duty_cycle)?,
+            Output::OnOff(pin) if duty_cycle >= ON_OFF_THRESHOLD => pin.set_high(),
+            Output::OnOff(pin) => pin.set_low(),
+        }
+
+        Ok(())
+    }
+
+    fn off(&mut self) -> Result<()> {
+        match self {
+            Output::Pwm(pwm) => pwm.disable()?,
+            Output::Soft(pin) => {
+                pin.clear_pwm()?;
+                pin.set_low();
+            }
+            Output::OnOff(pin) => pin.set_low(),
+        }
+
+        Ok(())
+    }
+}
+
+// Controller state that's carried over between updates
+#[derive(Debug, Default)]
+struct Controller {
+    running: bool,
+    integral: f64,
+    prev_error: Option<f64>,
+}
+
+impl Controller {
+    fn update(&mut self, control: Control, temperature: f64, dt: f64) -> f64 {
+        match control {
+            Control::Hysteresis { on, off } => {
+                if temperature >= on {
+                    self.running = true;
+                } else if temperature <= off {
+                    self.running = false;
+                }
+
+                if self.running {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Control::Pid { target, kp, ki, kd } => {
+                let error = temperature - target;
+
+                // Limit the integral term to the output range to prevent windup
+                if ki > 0.0 {
+                    self.integral = (self.integral + error * dt).clamp(0.0, 1.0 / ki);
+                }
+
+                let derivative = match self.prev_error {
+                    Some(prev_error) if dt > 0.0 => (error - prev_error) / dt,
+                    _ => 0.0,
+                };
+                self.prev_error = Some(error);
+
+                (kp * error + ki * self.integral + kd * derivative).clamp(0.0, 1.0)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    control: Control,
+    interval: Duration,
+    frequency: f64,
+    min_duty_cycle: f64,
+    temperature: Option<f64>,
+    duty_cycle: f64,
+    error: Option<Error>,
+    changed: bool,
+    reset_controller: bool,
+    shutdown: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+/// Temperature-controlled cooling fan.
+///
+/// The control loop runs on a background thread, which updates the fan speed
+/// at a fixed interval. The fan is switched off when `Fan` goes out of scope.
+///
+/// More information can be found in the [module documentation].
+///
+/// [module documentation]: index.html
+#[derive(Debug)]
+pub struct Fan {
+    shared: Arc<Shared>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Fan {
+    /// Constructs a new `Fan` driven by a hardware PWM channel, at a frequency
+    /// of 25 kHz.
+    pub fn new(pwm: Pwm, control: Control) -> Result<Fan> {
+        Fan::with_output(Output::Pwm(pwm), DEFAULT_PWM_FREQUENCY, control)
+    }
+
+    /// Constructs a new `Fan` driven by software-based PWM on the specified pin,
+    /// at a frequency of 100 Hz.
+    pub fn with_soft_pwm(pin: OutputPin, control: Control) -> Result<Fan> {
+        Fan::with_output(Output::Soft(pin), DEFAULT_SOFT_PWM_FREQUENCY, control)
+    }
+
+    /// Constructs a new `Fan` that's switched on and off through the specified pin.
+    ///
+    /// With [`Control::Pid`], the fan is switched on while the calculated duty
+    /// cycle is 50% or more.
+    ///
+    /// [`Control::Pid`]: enum.Control.html#variant.Pid
+    pub fn with_gpio(pin: OutputPin, control: Control) -> Result<Fan> {
+        Fan::with_output(Output::OnOff(pin), DEFAULT_PWM_FREQUENCY, control)
+    }
+
+    fn with_output(mut output: Output, frequency: f64, control: Control) -> Result<Fan> {
+        let control = control.validate()?;
+        output.off()?;
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                control,
+                interval: DEFAULT_INTERVAL,
+                frequency,
+                min_duty_cycle: 0.0,
+                temperature: None,
+                duty_cycle: 0.0,
+                error: None,
+                changed: false,
+                reset_controller: false,
+                shutdown: false,
+            }),
+            changed: Condvar::new(),
+        });
+
+        let thread_shared = shared.clone();
+        let thread = thread::spawn(move || {
+            Fan::run(&thread_shared, output);
+        });
+
+        Ok(Fan {
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    fn run(shared: &Shared, mut output: Output) {
+        let health = Health::new();
+        let mut controller = Controller::default();
+        let mut last_update: Option<Instant> = None;
+        let mut state = shared.state.lock().unwrap();
+
+        while !state.shutdown {
+            state.changed = false;
+            if state.reset_controller {
+                state.reset_controller = false;
+                controller = Controller::default();
+            }
+
+            // Don't hold the lock while reading the temperature, which might
+            // involve a mailbox request
+            let (control, frequency, min_duty_cycle) =
+                (state.control, state.frequency, state.min_duty_cycle);
+            drop(state);
+
+            let now = Instant::now();
+            let dt = last_update.map_or(0.0, |last| (now - last).as_secs_f64());
+            last_update = Some(now);
+
+            let result = health
+                .temperature()
+                .map_err(Error::from)
+                .and_then(|temperature| {
+                    let duty_cycle = match controller.update(control, temperature, dt) {
+                        duty_cycle if duty_cycle <= 0.0 => 0.0,
+                        duty_cycle => duty_cycle.max(min_duty_cycle),
+                    };
+
+                    output.set(frequency, duty_cycle)?;
+
+                    Ok((temperature, duty_cycle))
+                });
+
+            state = shared.state.lock().unwrap();
+            match result {
+                Ok((temperature, duty_cycle)) => {
+                    state.temperature = Some(temperature);
+                    state.duty_cycle = duty_cycle;
+                }
+                Err(e) => {
+                    state.error.get_or_insert(e);
+                }
+            }
+
+            let deadline = now + state.interval;
+            loop {
+                let now = Instant::now();
+                if state.shutdown || state.changed || now >= deadline {
+                    break;
+                }
+
+                state = shared
+                    .changed
+                    .wait_timeout(state, deadline - now)
+                    .unwrap()
+                    .0;
+            }
+        }
+
+        let _ = output.off();
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.shared.state.lock().unwrap()
+    }
+
+    // Applies a configuration change, and wakes up the control loop so it's
+    // applied immediately.
+    fn update<F: FnOnce(&mut State)>(&self, f: F) {
+        let mut state = self.state();
+        f(&mut state);
+        state.changed = true;
+        self.shared.changed.notify_all();
+    }
+
+    /// Returns the control strategy.
+    pub fn control(&self) -> Control {
+        self.state().control
+    }
+
+    /// Sets the control strategy, and resets the controller state.
+    pub fn set_control(&self, control: Control) -> Result<()> {
+        let control = control.validate()?;
+        self.update(|state| {
+            state.control = control;
+            state.reset_controller = true;
+        });
+
+        Ok(())
+    }
+
+    /// Returns the interval between updates.
+    pub fn interval(&self) -> Duration {
+        self.state().interval
+    }
+
+    /// Sets the interval between updates.
+    ///
+    /// The SoC temperature changes slowly, so updating more than once per second
+    /// rarely improves control. By default, `interval` is set to 2 seconds.
+    pub fn set_interval(&self, interval: Duration) {
+        self.update(|state| state.interval = interval);
+    }
+
+    /// Returns the PWM frequency in herz (Hz).
+    pub fn pwm_frequency(&self) -> f64 {
+        self.state().frequency
+    }
+
+    /// Sets the PWM frequency in herz (Hz).
+    ///
+    /// By default, the frequency is set to 25 kHz for hardware PWM, as specified
+    /// for 4-pin PC fans, and 100 Hz for software-based PWM. The frequency has no
+    /// effect on fans that are switched on and off through a GPIO pin.
+    pub fn set_pwm_frequency(&self, frequency: f64) {
+        self.update(|state| state.frequency = frequency);
+    }
+
+    /// Returns the minimum duty cycle the fan is driven at while it's running.
+    pub fn min_duty_cycle(&self) -> f64 {
+        self.state().min_duty_cycle
+    }
+
+    /// Sets the minimum duty cycle (between `0.0` and `1.0`) the fan is driven at
+    /// while it's running, which prevents the fan from stalling.
+    ///
+    /// By default, `min_duty_cycle` is set to `0.0`.
+    pub fn set_min_duty_cycle(&self, min_duty_cycle: f64) {
+        let min_duty_cycle = if min_duty_cycle.is_finite() {
+            min_duty_cycle.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        self.update(|state| state.min_duty_cycle = min_duty_cycle);
+    }
+
+    /// Returns the SoC temperature in degrees Celsius (°C) measured during the
+    /// last update, or `None` if it hasn't been read successfully yet.
+    pub fn temperature(&self) -> Option<f64> {
+        self.state().temperature
+    }
+
+    /// Returns the duty cycle (between `0.0` and `1.0`) the fan was set to
+    /// during the last update.
+    pub fn duty_cycle(&self) -> f64 {
+        self.state().duty_cycle
+    }
+
+    /// Returns the first error that occurred on the background thread since
+    /// the last call to `take_error`, if any.
+    ///
+    /// The control loop keeps running after an error, and retries during the
+    /// next update.
+    pub fn take_error(&self) -> Option<Error> {
+        self.state().error.take()
+    }
+}
+
+impl Drop for Fan {
+    fn drop(&mut self) {
+        {
+            let mut state = self.state();
+            state.shutdown = true;
+            self.shared.changed.notify_all();
+        }
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
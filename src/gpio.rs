@@ -25,22 +25,93 @@
 //! GPIO interrupts are controlled using the `/dev/gpiochipN` (where N=0, 1 and 2)
 //! character device.
 //!
+//! On systems where the GPIO registers can't be memory-mapped, [`Gpio::with_backend`]
+//! with [`Backend::CharDev`] configures the pins through `/dev/gpiochipN` instead.
+//!
+//! ## Sharing Gpio
+//!
+//! [`Gpio`] is a cheap handle to a single, process-wide GPIO state. The state is
+//! initialized on the first call to [`Gpio::new`], and every subsequent call returns
+//! a handle to the same state for as long as any `Gpio` or [`Pin`] instance is
+//! alive. Cloning a `Gpio` only increments a reference count. As a result, the GPIO
+//! registers are only mapped once, and pin ownership is tracked across all handles,
+//! which lets independent libraries each hold their own `Gpio` without coordinating
+//! with each other. `Gpio` is `Send` and `Sync`.
+//!
+//! When the last handle, including any retrieved pins, goes out of scope, the state
+//! is released, and the next call to `Gpio::new` initializes it again.
+//!
 //! ## Pins
 //!
 //! Pins are addressed by their BCM numbers, rather than their
-//! physical location.
+//! physical location. Use [`Gpio::get_physical`] to retrieve a pin
+//! based on its physical location on the GPIO header instead. The header
+//! layout for each model is available through the [`header`] module.
+//!
+//! Instead of selecting one of the alternate modes by number, [`Pin::into_alt_function`]
+//! picks the appropriate mode for a peripheral [`Function`] on the detected SoC.
+//! [`alt_functions`] lists the functions that are available on each pin.
 //!
 //! By default, pins are reset to their original state when they go out of scope.
-//! Use [`InputPin::set_reset_on_drop(false)`], [`OutputPin::set_reset_on_drop(false)`]
-//! or [`AltPin::set_reset_on_drop(false)`], respectively, to disable this behavior.
+//! Use [`InputPin::set_reset_on_drop(false)`], [`OutputPin::set_reset_on_drop(false)`],
+//! [`AltPin::set_reset_on_drop(false)`] or [`IoPin::set_reset_on_drop(false)`],
+//! respectively, to disable this behavior.
 //! Note that `drop` methods aren't called when a program is abnormally terminated (for
 //! instance when a SIGINT isn't caught).
 //!
+//! [`InputPin`], [`OutputPin`], [`AltPin`] and [`IoPin`] can be converted into a
+//! different mode directly, or back into a [`Pin`] with `into_pin`, without releasing
+//! the pin. The pin is reset to its original state before it's reconfigured.
+//! [`IoPin`] can also switch between modes at runtime through `set_mode`, which is
+//! useful for bit-banged protocols that use a single line in both directions.
+//!
+//! [`InputPinLike`] and [`OutputPinLike`] are implemented by the GPIO pin types, as
+//! well as by the pins exposed through the [`expander`] module, which lets drivers
+//! accept any kind of pin, including boxed trait objects, without committing to a
+//! specific version of `embedded-hal`.
+//!
+//! [`Gpio::builder`] configures crate-wide defaults for `reset_on_drop`, the logic
+//! levels output pins are set to when they're released, and whether interrupts are
+//! cleared during cleanup. [`Gpio::cleanup`] immediately restores all pins that are
+//! in use, which simplifies shutdown handling in daemons.
+//! [`Gpio::register_cleanup_on_signal`] calls it automatically when the process
+//! receives a termination signal such as `SIGINT` or `SIGTERM`.
+//!
+//! ## Software-based PWM
+//!
+//! [`OutputPin::set_pwm`] and [`OutputPin::set_pwm_frequency`] generate a PWM
+//! signal on any available GPIO pin by toggling its logic level on a dedicated
+//! thread. Software-based PWM is inherently inaccurate on a multi-threaded OS
+//! due to scheduling/preemption. The PWM thread is assigned real-time scheduling
+//! when the application runs with superuser privileges, which improves accuracy,
+//! but expect timing to deviate by tens of microseconds under load. If an
+//! accurate or faster PWM signal is required, use the hardware [`Pwm`]
+//! peripheral instead.
+//!
+//! ## Recording and replay
+//!
+//! A [`Recorder`] attached through [`GpioBuilder::recorder`] logs every mode
+//! change, write and read with a timestamp to a file, as JSON lines. When the
+//! `mock` feature is enabled, [`MockGpio::replay`] feeds the recorded inputs back
+//! to an application, which allows regression testing without any hardware.
+//!
+//! The [`trace`] module captures output transitions and sampled inputs into a VCD
+//! file, which can be inspected in a waveform viewer such as GTKWave.
+//!
+//! ## Embedded HAL trait implementations
+//!
+//! When the optional `hal` feature is enabled, [`InputPin`], [`OutputPin`],
+//! [`AltPin`] and [`IoPin`] implement the `embedded-hal` digital traits (`InputPin`,
+//! `OutputPin`, `StatefulOutputPin` and `ToggleableOutputPin`), which lets you
+//! pass them directly to any driver written against `embedded-hal` 0.2. The
+//! `hal-1` feature adds implementations of the `embedded-hal` 1.0 digital
+//! traits (`InputPin`, `OutputPin` and `StatefulOutputPin`).
+//!
 //! ## Examples
 //!
 //! Basic example:
 //!
-//! ```
+//! ```no_run
 //! use std::thread::sleep;
 //! use std::time::Duration;
 //!
@@ -82,56 +153,352 @@
 //! [raspberrypi/linux#1225]: https://github.com/raspberrypi/linux/issues/1225
 //! [raspberrypi/linux#2289]: https://github.com/raspberrypi/linux/issues/2289
 //! [`Gpio`]: struct.Gpio.html
+//! [`Gpio::new`]: struct.Gpio.html#method.new
+//! [`Pin`]: struct.Pin.html
+//! [`Recorder`]: struct.Recorder.html
+//! [`trace`]: trace/index.html
+//! [`GpioBuilder::recorder`]: struct.GpioBuilder.html#method.recorder
+//! [`MockGpio::replay`]: struct.MockGpio.html#method.replay
+//! [`InputPinLike`]: trait.InputPinLike.html
+//! [`OutputPinLike`]: trait.OutputPinLike.html
+//! [`expander`]: ../expander/index.html
+//! [`Gpio::get_physical`]: struct.Gpio.html#method.get_physical
+//! [`header`]: header/index.html
+//! [`Gpio::with_backend`]: struct.Gpio.html#method.with_backend
+//! [`Backend::CharDev`]: enum.Backend.html#variant.CharDev
+//! [`Pin::into_alt_function`]: struct.Pin.html#method.into_alt_function
+//! [`Function`]: enum.Function.html
+//! [`alt_functions`]: fn.alt_functions.html
 //! [`InputPin::set_reset_on_drop(false)`]: struct.InputPin.html#method.set_reset_on_drop
 //! [`OutputPin::set_reset_on_drop(false)`]: struct.InputPin.html#method.set_reset_on_drop
 //! [`AltPin::set_reset_on_drop(false)`]: struct.InputPin.html#method.set_reset_on_drop
+//! [`IoPin::set_reset_on_drop(false)`]: struct.IoPin.html#method.set_reset_on_drop
+//! [`Gpio::builder`]: struct.Gpio.html#method.builder
+//! [`Gpio::cleanup`]: struct.Gpio.html#method.cleanup
+//! [`Gpio::register_cleanup_on_signal`]: struct.Gpio.html#method.register_cleanup_on_signal
 //! [`Error::InstanceExists`]: enum.Error.html#variant.InstanceExists
+//! [`OutputPin::set_pwm`]: struct.OutputPin.html#method.set_pwm
+//! [`OutputPin::set_pwm_frequency`]: struct.OutputPin.html#method.set_pwm_frequency
+//! [`Pwm`]: ../pwm/struct.Pwm.html
+//! [`InputPin`]: struct.InputPin.html
+//! [`OutputPin`]: struct.OutputPin.html
+//! [`AltPin`]: struct.AltPin.html
+//! [`IoPin`]: struct.IoPin.html
 
 use std::fmt;
 use std::io;
 use std::os::unix::io::AsRawFd;
+use std::ptr;
 use std::result;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex, Weak};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
 use std::time::Duration;
 
 use lazy_static::lazy_static;
-use quick_error::quick_error;
 
+use crate::system::{DeviceInfo, SoC};
+
+use self::trace::VcdTracer;
+
+mod alt;
+mod backend;
+mod cdev;
+mod cleanup;
 mod epoll;
+mod group;
+#[cfg(any(feature = "hal", feature = "hal-1"))]
+mod hal;
+pub mod header;
 mod interrupt;
 mod ioctl;
 mod mem;
+#[cfg(feature = "mock")]
+mod mock;
+mod multi;
+mod pads;
 mod pin;
+mod record;
+mod sched;
+pub mod sensors;
+pub mod shiftreg;
+mod signal;
+pub(crate) mod soft_pwm;
+#[cfg(feature = "async")]
+mod stream;
+pub mod trace;
+mod traits;
 
-pub use self::pin::{AltPin, InputPin, OutputPin, Pin};
+pub use self::alt::{alt_functions, Function};
+pub use self::backend::Backend;
+pub use self::cleanup::GpioBuilder;
+pub use self::group::OutputPinGroup;
+pub use self::header::PhysicalPin;
+#[cfg(feature = "mock")]
+pub use self::mock::MockGpio;
+pub use self::multi::{Events, MultiInterrupt, MultiInterruptBuilder};
+pub use self::pads::{DriveStrength, PadControl, PadGroup};
+pub use self::pin::{AltPin, AsyncInterruptHandle, InputPin, IoPin, OutputPin, Pin};
+pub use self::record::Recorder;
+pub use self::sched::ThreadConfig;
+pub use self::signal::Signal;
+#[cfg(feature = "async")]
+pub use self::stream::EventStream;
+pub use self::traits::{InputPinLike, OutputPinLike};
 
-quick_error! {
 /// Errors that can occur when accessing the GPIO peripheral.
-    #[derive(Debug)]
-    pub enum Error {
-/// Unknown model.
-///
-/// The Raspberry Pi model or SoC can't be identified. Support for
-/// new models is usually added shortly after they are officially
-/// announced and available to the public. Make sure you're using
-/// the latest release of RPPAL.
 ///
-/// You may also encounter this error if your Linux distribution
-/// doesn't provide any of the common user-accessible system files
-/// that are used to identify the model and SoC.
-        UnknownModel { description("unknown Raspberry Pi model") }
-/// Permission denied when opening `/dev/gpiomem`, `/dev/mem` or `/dev/gpiochipN` for
-/// read/write access.
+/// Errors that relate to a specific pin include its BCM GPIO number, and where
+/// applicable the [`Operation`] that failed. Underlying IO errors are available
+/// through [`std::error::Error::source`].
 ///
-/// More information on possible causes for this error can be found [here].
-///
-/// [here]: index.html#permission-denied
-        PermissionDenied { description("/dev/gpiomem, /dev/mem or /dev/gpiochipN insufficient permissions") }
-/// IO error.
-        Io(err: io::Error) { description(err.description()) from() }
-/// Interrupt polling thread panicked.
-        ThreadPanic { description("interrupt polling thread panicked") }
+/// [`Operation`]: enum.Operation.html
+/// [`std::error::Error::source`]: https://doc.rust-lang.org/std/error/trait.Error.html#method.source
+#[derive(Debug)]
+pub enum Error {
+    /// Unknown model.
+    ///
+    /// The Raspberry Pi model or SoC can't be identified. Support for
+    /// new models is usually added shortly after they are officially
+    /// announced and available to the public. Make sure you're using
+    /// the latest release of RPPAL.
+    ///
+    /// You may also encounter this error if your Linux distribution
+    /// doesn't provide any of the common user-accessible system files
+    /// that are used to identify the model and SoC.
+    UnknownModel,
+    /// Permission denied when opening `/dev/gpiomem`, `/dev/mem` or `/dev/gpiochipN` for
+    /// read/write access.
+    ///
+    /// More information on possible causes for this error can be found [here].
+    ///
+    /// [here]: index.html#permission-denied
+    PermissionDenied,
+    /// IO error.
+    Io(io::Error),
+    /// IO error while performing an operation on a pin.
+    ///
+    /// Contains the BCM GPIO number, the operation that failed, and the underlying
+    /// IO error.
+    PinIo(u8, Operation, io::Error),
+    /// Interrupt polling thread panicked.
+    ThreadPanic,
+    /// Invalid physical pin number.
+    ///
+    /// The specified physical pin number doesn't exist on the GPIO header of
+    /// your Raspberry Pi model. Compute Modules don't have a GPIO header.
+    InvalidPhysicalPin(u8),
+    /// Physical pin isn't a GPIO pin.
+    ///
+    /// The specified physical pin is connected to 3.3 V, 5 V or ground.
+    NotGpioPin(u8, PhysicalPin),
+    /// Invalid GPIO pin number.
+    ///
+    /// The specified BCM GPIO number doesn't exist.
+    InvalidPin(u8),
+    /// GPIO pin isn't available.
+    ///
+    /// The GPIO pin has already been retrieved within the current process, and
+    /// hasn't gone out of scope yet. [`Gpio::taken_pins`] lists all pins that are
+    /// currently in use.
+    ///
+    /// [`Gpio::taken_pins`]: struct.Gpio.html#method.taken_pins
+    PinNotAvailable(u8),
+    /// GPIO pin is in use by a kernel driver or another process.
+    ///
+    /// The GPIO character device refused to grant access to the pin's line.
+    /// The second field contains the consumer label reported by the kernel, for
+    /// instance `spi0 CS0`, `w1` or the name of another application. Disable the
+    /// driver or stop the application that's using the pin.
+    PinUsedBy(u8, String),
+    /// Unsupported SoC.
+    ///
+    /// The GPIO registers on the Raspberry Pi 5 are part of the RP1 I/O controller,
+    /// which uses a different register layout. Memory-mapped GPIO access isn't
+    /// supported on the BCM2712 (Raspberry Pi 5).
+    UnsupportedSoC(SoC),
+    /// Alternate function isn't available.
+    ///
+    /// The specified [`Function`] can't be selected on this GPIO pin on the
+    /// current SoC.
+    ///
+    /// [`Function`]: enum.Function.html
+    FunctionNotAvailable(u8, Function),
+    /// Interrupts aren't supported.
+    ///
+    /// The GPIO backend doesn't provide access to `/dev/gpiochipN`. This is the case
+    /// for a [`Gpio`] instance created with [`Gpio::with_mock`].
+    ///
+    /// [`Gpio`]: struct.Gpio.html
+    /// [`Gpio::with_mock`]: struct.Gpio.html#method.with_mock
+    InterruptsNotSupported,
+    /// GPIO backend is already in use.
+    ///
+    /// Only a single [`Backend`] can be active at a time, because all [`Gpio`]
+    /// instances share the same state. The current backend is stored in the
+    /// variant.
+    ///
+    /// [`Backend`]: enum.Backend.html
+    /// [`Gpio`]: struct.Gpio.html
+    BackendInUse(Backend),
+    /// Asynchronous interrupt callback panicked.
+    ///
+    /// The callback for the specified pin panicked while handling an event. The
+    /// panic was caught, and the interrupt thread continues handling new events.
+    /// The second field contains the panic message. Retrieved through
+    /// [`Gpio::take_error`].
+    ///
+    /// [`Gpio::take_error`]: struct.Gpio.html#method.take_error
+    CallbackPanic(u8, String),
+    /// Invalid register offset.
+    ///
+    /// The specified byte offset isn't aligned to a 32-bit register, or lies
+    /// outside of the memory-mapped GPIO register block.
+    InvalidRegister(usize),
+    /// Register access isn't supported.
+    ///
+    /// The GPIO backend doesn't memory-map the GPIO registers. This is the case
+    /// for [`Backend::CharDev`], and for a [`Gpio`] instance created with
+    /// [`Gpio::with_mock`].
+    ///
+    /// [`Backend::CharDev`]: enum.Backend.html#variant.CharDev
+    /// [`Gpio`]: struct.Gpio.html
+    /// [`Gpio::with_mock`]: struct.Gpio.html#method.with_mock
+    RegistersNotSupported,
+    /// GPIO state is already initialized.
+    ///
+    /// A [`Recorder`] or [`VcdTracer`] can only be attached by [`GpioBuilder::build`]
+    /// if no other [`Gpio`] instance exists yet, because all instances share the
+    /// same state.
+    ///
+    /// [`Recorder`]: struct.Recorder.html
+    /// [`VcdTracer`]: trace/struct.VcdTracer.html
+    /// [`GpioBuilder::build`]: struct.GpioBuilder.html#method.build
+    /// [`Gpio`]: struct.Gpio.html
+    AlreadyInitialized,
+}
+
+impl Error {
+    // Adds pin context to IO errors. Other errors already carry any relevant context.
+    pub(crate) fn with_pin(self, pin: u8, operation: Operation) -> Error {
+        match self {
+            Error::Io(err) => Error::PinIo(pin, operation, err),
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Error::UnknownModel => write!(f, "unknown Raspberry Pi model"),
+            Error::PermissionDenied => write!(
+                f,
+                "/dev/gpiomem, /dev/mem or /dev/gpiochipN insufficient permissions"
+            ),
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::PinIo(pin, operation, ref err) => {
+                write!(f, "GPIO {}: failed to {}: {}", pin, operation, err)
+            }
+            Error::ThreadPanic => write!(f, "interrupt polling thread panicked"),
+            Error::InvalidPhysicalPin(pin) => write!(f, "invalid physical pin number {}", pin),
+            Error::NotGpioPin(pin, physical_pin) => write!(
+                f,
+                "physical pin {} is a power or ground pin ({})",
+                pin, physical_pin
+            ),
+            Error::InvalidPin(pin) => write!(f, "invalid GPIO pin number {}", pin),
+            Error::PinNotAvailable(pin) => write!(f, "GPIO {} is already in use", pin),
+            Error::PinUsedBy(pin, ref consumer) => {
+                write!(f, "GPIO {} is in use by {}", pin, consumer)
+            }
+            Error::UnsupportedSoC(soc) => write!(f, "GPIO access isn't supported on the {}", soc),
+            Error::FunctionNotAvailable(pin, function) => write!(
+                f,
+                "alternate function {} isn't available on GPIO {}",
+                function, pin
+            ),
+            Error::InterruptsNotSupported => {
+                write!(f, "interrupts aren't supported by the GPIO backend")
+            }
+            Error::BackendInUse(backend) => write!(
+                f,
+                "a different GPIO backend ({}) is already in use",
+                backend
+            ),
+            Error::CallbackPanic(pin, ref message) => write!(
+                f,
+                "asynchronous interrupt callback for GPIO {} panicked: {}",
+                pin, message
+            ),
+            Error::InvalidRegister(offset) => write!(f, "invalid register offset 0x{:02x}", offset),
+            Error::RegistersNotSupported => {
+                write!(f, "register access isn't supported by the GPIO backend")
+            }
+            Error::AlreadyInitialized => write!(f, "GPIO state is already initialized"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    #[allow(deprecated)]
+    fn description(&self) -> &str {
+        match *self {
+            Error::UnknownModel => "unknown Raspberry Pi model",
+            Error::PermissionDenied => {
+                "/dev/gpiomem, /dev/mem or /dev/gpiochipN insufficient permissions"
+            }
+            Error::Io(ref err) | Error::PinIo(_, _, ref err) => err.description(),
+            Error::ThreadPanic => "interrupt polling thread panicked",
+            Error::InvalidPhysicalPin(_) => "invalid physical pin number",
+            Error::NotGpioPin(_, _) => "physical pin is a power or ground pin",
+            Error::InvalidPin(_) => "invalid GPIO pin number",
+            Error::PinNotAvailable(_) => "GPIO pin is already in use",
+            Error::PinUsedBy(_, _) => "GPIO pin is in use by a kernel driver or another process",
+            Error::UnsupportedSoC(_) => "GPIO access isn't supported on this SoC",
+            Error::FunctionNotAvailable(_, _) => {
+                "alternate function isn't available on this GPIO pin"
+            }
+            Error::InterruptsNotSupported => "interrupts aren't supported by the GPIO backend",
+            Error::BackendInUse(_) => "a different GPIO backend is already in use",
+            Error::CallbackPanic(_, _) => "asynchronous interrupt callback panicked",
+            Error::InvalidRegister(_) => "invalid register offset",
+            Error::RegistersNotSupported => "register access isn't supported by the GPIO backend",
+            Error::AlreadyInitialized => "GPIO state is already initialized",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) | Error::PinIo(_, _, ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+/// Pin operations that can fail with an IO error.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Operation {
+    /// Configuring an interrupt trigger.
+    ConfigureInterrupt,
+    /// Reading an interrupt trigger event.
+    ReadEvent,
+    /// Reading the pin's logic level through the GPIO character device.
+    ReadLevel,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Operation::ConfigureInterrupt => write!(f, "configure interrupt"),
+            Operation::ReadEvent => write!(f, "read interrupt event"),
+            Operation::ReadLevel => write!(f, "read logic level"),
+        }
     }
 }
 
@@ -140,6 +507,7 @@ pub type Result<T> = result::Result<T, Error>;
 
 /// Pin modes.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Mode {
     Input = 0b000,
@@ -169,6 +537,7 @@ impl fmt::Display for Mode {
 
 /// Pin logic levels.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Level {
     Low = 0,
@@ -186,7 +555,8 @@ impl fmt::Display for Level {
 
 /// Built-in pull-up/pull-down resistor states.
 #[derive(Debug, PartialEq, Copy, Clone)]
-pub(crate) enum PullUpDown {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PullUpDown {
     Off = 0b00,
     PullDown = 0b01,
     PullUp = 0b10,
@@ -202,8 +572,145 @@ impl fmt::Display for PullUpDown {
     }
 }
 
+/// Interrupt trigger event.
+///
+/// `Event` is returned by [`InputPin::poll_interrupt`] and [`Gpio::poll_interrupts`],
+/// and passed to the callbacks of asynchronous interrupt triggers.
+///
+/// [`InputPin::poll_interrupt`]: struct.InputPin.html#method.poll_interrupt
+/// [`Gpio::poll_interrupts`]: struct.Gpio.html#method.poll_interrupts
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Event {
+    pin: u8,
+    trigger: Trigger,
+    timestamp: Duration,
+    missed_events: u32,
+}
+
+impl Event {
+    // trigger is either RisingEdge or FallingEdge, as reported by the kernel
+    pub(crate) fn new(pin: u8, trigger: Trigger, timestamp: Duration) -> Event {
+        Event {
+            pin,
+            trigger,
+            timestamp,
+            missed_events: 0,
+        }
+    }
+
+    /// Returns the GPIO pin number that triggered the event.
+    ///
+    /// Pins are addressed by their BCM numbers, rather than their physical location.
+    pub fn pin(&self) -> u8 {
+        self.pin
+    }
+
+    /// Returns the pin's logic level after the event occurred, which is
+    /// `High` for a rising edge and `Low` for a falling edge.
+    ///
+    /// The level is derived from the edge reported by the kernel, rather than
+    /// read from the pin, so it's accurate even if the pin has changed state again
+    /// by the time the event is processed.
+    pub fn level(&self) -> Level {
+        match self.trigger {
+            Trigger::RisingEdge => Level::High,
+            _ => Level::Low,
+        }
+    }
+
+    /// Returns the edge that triggered the event, which is either
+    /// [`Trigger::RisingEdge`] or [`Trigger::FallingEdge`].
+    ///
+    /// When the pin is configured with [`Trigger::Both`], `trigger` indicates
+    /// which of the two edges occurred.
+    ///
+    /// [`Trigger::RisingEdge`]: enum.Trigger.html#variant.RisingEdge
+    /// [`Trigger::FallingEdge`]: enum.Trigger.html#variant.FallingEdge
+    /// [`Trigger::Both`]: enum.Trigger.html#variant.Both
+    pub fn trigger(&self) -> Trigger {
+        self.trigger
+    }
+
+    /// Returns the time the event occurred.
+    ///
+    /// The timestamp is set by the kernel when the interrupt is handled, rather
+    /// than when the event is processed in userspace, which makes it suitable
+    /// for measuring the time between edges. Depending on the kernel version, the
+    /// timestamp is based on either `CLOCK_REALTIME` (pre-5.7) or `CLOCK_MONOTONIC`,
+    /// so only the difference between timestamps should be relied upon.
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+
+    /// Returns the number of events that were discarded immediately before this
+    /// event, because the pin's event queue was full.
+    ///
+    /// Synchronous interrupt trigger events are cached in a bounded queue for each
+    /// pin until they're retrieved with [`InputPin::poll_interrupt`] or
+    /// [`Gpio::poll_interrupts`]. The queue capacity can be changed with
+    /// [`InputPin::set_interrupt_queue_capacity`]. Events that are delivered directly,
+    /// such as through asynchronous interrupt callbacks, always return 0.
+    ///
+    /// The kernel keeps a separate buffer of 16 events for each pin. Events that are
+    /// dropped when that buffer overflows can't be detected.
+    ///
+    /// [`InputPin::poll_interrupt`]: struct.InputPin.html#method.poll_interrupt
+    /// [`Gpio::poll_interrupts`]: struct.Gpio.html#method.poll_interrupts
+    /// [`InputPin::set_interrupt_queue_capacity`]: struct.InputPin.html#method.set_interrupt_queue_capacity
+    pub fn missed_events(&self) -> u32 {
+        self.missed_events
+    }
+}
+
+/// Frequency and duty cycle of a periodic signal.
+///
+/// Returned by [`InputPin::measure_frequency`].
+///
+/// [`InputPin::measure_frequency`]: struct.InputPin.html#method.measure_frequency
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct FrequencyMeasurement {
+    frequency: f64,
+    duty_cycle: f64,
+    periods: u32,
+    sampled: bool,
+}
+
+impl FrequencyMeasurement {
+    pub(crate) fn new(frequency: f64, duty_cycle: f64, periods: u32, sampled: bool) -> Self {
+        FrequencyMeasurement {
+            frequency,
+            duty_cycle,
+            periods,
+            sampled,
+        }
+    }
+
+    /// Returns the average frequency in hertz (Hz).
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    /// Returns the average duty cycle as a floating point value between `0.0` (0%)
+    /// and `1.0` (100%).
+    pub fn duty_cycle(&self) -> f64 {
+        self.duty_cycle
+    }
+
+    /// Returns the number of complete periods the measurement is based on.
+    pub fn periods(&self) -> u32 {
+        self.periods
+    }
+
+    /// Returns `true` if the measurement is based on sampling the pin's logic level,
+    /// rather than on timestamped interrupt trigger events.
+    pub fn is_sampled(&self) -> bool {
+        self.sampled
+    }
+}
+
 /// Interrupt trigger conditions.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Trigger {
     Disabled = 0,
     RisingEdge = 1,
@@ -225,23 +732,143 @@ impl fmt::Display for Trigger {
 // Store Gpio's state separately, so we can conveniently share it through
 // a cloned Arc.
 pub(crate) struct GpioState {
-    gpio_mem: mem::GpioMem,
-    cdev: std::fs::File,
+    backend: Option<Backend>,
+    gpio_mem: Box<dyn backend::GpioRegisters>,
+    cdev: Option<std::fs::File>,
     sync_interrupts: Mutex<interrupt::EventLoop>,
     pins_taken: [AtomicBool; pin::MAX],
+    // Held while rppal changes a pin's output level, which makes toggle() atomic
+    level_locks: [Mutex<()>; pin::MAX],
+    thread_config: Mutex<ThreadConfig>,
+    async_errors: interrupt::ErrorQueue,
+    cleanup: Mutex<cleanup::CleanupState>,
+    // Incremented by Gpio::cleanup(), which signals software-based PWM threads to stop
+    cleanup_generation: AtomicU64,
 }
 
 impl fmt::Debug for GpioState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("EventLoop")
+            .field("backend", &self.backend)
             .field("gpio_mem", &self.gpio_mem)
             .field("cdev", &self.cdev)
             .field("sync_interrupts", &self.sync_interrupts)
             .field("pins_taken", &format_args!("{{ .. }}"))
+            .field("level_locks", &format_args!("{{ .. }}"))
+            .field("thread_config", &self.thread_config)
+            .field("async_errors", &self.async_errors)
+            .field("cleanup", &self.cleanup)
+            .field("cleanup_generation", &self.cleanup_generation)
             .finish()
     }
 }
 
+impl GpioState {
+    // backend is None for a GpioState that isn't shared through GPIO_STATE
+    fn new(
+        backend: Option<Backend>,
+        gpio_mem: Box<dyn backend::GpioRegisters>,
+        cdev: Option<std::fs::File>,
+    ) -> Result<GpioState> {
+        // Without a cdev, the event loop is still needed to poll, but can't
+        // register any interrupts.
+        let cdev_fd = cdev.as_ref().map_or(-1, |cdev| cdev.as_raw_fd());
+        let sync_interrupts = Mutex::new(interrupt::EventLoop::new(cdev_fd, pin::MAX)?);
+        let pins_taken = init_array!(AtomicBool::new(false), pin::MAX);
+        let level_locks = init_array!(Mutex::new(()), pin::MAX);
+
+        Ok(GpioState {
+            backend,
+            gpio_mem,
+            cdev,
+            sync_interrupts,
+            pins_taken,
+            level_locks,
+            thread_config: Mutex::new(ThreadConfig::default()),
+            async_errors: interrupt::ErrorQueue::default(),
+            cleanup: Mutex::new(cleanup::CleanupState::default()),
+            cleanup_generation: AtomicU64::new(0),
+        })
+    }
+
+    // A poisoned lock only means a thread panicked while holding it, which
+    // doesn't leave the cleanup state inconsistent.
+    pub(crate) fn cleanup_state(&self) -> MutexGuard<'_, cleanup::CleanupState> {
+        self.cleanup.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    // Records the original mode of a pin that was just taken, unless it's
+    // already recorded because the pin was stolen.
+    pub(crate) fn record_pin(&self, pin: u8) {
+        let mut cleanup_state = self.cleanup_state();
+        if cleanup_state.pins[pin as usize].is_none() {
+            cleanup_state.pins[pin as usize] = Some(cleanup::PinRecord {
+                mode: self.gpio_mem.mode(pin),
+                pud_changed: false,
+            });
+        }
+    }
+
+    pub(crate) fn release_pin(&self, pin: u8) {
+        self.cleanup_state().pins[pin as usize] = None;
+    }
+
+    pub(crate) fn record_pullupdown(&self, pin: u8, pud: PullUpDown) {
+        if let Some(ref mut record) = self.cleanup_state().pins[pin as usize] {
+            record.pud_changed |= pud != PullUpDown::Off;
+        }
+    }
+
+    // The lock doesn't protect any data, so a poisoned lock can safely be ignored.
+    pub(crate) fn lock_level(&self, pin: u8) -> MutexGuard<'_, ()> {
+        self.level_locks[pin as usize]
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    // Sets a pin's output level, without interfering with a concurrent toggle()
+    pub(crate) fn write_level(&self, pin: u8, level: Level) {
+        let _level_lock = self.lock_level(pin);
+
+        match level {
+            Level::Low => self.gpio_mem.set_low(pin),
+            Level::High => self.gpio_mem.set_high(pin),
+        }
+    }
+
+    pub(crate) fn default_reset_on_drop(&self) -> bool {
+        self.cleanup_state().policy.reset_on_drop
+    }
+
+    pub(crate) fn drop_level(&self, pin: u8) -> Option<Level> {
+        self.cleanup_state().policy.drop_levels[pin as usize]
+    }
+
+    pub(crate) fn cleanup_generation(&self) -> u64 {
+        self.cleanup_generation.load(Ordering::SeqCst)
+    }
+
+    // Returns the file descriptor for /dev/gpiochipN, which is needed to configure
+    // interrupts on pin. Any line held by the backend for pin is released first.
+    pub(crate) fn cdev_fd(&self, pin: u8) -> Result<i32> {
+        self.gpio_mem.release(pin);
+
+        self.cdev
+            .as_ref()
+            .map(|cdev| cdev.as_raw_fd())
+            .ok_or(Error::InterruptsNotSupported)
+    }
+
+    // Returns the pin's thread configuration, or the default configuration for
+    // all pins if the pin doesn't have one.
+    pub(crate) fn resolve_thread_config(&self, config: &Option<ThreadConfig>) -> ThreadConfig {
+        match config {
+            Some(config) => config.clone(),
+            None => self.thread_config.lock().unwrap().clone(),
+        }
+    }
+}
+
 // Share state between Gpio and Pin instances. GpioState is dropped after
 // all Gpio and Pin instances go out of scope, guaranteeing we won't have
 // any pins simultaneously using different EventLoop or GpioMem instances.
@@ -250,42 +877,140 @@ lazy_static! {
 }
 
 /// Provides access to the Raspberry Pi's GPIO peripheral.
+///
+/// `Gpio` is a handle to a shared, process-wide state, which is created on the
+/// first call to [`new`]. Any `Gpio` instances constructed while that state is
+/// alive, either through [`new`] or [`clone`], refer to the same register mapping,
+/// interrupt event loop and pin ownership bookkeeping. A pin retrieved through one
+/// handle is unavailable through all other handles until it goes out of scope.
+///
+/// Cloning a `Gpio` is cheap, and `Gpio` can be shared between threads.
+///
+/// [`new`]: #method.new
+/// [`clone`]: #method.clone
 #[derive(Clone, Debug)]
 pub struct Gpio {
     inner: Arc<GpioState>,
 }
 
+// Libraries rely on being able to store a Gpio in their own thread-safe types
+#[allow(dead_code)]
+fn assert_gpio_send_sync() {
+    fn is_send_sync<T: Send + Sync>() {}
+
+    is_send_sync::<Gpio>();
+}
+
 impl Gpio {
     /// Constructs a new `Gpio`.
+    ///
+    /// The GPIO pins are accessed through the memory-mapped registers. If a `Gpio`
+    /// instance already exists, `new` shares its state and backend, and is as cheap
+    /// as calling `clone` on that instance.
     pub fn new() -> Result<Gpio> {
-        let mut static_state = GPIO_STATE.lock().unwrap();
+        let static_state = GPIO_STATE.lock().unwrap();
 
         // Create a strong reference if a GpioState instance already exists,
-        // otherwise initialize it here so we can return any relevant errors.
+        // regardless of the backend it uses.
         if let Some(ref state) = static_state.upgrade() {
             Ok(Gpio {
                 inner: state.clone(),
             })
         } else {
-            let gpio_mem = mem::GpioMem::open()?;
-            let cdev = ioctl::find_gpiochip()?;
-            let sync_interrupts =
-                Mutex::new(interrupt::EventLoop::new(cdev.as_raw_fd(), pin::MAX)?);
-            let pins_taken = init_array!(AtomicBool::new(false), pin::MAX);
-
-            let gpio_state = Arc::new(GpioState {
-                gpio_mem,
-                cdev,
-                sync_interrupts,
-                pins_taken,
-            });
+            Gpio::init(static_state, Backend::GpioMem, None, None)
+        }
+    }
 
-            // Store a weak reference to our state. This gets dropped when
-            // all Gpio and Pin instances go out of scope.
-            *static_state = Arc::downgrade(&gpio_state);
+    /// Returns a [`GpioBuilder`], which constructs a new `Gpio` with custom
+    /// crate-wide cleanup defaults.
+    ///
+    /// [`GpioBuilder`]: struct.GpioBuilder.html
+    pub fn builder() -> GpioBuilder {
+        GpioBuilder::new()
+    }
+
+    /// Constructs a new `Gpio` using the specified [`Backend`].
+    ///
+    /// All `Gpio` instances share the same state, so only a single backend can be
+    /// active at a time. If a `Gpio` instance already exists with a different
+    /// backend, `with_backend` returns [`Error::BackendInUse`].
+    ///
+    /// [`Backend`]: enum.Backend.html
+    /// [`Error::BackendInUse`]: enum.Error.html#variant.BackendInUse
+    pub fn with_backend(backend: Backend) -> Result<Gpio> {
+        let static_state = GPIO_STATE.lock().unwrap();
 
-            Ok(Gpio { inner: gpio_state })
+        if let Some(ref state) = static_state.upgrade() {
+            match state.backend {
+                Some(current) if current != backend => Err(Error::BackendInUse(current)),
+                _ => Ok(Gpio {
+                    inner: state.clone(),
+                }),
+            }
+        } else {
+            Gpio::init(static_state, backend, None, None)
+        }
+    }
+
+    // Constructs a new Gpio with register access passed through a recorder
+    // and/or tracer, which is only possible if the shared state doesn't exist yet.
+    pub(crate) fn with_instruments(
+        backend: Backend,
+        recorder: Option<Recorder>,
+        tracer: Option<VcdTracer>,
+    ) -> Result<Gpio> {
+        let static_state = GPIO_STATE.lock().unwrap();
+
+        if static_state.upgrade().is_some() {
+            Err(Error::AlreadyInitialized)
+        } else {
+            Gpio::init(static_state, backend, recorder, tracer)
+        }
+    }
+
+    fn init(
+        mut static_state: MutexGuard<'_, Weak<GpioState>>,
+        backend: Backend,
+        recorder: Option<Recorder>,
+        tracer: Option<VcdTracer>,
+    ) -> Result<Gpio> {
+        let mut gpio_mem: Box<dyn backend::GpioRegisters> = match backend {
+            Backend::GpioMem => Box::new(mem::GpioMem::open()?),
+            Backend::CharDev => Box::new(cdev::GpioCdev::open()?),
+        };
+        if let Some(tracer) = tracer {
+            gpio_mem = Box::new(trace::TracingRegisters::new(gpio_mem, tracer)?);
+        }
+        if let Some(recorder) = recorder {
+            gpio_mem = Box::new(record::RecordingRegisters::new(gpio_mem, recorder));
         }
+        let cdev = ioctl::find_gpiochip()?;
+        let gpio_state = Arc::new(GpioState::new(Some(backend), gpio_mem, Some(cdev))?);
+
+        // Store a weak reference to our state. This gets dropped when
+        // all Gpio and Pin instances go out of scope.
+        *static_state = Arc::downgrade(&gpio_state);
+
+        Ok(Gpio { inner: gpio_state })
+    }
+
+    /// Constructs a new `Gpio` backed by the specified [`MockGpio`], rather than the
+    /// GPIO peripheral.
+    ///
+    /// Unlike [`new`], every call to `with_mock` returns an independent instance,
+    /// with its own set of available pins. Pins are read from and written to `mock`,
+    /// which can be used to simulate inputs and inspect outputs. Interrupts aren't
+    /// supported.
+    ///
+    /// `with_mock` is only available when the `mock` feature is enabled.
+    ///
+    /// [`MockGpio`]: struct.MockGpio.html
+    /// [`new`]: #method.new
+    #[cfg(feature = "mock")]
+    pub fn with_mock(mock: &MockGpio) -> Result<Gpio> {
+        Ok(Gpio {
+            inner: Arc::new(GpioState::new(None, Box::new(mock.clone()), None)?),
+        })
     }
 
     /// Returns a [`Pin`] for the specified GPIO pin number.
@@ -303,7 +1028,10 @@ impl Gpio {
 
         // Returns true if the pin is currently taken, otherwise atomically sets
         // it to true here
-        if self.inner.pins_taken[pin as usize].compare_and_swap(false, true, Ordering::SeqCst) {
+        if self.inner.pins_taken[pin as usize]
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
             // Pin is currently taken
             None
         } else {
@@ -314,6 +1042,348 @@ impl Gpio {
         }
     }
 
+    /// Returns a [`Pin`] for the specified GPIO pin number, regardless of whether
+    /// it's already in use.
+    ///
+    /// `steal` bypasses the ownership check performed by [`get`], which allows
+    /// supervisory tools and interactive sessions to recover a pin that was left
+    /// marked as taken, for instance by a [`Pin`] that was leaked with
+    /// `mem::forget`, or is stuck inside a data structure owned by a thread that
+    /// panicked. The pin is marked as taken afterwards. Returns `None` if the pin
+    /// number is invalid.
+    ///
+    /// # Safety
+    ///
+    /// Any existing owner of the pin keeps access to it. Both instances can
+    /// change the pin's mode and logic level without being aware of each other, and
+    /// whichever instance goes out of scope first resets the pin and marks it as
+    /// available, while the other instance is still in use. Only call `steal` if
+    /// the previous owner is known to be unused.
+    ///
+    /// [`Pin`]: struct.Pin.html
+    /// [`get`]: #method.get
+    pub unsafe fn steal(&self, pin: u8) -> Option<pin::Pin> {
+        if pin as usize >= pin::MAX {
+            return None;
+        }
+
+        self.inner.pins_taken[pin as usize].store(true, Ordering::SeqCst);
+
+        Some(pin::Pin::new(pin, self.inner.clone()))
+    }
+
+    /// Returns `true` if the specified GPIO pin is currently in use by any
+    /// [`Pin`], [`InputPin`], [`OutputPin`] or [`AltPin`] instance within
+    /// the current process.
+    ///
+    /// Invalid pin numbers return `false`. Pins that are used by other processes
+    /// or kernel drivers aren't tracked, and aren't reported as taken.
+    ///
+    /// [`Pin`]: struct.Pin.html
+    /// [`InputPin`]: struct.InputPin.html
+    /// [`OutputPin`]: struct.OutputPin.html
+    /// [`AltPin`]: struct.AltPin.html
+    pub fn is_taken(&self, pin: u8) -> bool {
+        self.inner
+            .pins_taken
+            .get(pin as usize)
+            .is_some_and(|taken| taken.load(Ordering::SeqCst))
+    }
+
+    /// Returns the pin numbers of all GPIO pins that are currently in use within
+    /// the current process, in ascending order.
+    ///
+    /// More information can be found at [`is_taken`].
+    ///
+    /// [`is_taken`]: #method.is_taken
+    pub fn taken_pins(&self) -> Vec<u8> {
+        (0..pin::MAX as u8)
+            .filter(|&pin| self.is_taken(pin))
+            .collect()
+    }
+
+    /// Immediately restores all pins that are currently in use within the current
+    /// process.
+    ///
+    /// `cleanup` stops any software-based PWM signals, clears synchronous interrupt
+    /// triggers and edge detection if configured through
+    /// [`GpioBuilder::clear_interrupts`], sets output pins to their configured
+    /// [`drop_level`], restores every pin's mode to the mode it had when it was
+    /// retrieved, and disables any built-in pull-up/pull-down resistors that were
+    /// enabled.
+    ///
+    /// This is intended for shutdown handlers, where waiting for every pin to go
+    /// out of scope isn't practical. Pins remain taken until they go out of scope,
+    /// and can still be used afterwards, although their mode no longer matches
+    /// their type. Pins that are used by other processes aren't affected.
+    ///
+    /// [`GpioBuilder::clear_interrupts`]: struct.GpioBuilder.html#method.clear_interrupts
+    /// [`drop_level`]: struct.GpioBuilder.html#method.drop_level
+    pub fn cleanup(&self) {
+        cleanup::cleanup(&self.inner);
+    }
+
+    /// Installs signal handlers that call [`cleanup`] when any of the specified
+    /// termination signals is received, and then terminate the process.
+    ///
+    /// `drop` methods aren't called when a program is terminated by a signal, which
+    /// leaves output pins driving their last logic level, and software-based PWM
+    /// signals running until the process exits. For pins that control motors,
+    /// heaters or relays, that's a real hazard. Configure safe logic levels through
+    /// [`GpioBuilder::drop_level`] to make sure those pins are switched off.
+    ///
+    /// Signal handlers can only safely call a very limited set of functions, so the
+    /// handler forwards the signal to a dedicated cleanup thread, which restores the
+    /// pins. Afterwards, the signal's default action is restored, and the signal is
+    /// raised again, which terminates the process with the expected exit status.
+    ///
+    /// Any previously installed handlers for the specified signals are replaced.
+    /// Calling `register_cleanup_on_signal` again adds handlers for any additional
+    /// signals. Signals that can't be caught, such as `SIGKILL`, will still leave
+    /// the pins in their current state.
+    ///
+    /// [`cleanup`]: #method.cleanup
+    /// [`GpioBuilder::drop_level`]: struct.GpioBuilder.html#method.drop_level
+    pub fn register_cleanup_on_signal(&self, signals: &[Signal]) -> Result<()> {
+        signal::register(&self.inner, signals)
+    }
+
+    /// Sets the default scheduling policy and CPU affinity for threads started by
+    /// any pin, such as asynchronous interrupt handlers and software-based PWM.
+    ///
+    /// The configuration is shared by all `Gpio` instances, and is applied to threads
+    /// that are started after the call. Individual pins can override it through
+    /// [`InputPin::set_thread_config`] and [`OutputPin::set_thread_config`]. More
+    /// information can be found at [`ThreadConfig`].
+    ///
+    /// [`InputPin::set_thread_config`]: struct.InputPin.html#method.set_thread_config
+    /// [`OutputPin::set_thread_config`]: struct.OutputPin.html#method.set_thread_config
+    /// [`ThreadConfig`]: struct.ThreadConfig.html
+    pub fn set_thread_config(&self, config: ThreadConfig) {
+        *self.inner.thread_config.lock().unwrap() = config;
+    }
+
+    /// Returns the default scheduling policy and CPU affinity for threads started by
+    /// any pin.
+    pub fn thread_config(&self) -> ThreadConfig {
+        self.inner.thread_config.lock().unwrap().clone()
+    }
+
+    /// Returns the oldest error that occurred on an asynchronous interrupt thread,
+    /// and removes it from the queue.
+    ///
+    /// Asynchronous interrupt callbacks run on a separate thread, where errors can't
+    /// be returned to the caller. If a callback panics, the panic is caught, and
+    /// reported as [`Error::CallbackPanic`], while the interrupt thread continues
+    /// handling new events. If reading an event fails, the error is reported, and
+    /// the interrupt thread stops, which leaves the pin without an active
+    /// asynchronous interrupt trigger until it's reconfigured.
+    ///
+    /// The queue is shared by all `Gpio` instances, and holds up to 16 errors. Older
+    /// errors are discarded first. Returns `None` if no errors occurred.
+    ///
+    /// [`Error::CallbackPanic`]: enum.Error.html#variant.CallbackPanic
+    pub fn take_error(&self) -> Option<Error> {
+        self.inner.async_errors.pop()
+    }
+
+    /// Reads the GPIO register at the specified byte offset.
+    ///
+    /// `read_register` provides direct access to registers that aren't covered by
+    /// the rest of the API. `offset` is relative to the start of the GPIO register
+    /// block, as listed in the BCM2835 ARM Peripherals datasheet (section 6.1),
+    /// for instance `0x40` for GPEDS0. Reading any of the GPIO registers has no side
+    /// effects.
+    ///
+    /// Returns [`Error::InvalidRegister`] if `offset` isn't a multiple of 4, or lies
+    /// outside of the GPIO register block, and [`Error::RegistersNotSupported`] if
+    /// the GPIO backend doesn't memory-map the registers.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use rppal::gpio::Gpio;
+    ///
+    /// # fn main() -> rppal::gpio::Result<()> {
+    /// // GPEDS0 (event detect status for GPIO 0-31)
+    /// let gpeds0 = Gpio::new()?.read_register(0x40)?;
+    /// println!("GPEDS0: {:#010x}", gpeds0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Error::InvalidRegister`]: enum.Error.html#variant.InvalidRegister
+    /// [`Error::RegistersNotSupported`]: enum.Error.html#variant.RegistersNotSupported
+    pub fn read_register(&self, offset: usize) -> Result<u32> {
+        let reg_ptr = self.inner.gpio_mem.register_ptr(offset)?;
+
+        Ok(unsafe { ptr::read_volatile(reg_ptr) })
+    }
+
+    /// Writes `value` to the GPIO register at the specified byte offset.
+    ///
+    /// More information on `offset` can be found at [`read_register`].
+    ///
+    /// # Safety
+    ///
+    /// Writing to the GPIO registers bypasses any state tracked by RPPAL, and
+    /// can change the configuration of pins owned by other parts of your application,
+    /// the kernel, or other processes. Read-modify-write cycles performed through
+    /// `read_register` and `write_register` aren't synchronized with RPPAL's own
+    /// register accesses, and may undo concurrent changes to the same register
+    /// made through methods such as [`Pin::into_output`].
+    ///
+    /// [`read_register`]: #method.read_register
+    /// [`Pin::into_output`]: struct.Pin.html#method.into_output
+    pub unsafe fn write_register(&self, offset: usize, value: u32) -> Result<()> {
+        let reg_ptr = self.inner.gpio_mem.register_ptr(offset)?;
+
+        ptr::write_volatile(reg_ptr, value);
+
+        Ok(())
+    }
+
+    /// Returns the drive strength, slew rate and hysteresis configuration for the
+    /// specified pad group.
+    ///
+    /// More information can be found at [`set_pad_control`].
+    ///
+    /// [`set_pad_control`]: #method.set_pad_control
+    pub fn pad_control(&self, group: PadGroup) -> Result<PadControl> {
+        if self.inner.backend.is_none() {
+            return Err(Error::RegistersNotSupported);
+        }
+
+        pads::pad_control(group)
+    }
+
+    /// Configures the drive strength, slew rate limiting and input hysteresis for
+    /// all pins in the specified pad group.
+    ///
+    /// Increasing the drive strength and disabling slew rate limiting can help when
+    /// driving long cables or high-capacitance loads, at the cost of more ringing
+    /// and electromagnetic interference. The default configuration is 8 mA, with slew
+    /// rate limiting and hysteresis enabled. The drive strength doesn't limit the
+    /// current a pin can source or sink, and doesn't change the pin's maximum
+    /// ratings.
+    ///
+    /// The pads control registers are only accessible through `/dev/mem`, which
+    /// requires superuser privileges, even if the pins themselves are accessed
+    /// through `/dev/gpiomem` or `/dev/gpiochipN`. The RP1 I/O controller on the
+    /// Raspberry Pi 5 configures pads per pin, and isn't supported.
+    ///
+    /// Returns [`Error::RegistersNotSupported`] for a `Gpio` instance created with
+    /// [`Gpio::with_mock`].
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use rppal::gpio::{DriveStrength, Gpio, PadGroup};
+    ///
+    /// # fn main() -> rppal::gpio::Result<()> {
+    /// // Drive GPIO 0-27 at 16 mA without slew rate limiting
+    /// Gpio::new()?.set_pad_control(PadGroup::Gpio0To27, DriveStrength::Ma16, false, true)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Error::RegistersNotSupported`]: enum.Error.html#variant.RegistersNotSupported
+    /// [`Gpio::with_mock`]: #method.with_mock
+    pub fn set_pad_control(
+        &self,
+        group: PadGroup,
+        drive_strength: DriveStrength,
+        slew_rate_limited: bool,
+        hysteresis: bool,
+    ) -> Result<()> {
+        if self.inner.backend.is_none() {
+            return Err(Error::RegistersNotSupported);
+        }
+
+        pads::set_pad_control(group, drive_strength, slew_rate_limited, hysteresis)
+    }
+
+    /// Returns a [`Pin`] for each of the specified GPIO pin numbers.
+    ///
+    /// `get_multiple` either retrieves all of the requested pins, or none of them.
+    /// If any of the pin numbers is invalid, already in use, or listed more than once,
+    /// any pins retrieved up to that point are released again, and an error is returned
+    /// for the first pin that couldn't be retrieved: [`Error::InvalidPin`] for invalid
+    /// pin numbers, and [`Error::PinNotAvailable`] for pins that are already in use.
+    ///
+    /// The pins are returned in the same order as `pins`.
+    ///
+    /// [`Pin`]: struct.Pin.html
+    /// [`Error::InvalidPin`]: enum.Error.html#variant.InvalidPin
+    /// [`Error::PinNotAvailable`]: enum.Error.html#variant.PinNotAvailable
+    pub fn get_multiple(&self, pins: &[u8]) -> Result<Vec<pin::Pin>> {
+        let mut pin_instances = Vec::with_capacity(pins.len());
+
+        for &pin in pins {
+            match self.get(pin) {
+                Some(pin_instance) => pin_instances.push(pin_instance),
+                // Dropping pin_instances releases any pins we've retrieved so far
+                None if pin as usize >= pin::MAX => return Err(Error::InvalidPin(pin)),
+                None => return Err(Error::PinNotAvailable(pin)),
+            }
+        }
+
+        Ok(pin_instances)
+    }
+
+    /// Reads the logic levels of multiple pins at once, and returns them as a bitmask.
+    ///
+    /// All levels are read from the same register sample, so the result represents
+    /// the state of the pins at a single point in time, which is useful for sampling
+    /// parallel inputs. Bit 0 of the returned value contains the logic level of the
+    /// first pin in `pins`, bit 1 the level of the second pin, etc. A 1 indicates
+    /// the pin's level is high, 0 indicates low. Invalid pin numbers are read as low.
+    ///
+    /// `read_levels` doesn't require the pins to be retrieved using [`get`] first.
+    /// Any pins beyond the first 64 entries are ignored.
+    ///
+    /// [`get`]: #method.get
+    pub fn read_levels(&self, pins: &[u8]) -> u64 {
+        let levels = self.inner.gpio_mem.levels();
+
+        pins.iter()
+            .take(64)
+            .enumerate()
+            .fold(0, |acc, (idx, &pin)| {
+                if (pin as usize) < pin::MAX && (levels >> pin) & 1 == 1 {
+                    acc | (1 << idx)
+                } else {
+                    acc
+                }
+            })
+    }
+
+    /// Returns a [`Pin`] for the specified physical pin number on the GPIO header.
+    ///
+    /// Physical pin numbers start at 1 (3.3 V) in the corner closest to the SD card
+    /// slot, with odd numbers on the inner row and even numbers on the outer row.
+    /// The physical pin is translated to its BCM GPIO number based on the detected
+    /// Raspberry Pi model, after which `get_physical` behaves like [`get`].
+    ///
+    /// Returns [`Error::InvalidPhysicalPin`] if the pin doesn't exist on the header,
+    /// [`Error::NotGpioPin`] if it's a power or ground pin, and [`Error::PinNotAvailable`]
+    /// if the GPIO pin is already in use.
+    ///
+    /// [`Pin`]: struct.Pin.html
+    /// [`get`]: #method.get
+    /// [`Error::InvalidPhysicalPin`]: enum.Error.html#variant.InvalidPhysicalPin
+    /// [`Error::NotGpioPin`]: enum.Error.html#variant.NotGpioPin
+    /// [`Error::PinNotAvailable`]: enum.Error.html#variant.PinNotAvailable
+    pub fn get_physical(&self, header_pin: u8) -> Result<pin::Pin> {
+        let model = DeviceInfo::new().map_err(|_| Error::UnknownModel)?.model();
+
+        match header::physical_pin(model, header_pin) {
+            Some(PhysicalPin::Gpio(pin)) => self.get(pin).ok_or(Error::PinNotAvailable(pin)),
+            Some(physical_pin) => Err(Error::NotGpioPin(header_pin, physical_pin)),
+            None => Err(Error::InvalidPhysicalPin(header_pin)),
+        }
+    }
+
     /// Blocks until an interrupt is triggered on any of the specified pins, or until a timeout occurs.
     ///
     /// This only works for pins that have been configured for synchronous interrupts using
@@ -332,7 +1402,7 @@ impl Gpio {
     /// `timeout` can be set to `None` to wait indefinitely.
     ///
     /// When an interrupt event is triggered, `poll_interrupts` returns
-    /// `Ok((&`[`InputPin`]`, `[`Level`]`))` containing the corresponding pin and logic level. If multiple events trigger
+    /// `Ok((&`[`InputPin`]`, `[`Event`]`))` containing the corresponding pin and event details. If multiple events trigger
     /// at the same time, only the first one is returned. The remaining events are cached and will be returned
     /// the next time [`InputPin::poll_interrupt`] or `poll_interrupts` is called.
     ///
@@ -340,13 +1410,47 @@ impl Gpio {
     /// [`InputPin::poll_interrupt`]: struct.InputPin#method.poll_interrupt
     /// [`InputPin::set_async_interrupt`]: struct.InputPin#method.set_async_interrupt
     /// [`InputPin`]: struct.InputPin
-    /// [`Level`]: struct.Level
+    /// [`Event`]: struct.Event.html
     pub fn poll_interrupts<'a>(
         &self,
         pins: &[&'a InputPin],
         reset: bool,
         timeout: Option<Duration>,
-    ) -> Result<Option<(&'a InputPin, Level)>> {
+    ) -> Result<Option<(&'a InputPin, Event)>> {
         (*self.inner.sync_interrupts.lock().unwrap()).poll(pins, reset, timeout)
     }
+
+    /// Returns a [`MultiInterruptBuilder`], which configures interrupt triggers for
+    /// multiple pins that can be polled simultaneously.
+    ///
+    /// Each pin can be configured with a different trigger and debounce filter.
+    /// The resulting [`MultiInterrupt`] returns an [`Event`] for every trigger,
+    /// containing the pin number, logic level and kernel timestamp.
+    ///
+    /// ```no_run
+    /// use rppal::gpio::{Gpio, Trigger};
+    ///
+    /// # fn main() -> rppal::gpio::Result<()> {
+    /// let gpio = Gpio::new()?;
+    ///
+    /// let mut interrupts = gpio
+    ///     .multi_interrupt()
+    ///     .add(gpio.get(23).unwrap().into_input_pullup(), Trigger::FallingEdge)
+    ///     .add(gpio.get(24).unwrap().into_input(), Trigger::Both)
+    ///     .build()?;
+    ///
+    /// for event in interrupts.events() {
+    ///     let event = event?;
+    ///     println!("Pin {} is {} ({:?})", event.pin(), event.level(), event.timestamp());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`MultiInterruptBuilder`]: struct.MultiInterruptBuilder.html
+    /// [`MultiInterrupt`]: struct.MultiInterrupt.html
+    /// [`Event`]: struct.Event.html
+    pub fn multi_interrupt(&self) -> MultiInterruptBuilder {
+        MultiInterruptBuilder::new(self.inner.clone())
+    }
 }
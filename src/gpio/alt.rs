@@ -0,0 +1,876 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fmt;
+
+use crate::gpio::Mode;
+use crate::system::SoC;
+
+use self::Function::*;
+
+/// Alternate pin functions.
+///
+/// Each GPIO pin can be connected to one of up to six peripheral functions by
+/// switching it to one of the [`Mode::Alt0`]-[`Mode::Alt5`] modes. `Function`
+/// describes the peripheral signal that's selected by a specific mode, and can
+/// be passed to [`Pin::into_alt_function`] instead of an alternate mode.
+///
+/// Bus, channel and signal numbers follow the naming used in the BCM2835 and
+/// BCM2711 datasheets.
+///
+/// [`Mode::Alt0`]: enum.Mode.html#variant.Alt0
+/// [`Mode::Alt5`]: enum.Mode.html#variant.Alt5
+/// [`Pin::into_alt_function`]: struct.Pin.html#method.into_alt_function
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Function {
+    /// I2C bus data line (`SDAn`).
+    I2cSda(u8),
+    /// I2C bus clock line (`SCLn`).
+    I2cScl(u8),
+    /// SPI bus chip enable line (`SPIn_CEm_N`), specified as bus and chip enable number.
+    SpiCe(u8, u8),
+    /// SPI bus master in, slave out (`SPIn_MISO`).
+    SpiMiso(u8),
+    /// SPI bus master out, slave in (`SPIn_MOSI`).
+    SpiMosi(u8),
+    /// SPI bus serial clock (`SPIn_SCLK`).
+    SpiSclk(u8),
+    /// PWM channel output (`PWM0`, `PWM1`).
+    Pwm(u8),
+    /// General purpose clock output (`GPCLKn`).
+    Gpclk(u8),
+    /// UART transmit data (`TXDn`).
+    UartTxd(u8),
+    /// UART receive data (`RXDn`).
+    UartRxd(u8),
+    /// UART clear to send (`CTSn`).
+    UartCts(u8),
+    /// UART request to send (`RTSn`).
+    UartRts(u8),
+    /// PCM clock (`PCM_CLK`).
+    PcmClk,
+    /// PCM frame sync (`PCM_FS`).
+    PcmFs,
+    /// PCM data in (`PCM_DIN`).
+    PcmDin,
+    /// PCM data out (`PCM_DOUT`).
+    PcmDout,
+    /// BSC/SPI slave data (`BSCSL SDA / MOSI`).
+    BscSlaveSdaMosi,
+    /// BSC/SPI slave clock (`BSCSL SCL / SCLK`).
+    BscSlaveSclSclk,
+    /// SPI slave master in, slave out (`BSCSL / MISO`).
+    BscSlaveMiso,
+    /// SPI slave chip enable (`BSCSL / CE_N`).
+    BscSlaveCe,
+    /// SD card interface clock (`SDn_CLK`).
+    SdClk(u8),
+    /// SD card interface command (`SDn_CMD`).
+    SdCmd(u8),
+    /// SD card interface data line (`SDn_DATm`), specified as interface and line number.
+    SdData(u8, u8),
+    /// Secondary memory interface address line (`SAn`).
+    SmiAddress(u8),
+    /// Secondary memory interface data line (`SDn`).
+    SmiData(u8),
+    /// Secondary memory interface output enable (`SOE_N / SE`).
+    SmiOutputEnable,
+    /// Secondary memory interface write enable (`SWE_N / SRW_N`).
+    SmiWriteEnable,
+    /// Display parallel interface pixel clock (`PCLK`).
+    DpiPclk,
+    /// Display parallel interface data enable (`DE`).
+    DpiDe,
+    /// Display parallel interface vertical sync (`LCD_VSYNC`).
+    DpiVsync,
+    /// Display parallel interface horizontal sync (`LCD_HSYNC`).
+    DpiHsync,
+    /// Display parallel interface data line (`DPI_Dn`).
+    DpiData(u8),
+    /// ARM JTAG test data in (`ARM_TDI`).
+    ArmTdi,
+    /// ARM JTAG test data out (`ARM_TDO`).
+    ArmTdo,
+    /// ARM JTAG return clock (`ARM_RTCK`).
+    ArmRtck,
+    /// ARM JTAG test mode select (`ARM_TMS`).
+    ArmTms,
+    /// ARM JTAG test clock (`ARM_TCK`).
+    ArmTck,
+    /// ARM JTAG test reset (`ARM_TRST`).
+    ArmTrst,
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            I2cSda(bus) => write!(f, "SDA{}", bus),
+            I2cScl(bus) => write!(f, "SCL{}", bus),
+            SpiCe(bus, ce) => write!(f, "SPI{}_CE{}_N", bus, ce),
+            SpiMiso(bus) => write!(f, "SPI{}_MISO", bus),
+            SpiMosi(bus) => write!(f, "SPI{}_MOSI", bus),
+            SpiSclk(bus) => write!(f, "SPI{}_SCLK", bus),
+            Pwm(channel) => write!(f, "PWM{}", channel),
+            Gpclk(clock) => write!(f, "GPCLK{}", clock),
+            UartTxd(uart) => write!(f, "TXD{}", uart),
+            UartRxd(uart) => write!(f, "RXD{}", uart),
+            UartCts(uart) => write!(f, "CTS{}", uart),
+            UartRts(uart) => write!(f, "RTS{}", uart),
+            PcmClk => write!(f, "PCM_CLK"),
+            PcmFs => write!(f, "PCM_FS"),
+            PcmDin => write!(f, "PCM_DIN"),
+            PcmDout => write!(f, "PCM_DOUT"),
+            BscSlaveSdaMosi => write!(f, "BSCSL_SDA_MOSI"),
+            BscSlaveSclSclk => write!(f, "BSCSL_SCL_SCLK"),
+            BscSlaveMiso => write!(f, "BSCSL_MISO"),
+            BscSlaveCe => write!(f, "BSCSL_CE_N"),
+            SdClk(interface) => write!(f, "SD{}_CLK", interface),
+            SdCmd(interface) => write!(f, "SD{}_CMD", interface),
+            SdData(interface, line) => write!(f, "SD{}_DAT{}", interface, line),
+            SmiAddress(line) => write!(f, "SA{}", line),
+            SmiData(line) => write!(f, "SD{}", line),
+            SmiOutputEnable => write!(f, "SOE_N"),
+            SmiWriteEnable => write!(f, "SWE_N"),
+            DpiPclk => write!(f, "PCLK"),
+            DpiDe => write!(f, "DE"),
+            DpiVsync => write!(f, "LCD_VSYNC"),
+            DpiHsync => write!(f, "LCD_HSYNC"),
+            DpiData(line) => write!(f, "DPI_D{}", line),
+            ArmTdi => write!(f, "ARM_TDI"),
+            ArmTdo => write!(f, "ARM_TDO"),
+            ArmRtck => write!(f, "ARM_RTCK"),
+            ArmTms => write!(f, "ARM_TMS"),
+            ArmTck => write!(f, "ARM_TCK"),
+            ArmTrst => write!(f, "ARM_TRST"),
+        }
+    }
+}
+
+const ALT_MODES: [Mode; 6] = [
+    Mode::Alt0,
+    Mode::Alt1,
+    Mode::Alt2,
+    Mode::Alt3,
+    Mode::Alt4,
+    Mode::Alt5,
+];
+
+/// Returns the alternate functions available on the specified pin for the given SoC.
+///
+/// Each entry pairs an alternate mode with the function it selects. Modes that
+/// are reserved, or not documented, aren't included.
+///
+/// The BCM2835, BCM2836 and BCM2837 share the same alternate function
+/// assignments, and are covered for GPIO 0-45. On the BCM2711, only GPIO 0-27 are
+/// included. GPIO 46-53 are used internally, and the BCM2712 (Raspberry Pi 5)
+/// routes its GPIO pins through the RP1 I/O controller, so an empty list is returned
+/// for those.
+///
+/// Pins are addressed by their BCM numbers, rather than their physical location.
+pub fn alt_functions(soc: SoC, pin: u8) -> Vec<(Mode, Function)> {
+    let table: &[[Option<Function>; 6]] = match soc {
+        SoC::Bcm2835 | SoC::Bcm2836 | SoC::Bcm2837A1 | SoC::Bcm2837B0 => &ALT_BCM2835,
+        SoC::Bcm2711 => &ALT_BCM2711,
+        SoC::Bcm2712 => &[],
+    };
+
+    match table.get(pin as usize) {
+        Some(functions) => ALT_MODES
+            .iter()
+            .zip(functions.iter())
+            .filter_map(|(&mode, &function)| function.map(|function| (mode, function)))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+// BCM2835 ARM Peripherals @ 6.2 (Alternative Function Assignments)
+const ALT_BCM2835: [[Option<Function>; 6]; 46] = [
+    // GPIO 0
+    [
+        Some(I2cSda(0)),
+        Some(SmiAddress(5)),
+        Some(DpiPclk),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 1
+    [
+        Some(I2cScl(0)),
+        Some(SmiAddress(4)),
+        Some(DpiDe),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 2
+    [
+        Some(I2cSda(1)),
+        Some(SmiAddress(3)),
+        Some(DpiVsync),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 3
+    [
+        Some(I2cScl(1)),
+        Some(SmiAddress(2)),
+        Some(DpiHsync),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 4
+    [
+        Some(Gpclk(0)),
+        Some(SmiAddress(1)),
+        Some(DpiData(0)),
+        None,
+        None,
+        Some(ArmTdi),
+    ],
+    // GPIO 5
+    [
+        Some(Gpclk(1)),
+        Some(SmiAddress(0)),
+        Some(DpiData(1)),
+        None,
+        None,
+        Some(ArmTdo),
+    ],
+    // GPIO 6
+    [
+        Some(Gpclk(2)),
+        Some(SmiOutputEnable),
+        Some(DpiData(2)),
+        None,
+        None,
+        Some(ArmRtck),
+    ],
+    // GPIO 7
+    [
+        Some(SpiCe(0, 1)),
+        Some(SmiWriteEnable),
+        Some(DpiData(3)),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 8
+    [
+        Some(SpiCe(0, 0)),
+        Some(SmiData(0)),
+        Some(DpiData(4)),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 9
+    [
+        Some(SpiMiso(0)),
+        Some(SmiData(1)),
+        Some(DpiData(5)),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 10
+    [
+        Some(SpiMosi(0)),
+        Some(SmiData(2)),
+        Some(DpiData(6)),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 11
+    [
+        Some(SpiSclk(0)),
+        Some(SmiData(3)),
+        Some(DpiData(7)),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 12
+    [
+        Some(Pwm(0)),
+        Some(SmiData(4)),
+        Some(DpiData(8)),
+        None,
+        None,
+        Some(ArmTms),
+    ],
+    // GPIO 13
+    [
+        Some(Pwm(1)),
+        Some(SmiData(5)),
+        Some(DpiData(9)),
+        None,
+        None,
+        Some(ArmTck),
+    ],
+    // GPIO 14
+    [
+        Some(UartTxd(0)),
+        Some(SmiData(6)),
+        Some(DpiData(10)),
+        None,
+        None,
+        Some(UartTxd(1)),
+    ],
+    // GPIO 15
+    [
+        Some(UartRxd(0)),
+        Some(SmiData(7)),
+        Some(DpiData(11)),
+        None,
+        None,
+        Some(UartRxd(1)),
+    ],
+    // GPIO 16
+    [
+        None,
+        Some(SmiData(8)),
+        Some(DpiData(12)),
+        Some(UartCts(0)),
+        Some(SpiCe(1, 2)),
+        Some(UartCts(1)),
+    ],
+    // GPIO 17
+    [
+        None,
+        Some(SmiData(9)),
+        Some(DpiData(13)),
+        Some(UartRts(0)),
+        Some(SpiCe(1, 1)),
+        Some(UartRts(1)),
+    ],
+    // GPIO 18
+    [
+        Some(PcmClk),
+        Some(SmiData(10)),
+        Some(DpiData(14)),
+        Some(BscSlaveSdaMosi),
+        Some(SpiCe(1, 0)),
+        Some(Pwm(0)),
+    ],
+    // GPIO 19
+    [
+        Some(PcmFs),
+        Some(SmiData(11)),
+        Some(DpiData(15)),
+        Some(BscSlaveSclSclk),
+        Some(SpiMiso(1)),
+        Some(Pwm(1)),
+    ],
+    // GPIO 20
+    [
+        Some(PcmDin),
+        Some(SmiData(12)),
+        Some(DpiData(16)),
+        Some(BscSlaveMiso),
+        Some(SpiMosi(1)),
+        Some(Gpclk(0)),
+    ],
+    // GPIO 21
+    [
+        Some(PcmDout),
+        Some(SmiData(13)),
+        Some(DpiData(17)),
+        Some(BscSlaveCe),
+        Some(SpiSclk(1)),
+        Some(Gpclk(1)),
+    ],
+    // GPIO 22
+    [
+        None,
+        Some(SmiData(14)),
+        Some(DpiData(18)),
+        Some(SdClk(1)),
+        Some(ArmTrst),
+        None,
+    ],
+    // GPIO 23
+    [
+        None,
+        Some(SmiData(15)),
+        Some(DpiData(19)),
+        Some(SdCmd(1)),
+        Some(ArmRtck),
+        None,
+    ],
+    // GPIO 24
+    [
+        None,
+        Some(SmiData(16)),
+        Some(DpiData(20)),
+        Some(SdData(1, 0)),
+        Some(ArmTdo),
+        None,
+    ],
+    // GPIO 25
+    [
+        None,
+        Some(SmiData(17)),
+        Some(DpiData(21)),
+        Some(SdData(1, 1)),
+        Some(ArmTck),
+        None,
+    ],
+    // GPIO 26
+    [
+        None,
+        None,
+        Some(DpiData(22)),
+        Some(SdData(1, 2)),
+        Some(ArmTdi),
+        None,
+    ],
+    // GPIO 27
+    [
+        None,
+        None,
+        Some(DpiData(23)),
+        Some(SdData(1, 3)),
+        Some(ArmTms),
+        None,
+    ],
+    // GPIO 28
+    [
+        Some(I2cSda(0)),
+        Some(SmiAddress(5)),
+        Some(PcmClk),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 29
+    [
+        Some(I2cScl(0)),
+        Some(SmiAddress(4)),
+        Some(PcmFs),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 30
+    [
+        None,
+        Some(SmiAddress(3)),
+        Some(PcmDin),
+        Some(UartCts(0)),
+        None,
+        Some(UartCts(1)),
+    ],
+    // GPIO 31
+    [
+        None,
+        Some(SmiAddress(2)),
+        Some(PcmDout),
+        Some(UartRts(0)),
+        None,
+        Some(UartRts(1)),
+    ],
+    // GPIO 32
+    [
+        Some(Gpclk(0)),
+        Some(SmiAddress(1)),
+        None,
+        Some(UartTxd(0)),
+        None,
+        Some(UartTxd(1)),
+    ],
+    // GPIO 33
+    [
+        None,
+        Some(SmiAddress(0)),
+        None,
+        Some(UartRxd(0)),
+        None,
+        Some(UartRxd(1)),
+    ],
+    // GPIO 34
+    [
+        Some(Gpclk(0)),
+        Some(SmiOutputEnable),
+        None,
+        None,
+        None,
+        None,
+    ],
+    // GPIO 35
+    [
+        Some(SpiCe(0, 1)),
+        Some(SmiWriteEnable),
+        None,
+        None,
+        None,
+        None,
+    ],
+    // GPIO 36
+    [
+        Some(SpiCe(0, 0)),
+        Some(SmiData(0)),
+        Some(UartTxd(0)),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 37
+    [
+        Some(SpiMiso(0)),
+        Some(SmiData(1)),
+        Some(UartRxd(0)),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 38
+    [
+        Some(SpiMosi(0)),
+        Some(SmiData(2)),
+        Some(UartRts(0)),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 39
+    [
+        Some(SpiSclk(0)),
+        Some(SmiData(3)),
+        Some(UartCts(0)),
+        None,
+        None,
+        None,
+    ],
+    // GPIO 40
+    [
+        Some(Pwm(0)),
+        Some(SmiData(4)),
+        None,
+        None,
+        Some(SpiMiso(2)),
+        Some(UartTxd(1)),
+    ],
+    // GPIO 41
+    [
+        Some(Pwm(1)),
+        Some(SmiData(5)),
+        None,
+        None,
+        Some(SpiMosi(2)),
+        Some(UartRxd(1)),
+    ],
+    // GPIO 42
+    [
+        Some(Gpclk(1)),
+        Some(SmiData(6)),
+        None,
+        None,
+        Some(SpiSclk(2)),
+        Some(UartRts(1)),
+    ],
+    // GPIO 43
+    [
+        Some(Gpclk(2)),
+        Some(SmiData(7)),
+        None,
+        None,
+        Some(SpiCe(2, 0)),
+        Some(UartCts(1)),
+    ],
+    // GPIO 44
+    [
+        Some(Gpclk(1)),
+        Some(I2cSda(0)),
+        Some(I2cSda(1)),
+        None,
+        Some(SpiCe(2, 1)),
+        None,
+    ],
+    // GPIO 45
+    [
+        Some(Pwm(1)),
+        Some(I2cScl(0)),
+        Some(I2cScl(1)),
+        None,
+        Some(SpiCe(2, 2)),
+        None,
+    ],
+];
+
+// BCM2711 ARM Peripherals @ 5.3 (Alternative Function Assignments)
+const ALT_BCM2711: [[Option<Function>; 6]; 28] = [
+    // GPIO 0
+    [
+        Some(I2cSda(0)),
+        Some(SmiAddress(5)),
+        Some(DpiPclk),
+        Some(SpiCe(3, 0)),
+        Some(UartTxd(2)),
+        Some(I2cSda(6)),
+    ],
+    // GPIO 1
+    [
+        Some(I2cScl(0)),
+        Some(SmiAddress(4)),
+        Some(DpiDe),
+        Some(SpiMiso(3)),
+        Some(UartRxd(2)),
+        Some(I2cScl(6)),
+    ],
+    // GPIO 2
+    [
+        Some(I2cSda(1)),
+        Some(SmiAddress(3)),
+        Some(DpiVsync),
+        Some(SpiMosi(3)),
+        Some(UartCts(2)),
+        Some(I2cSda(3)),
+    ],
+    // GPIO 3
+    [
+        Some(I2cScl(1)),
+        Some(SmiAddress(2)),
+        Some(DpiHsync),
+        Some(SpiSclk(3)),
+        Some(UartRts(2)),
+        Some(I2cScl(3)),
+    ],
+    // GPIO 4
+    [
+        Some(Gpclk(0)),
+        Some(SmiAddress(1)),
+        Some(DpiData(0)),
+        Some(SpiCe(4, 0)),
+        Some(UartTxd(3)),
+        Some(I2cSda(3)),
+    ],
+    // GPIO 5
+    [
+        Some(Gpclk(1)),
+        Some(SmiAddress(0)),
+        Some(DpiData(1)),
+        Some(SpiMiso(4)),
+        Some(UartRxd(3)),
+        Some(I2cScl(3)),
+    ],
+    // GPIO 6
+    [
+        Some(Gpclk(2)),
+        Some(SmiOutputEnable),
+        Some(DpiData(2)),
+        Some(SpiMosi(4)),
+        Some(UartCts(3)),
+        Some(I2cSda(4)),
+    ],
+    // GPIO 7
+    [
+        Some(SpiCe(0, 1)),
+        Some(SmiWriteEnable),
+        Some(DpiData(3)),
+        Some(SpiSclk(4)),
+        Some(UartRts(3)),
+        Some(I2cScl(4)),
+    ],
+    // GPIO 8
+    [
+        Some(SpiCe(0, 0)),
+        Some(SmiData(0)),
+        Some(DpiData(4)),
+        Some(BscSlaveCe),
+        Some(UartTxd(4)),
+        Some(I2cSda(4)),
+    ],
+    // GPIO 9
+    [
+        Some(SpiMiso(0)),
+        Some(SmiData(1)),
+        Some(DpiData(5)),
+        Some(BscSlaveMiso),
+        Some(UartRxd(4)),
+        Some(I2cScl(4)),
+    ],
+    // GPIO 10
+    [
+        Some(SpiMosi(0)),
+        Some(SmiData(2)),
+        Some(DpiData(6)),
+        Some(BscSlaveSdaMosi),
+        Some(UartCts(4)),
+        Some(I2cSda(5)),
+    ],
+    // GPIO 11
+    [
+        Some(SpiSclk(0)),
+        Some(SmiData(3)),
+        Some(DpiData(7)),
+        Some(BscSlaveSclSclk),
+        Some(UartRts(4)),
+        Some(I2cScl(5)),
+    ],
+    // GPIO 12
+    [
+        Some(Pwm(0)),
+        Some(SmiData(4)),
+        Some(DpiData(8)),
+        Some(SpiCe(5, 0)),
+        Some(UartTxd(5)),
+        Some(I2cSda(5)),
+    ],
+    // GPIO 13
+    [
+        Some(Pwm(1)),
+        Some(SmiData(5)),
+        Some(DpiData(9)),
+        Some(SpiMiso(5)),
+        Some(UartRxd(5)),
+        Some(I2cScl(5)),
+    ],
+    // GPIO 14
+    [
+        Some(UartTxd(0)),
+        Some(SmiData(6)),
+        Some(DpiData(10)),
+        Some(SpiMosi(5)),
+        Some(UartCts(5)),
+        Some(UartTxd(1)),
+    ],
+    // GPIO 15
+    [
+        Some(UartRxd(0)),
+        Some(SmiData(7)),
+        Some(DpiData(11)),
+        Some(SpiSclk(5)),
+        Some(UartRts(5)),
+        Some(UartRxd(1)),
+    ],
+    // GPIO 16
+    [
+        None,
+        Some(SmiData(8)),
+        Some(DpiData(12)),
+        Some(UartCts(0)),
+        Some(SpiCe(1, 2)),
+        Some(UartCts(1)),
+    ],
+    // GPIO 17
+    [
+        None,
+        Some(SmiData(9)),
+        Some(DpiData(13)),
+        Some(UartRts(0)),
+        Some(SpiCe(1, 1)),
+        Some(UartRts(1)),
+    ],
+    // GPIO 18
+    [
+        Some(PcmClk),
+        Some(SmiData(10)),
+        Some(DpiData(14)),
+        Some(SpiCe(6, 0)),
+        Some(SpiCe(1, 0)),
+        Some(Pwm(0)),
+    ],
+    // GPIO 19
+    [
+        Some(PcmFs),
+        Some(SmiData(11)),
+        Some(DpiData(15)),
+        Some(SpiMiso(6)),
+        Some(SpiMiso(1)),
+        Some(Pwm(1)),
+    ],
+    // GPIO 20
+    [
+        Some(PcmDin),
+        Some(SmiData(12)),
+        Some(DpiData(16)),
+        Some(SpiMosi(6)),
+        Some(SpiMosi(1)),
+        Some(Gpclk(0)),
+    ],
+    // GPIO 21
+    [
+        Some(PcmDout),
+        Some(SmiData(13)),
+        Some(DpiData(17)),
+        Some(SpiSclk(6)),
+        Some(SpiSclk(1)),
+        Some(Gpclk(1)),
+    ],
+    // GPIO 22
+    [
+        Some(SdClk(0)),
+        Some(SmiData(14)),
+        Some(DpiData(18)),
+        Some(SdClk(1)),
+        Some(ArmTrst),
+        Some(I2cSda(6)),
+    ],
+    // GPIO 23
+    [
+        Some(SdCmd(0)),
+        Some(SmiData(15)),
+        Some(DpiData(19)),
+        Some(SdCmd(1)),
+        Some(ArmRtck),
+        Some(I2cScl(6)),
+    ],
+    // GPIO 24
+    [
+        Some(SdData(0, 0)),
+        Some(SmiData(16)),
+        Some(DpiData(20)),
+        Some(SdData(1, 0)),
+        Some(ArmTdo),
+        Some(SpiCe(3, 1)),
+    ],
+    // GPIO 25
+    [
+        Some(SdData(0, 1)),
+        Some(SmiData(17)),
+        Some(DpiData(21)),
+        Some(SdData(1, 1)),
+        Some(ArmTck),
+        Some(SpiCe(4, 1)),
+    ],
+    // GPIO 26
+    [
+        Some(SdData(0, 2)),
+        None,
+        Some(DpiData(22)),
+        Some(SdData(1, 2)),
+        Some(ArmTdi),
+        Some(SpiCe(5, 1)),
+    ],
+    // GPIO 27
+    [
+        Some(SdData(0, 3)),
+        None,
+        Some(DpiData(23)),
+        Some(SdData(1, 3)),
+        Some(ArmTms),
+        Some(SpiCe(6, 1)),
+    ],
+];
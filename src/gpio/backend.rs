@@ -0,0 +1,145 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fmt;
+use std::ptr;
+
+use crate::gpio::{Error, Level, Mode, PullUpDown, Result, Trigger};
+use crate::system::SoC;
+
+// Register-level access to the GPIO peripheral. GpioMem implements this through
+// memory-mapped registers, GpioCdev through line requests on /dev/gpiochipN, and
+// MockGpio keeps the pin states in memory, which allows Gpio and all pin types
+// to run unmodified on top of any of the backends.
+pub(crate) trait GpioRegisters: fmt::Debug + Send + Sync {
+    fn soc(&self) -> SoC;
+    fn set_high(&self, pin: u8);
+    fn set_low(&self, pin: u8);
+    // Sets all pins in set_mask high and all pins in clear_mask low
+    fn write_levels(&self, set_mask: u64, clear_mask: u64);
+    fn level(&self, pin: u8) -> Level;
+    // Returns the logic levels of all pins as a bitmask, with bit 0 mapped to GPIO 0
+    fn levels(&self) -> u64;
+    fn mode(&self, pin: u8) -> Mode;
+    fn set_mode(&self, pin: u8, mode: Mode);
+    fn set_pullupdown(&self, pin: u8, pud: PullUpDown);
+    // Returns None if the backend can't read back the configured state
+    fn pullupdown(&self, pin: u8) -> Option<PullUpDown>;
+    // Releases any resources held for the pin that would prevent it from being
+    // requested for interrupts
+    fn release(&self, _pin: u8) {}
+    // Returns direct pointers to the pin's GPSET/GPCLR registers, if the backend
+    // is memory-mapped
+    fn fast_io(&self, _pin: u8) -> Option<FastIo> {
+        None
+    }
+    // Returns a pointer to the 32-bit register at the specified byte offset, if the
+    // backend is memory-mapped
+    fn register_ptr(&self, _offset: usize) -> Result<*mut u32> {
+        Err(Error::RegistersNotSupported)
+    }
+    // Configures edge detection through GPRENn/GPFENn, which sets the pin's bit
+    // in GPEDSn when a matching edge occurs
+    fn set_edge_detect(&self, _pin: u8, _trigger: Trigger) -> Result<()> {
+        Err(Error::RegistersNotSupported)
+    }
+    // Returns true if the pin's bit in GPEDSn is set, and clears it
+    fn take_edge_event(&self, _pin: u8) -> Result<bool> {
+        Err(Error::RegistersNotSupported)
+    }
+}
+
+// Pointers to the registers that set and clear a single pin's output level, used
+// by OutputPin's fast I/O methods to skip the dynamic dispatch and register offset
+// calculations. The pointers remain valid for as long as the backend that created
+// them is alive.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct FastIo {
+    set: *mut u32,
+    clear: *mut u32,
+    mask: u32,
+}
+
+impl FastIo {
+    // The caller needs to make sure set and clear point to the memory-mapped
+    // GPSETn/GPCLRn registers, and outlive FastIo.
+    pub(crate) unsafe fn new(set: *mut u32, clear: *mut u32, mask: u32) -> FastIo {
+        FastIo { set, clear, mask }
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_high(&self) {
+        unsafe {
+            ptr::write_volatile(self.set, self.mask);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_low(&self) {
+        unsafe {
+            ptr::write_volatile(self.clear, self.mask);
+        }
+    }
+}
+
+// Required because of the raw pointers to the memory-mapped registers. Writes to
+// GPSETn/GPCLRn only affect the bits that are set, so they're safe to issue from
+// multiple threads.
+unsafe impl Send for FastIo {}
+unsafe impl Sync for FastIo {}
+
+/// GPIO backends.
+///
+/// The backend determines how [`Gpio`] accesses the GPIO pins. Interrupts are
+/// always handled through `/dev/gpiochipN`.
+///
+/// [`Gpio`]: struct.Gpio.html
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Backend {
+    /// Memory-mapped GPIO registers through `/dev/gpiomem` or `/dev/mem` (default).
+    ///
+    /// This provides the fastest access, and supports all pin modes.
+    GpioMem,
+    /// Line requests through the GPIO character device `/dev/gpiochipN`.
+    ///
+    /// This works on systems where memory-mapping the GPIO registers isn't
+    /// permitted, for instance within containers that only expose `/dev/gpiochipN`.
+    /// Each pin is requested as a line when it's configured as an input or output,
+    /// which is slower than register access, and prevents other processes from
+    /// claiming the pin.
+    ///
+    /// The alternate modes can't be selected through the character device. Setting
+    /// a pin to [`Mode::Alt0`]-[`Mode::Alt5`] releases the line, which resets it to an
+    /// input. While an interrupt is configured on a pin, the kernel doesn't allow
+    /// the line to be requested again, and reading its logic level returns `Low`.
+    ///
+    /// [`Mode::Alt0`]: enum.Mode.html#variant.Alt0
+    /// [`Mode::Alt5`]: enum.Mode.html#variant.Alt5
+    CharDev,
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Backend::GpioMem => write!(f, "GpioMem"),
+            Backend::CharDev => write!(f, "CharDev"),
+        }
+    }
+}
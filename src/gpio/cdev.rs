@@ -0,0 +1,239 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fmt;
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+use std::sync::Mutex;
+
+use libc::c_int;
+
+use crate::gpio::backend::GpioRegisters;
+use crate::gpio::ioctl::{self, HandleRequest, LineInfo};
+use crate::gpio::{pin, Level, Mode, PullUpDown, Result};
+use crate::system::{DeviceInfo, SoC};
+
+#[derive(Debug, Copy, Clone)]
+struct LineState {
+    // Line handle fd, which is kept open while the pin is configured as an input
+    // or output through this backend
+    handle_fd: Option<c_int>,
+    mode: Option<Mode>,
+    pud: Option<PullUpDown>,
+    // Output level, which is applied when the pin switches to output mode,
+    // similar to the output latch on the BCM283x
+    level: Level,
+}
+
+impl Default for LineState {
+    fn default() -> LineState {
+        LineState {
+            handle_fd: None,
+            mode: None,
+            pud: None,
+            level: Level::Low,
+        }
+    }
+}
+
+// GPIO backend that configures pins through line handles on /dev/gpiochipN, rather
+// than by accessing the GPIO registers directly.
+//
+// The character device doesn't provide access to the alternate functions. Switching
+// a pin to any of the alternate modes releases the line, which resets it to an input.
+pub struct GpioCdev {
+    cdev: File,
+    soc: SoC,
+    lines: Mutex<Vec<LineState>>,
+}
+
+impl fmt::Debug for GpioCdev {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GpioCdev")
+            .field("cdev", &self.cdev)
+            .field("soc", &self.soc)
+            .field("lines", &format_args!("{{ .. }}"))
+            .finish()
+    }
+}
+
+impl GpioCdev {
+    pub fn open() -> Result<GpioCdev> {
+        let soc = DeviceInfo::new()
+            .map(|device_info| device_info.soc())
+            .unwrap_or(SoC::Bcm2835);
+        let cdev = ioctl::find_gpiochip()?;
+
+        Ok(GpioCdev {
+            cdev,
+            soc,
+            lines: Mutex::new(vec![LineState::default(); pin::MAX]),
+        })
+    }
+
+    // (Re)requests the line with the configured mode and pull-up/pull-down state
+    fn request(&self, pin: u8, line: &mut LineState) {
+        if let Some(fd) = line.handle_fd.take() {
+            ioctl::close(fd);
+        }
+
+        let flags = match line.mode {
+            Some(Mode::Output) => ioctl::HANDLE_FLAG_OUTPUT,
+            Some(Mode::Input) => ioctl::HANDLE_FLAG_INPUT,
+            _ => return,
+        };
+
+        // The kernel only accepts bias flags for inputs
+        let bias = match (line.mode, line.pud) {
+            (Some(Mode::Input), Some(PullUpDown::PullUp)) => ioctl::HANDLE_FLAG_BIAS_PULL_UP,
+            (Some(Mode::Input), Some(PullUpDown::PullDown)) => ioctl::HANDLE_FLAG_BIAS_PULL_DOWN,
+            (Some(Mode::Input), Some(PullUpDown::Off)) => ioctl::HANDLE_FLAG_BIAS_DISABLE,
+            _ => 0,
+        };
+
+        let fd = self.cdev.as_raw_fd();
+
+        // Kernels older than 5.5 don't support bias flags, so try once more without them
+        let handle_request = HandleRequest::with_flags(fd, pin, flags | bias, line.level)
+            .or_else(|_| HandleRequest::with_flags(fd, pin, flags, line.level));
+
+        if let Ok(handle_request) = handle_request {
+            line.handle_fd = Some(handle_request.fd);
+        }
+    }
+
+    fn write(&self, pin: u8, level: Level) {
+        let mut lines = self.lines.lock().unwrap();
+
+        if let Some(line) = lines.get_mut(pin as usize) {
+            line.level = level;
+
+            if let (Some(Mode::Output), Some(fd)) = (line.mode, line.handle_fd) {
+                let _ = ioctl::set_line_level(fd, level);
+            }
+        }
+    }
+}
+
+impl GpioRegisters for GpioCdev {
+    fn soc(&self) -> SoC {
+        self.soc
+    }
+
+    fn set_high(&self, pin: u8) {
+        self.write(pin, Level::High);
+    }
+
+    fn set_low(&self, pin: u8) {
+        self.write(pin, Level::Low);
+    }
+
+    fn write_levels(&self, set_mask: u64, clear_mask: u64) {
+        for pin in 0..pin::MAX as u8 {
+            if (clear_mask >> pin) & 1 == 1 {
+                self.write(pin, Level::Low);
+            } else if (set_mask >> pin) & 1 == 1 {
+                self.write(pin, Level::High);
+            }
+        }
+    }
+
+    fn level(&self, pin: u8) -> Level {
+        let handle_fd = match self.lines.lock().unwrap().get(pin as usize) {
+            Some(line) => line.handle_fd,
+            None => return Level::Low,
+        };
+
+        let level = match handle_fd {
+            Some(fd) => ioctl::get_line_level(fd),
+            None => ioctl::get_level(self.cdev.as_raw_fd(), pin),
+        };
+
+        level.unwrap_or(Level::Low)
+    }
+
+    fn levels(&self) -> u64 {
+        (0..pin::MAX as u8).fold(0, |acc, pin| match self.level(pin) {
+            Level::High => acc | (1 << pin),
+            Level::Low => acc,
+        })
+    }
+
+    fn mode(&self, pin: u8) -> Mode {
+        let mode = match self.lines.lock().unwrap().get(pin as usize) {
+            Some(line) if line.handle_fd.is_some() => line.mode,
+            _ => None,
+        };
+
+        mode.unwrap_or_else(|| match LineInfo::with_pin(self.cdev.as_raw_fd(), pin) {
+            Ok(line_info) if line_info.is_output() => Mode::Output,
+            _ => Mode::Input,
+        })
+    }
+
+    fn set_mode(&self, pin: u8, mode: Mode) {
+        let mut lines = self.lines.lock().unwrap();
+
+        if let Some(line) = lines.get_mut(pin as usize) {
+            line.mode = Some(mode);
+            self.request(pin, line);
+        }
+    }
+
+    fn set_pullupdown(&self, pin: u8, pud: PullUpDown) {
+        let mut lines = self.lines.lock().unwrap();
+
+        if let Some(line) = lines.get_mut(pin as usize) {
+            line.pud = Some(pud);
+
+            if line.handle_fd.is_some() {
+                self.request(pin, line);
+            }
+        }
+    }
+
+    fn pullupdown(&self, pin: u8) -> Option<PullUpDown> {
+        self.lines
+            .lock()
+            .unwrap()
+            .get(pin as usize)
+            .and_then(|line| line.pud)
+    }
+
+    fn release(&self, pin: u8) {
+        let mut lines = self.lines.lock().unwrap();
+
+        if let Some(line) = lines.get_mut(pin as usize) {
+            if let Some(fd) = line.handle_fd.take() {
+                ioctl::close(fd);
+            }
+        }
+    }
+}
+
+impl Drop for GpioCdev {
+    fn drop(&mut self) {
+        for line in self.lines.lock().unwrap().iter_mut() {
+            if let Some(fd) = line.handle_fd.take() {
+                ioctl::close(fd);
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::sync::atomic::Ordering;
+
+use crate::gpio::trace::VcdTracer;
+use crate::gpio::{
+    pin, Backend, Gpio, GpioState, Level, Mode, PullUpDown, Recorder, Result, Trigger,
+};
+
+// Crate-wide defaults that determine how pins are restored when they go out
+// of scope, or when Gpio::cleanup() is called.
+#[derive(Debug, Clone)]
+pub(crate) struct CleanupPolicy {
+    pub(crate) reset_on_drop: bool,
+    pub(crate) drop_levels: [Option<Level>; pin::MAX],
+    pub(crate) clear_interrupts: bool,
+}
+
+impl Default for CleanupPolicy {
+    fn default() -> CleanupPolicy {
+        CleanupPolicy {
+            reset_on_drop: true,
+            drop_levels: [None; pin::MAX],
+            clear_interrupts: true,
+        }
+    }
+}
+
+// The original state of a taken pin, recorded when it was retrieved through
+// Gpio::get() or Gpio::steal(), which is restored by Gpio::cleanup().
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct PinRecord {
+    pub(crate) mode: Mode,
+    pub(crate) pud_changed: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct CleanupState {
+    pub(crate) policy: CleanupPolicy,
+    pub(crate) pins: [Option<PinRecord>; pin::MAX],
+}
+
+impl Default for CleanupState {
+    fn default() -> CleanupState {
+        CleanupState {
+            policy: CleanupPolicy::default(),
+            pins: [None; pin::MAX],
+        }
+    }
+}
+
+// Restores all taken pins based on their records and the current policy.
+pub(crate) fn cleanup(gpio_state: &GpioState) {
+    // Signal any software-based PWM threads to stop
+    gpio_state.cleanup_generation.fetch_add(1, Ordering::SeqCst);
+
+    let cleanup_state = gpio_state.cleanup_state();
+
+    for (pin, record) in cleanup_state.pins.iter().enumerate() {
+        let record = match record {
+            Some(record) => record,
+            None => continue,
+        };
+        let pin = pin as u8;
+
+        if cleanup_state.policy.clear_interrupts {
+            // The event loop is locked while another thread is blocked in
+            // poll_interrupts(), in which case we can't wait for it.
+            if let Ok(mut sync_interrupts) = gpio_state.sync_interrupts.try_lock() {
+                let _ = sync_interrupts.clear_interrupt(pin);
+            }
+
+            let _ = gpio_state.gpio_mem.set_edge_detect(pin, Trigger::Disabled);
+        }
+
+        if gpio_state.gpio_mem.mode(pin) == Mode::Output {
+            match cleanup_state.policy.drop_levels[pin as usize] {
+                Some(Level::Low) => gpio_state.gpio_mem.set_low(pin),
+                Some(Level::High) => gpio_state.gpio_mem.set_high(pin),
+                None => (),
+            }
+        }
+
+        gpio_state.gpio_mem.set_mode(pin, record.mode);
+
+        if record.pud_changed {
+            gpio_state.gpio_mem.set_pullupdown(pin, PullUpDown::Off);
+        }
+    }
+}
+
+/// Builder for [`Gpio`] that configures crate-wide cleanup defaults.
+///
+/// `GpioBuilder` is returned by [`Gpio::builder`]. The configured defaults
+/// determine how pins are restored when they go out of scope, and when
+/// [`Gpio::cleanup`] is called, which is useful for daemons that need to leave
+/// the hardware in a safe state when they shut down.
+///
+/// All `Gpio` instances share the same state, so the defaults apply to every
+/// `Gpio` instance within the current process. Calling [`build`] while a `Gpio`
+/// instance already exists replaces the current defaults.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::{Gpio, Level};
+///
+/// # fn main() -> rppal::gpio::Result<()> {
+/// // Always drive the motor enable pin low when it's released
+/// let gpio = Gpio::builder().drop_level(17, Level::Low).build()?;
+///
+/// let mut motor_enable = gpio.get(17).unwrap().into_output();
+/// motor_enable.set_high();
+///
+/// // ...
+///
+/// gpio.cleanup();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Gpio`]: struct.Gpio.html
+/// [`Gpio::builder`]: struct.Gpio.html#method.builder
+/// [`Gpio::cleanup`]: struct.Gpio.html#method.cleanup
+/// [`build`]: #method.build
+#[derive(Debug, Clone)]
+pub struct GpioBuilder {
+    backend: Backend,
+    policy: CleanupPolicy,
+    recorder: Option<Recorder>,
+    tracer: Option<VcdTracer>,
+}
+
+impl GpioBuilder {
+    pub(crate) fn new() -> GpioBuilder {
+        GpioBuilder {
+            backend: Backend::GpioMem,
+            policy: CleanupPolicy::default(),
+            recorder: None,
+            tracer: None,
+        }
+    }
+
+    /// Sets the [`Backend`] used to access the GPIO pins.
+    ///
+    /// By default, `backend` is set to `Backend::GpioMem`.
+    ///
+    /// [`Backend`]: enum.Backend.html
+    pub fn backend(mut self, backend: Backend) -> GpioBuilder {
+        self.backend = backend;
+        self
+    }
+
+    /// Sets the default value of `reset_on_drop` for pins that are converted
+    /// into an [`InputPin`], [`OutputPin`] or [`AltPin`] afterwards.
+    ///
+    /// Individual pins can override the default through `set_reset_on_drop`.
+    /// By default, `reset_on_drop` is set to `true`.
+    ///
+    /// [`InputPin`]: struct.InputPin.html
+    /// [`OutputPin`]: struct.OutputPin.html
+    /// [`AltPin`]: struct.AltPin.html
+    pub fn reset_on_drop(mut self, reset_on_drop: bool) -> GpioBuilder {
+        self.policy.reset_on_drop = reset_on_drop;
+        self
+    }
+
+    /// Sets the logic level an [`OutputPin`] is set to when it goes out of scope,
+    /// or when [`Gpio::cleanup`] is called.
+    ///
+    /// The level is applied before the pin's mode is reset. If `reset_on_drop`
+    /// is disabled, the pin keeps driving the level after it's released. Invalid
+    /// pin numbers are ignored.
+    ///
+    /// [`OutputPin`]: struct.OutputPin.html
+    /// [`Gpio::cleanup`]: struct.Gpio.html#method.cleanup
+    pub fn drop_level(mut self, pin: u8, level: Level) -> GpioBuilder {
+        if let Some(drop_level) = self.policy.drop_levels.get_mut(pin as usize) {
+            *drop_level = Some(level);
+        }
+
+        self
+    }
+
+    /// When enabled, [`Gpio::cleanup`] clears any synchronous interrupt triggers
+    /// and edge detection on all taken pins.
+    ///
+    /// By default, `clear_interrupts` is set to `true`.
+    ///
+    /// [`Gpio::cleanup`]: struct.Gpio.html#method.cleanup
+    pub fn clear_interrupts(mut self, clear_interrupts: bool) -> GpioBuilder {
+        self.policy.clear_interrupts = clear_interrupts;
+        self
+    }
+
+    /// Logs all GPIO activity to the specified [`Recorder`].
+    ///
+    /// Recording can only be enabled when the first `Gpio` instance is
+    /// constructed. If a `Gpio` instance already exists, [`build`] returns
+    /// [`Error::AlreadyInitialized`]. While recording is enabled, output pins
+    /// don't use the faster direct register access.
+    ///
+    /// [`Recorder`]: struct.Recorder.html
+    /// [`build`]: #method.build
+    /// [`Error::AlreadyInitialized`]: enum.Error.html#variant.AlreadyInitialized
+    pub fn recorder(mut self, recorder: Recorder) -> GpioBuilder {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Captures pin activity through the specified [`VcdTracer`].
+    ///
+    /// Tracing can only be enabled when the first `Gpio` instance is
+    /// constructed. If a `Gpio` instance already exists, [`build`] returns
+    /// [`Error::AlreadyInitialized`]. While tracing is enabled, output pins
+    /// don't use the faster direct register access.
+    ///
+    /// [`VcdTracer`]: trace/struct.VcdTracer.html
+    /// [`build`]: #method.build
+    /// [`Error::AlreadyInitialized`]: enum.Error.html#variant.AlreadyInitialized
+    pub fn tracer(mut self, tracer: VcdTracer) -> GpioBuilder {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    /// Constructs a new `Gpio` with the configured defaults.
+    ///
+    /// If a `Gpio` instance already exists with a different backend, `build`
+    /// returns [`Error::BackendInUse`]. If a [`Recorder`] or tracer is configured
+    /// and any `Gpio` instance already exists, `build` returns
+    /// [`Error::AlreadyInitialized`].
+    ///
+    /// [`Error::BackendInUse`]: enum.Error.html#variant.BackendInUse
+    /// [`Recorder`]: struct.Recorder.html
+    /// [`Error::AlreadyInitialized`]: enum.Error.html#variant.AlreadyInitialized
+    pub fn build(self) -> Result<Gpio> {
+        let gpio = if self.recorder.is_some() || self.tracer.is_some() {
+            Gpio::with_instruments(self.backend, self.recorder, self.tracer)?
+        } else {
+            Gpio::with_backend(self.backend)?
+        };
+
+        gpio.inner.cleanup_state().policy = self.policy;
+
+        Ok(gpio)
+    }
+}
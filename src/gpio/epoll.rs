@@ -27,7 +27,7 @@ use std::time::Duration;
 use libc;
 use libc::c_int;
 
-pub use libc::{epoll_event, EPOLLERR, EPOLLET, EPOLLIN, EPOLLONESHOT, EPOLLOUT, EPOLLPRI};
+pub use libc::{epoll_event, EPOLLERR, EPOLLET, EPOLLIN, EPOLLPRI};
 
 pub type Result<T> = result::Result<T, io::Error>;
 
@@ -85,7 +85,7 @@ impl Epoll {
     pub fn add(&self, fd: i32, id: u64, event_mask: i32) -> Result<()> {
         let mut event = libc::epoll_event {
             events: event_mask as u32,
-            u64: id as u64,
+            u64: id,
         };
 
         parse_retval!(unsafe { libc::epoll_ctl(self.fd, libc::EPOLL_CTL_ADD, fd, &mut event) })?;
@@ -96,7 +96,7 @@ impl Epoll {
     pub fn modify(&self, fd: i32, id: u64, event_mask: i32) -> Result<()> {
         let mut event = libc::epoll_event {
             events: event_mask as u32,
-            u64: id as u64,
+            u64: id,
         };
 
         parse_retval!(unsafe { libc::epoll_ctl(self.fd, libc::EPOLL_CTL_MOD, fd, &mut event) })?;
@@ -112,6 +112,10 @@ impl Epoll {
         Ok(())
     }
 
+    pub fn fd(&self) -> i32 {
+        self.fd
+    }
+
     pub fn wait(
         &self,
         events: &mut [libc::epoll_event],
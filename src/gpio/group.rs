@@ -0,0 +1,121 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::gpio::{Level, OutputPin};
+
+/// Group of output pins that are updated simultaneously.
+///
+/// `OutputPinGroup` batches level changes for all of its pins into a single
+/// write to the GPSET and GPCLR registers (per register bank), which avoids
+/// the skew between edges you'd get when updating each pin individually. This
+/// is useful for driving parallel buses, like the 8-bit data lines of an LCD.
+///
+/// Pins are set before they're cleared, so there is a short delay (typically
+/// tens of nanoseconds) between rising and falling edges. GPIO pins 0-31
+/// and 32-53 are located in separate register banks, so combining pins
+/// from both banks in a single group adds another short delay.
+#[derive(Debug)]
+pub struct OutputPinGroup {
+    pins: Vec<OutputPin>,
+    masks: Vec<u64>,
+}
+
+impl OutputPinGroup {
+    /// Constructs a new `OutputPinGroup`.
+    ///
+    /// The order of `pins` determines which bit of the value passed to [`write`]
+    /// controls each pin, with bit 0 mapped to the first pin.
+    ///
+    /// [`write`]: #method.write
+    pub fn new(pins: Vec<OutputPin>) -> OutputPinGroup {
+        let masks = pins.iter().map(|pin| 1u64 << pin.pin()).collect();
+
+        OutputPinGroup { pins, masks }
+    }
+
+    /// Returns the pins in the group.
+    pub fn pins(&self) -> &[OutputPin] {
+        &self.pins
+    }
+
+    /// Consumes the `OutputPinGroup` and returns its pins.
+    pub fn into_pins(self) -> Vec<OutputPin> {
+        self.pins
+    }
+
+    /// Sets the logic level of all pins based on the bits in `value`.
+    ///
+    /// Bit 0 of `value` is mapped to the first pin in the group, bit 1 to the
+    /// second pin, etc. A value of 1 sets the pin's logic level to high, 0 sets
+    /// it to low. Any bits without a corresponding pin are ignored.
+    pub fn write(&mut self, value: u64) {
+        let mut set_mask = 0;
+        let mut clear_mask = 0;
+
+        for (idx, mask) in self.masks.iter().enumerate() {
+            if value & (1 << idx) != 0 {
+                set_mask |= mask;
+            } else {
+                clear_mask |= mask;
+            }
+        }
+
+        self.write_masks(set_mask, clear_mask);
+    }
+
+    /// Sets the logic level of all pins based on `levels`.
+    ///
+    /// Each entry in `levels` is mapped to the pin at the same position in the
+    /// group. Pins without a corresponding entry retain their current level.
+    pub fn write_levels(&mut self, levels: &[Level]) {
+        let mut set_mask = 0;
+        let mut clear_mask = 0;
+
+        for (mask, level) in self.masks.iter().zip(levels) {
+            match level {
+                Level::High => set_mask |= mask,
+                Level::Low => clear_mask |= mask,
+            }
+        }
+
+        self.write_masks(set_mask, clear_mask);
+    }
+
+    /// Sets the logic level of all pins to high.
+    pub fn set_high(&mut self) {
+        let mask = self.masks.iter().fold(0, |acc, mask| acc | mask);
+        self.write_masks(mask, 0);
+    }
+
+    /// Sets the logic level of all pins to low.
+    pub fn set_low(&mut self) {
+        let mask = self.masks.iter().fold(0, |acc, mask| acc | mask);
+        self.write_masks(0, mask);
+    }
+
+    fn write_masks(&mut self, set_mask: u64, clear_mask: u64) {
+        if let Some(pin) = self.pins.first() {
+            pin.pin
+                .gpio_state
+                .gpio_mem
+                .write_levels(set_mask, clear_mask);
+        }
+    }
+}
@@ -0,0 +1,333 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::convert::Infallible;
+
+#[cfg(feature = "hal")]
+use embedded_hal::digital::v2;
+#[cfg(feature = "hal-1")]
+use embedded_hal_1::digital;
+
+use crate::gpio::{AltPin, InputPin, IoPin, OutputPin};
+
+#[cfg(feature = "hal")]
+impl v2::InputPin for InputPin {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(InputPin::is_high(self))
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(InputPin::is_low(self))
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::OutputPin for OutputPin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        OutputPin::set_low(self);
+
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        OutputPin::set_high(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::StatefulOutputPin for OutputPin {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(OutputPin::is_high(self))
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(OutputPin::is_low(self))
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::ToggleableOutputPin for OutputPin {
+    type Error = Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        OutputPin::toggle(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::InputPin for AltPin {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(AltPin::is_high(self))
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(AltPin::is_low(self))
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::OutputPin for AltPin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        AltPin::set_low(self);
+
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        AltPin::set_high(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::StatefulOutputPin for AltPin {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(AltPin::is_high(self))
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(AltPin::is_low(self))
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::ToggleableOutputPin for AltPin {
+    type Error = Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        AltPin::toggle(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::InputPin for IoPin {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(IoPin::is_high(self))
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(IoPin::is_low(self))
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::OutputPin for IoPin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        IoPin::set_low(self);
+
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        IoPin::set_high(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::StatefulOutputPin for IoPin {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(IoPin::is_high(self))
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(IoPin::is_low(self))
+    }
+}
+
+#[cfg(feature = "hal")]
+impl v2::ToggleableOutputPin for IoPin {
+    type Error = Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        IoPin::toggle(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::ErrorType for InputPin {
+    type Error = Infallible;
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::InputPin for InputPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(InputPin::is_high(self))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(InputPin::is_low(self))
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::ErrorType for OutputPin {
+    type Error = Infallible;
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::OutputPin for OutputPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        OutputPin::set_low(self);
+
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        OutputPin::set_high(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::StatefulOutputPin for OutputPin {
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(OutputPin::is_high(self))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(OutputPin::is_low(self))
+    }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        OutputPin::toggle(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::ErrorType for AltPin {
+    type Error = Infallible;
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::InputPin for AltPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(AltPin::is_high(self))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(AltPin::is_low(self))
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::OutputPin for AltPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        AltPin::set_low(self);
+
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        AltPin::set_high(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::StatefulOutputPin for AltPin {
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(AltPin::is_high(self))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(AltPin::is_low(self))
+    }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        AltPin::toggle(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::ErrorType for IoPin {
+    type Error = Infallible;
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::InputPin for IoPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(IoPin::is_high(self))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(IoPin::is_low(self))
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::OutputPin for IoPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        IoPin::set_low(self);
+
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        IoPin::set_high(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl digital::StatefulOutputPin for IoPin {
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(IoPin::is_high(self))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(IoPin::is_low(self))
+    }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        IoPin::toggle(self);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! GPIO header pinout.
+//!
+//! [`Header`] describes the physical layout of the GPIO header for the current,
+//! or any specified, Raspberry Pi model. It lists which BCM GPIO pins are exposed,
+//! which physical pins are connected to 3.3 V, 5 V or ground, and the functions
+//! commonly associated with each GPIO pin. This can be used to render pinout
+//! diagrams, or to validate user-supplied pin configurations.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::gpio::header::Header;
+//!
+//! # fn main() -> rppal::gpio::Result<()> {
+//! let header = Header::new()?;
+//!
+//! for header_pin in header.pins() {
+//!     match header_pin.default_function() {
+//!         Some(function) => println!(
+//!             "{:>2}: {} ({})",
+//!             header_pin.number(),
+//!             header_pin.physical_pin(),
+//!             function
+//!         ),
+//!         None => println!("{:>2}: {}", header_pin.number(), header_pin.physical_pin()),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Header`]: struct.Header.html
+
+use std::fmt;
+
+use crate::gpio::{Error, Result};
+use crate::system::{DeviceInfo, Model};
+
+/// Physical GPIO header pin types.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum PhysicalPin {
+    /// GPIO pin, addressed by its BCM number.
+    Gpio(u8),
+    /// 3.3 V power pin.
+    Power3v3,
+    /// 5 V power pin.
+    Power5v,
+    /// Ground pin.
+    Ground,
+}
+
+impl fmt::Display for PhysicalPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PhysicalPin::Gpio(pin) => write!(f, "GPIO{}", pin),
+            PhysicalPin::Power3v3 => write!(f, "3.3 V"),
+            PhysicalPin::Power5v => write!(f, "5 V"),
+            PhysicalPin::Ground => write!(f, "GND"),
+        }
+    }
+}
+
+// 40-pin header (A+, B+, 2B, 3B, 3B+, 3A+, 4B, 400, 5, Zero, Zero W, Zero 2 W)
+const HEADER_40: [PhysicalPin; 40] = [
+    PhysicalPin::Power3v3, // Physical pin 1
+    PhysicalPin::Power5v,  // Physical pin 2
+    PhysicalPin::Gpio(2),  // Physical pin 3
+    PhysicalPin::Power5v,  // Physical pin 4
+    PhysicalPin::Gpio(3),  // Physical pin 5
+    PhysicalPin::Ground,   // Physical pin 6
+    PhysicalPin::Gpio(4),  // Physical pin 7
+    PhysicalPin::Gpio(14), // Physical pin 8
+    PhysicalPin::Ground,   // Physical pin 9
+    PhysicalPin::Gpio(15), // Physical pin 10
+    PhysicalPin::Gpio(17), // Physical pin 11
+    PhysicalPin::Gpio(18), // Physical pin 12
+    PhysicalPin::Gpio(27), // Physical pin 13
+    PhysicalPin::Ground,   // Physical pin 14
+    PhysicalPin::Gpio(22), // Physical pin 15
+    PhysicalPin::Gpio(23), // Physical pin 16
+    PhysicalPin::Power3v3, // Physical pin 17
+    PhysicalPin::Gpio(24), // Physical pin 18
+    PhysicalPin::Gpio(10), // Physical pin 19
+    PhysicalPin::Ground,   // Physical pin 20
+    PhysicalPin::Gpio(9),  // Physical pin 21
+    PhysicalPin::Gpio(25), // Physical pin 22
+    PhysicalPin::Gpio(11), // Physical pin 23
+    PhysicalPin::Gpio(8),  // Physical pin 24
+    PhysicalPin::Ground,   // Physical pin 25
+    PhysicalPin::Gpio(7),  // Physical pin 26
+    PhysicalPin::Gpio(0),  // Physical pin 27
+    PhysicalPin::Gpio(1),  // Physical pin 28
+    PhysicalPin::Gpio(5),  // Physical pin 29
+    PhysicalPin::Ground,   // Physical pin 30
+    PhysicalPin::Gpio(6),  // Physical pin 31
+    PhysicalPin::Gpio(12), // Physical pin 32
+    PhysicalPin::Gpio(13), // Physical pin 33
+    PhysicalPin::Ground,   // Physical pin 34
+    PhysicalPin::Gpio(19), // Physical pin 35
+    PhysicalPin::Gpio(16), // Physical pin 36
+    PhysicalPin::Gpio(26), // Physical pin 37
+    PhysicalPin::Gpio(20), // Physical pin 38
+    PhysicalPin::Ground,   // Physical pin 39
+    PhysicalPin::Gpio(21), // Physical pin 40
+];
+
+// 26-pin header on the B Rev 1, which routes different GPIO pins to
+// physical pins 3, 5 and 13. Pins 4, 9, 14, 17, 20 and 25 were originally
+// marked as DNC (do not connect), but are wired to power/ground.
+const HEADER_26_REV1: [PhysicalPin; 26] = [
+    PhysicalPin::Power3v3, // Physical pin 1
+    PhysicalPin::Power5v,  // Physical pin 2
+    PhysicalPin::Gpio(0),  // Physical pin 3
+    PhysicalPin::Power5v,  // Physical pin 4
+    PhysicalPin::Gpio(1),  // Physical pin 5
+    PhysicalPin::Ground,   // Physical pin 6
+    PhysicalPin::Gpio(4),  // Physical pin 7
+    PhysicalPin::Gpio(14), // Physical pin 8
+    PhysicalPin::Ground,   // Physical pin 9
+    PhysicalPin::Gpio(15), // Physical pin 10
+    PhysicalPin::Gpio(17), // Physical pin 11
+    PhysicalPin::Gpio(18), // Physical pin 12
+    PhysicalPin::Gpio(21), // Physical pin 13
+    PhysicalPin::Ground,   // Physical pin 14
+    PhysicalPin::Gpio(22), // Physical pin 15
+    PhysicalPin::Gpio(23), // Physical pin 16
+    PhysicalPin::Power3v3, // Physical pin 17
+    PhysicalPin::Gpio(24), // Physical pin 18
+    PhysicalPin::Gpio(10), // Physical pin 19
+    PhysicalPin::Ground,   // Physical pin 20
+    PhysicalPin::Gpio(9),  // Physical pin 21
+    PhysicalPin::Gpio(25), // Physical pin 22
+    PhysicalPin::Gpio(11), // Physical pin 23
+    PhysicalPin::Gpio(8),  // Physical pin 24
+    PhysicalPin::Ground,   // Physical pin 25
+    PhysicalPin::Gpio(7),  // Physical pin 26
+];
+
+// Returns the header layout for the specified model. Compute Modules don't
+// have a GPIO header.
+pub(crate) fn layout(model: Model) -> &'static [PhysicalPin] {
+    match model {
+        Model::RaspberryPiBRev1 => &HEADER_26_REV1,
+        // The 26-pin header on the A and B Rev 2 matches the first 26 pins
+        // of the 40-pin header.
+        Model::RaspberryPiA | Model::RaspberryPiBRev2 => &HEADER_40[..26],
+        Model::RaspberryPiAPlus
+        | Model::RaspberryPiBPlus
+        | Model::RaspberryPi2B
+        | Model::RaspberryPi3B
+        | Model::RaspberryPi3BPlus
+        | Model::RaspberryPi3APlus
+        | Model::RaspberryPi4B
+        | Model::RaspberryPi400
+        | Model::RaspberryPi5
+        | Model::RaspberryPiZero
+        | Model::RaspberryPiZeroW
+        | Model::RaspberryPiZero2W => &HEADER_40,
+        Model::RaspberryPiComputeModule
+        | Model::RaspberryPiComputeModule3
+        | Model::RaspberryPiComputeModule3Plus
+        | Model::RaspberryPiComputeModule4 => &[],
+    }
+}
+
+// Returns the pin type for the specified physical pin number (1-based)
+pub(crate) fn physical_pin(model: Model, header_pin: u8) -> Option<PhysicalPin> {
+    if header_pin == 0 {
+        return None;
+    }
+
+    layout(model).get(header_pin as usize - 1).copied()
+}
+
+// Returns the function commonly associated with the specified BCM GPIO pin on
+// the GPIO header, based on the official pinout.
+fn default_function(pin: u8) -> Option<&'static str> {
+    Some(match pin {
+        0 => "SDA0",
+        1 => "SCL0",
+        2 => "SDA1",
+        3 => "SCL1",
+        4 => "GPCLK0",
+        5 => "GPCLK1",
+        6 => "GPCLK2",
+        7 => "SPI0 CE1",
+        8 => "SPI0 CE0",
+        9 => "SPI0 MISO",
+        10 => "SPI0 MOSI",
+        11 => "SPI0 SCLK",
+        12 => "PWM0",
+        13 => "PWM1",
+        14 => "UART TXD",
+        15 => "UART RXD",
+        16 => "SPI1 CE2",
+        17 => "SPI1 CE1",
+        18 => "PCM CLK",
+        19 => "PCM FS",
+        20 => "PCM DIN",
+        21 => "PCM DOUT",
+        _ => return None,
+    })
+}
+
+/// Physical pin on the GPIO header.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct HeaderPin {
+    number: u8,
+    physical_pin: PhysicalPin,
+}
+
+impl HeaderPin {
+    /// Returns the physical pin number.
+    ///
+    /// Physical pin numbers start at 1 (3.3 V) in the corner closest to the SD card
+    /// slot, with odd numbers on the inner row and even numbers on the outer row.
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    /// Returns the pin type.
+    pub fn physical_pin(&self) -> PhysicalPin {
+        self.physical_pin
+    }
+
+    /// Returns the BCM GPIO pin number, or `None` for power and ground pins.
+    pub fn gpio(&self) -> Option<u8> {
+        match self.physical_pin {
+            PhysicalPin::Gpio(pin) => Some(pin),
+            _ => None,
+        }
+    }
+
+    /// Returns the function commonly associated with the GPIO pin, as shown on
+    /// the official pinout.
+    ///
+    /// The default function is only a label. Pins are configured as inputs after
+    /// a reset, and the peripheral needs to be enabled before the pin is used for
+    /// its default function. Returns `None` for power and ground pins, and for
+    /// general-purpose pins without a commonly associated function.
+    pub fn default_function(&self) -> Option<&'static str> {
+        self.gpio().and_then(default_function)
+    }
+}
+
+/// GPIO header layout.
+///
+/// Compute Modules don't have a GPIO header, and return an empty layout.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Header {
+    model: Model,
+    layout: &'static [PhysicalPin],
+}
+
+impl Header {
+    /// Constructs a new `Header` for the current Raspberry Pi model.
+    ///
+    /// Returns [`Error::UnknownModel`] if the model can't be identified.
+    ///
+    /// [`Error::UnknownModel`]: ../enum.Error.html#variant.UnknownModel
+    pub fn new() -> Result<Header> {
+        let model = DeviceInfo::new().map_err(|_| Error::UnknownModel)?.model();
+
+        Ok(Header::with_model(model))
+    }
+
+    /// Constructs a new `Header` for the specified Raspberry Pi model.
+    pub fn with_model(model: Model) -> Header {
+        Header {
+            model,
+            layout: layout(model),
+        }
+    }
+
+    /// Returns the Raspberry Pi model.
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// Returns the number of physical pins on the header.
+    pub fn len(&self) -> usize {
+        self.layout.len()
+    }
+
+    /// Returns `true` if the model doesn't have a GPIO header.
+    pub fn is_empty(&self) -> bool {
+        self.layout.is_empty()
+    }
+
+    /// Returns the specified physical pin, or `None` if the pin doesn't exist.
+    pub fn pin(&self, number: u8) -> Option<HeaderPin> {
+        physical_pin(self.model, number).map(|physical_pin| HeaderPin {
+            number,
+            physical_pin,
+        })
+    }
+
+    /// Returns all physical pins on the header, ordered by their pin number.
+    pub fn pins(&self) -> Vec<HeaderPin> {
+        self.layout
+            .iter()
+            .enumerate()
+            .map(|(idx, &physical_pin)| HeaderPin {
+                number: idx as u8 + 1,
+                physical_pin,
+            })
+            .collect()
+    }
+
+    /// Returns the BCM GPIO pin numbers exposed on the header, in ascending order.
+    pub fn gpio_pins(&self) -> Vec<u8> {
+        let mut pins: Vec<u8> = self
+            .layout
+            .iter()
+            .filter_map(|physical_pin| match physical_pin {
+                PhysicalPin::Gpio(pin) => Some(*pin),
+                _ => None,
+            })
+            .collect();
+
+        pins.sort_unstable();
+        pins
+    }
+
+    /// Returns the physical pin number for the specified BCM GPIO pin, or `None`
+    /// if the GPIO pin isn't exposed on the header.
+    pub fn physical_number(&self, gpio: u8) -> Option<u8> {
+        self.layout
+            .iter()
+            .position(|&physical_pin| physical_pin == PhysicalPin::Gpio(gpio))
+            .map(|idx| idx as u8 + 1)
+    }
+}
@@ -20,30 +20,43 @@
 
 #![allow(dead_code)]
 
+use std::collections::VecDeque;
 use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use crate::gpio::epoll::{epoll_event, Epoll, EventFd, EPOLLERR, EPOLLET, EPOLLIN, EPOLLPRI};
 use crate::gpio::ioctl;
 use crate::gpio::pin::InputPin;
-use crate::gpio::{Error, Level, Result, Trigger};
+use crate::gpio::sched::{self, ThreadConfig};
+use crate::gpio::{Error, Event, Level, Operation, Result, Trigger};
 
 #[derive(Debug)]
-struct Interrupt {
+pub(crate) struct Interrupt {
     pin: u8,
     trigger: Trigger,
+    debounce: Option<Duration>,
+    last_event: Option<Duration>,
     cdev_fd: i32,
     event_fd: i32,
 }
 
 impl Interrupt {
-    fn new(fd: i32, pin: u8, trigger: Trigger) -> Result<Interrupt> {
-        let event_request = ioctl::EventRequest::new(fd, pin, trigger)?;
+    pub(crate) fn new(
+        fd: i32,
+        pin: u8,
+        trigger: Trigger,
+        debounce: Option<Duration>,
+    ) -> Result<Interrupt> {
+        let event_request = request_events(fd, pin, trigger)?;
 
         Ok(Interrupt {
             pin,
             trigger,
+            debounce,
+            last_event: None,
             cdev_fd: fd,
             event_fd: event_request.fd,
         })
@@ -53,11 +66,11 @@ impl Interrupt {
         self.trigger
     }
 
-    fn fd(&self) -> i32 {
+    pub(crate) fn fd(&self) -> i32 {
         self.event_fd
     }
 
-    fn pin(&self) -> u8 {
+    pub(crate) fn pin(&self) -> u8 {
         self.pin
     }
 
@@ -67,9 +80,33 @@ impl Interrupt {
         self.reset()
     }
 
-    // This might block if there are no events waiting
-    fn event(&mut self) -> Result<ioctl::Event> {
-        ioctl::get_event(self.event_fd)
+    fn set_debounce(&mut self, debounce: Option<Duration>) {
+        self.debounce = debounce;
+        self.last_event = None;
+    }
+
+    // This might block if there are no events waiting. Returns None if the
+    // event was suppressed by the debounce filter.
+    pub(crate) fn event(&mut self) -> Result<Option<Event>> {
+        let event = ioctl::get_event(self.event_fd)
+            .map_err(|e| e.with_pin(self.pin, Operation::ReadEvent))?;
+
+        if let Some(debounce) = self.debounce {
+            // Compare kernel timestamps, so any delays caused by scheduling don't
+            // affect the filter. If the timestamp moved backwards (the event clock
+            // might be CLOCK_REALTIME), accept the event.
+            if let Some(last_event) = self.last_event {
+                if let Some(elapsed) = event.timestamp.checked_sub(last_event) {
+                    if elapsed < debounce {
+                        return Ok(None);
+                    }
+                }
+            }
+
+            self.last_event = Some(event.timestamp);
+        }
+
+        Ok(Some(Event::new(self.pin, event.trigger, event.timestamp)))
     }
 
     fn reset(&mut self) -> Result<()> {
@@ -78,7 +115,9 @@ impl Interrupt {
             self.event_fd = -1;
         }
 
-        let event_request = ioctl::EventRequest::new(self.cdev_fd, self.pin, self.trigger)?;
+        self.last_event = None;
+
+        let event_request = request_events(self.cdev_fd, self.pin, self.trigger)?;
         self.event_fd = event_request.fd;
 
         Ok(())
@@ -86,6 +125,40 @@ impl Interrupt {
 
     fn level(&mut self) -> Result<Level> {
         ioctl::get_level(self.cdev_fd, self.pin)
+            .map_err(|e| e.with_pin(self.pin, Operation::ReadLevel))
+    }
+
+    // Makes event() return an io::ErrorKind::WouldBlock error instead of blocking
+    // when no events are pending
+    #[cfg(feature = "async")]
+    pub(crate) fn set_nonblocking(&self) -> Result<()> {
+        let flags = parse_retval!(unsafe { libc::fcntl(self.event_fd, libc::F_GETFL) })?;
+        parse_retval!(unsafe {
+            libc::fcntl(self.event_fd, libc::F_SETFL, flags | libc::O_NONBLOCK)
+        })?;
+
+        Ok(())
+    }
+}
+
+// Requests the pin's line for interrupt events. If the line is already claimed by
+// a kernel driver or another process, the error includes the current consumer.
+fn request_events(cdev_fd: i32, pin: u8, trigger: Trigger) -> Result<ioctl::EventRequest> {
+    ioctl::EventRequest::new(cdev_fd, pin, trigger).map_err(|e| match e {
+        Error::Io(ref err) if err.raw_os_error() == Some(libc::EBUSY) => {
+            match ioctl::LineInfo::with_pin(cdev_fd, pin) {
+                Ok(line_info) => Error::PinUsedBy(pin, line_info.consumer_label()),
+                Err(_) => e.with_pin(pin, Operation::ConfigureInterrupt),
+            }
+        }
+        e => e.with_pin(pin, Operation::ConfigureInterrupt),
+    })
+}
+
+#[cfg(feature = "async")]
+impl std::os::unix::io::AsRawFd for Interrupt {
+    fn as_raw_fd(&self) -> i32 {
+        self.event_fd
     }
 }
 
@@ -97,11 +170,35 @@ impl Drop for Interrupt {
     }
 }
 
+// Default number of events cached per pin, which matches the size of the
+// kernel's event buffer for each line.
+const DEFAULT_QUEUE_CAPACITY: usize = 16;
+
 #[derive(Debug)]
 struct TriggerStatus {
     interrupt: Option<Interrupt>,
-    triggered: bool,
-    level: Level,
+    events: VecDeque<Event>,
+    capacity: usize,
+    // Events dropped because the queue was full, reported on the next queued event
+    missed_events: u32,
+}
+
+impl TriggerStatus {
+    fn push(&mut self, mut event: Event) {
+        if self.events.len() >= self.capacity {
+            self.missed_events = self.missed_events.saturating_add(1);
+            return;
+        }
+
+        event.missed_events = self.missed_events;
+        self.missed_events = 0;
+        self.events.push_back(event);
+    }
+
+    fn clear(&mut self) {
+        self.events.clear();
+        self.missed_events = 0;
+    }
 }
 
 pub struct EventLoop {
@@ -130,8 +227,9 @@ impl EventLoop {
         for _ in 0..trigger_status.capacity() {
             trigger_status.push(TriggerStatus {
                 interrupt: None,
-                triggered: false,
-                level: Level::Low,
+                events: VecDeque::new(),
+                capacity: DEFAULT_QUEUE_CAPACITY,
+                missed_events: 0,
             });
         }
 
@@ -148,17 +246,15 @@ impl EventLoop {
         pins: &[&'a InputPin],
         reset: bool,
         timeout: Option<Duration>,
-    ) -> Result<Option<(&'a InputPin, Level)>> {
+    ) -> Result<Option<(&'a InputPin, Event)>> {
         for pin in pins {
             let trigger_status = &mut self.trigger_status[pin.pin() as usize];
 
             // Did we cache any trigger events during the previous poll?
-            if trigger_status.triggered {
-                trigger_status.triggered = false;
-
-                if !reset {
-                    return Ok(Some((pin, trigger_status.level)));
-                }
+            if reset {
+                trigger_status.clear();
+            } else if let Some(event) = trigger_status.events.pop_front() {
+                return Ok(Some((pin, event)));
             }
 
             // Reset any pending trigger events
@@ -192,18 +288,17 @@ impl EventLoop {
 
                 debug_assert!(
                     trigger_status.interrupt.is_some(),
-                    format!("No interrupt set for pin {}", pin)
+                    "No interrupt set for pin {}", pin
                 );
 
-                if let Some(ref mut interrupt) = trigger_status.interrupt {
-                    trigger_status.level = match interrupt.event()?.trigger {
-                        Trigger::RisingEdge => Level::High,
-                        Trigger::FallingEdge => Level::Low,
-                        _ => unsafe { std::hint::unreachable_unchecked() },
-                    };
-
-                    trigger_status.triggered = true;
+                let event = match trigger_status.interrupt {
+                    Some(ref mut interrupt) => interrupt.event()?,
+                    None => None,
                 };
+
+                if let Some(event) = event {
+                    trigger_status.push(event);
+                }
             }
 
             // Were any interrupts triggered? If so, return one. The rest
@@ -211,9 +306,8 @@ impl EventLoop {
             for pin in pins {
                 let trigger_status = &mut self.trigger_status[pin.pin() as usize];
 
-                if trigger_status.triggered {
-                    trigger_status.triggered = false;
-                    return Ok(Some((pin, trigger_status.level)));
+                if let Some(event) = trigger_status.events.pop_front() {
+                    return Ok(Some((pin, event)));
                 }
             }
 
@@ -229,13 +323,20 @@ impl EventLoop {
         }
     }
 
-    pub fn set_interrupt(&mut self, pin: u8, trigger: Trigger) -> Result<()> {
+    pub fn set_interrupt(
+        &mut self,
+        pin: u8,
+        trigger: Trigger,
+        debounce: Option<Duration>,
+    ) -> Result<()> {
         let trigger_status = &mut self.trigger_status[pin as usize];
 
-        trigger_status.triggered = false;
+        trigger_status.clear();
 
-        // Interrupt already exists. We just need to change the trigger.
+        // Interrupt already exists. We just need to change the trigger and debounce filter.
         if let Some(ref mut interrupt) = trigger_status.interrupt {
+            interrupt.set_debounce(debounce);
+
             if interrupt.trigger != trigger {
                 // This requires a new event request, so the fd might change
                 self.poll.delete(interrupt.fd())?;
@@ -248,7 +349,7 @@ impl EventLoop {
         }
 
         // Register a new interrupt
-        let interrupt = Interrupt::new(self.cdev_fd, pin, trigger)?;
+        let interrupt = Interrupt::new(self.cdev_fd, pin, trigger, debounce)?;
         self.poll
             .add(interrupt.fd(), u64::from(pin), EPOLLIN | EPOLLPRI)?;
         trigger_status.interrupt = Some(interrupt);
@@ -256,10 +357,29 @@ impl EventLoop {
         Ok(())
     }
 
+    // Returns the event fd for the pin's interrupt, if one is configured
+    pub fn interrupt_fd(&self, pin: u8) -> Option<i32> {
+        self.trigger_status[pin as usize]
+            .interrupt
+            .as_ref()
+            .map(|interrupt| interrupt.fd())
+    }
+
+    pub fn set_queue_capacity(&mut self, pin: u8, capacity: usize) {
+        let trigger_status = &mut self.trigger_status[pin as usize];
+
+        trigger_status.capacity = capacity.max(1);
+        trigger_status.events.truncate(trigger_status.capacity);
+    }
+
+    pub fn queue_capacity(&self, pin: u8) -> usize {
+        self.trigger_status[pin as usize].capacity
+    }
+
     pub fn clear_interrupt(&mut self, pin: u8) -> Result<()> {
         let trigger_status = &mut self.trigger_status[pin as usize];
 
-        trigger_status.triggered = false;
+        trigger_status.clear();
 
         if let Some(interrupt) = trigger_status.interrupt.take() {
             self.poll.delete(interrupt.fd())?;
@@ -269,6 +389,46 @@ impl EventLoop {
     }
 }
 
+// Maximum number of errors kept by ErrorQueue. Older errors are discarded first.
+const ERROR_QUEUE_CAPACITY: usize = 16;
+
+// Collects errors that occur on asynchronous interrupt threads, so they can be
+// retrieved through Gpio::take_error.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ErrorQueue {
+    errors: Arc<Mutex<VecDeque<Error>>>,
+}
+
+impl ErrorQueue {
+    pub(crate) fn push(&self, error: Error) {
+        let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+
+        if errors.len() >= ERROR_QUEUE_CAPACITY {
+            errors.pop_front();
+        }
+
+        errors.push_back(error);
+    }
+
+    pub(crate) fn pop(&self) -> Option<Error> {
+        self.errors
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front()
+    }
+}
+
+// Extracts the message from a panic payload, which is usually a &str or String
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("unknown panic payload")
+    }
+}
+
 #[derive(Debug)]
 pub struct AsyncInterrupt {
     poll_thread: Option<thread::JoinHandle<Result<()>>>,
@@ -276,20 +436,28 @@ pub struct AsyncInterrupt {
 }
 
 impl AsyncInterrupt {
-    pub fn new<C>(fd: i32, pin: u8, trigger: Trigger, mut callback: C) -> Result<AsyncInterrupt>
+    pub fn new<C>(
+        fd: i32,
+        pin: u8,
+        trigger: Trigger,
+        debounce: Option<Duration>,
+        thread_config: ThreadConfig,
+        errors: ErrorQueue,
+        mut callback: C,
+    ) -> Result<AsyncInterrupt>
     where
-        C: FnMut(Level) + Send + 'static,
+        C: FnMut(Event) + Send + 'static,
     {
         let tx = EventFd::new()?;
         let rx = tx.fd();
 
-        let poll_thread = thread::spawn(move || -> Result<()> {
+        let poll_thread = sched::spawn(thread_config, false, move || -> Result<()> {
             let poll = Epoll::new()?;
 
             // rx becomes readable when the main thread calls notify()
             poll.add(rx, rx as u64, EPOLLERR | EPOLLET | EPOLLIN)?;
 
-            let mut interrupt = Interrupt::new(fd, pin, trigger)?;
+            let mut interrupt = Interrupt::new(fd, pin, trigger, debounce)?;
             poll.add(interrupt.fd(), interrupt.fd() as u64, EPOLLIN | EPOLLPRI)?;
 
             let mut events = [epoll_event { events: 0, u64: 0 }; 2];
@@ -301,17 +469,28 @@ impl AsyncInterrupt {
                         if fd == rx {
                             return Ok(()); // The main thread asked us to stop
                         } else if fd == interrupt.fd() {
-                            let level = match interrupt.event()?.trigger {
-                                Trigger::RisingEdge => Level::High,
-                                _ => Level::Low,
+                            let event = match interrupt.event() {
+                                Ok(Some(event)) => event,
+                                Ok(None) => continue,
+                                Err(e) => {
+                                    // Without a working event fd, the pin can't receive
+                                    // any further events, so report the error and stop.
+                                    errors.push(e);
+                                    return Ok(());
+                                }
                             };
 
-                            callback(level);
+                            // Keep the event loop alive if the callback panics
+                            if let Err(payload) =
+                                panic::catch_unwind(AssertUnwindSafe(|| callback(event)))
+                            {
+                                errors.push(Error::CallbackPanic(pin, panic_message(&*payload)));
+                            }
                         }
                     }
                 }
             }
-        });
+        })?;
 
         Ok(AsyncInterrupt {
             poll_thread: Some(poll_thread),
@@ -332,3 +511,16 @@ impl AsyncInterrupt {
         Ok(())
     }
 }
+
+impl Drop for AsyncInterrupt {
+    fn drop(&mut self) {
+        // Don't wait for the poll thread to exit if the main thread is panicking,
+        // because we could potentially block indefinitely while unwinding if the
+        // callback never returns.
+        if thread::panicking() {
+            let _ = self.tx.notify();
+        } else {
+            let _ = self.stop();
+        }
+    }
+}
@@ -40,9 +40,9 @@ const SIZEBITS: u8 = 14;
 const DIRBITS: u8 = 2;
 
 const NRSHIFT: u8 = 0;
-const TYPESHIFT: u8 = (NRSHIFT + NRBITS);
-const SIZESHIFT: u8 = (TYPESHIFT + TYPEBITS);
-const DIRSHIFT: u8 = (SIZESHIFT + SIZEBITS);
+const TYPESHIFT: u8 = NRSHIFT + NRBITS;
+const SIZESHIFT: u8 = TYPESHIFT + TYPEBITS;
+const DIRSHIFT: u8 = SIZESHIFT + SIZEBITS;
 
 const NR_GET_CHIP_INFO: IoctlLong = 0x01 << NRSHIFT;
 const NR_GET_LINE_INFO: IoctlLong = 0x02 << NRSHIFT;
@@ -124,14 +124,44 @@ impl LineInfo {
             consumer: [0u8; LABEL_BUFSIZE],
         }
     }
+
+    pub fn with_pin(cdev_fd: c_int, pin: u8) -> Result<LineInfo> {
+        let mut line_info = LineInfo::new();
+        line_info.line_offset = u32::from(pin);
+
+        parse_retval!(unsafe { ioctl(cdev_fd, REQ_GET_LINE_INFO, &mut line_info) })?;
+
+        Ok(line_info)
+    }
+
+    pub fn is_output(&self) -> bool {
+        self.flags & LINE_FLAG_IS_OUT > 0
+    }
+
+    // Returns the consumer label, or "unknown consumer" if the kernel didn't provide one.
+    pub fn consumer_label(&self) -> String {
+        let len = self
+            .consumer
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(LABEL_BUFSIZE);
+
+        match String::from_utf8_lossy(&self.consumer[..len]).trim() {
+            "" => String::from("unknown consumer"),
+            label => label.to_owned(),
+        }
+    }
 }
 
 const HANDLES_MAX: usize = 64;
-const HANDLE_FLAG_INPUT: u32 = 0x01;
-const HANDLE_FLAG_OUTPUT: u32 = 0x02;
+pub const HANDLE_FLAG_INPUT: u32 = 0x01;
+pub const HANDLE_FLAG_OUTPUT: u32 = 0x02;
 const HANDLE_FLAG_ACTIVE_LOW: u32 = 0x04;
 const HANDLE_FLAG_OPEN_DRAIN: u32 = 0x08;
 const HANDLE_FLAG_OPEN_SOURCE: u32 = 0x10;
+pub const HANDLE_FLAG_BIAS_PULL_UP: u32 = 0x20;
+pub const HANDLE_FLAG_BIAS_PULL_DOWN: u32 = 0x40;
+pub const HANDLE_FLAG_BIAS_DISABLE: u32 = 0x80;
 
 #[derive(Copy, Clone)]
 #[repr(C)]
@@ -172,6 +202,29 @@ impl HandleRequest {
         Ok(handle_request)
     }
 
+    // Requests a single line with the specified flags. For outputs, the line is
+    // initially set to level.
+    pub fn with_flags(cdev_fd: c_int, pin: u8, flags: u32, level: Level) -> Result<HandleRequest> {
+        let mut handle_request = HandleRequest {
+            line_offsets: [0u32; HANDLES_MAX],
+            flags,
+            default_values: [0u8; HANDLES_MAX],
+            consumer_label: [0u8; LABEL_BUFSIZE],
+            lines: 1,
+            fd: 0,
+        };
+
+        handle_request.line_offsets[0] = u32::from(pin);
+        handle_request.default_values[0] = level as u8;
+
+        let label = b"rppal";
+        handle_request.consumer_label[..label.len()].copy_from_slice(label);
+
+        parse_retval!(unsafe { ioctl(cdev_fd, REQ_GET_LINE_HANDLE, &mut handle_request) })?;
+
+        Ok(handle_request)
+    }
+
     pub fn levels(&self) -> Result<HandleData> {
         let mut handle_data = HandleData::new();
 
@@ -306,7 +359,7 @@ pub fn get_event(event_fd: c_int) -> Result<Event> {
 
 // Find the correct gpiochip device based on its label
 pub fn find_gpiochip() -> Result<File> {
-    let driver_name = b"pinctrl-bcm2835\0";
+    let driver_names: [&[u8]; 3] = [b"pinctrl-bcm2835\0", b"pinctrl-bcm2711\0", b"pinctrl-rp1\0"];
 
     for idx in 0..=255 {
         let gpiochip = OpenOptions::new()
@@ -315,7 +368,10 @@ pub fn find_gpiochip() -> Result<File> {
             .open(format!("/dev/gpiochip{}", idx))?;
 
         let chip_info = ChipInfo::new(gpiochip.as_raw_fd())?;
-        if chip_info.label[0..driver_name.len()] == driver_name[..] {
+        if driver_names
+            .iter()
+            .any(|driver_name| chip_info.label[0..driver_name.len()] == driver_name[..])
+        {
             return Ok(gpiochip);
         }
     }
@@ -324,13 +380,40 @@ pub fn find_gpiochip() -> Result<File> {
 }
 
 pub fn get_level(cdev_fd: c_int, pin: u8) -> Result<Level> {
-    match HandleRequest::new(cdev_fd, &[pin])?.levels()?.values[0] {
+    let handle_request = HandleRequest::new(cdev_fd, &[pin])?;
+    let levels = handle_request.levels();
+
+    // Release the line, so it can be requested again
+    close(handle_request.fd);
+
+    match levels?.values[0] {
         0 => Ok(Level::Low),
-        1 => Ok(Level::High),
-        _ => unreachable!(),
+        _ => Ok(Level::High),
     }
 }
 
+// Reads the level of the first line of an existing line handle
+pub fn get_line_level(handle_fd: c_int) -> Result<Level> {
+    let mut handle_data = HandleData::new();
+
+    parse_retval!(unsafe { ioctl(handle_fd, REQ_GET_LINE_VALUES, &mut handle_data) })?;
+
+    match handle_data.values[0] {
+        0 => Ok(Level::Low),
+        _ => Ok(Level::High),
+    }
+}
+
+// Sets the level of the first line of an existing line handle
+pub fn set_line_level(handle_fd: c_int, level: Level) -> Result<()> {
+    let mut handle_data = HandleData::new();
+    handle_data.values[0] = level as u8;
+
+    parse_retval!(unsafe { ioctl(handle_fd, REQ_SET_LINE_VALUES, &mut handle_data) })?;
+
+    Ok(())
+}
+
 pub fn close(fd: c_int) {
     unsafe {
         libc::close(fd);
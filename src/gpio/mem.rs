@@ -20,33 +20,61 @@
 
 use std::fmt;
 use std::fs::OpenOptions;
+use std::hint;
 use std::io;
 use std::os::unix::fs::OpenOptionsExt;
 use std::os::unix::io::AsRawFd;
 use std::ptr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread::sleep;
+use std::thread::{self, sleep};
 use std::time::Duration;
 
 use libc;
 
-use crate::gpio::{Error, Level, Mode, PullUpDown, Result};
-use crate::system::DeviceInfo;
+use crate::gpio::backend::{FastIo, GpioRegisters};
+use crate::gpio::{Error, Level, Mode, PullUpDown, Result, Trigger};
+use crate::system::{DeviceInfo, SoC};
 
 // The BCM2835 has 41 32-bit registers related to the GPIO (datasheet @ 6.1).
-const GPIO_MEM_REGISTERS: usize = 41;
+// The BCM2711 adds 4 pull-up/pull-down control registers at 0xe4-0xf0.
+const GPIO_MEM_REGISTERS: usize = 61;
 const GPIO_MEM_SIZE: usize = GPIO_MEM_REGISTERS * std::mem::size_of::<u32>();
 
 const GPFSEL0: usize = 0x00;
 const GPSET0: usize = 0x1c / std::mem::size_of::<u32>();
 const GPCLR0: usize = 0x28 / std::mem::size_of::<u32>();
 const GPLEV0: usize = 0x34 / std::mem::size_of::<u32>();
+const GPEDS0: usize = 0x40 / std::mem::size_of::<u32>();
+const GPREN0: usize = 0x4c / std::mem::size_of::<u32>();
+const GPFEN0: usize = 0x58 / std::mem::size_of::<u32>();
 const GPPUD: usize = 0x94 / std::mem::size_of::<u32>();
 const GPPUDCLK0: usize = 0x98 / std::mem::size_of::<u32>();
+const GPPUPPDN0: usize = 0xe4 / std::mem::size_of::<u32>();
+
+// GPPUPPDN0-3 use a different encoding than GPPUD
+const GPPUPPDN_OFF: u32 = 0b00;
+const GPPUPPDN_PULLUP: u32 = 0b01;
+const GPPUPPDN_PULLDOWN: u32 = 0b10;
+
+// Number of spin iterations after which a contended register lock starts yielding
+// to other threads, rather than doubling the backoff
+const LOCK_SPIN_LIMIT: u32 = 64;
+
+// Releases a register lock when it goes out of scope
+struct RegisterGuard<'a> {
+    lock: &'a AtomicBool,
+}
+
+impl Drop for RegisterGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.store(false, Ordering::Release);
+    }
+}
 
 pub struct GpioMem {
     mem_ptr: *mut u32,
     locks: [AtomicBool; GPIO_MEM_REGISTERS],
+    soc: SoC,
 }
 
 impl fmt::Debug for GpioMem {
@@ -54,12 +82,25 @@ impl fmt::Debug for GpioMem {
         f.debug_struct("GpioMem")
             .field("mem_ptr", &self.mem_ptr)
             .field("locks", &format_args!("{{ .. }}"))
+            .field("soc", &self.soc)
             .finish()
     }
 }
 
 impl GpioMem {
     pub fn open() -> Result<GpioMem> {
+        // The SoC determines which registers are used to configure the pull-up/pull-down
+        // resistors. If we can't identify the SoC, fall back to the BCM2835 registers,
+        // which used to be the only option.
+        let soc = DeviceInfo::new()
+            .map(|device_info| device_info.soc())
+            .unwrap_or(SoC::Bcm2835);
+
+        // The RP1's GPIO registers are incompatible with the BCM283x/BCM2711 layout
+        if soc == SoC::Bcm2712 {
+            return Err(Error::UnsupportedSoC(soc));
+        }
+
         // Try /dev/gpiomem first. If that fails, try /dev/mem instead. If neither works,
         // report back the error that's the most relevant.
         let mem_ptr = match Self::map_devgpiomem() {
@@ -76,7 +117,11 @@ impl GpioMem {
 
         let locks = init_array!(AtomicBool::new(false), GPIO_MEM_REGISTERS);
 
-        Ok(GpioMem { mem_ptr, locks })
+        Ok(GpioMem {
+            mem_ptr,
+            locks,
+            soc,
+        })
     }
 
     fn map_devgpiomem() -> Result<*mut u32> {
@@ -127,7 +172,8 @@ impl GpioMem {
                 libc::PROT_READ | libc::PROT_WRITE,
                 libc::MAP_SHARED,
                 mem_file.as_raw_fd(),
-                (device_info.peripheral_base() + device_info.gpio_offset()) as libc::off_t,
+                (device_info.peripheral_base() + u64::from(device_info.gpio_offset()))
+                    as libc::off_t,
             )
         };
 
@@ -150,19 +196,129 @@ impl GpioMem {
         }
     }
 
-    pub(crate) fn set_high(&self, pin: u8) {
+    // Acquires the lock for a register that needs a read-modify-write cycle. Other
+    // threads only hold a register lock for a few instructions (or ~10 µs while
+    // updating GPPUD), so we spin with exponential backoff before yielding.
+    fn lock(&self, offset: usize) -> RegisterGuard<'_> {
+        let lock = &self.locks[offset];
+        let mut spins = 1;
+
+        while lock
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            // Wait until the lock looks free before retrying, so we don't keep
+            // invalidating the cache line while it's held
+            while lock.load(Ordering::Relaxed) {
+                if spins < LOCK_SPIN_LIMIT {
+                    for _ in 0..spins {
+                        hint::spin_loop();
+                    }
+
+                    spins *= 2;
+                } else {
+                    thread::yield_now();
+                }
+            }
+        }
+
+        RegisterGuard { lock }
+    }
+
+    fn set_pullupdown_bcm2711(&self, pin: u8, pud: PullUpDown) {
+        let offset = GPPUPPDN0 + pin as usize / 16;
+        let shift = (pin % 16) * 2;
+
+        let pud_value = match pud {
+            PullUpDown::Off => GPPUPPDN_OFF,
+            PullUpDown::PullUp => GPPUPPDN_PULLUP,
+            PullUpDown::PullDown => GPPUPPDN_PULLDOWN,
+        };
+
+        let _guard = self.lock(offset);
+
+        let reg_value = self.read(offset);
+        self.write(
+            offset,
+            (reg_value & !(0b11 << shift)) | (pud_value << shift),
+        );
+    }
+
+    fn set_pullupdown_bcm2835(&self, pin: u8, pud: PullUpDown) {
+        let offset = GPPUDCLK0 + pin as usize / 32;
+        let shift = pin % 32;
+
+        // GPPUDCLKn is only ever locked while holding the GPPUD lock, so acquiring
+        // both in this order can't deadlock
+        let _gppud_guard = self.lock(GPPUD);
+        let _gppudclk_guard = self.lock(offset);
+
+        // Set the control signal in GPPUD.
+        let reg_value = self.read(GPPUD);
+        self.write(GPPUD, (reg_value & !0b11) | ((pud as u32) & 0b11));
+
+        // The datasheet mentions waiting at least 150 cycles for set-up and hold, but
+        // doesn't state which clock is used. This is likely the VPU clock (see
+        // https://www.raspberrypi.org/forums/viewtopic.php?f=72&t=163352). At either
+        // 250MHz or 400MHz, a 5µs delay + overhead is more than adequate.
+
+        // Set-up time for the control signal.
+        sleep(Duration::new(0, 5000)); // >= 5µs
+
+        // Clock the control signal into the selected pin.
+        self.write(offset, 1 << shift);
+
+        // Hold time for the control signal.
+        sleep(Duration::new(0, 5000)); // >= 5µs
+
+        // Remove the control signal and clock.
+        self.write(GPPUD, reg_value & !0b11);
+        self.write(offset, 0);
+    }
+}
+
+impl GpioRegisters for GpioMem {
+    #[inline]
+    fn soc(&self) -> SoC {
+        self.soc
+    }
+
+    // set_high(), set_low(), write_levels(), level() and levels() never take a
+    // register lock. GPSETn and GPCLRn only act on bits that are set to 1, and GPLEVn
+    // is read-only, so a single volatile access is atomic with respect to other pins,
+    // and these methods stay safe to call from interrupt callbacks and the software
+    // PWM thread while another thread is changing a pin's mode or pull-up/down state.
+    fn set_high(&self, pin: u8) {
         let offset = GPSET0 + pin as usize / 32;
         let shift = pin % 32;
         self.write(offset, 1 << shift);
     }
 
-    pub(crate) fn set_low(&self, pin: u8) {
+    fn set_low(&self, pin: u8) {
         let offset = GPCLR0 + pin as usize / 32;
         let shift = pin % 32;
         self.write(offset, 1 << shift);
     }
 
-    pub(crate) fn level(&self, pin: u8) -> Level {
+    // Sets all pins in set_mask high and all pins in clear_mask low, using a single
+    // register write per bank. Writing a 0 to GPSETn/GPCLRn has no effect, so
+    // there's no need for a read-modify-write or locking.
+    fn write_levels(&self, set_mask: u64, clear_mask: u64) {
+        for bank in 0..2 {
+            let set_bits = (set_mask >> (bank * 32)) as u32;
+            let clear_bits = (clear_mask >> (bank * 32)) as u32;
+
+            if set_bits != 0 {
+                self.write(GPSET0 + bank, set_bits);
+            }
+
+            if clear_bits != 0 {
+                self.write(GPCLR0 + bank, clear_bits);
+            }
+        }
+    }
+
+    fn level(&self, pin: u8) -> Level {
         let offset = GPLEV0 + pin as usize / 32;
         let shift = pin % 32;
 
@@ -171,7 +327,76 @@ impl GpioMem {
         unsafe { std::mem::transmute((reg_value >> shift) as u8 & 0b1) }
     }
 
-    pub(crate) fn mode(&self, pin: u8) -> Mode {
+    // Reads GPLEV0 and GPLEV1, and returns the logic levels of all pins
+    // as a bitmask, with bit 0 mapped to GPIO 0.
+    fn levels(&self) -> u64 {
+        u64::from(self.read(GPLEV0)) | (u64::from(self.read(GPLEV0 + 1)) << 32)
+    }
+
+    fn fast_io(&self, pin: u8) -> Option<FastIo> {
+        let offset = pin as usize / 32;
+        let mask = 1 << (pin % 32);
+
+        unsafe {
+            Some(FastIo::new(
+                self.mem_ptr.add(GPSET0 + offset),
+                self.mem_ptr.add(GPCLR0 + offset),
+                mask,
+            ))
+        }
+    }
+
+    fn register_ptr(&self, offset: usize) -> Result<*mut u32> {
+        if offset & 0b11 != 0 || offset >= GPIO_MEM_SIZE {
+            return Err(Error::InvalidRegister(offset));
+        }
+
+        Ok(unsafe { self.mem_ptr.add(offset / std::mem::size_of::<u32>()) })
+    }
+
+    fn set_edge_detect(&self, pin: u8, trigger: Trigger) -> Result<()> {
+        let offset = pin as usize / 32;
+        let mask = 1 << (pin % 32);
+
+        let rising = trigger == Trigger::RisingEdge || trigger == Trigger::Both;
+        let falling = trigger == Trigger::FallingEdge || trigger == Trigger::Both;
+
+        for &(reg, enable) in &[(GPREN0 + offset, rising), (GPFEN0 + offset, falling)] {
+            let _guard = self.lock(reg);
+
+            let reg_value = self.read(reg);
+            self.write(
+                reg,
+                if enable {
+                    reg_value | mask
+                } else {
+                    reg_value & !mask
+                },
+            );
+        }
+
+        // Clear any event that was detected before the trigger was changed
+        self.write(GPEDS0 + offset, mask);
+
+        Ok(())
+    }
+
+    // GPEDSn bits are cleared by writing a 1, which leaves the other pins' bits
+    // untouched, so this doesn't need a lock.
+    fn take_edge_event(&self, pin: u8) -> Result<bool> {
+        let offset = GPEDS0 + pin as usize / 32;
+        let mask = 1 << (pin % 32);
+
+        if self.read(offset) & mask == 0 {
+            return Ok(false);
+        }
+
+        self.write(offset, mask);
+
+        Ok(true)
+    }
+
+    fn mode(&self, pin: u8) -> Mode {
         let offset = GPFSEL0 + pin as usize / 10;
         let shift = (pin % 10) * 3;
 
@@ -180,64 +405,45 @@ impl GpioMem {
         unsafe { std::mem::transmute((reg_value >> shift) as u8 & 0b111) }
     }
 
-    pub(crate) fn set_mode(&self, pin: u8, mode: Mode) {
+    fn set_mode(&self, pin: u8, mode: Mode) {
         let offset = GPFSEL0 + pin as usize / 10;
         let shift = (pin % 10) * 3;
 
-        loop {
-            if !self.locks[offset].compare_and_swap(false, true, Ordering::SeqCst) {
-                break;
-            }
-        }
+        let _guard = self.lock(offset);
 
         let reg_value = self.read(offset);
         self.write(
             offset,
             (reg_value & !(0b111 << shift)) | ((mode as u32) << shift),
         );
-
-        self.locks[offset].store(false, Ordering::SeqCst);
     }
 
     /// Configures the built-in GPIO pull-up/pull-down resistors.
-    pub(crate) fn set_pullupdown(&self, pin: u8, pud: PullUpDown) {
-        let offset = GPPUDCLK0 + pin as usize / 32;
-        let shift = pin % 32;
-
-        loop {
-            if !self.locks[GPPUD].compare_and_swap(false, true, Ordering::SeqCst) {
-                if !self.locks[offset].compare_and_swap(false, true, Ordering::SeqCst) {
-                    break;
-                } else {
-                    self.locks[GPPUD].store(false, Ordering::SeqCst);
-                }
-            }
+    fn set_pullupdown(&self, pin: u8, pud: PullUpDown) {
+        if self.soc == SoC::Bcm2711 {
+            self.set_pullupdown_bcm2711(pin, pud);
+        } else {
+            self.set_pullupdown_bcm2835(pin, pud);
         }
+    }
 
-        // Set the control signal in GPPUD.
-        let reg_value = self.read(GPPUD);
-        self.write(GPPUD, (reg_value & !0b11) | ((pud as u32) & 0b11));
-
-        // The datasheet mentions waiting at least 150 cycles for set-up and hold, but
-        // doesn't state which clock is used. This is likely the VPU clock (see
-        // https://www.raspberrypi.org/forums/viewtopic.php?f=72&t=163352). At either
-        // 250MHz or 400MHz, a 5µs delay + overhead is more than adequate.
-
-        // Set-up time for the control signal.
-        sleep(Duration::new(0, 5000)); // >= 5µs
-
-        // Clock the control signal into the selected pin.
-        self.write(offset, 1 << shift);
-
-        // Hold time for the control signal.
-        sleep(Duration::new(0, 5000)); // >= 5µs
+    /// Returns the configured state of the built-in GPIO pull-up/pull-down resistors.
+    ///
+    /// Only the BCM2711 supports reading back the configured state. Returns `None`
+    /// on older SoCs.
+    fn pullupdown(&self, pin: u8) -> Option<PullUpDown> {
+        if self.soc != SoC::Bcm2711 {
+            return None;
+        }
 
-        // Remove the control signal and clock.
-        self.write(GPPUD, reg_value & !0b11);
-        self.write(offset, 0);
+        let offset = GPPUPPDN0 + pin as usize / 16;
+        let shift = (pin % 16) * 2;
 
-        self.locks[offset].store(false, Ordering::SeqCst);
-        self.locks[GPPUD].store(false, Ordering::SeqCst);
+        match (self.read(offset) >> shift) & 0b11 {
+            GPPUPPDN_PULLUP => Some(PullUpDown::PullUp),
+            GPPUPPDN_PULLDOWN => Some(PullUpDown::PullDown),
+            _ => Some(PullUpDown::Off),
+        }
     }
 }
 
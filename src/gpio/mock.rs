@@ -0,0 +1,394 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::gpio::backend::GpioRegisters;
+use crate::gpio::record;
+use crate::gpio::{pin, Level, Mode, PullUpDown, Result};
+use crate::system::SoC;
+
+#[derive(Debug, Copy, Clone)]
+struct PinState {
+    mode: Mode,
+    output: Level,
+    input: Option<Level>,
+    pud: PullUpDown,
+}
+
+impl Default for PinState {
+    fn default() -> PinState {
+        PinState {
+            mode: Mode::Input,
+            output: Level::Low,
+            input: None,
+            pud: PullUpDown::Off,
+        }
+    }
+}
+
+// Recorded input levels that are returned by subsequent reads, in order.
+#[derive(Debug)]
+struct Replay {
+    reads: Vec<VecDeque<Level>>,
+    levels: VecDeque<u64>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    soc: SoC,
+    pins: Mutex<[PinState; pin::MAX]>,
+    replay: Mutex<Replay>,
+}
+
+/// Simulated GPIO peripheral for host-side testing.
+///
+/// `MockGpio` keeps the mode, output level and pull-up/pull-down state of each pin
+/// in memory, instead of accessing the GPIO registers. A [`Gpio`] instance backed by
+/// `MockGpio` can be created with [`Gpio::with_mock`], which allows applications
+/// and libraries built on RPPAL to be tested on any Linux machine without access to
+/// a Raspberry Pi.
+///
+/// Tests can simulate external signals with [`set_input_level`], and inspect
+/// what the application configured and wrote with [`mode`], [`level`] and
+/// [`pullupdown`]. An input pin that hasn't been assigned a level reads as
+/// `High` when its pull-up resistor is enabled, and `Low` otherwise. A pin that's
+/// configured as an output reads back its output level.
+///
+/// Inputs recorded on a Raspberry Pi through a [`Recorder`] can be fed back to
+/// the application with [`replay`].
+///
+/// `MockGpio` can be cloned cheaply, and all clones share the same simulated state.
+/// Interrupts aren't supported, and return [`Error::InterruptsNotSupported`].
+///
+/// `MockGpio` is only available when the `mock` feature is enabled.
+///
+/// ## Examples
+///
+/// ```
+/// use rppal::gpio::{Gpio, Level, MockGpio, Mode};
+///
+/// # fn main() -> rppal::gpio::Result<()> {
+/// let mock = MockGpio::new();
+/// let gpio = Gpio::with_mock(&mock)?;
+///
+/// let mut led = gpio.get(23).unwrap().into_output();
+/// led.set_high();
+/// assert_eq!(mock.mode(23), Mode::Output);
+/// assert_eq!(mock.level(23), Level::High);
+///
+/// let button = gpio.get(24).unwrap().into_input();
+/// mock.set_input_level(24, Level::High);
+/// assert!(button.is_high());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Gpio`]: struct.Gpio.html
+/// [`Gpio::with_mock`]: struct.Gpio.html#method.with_mock
+/// [`set_input_level`]: #method.set_input_level
+/// [`mode`]: #method.mode
+/// [`level`]: #method.level
+/// [`pullupdown`]: #method.pullupdown
+/// [`Recorder`]: struct.Recorder.html
+/// [`replay`]: #method.replay
+/// [`Error::InterruptsNotSupported`]: enum.Error.html#variant.InterruptsNotSupported
+#[derive(Clone, Debug)]
+pub struct MockGpio {
+    inner: Arc<MockState>,
+}
+
+impl MockGpio {
+    /// Constructs a new `MockGpio` that simulates a BCM2711 (Raspberry Pi 4).
+    ///
+    /// All pins start out as inputs, with their output level set to `Low` and the
+    /// built-in pull-up/pull-down resistors disabled.
+    pub fn new() -> MockGpio {
+        MockGpio::with_soc(SoC::Bcm2711)
+    }
+
+    /// Constructs a new `MockGpio` that simulates the specified SoC.
+    ///
+    /// The SoC determines which alternate functions are reported by
+    /// [`Pin::alt_functions`].
+    ///
+    /// [`Pin::alt_functions`]: struct.Pin.html#method.alt_functions
+    pub fn with_soc(soc: SoC) -> MockGpio {
+        MockGpio {
+            inner: Arc::new(MockState {
+                soc,
+                pins: Mutex::new([PinState::default(); pin::MAX]),
+                replay: Mutex::new(Replay {
+                    reads: vec![VecDeque::new(); pin::MAX],
+                    levels: VecDeque::new(),
+                }),
+            }),
+        }
+    }
+
+    /// Returns the simulated SoC.
+    pub fn soc(&self) -> SoC {
+        self.inner.soc
+    }
+
+    /// Simulates an external signal driving the pin to the specified logic level.
+    ///
+    /// The level is returned whenever the pin is read while it isn't configured
+    /// as an output. Invalid pin numbers are ignored.
+    pub fn set_input_level(&self, pin: u8, level: Level) {
+        self.update(pin, |state| state.input = Some(level));
+    }
+
+    /// Removes a previously simulated external signal, leaving the pin floating.
+    ///
+    /// A floating input reads as `High` when its pull-up resistor is enabled, and
+    /// `Low` otherwise. Invalid pin numbers are ignored.
+    pub fn clear_input_level(&self, pin: u8) {
+        self.update(pin, |state| state.input = None);
+    }
+
+    /// Returns the pin's current logic level.
+    ///
+    /// For output pins, this is the level that was last written. Invalid pin
+    /// numbers read as `Low`.
+    pub fn level(&self, pin: u8) -> Level {
+        match self.state(pin) {
+            Some(state) if state.mode == Mode::Output => state.output,
+            Some(state) => match (state.input, state.pud) {
+                (Some(level), _) => level,
+                (None, PullUpDown::PullUp) => Level::High,
+                (None, _) => Level::Low,
+            },
+            None => Level::Low,
+        }
+    }
+
+    /// Returns the pin's current mode.
+    ///
+    /// Invalid pin numbers return `Mode::Input`.
+    pub fn mode(&self, pin: u8) -> Mode {
+        self.state(pin).map_or(Mode::Input, |state| state.mode)
+    }
+
+    /// Returns the configured state of the pin's built-in pull-up/pull-down resistors.
+    ///
+    /// Invalid pin numbers return `PullUpDown::Off`.
+    pub fn pullupdown(&self, pin: u8) -> PullUpDown {
+        self.state(pin).map_or(PullUpDown::Off, |state| state.pud)
+    }
+
+    /// Queues the input levels from a recording created by a [`Recorder`].
+    ///
+    /// Each `read` event in the recording is returned in order by the next read
+    /// of the same pin while it isn't configured as an output, and each `levels`
+    /// event by the next time multiple pins are read at once. After a pin's
+    /// recorded reads are exhausted, it keeps the last replayed level, which can
+    /// be overridden with [`set_input_level`]. All other events are ignored.
+    ///
+    /// Replaying an application's recorded inputs while comparing its outputs
+    /// against the recording allows hardware-free regression testing.
+    ///
+    /// [`Recorder`]: struct.Recorder.html
+    /// [`set_input_level`]: #method.set_input_level
+    pub fn replay<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.replay_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Queues the input levels from a recording read from the specified reader.
+    ///
+    /// See [`replay`] for more details.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use rppal::gpio::{Gpio, Level, MockGpio};
+    ///
+    /// # fn main() -> rppal::gpio::Result<()> {
+    /// let recording = "{\"time\":16340,\"event\":\"read\",\"pin\":24,\"level\":\"High\"}\n\
+    ///                  {\"time\":17914,\"event\":\"read\",\"pin\":24,\"level\":\"Low\"}\n";
+    ///
+    /// let mock = MockGpio::new();
+    /// mock.replay_from(recording.as_bytes())?;
+    ///
+    /// let gpio = Gpio::with_mock(&mock)?;
+    /// let button = gpio.get(24).unwrap().into_input();
+    ///
+    /// assert_eq!(button.read(), Level::High);
+    /// assert_eq!(button.read(), Level::Low);
+    /// assert_eq!(mock.replay_pending(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`replay`]: #method.replay
+    pub fn replay_from<R: BufRead>(&self, reader: R) -> Result<()> {
+        let mut replay = self.inner.replay.lock().unwrap();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid GPIO recording on line {}", index + 1),
+                )
+            };
+
+            match record::field(&line, "event") {
+                Some("read") => {
+                    let pin: usize = record::field(&line, "pin")
+                        .and_then(|pin| pin.parse().ok())
+                        .filter(|&pin| pin < pin::MAX)
+                        .ok_or_else(invalid)?;
+                    let level = match record::field(&line, "level") {
+                        Some("Low") => Level::Low,
+                        Some("High") => Level::High,
+                        _ => return Err(invalid().into()),
+                    };
+
+                    replay.reads[pin].push_back(level);
+                }
+                Some("levels") => {
+                    let levels = record::field(&line, "levels")
+                        .and_then(|levels| levels.parse().ok())
+                        .ok_or_else(invalid)?;
+
+                    replay.levels.push_back(levels);
+                }
+                Some(_) => (),
+                None if line.trim().is_empty() => (),
+                None => return Err(invalid().into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of recorded reads that haven't been replayed yet.
+    pub fn replay_pending(&self) -> usize {
+        let replay = self.inner.replay.lock().unwrap();
+
+        replay.reads.iter().map(VecDeque::len).sum::<usize>() + replay.levels.len()
+    }
+
+    fn state(&self, pin: u8) -> Option<PinState> {
+        self.inner.pins.lock().unwrap().get(pin as usize).copied()
+    }
+
+    fn update<F>(&self, pin: u8, f: F)
+    where
+        F: FnOnce(&mut PinState),
+    {
+        if let Some(state) = self.inner.pins.lock().unwrap().get_mut(pin as usize) {
+            f(state);
+        }
+    }
+}
+
+impl Default for MockGpio {
+    fn default() -> MockGpio {
+        MockGpio::new()
+    }
+}
+
+impl GpioRegisters for MockGpio {
+    fn soc(&self) -> SoC {
+        self.inner.soc
+    }
+
+    fn set_high(&self, pin: u8) {
+        self.update(pin, |state| state.output = Level::High);
+    }
+
+    fn set_low(&self, pin: u8) {
+        self.update(pin, |state| state.output = Level::Low);
+    }
+
+    fn write_levels(&self, set_mask: u64, clear_mask: u64) {
+        let mut pins = self.inner.pins.lock().unwrap();
+
+        for (pin, state) in pins.iter_mut().enumerate() {
+            if (set_mask >> pin) & 1 == 1 {
+                state.output = Level::High;
+            }
+
+            if (clear_mask >> pin) & 1 == 1 {
+                state.output = Level::Low;
+            }
+        }
+    }
+
+    fn level(&self, pin: u8) -> Level {
+        if MockGpio::mode(self, pin) != Mode::Output {
+            let replayed = self
+                .inner
+                .replay
+                .lock()
+                .unwrap()
+                .reads
+                .get_mut(pin as usize)
+                .and_then(VecDeque::pop_front);
+
+            if let Some(level) = replayed {
+                self.set_input_level(pin, level);
+            }
+        }
+
+        MockGpio::level(self, pin)
+    }
+
+    fn levels(&self) -> u64 {
+        let replayed = self.inner.replay.lock().unwrap().levels.pop_front();
+
+        if let Some(levels) = replayed {
+            for (pin, state) in self.inner.pins.lock().unwrap().iter_mut().enumerate() {
+                state.input = Some(if (levels >> pin) & 1 == 1 {
+                    Level::High
+                } else {
+                    Level::Low
+                });
+            }
+        }
+
+        (0..pin::MAX as u8).fold(0, |acc, pin| match MockGpio::level(self, pin) {
+            Level::High => acc | (1 << pin),
+            Level::Low => acc,
+        })
+    }
+
+    fn mode(&self, pin: u8) -> Mode {
+        MockGpio::mode(self, pin)
+    }
+
+    fn set_mode(&self, pin: u8, mode: Mode) {
+        self.update(pin, |state| state.mode = mode);
+    }
+
+    fn set_pullupdown(&self, pin: u8, pud: PullUpDown) {
+        self.update(pin, |state| state.pud = pud);
+    }
+
+    fn pullupdown(&self, pin: u8) -> Option<PullUpDown> {
+        Some(MockGpio::pullupdown(self, pin))
+    }
+}
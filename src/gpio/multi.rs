@@ -0,0 +1,284 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::gpio::epoll::{epoll_event, Epoll, EPOLLIN, EPOLLPRI};
+use crate::gpio::interrupt::Interrupt;
+use crate::gpio::{Event, GpioState, InputPin, Result, Trigger};
+
+/// Builder for [`MultiInterrupt`].
+///
+/// `MultiInterruptBuilder` is returned by [`Gpio::multi_interrupt`]. Each call to
+/// [`add`] or [`add_debounced`] moves an [`InputPin`] into the builder, and configures
+/// its interrupt trigger.
+///
+/// [`MultiInterrupt`]: struct.MultiInterrupt.html
+/// [`Gpio::multi_interrupt`]: struct.Gpio.html#method.multi_interrupt
+/// [`add`]: #method.add
+/// [`add_debounced`]: #method.add_debounced
+/// [`InputPin`]: struct.InputPin.html
+#[derive(Debug)]
+pub struct MultiInterruptBuilder {
+    gpio_state: Arc<GpioState>,
+    pins: Vec<(InputPin, Trigger, Option<Duration>)>,
+}
+
+impl MultiInterruptBuilder {
+    pub(crate) fn new(gpio_state: Arc<GpioState>) -> MultiInterruptBuilder {
+        MultiInterruptBuilder {
+            gpio_state,
+            pins: Vec::new(),
+        }
+    }
+
+    /// Adds a pin with the specified interrupt trigger.
+    pub fn add(mut self, pin: InputPin, trigger: Trigger) -> MultiInterruptBuilder {
+        self.pins.push((pin, trigger, None));
+        self
+    }
+
+    /// Adds a pin with the specified interrupt trigger and debounce filter.
+    ///
+    /// More information on debouncing can be found at [`InputPin::set_interrupt_debounced`].
+    ///
+    /// [`InputPin::set_interrupt_debounced`]: struct.InputPin.html#method.set_interrupt_debounced
+    pub fn add_debounced(
+        mut self,
+        pin: InputPin,
+        trigger: Trigger,
+        debounce: Duration,
+    ) -> MultiInterruptBuilder {
+        self.pins.push((pin, trigger, Some(debounce)));
+        self
+    }
+
+    /// Configures the interrupt triggers for all added pins, and returns a [`MultiInterrupt`].
+    ///
+    /// Any previously configured (a)synchronous interrupt triggers on the added
+    /// pins will be cleared.
+    ///
+    /// [`MultiInterrupt`]: struct.MultiInterrupt.html
+    pub fn build(self) -> Result<MultiInterrupt> {
+        let poll = Epoll::new()?;
+
+        let mut interrupts = Vec::with_capacity(self.pins.len());
+        let mut pins = Vec::with_capacity(self.pins.len());
+
+        for (idx, (mut pin, trigger, debounce)) in self.pins.into_iter().enumerate() {
+            pin.clear_interrupt()?;
+            pin.clear_async_interrupt()?;
+
+            let cdev_fd = self.gpio_state.cdev_fd(pin.pin())?;
+            let interrupt = Interrupt::new(cdev_fd, pin.pin(), trigger, debounce)?;
+            poll.add(interrupt.fd(), idx as u64, EPOLLIN | EPOLLPRI)?;
+
+            interrupts.push(interrupt);
+            pins.push(pin);
+        }
+
+        Ok(MultiInterrupt {
+            poll,
+            events: vec![epoll_event { events: 0, u64: 0 }; interrupts.len()],
+            pending: VecDeque::new(),
+            interrupts,
+            pins,
+            _gpio_state: self.gpio_state,
+        })
+    }
+}
+
+/// Synchronous interrupt triggers for multiple pins.
+///
+/// `MultiInterrupt` owns a set of [`InputPin`]s, each configured with its own
+/// interrupt trigger, and lets you wait for events on any of them using [`poll`]
+/// or the iterator returned by [`events`]. Unlike [`Gpio::poll_interrupts`],
+/// `MultiInterrupt` doesn't share its state with other pins, so it can be polled
+/// without blocking calls to [`InputPin::poll_interrupt`].
+///
+/// Use [`Gpio::multi_interrupt`] to construct a new `MultiInterrupt`.
+///
+/// `MultiInterrupt` implements `AsRawFd`, so it can be registered with an
+/// external event loop, and polled with [`try_poll`] when it becomes readable.
+///
+/// [`InputPin`]: struct.InputPin.html
+/// [`poll`]: #method.poll
+/// [`events`]: #method.events
+/// [`Gpio::poll_interrupts`]: struct.Gpio.html#method.poll_interrupts
+/// [`InputPin::poll_interrupt`]: struct.InputPin.html#method.poll_interrupt
+/// [`Gpio::multi_interrupt`]: struct.Gpio.html#method.multi_interrupt
+/// [`try_poll`]: #method.try_poll
+pub struct MultiInterrupt {
+    poll: Epoll,
+    events: Vec<epoll_event>,
+    pending: VecDeque<Event>,
+    // Declared before pins, so the event requests are released before the pins are reset
+    interrupts: Vec<Interrupt>,
+    pins: Vec<InputPin>,
+    _gpio_state: Arc<GpioState>,
+}
+
+impl fmt::Debug for MultiInterrupt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiInterrupt")
+            .field("poll", &self.poll)
+            .field("events", &format_args!("{{ .. }}"))
+            .field("pending", &self.pending)
+            .field("interrupts", &self.interrupts)
+            .field("pins", &self.pins)
+            .finish()
+    }
+}
+
+impl MultiInterrupt {
+    /// Blocks until an interrupt is triggered on any of the pins, or until a timeout occurs.
+    ///
+    /// The `timeout` duration indicates how long the call to `poll` will block while waiting
+    /// for interrupt trigger events, after which an `Ok(None)` is returned.
+    /// `timeout` can be set to `None` to wait indefinitely.
+    ///
+    /// If multiple events trigger at the same time, only the first one is returned. The
+    /// remaining events are cached and will be returned the next time `poll` is called.
+    pub fn poll(&mut self, timeout: Option<Duration>) -> Result<Option<Event>> {
+        if let Some(event) = self.pending.pop_front() {
+            return Ok(Some(event));
+        }
+
+        let start = Instant::now();
+        let mut first = true;
+        loop {
+            // Events might get dropped by a debounce filter, so keep track of how
+            // much time we have left. Always wait at least once, so a zero timeout
+            // still picks up any pending events.
+            let remaining = match timeout {
+                Some(t) => match t.checked_sub(start.elapsed()) {
+                    Some(remaining) => Some(remaining),
+                    None if first => Some(Duration::from_millis(0)),
+                    None => return Ok(None),
+                },
+                None => None,
+            };
+
+            let num_events = self.poll.wait(&mut self.events, remaining)?;
+            first = false;
+
+            // No events means a timeout occurred
+            if num_events == 0 {
+                return Ok(None);
+            }
+
+            for event in &self.events[0..num_events] {
+                if let Some(interrupt) = self.interrupts.get_mut(event.u64 as usize) {
+                    if let Some(event) = interrupt.event()? {
+                        self.pending.push_back(event);
+                    }
+                }
+            }
+
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+        }
+    }
+
+    /// Checks for an interrupt trigger event on any of the pins without blocking.
+    ///
+    /// `try_poll` behaves like [`poll`] with a zero timeout. It returns `Ok(None)`
+    /// if no events are pending.
+    ///
+    /// [`poll`]: #method.poll
+    pub fn try_poll(&mut self) -> Result<Option<Event>> {
+        self.poll(Some(Duration::from_millis(0)))
+    }
+
+    /// Returns an iterator that blocks while waiting for the next interrupt event.
+    ///
+    /// The iterator only returns `None` if an error occurs. The error is
+    /// returned first as `Some(Err(_))`.
+    pub fn events(&mut self) -> Events<'_> {
+        Events {
+            multi_interrupt: self,
+            failed: false,
+        }
+    }
+
+    /// Returns the pins managed by this `MultiInterrupt`.
+    pub fn pins(&self) -> &[InputPin] {
+        &self.pins
+    }
+
+    /// Consumes the `MultiInterrupt`, clears all interrupt triggers and returns the pins.
+    pub fn into_pins(self) -> Vec<InputPin> {
+        let MultiInterrupt {
+            interrupts, pins, ..
+        } = self;
+
+        drop(interrupts);
+
+        pins
+    }
+}
+
+impl AsRawFd for MultiInterrupt {
+    /// Returns the file descriptor of the underlying epoll instance.
+    ///
+    /// The file descriptor becomes readable when an interrupt trigger event is
+    /// pending on any of the pins, which allows it to be registered with an external
+    /// event loop. Once it's readable, retrieve the events with [`try_poll`].
+    ///
+    /// [`try_poll`]: #method.try_poll
+    fn as_raw_fd(&self) -> RawFd {
+        self.poll.fd()
+    }
+}
+
+/// Blocking iterator over interrupt events.
+///
+/// `Events` is returned by [`MultiInterrupt::events`].
+///
+/// [`MultiInterrupt::events`]: struct.MultiInterrupt.html#method.events
+#[derive(Debug)]
+pub struct Events<'a> {
+    multi_interrupt: &'a mut MultiInterrupt,
+    failed: bool,
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Result<Event>> {
+        if self.failed {
+            return None;
+        }
+
+        match self.multi_interrupt.poll(None) {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => None,
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+
+use libc;
+
+use crate::gpio::{Error, Result};
+use crate::system::{DeviceInfo, SoC};
+
+// The pads control registers are located in the power management block, which
+// isn't accessible through /dev/gpiomem.
+const PADS_OFFSET: u64 = 0x10_0000;
+const PADS_MEM_SIZE: usize = 4096;
+
+const PADS_GPIO_0_27: usize = 0x2c / std::mem::size_of::<u32>();
+
+const PADS_PASSWD: u32 = 0x5a << 24;
+const PADS_SLEW: u32 = 1 << 4;
+const PADS_HYST: u32 = 1 << 3;
+const PADS_DRIVE: u32 = 0b111;
+
+/// Pad groups.
+///
+/// The BCM283x and BCM2711 share a single pads control register between all
+/// pins in a group.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum PadGroup {
+    /// BCM GPIO 0-27.
+    Gpio0To27 = 0,
+    /// BCM GPIO 28-45.
+    Gpio28To45 = 1,
+    /// BCM GPIO 46-53.
+    Gpio46To53 = 2,
+}
+
+impl PadGroup {
+    /// Returns the pad group that controls the specified BCM GPIO pin.
+    pub fn from_pin(pin: u8) -> Option<PadGroup> {
+        match pin {
+            0..=27 => Some(PadGroup::Gpio0To27),
+            28..=45 => Some(PadGroup::Gpio28To45),
+            46..=53 => Some(PadGroup::Gpio46To53),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PadGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PadGroup::Gpio0To27 => write!(f, "GPIO 0-27"),
+            PadGroup::Gpio28To45 => write!(f, "GPIO 28-45"),
+            PadGroup::Gpio46To53 => write!(f, "GPIO 46-53"),
+        }
+    }
+}
+
+/// Pad drive strengths.
+///
+/// The drive strength sets the maximum current a pin can source or sink while
+/// keeping its output voltage within the logic level specifications. It doesn't
+/// limit the current.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[repr(u8)]
+pub enum DriveStrength {
+    /// 2 mA.
+    Ma2 = 0,
+    /// 4 mA.
+    Ma4 = 1,
+    /// 6 mA.
+    Ma6 = 2,
+    /// 8 mA (default).
+    Ma8 = 3,
+    /// 10 mA.
+    Ma10 = 4,
+    /// 12 mA.
+    Ma12 = 5,
+    /// 14 mA.
+    Ma14 = 6,
+    /// 16 mA.
+    Ma16 = 7,
+}
+
+impl DriveStrength {
+    /// Returns the drive strength in milliamps (mA).
+    pub fn milliamps(self) -> u8 {
+        (self as u8 + 1) * 2
+    }
+}
+
+impl fmt::Display for DriveStrength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} mA", self.milliamps())
+    }
+}
+
+/// Pad configuration for a [`PadGroup`].
+///
+/// Retrieved through [`Gpio::pad_control`].
+///
+/// [`PadGroup`]: enum.PadGroup.html
+/// [`Gpio::pad_control`]: struct.Gpio.html#method.pad_control
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct PadControl {
+    drive_strength: DriveStrength,
+    slew_rate_limited: bool,
+    hysteresis: bool,
+}
+
+impl PadControl {
+    /// Returns the drive strength.
+    pub fn drive_strength(&self) -> DriveStrength {
+        self.drive_strength
+    }
+
+    /// Returns `true` if the output slew rate is limited.
+    pub fn slew_rate_limited(&self) -> bool {
+        self.slew_rate_limited
+    }
+
+    /// Returns `true` if the input hysteresis (Schmitt trigger) is enabled.
+    pub fn hysteresis(&self) -> bool {
+        self.hysteresis
+    }
+}
+
+struct PadsMem {
+    mem_ptr: *mut u32,
+}
+
+impl PadsMem {
+    fn open() -> Result<PadsMem> {
+        let device_info = DeviceInfo::new().map_err(|_| Error::UnknownModel)?;
+
+        // The RP1 on the Raspberry Pi 5 uses per-pin pad registers
+        if device_info.soc() == SoC::Bcm2712 {
+            return Err(Error::UnsupportedSoC(SoC::Bcm2712));
+        }
+
+        let mem_file = match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_SYNC)
+            .open("/dev/mem")
+        {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                return Err(Error::PermissionDenied)
+            }
+            Err(e) => return Err(Error::Io(e)),
+        };
+
+        let mem_ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                PADS_MEM_SIZE,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                mem_file.as_raw_fd(),
+                (device_info.peripheral_base() + PADS_OFFSET) as libc::off_t,
+            )
+        };
+
+        if mem_ptr == libc::MAP_FAILED {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+
+        Ok(PadsMem {
+            mem_ptr: mem_ptr as *mut u32,
+        })
+    }
+
+    #[inline]
+    fn read(&self, group: PadGroup) -> u32 {
+        unsafe { ptr::read_volatile(self.mem_ptr.add(PADS_GPIO_0_27 + group as usize)) }
+    }
+
+    #[inline]
+    fn write(&self, group: PadGroup, value: u32) {
+        unsafe {
+            ptr::write_volatile(
+                self.mem_ptr.add(PADS_GPIO_0_27 + group as usize),
+                PADS_PASSWD | value,
+            );
+        }
+    }
+}
+
+impl Drop for PadsMem {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.mem_ptr as *mut libc::c_void, PADS_MEM_SIZE);
+        }
+    }
+}
+
+pub(crate) fn pad_control(group: PadGroup) -> Result<PadControl> {
+    let reg_value = PadsMem::open()?.read(group);
+
+    Ok(PadControl {
+        drive_strength: match reg_value & PADS_DRIVE {
+            0 => DriveStrength::Ma2,
+            1 => DriveStrength::Ma4,
+            2 => DriveStrength::Ma6,
+            3 => DriveStrength::Ma8,
+            4 => DriveStrength::Ma10,
+            5 => DriveStrength::Ma12,
+            6 => DriveStrength::Ma14,
+            _ => DriveStrength::Ma16,
+        },
+        slew_rate_limited: reg_value & PADS_SLEW == 0,
+        hysteresis: reg_value & PADS_HYST != 0,
+    })
+}
+
+pub(crate) fn set_pad_control(
+    group: PadGroup,
+    drive_strength: DriveStrength,
+    slew_rate_limited: bool,
+    hysteresis: bool,
+) -> Result<()> {
+    let pads_mem = PadsMem::open()?;
+
+    // Keep any reserved bits intact, apart from the password field
+    let mut reg_value = pads_mem.read(group) & !(0xff << 24 | PADS_SLEW | PADS_HYST | PADS_DRIVE);
+
+    reg_value |= drive_strength as u32;
+
+    if !slew_rate_limited {
+        reg_value |= PADS_SLEW;
+    }
+
+    if hysteresis {
+        reg_value |= PADS_HYST;
+    }
+
+    pads_mem.write(group, reg_value);
+
+    Ok(())
+}
@@ -18,28 +18,115 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use std::os::unix::io::AsRawFd;
-use std::sync::atomic::Ordering;
-use std::sync::Arc;
-use std::time::Duration;
+use std::os::unix::io::RawFd;
+use std::result;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::gpio::{interrupt::AsyncInterrupt, GpioState, Level, Mode, PullUpDown, Result, Trigger};
+use crate::delay;
+use crate::gpio::{
+    alt, backend::FastIo, interrupt::AsyncInterrupt, sched, soft_pwm, soft_pwm::SoftPwm, Error,
+    Event, FrequencyMeasurement, Function, GpioState, Level, Mode, PullUpDown, Result,
+    ThreadConfig, Trigger,
+};
+#[cfg(feature = "async")]
+use crate::gpio::{stream, EventStream};
 
 // Maximum GPIO pins on the BCM2835. The actual number of pins
 // exposed through the Pi's GPIO header depends on the model.
 pub const MAX: usize = 54;
 
+// Edge rate (edges per second) above which measure_frequency switches to sampling
+const MEASURE_MAX_EDGE_RATE: u64 = 20_000;
+// Minimum number of edges before the edge rate is evaluated
+const MEASURE_MIN_EDGES: u64 = 16;
+
+// Accumulates complete periods from a sequence of edges, measured from one rising
+// edge to the next.
+#[derive(Debug, Default)]
+struct EdgeStats {
+    period_start: Option<i64>,
+    high_ns: Option<i64>,
+    periods: u32,
+    total_ns: i64,
+    total_high_ns: i64,
+}
+
+impl EdgeStats {
+    fn edge(&mut self, level: Level, timestamp_ns: i64) {
+        match level {
+            Level::High => {
+                if let (Some(period_start), Some(high_ns)) = (self.period_start, self.high_ns) {
+                    self.periods += 1;
+                    self.total_ns += timestamp_ns - period_start;
+                    self.total_high_ns += high_ns;
+                }
+
+                self.period_start = Some(timestamp_ns);
+                self.high_ns = None;
+            }
+            Level::Low => {
+                if let (Some(period_start), None) = (self.period_start, self.high_ns) {
+                    self.high_ns = Some(timestamp_ns - period_start);
+                }
+            }
+        }
+    }
+
+    fn measurement(&self, sampled: bool) -> Option<FrequencyMeasurement> {
+        if self.periods == 0 || self.total_ns <= 0 {
+            return None;
+        }
+
+        Some(FrequencyMeasurement::new(
+            f64::from(self.periods) * 1_000_000_000.0 / self.total_ns as f64,
+            self.total_high_ns as f64 / self.total_ns as f64,
+            self.periods,
+            sampled,
+        ))
+    }
+}
+
 /// Unconfigured GPIO pin.
 #[derive(Debug)]
 pub struct Pin {
     pub(crate) pin: u8,
-    gpio_state: Arc<GpioState>,
+    pub(crate) gpio_state: Arc<GpioState>,
+    // Set to false when ownership of the pin is transferred to a new Pin instance
+    release_on_drop: bool,
 }
 
 impl Pin {
     #[inline]
     pub(crate) fn new(pin: u8, gpio_state: Arc<GpioState>) -> Pin {
-        Pin { pin, gpio_state }
+        gpio_state.record_pin(pin);
+
+        Pin {
+            pin,
+            gpio_state,
+            release_on_drop: true,
+        }
+    }
+
+    // Returns a new Pin instance that takes over ownership of the pin. The
+    // current instance no longer marks the pin as available when it's dropped.
+    pub(crate) fn transfer(&mut self) -> Pin {
+        self.release_on_drop = false;
+
+        Pin {
+            pin: self.pin,
+            gpio_state: self.gpio_state.clone(),
+            release_on_drop: true,
+        }
+    }
+
+    // Returns false if ownership of the pin has been transferred to a new
+    // Pin instance.
+    #[inline]
+    pub(crate) fn is_owner(&self) -> bool {
+        self.release_on_drop
     }
 
     /// Consumes the `Pin`, returns an [`InputPin`], sets its mode to [`Mode::Input`],
@@ -86,7 +173,35 @@ impl Pin {
     /// [`Mode::Output`]: enum.Mode.html#variant.Output
     #[inline]
     pub fn into_output(self) -> OutputPin {
-        OutputPin::new(self)
+        OutputPin::new(self, None)
+    }
+
+    /// Consumes the pin, returns an [`OutputPin`], sets its logic level to [`Level::Low`]
+    /// and then sets its mode to [`Mode::Output`].
+    ///
+    /// The logic level is set before the mode is changed, which prevents the pin from
+    /// briefly outputting whatever level was previously latched.
+    ///
+    /// [`OutputPin`]: struct.OutputPin.html
+    /// [`Level::Low`]: enum.Level.html#variant.Low
+    /// [`Mode::Output`]: enum.Mode.html#variant.Output
+    #[inline]
+    pub fn into_output_low(self) -> OutputPin {
+        OutputPin::new(self, Some(Level::Low))
+    }
+
+    /// Consumes the pin, returns an [`OutputPin`], sets its logic level to [`Level::High`]
+    /// and then sets its mode to [`Mode::Output`].
+    ///
+    /// The logic level is set before the mode is changed, which prevents the pin from
+    /// briefly outputting whatever level was previously latched.
+    ///
+    /// [`OutputPin`]: struct.OutputPin.html
+    /// [`Level::High`]: enum.Level.html#variant.High
+    /// [`Mode::Output`]: enum.Mode.html#variant.Output
+    #[inline]
+    pub fn into_output_high(self) -> OutputPin {
+        OutputPin::new(self, Some(Level::High))
     }
 
     /// Consumes the pin, returns an [`AltPin`] and sets its mode to the given mode.
@@ -98,6 +213,46 @@ impl Pin {
         AltPin::new(self, mode)
     }
 
+    /// Consumes the pin, returns an [`AltPin`] and sets its mode to the alternate mode
+    /// that selects the specified function.
+    ///
+    /// Returns [`Error::FunctionNotAvailable`] if `function` can't be selected on this
+    /// pin. The available functions are listed by [`alt_functions`].
+    ///
+    /// [`AltPin`]: struct.AltPin.html
+    /// [`Error::FunctionNotAvailable`]: enum.Error.html#variant.FunctionNotAvailable
+    /// [`alt_functions`]: #method.alt_functions
+    pub fn into_alt_function(self, function: Function) -> Result<AltPin> {
+        match self
+            .alt_functions()
+            .into_iter()
+            .find(|&(_, alt_function)| alt_function == function)
+        {
+            Some((mode, _)) => Ok(AltPin::new(self, mode)),
+            None => Err(Error::FunctionNotAvailable(self.pin, function)),
+        }
+    }
+
+    /// Consumes the pin, returns an [`IoPin`] and sets its mode to the given mode.
+    ///
+    /// [`IoPin`]: struct.IoPin.html
+    /// [`Mode`]: enum.Mode.html
+    #[inline]
+    pub fn into_io(self, mode: Mode) -> IoPin {
+        IoPin::new(self, mode)
+    }
+
+    /// Returns the alternate functions that are available on this pin, paired with
+    /// the alternate mode that selects them on the detected SoC.
+    ///
+    /// See [`gpio::alt_functions`] for details on which SoCs and pins are covered.
+    ///
+    /// [`gpio::alt_functions`]: fn.alt_functions.html
+    #[inline]
+    pub fn alt_functions(&self) -> Vec<(Mode, Function)> {
+        alt::alt_functions(self.gpio_state.gpio_mem.soc(), self.pin)
+    }
+
     /// Returns the GPIO pin number.
     ///
     /// Pins are addressed by their BCM numbers, rather than their physical location.
@@ -121,6 +276,16 @@ impl Pin {
     #[inline]
     pub(crate) fn set_pullupdown(&self, pud: PullUpDown) {
         self.gpio_state.gpio_mem.set_pullupdown(self.pin, pud);
+        self.gpio_state.record_pullupdown(self.pin, pud);
+    }
+
+    /// Returns the configured state of the built-in GPIO pull-up/pull-down resistors.
+    ///
+    /// Reading back the pull-up/pull-down state is only supported on the BCM2711
+    /// (Raspberry Pi 4). On older models, `pud` returns `None`.
+    #[inline]
+    pub fn pud(&self) -> Option<PullUpDown> {
+        self.gpio_state.gpio_mem.pullupdown(self.pin)
     }
 
     /// Reads the pin's current logic level.
@@ -131,26 +296,41 @@ impl Pin {
 
     #[inline]
     pub(crate) fn set_low(&mut self) {
-        self.gpio_state.gpio_mem.set_low(self.pin);
+        self.gpio_state.write_level(self.pin, Level::Low);
     }
 
     #[inline]
     pub(crate) fn set_high(&mut self) {
-        self.gpio_state.gpio_mem.set_high(self.pin);
+        self.gpio_state.write_level(self.pin, Level::High);
     }
 
     #[inline]
     pub(crate) fn write(&mut self, level: Level) {
-        match level {
-            Level::Low => self.set_low(),
-            Level::High => self.set_high(),
+        self.gpio_state.write_level(self.pin, level);
+    }
+
+    #[inline]
+    pub(crate) fn toggle(&mut self) {
+        // None of the backends can flip a pin's output level in a single operation,
+        // so the level lock is held between reading the current level and setting
+        // the opposite level.
+        let _level_lock = self.gpio_state.lock_level(self.pin);
+
+        match self.read() {
+            Level::Low => self.gpio_state.gpio_mem.set_high(self.pin),
+            Level::High => self.gpio_state.gpio_mem.set_low(self.pin),
         };
     }
 }
 
 impl Drop for Pin {
     fn drop(&mut self) {
+        if !self.release_on_drop {
+            return;
+        }
+
         // Release taken pin
+        self.gpio_state.release_pin(self.pin);
         self.gpio_state.pins_taken[self.pin as usize].store(false, Ordering::SeqCst);
     }
 }
@@ -164,6 +344,15 @@ macro_rules! impl_pin {
         pub fn pin(&self) -> u8 {
             self.pin.pin
         }
+
+        /// Returns the configured state of the built-in GPIO pull-up/pull-down resistors.
+        ///
+        /// Reading back the pull-up/pull-down state is only supported on the BCM2711
+        /// (Raspberry Pi 4). On older models, `pud` returns `None`.
+        #[inline]
+        pub fn pud(&self) -> Option<PullUpDown> {
+            self.pin.pud()
+        }
     }
 }
 
@@ -216,9 +405,178 @@ macro_rules! impl_output {
         pub fn write(&mut self, level: Level) {
             self.pin.write(level)
         }
+
+        /// Toggles the pin's logic level between [`Level::Low`] and [`Level::High`].
+        ///
+        /// The new logic level is based on the pin's current level as read from the
+        /// GPIO peripheral, rather than a cached value, so `toggle` also works
+        /// correctly after the level has been changed elsewhere.
+        ///
+        /// `toggle` is atomic with respect to all other level changes made through
+        /// RPPAL, including software-based PWM, so a concurrent change can't get
+        /// lost in between reading and setting the level. The only exceptions are
+        /// `set_low_fast` and `set_high_fast`, which bypass the lock for speed.
+        ///
+        /// [`Level::Low`]: enum.Level.html
+        /// [`Level::High`]: enum.Level.html
+        #[inline]
+        pub fn toggle(&mut self) {
+            self.pin.toggle()
+        }
     }
 }
 
+macro_rules! impl_pullupdown {
+    () => {
+        /// Configures the built-in GPIO pull-up/pull-down resistors.
+        ///
+        /// The resistors are disabled when the pin goes out of scope if [`reset_on_drop`]
+        /// is set to `true` (default).
+        ///
+        /// [`reset_on_drop`]: #method.set_reset_on_drop
+        pub fn set_pullupdown(&mut self, pud: PullUpDown) {
+            self.pin.set_pullupdown(pud);
+            self.pud_mode = pud;
+        }
+    };
+}
+
+macro_rules! impl_into {
+    () => {
+        /// Consumes the pin, and returns the underlying [`Pin`] without releasing it.
+        ///
+        /// The pin is reset the same way as when it goes out of scope, which includes
+        /// resetting its mode and disabling the built-in pull-up/pull-down resistors if
+        /// [`reset_on_drop`] is set to `true` (default). The pin remains taken, so it
+        /// can be reconfigured without calling [`Gpio::get`] again.
+        ///
+        /// [`Pin`]: struct.Pin.html
+        /// [`reset_on_drop`]: #method.set_reset_on_drop
+        /// [`Gpio::get`]: struct.Gpio.html#method.get
+        pub fn into_pin(mut self) -> Pin {
+            self.pin.transfer()
+        }
+
+        /// Reconfigures the pin as an [`InputPin`], and disables the pin's built-in
+        /// pull-up/pull-down resistors.
+        ///
+        /// Equivalent to calling [`into_pin`] followed by [`Pin::into_input`].
+        ///
+        /// [`InputPin`]: struct.InputPin.html
+        /// [`into_pin`]: #method.into_pin
+        /// [`Pin::into_input`]: struct.Pin.html#method.into_input
+        #[inline]
+        pub fn into_input(self) -> InputPin {
+            self.into_pin().into_input()
+        }
+
+        /// Reconfigures the pin as an [`InputPin`], and enables the pin's built-in
+        /// pull-down resistor.
+        ///
+        /// Equivalent to calling [`into_pin`] followed by [`Pin::into_input_pulldown`].
+        ///
+        /// [`InputPin`]: struct.InputPin.html
+        /// [`into_pin`]: #method.into_pin
+        /// [`Pin::into_input_pulldown`]: struct.Pin.html#method.into_input_pulldown
+        #[inline]
+        pub fn into_input_pulldown(self) -> InputPin {
+            self.into_pin().into_input_pulldown()
+        }
+
+        /// Reconfigures the pin as an [`InputPin`], and enables the pin's built-in
+        /// pull-up resistor.
+        ///
+        /// Equivalent to calling [`into_pin`] followed by [`Pin::into_input_pullup`].
+        ///
+        /// [`InputPin`]: struct.InputPin.html
+        /// [`into_pin`]: #method.into_pin
+        /// [`Pin::into_input_pullup`]: struct.Pin.html#method.into_input_pullup
+        #[inline]
+        pub fn into_input_pullup(self) -> InputPin {
+            self.into_pin().into_input_pullup()
+        }
+
+        /// Reconfigures the pin as an [`OutputPin`].
+        ///
+        /// Equivalent to calling [`into_pin`] followed by [`Pin::into_output`].
+        ///
+        /// [`OutputPin`]: struct.OutputPin.html
+        /// [`into_pin`]: #method.into_pin
+        /// [`Pin::into_output`]: struct.Pin.html#method.into_output
+        #[inline]
+        pub fn into_output(self) -> OutputPin {
+            self.into_pin().into_output()
+        }
+
+        /// Reconfigures the pin as an [`OutputPin`], with its logic level set to
+        /// [`Level::Low`].
+        ///
+        /// Equivalent to calling [`into_pin`] followed by [`Pin::into_output_low`].
+        ///
+        /// [`OutputPin`]: struct.OutputPin.html
+        /// [`Level::Low`]: enum.Level.html#variant.Low
+        /// [`into_pin`]: #method.into_pin
+        /// [`Pin::into_output_low`]: struct.Pin.html#method.into_output_low
+        #[inline]
+        pub fn into_output_low(self) -> OutputPin {
+            self.into_pin().into_output_low()
+        }
+
+        /// Reconfigures the pin as an [`OutputPin`], with its logic level set to
+        /// [`Level::High`].
+        ///
+        /// Equivalent to calling [`into_pin`] followed by [`Pin::into_output_high`].
+        ///
+        /// [`OutputPin`]: struct.OutputPin.html
+        /// [`Level::High`]: enum.Level.html#variant.High
+        /// [`into_pin`]: #method.into_pin
+        /// [`Pin::into_output_high`]: struct.Pin.html#method.into_output_high
+        #[inline]
+        pub fn into_output_high(self) -> OutputPin {
+            self.into_pin().into_output_high()
+        }
+
+        /// Reconfigures the pin as an [`AltPin`] with the given mode.
+        ///
+        /// Equivalent to calling [`into_pin`] followed by [`Pin::into_alt`].
+        ///
+        /// [`AltPin`]: struct.AltPin.html
+        /// [`into_pin`]: #method.into_pin
+        /// [`Pin::into_alt`]: struct.Pin.html#method.into_alt
+        #[inline]
+        pub fn into_alt(self, mode: Mode) -> AltPin {
+            self.into_pin().into_alt(mode)
+        }
+
+        /// Reconfigures the pin as an [`AltPin`] with the alternate mode that
+        /// selects the specified function.
+        ///
+        /// Equivalent to calling [`into_pin`] followed by [`Pin::into_alt_function`].
+        /// If `function` isn't available, the pin is still reset, and can be
+        /// retrieved again through [`Gpio::get`].
+        ///
+        /// [`AltPin`]: struct.AltPin.html
+        /// [`into_pin`]: #method.into_pin
+        /// [`Pin::into_alt_function`]: struct.Pin.html#method.into_alt_function
+        /// [`Gpio::get`]: struct.Gpio.html#method.get
+        pub fn into_alt_function(self, function: Function) -> Result<AltPin> {
+            self.into_pin().into_alt_function(function)
+        }
+
+        /// Reconfigures the pin as an [`IoPin`] with the given mode.
+        ///
+        /// Equivalent to calling [`into_pin`] followed by [`Pin::into_io`].
+        ///
+        /// [`IoPin`]: struct.IoPin.html
+        /// [`into_pin`]: #method.into_pin
+        /// [`Pin::into_io`]: struct.Pin.html#method.into_io
+        #[inline]
+        pub fn into_io(self, mode: Mode) -> IoPin {
+            self.into_pin().into_io(mode)
+        }
+    };
+}
+
 macro_rules! impl_reset_on_drop {
     () => {
         /// Returns the value of `reset_on_drop`.
@@ -228,15 +586,19 @@ macro_rules! impl_reset_on_drop {
 
         /// When enabled, resets the pin's mode to its original state and disables the
         /// built-in pull-up/pull-down resistors, when the pin goes out of scope.
-        /// By default, this is set to `true`.
+        /// By default, this is set to `true`, unless a different default was
+        /// configured through [`GpioBuilder::reset_on_drop`].
         ///
         /// ## Note
         ///
         /// Drop methods aren't called when a program is abnormally terminated, for
         /// instance when a user presses <kbd>Ctrl + C</kbd>, and the `SIGINT` signal
-        /// isn't caught. You catch those using crates such as [`simple_signal`].
+        /// isn't caught. You catch those using crates such as [`simple_signal`], and
+        /// restore all pins at once through [`Gpio::cleanup`].
         ///
+        /// [`GpioBuilder::reset_on_drop`]: struct.GpioBuilder.html#method.reset_on_drop
         /// [`simple_signal`]: https://crates.io/crates/simple-signal
+        /// [`Gpio::cleanup`]: struct.Gpio.html#method.cleanup
         pub fn set_reset_on_drop(&mut self, reset_on_drop: bool) {
             self.reset_on_drop = reset_on_drop;
         }
@@ -271,6 +633,9 @@ pub struct InputPin {
     pub(crate) pin: Pin,
     prev_mode: Option<Mode>,
     async_interrupt: Option<AsyncInterrupt>,
+    counter: Arc<AtomicU64>,
+    edge_detect: Trigger,
+    thread_config: Option<ThreadConfig>,
     reset_on_drop: bool,
     pud_mode: PullUpDown,
 }
@@ -288,17 +653,23 @@ impl InputPin {
 
         pin.set_pullupdown(pud_mode);
 
+        let reset_on_drop = pin.gpio_state.default_reset_on_drop();
+
         InputPin {
             pin,
             prev_mode,
             async_interrupt: None,
-            reset_on_drop: true,
+            counter: Arc::new(AtomicU64::new(0)),
+            edge_detect: Trigger::Disabled,
+            thread_config: None,
+            reset_on_drop,
             pud_mode,
         }
     }
 
     impl_pin!();
     impl_input!();
+    impl_pullupdown!();
 
     /// Configures a synchronous interrupt trigger.
     ///
@@ -310,10 +681,36 @@ impl InputPin {
     /// [`poll_interrupt`]: #method.poll_interrupt
     /// [`Gpio::poll_interrupts`]: struct.Gpio#method.poll_interrupts
     pub fn set_interrupt(&mut self, trigger: Trigger) -> Result<()> {
+        self.configure_interrupt(trigger, None)
+    }
+
+    /// Configures a synchronous interrupt trigger with a debounce filter.
+    ///
+    /// `set_interrupt_debounced` works similarly to [`set_interrupt`], but
+    /// ignores any trigger events that occur within `debounce` of the previously
+    /// accepted event, which suppresses spurious edges caused by contact bounce
+    /// on mechanical switches and buttons. Events are compared based on their
+    /// kernel timestamps, rather than the time they're processed.
+    ///
+    /// Typical debounce values for push buttons range from 5 ms to 50 ms.
+    ///
+    /// Any previously configured (a)synchronous interrupt triggers will be cleared.
+    ///
+    /// [`set_interrupt`]: #method.set_interrupt
+    pub fn set_interrupt_debounced(&mut self, trigger: Trigger, debounce: Duration) -> Result<()> {
+        self.configure_interrupt(trigger, Some(debounce))
+    }
+
+    fn configure_interrupt(&mut self, trigger: Trigger, debounce: Option<Duration>) -> Result<()> {
         self.clear_async_interrupt()?;
+        self.pin.gpio_state.cdev_fd(self.pin.pin)?;
 
         // Each pin can only be configured for a single trigger type
-        (*self.pin.gpio_state.sync_interrupts.lock().unwrap()).set_interrupt(self.pin(), trigger)
+        (*self.pin.gpio_state.sync_interrupts.lock().unwrap()).set_interrupt(
+            self.pin(),
+            trigger,
+            debounce,
+        )
     }
 
     /// Removes a previously configured synchronous interrupt trigger.
@@ -339,6 +736,10 @@ impl InputPin {
     /// for interrupt trigger events, after which an `Ok(None))` is returned.
     /// `timeout` can be set to `None` to wait indefinitely.
     ///
+    /// When an interrupt event is triggered, `poll_interrupt` returns `Ok(Some(`[`Event`]`))`
+    /// containing the pin's logic level and the kernel timestamp of the event.
+    ///
+    /// [`Event`]: struct.Event.html
     /// [`set_interrupt`]: #method.set_interrupt
     /// [`Gpio::poll_interrupts`]: struct.Gpio#method.poll_interrupts
     /// [`set_async_interrupt`]: #method.set_async_interrupt
@@ -346,7 +747,7 @@ impl InputPin {
         &mut self,
         reset: bool,
         timeout: Option<Duration>,
-    ) -> Result<Option<Level>> {
+    ) -> Result<Option<Event>> {
         let opt =
             (*self.pin.gpio_state.sync_interrupts.lock().unwrap()).poll(&[self], reset, timeout)?;
 
@@ -357,36 +758,599 @@ impl InputPin {
         }
     }
 
+    /// Sets the maximum number of synchronous interrupt trigger events that are
+    /// cached for this pin.
+    ///
+    /// Events that are read from the kernel while polling, but not returned yet,
+    /// are stored in a queue until the next call to [`poll_interrupt`] or
+    /// [`Gpio::poll_interrupts`]. When the queue is full, any new events are
+    /// discarded, and reported through [`Event::missed_events`] on the next event
+    /// that's queued. `capacity` is set to at least 1. Defaults to 16.
+    ///
+    /// [`poll_interrupt`]: #method.poll_interrupt
+    /// [`Gpio::poll_interrupts`]: struct.Gpio.html#method.poll_interrupts
+    /// [`Event::missed_events`]: struct.Event.html#method.missed_events
+    pub fn set_interrupt_queue_capacity(&mut self, capacity: usize) {
+        (*self.pin.gpio_state.sync_interrupts.lock().unwrap())
+            .set_queue_capacity(self.pin(), capacity);
+    }
+
+    /// Returns the maximum number of synchronous interrupt trigger events that are
+    /// cached for this pin.
+    pub fn interrupt_queue_capacity(&self) -> usize {
+        (*self.pin.gpio_state.sync_interrupts.lock().unwrap()).queue_capacity(self.pin())
+    }
+
+    /// Checks for an interrupt trigger event without blocking.
+    ///
+    /// `try_poll_interrupt` behaves like [`poll_interrupt`] with `reset` set to
+    /// `false` and a zero timeout. It returns `Ok(None)` if no events are pending.
+    ///
+    /// [`poll_interrupt`]: #method.poll_interrupt
+    pub fn try_poll_interrupt(&mut self) -> Result<Option<Event>> {
+        self.poll_interrupt(false, Some(Duration::from_millis(0)))
+    }
+
+    /// Returns the file descriptor of the pin's synchronous interrupt trigger.
+    ///
+    /// The file descriptor becomes readable when an interrupt trigger event is
+    /// pending, which allows it to be registered with an external event loop, such
+    /// as `mio`, `calloop` or `tokio`'s `AsyncFd`. Once it's readable, retrieve the
+    /// event with [`try_poll_interrupt`]. Don't read from the file descriptor directly.
+    ///
+    /// Returns `None` if no synchronous interrupt trigger is configured. The file
+    /// descriptor changes when the trigger is reconfigured, or when pending events
+    /// are reset by calling [`poll_interrupt`] with `reset` set to `true`, and is
+    /// closed when the trigger is cleared.
+    ///
+    /// [`try_poll_interrupt`]: #method.try_poll_interrupt
+    /// [`poll_interrupt`]: #method.poll_interrupt
+    pub fn interrupt_fd(&self) -> Option<RawFd> {
+        (*self.pin.gpio_state.sync_interrupts.lock().unwrap()).interrupt_fd(self.pin())
+    }
+
+    /// Consumes the `InputPin`, and returns an [`EventStream`] that yields an [`Event`]
+    /// for every interrupt trigger event.
+    ///
+    /// The stream waits for events through tokio's `AsyncFd`, rather than on a
+    /// separate thread, and must be created from within a tokio runtime with IO
+    /// enabled. Any previously configured (a)synchronous interrupt triggers will be
+    /// cleared. Use [`EventStream::into_pin`] to get the pin back.
+    ///
+    /// `into_event_stream` is only available when the `async` feature is enabled.
+    ///
+    /// [`EventStream`]: struct.EventStream.html
+    /// [`Event`]: struct.Event.html
+    /// [`EventStream::into_pin`]: struct.EventStream.html#method.into_pin
+    #[cfg(feature = "async")]
+    pub fn into_event_stream(self, trigger: Trigger) -> Result<EventStream> {
+        EventStream::new(self, trigger)
+    }
+
+    /// Waits asynchronously until the specified interrupt trigger event occurs.
+    ///
+    /// `wait_for_edge_async` is the asynchronous counterpart of [`wait_for_edge`],
+    /// without a timeout. Use the timer provided by your runtime to cancel the
+    /// returned future if needed. The interrupt trigger is configured when the future
+    /// is first polled, and removed when it completes or is dropped. Any previously
+    /// configured (a)synchronous interrupt triggers will be cleared.
+    ///
+    /// `wait_for_edge_async` is only available when the `async` feature is enabled,
+    /// and must be awaited from within a tokio runtime with IO enabled.
+    ///
+    /// [`wait_for_edge`]: #method.wait_for_edge
+    #[cfg(feature = "async")]
+    pub async fn wait_for_edge_async(&mut self, trigger: Trigger) -> Result<Event> {
+        let mut interrupt = stream::async_interrupt(self, trigger)?;
+
+        std::future::poll_fn(|cx| stream::poll_event(&mut interrupt, cx)).await
+    }
+
+    /// Blocks until the pin's logic level is high, or a timeout occurs.
+    ///
+    /// Returns `Ok(true)` immediately if the pin is already high, or as soon
+    /// as a rising edge is detected. Returns `Ok(false)` if a timeout occurred.
+    /// `timeout` can be set to `None` to wait indefinitely.
+    ///
+    /// `wait_for_high` temporarily configures a synchronous interrupt trigger,
+    /// which is removed before it returns. Any previously configured
+    /// (a)synchronous interrupt triggers will be cleared.
+    pub fn wait_for_high(&mut self, timeout: Option<Duration>) -> Result<bool> {
+        self.wait_for_level(Level::High, timeout)
+    }
+
+    /// Blocks until the pin's logic level is low, or a timeout occurs.
+    ///
+    /// Returns `Ok(true)` immediately if the pin is already low, or as soon
+    /// as a falling edge is detected. Returns `Ok(false)` if a timeout occurred.
+    /// `timeout` can be set to `None` to wait indefinitely.
+    ///
+    /// `wait_for_low` temporarily configures a synchronous interrupt trigger,
+    /// which is removed before it returns. Any previously configured
+    /// (a)synchronous interrupt triggers will be cleared.
+    pub fn wait_for_low(&mut self, timeout: Option<Duration>) -> Result<bool> {
+        self.wait_for_level(Level::Low, timeout)
+    }
+
+    /// Blocks until the specified trigger condition occurs, or a timeout occurs.
+    ///
+    /// Unlike [`wait_for_high`] and [`wait_for_low`], `wait_for_edge` always waits
+    /// for a new edge, regardless of the pin's current logic level. Returns
+    /// `Ok(Some(`[`Event`]`))` when the trigger condition occurs, or `Ok(None)`
+    /// if a timeout occurred. `timeout` can be set to `None` to wait indefinitely.
+    ///
+    /// `wait_for_edge` temporarily configures a synchronous interrupt trigger,
+    /// which is removed before it returns. Any previously configured
+    /// (a)synchronous interrupt triggers will be cleared.
+    ///
+    /// [`wait_for_high`]: #method.wait_for_high
+    /// [`wait_for_low`]: #method.wait_for_low
+    /// [`Event`]: struct.Event.html
+    pub fn wait_for_edge(
+        &mut self,
+        trigger: Trigger,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Event>> {
+        // Start with a new event request, so we don't return any stale events
+        self.clear_interrupt()?;
+        self.set_interrupt(trigger)?;
+
+        let result = self.poll_interrupt(false, timeout);
+        self.clear_interrupt()?;
+
+        result
+    }
+
+    /// Measures the duration of the next pulse at the specified logic level.
+    ///
+    /// `measure_pulse` waits for the pin to change to `level`, and then waits for
+    /// it to change back. The pulse width is calculated based on the kernel timestamps
+    /// of both edges, so it isn't affected by any scheduling delays in userspace.
+    /// If the pin is already at `level` when `measure_pulse` is called, the current
+    /// pulse is ignored, and the next complete pulse is measured instead.
+    ///
+    /// The `timeout` duration applies to the entire measurement, after which an
+    /// `Ok(None)` is returned. `timeout` can be set to `None` to wait indefinitely.
+    ///
+    /// `measure_pulse` temporarily configures a synchronous interrupt trigger,
+    /// which is removed before it returns. Any previously configured
+    /// (a)synchronous interrupt triggers will be cleared.
+    pub fn measure_pulse(
+        &mut self,
+        level: Level,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Duration>> {
+        self.clear_interrupt()?;
+        self.set_interrupt(Trigger::Both)?;
+
+        let result = self.measure_pulse_events(level, timeout);
+        self.clear_interrupt()?;
+
+        result
+    }
+
+    // Measures a pulse using an already configured Trigger::Both interrupt, which lets
+    // callers arm the interrupt before triggering the pulse
+    pub(crate) fn measure_pulse_events(
+        &mut self,
+        level: Level,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Duration>> {
+        let start = Instant::now();
+        let mut pulse_start: Option<Duration> = None;
+
+        loop {
+            let remaining = match timeout {
+                Some(t) => match t.checked_sub(start.elapsed()) {
+                    Some(remaining) => Some(remaining),
+                    None => return Ok(None),
+                },
+                None => None,
+            };
+
+            let event = match self.poll_interrupt(false, remaining)? {
+                Some(event) => event,
+                None => return Ok(None),
+            };
+
+            if event.level() == level {
+                // If we somehow missed the end of a previous pulse, start over
+                pulse_start = Some(event.timestamp());
+            } else if let Some(pulse_start) = pulse_start {
+                return Ok(Some(
+                    event
+                        .timestamp()
+                        .checked_sub(pulse_start)
+                        .unwrap_or_default(),
+                ));
+            }
+        }
+    }
+
+    /// Measures the frequency and duty cycle of a periodic signal during the
+    /// specified sample window.
+    ///
+    /// The measurement is based on the kernel timestamps of the rising and falling
+    /// edges, which makes it suitable for tachometers, fan speed sensors and other
+    /// low to medium frequency signals. Only complete periods, measured from one
+    /// rising edge to the next, are included.
+    ///
+    /// If the edge rate exceeds what can reliably be processed through interrupt
+    /// trigger events, or events are dropped, `measure_frequency` falls back to
+    /// sampling the pin's logic level in a busy loop for the remainder of the sample
+    /// window. Sampled measurements are less accurate, and keep a CPU core busy
+    /// until the sample window ends. [`FrequencyMeasurement::is_sampled`] indicates
+    /// which method was used.
+    ///
+    /// Returns `Ok(None)` if no complete period was detected within the sample window.
+    ///
+    /// `measure_frequency` temporarily configures a synchronous interrupt trigger,
+    /// which is removed before it returns. Any previously configured
+    /// (a)synchronous interrupt triggers will be cleared.
+    ///
+    /// [`FrequencyMeasurement::is_sampled`]: struct.FrequencyMeasurement.html#method.is_sampled
+    pub fn measure_frequency(
+        &mut self,
+        sample_window: Duration,
+    ) -> Result<Option<FrequencyMeasurement>> {
+        self.clear_interrupt()?;
+        self.set_interrupt(Trigger::Both)?;
+
+        let result = self.measure_frequency_events(sample_window);
+        self.clear_interrupt()?;
+
+        let start = match result? {
+            Ok(stats) => return Ok(stats.measurement(false)),
+            Err(start) => start,
+        };
+
+        // Fall back to sampling for the remainder of the sample window
+        let mut stats = EdgeStats::default();
+        let end_ns = start.saturating_add(delay::duration_to_ns(sample_window));
+        let mut level = self.read();
+
+        loop {
+            let now_ns = delay::get_time_ns();
+            if now_ns >= end_ns {
+                break;
+            }
+
+            let current = self.read();
+            if current != level {
+                stats.edge(current, now_ns);
+                level = current;
+            }
+        }
+
+        Ok(stats.measurement(true))
+    }
+
+    // Collects edge events until the sample window ends. Returns Err with the start
+    // time if the edge rate is too high for the event path.
+    fn measure_frequency_events(
+        &mut self,
+        sample_window: Duration,
+    ) -> Result<result::Result<EdgeStats, i64>> {
+        let start_ns = delay::get_time_ns();
+        let end_ns = start_ns.saturating_add(delay::duration_to_ns(sample_window));
+        let mut stats = EdgeStats::default();
+        let mut last_level: Option<Level> = None;
+        let mut edges: u64 = 0;
+
+        loop {
+            let now_ns = delay::get_time_ns();
+            if now_ns >= end_ns {
+                return Ok(Ok(stats));
+            }
+
+            let remaining = Duration::from_nanos((end_ns - now_ns) as u64);
+            let event = match self.poll_interrupt(false, Some(remaining))? {
+                Some(event) => event,
+                None => return Ok(Ok(stats)),
+            };
+
+            edges += 1;
+
+            // Two consecutive edges in the same direction indicate the kernel dropped
+            // events. Also bail out if the edge rate approaches what we can handle.
+            let elapsed_ns = (delay::get_time_ns() - start_ns).max(1);
+            if event.missed_events() > 0
+                || last_level == Some(event.level())
+                || (edges > MEASURE_MIN_EDGES
+                    && edges * 1_000_000_000 / elapsed_ns as u64 > MEASURE_MAX_EDGE_RATE)
+            {
+                return Ok(Err(start_ns));
+            }
+
+            last_level = Some(event.level());
+            stats.edge(event.level(), delay::duration_to_ns(event.timestamp()));
+        }
+    }
+
+    fn wait_for_level(&mut self, level: Level, timeout: Option<Duration>) -> Result<bool> {
+        let trigger = match level {
+            Level::High => Trigger::RisingEdge,
+            Level::Low => Trigger::FallingEdge,
+        };
+
+        self.clear_interrupt()?;
+        self.set_interrupt(trigger)?;
+
+        // Check the current level after the interrupt has been configured, so
+        // we can't miss an edge that occurs in between.
+        let result = if self.read() == level {
+            Ok(true)
+        } else {
+            self.poll_interrupt(false, timeout)
+                .map(|event| event.is_some())
+        };
+
+        self.clear_interrupt()?;
+
+        result
+    }
+
     /// Configures an asynchronous interrupt trigger, which will execute the callback on a
     /// separate thread when the interrupt is triggered.
     ///
-    /// The callback closure or function pointer is called with a single [`Level`] argument.
+    /// The callback closure or function pointer is called with a single [`Event`] argument,
+    /// which contains the pin's logic level and the kernel timestamp of the event.
     ///
     /// Any previously configured (a)synchronous interrupt triggers will be cleared.
     ///
-    /// The interrupt thread will continue to wait for a trigger and execute the callback even
-    /// after `InputPin` is dropped. You must manually call [`clear_async_interrupt`] to
-    /// remove the trigger before `InputPin` goes out of scope.
+    /// The interrupt thread is stopped when `InputPin` goes out of scope, or when
+    /// [`clear_async_interrupt`] is called. If the callback needs access to the pin,
+    /// use [`set_async_interrupt_owned`] instead.
+    ///
+    /// If the callback panics, the panic is caught and reported through
+    /// [`Gpio::take_error`], and the interrupt thread keeps handling new events.
     ///
     /// [`clear_async_interrupt`]: #method.clear_async_interrupt
-    /// [`Level`]: enum.Level.html
+    /// [`set_async_interrupt_owned`]: #method.set_async_interrupt_owned
+    /// [`Gpio::take_error`]: struct.Gpio.html#method.take_error
+    /// [`Event`]: struct.Event.html
     pub fn set_async_interrupt<C>(&mut self, trigger: Trigger, callback: C) -> Result<()>
     where
-        C: FnMut(Level) + Send + 'static,
+        C: FnMut(Event) + Send + 'static,
+    {
+        self.configure_async_interrupt(trigger, None, callback)
+    }
+
+    /// Configures an asynchronous interrupt trigger with a debounce filter, which will
+    /// execute the callback on a separate thread when the interrupt is triggered.
+    ///
+    /// `set_async_interrupt_debounced` works similarly to [`set_async_interrupt`], but
+    /// ignores any trigger events that occur within `debounce` of the previously
+    /// accepted event. The filter runs on the interrupt polling thread, so the
+    /// callback is only called for accepted events. More information on debouncing
+    /// can be found at [`set_interrupt_debounced`].
+    ///
+    /// Any previously configured (a)synchronous interrupt triggers will be cleared.
+    ///
+    /// [`set_async_interrupt`]: #method.set_async_interrupt
+    /// [`set_interrupt_debounced`]: #method.set_interrupt_debounced
+    pub fn set_async_interrupt_debounced<C>(
+        &mut self,
+        trigger: Trigger,
+        debounce: Duration,
+        callback: C,
+    ) -> Result<()>
+    where
+        C: FnMut(Event) + Send + 'static,
+    {
+        self.configure_async_interrupt(trigger, Some(debounce), callback)
+    }
+
+    /// Configures an asynchronous interrupt trigger, and moves the `InputPin` into the
+    /// context of the callback, which will be executed on a separate thread when the
+    /// interrupt is triggered.
+    ///
+    /// The callback closure or function pointer is called with a mutable reference
+    /// to the `InputPin` and an [`Event`] argument.
+    ///
+    /// Any previously configured (a)synchronous interrupt triggers will be cleared.
+    ///
+    /// Returns an [`AsyncInterruptHandle`], which ties the interrupt thread to the
+    /// lifetime of the pin. When the handle goes out of scope, the interrupt thread
+    /// is stopped before the pin is dropped. Use [`AsyncInterruptHandle::stop`] to
+    /// stop the interrupt thread and regain ownership of the `InputPin`.
+    ///
+    /// [`AsyncInterruptHandle`]: struct.AsyncInterruptHandle.html
+    /// [`AsyncInterruptHandle::stop`]: struct.AsyncInterruptHandle.html#method.stop
+    /// [`Event`]: struct.Event.html
+    pub fn set_async_interrupt_owned<C>(
+        mut self,
+        trigger: Trigger,
+        mut callback: C,
+    ) -> Result<AsyncInterruptHandle>
+    where
+        C: FnMut(&mut InputPin, Event) + Send + 'static,
+    {
+        self.clear_interrupt()?;
+        self.clear_async_interrupt()?;
+
+        let fd = self.pin.gpio_state.cdev_fd(self.pin.pin)?;
+        let pin = self.pin();
+        let thread_config = self
+            .pin
+            .gpio_state
+            .resolve_thread_config(&self.thread_config);
+        let errors = self.pin.gpio_state.async_errors.clone();
+        let input_pin = Arc::new(Mutex::new(self));
+        let callback_pin = input_pin.clone();
+
+        let interrupt = AsyncInterrupt::new(
+            fd,
+            pin,
+            trigger,
+            None,
+            thread_config,
+            errors,
+            move |event| {
+                let mut input_pin = callback_pin.lock().unwrap_or_else(PoisonError::into_inner);
+
+                callback(&mut input_pin, event);
+            },
+        )?;
+
+        Ok(AsyncInterruptHandle {
+            interrupt,
+            pin,
+            input_pin,
+        })
+    }
+
+    fn configure_async_interrupt<C>(
+        &mut self,
+        trigger: Trigger,
+        debounce: Option<Duration>,
+        callback: C,
+    ) -> Result<()>
+    where
+        C: FnMut(Event) + Send + 'static,
     {
         self.clear_interrupt()?;
         self.clear_async_interrupt()?;
 
         self.async_interrupt = Some(AsyncInterrupt::new(
-            self.pin.gpio_state.cdev.as_raw_fd(),
+            self.pin.gpio_state.cdev_fd(self.pin.pin)?,
             self.pin(),
             trigger,
+            debounce,
+            self.pin
+                .gpio_state
+                .resolve_thread_config(&self.thread_config),
+            self.pin.gpio_state.async_errors.clone(),
             callback,
         )?);
 
         Ok(())
     }
 
+    /// Configures an asynchronous interrupt trigger that counts the number of times
+    /// the trigger condition occurs.
+    ///
+    /// The counter is incremented on the interrupt thread, without calling any user
+    /// code, and can be read at any time with [`count`]. This is useful for flow
+    /// meters, rotary encoders and other sensors where only the total number of
+    /// pulses is relevant. The counter is reset to 0.
+    ///
+    /// The counter uses the pin's asynchronous interrupt trigger. Any previously
+    /// configured (a)synchronous interrupt triggers will be cleared, and configuring
+    /// a new asynchronous interrupt trigger, or calling [`clear_async_interrupt`],
+    /// stops the counter. Events that are dropped by the kernel when its event buffer
+    /// overflows aren't counted.
+    ///
+    /// [`count`]: #method.count
+    /// [`clear_async_interrupt`]: #method.clear_async_interrupt
+    pub fn enable_counter(&mut self, trigger: Trigger) -> Result<()> {
+        self.counter.store(0, Ordering::SeqCst);
+
+        let counter = self.counter.clone();
+        self.configure_async_interrupt(trigger, None, move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        })
+    }
+
+    /// Stops the counter configured with [`enable_counter`].
+    ///
+    /// The current count is retained, and can still be retrieved with [`count`].
+    ///
+    /// [`enable_counter`]: #method.enable_counter
+    /// [`count`]: #method.count
+    pub fn disable_counter(&mut self) -> Result<()> {
+        self.clear_async_interrupt()
+    }
+
+    /// Returns the number of events counted since the counter was enabled or last reset.
+    pub fn count(&self) -> u64 {
+        self.counter.load(Ordering::Relaxed)
+    }
+
+    /// Resets the counter to 0, and returns the count up to that point.
+    ///
+    /// The counter is reset atomically, so no events are lost when `reset_count`
+    /// is called while the counter is running.
+    pub fn reset_count(&mut self) -> u64 {
+        self.counter.swap(0, Ordering::Relaxed)
+    }
+
+    /// Configures the GPIO peripheral's edge detection for the pin.
+    ///
+    /// Once enabled, the hardware latches an event flag whenever an edge matching
+    /// `trigger` occurs, which can be checked and cleared with [`take_edge_flag`].
+    /// Unlike interrupt triggers, this doesn't require a thread to be blocked
+    /// waiting for events, and doesn't involve the kernel, so an edge is never missed
+    /// regardless of how infrequently the flag is checked. Multiple edges that occur
+    /// between two checks are reported as a single event.
+    ///
+    /// Edge detection is sampled synchronously with the system clock, which filters
+    /// out glitches that are only a few clock cycles long. Setting `trigger` to
+    /// [`Trigger::Disabled`] disables edge detection. Edge detection is disabled
+    /// when `InputPin` goes out of scope.
+    ///
+    /// Edge detection uses the same GPREN, GPFEN and GPEDS registers as the kernel's
+    /// GPIO interrupt handling, and requires the [`Backend::GpioMem`] backend. Don't
+    /// combine it with a synchronous or asynchronous interrupt trigger on the same
+    /// pin, as the kernel clears the event flag when it handles the interrupt. While
+    /// the kernel's GPIO driver has the bank interrupts enabled, an event flag that
+    /// isn't cleared keeps re-triggering that bank's interrupt until [`take_edge_flag`]
+    /// is called, which can noticeably increase CPU load. Check the flag regularly,
+    /// and disable edge detection when it's no longer needed.
+    ///
+    /// Returns [`Error::RegistersNotSupported`] if the GPIO backend doesn't
+    /// memory-map the registers.
+    ///
+    /// [`take_edge_flag`]: #method.take_edge_flag
+    /// [`Trigger::Disabled`]: enum.Trigger.html#variant.Disabled
+    /// [`Backend::GpioMem`]: enum.Backend.html#variant.GpioMem
+    /// [`Error::RegistersNotSupported`]: enum.Error.html#variant.RegistersNotSupported
+    pub fn set_edge_detect(&mut self, trigger: Trigger) -> Result<()> {
+        self.pin
+            .gpio_state
+            .gpio_mem
+            .set_edge_detect(self.pin.pin, trigger)?;
+
+        self.edge_detect = trigger;
+
+        Ok(())
+    }
+
+    /// Returns `true` if an edge was detected since edge detection was enabled
+    /// through [`set_edge_detect`], or since the last call to `take_edge_flag`,
+    /// and clears the event flag.
+    ///
+    /// Returns `Ok(false)` if edge detection isn't enabled.
+    ///
+    /// [`set_edge_detect`]: #method.set_edge_detect
+    pub fn take_edge_flag(&mut self) -> Result<bool> {
+        if self.edge_detect == Trigger::Disabled {
+            return Ok(false);
+        }
+
+        self.pin.gpio_state.gpio_mem.take_edge_event(self.pin.pin)
+    }
+
+    /// Sets the scheduling policy and CPU affinity for asynchronous interrupt threads
+    /// started by this pin, overriding the default set through [`Gpio::set_thread_config`].
+    ///
+    /// If `config` is set to `None`, the default configuration is used. The
+    /// configuration is applied to threads that are started after the call. More
+    /// information can be found at [`ThreadConfig`].
+    ///
+    /// [`Gpio::set_thread_config`]: struct.Gpio.html#method.set_thread_config
+    /// [`ThreadConfig`]: struct.ThreadConfig.html
+    pub fn set_thread_config(&mut self, config: Option<ThreadConfig>) {
+        self.thread_config = config;
+    }
+
+    /// Returns the scheduling policy and CPU affinity for threads started by this pin.
+    pub fn thread_config(&self) -> ThreadConfig {
+        self.pin
+            .gpio_state
+            .resolve_thread_config(&self.thread_config)
+    }
+
     /// Removes a previously configured asynchronous interrupt trigger.
     pub fn clear_async_interrupt(&mut self) -> Result<()> {
         if let Some(mut interrupt) = self.async_interrupt.take() {
@@ -396,22 +1360,102 @@ impl InputPin {
         Ok(())
     }
 
+    impl_into!();
     impl_reset_on_drop!();
 }
 
-impl_drop!(InputPin);
+impl Drop for InputPin {
+    /// Stops any active asynchronous interrupt thread and disables edge detection.
+    /// Resets the pin's mode and disables the built-in pull-up/pull-down resistors if
+    /// `reset_on_drop` is set to `true` (default).
+    fn drop(&mut self) {
+        let _ = self.clear_async_interrupt();
+
+        if self.edge_detect != Trigger::Disabled {
+            let _ = self.set_edge_detect(Trigger::Disabled);
+        }
+
+        if !self.reset_on_drop {
+            return;
+        }
+
+        if let Some(prev_mode) = self.prev_mode {
+            self.pin.set_mode(prev_mode);
+        }
+
+        if self.pud_mode != PullUpDown::Off {
+            self.pin.set_pullupdown(PullUpDown::Off);
+        }
+    }
+}
+
+/// Asynchronous interrupt trigger that owns its [`InputPin`].
+///
+/// `AsyncInterruptHandle` is returned by [`InputPin::set_async_interrupt_owned`].
+/// When the handle goes out of scope, the interrupt thread is stopped, after
+/// which the `InputPin` is dropped.
+///
+/// [`InputPin`]: struct.InputPin.html
+/// [`InputPin::set_async_interrupt_owned`]: struct.InputPin.html#method.set_async_interrupt_owned
+#[derive(Debug)]
+pub struct AsyncInterruptHandle {
+    // Declared first, so the interrupt thread is stopped before the pin is dropped
+    interrupt: AsyncInterrupt,
+    pin: u8,
+    input_pin: Arc<Mutex<InputPin>>,
+}
+
+impl AsyncInterruptHandle {
+    /// Returns the GPIO pin number.
+    ///
+    /// Pins are addressed by their BCM numbers, rather than their physical location.
+    #[inline]
+    pub fn pin(&self) -> u8 {
+        self.pin
+    }
+
+    /// Stops the interrupt thread, and returns the `InputPin`.
+    pub fn stop(self) -> Result<InputPin> {
+        let AsyncInterruptHandle {
+            mut interrupt,
+            input_pin,
+            ..
+        } = self;
+
+        interrupt.stop()?;
+
+        // The interrupt thread has exited, so we hold the only remaining reference
+        match Arc::try_unwrap(input_pin) {
+            Ok(input_pin) => Ok(input_pin
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)),
+            Err(_) => unreachable!(),
+        }
+    }
+}
 
 /// GPIO pin configured as output.
 #[derive(Debug)]
 pub struct OutputPin {
-    pin: Pin,
+    pub(crate) pin: Pin,
     prev_mode: Option<Mode>,
     reset_on_drop: bool,
     pud_mode: PullUpDown,
+    soft_pwm: Option<SoftPwm>,
+    pulse_thread: Option<thread::JoinHandle<Result<()>>>,
+    thread_config: Option<ThreadConfig>,
+    fast_io: Option<FastIo>,
 }
 
 impl OutputPin {
-    pub(crate) fn new(mut pin: Pin) -> OutputPin {
+    pub(crate) fn new(mut pin: Pin, level: Option<Level>) -> OutputPin {
+        // Writing to GPSET/GPCLR while the pin is configured as an input
+        // latches the level, which the pin starts driving once we switch
+        // the mode to output.
+        if let Some(level) = level {
+            pin.write(level);
+        }
+
         let prev_mode = pin.mode();
 
         let prev_mode = if prev_mode == Mode::Output {
@@ -421,21 +1465,281 @@ impl OutputPin {
             Some(prev_mode)
         };
 
+        let fast_io = pin.gpio_state.gpio_mem.fast_io(pin.pin);
+
+        let reset_on_drop = pin.gpio_state.default_reset_on_drop();
+
         OutputPin {
             pin,
             prev_mode,
-            reset_on_drop: true,
+            reset_on_drop,
             pud_mode: PullUpDown::Off,
+            soft_pwm: None,
+            pulse_thread: None,
+            thread_config: None,
+            fast_io,
         }
     }
 
     impl_pin!();
     impl_input!();
     impl_output!();
+    impl_pullupdown!();
+
+    /// Sets the pin's logic level to [`Level::Low`] through a direct register write.
+    ///
+    /// `set_low_fast` is intended for bit-banging protocols at multi-MHz rates.
+    /// When the [`Backend::GpioMem`] backend is used, the address of the pin's
+    /// GPCLR register is looked up when `OutputPin` is created, so each call
+    /// compiles down to a single volatile store, without the dynamic dispatch and
+    /// register offset calculation involved in [`set_low`]. Other backends fall back
+    /// to [`set_low`].
+    ///
+    /// [`Level::Low`]: enum.Level.html#variant.Low
+    /// [`Backend::GpioMem`]: enum.Backend.html#variant.GpioMem
+    /// [`set_low`]: #method.set_low
+    #[inline(always)]
+    pub fn set_low_fast(&mut self) {
+        match self.fast_io {
+            Some(ref fast_io) => fast_io.set_low(),
+            None => self.pin.set_low(),
+        }
+    }
+
+    /// Sets the pin's logic level to [`Level::High`] through a direct register write.
+    ///
+    /// More information can be found at [`set_low_fast`].
+    ///
+    /// [`Level::High`]: enum.Level.html#variant.High
+    /// [`set_low_fast`]: #method.set_low_fast
+    #[inline(always)]
+    pub fn set_high_fast(&mut self) {
+        match self.fast_io {
+            Some(ref fast_io) => fast_io.set_high(),
+            None => self.pin.set_high(),
+        }
+    }
+
+    /// Configures a software-based PWM signal.
+    ///
+    /// `period` indicates the time it takes to complete one cycle.
+    ///
+    /// `pulse_width` indicates the amount of time the PWM signal is active during a
+    /// single period. If `pulse_width` is longer than `period`, it's limited to
+    /// the length of `period`.
+    ///
+    /// Software-based PWM is inherently inaccurate on a multi-threaded OS due to
+    /// scheduling/preemption. If an accurate or faster PWM signal is required, use the
+    /// hardware [`Pwm`] peripheral instead. More information can be found [here].
+    ///
+    /// The PWM signal is generated on a separate thread. Calling `set_pwm` while a
+    /// PWM signal is already active updates the period and pulse width at the end
+    /// of the current cycle. Changing the pin's logic level through [`set_low`],
+    /// [`set_high`] or [`write`] while PWM is active results in undefined behavior.
+    ///
+    /// The PWM thread is stopped when [`clear_pwm`] is called, or when `OutputPin`
+    /// goes out of scope.
+    ///
+    /// [`Pwm`]: ../pwm/struct.Pwm.html
+    /// [here]: index.html#software-based-pwm
+    /// [`set_low`]: #method.set_low
+    /// [`set_high`]: #method.set_high
+    /// [`write`]: #method.write
+    /// [`clear_pwm`]: #method.clear_pwm
+    pub fn set_pwm(&mut self, period: Duration, pulse_width: Duration) -> Result<()> {
+        if let Some(ref mut soft_pwm) = self.soft_pwm {
+            soft_pwm.reconfigure(period, pulse_width);
+        } else {
+            self.soft_pwm = Some(SoftPwm::new(
+                self.pin.pin,
+                self.pin.gpio_state.clone(),
+                period,
+                pulse_width,
+                self.pin
+                    .gpio_state
+                    .resolve_thread_config(&self.thread_config),
+            )?);
+        }
+
+        Ok(())
+    }
+
+    /// Configures a software-based PWM signal.
+    ///
+    /// `set_pwm_frequency` is a convenience method that converts `frequency` to a period,
+    /// and calculates the pulse width as a percentage of the period.
+    ///
+    /// `frequency` is specified in herz (Hz).
+    ///
+    /// `duty_cycle` is specified as a floating point value between `0.0` (0%) and `1.0` (100%).
+    ///
+    /// More information on software-based PWM can be found at [`set_pwm`].
+    ///
+    /// [`set_pwm`]: #method.set_pwm
+    pub fn set_pwm_frequency(&mut self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        let (period, pulse_width) = soft_pwm::frequency_to_period(frequency, duty_cycle);
+
+        self.set_pwm(period, pulse_width)
+    }
+
+    /// Stops a previously configured software-based PWM signal.
+    ///
+    /// The pin's logic level is set to [`Level::Low`] after the PWM thread exits.
+    ///
+    /// [`Level::Low`]: enum.Level.html
+    pub fn clear_pwm(&mut self) -> Result<()> {
+        if let Some(mut soft_pwm) = self.soft_pwm.take() {
+            soft_pwm.stop()?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the pin's logic level to high for the specified duration, and then sets it to low.
+    ///
+    /// More information can be found at [`pulse`].
+    ///
+    /// [`pulse`]: #method.pulse
+    pub fn set_high_for(&mut self, duration: Duration) -> Result<()> {
+        self.pulse(Level::High, duration)
+    }
+
+    /// Sets the pin's logic level to low for the specified duration, and then sets it to high.
+    ///
+    /// More information can be found at [`pulse`].
+    ///
+    /// [`pulse`]: #method.pulse
+    pub fn set_low_for(&mut self, duration: Duration) -> Result<()> {
+        self.pulse(Level::Low, duration)
+    }
+
+    /// Sets the pin's logic level to `level` for the specified duration, and then
+    /// sets it to the opposite level.
+    ///
+    /// `pulse` blocks until the pulse has completed. To improve accuracy, `pulse`
+    /// sleeps for most of the duration, and busy-waits for the remaining time.
+    /// Depending on scheduling/preemption, expect the pulse to take a few
+    /// microseconds longer than requested.
+    ///
+    /// Any active software-based PWM signal is stopped, and any pulse started
+    /// by [`pulse_async`] is completed first.
+    ///
+    /// [`pulse_async`]: #method.pulse_async
+    pub fn pulse(&mut self, level: Level, duration: Duration) -> Result<()> {
+        self.clear_pwm()?;
+        self.wait_pulse()?;
+
+        soft_pwm::pulse(&self.pin.gpio_state, self.pin.pin, level, duration);
+
+        Ok(())
+    }
+
+    /// Sets the pin's logic level to `level` for the specified duration on a separate
+    /// thread, and then sets it to the opposite level.
+    ///
+    /// `pulse_async` returns immediately. The pulse is generated on a separate thread,
+    /// which is assigned real-time scheduling when the application runs with superuser
+    /// privileges. Use [`wait_pulse`] to block until the pulse has completed.
+    ///
+    /// Any active software-based PWM signal is stopped, and any pulse started by a
+    /// previous call to `pulse_async` is completed first. Changing the pin's logic level
+    /// through [`set_low`], [`set_high`] or [`write`] while the pulse is active results
+    /// in undefined behavior.
+    ///
+    /// [`wait_pulse`]: #method.wait_pulse
+    /// [`set_low`]: #method.set_low
+    /// [`set_high`]: #method.set_high
+    /// [`write`]: #method.write
+    pub fn pulse_async(&mut self, level: Level, duration: Duration) -> Result<()> {
+        self.clear_pwm()?;
+        self.wait_pulse()?;
+
+        let pin = self.pin.pin;
+        let gpio_state = self.pin.gpio_state.clone();
+        let thread_config = gpio_state.resolve_thread_config(&self.thread_config);
+
+        self.pulse_thread = Some(sched::spawn(thread_config, true, move || {
+            soft_pwm::pulse(&gpio_state, pin, level, duration);
+            Ok(())
+        })?);
+
+        Ok(())
+    }
+
+    /// Blocks until any pulse started with [`pulse_async`] has completed.
+    ///
+    /// [`pulse_async`]: #method.pulse_async
+    pub fn wait_pulse(&mut self) -> Result<()> {
+        if let Some(pulse_thread) = self.pulse_thread.take() {
+            match pulse_thread.join() {
+                Ok(r) => return r,
+                Err(_) => return Err(Error::ThreadPanic),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the scheduling policy and CPU affinity for software-based PWM and
+    /// asynchronous pulse threads started by this pin, overriding the default set
+    /// through [`Gpio::set_thread_config`].
+    ///
+    /// If `config` is set to `None`, the default configuration is used. The
+    /// configuration is applied to threads that are started after the call. More
+    /// information can be found at [`ThreadConfig`].
+    ///
+    /// [`Gpio::set_thread_config`]: struct.Gpio.html#method.set_thread_config
+    /// [`ThreadConfig`]: struct.ThreadConfig.html
+    pub fn set_thread_config(&mut self, config: Option<ThreadConfig>) {
+        self.thread_config = config;
+    }
+
+    /// Returns the scheduling policy and CPU affinity for threads started by this pin.
+    pub fn thread_config(&self) -> ThreadConfig {
+        self.pin
+            .gpio_state
+            .resolve_thread_config(&self.thread_config)
+    }
+
+    impl_into!();
     impl_reset_on_drop!();
 }
 
-impl_drop!(OutputPin);
+impl Drop for OutputPin {
+    /// Stops any active software-based PWM signal, waits for any active pulse to complete,
+    /// sets the pin to its configured [`GpioBuilder::drop_level`], and resets the pin's
+    /// mode and disables the built-in pull-up/pull-down resistors if `reset_on_drop` is
+    /// set to `true` (default).
+    ///
+    /// [`GpioBuilder::drop_level`]: struct.GpioBuilder.html#method.drop_level
+    fn drop(&mut self) {
+        let _ = self.clear_pwm();
+
+        if !thread::panicking() {
+            let _ = self.wait_pulse();
+        }
+
+        // The drop level is only applied when the pin is released, rather than
+        // converted through into_pin()
+        if self.pin.is_owner() {
+            if let Some(level) = self.pin.gpio_state.drop_level(self.pin.pin) {
+                self.pin.write(level);
+            }
+        }
+
+        if !self.reset_on_drop {
+            return;
+        }
+
+        if let Some(prev_mode) = self.prev_mode {
+            self.pin.set_mode(prev_mode);
+        }
+
+        if self.pud_mode != PullUpDown::Off {
+            self.pin.set_pullupdown(PullUpDown::Off);
+        }
+    }
+}
 
 /// GPIO pin configured with an alternate function.
 #[derive(Debug)]
@@ -458,19 +1762,100 @@ impl AltPin {
             Some(prev_mode)
         };
 
+        let reset_on_drop = pin.gpio_state.default_reset_on_drop();
+
         AltPin {
             pin,
             mode,
             prev_mode,
-            reset_on_drop: true,
+            reset_on_drop,
             pud_mode: PullUpDown::Off,
         }
     }
 
     impl_pin!();
+
+    /// Returns the alternate function mode the pin is configured for.
+    #[inline]
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
     impl_input!();
     impl_output!();
+    impl_pullupdown!();
+    impl_into!();
     impl_reset_on_drop!();
 }
 
 impl_drop!(AltPin);
+
+/// GPIO pin that can be switched between any of the available modes.
+///
+/// `IoPin` is intended for bit-banged protocols that share a single line for
+/// both directions, such as the data line of a DHT sensor. When `IoPin` goes
+/// out of scope, the pin is reset to the mode it had before it was converted,
+/// rather than the most recently selected mode, if [`reset_on_drop`] is set to
+/// `true` (default).
+///
+/// [`reset_on_drop`]: #method.set_reset_on_drop
+#[derive(Debug)]
+pub struct IoPin {
+    pin: Pin,
+    mode: Mode,
+    prev_mode: Option<Mode>,
+    reset_on_drop: bool,
+    pud_mode: PullUpDown,
+}
+
+impl IoPin {
+    pub(crate) fn new(mut pin: Pin, mode: Mode) -> IoPin {
+        let prev_mode = pin.mode();
+
+        let prev_mode = if prev_mode == mode {
+            None
+        } else {
+            pin.set_mode(mode);
+            Some(prev_mode)
+        };
+
+        let reset_on_drop = pin.gpio_state.default_reset_on_drop();
+
+        IoPin {
+            pin,
+            mode,
+            prev_mode,
+            reset_on_drop,
+            pud_mode: PullUpDown::Off,
+        }
+    }
+
+    impl_pin!();
+
+    /// Returns the pin's mode.
+    #[inline]
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Sets the pin's mode.
+    #[inline]
+    pub fn set_mode(&mut self, mode: Mode) {
+        // The original mode only needs to be stored once, the first time the
+        // mode is actually changed
+        if self.prev_mode.is_none() && mode != self.mode {
+            self.prev_mode = Some(self.mode);
+        }
+
+        self.pin.set_mode(mode);
+        self.mode = mode;
+    }
+
+    impl_input!();
+    impl_output!();
+    impl_pullupdown!();
+    impl_into!();
+    impl_reset_on_drop!();
+}
+
+impl_drop!(IoPin);
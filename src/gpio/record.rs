@@ -0,0 +1,254 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::delay::get_time_ns;
+use crate::gpio::backend::{FastIo, GpioRegisters};
+use crate::gpio::{pin, Level, Mode, PullUpDown, Result, Trigger};
+use crate::system::SoC;
+
+struct RecorderState {
+    writer: Box<dyn Write + Send>,
+    start_ns: i64,
+}
+
+/// Records GPIO activity to a file or any other writer.
+///
+/// A `Recorder` is attached to a [`Gpio`] instance through
+/// [`GpioBuilder::recorder`]. Every mode change, pull-up/pull-down change, write
+/// and read is logged as a single line containing a JSON object, which makes the
+/// recording easy to inspect with standard tools, or to post-process into a
+/// logic analyzer format.
+///
+/// Each event contains the time in nanoseconds since the `Recorder` was
+/// constructed, the event type and the pin number:
+///
+/// ```text
+/// {"time":10250,"event":"mode","pin":23,"mode":"Out"}
+/// {"time":11873,"event":"write","pin":23,"level":"High"}
+/// {"time":15012,"event":"pullupdown","pin":24,"pud":"PullUp"}
+/// {"time":16340,"event":"read","pin":24,"level":"Low"}
+/// {"time":18211,"event":"levels","levels":16777216}
+/// ```
+///
+/// `levels` events are logged when multiple pins are read at once, and contain
+/// the logic levels of all pins as a bitmask, with bit 0 mapped to GPIO 0.
+///
+/// Recorded inputs can be fed back to an application with [`MockGpio::replay`].
+///
+/// Writing to the underlying writer is buffered. Any errors that occur while
+/// writing are ignored, but can be detected by calling [`flush`]. The buffer is
+/// flushed automatically when the last `Gpio` instance and `Recorder` clone go out
+/// of scope.
+///
+/// [`Gpio`]: struct.Gpio.html
+/// [`GpioBuilder::recorder`]: struct.GpioBuilder.html#method.recorder
+/// [`MockGpio::replay`]: struct.MockGpio.html#method.replay
+/// [`flush`]: #method.flush
+#[derive(Clone)]
+pub struct Recorder {
+    inner: Arc<Mutex<RecorderState>>,
+}
+
+impl Recorder {
+    /// Constructs a new `Recorder` that writes to the specified file.
+    ///
+    /// The file is created if it doesn't exist, and truncated if it does.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Recorder> {
+        Ok(Recorder::new(File::create(path)?))
+    }
+
+    /// Constructs a new `Recorder` that writes to the specified writer.
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Recorder {
+        Recorder {
+            inner: Arc::new(Mutex::new(RecorderState {
+                writer: Box::new(BufWriter::new(writer)),
+                start_ns: get_time_ns(),
+            })),
+        }
+    }
+
+    /// Flushes any buffered events to the underlying writer.
+    pub fn flush(&self) -> Result<()> {
+        self.state().writer.flush()?;
+
+        Ok(())
+    }
+
+    // A poisoned lock only means a thread panicked while holding it, which
+    // at worst leaves a partially written line.
+    fn state(&self) -> std::sync::MutexGuard<'_, RecorderState> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn log(&self, event: &str, pin: Option<u8>, field: &str, value: fmt::Arguments<'_>) {
+        let mut state = self.state();
+        let time = get_time_ns() - state.start_ns;
+
+        let _ = match pin {
+            Some(pin) => writeln!(
+                state.writer,
+                "{{\"time\":{},\"event\":\"{}\",\"pin\":{},\"{}\":{}}}",
+                time, event, pin, field, value
+            ),
+            None => writeln!(
+                state.writer,
+                "{{\"time\":{},\"event\":\"{}\",\"{}\":{}}}",
+                time, event, field, value
+            ),
+        };
+    }
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("start_ns", &self.state().start_ns)
+            .finish()
+    }
+}
+
+// Wraps any of the GPIO backends, and logs every register-level operation to
+// a Recorder before passing it on.
+#[derive(Debug)]
+pub(crate) struct RecordingRegisters {
+    inner: Box<dyn GpioRegisters>,
+    recorder: Recorder,
+}
+
+impl RecordingRegisters {
+    pub(crate) fn new(inner: Box<dyn GpioRegisters>, recorder: Recorder) -> RecordingRegisters {
+        RecordingRegisters { inner, recorder }
+    }
+
+    fn log_write(&self, pin: u8, level: Level) {
+        self.recorder
+            .log("write", Some(pin), "level", format_args!("\"{}\"", level));
+    }
+}
+
+impl GpioRegisters for RecordingRegisters {
+    fn soc(&self) -> SoC {
+        self.inner.soc()
+    }
+
+    fn set_high(&self, pin: u8) {
+        self.inner.set_high(pin);
+        self.log_write(pin, Level::High);
+    }
+
+    fn set_low(&self, pin: u8) {
+        self.inner.set_low(pin);
+        self.log_write(pin, Level::Low);
+    }
+
+    fn write_levels(&self, set_mask: u64, clear_mask: u64) {
+        self.inner.write_levels(set_mask, clear_mask);
+
+        for pin in 0..pin::MAX as u8 {
+            if (set_mask >> pin) & 1 == 1 {
+                self.log_write(pin, Level::High);
+            }
+
+            if (clear_mask >> pin) & 1 == 1 {
+                self.log_write(pin, Level::Low);
+            }
+        }
+    }
+
+    fn level(&self, pin: u8) -> Level {
+        let level = self.inner.level(pin);
+        self.recorder
+            .log("read", Some(pin), "level", format_args!("\"{}\"", level));
+
+        level
+    }
+
+    fn levels(&self) -> u64 {
+        let levels = self.inner.levels();
+        self.recorder
+            .log("levels", None, "levels", format_args!("{}", levels));
+
+        levels
+    }
+
+    fn mode(&self, pin: u8) -> Mode {
+        self.inner.mode(pin)
+    }
+
+    fn set_mode(&self, pin: u8, mode: Mode) {
+        self.inner.set_mode(pin, mode);
+        self.recorder
+            .log("mode", Some(pin), "mode", format_args!("\"{}\"", mode));
+    }
+
+    fn set_pullupdown(&self, pin: u8, pud: PullUpDown) {
+        self.inner.set_pullupdown(pin, pud);
+        self.recorder
+            .log("pullupdown", Some(pin), "pud", format_args!("\"{}\"", pud));
+    }
+
+    fn pullupdown(&self, pin: u8) -> Option<PullUpDown> {
+        self.inner.pullupdown(pin)
+    }
+
+    fn release(&self, pin: u8) {
+        self.inner.release(pin)
+    }
+
+    // Writes through FastIo would bypass the recorder, so OutputPin falls back
+    // to set_high() and set_low().
+    fn fast_io(&self, _pin: u8) -> Option<FastIo> {
+        None
+    }
+
+    fn register_ptr(&self, offset: usize) -> Result<*mut u32> {
+        self.inner.register_ptr(offset)
+    }
+
+    fn set_edge_detect(&self, pin: u8, trigger: Trigger) -> Result<()> {
+        self.inner.set_edge_detect(pin, trigger)
+    }
+
+    fn take_edge_event(&self, pin: u8) -> Result<bool> {
+        self.inner.take_edge_event(pin)
+    }
+}
+
+// Extracts the raw value of a top-level field from a single-line JSON object
+// written by Recorder. String values are returned without quotes.
+#[cfg(feature = "mock")]
+pub(crate) fn field<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let pattern = format!("\"{}\":", key);
+    let start = line.find(&pattern)? + pattern.len();
+    let value = &line[start..];
+
+    if let Some(value) = value.strip_prefix('"') {
+        value.find('"').map(|end| &value[..end])
+    } else {
+        let end = value.find([',', '}']).unwrap_or(value.len());
+        Some(value[..end].trim())
+    }
+}
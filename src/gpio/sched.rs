@@ -0,0 +1,167 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::io;
+use std::mem;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+
+use libc;
+
+use crate::gpio::{soft_pwm, Error, Result};
+
+/// Scheduling policy and CPU affinity for threads spawned by RPPAL.
+///
+/// Asynchronous interrupt handlers, software-based PWM and asynchronous pulses run
+/// on separate threads. By default, the PWM and pulse threads request the highest
+/// round-robin real-time priority, which silently fails without superuser
+/// privileges, while interrupt threads use the default scheduling policy.
+///
+/// `ThreadConfig` replaces those defaults. When a real-time priority is set, the
+/// thread uses the `SCHED_FIFO` policy at the specified priority. When one or more
+/// CPUs are set, the thread only runs on those CPU cores, which is useful in
+/// combination with the `isolcpus` kernel parameter to keep other processes from
+/// interfering with timing-critical code. Unlike the defaults, any failure to apply
+/// the configuration is returned as an error when the thread is started.
+///
+/// A default configuration for all pins can be set with [`Gpio::set_thread_config`],
+/// and overridden for individual pins through [`InputPin::set_thread_config`] and
+/// [`OutputPin::set_thread_config`]. The configuration is applied to threads that
+/// are started afterwards.
+///
+/// ```no_run
+/// use rppal::gpio::{Gpio, ThreadConfig, Trigger};
+///
+/// # fn main() -> rppal::gpio::Result<()> {
+/// let gpio = Gpio::new()?;
+/// gpio.set_thread_config(ThreadConfig::new().with_priority(80).with_cpus(&[3]));
+///
+/// let mut pin = gpio.get(23).unwrap().into_input();
+/// pin.set_async_interrupt(Trigger::RisingEdge, |event| println!("{:?}", event))?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Gpio::set_thread_config`]: struct.Gpio.html#method.set_thread_config
+/// [`InputPin::set_thread_config`]: struct.InputPin.html#method.set_thread_config
+/// [`OutputPin::set_thread_config`]: struct.OutputPin.html#method.set_thread_config
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ThreadConfig {
+    priority: Option<i32>,
+    cpus: Vec<usize>,
+}
+
+impl ThreadConfig {
+    /// Constructs a new `ThreadConfig`, which uses RPPAL's default scheduling settings.
+    pub fn new() -> ThreadConfig {
+        ThreadConfig::default()
+    }
+
+    /// Sets the `SCHED_FIFO` real-time priority, between 1 (lowest) and 99 (highest).
+    ///
+    /// Real-time scheduling requires superuser privileges, or the `CAP_SYS_NICE`
+    /// capability.
+    pub fn with_priority(mut self, priority: i32) -> ThreadConfig {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Restricts the thread to the specified CPU cores, numbered from 0.
+    pub fn with_cpus(mut self, cpus: &[usize]) -> ThreadConfig {
+        self.cpus = cpus.to_vec();
+        self
+    }
+
+    /// Returns the `SCHED_FIFO` real-time priority, if set.
+    pub fn priority(&self) -> Option<i32> {
+        self.priority
+    }
+
+    /// Returns the CPU cores the thread is restricted to. An empty slice indicates
+    /// the thread can run on any core.
+    pub fn cpus(&self) -> &[usize] {
+        &self.cpus
+    }
+
+    // Applies the configuration to the calling thread. If no priority is set and
+    // realtime is true, falls back to RPPAL's default real-time priority.
+    fn apply(&self, realtime: bool) -> Result<()> {
+        match self.priority {
+            Some(priority) => {
+                let params = libc::sched_param {
+                    sched_priority: priority,
+                };
+
+                parse_retval!(unsafe { libc::sched_setscheduler(0, libc::SCHED_FIFO, &params) })?;
+            }
+            None if realtime => soft_pwm::set_realtime_priority(),
+            None => (),
+        }
+
+        if !self.cpus.is_empty() {
+            let mut cpu_set: libc::cpu_set_t = unsafe { mem::zeroed() };
+
+            for &cpu in &self.cpus {
+                if cpu >= libc::CPU_SETSIZE as usize {
+                    return Err(Error::Io(io::Error::from_raw_os_error(libc::EINVAL)));
+                }
+
+                unsafe { libc::CPU_SET(cpu, &mut cpu_set) };
+            }
+
+            parse_retval!(unsafe {
+                libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &cpu_set)
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+// Spawns a thread with the specified configuration, and waits until the configuration
+// has been applied, so any errors are returned to the caller rather than the thread.
+pub(crate) fn spawn<F>(config: ThreadConfig, realtime: bool, f: F) -> Result<JoinHandle<Result<()>>>
+where
+    F: FnOnce() -> Result<()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::sync_channel(1);
+
+    let handle = thread::spawn(move || -> Result<()> {
+        match config.apply(realtime) {
+            Ok(()) => {
+                let _ = tx.send(Ok(()));
+                f()
+            }
+            Err(e) => {
+                let _ = tx.send(Err(e));
+                Ok(())
+            }
+        }
+    });
+
+    match rx.recv() {
+        Ok(Ok(())) => Ok(handle),
+        Ok(Err(e)) => {
+            let _ = handle.join();
+            Err(e)
+        }
+        Err(_) => Err(Error::ThreadPanic),
+    }
+}
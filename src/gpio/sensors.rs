@@ -0,0 +1,76 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Drivers for sensors with timing-critical single-pin protocols.
+//!
+//! These sensors don't use any of the standard buses, and require precise timing
+//! that's difficult to get right in application code. The drivers bit-bang the
+//! protocols on any available GPIO pin by sleeping and busy waiting on the
+//! current thread.
+//!
+//! * [`Dht`] reads temperature and relative humidity from DHT11 and DHT22 (AM2302)
+//!   sensors.
+//! * [`HcSr04`] measures distances using HC-SR04 ultrasonic sensors.
+//!
+//! Timing-critical code running in userspace can be interrupted by the scheduler at
+//! any time, so an occasional failed read is expected. Reads that are affected by
+//! timing issues are detected and reported as an error, and can safely be retried.
+//!
+//! [`Dht`]: struct.Dht.html
+//! [`HcSr04`]: struct.HcSr04.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::gpio;
+
+mod dht;
+mod hcsr04;
+
+pub use self::dht::{Dht, DhtModel, Reading};
+pub use self::hcsr04::HcSr04;
+
+quick_error! {
+/// Errors that can occur when communicating with a sensor.
+    #[derive(Debug)]
+    pub enum Error {
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Timeout.
+///
+/// The sensor didn't respond, or stopped responding before the transfer was
+/// completed. Make sure the sensor is connected and powered, and that the
+/// data line has a pull-up resistor.
+        Timeout { description("sensor didn't respond in time") }
+/// Checksum mismatch.
+///
+/// The received data is corrupted, which is usually caused by the current
+/// thread getting interrupted during the transfer.
+        Checksum { description("checksum mismatch") }
+/// Out of range.
+///
+/// The measured value exceeds the sensor's maximum range.
+        OutOfRange { description("measurement out of range") }
+    }
+}
+
+/// Result type returned from methods that can have `sensors::Error`s.
+pub type Result<T> = result::Result<T, Error>;
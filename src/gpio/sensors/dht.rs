@@ -0,0 +1,267 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fmt;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::delay::get_time_ns;
+use crate::gpio::sensors::{Error, Result};
+use crate::gpio::{IoPin, Level, Mode, PullUpDown};
+
+// Maximum time spent waiting for any single edge, which is well above the
+// longest pulse (80 µs) defined in the datasheets
+const EDGE_TIMEOUT_NS: i64 = 200_000;
+// High pulses are 26-28 µs for a 0 bit, and 70 µs for a 1 bit
+const BIT_THRESHOLD_NS: i64 = 48_000;
+
+/// DHT sensor models.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum DhtModel {
+    /// DHT11. Temperature range 0-50 °C, humidity range 20-90 %.
+    Dht11,
+    /// DHT22 (AM2302). Temperature range -40-80 °C, humidity range 0-100 %.
+    Dht22,
+}
+
+impl DhtModel {
+    // Duration of the start signal
+    fn start_signal(self) -> Duration {
+        match self {
+            DhtModel::Dht11 => Duration::from_millis(20),
+            DhtModel::Dht22 => Duration::from_micros(1100),
+        }
+    }
+
+    // Minimum interval between two measurements
+    fn min_interval(self) -> Duration {
+        match self {
+            DhtModel::Dht11 => Duration::from_secs(1),
+            DhtModel::Dht22 => Duration::from_secs(2),
+        }
+    }
+}
+
+impl fmt::Display for DhtModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DhtModel::Dht11 => write!(f, "DHT11"),
+            DhtModel::Dht22 => write!(f, "DHT22"),
+        }
+    }
+}
+
+/// Temperature and relative humidity measurement.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Reading {
+    temperature: f32,
+    humidity: f32,
+}
+
+impl Reading {
+    /// Returns the temperature in degrees Celsius (°C).
+    pub fn temperature(&self) -> f32 {
+        self.temperature
+    }
+
+    /// Returns the relative humidity in percent (%).
+    pub fn humidity(&self) -> f32 {
+        self.humidity
+    }
+}
+
+/// DHT11 and DHT22 (AM2302) temperature and humidity sensor driver.
+///
+/// The sensor's data line can be connected to any available GPIO pin. The built-in
+/// pull-up resistor is enabled, but an external 4.7-10 kΩ pull-up resistor is
+/// recommended, especially for longer wires.
+///
+/// `Dht` takes ownership of an [`IoPin`]. When `Dht` goes out of scope, the pin is
+/// reset to its original mode and the pull-up resistor is disabled, unless
+/// [`reset_on_drop`] was set to `false` on the `IoPin`.
+///
+/// The sensors only allow a measurement once every second (DHT11) or every two
+/// seconds (DHT22). If [`read`] is called again before that interval has elapsed,
+/// it sleeps for the remainder of the interval before starting a new measurement.
+///
+/// The sensor's response is timed by busy waiting on the current thread. If the
+/// thread is interrupted during the transfer, [`read`] returns
+/// [`Error::Checksum`] or [`Error::Timeout`], after which it can be retried.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::sensors::{Dht, DhtModel};
+/// use rppal::gpio::{Gpio, Mode};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gpio = Gpio::new()?;
+/// let mut dht = Dht::new(gpio.get(4).unwrap().into_io(Mode::Input), DhtModel::Dht22);
+///
+/// let reading = dht.read()?;
+/// println!("{:.1} °C, {:.1} %", reading.temperature(), reading.humidity());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`IoPin`]: ../struct.IoPin.html
+/// [`reset_on_drop`]: ../struct.IoPin.html#method.set_reset_on_drop
+/// [`read`]: #method.read
+/// [`Error::Checksum`]: enum.Error.html#variant.Checksum
+/// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+#[derive(Debug)]
+pub struct Dht {
+    pin: IoPin,
+    model: DhtModel,
+    last_read: Option<Instant>,
+}
+
+impl Dht {
+    /// Constructs a new `Dht`.
+    ///
+    /// The pin is configured as an input with its built-in pull-up resistor enabled,
+    /// which keeps the data line idle until a measurement is started.
+    pub fn new(mut pin: IoPin, model: DhtModel) -> Dht {
+        pin.set_mode(Mode::Input);
+        pin.set_pullupdown(PullUpDown::PullUp);
+        // Once the pin is switched to output mode, it should always pull the line low
+        pin.set_low();
+
+        Dht {
+            pin,
+            model,
+            last_read: None,
+        }
+    }
+
+    /// Returns the sensor model.
+    pub fn model(&self) -> DhtModel {
+        self.model
+    }
+
+    /// Consumes the `Dht`, and returns the data pin.
+    pub fn into_pin(self) -> IoPin {
+        self.pin
+    }
+
+    /// Starts a new measurement, and returns the temperature and relative humidity.
+    pub fn read(&mut self) -> Result<Reading> {
+        if let Some(last_read) = self.last_read {
+            if let Some(remaining) = self.model.min_interval().checked_sub(last_read.elapsed()) {
+                sleep(remaining);
+            }
+        }
+
+        let data = self.transfer();
+        self.last_read = Some(Instant::now());
+        let data = data?;
+
+        let checksum = data[0]
+            .wrapping_add(data[1])
+            .wrapping_add(data[2])
+            .wrapping_add(data[3]);
+
+        if checksum != data[4] {
+            return Err(Error::Checksum);
+        }
+
+        Ok(self.decode(&data))
+    }
+
+    fn decode(&self, data: &[u8; 5]) -> Reading {
+        match self.model {
+            DhtModel::Dht11 => {
+                // The decimal part is always 0 on older DHT11 revisions. Bit 7 of
+                // the decimal temperature byte indicates a negative temperature.
+                let humidity = f32::from(data[0]) + f32::from(data[1]) / 10.0;
+                let temperature = f32::from(data[2]) + f32::from(data[3] & 0x7f) / 10.0;
+
+                Reading {
+                    temperature: if data[3] & 0x80 > 0 {
+                        -temperature
+                    } else {
+                        temperature
+                    },
+                    humidity,
+                }
+            }
+            DhtModel::Dht22 => {
+                let humidity = f32::from(u16::from(data[0]) << 8 | u16::from(data[1])) / 10.0;
+                let temperature =
+                    f32::from(u16::from(data[2] & 0x7f) << 8 | u16::from(data[3])) / 10.0;
+
+                Reading {
+                    temperature: if data[2] & 0x80 > 0 {
+                        -temperature
+                    } else {
+                        temperature
+                    },
+                    humidity,
+                }
+            }
+        }
+    }
+
+    fn transfer(&mut self) -> Result<[u8; 5]> {
+        // Start signal. Pull the line low, and then release it.
+        self.pin.set_mode(Mode::Output);
+        sleep(self.model.start_signal());
+        self.pin.set_mode(Mode::Input);
+
+        // The sensor responds by pulling the line low for 80 µs, and then releasing
+        // it for 80 µs, after which the first bit is sent
+        self.wait_for(Level::Low)?;
+        self.wait_for(Level::High)?;
+        self.wait_for(Level::Low)?;
+
+        // Each bit starts with a 50 µs low pulse, followed by a high pulse that
+        // determines the bit value
+        let mut data = [0u8; 5];
+        for bit in 0..40 {
+            let start = self.wait_for(Level::High)?;
+            let end = self.wait_for(Level::Low)?;
+
+            if end - start > BIT_THRESHOLD_NS {
+                data[bit / 8] |= 0x80 >> (bit % 8);
+            }
+        }
+
+        Ok(data)
+    }
+
+    // Busy waits until the line reaches the specified level, and returns the time
+    // the level change was detected
+    #[inline(always)]
+    fn wait_for(&self, level: Level) -> Result<i64> {
+        let deadline = get_time_ns() + EDGE_TIMEOUT_NS;
+
+        loop {
+            let now = get_time_ns();
+
+            if self.pin.read() == level {
+                return Ok(now);
+            }
+
+            if now > deadline {
+                return Err(Error::Timeout);
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::gpio::sensors::{Error, Result};
+use crate::gpio::{InputPin, Level, OutputPin, Trigger};
+
+const TRIGGER_PULSE: Duration = Duration::from_micros(10);
+// The echo pulse is at most 38 ms when no obstacle is detected. The sensor
+// starts its burst up to 0.5 ms after the trigger pulse.
+const ECHO_TIMEOUT: Duration = Duration::from_millis(50);
+// Minimum interval between measurements recommended by the datasheet, which
+// prevents echoes from a previous measurement from being picked up
+const MIN_INTERVAL: Duration = Duration::from_millis(60);
+// Maximum range according to the datasheet
+const MAX_DISTANCE: f64 = 4.0;
+const DEFAULT_TEMPERATURE: f64 = 20.0;
+
+/// HC-SR04 ultrasonic distance sensor driver.
+///
+/// The sensor's trigger input can be connected to any available GPIO pin. The
+/// echo output uses 5 V logic levels, and needs a voltage divider or level shifter
+/// before it's connected to the Raspberry Pi.
+///
+/// The echo pulse is measured using the kernel timestamps of its edges, which
+/// makes it unaffected by any scheduling delays in userspace. The speed of sound
+/// is compensated for the air temperature, which can be changed with
+/// [`set_temperature`].
+///
+/// If [`measure`] is called again within 60 ms of the previous measurement, it
+/// sleeps for the remainder of the interval before starting a new measurement.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::sensors::HcSr04;
+/// use rppal::gpio::Gpio;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gpio = Gpio::new()?;
+/// let mut sensor = HcSr04::new(
+///     gpio.get(23).unwrap().into_output_low(),
+///     gpio.get(24).unwrap().into_input(),
+/// );
+///
+/// println!("{:.3} m", sensor.measure()?);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`set_temperature`]: #method.set_temperature
+/// [`measure`]: #method.measure
+#[derive(Debug)]
+pub struct HcSr04 {
+    trigger: OutputPin,
+    echo: InputPin,
+    temperature: f64,
+    last_measurement: Option<Instant>,
+}
+
+impl HcSr04 {
+    /// Constructs a new `HcSr04`.
+    pub fn new(mut trigger: OutputPin, echo: InputPin) -> HcSr04 {
+        trigger.set_low();
+
+        HcSr04 {
+            trigger,
+            echo,
+            temperature: DEFAULT_TEMPERATURE,
+            last_measurement: None,
+        }
+    }
+
+    /// Returns the air temperature in degrees Celsius (°C) used to calculate the
+    /// speed of sound.
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    /// Sets the air temperature in degrees Celsius (°C) used to calculate the
+    /// speed of sound.
+    ///
+    /// Defaults to 20 °C.
+    pub fn set_temperature(&mut self, temperature: f64) {
+        self.temperature = temperature;
+    }
+
+    /// Returns the speed of sound in meters per second (m/s), based on the
+    /// configured air temperature.
+    pub fn speed_of_sound(&self) -> f64 {
+        331.3 + 0.606 * self.temperature
+    }
+
+    /// Starts a new measurement, and returns the distance in meters (m).
+    ///
+    /// Returns [`Error::Timeout`] if no echo was detected, or [`Error::OutOfRange`]
+    /// if the distance exceeds the sensor's maximum range of 4 m.
+    ///
+    /// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+    /// [`Error::OutOfRange`]: enum.Error.html#variant.OutOfRange
+    pub fn measure(&mut self) -> Result<f64> {
+        let echo = self.measure_echo()?;
+
+        // The echo pulse covers the round trip
+        let distance = echo.as_secs_f64() * self.speed_of_sound() / 2.0;
+
+        if distance > MAX_DISTANCE {
+            return Err(Error::OutOfRange);
+        }
+
+        Ok(distance)
+    }
+
+    /// Starts a new measurement, and returns the duration of the echo pulse.
+    ///
+    /// Returns [`Error::Timeout`] if no echo was detected.
+    ///
+    /// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+    pub fn measure_echo(&mut self) -> Result<Duration> {
+        if let Some(last_measurement) = self.last_measurement {
+            if let Some(remaining) = MIN_INTERVAL.checked_sub(last_measurement.elapsed()) {
+                sleep(remaining);
+            }
+        }
+
+        // Configure the interrupt trigger before the trigger pulse is sent, so
+        // we can't miss the start of the echo pulse
+        self.echo.clear_interrupt()?;
+        self.echo.set_interrupt(Trigger::Both)?;
+
+        let result = self
+            .trigger
+            .pulse(Level::High, TRIGGER_PULSE)
+            .and_then(|_| {
+                self.echo
+                    .measure_pulse_events(Level::High, Some(ECHO_TIMEOUT))
+            });
+
+        self.last_measurement = Some(Instant::now());
+        self.echo.clear_interrupt()?;
+
+        result?.ok_or(Error::Timeout)
+    }
+}
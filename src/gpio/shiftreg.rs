@@ -0,0 +1,332 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Bit-banged shift register drivers.
+//!
+//! [`ShiftOut`] drives serial-in, parallel-out shift registers like the
+//! 74HC595, and [`ShiftIn`] reads parallel-in, serial-out shift registers
+//! like the 74HC165, using any available GPIO pins.
+//!
+//! Both drivers support a configurable bit order and clock polarity. Multiple
+//! daisy-chained shift registers can be accessed by writing or reading multiple
+//! bytes at once.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::gpio::shiftreg::ShiftOut;
+//! use rppal::gpio::Gpio;
+//!
+//! # fn main() -> rppal::gpio::Result<()> {
+//! let gpio = Gpio::new()?;
+//!
+//! // SER, SRCLK and RCLK on a 74HC595
+//! let mut shift_out = ShiftOut::new(
+//!     gpio.get(17).unwrap().into_output(),
+//!     gpio.get(27).unwrap().into_output(),
+//!     Some(gpio.get(22).unwrap().into_output()),
+//! );
+//!
+//! shift_out.write(&[0b1010_0101]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ShiftOut`]: struct.ShiftOut.html
+//! [`ShiftIn`]: struct.ShiftIn.html
+
+use std::time::Duration;
+
+use crate::delay::{delay_until_ns, duration_to_ns, get_time_ns};
+use crate::gpio::{InputPin, Level, OutputPin};
+use crate::spi::BitOrder;
+
+/// Clock polarity.
+///
+/// Indicates the logic level of the clock pin while it's idle. Data is shifted
+/// on the transition away from the idle level.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ClockPolarity {
+    /// Clock idles low. Data is shifted on the rising edge.
+    IdleLow,
+    /// Clock idles high. Data is shifted on the falling edge.
+    IdleHigh,
+}
+
+impl ClockPolarity {
+    fn idle_level(self) -> Level {
+        match self {
+            ClockPolarity::IdleLow => Level::Low,
+            ClockPolarity::IdleHigh => Level::High,
+        }
+    }
+
+    fn active_level(self) -> Level {
+        match self {
+            ClockPolarity::IdleLow => Level::High,
+            ClockPolarity::IdleHigh => Level::Low,
+        }
+    }
+}
+
+#[inline]
+fn delay(delay_ns: i64) {
+    if delay_ns > 0 {
+        delay_until_ns(get_time_ns().saturating_add(delay_ns));
+    }
+}
+
+#[inline]
+fn bit(byte: u8, idx: u8, bit_order: BitOrder) -> bool {
+    match bit_order {
+        BitOrder::MsbFirst => byte & (0x80 >> idx) != 0,
+        BitOrder::LsbFirst => byte & (0x01 << idx) != 0,
+    }
+}
+
+/// Serial-in, parallel-out shift register driver (74HC595).
+///
+/// `ShiftOut` sets the data pin for each bit, and then toggles the clock pin.
+/// If a latch pin is configured, it's pulsed high after all bytes have been
+/// shifted out, which transfers the data to the shift register's outputs.
+#[derive(Debug)]
+pub struct ShiftOut {
+    data: OutputPin,
+    clock: OutputPin,
+    latch: Option<OutputPin>,
+    bit_order: BitOrder,
+    clock_polarity: ClockPolarity,
+    delay_ns: i64,
+}
+
+impl ShiftOut {
+    /// Constructs a new `ShiftOut`.
+    ///
+    /// `data` is connected to the shift register's serial data input (SER), `clock`
+    /// to its shift register clock (SRCLK), and the optional `latch` to its storage
+    /// register clock (RCLK).
+    ///
+    /// By default, data is shifted out [`MsbFirst`], with a clock that idles low.
+    ///
+    /// [`MsbFirst`]: ../../spi/enum.BitOrder.html#variant.MsbFirst
+    pub fn new(data: OutputPin, clock: OutputPin, latch: Option<OutputPin>) -> ShiftOut {
+        let mut shift_out = ShiftOut {
+            data,
+            clock,
+            latch,
+            bit_order: BitOrder::MsbFirst,
+            clock_polarity: ClockPolarity::IdleLow,
+            delay_ns: 0,
+        };
+
+        shift_out.clock.write(shift_out.clock_polarity.idle_level());
+        if let Some(ref mut latch) = shift_out.latch {
+            latch.set_low();
+        }
+
+        shift_out
+    }
+
+    /// Returns the bit order.
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Sets the order in which bits are shifted out.
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) {
+        self.bit_order = bit_order;
+    }
+
+    /// Returns the clock polarity.
+    pub fn clock_polarity(&self) -> ClockPolarity {
+        self.clock_polarity
+    }
+
+    /// Sets the clock polarity, and changes the clock pin to the new idle level.
+    pub fn set_clock_polarity(&mut self, clock_polarity: ClockPolarity) {
+        self.clock_polarity = clock_polarity;
+        self.clock.write(clock_polarity.idle_level());
+    }
+
+    /// Sets the minimum delay between consecutive pin changes.
+    ///
+    /// By default, no delay is added, and the pins are toggled as fast as
+    /// possible. Increase the delay if your shift register, or long wires,
+    /// can't keep up.
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay_ns = duration_to_ns(delay);
+    }
+
+    /// Shifts out a single byte, and pulses the latch pin.
+    pub fn write_byte(&mut self, byte: u8) {
+        self.write(&[byte]);
+    }
+
+    /// Shifts out all bytes in `buffer`, and then pulses the latch pin.
+    ///
+    /// When multiple shift registers are daisy-chained, the first byte ends up
+    /// in the register furthest from the Raspberry Pi.
+    pub fn write(&mut self, buffer: &[u8]) {
+        for &byte in buffer {
+            for idx in 0..8 {
+                if bit(byte, idx, self.bit_order) {
+                    self.data.set_high();
+                } else {
+                    self.data.set_low();
+                }
+
+                delay(self.delay_ns);
+                self.clock.write(self.clock_polarity.active_level());
+                delay(self.delay_ns);
+                self.clock.write(self.clock_polarity.idle_level());
+            }
+        }
+
+        if let Some(ref mut latch) = self.latch {
+            delay(self.delay_ns);
+            latch.set_high();
+            delay(self.delay_ns);
+            latch.set_low();
+        }
+    }
+
+    /// Consumes the `ShiftOut`, and returns the data, clock and latch pins.
+    pub fn into_pins(self) -> (OutputPin, OutputPin, Option<OutputPin>) {
+        (self.data, self.clock, self.latch)
+    }
+}
+
+/// Parallel-in, serial-out shift register driver (74HC165).
+///
+/// If a load pin is configured, `ShiftIn` pulses it low to sample the shift
+/// register's parallel inputs. It then reads the data pin for each bit, and
+/// toggles the clock pin to shift in the next bit.
+#[derive(Debug)]
+pub struct ShiftIn {
+    data: InputPin,
+    clock: OutputPin,
+    load: Option<OutputPin>,
+    bit_order: BitOrder,
+    clock_polarity: ClockPolarity,
+    delay_ns: i64,
+}
+
+impl ShiftIn {
+    /// Constructs a new `ShiftIn`.
+    ///
+    /// `data` is connected to the shift register's serial output (QH), `clock`
+    /// to its clock input (CLK), and the optional `load` to its active-low
+    /// parallel load input (SH/LD).
+    ///
+    /// By default, data is shifted in [`MsbFirst`], with a clock that idles low.
+    ///
+    /// [`MsbFirst`]: ../../spi/enum.BitOrder.html#variant.MsbFirst
+    pub fn new(data: InputPin, clock: OutputPin, load: Option<OutputPin>) -> ShiftIn {
+        let mut shift_in = ShiftIn {
+            data,
+            clock,
+            load,
+            bit_order: BitOrder::MsbFirst,
+            clock_polarity: ClockPolarity::IdleLow,
+            delay_ns: 0,
+        };
+
+        shift_in.clock.write(shift_in.clock_polarity.idle_level());
+        if let Some(ref mut load) = shift_in.load {
+            load.set_high();
+        }
+
+        shift_in
+    }
+
+    /// Returns the bit order.
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Sets the order in which bits are shifted in.
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) {
+        self.bit_order = bit_order;
+    }
+
+    /// Returns the clock polarity.
+    pub fn clock_polarity(&self) -> ClockPolarity {
+        self.clock_polarity
+    }
+
+    /// Sets the clock polarity, and changes the clock pin to the new idle level.
+    pub fn set_clock_polarity(&mut self, clock_polarity: ClockPolarity) {
+        self.clock_polarity = clock_polarity;
+        self.clock.write(clock_polarity.idle_level());
+    }
+
+    /// Sets the minimum delay between consecutive pin changes.
+    ///
+    /// By default, no delay is added, and the pins are toggled as fast as
+    /// possible. Increase the delay if your shift register, or long wires,
+    /// can't keep up.
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay_ns = duration_to_ns(delay);
+    }
+
+    /// Pulses the load pin, and shifts in a single byte.
+    pub fn read_byte(&mut self) -> u8 {
+        let mut buffer = [0u8; 1];
+        self.read(&mut buffer);
+
+        buffer[0]
+    }
+
+    /// Pulses the load pin, and shifts in enough bytes to fill `buffer`.
+    ///
+    /// When multiple shift registers are daisy-chained, the first byte is read
+    /// from the register closest to the Raspberry Pi.
+    pub fn read(&mut self, buffer: &mut [u8]) {
+        if let Some(ref mut load) = self.load {
+            load.set_low();
+            delay(self.delay_ns);
+            load.set_high();
+            delay(self.delay_ns);
+        }
+
+        for byte in buffer.iter_mut() {
+            *byte = 0;
+
+            for idx in 0..8 {
+                if self.data.is_high() {
+                    *byte |= match self.bit_order {
+                        BitOrder::MsbFirst => 0x80 >> idx,
+                        BitOrder::LsbFirst => 0x01 << idx,
+                    };
+                }
+
+                self.clock.write(self.clock_polarity.active_level());
+                delay(self.delay_ns);
+                self.clock.write(self.clock_polarity.idle_level());
+                delay(self.delay_ns);
+            }
+        }
+    }
+
+    /// Consumes the `ShiftIn`, and returns the data, clock and load pins.
+    pub fn into_pins(self) -> (InputPin, OutputPin, Option<OutputPin>) {
+        (self.data, self.clock, self.load)
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fmt;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+
+use lazy_static::lazy_static;
+use libc::{self, c_int, c_void};
+
+use crate::gpio::{cleanup, GpioState, Result};
+
+/// Termination signals that trigger an emergency cleanup.
+///
+/// Used by [`Gpio::register_cleanup_on_signal`].
+///
+/// [`Gpio::register_cleanup_on_signal`]: struct.Gpio.html#method.register_cleanup_on_signal
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Signal {
+    /// `SIGINT`, sent when the user presses <kbd>Ctrl + C</kbd>.
+    Interrupt,
+    /// `SIGTERM`, sent by `kill` and service managers such as systemd.
+    Terminate,
+    /// `SIGHUP`, sent when the controlling terminal is closed.
+    Hangup,
+    /// `SIGQUIT`, sent when the user presses <kbd>Ctrl + \\</kbd>.
+    Quit,
+}
+
+impl Signal {
+    fn signum(self) -> c_int {
+        match self {
+            Signal::Interrupt => libc::SIGINT,
+            Signal::Terminate => libc::SIGTERM,
+            Signal::Hangup => libc::SIGHUP,
+            Signal::Quit => libc::SIGQUIT,
+        }
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Signal::Interrupt => write!(f, "SIGINT"),
+            Signal::Terminate => write!(f, "SIGTERM"),
+            Signal::Hangup => write!(f, "SIGHUP"),
+            Signal::Quit => write!(f, "SIGQUIT"),
+        }
+    }
+}
+
+// Write end of the pipe used to notify the cleanup thread, or -1 if the
+// cleanup thread hasn't been started yet.
+static PIPE_FD: AtomicI32 = AtomicI32::new(-1);
+
+lazy_static! {
+    // The state that's restored when a signal is caught. This is None until
+    // the cleanup thread is started.
+    static ref CLEANUP_TARGET: Mutex<Option<Weak<GpioState>>> = Mutex::new(None);
+}
+
+// Only async-signal-safe functions can be called inside a signal handler,
+// which rules out locking a mutex or accessing the GPIO state. Instead, the
+// signal number is forwarded to the cleanup thread through a pipe.
+extern "C" fn handle_signal(signum: c_int) {
+    let fd = PIPE_FD.load(Ordering::SeqCst);
+    if fd >= 0 {
+        let signum = signum as u8;
+        unsafe {
+            libc::write(fd, &signum as *const u8 as *const c_void, 1);
+        }
+    }
+}
+
+fn cleanup_thread(read_fd: c_int) {
+    loop {
+        let mut signum: u8 = 0;
+        let retval = unsafe { libc::read(read_fd, &mut signum as *mut u8 as *mut c_void, 1) };
+
+        if retval != 1 {
+            if retval == -1 && std::io::Error::last_os_error().raw_os_error() == Some(libc::EINTR) {
+                continue;
+            }
+
+            return;
+        }
+
+        // Holding the lock keeps Gpio::register_cleanup_on_signal() from
+        // reinstalling any handlers while the process is terminated.
+        let target = CLEANUP_TARGET.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(gpio_state) = target.as_ref().and_then(Weak::upgrade) {
+            cleanup::cleanup(&gpio_state);
+        }
+
+        // Terminate the process the same way it would've been terminated
+        // without our handler, so the exit status reflects the signal.
+        unsafe {
+            libc::signal(c_int::from(signum), libc::SIG_DFL);
+            libc::raise(c_int::from(signum));
+        }
+    }
+}
+
+pub(crate) fn register(gpio_state: &Arc<GpioState>, signals: &[Signal]) -> Result<()> {
+    let mut target = CLEANUP_TARGET.lock().unwrap_or_else(|err| err.into_inner());
+
+    if target.is_none() {
+        let mut fds: [c_int; 2] = [-1; 2];
+        parse_retval!(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) })?;
+
+        let read_fd = fds[0];
+        thread::spawn(move || cleanup_thread(read_fd));
+
+        PIPE_FD.store(fds[1], Ordering::SeqCst);
+    }
+
+    *target = Some(Arc::downgrade(gpio_state));
+
+    for signal in signals {
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = handle_signal as *const () as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+
+            parse_retval!(libc::sigaction(signal.signum(), &action, ptr::null_mut()))?;
+        }
+    }
+
+    Ok(())
+}
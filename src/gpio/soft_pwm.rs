@@ -0,0 +1,177 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use libc;
+
+use crate::delay::{delay_until_ns, duration_to_ns, get_time_ns};
+use crate::gpio::sched::{self, ThreadConfig};
+use crate::gpio::{Error, GpioState, Level, Result};
+
+const NANOS_PER_SEC: f64 = 1_000_000_000.0;
+
+#[derive(Debug)]
+enum Msg {
+    Reconfigure(Duration, Duration),
+    Stop,
+}
+
+#[derive(Debug)]
+pub(crate) struct SoftPwm {
+    pwm_thread: Option<thread::JoinHandle<Result<()>>>,
+    sender: Sender<Msg>,
+}
+
+impl SoftPwm {
+    pub(crate) fn new(
+        pin: u8,
+        gpio_state: Arc<GpioState>,
+        period: Duration,
+        pulse_width: Duration,
+        thread_config: ThreadConfig,
+    ) -> Result<SoftPwm> {
+        let (sender, receiver): (Sender<Msg>, Receiver<Msg>) = mpsc::channel();
+
+        let pwm_thread = sched::spawn(thread_config, true, move || -> Result<()> {
+            let mut period_ns = duration_to_ns(period);
+            let mut pulse_width_ns = duration_to_ns(pulse_width).min(period_ns);
+
+            let mut start_ns = get_time_ns();
+            let cleanup_generation = gpio_state.cleanup_generation();
+
+            loop {
+                // PWM active
+                if pulse_width_ns > 0 {
+                    gpio_state.write_level(pin, Level::High);
+                }
+
+                // Wait for the remaining active time
+                delay_until_ns(start_ns.saturating_add(pulse_width_ns));
+
+                // PWM inactive
+                gpio_state.write_level(pin, Level::Low);
+
+                // Gpio::cleanup() was called
+                if gpio_state.cleanup_generation() != cleanup_generation {
+                    return Ok(());
+                }
+
+                while let Ok(msg) = receiver.try_recv() {
+                    match msg {
+                        Msg::Reconfigure(period, pulse_width) => {
+                            // Reconfigure period and pulse width
+                            period_ns = duration_to_ns(period);
+                            pulse_width_ns = duration_to_ns(pulse_width).min(period_ns);
+                        }
+                        Msg::Stop => {
+                            // The main thread asked us to stop
+                            return Ok(());
+                        }
+                    }
+                }
+
+                // Wait for the remaining inactive time
+                delay_until_ns(start_ns.saturating_add(period_ns));
+                start_ns = get_time_ns();
+            }
+        })?;
+
+        Ok(SoftPwm {
+            pwm_thread: Some(pwm_thread),
+            sender,
+        })
+    }
+
+    pub(crate) fn reconfigure(&mut self, period: Duration, pulse_width: Duration) {
+        let _ = self.sender.send(Msg::Reconfigure(period, pulse_width));
+    }
+
+    pub(crate) fn stop(&mut self) -> Result<()> {
+        let _ = self.sender.send(Msg::Stop);
+        if let Some(pwm_thread) = self.pwm_thread.take() {
+            match pwm_thread.join() {
+                Ok(r) => return r,
+                Err(_) => return Err(Error::ThreadPanic),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for SoftPwm {
+    fn drop(&mut self) {
+        // Don't wait for the pwm thread to exit if the main thread is panicking,
+        // because we could potentially block indefinitely while unwinding if the
+        // pwm thread is executing a really long sleep.
+        if !thread::panicking() {
+            let _ = self.stop();
+        }
+    }
+}
+
+// Set the scheduling policy for the current thread to real-time round robin at the
+// highest priority. This will silently fail if we're not running as root.
+pub(crate) fn set_realtime_priority() {
+    let params = libc::sched_param {
+        sched_priority: unsafe { libc::sched_get_priority_max(libc::SCHED_RR) },
+    };
+
+    unsafe {
+        libc::sched_setscheduler(0, libc::SCHED_RR, &params);
+    }
+}
+
+// Sets the pin to the specified level for the specified duration, after which it's
+// set to the opposite level.
+pub(crate) fn pulse(gpio_state: &GpioState, pin: u8, level: Level, duration: Duration) {
+    let start_ns = get_time_ns();
+
+    gpio_state.write_level(pin, level);
+
+    delay_until_ns(start_ns.saturating_add(duration_to_ns(duration)));
+
+    let opposite_level = match level {
+        Level::High => Level::Low,
+        Level::Low => Level::High,
+    };
+    gpio_state.write_level(pin, opposite_level);
+}
+
+// Converts a frequency in herz (Hz) and a duty cycle between 0.0 and 1.0 into
+// a period and pulse width.
+pub(crate) fn frequency_to_period(frequency: f64, duty_cycle: f64) -> (Duration, Duration) {
+    let period = if frequency <= 0.0 {
+        0.0
+    } else {
+        NANOS_PER_SEC / frequency
+    };
+
+    let pulse_width = period * duty_cycle.clamp(0.0, 1.0);
+
+    (
+        Duration::from_nanos(period as u64),
+        Duration::from_nanos(pulse_width as u64),
+    )
+}
@@ -0,0 +1,137 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fmt;
+use std::future::poll_fn;
+use std::io;
+use std::pin::Pin as StdPin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::io::unix::AsyncFd;
+
+use crate::gpio::interrupt::Interrupt;
+use crate::gpio::{Error, Event, InputPin, Operation, Result, Trigger};
+
+/// Asynchronous stream of interrupt trigger events.
+///
+/// `EventStream` owns an [`InputPin`] configured with an interrupt trigger, and
+/// yields an [`Event`] for every edge reported by the kernel. Events are read
+/// through tokio's `AsyncFd`, so no additional threads are needed to wait for
+/// interrupts.
+///
+/// `EventStream` is returned by [`InputPin::into_event_stream`], and is only
+/// available when the `async` feature is enabled. It must be created and used
+/// from within a tokio runtime with IO enabled.
+///
+/// [`InputPin`]: struct.InputPin.html
+/// [`Event`]: struct.Event.html
+/// [`InputPin::into_event_stream`]: struct.InputPin.html#method.into_event_stream
+pub struct EventStream {
+    // Declared before pin, so the event request is released before the pin is reset
+    interrupt: AsyncFd<Interrupt>,
+    pin: InputPin,
+}
+
+impl fmt::Debug for EventStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventStream")
+            .field("interrupt", self.interrupt.get_ref())
+            .field("pin", &self.pin)
+            .finish()
+    }
+}
+
+impl EventStream {
+    pub(crate) fn new(mut pin: InputPin, trigger: Trigger) -> Result<EventStream> {
+        let interrupt = async_interrupt(&mut pin, trigger)?;
+
+        Ok(EventStream { interrupt, pin })
+    }
+
+    /// Waits for the next interrupt trigger event.
+    pub async fn next_event(&mut self) -> Result<Event> {
+        poll_fn(|cx| poll_event(&mut self.interrupt, cx)).await
+    }
+
+    /// Returns a reference to the pin.
+    pub fn pin(&self) -> &InputPin {
+        &self.pin
+    }
+
+    /// Consumes the `EventStream`, clears the interrupt trigger and returns the pin.
+    pub fn into_pin(self) -> InputPin {
+        let EventStream { interrupt, pin } = self;
+
+        drop(interrupt);
+
+        pin
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Result<Event>;
+
+    fn poll_next(self: StdPin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        poll_event(&mut self.get_mut().interrupt, cx).map(Some)
+    }
+}
+
+// Clears any existing interrupt triggers, and configures a non-blocking event
+// request that's registered with the tokio reactor.
+pub(crate) fn async_interrupt(pin: &mut InputPin, trigger: Trigger) -> Result<AsyncFd<Interrupt>> {
+    pin.clear_interrupt()?;
+    pin.clear_async_interrupt()?;
+
+    let cdev_fd = pin.pin.gpio_state.cdev_fd(pin.pin())?;
+    let interrupt = Interrupt::new(cdev_fd, pin.pin(), trigger, None)?;
+    interrupt.set_nonblocking()?;
+
+    Ok(AsyncFd::new(interrupt)?)
+}
+
+pub(crate) fn poll_event(
+    interrupt: &mut AsyncFd<Interrupt>,
+    cx: &mut Context<'_>,
+) -> Poll<Result<Event>> {
+    loop {
+        let mut guard = match interrupt.poll_read_ready_mut(cx) {
+            Poll::Ready(Ok(guard)) => guard,
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(Error::Io(e))),
+            Poll::Pending => return Poll::Pending,
+        };
+
+        let pin = guard.get_ref().get_ref().pin();
+
+        // try_io clears the readiness flag when the read returns WouldBlock
+        let event = guard.try_io(|interrupt| match interrupt.get_mut().event() {
+            Ok(event) => Ok(event),
+            Err(Error::Io(e)) | Err(Error::PinIo(_, _, e)) => Err(e),
+            Err(e) => Err(io::Error::other(e.to_string())),
+        });
+
+        match event {
+            Ok(Ok(Some(event))) => return Poll::Ready(Ok(event)),
+            Ok(Err(e)) => return Poll::Ready(Err(Error::PinIo(pin, Operation::ReadEvent, e))),
+            // Suppressed by the debounce filter, or no event available yet
+            Ok(Ok(None)) | Err(_) => continue,
+        }
+    }
+}
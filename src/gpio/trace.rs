@@ -0,0 +1,416 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Value Change Dump (VCD) tracing of GPIO activity.
+//!
+//! [`VcdTracer`] captures the logic levels of a selection of pins into a VCD
+//! file, which can be opened in waveform viewers such as GTKWave. This is useful
+//! for debugging the timing of bit-banged protocols without a logic analyzer.
+//!
+//! A tracer is attached to a [`Gpio`] instance through [`GpioBuilder::tracer`].
+//! Output transitions are captured when a pin's level is written. Inputs are
+//! captured by reading the levels of all selected pins at the configured sample
+//! period on a dedicated thread. Each value change is timestamped with
+//! nanosecond resolution, relative to the moment the tracer was attached.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rppal::gpio::trace::VcdTracer;
+//! use rppal::gpio::Gpio;
+//!
+//! # fn main() -> rppal::gpio::Result<()> {
+//! let tracer = VcdTracer::create("/tmp/gpio.vcd")?
+//!     .pins(&[17, 27])
+//!     .sample_period(Duration::from_micros(10));
+//!
+//! let gpio = Gpio::builder().tracer(tracer.clone()).build()?;
+//!
+//! let _data = gpio.get(27).unwrap().into_input();
+//! let mut clock = gpio.get(17).unwrap().into_output_low();
+//! for _ in 0..8 {
+//!     clock.toggle();
+//! }
+//!
+//! tracer.flush()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Gpio`]: ../struct.Gpio.html
+//! [`GpioBuilder::tracer`]: ../struct.GpioBuilder.html#method.tracer
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::delay::{delay_until_ns, duration_to_ns, get_time_ns};
+use crate::gpio::backend::{FastIo, GpioRegisters};
+use crate::gpio::sched::{self, ThreadConfig};
+use crate::gpio::{pin, Level, Mode, PullUpDown, Result, Trigger};
+use crate::system::SoC;
+
+// VCD identifiers are printable ASCII characters starting at '!'
+const ID_BASE: u8 = b'!';
+
+struct TraceState {
+    writer: Box<dyn Write + Send>,
+    pins: u64,
+    sample_period: Option<Duration>,
+    start_ns: i64,
+    levels: u64,
+    attached: bool,
+}
+
+impl TraceState {
+    // Writes the header and the initial levels of all selected pins.
+    fn start(&mut self, levels: u64) {
+        self.start_ns = get_time_ns();
+        self.levels = levels;
+        self.attached = true;
+
+        let _ = writeln!(
+            self.writer,
+            "$version rppal {} $end",
+            env!("CARGO_PKG_VERSION")
+        );
+        let _ = writeln!(self.writer, "$timescale 1 ns $end");
+        let _ = writeln!(self.writer, "$scope module gpio $end");
+        for pin in self.selected() {
+            let _ = writeln!(self.writer, "$var wire 1 {} gpio{} $end", id(pin), pin);
+        }
+        let _ = writeln!(self.writer, "$upscope $end");
+        let _ = writeln!(self.writer, "$enddefinitions $end");
+        let _ = writeln!(self.writer, "#0");
+        let _ = writeln!(self.writer, "$dumpvars");
+        for pin in self.selected() {
+            let _ = writeln!(self.writer, "{}{}", (levels >> pin) & 1, id(pin));
+        }
+        let _ = writeln!(self.writer, "$end");
+    }
+
+    // Logs any selected pins in mask whose level differs from the last known
+    // levels. The timestamp is taken while the state is locked, which keeps
+    // the timestamps in order.
+    fn update(&mut self, mask: u64, levels: u64) {
+        let changed = (self.levels ^ levels) & mask & self.pins;
+        if changed == 0 {
+            return;
+        }
+
+        let time = get_time_ns() - self.start_ns;
+        let _ = writeln!(self.writer, "#{}", time);
+
+        for pin in 0..pin::MAX as u8 {
+            if (changed >> pin) & 1 == 1 {
+                let _ = writeln!(self.writer, "{}{}", (levels >> pin) & 1, id(pin));
+            }
+        }
+
+        self.levels = (self.levels & !changed) | (levels & changed);
+    }
+
+    fn selected(&self) -> impl Iterator<Item = u8> {
+        let pins = self.pins;
+
+        (0..pin::MAX as u8).filter(move |pin| (pins >> pin) & 1 == 1)
+    }
+}
+
+fn id(pin: u8) -> char {
+    (ID_BASE + pin) as char
+}
+
+/// Captures GPIO pin activity into a VCD file.
+///
+/// `VcdTracer` is configured through [`pins`] and [`sample_period`], and
+/// attached to a [`Gpio`] instance through [`GpioBuilder::tracer`]. By default,
+/// all pins are traced, and only output transitions are captured.
+///
+/// Writing to the underlying writer is buffered. Any errors that occur while
+/// writing are ignored, but can be detected by calling [`flush`]. The buffer is
+/// flushed automatically when the last `Gpio` instance and `VcdTracer` clone go
+/// out of scope.
+///
+/// `VcdTracer` can be cloned cheaply, and all clones share the same output.
+///
+/// [`pins`]: #method.pins
+/// [`sample_period`]: #method.sample_period
+/// [`Gpio`]: ../struct.Gpio.html
+/// [`GpioBuilder::tracer`]: ../struct.GpioBuilder.html#method.tracer
+/// [`flush`]: #method.flush
+#[derive(Clone)]
+pub struct VcdTracer {
+    inner: Arc<Mutex<TraceState>>,
+}
+
+impl VcdTracer {
+    /// Constructs a new `VcdTracer` that writes to the specified file.
+    ///
+    /// The file is created if it doesn't exist, and truncated if it does.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<VcdTracer> {
+        Ok(VcdTracer::new(File::create(path)?))
+    }
+
+    /// Constructs a new `VcdTracer` that writes to the specified writer.
+    pub fn new<W: Write + Send + 'static>(writer: W) -> VcdTracer {
+        VcdTracer {
+            inner: Arc::new(Mutex::new(TraceState {
+                writer: Box::new(BufWriter::new(writer)),
+                pins: (1 << pin::MAX) - 1,
+                sample_period: None,
+                start_ns: 0,
+                levels: 0,
+                attached: false,
+            })),
+        }
+    }
+
+    /// Selects the pins that are traced.
+    ///
+    /// Invalid pin numbers are ignored. Changing the selection after the tracer
+    /// has been attached has no effect.
+    pub fn pins(self, pins: &[u8]) -> VcdTracer {
+        {
+            let mut state = self.state();
+
+            if !state.attached {
+                state.pins = pins
+                    .iter()
+                    .filter(|&&pin| (pin as usize) < pin::MAX)
+                    .fold(0, |acc, &pin| acc | (1 << pin));
+            }
+        }
+
+        self
+    }
+
+    /// Enables sampling the levels of all selected pins at the specified period,
+    /// which captures changes on input pins.
+    ///
+    /// Sampling runs on a dedicated thread, which busy-waits between samples when
+    /// `period` is short. Changes that occur between two samples aren't
+    /// captured. By default, sampling is disabled. Changing the period after the
+    /// tracer has been attached has no effect.
+    pub fn sample_period(self, period: Duration) -> VcdTracer {
+        {
+            let mut state = self.state();
+
+            if !state.attached {
+                state.sample_period = Some(period);
+            }
+        }
+
+        self
+    }
+
+    /// Flushes any buffered value changes to the underlying writer.
+    pub fn flush(&self) -> Result<()> {
+        self.state().writer.flush()?;
+
+        Ok(())
+    }
+
+    // A poisoned lock only means a thread panicked while holding it, which
+    // at worst leaves a partially written line.
+    fn state(&self) -> MutexGuard<'_, TraceState> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl fmt::Debug for VcdTracer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state();
+
+        f.debug_struct("VcdTracer")
+            .field("pins", &format_args!("{:#x}", state.pins))
+            .field("sample_period", &state.sample_period)
+            .field("attached", &state.attached)
+            .finish()
+    }
+}
+
+// Wraps any of the GPIO backends, and passes output transitions on to a
+// VcdTracer. The sampling thread reads the levels directly from the wrapped
+// backend, so it isn't affected by any other wrappers.
+#[derive(Debug)]
+pub(crate) struct TracingRegisters {
+    inner: Arc<dyn GpioRegisters>,
+    tracer: VcdTracer,
+    stop: Arc<AtomicBool>,
+    sampler: Option<JoinHandle<Result<()>>>,
+}
+
+impl TracingRegisters {
+    pub(crate) fn new(
+        inner: Box<dyn GpioRegisters>,
+        tracer: VcdTracer,
+    ) -> Result<TracingRegisters> {
+        let inner: Arc<dyn GpioRegisters> = Arc::from(inner);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let sample_period = {
+            let mut state = tracer.state();
+            if !state.attached {
+                state.start(inner.levels());
+            }
+
+            state.sample_period
+        };
+
+        let sampler = match sample_period {
+            Some(period) => {
+                let inner = inner.clone();
+                let tracer = tracer.clone();
+                let stop = stop.clone();
+                let period_ns = duration_to_ns(period).max(1);
+
+                Some(sched::spawn(ThreadConfig::default(), false, move || {
+                    let mut deadline_ns = get_time_ns();
+
+                    while !stop.load(Ordering::Relaxed) {
+                        let levels = inner.levels();
+                        tracer.state().update(!0, levels);
+
+                        deadline_ns += period_ns;
+                        delay_until_ns(deadline_ns);
+                    }
+
+                    Ok(())
+                })?)
+            }
+            None => None,
+        };
+
+        Ok(TracingRegisters {
+            inner,
+            tracer,
+            stop,
+            sampler,
+        })
+    }
+
+    // Writing to GPSETn/GPCLRn doesn't affect pins that aren't configured as
+    // outputs, so those are left to the sampling thread.
+    fn output_mask(&self, mask: u64) -> u64 {
+        (0..pin::MAX as u8)
+            .filter(|&pin| (mask >> pin) & 1 == 1 && self.inner.mode(pin) == Mode::Output)
+            .fold(0, |acc, pin| acc | (1 << pin))
+    }
+
+    fn trace_write(&self, pin: u8, level: Level) {
+        if self.inner.mode(pin) != Mode::Output {
+            return;
+        }
+
+        let levels = match level {
+            Level::High => 1 << pin,
+            Level::Low => 0,
+        };
+
+        self.tracer.state().update(1 << pin, levels);
+    }
+}
+
+impl Drop for TracingRegisters {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(sampler) = self.sampler.take() {
+            let _ = sampler.join();
+        }
+    }
+}
+
+impl GpioRegisters for TracingRegisters {
+    fn soc(&self) -> SoC {
+        self.inner.soc()
+    }
+
+    fn set_high(&self, pin: u8) {
+        self.inner.set_high(pin);
+        self.trace_write(pin, Level::High);
+    }
+
+    fn set_low(&self, pin: u8) {
+        self.inner.set_low(pin);
+        self.trace_write(pin, Level::Low);
+    }
+
+    fn write_levels(&self, set_mask: u64, clear_mask: u64) {
+        self.inner.write_levels(set_mask, clear_mask);
+
+        let mask = self.output_mask(set_mask | clear_mask);
+        self.tracer.state().update(mask, set_mask & !clear_mask);
+    }
+
+    fn level(&self, pin: u8) -> Level {
+        self.inner.level(pin)
+    }
+
+    fn levels(&self) -> u64 {
+        self.inner.levels()
+    }
+
+    fn mode(&self, pin: u8) -> Mode {
+        self.inner.mode(pin)
+    }
+
+    fn set_mode(&self, pin: u8, mode: Mode) {
+        self.inner.set_mode(pin, mode)
+    }
+
+    fn set_pullupdown(&self, pin: u8, pud: PullUpDown) {
+        self.inner.set_pullupdown(pin, pud)
+    }
+
+    fn pullupdown(&self, pin: u8) -> Option<PullUpDown> {
+        self.inner.pullupdown(pin)
+    }
+
+    fn release(&self, pin: u8) {
+        self.inner.release(pin)
+    }
+
+    // Writes through FastIo would bypass the tracer, so OutputPin falls back
+    // to set_high() and set_low().
+    fn fast_io(&self, _pin: u8) -> Option<FastIo> {
+        None
+    }
+
+    fn register_ptr(&self, offset: usize) -> Result<*mut u32> {
+        self.inner.register_ptr(offset)
+    }
+
+    fn set_edge_detect(&self, pin: u8, trigger: Trigger) -> Result<()> {
+        self.inner.set_edge_detect(pin, trigger)
+    }
+
+    fn take_edge_event(&self, pin: u8) -> Result<bool> {
+        self.inner.take_edge_event(pin)
+    }
+}
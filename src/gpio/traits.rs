@@ -0,0 +1,209 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::expander;
+use crate::gpio::{AltPin, InputPin, IoPin, Level, OutputPin};
+use crate::Result;
+
+/// Common interface for pins that can be read.
+///
+/// `InputPinLike` is implemented by [`InputPin`], [`OutputPin`], [`AltPin`] and
+/// [`IoPin`], as well as by the [`expander`] module's `InputPin`. Drivers that
+/// accept an `InputPinLike`, or a `Box<dyn InputPinLike>`, work with any of those
+/// pin types, without depending on a specific version of `embedded-hal`.
+///
+/// Errors are returned as the crate-wide [`rppal::Error`], which wraps the
+/// module-specific error types. Reading a GPIO pin never fails.
+///
+/// [`InputPin`]: struct.InputPin.html
+/// [`OutputPin`]: struct.OutputPin.html
+/// [`AltPin`]: struct.AltPin.html
+/// [`IoPin`]: struct.IoPin.html
+/// [`expander`]: ../expander/index.html
+/// [`rppal::Error`]: ../enum.Error.html
+pub trait InputPinLike {
+    /// Returns the pin number.
+    fn pin(&self) -> u8;
+
+    /// Reads the pin's logic level.
+    fn read(&self) -> Result<Level>;
+
+    /// Returns `true` if the pin's logic level is low.
+    fn is_low(&self) -> Result<bool> {
+        Ok(self.read()? == Level::Low)
+    }
+
+    /// Returns `true` if the pin's logic level is high.
+    fn is_high(&self) -> Result<bool> {
+        Ok(self.read()? == Level::High)
+    }
+}
+
+/// Common interface for pins that can be driven low or high.
+///
+/// `OutputPinLike` is implemented by [`OutputPin`], [`AltPin`] and [`IoPin`], as
+/// well as by the [`expander`] module's `OutputPin`. Drivers that accept an
+/// `OutputPinLike`, or a `Box<dyn OutputPinLike>`, work with any of those pin
+/// types, without depending on a specific version of `embedded-hal`.
+///
+/// Errors are returned as the crate-wide [`rppal::Error`]. Writing to a GPIO pin
+/// never fails.
+///
+/// [`OutputPin`]: struct.OutputPin.html
+/// [`AltPin`]: struct.AltPin.html
+/// [`IoPin`]: struct.IoPin.html
+/// [`expander`]: ../expander/index.html
+/// [`rppal::Error`]: ../enum.Error.html
+pub trait OutputPinLike {
+    /// Returns the pin number.
+    fn pin(&self) -> u8;
+
+    /// Sets the pin's output level.
+    fn write(&mut self, level: Level) -> Result<()>;
+
+    /// Sets the pin's output level to low.
+    fn set_low(&mut self) -> Result<()> {
+        self.write(Level::Low)
+    }
+
+    /// Sets the pin's output level to high.
+    fn set_high(&mut self) -> Result<()> {
+        self.write(Level::High)
+    }
+
+    /// Toggles the pin's output level between low and high.
+    fn toggle(&mut self) -> Result<()>;
+}
+
+macro_rules! impl_input_pin_like {
+    ($pin:ty) => {
+        impl InputPinLike for $pin {
+            fn pin(&self) -> u8 {
+                <$pin>::pin(self)
+            }
+
+            fn read(&self) -> Result<Level> {
+                Ok(<$pin>::read(self))
+            }
+        }
+    };
+}
+
+macro_rules! impl_output_pin_like {
+    ($pin:ty) => {
+        impl OutputPinLike for $pin {
+            fn pin(&self) -> u8 {
+                <$pin>::pin(self)
+            }
+
+            fn write(&mut self, level: Level) -> Result<()> {
+                <$pin>::write(self, level);
+
+                Ok(())
+            }
+
+            fn toggle(&mut self) -> Result<()> {
+                <$pin>::toggle(self);
+
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_input_pin_like!(InputPin);
+impl_input_pin_like!(OutputPin);
+impl_input_pin_like!(AltPin);
+impl_input_pin_like!(IoPin);
+impl_output_pin_like!(OutputPin);
+impl_output_pin_like!(AltPin);
+impl_output_pin_like!(IoPin);
+
+impl InputPinLike for expander::InputPin {
+    fn pin(&self) -> u8 {
+        expander::InputPin::pin(self)
+    }
+
+    fn read(&self) -> Result<Level> {
+        Ok(expander::InputPin::read(self)?)
+    }
+}
+
+impl OutputPinLike for expander::OutputPin {
+    fn pin(&self) -> u8 {
+        expander::OutputPin::pin(self)
+    }
+
+    fn write(&mut self, level: Level) -> Result<()> {
+        Ok(expander::OutputPin::write(self, level)?)
+    }
+
+    fn toggle(&mut self) -> Result<()> {
+        Ok(expander::OutputPin::toggle(self)?)
+    }
+}
+
+impl<T: InputPinLike + ?Sized> InputPinLike for &T {
+    fn pin(&self) -> u8 {
+        (**self).pin()
+    }
+
+    fn read(&self) -> Result<Level> {
+        (**self).read()
+    }
+}
+
+impl<T: InputPinLike + ?Sized> InputPinLike for Box<T> {
+    fn pin(&self) -> u8 {
+        (**self).pin()
+    }
+
+    fn read(&self) -> Result<Level> {
+        (**self).read()
+    }
+}
+
+impl<T: OutputPinLike + ?Sized> OutputPinLike for &mut T {
+    fn pin(&self) -> u8 {
+        (**self).pin()
+    }
+
+    fn write(&mut self, level: Level) -> Result<()> {
+        (**self).write(level)
+    }
+
+    fn toggle(&mut self) -> Result<()> {
+        (**self).toggle()
+    }
+}
+
+impl<T: OutputPinLike + ?Sized> OutputPinLike for Box<T> {
+    fn pin(&self) -> u8 {
+        (**self).pin()
+    }
+
+    fn write(&mut self, level: Level) -> Result<()> {
+        (**self).write(level)
+    }
+
+    fn toggle(&mut self) -> Result<()> {
+        (**self).toggle()
+    }
+}
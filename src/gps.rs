@@ -0,0 +1,74 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! NMEA 0183 GPS receiver support.
+//!
+//! [`Gps`] reads NMEA 0183 sentences from a GPS receiver connected through
+//! [`Uart`], and parses the RMC (recommended minimum data) and GGA (fix data)
+//! sentences, which contain the position, UTC time and date, speed, course and
+//! fix quality. Sentences can be read through a blocking iterator, or, with the
+//! `async` feature enabled, through a [`SentenceStream`].
+//!
+//! Receivers with a pulse-per-second (PPS) output can be connected to a GPIO
+//! pin, which timestamps the start of every second through a GPIO interrupt.
+//!
+//! Individual sentences can be parsed with [`parse`].
+//!
+//! [`Gps`]: struct.Gps.html
+//! [`Uart`]: ../uart/struct.Uart.html
+//! [`SentenceStream`]: struct.SentenceStream.html
+//! [`parse`]: fn.parse.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::gpio;
+use crate::uart;
+
+mod nmea;
+mod receiver;
+#[cfg(feature = "async")]
+mod stream;
+
+pub use self::nmea::{parse, FixQuality, Gga, Rmc, Sentence, Time};
+pub use self::receiver::{Gps, Sentences};
+#[cfg(feature = "async")]
+pub use self::stream::SentenceStream;
+
+quick_error! {
+/// Errors that can occur when reading from a GPS receiver.
+    #[derive(Debug)]
+    pub enum Error {
+/// UART error.
+        Uart(err: uart::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Invalid sentence.
+///
+/// The sentence doesn't start with `$`, or one of its fields couldn't be parsed.
+        InvalidSentence { description("invalid sentence") }
+/// Checksum mismatch.
+        Checksum { description("checksum mismatch") }
+    }
+}
+
+/// Result type returned from methods that can have `gps::Error`s.
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,420 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::str::FromStr;
+
+use crate::gps::{Error, Result};
+use crate::rtc::DateTime;
+
+/// UTC time of day.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Time {
+    hour: u8,
+    minute: u8,
+    second: u8,
+    millisecond: u16,
+}
+
+impl Time {
+    /// Returns the hour (0-23).
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Returns the minute (0-59).
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Returns the second (0-60). A value of 60 indicates a leap second.
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Returns the millisecond (0-999).
+    pub fn millisecond(&self) -> u16 {
+        self.millisecond
+    }
+}
+
+/// GGA fix quality indicator.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum FixQuality {
+    /// No fix.
+    Invalid,
+    /// Standard GPS fix.
+    Gps,
+    /// Differential GPS fix.
+    Dgps,
+    /// Precise Positioning Service fix.
+    Pps,
+    /// Real-time kinematic fix with fixed integers.
+    Rtk,
+    /// Real-time kinematic fix with floating integers.
+    FloatRtk,
+    /// Estimated position (dead reckoning).
+    Estimated,
+    /// Manual input mode.
+    Manual,
+    /// Simulation mode.
+    Simulation,
+}
+
+/// Recommended minimum data (RMC) sentence.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rmc {
+    time: Option<Time>,
+    date: Option<(u8, u8, u8)>,
+    valid: bool,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    speed: Option<f64>,
+    course: Option<f64>,
+}
+
+impl Rmc {
+    /// Returns the UTC time of the fix.
+    pub fn time(&self) -> Option<Time> {
+        self.time
+    }
+
+    /// Returns the UTC date and time of the fix, rounded down to the second.
+    pub fn datetime(&self) -> Option<DateTime> {
+        let (day, month, year) = self.date?;
+        let time = self.time?;
+
+        DateTime::new(
+            2000 + u16::from(year),
+            month,
+            day,
+            time.hour,
+            time.minute,
+            time.second.min(59),
+        )
+        .ok()
+    }
+
+    /// Returns `true` if the receiver reported the fix as valid.
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Returns the latitude in decimal degrees. Positive values are north of the
+    /// equator.
+    pub fn latitude(&self) -> Option<f64> {
+        self.latitude
+    }
+
+    /// Returns the longitude in decimal degrees. Positive values are east of the
+    /// prime meridian.
+    pub fn longitude(&self) -> Option<f64> {
+        self.longitude
+    }
+
+    /// Returns the speed over ground in knots.
+    pub fn speed(&self) -> Option<f64> {
+        self.speed
+    }
+
+    /// Returns the speed over ground in kilometers per hour (km/h).
+    pub fn speed_kmh(&self) -> Option<f64> {
+        self.speed.map(|speed| speed * 1.852)
+    }
+
+    /// Returns the course over ground in degrees, relative to true north.
+    pub fn course(&self) -> Option<f64> {
+        self.course
+    }
+}
+
+/// Global positioning system fix data (GGA) sentence.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Gga {
+    time: Option<Time>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    fix_quality: FixQuality,
+    satellites: Option<u8>,
+    hdop: Option<f64>,
+    altitude: Option<f64>,
+}
+
+impl Gga {
+    /// Returns the UTC time of the fix.
+    pub fn time(&self) -> Option<Time> {
+        self.time
+    }
+
+    /// Returns the latitude in decimal degrees. Positive values are north of the
+    /// equator.
+    pub fn latitude(&self) -> Option<f64> {
+        self.latitude
+    }
+
+    /// Returns the longitude in decimal degrees. Positive values are east of the
+    /// prime meridian.
+    pub fn longitude(&self) -> Option<f64> {
+        self.longitude
+    }
+
+    /// Returns the fix quality.
+    pub fn fix_quality(&self) -> FixQuality {
+        self.fix_quality
+    }
+
+    /// Returns the number of satellites used for the fix.
+    pub fn satellites(&self) -> Option<u8> {
+        self.satellites
+    }
+
+    /// Returns the horizontal dilution of precision.
+    pub fn hdop(&self) -> Option<f64> {
+        self.hdop
+    }
+
+    /// Returns the altitude above mean sea level in meters (m).
+    pub fn altitude(&self) -> Option<f64> {
+        self.altitude
+    }
+}
+
+/// A parsed NMEA 0183 sentence.
+///
+/// The talker ID (`GP`, `GL`, `GA`, `GN`, etc.) is ignored, so sentences from
+/// multi-constellation receivers are handled the same way as those from GPS-only
+/// receivers.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Sentence {
+    /// Recommended minimum data.
+    Rmc(Rmc),
+    /// Fix data.
+    Gga(Gga),
+    /// Any other sentence with a valid checksum, which is left unparsed.
+    /// Contains the sentence without the leading `$` and the checksum.
+    Other(String),
+}
+
+/// Parses a single NMEA 0183 sentence.
+///
+/// `sentence` should start with `$`, and may end with a line terminator. If
+/// the sentence includes a checksum, it's verified, and [`Error::Checksum`] is
+/// returned on a mismatch.
+///
+/// [`Error::Checksum`]: enum.Error.html#variant.Checksum
+pub fn parse(sentence: &str) -> Result<Sentence> {
+    let sentence = sentence.trim_end_matches(&['\r', '\n'][..]);
+    if !sentence.starts_with('$') {
+        return Err(Error::InvalidSentence);
+    }
+
+    let body = match sentence[1..].rfind('*') {
+        Some(idx) => {
+            let (body, checksum) = (&sentence[1..=idx], &sentence[idx + 2..]);
+            let checksum = u8::from_str_radix(checksum, 16).map_err(|_| Error::InvalidSentence)?;
+
+            if body.bytes().fold(0, |acc, b| acc ^ b) != checksum {
+                return Err(Error::Checksum);
+            }
+
+            body
+        }
+        None => &sentence[1..],
+    };
+
+    let fields: Vec<&str> = body.split(',').collect();
+    // Skip the talker ID
+    let kind = fields[0].get(2..).ok_or(Error::InvalidSentence)?;
+
+    match kind {
+        "RMC" => parse_rmc(&fields).map(Sentence::Rmc),
+        "GGA" => parse_gga(&fields).map(Sentence::Gga),
+        _ => Ok(Sentence::Other(body.to_owned())),
+    }
+}
+
+fn parse_rmc(fields: &[&str]) -> Result<Rmc> {
+    if fields.len() < 10 {
+        return Err(Error::InvalidSentence);
+    }
+
+    Ok(Rmc {
+        time: parse_time(fields[1])?,
+        valid: fields[2] == "A",
+        latitude: parse_coordinate(fields[3], fields[4], 2)?,
+        longitude: parse_coordinate(fields[5], fields[6], 3)?,
+        speed: parse_number(fields[7])?,
+        course: parse_number(fields[8])?,
+        date: parse_date(fields[9])?,
+    })
+}
+
+fn parse_gga(fields: &[&str]) -> Result<Gga> {
+    if fields.len() < 10 {
+        return Err(Error::InvalidSentence);
+    }
+
+    let fix_quality = match fields[6] {
+        "1" => FixQuality::Gps,
+        "2" => FixQuality::Dgps,
+        "3" => FixQuality::Pps,
+        "4" => FixQuality::Rtk,
+        "5" => FixQuality::FloatRtk,
+        "6" => FixQuality::Estimated,
+        "7" => FixQuality::Manual,
+        "8" => FixQuality::Simulation,
+        _ => FixQuality::Invalid,
+    };
+
+    Ok(Gga {
+        time: parse_time(fields[1])?,
+        latitude: parse_coordinate(fields[2], fields[3], 2)?,
+        longitude: parse_coordinate(fields[4], fields[5], 3)?,
+        fix_quality,
+        satellites: parse_number(fields[7])?,
+        hdop: parse_number(fields[8])?,
+        altitude: parse_number(fields[9])?,
+    })
+}
+
+// Parses an optional numeric field, which is left empty when unavailable
+fn parse_number<T: FromStr>(field: &str) -> Result<Option<T>> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+
+    field.parse().map(Some).map_err(|_| Error::InvalidSentence)
+}
+
+// Parses hhmmss.sss
+fn parse_time(field: &str) -> Result<Option<Time>> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+
+    if field.len() < 6 || !field.as_bytes()[..6].iter().all(u8::is_ascii_digit) {
+        return Err(Error::InvalidSentence);
+    }
+
+    let fraction = match &field[6..] {
+        "" => 0.0,
+        fraction => fraction
+            .parse::<f64>()
+            .map_err(|_| Error::InvalidSentence)?,
+    };
+
+    let time = Time {
+        hour: field[0..2].parse().map_err(|_| Error::InvalidSentence)?,
+        minute: field[2..4].parse().map_err(|_| Error::InvalidSentence)?,
+        second: field[4..6].parse().map_err(|_| Error::InvalidSentence)?,
+        millisecond: ((fraction * 1000.0).round() as u16).min(999),
+    };
+
+    if time.hour > 23 || time.minute > 59 || time.second > 60 {
+        return Err(Error::InvalidSentence);
+    }
+
+    Ok(Some(time))
+}
+
+// Parses ddmmyy
+fn parse_date(field: &str) -> Result<Option<(u8, u8, u8)>> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+
+    if field.len() != 6 || !field.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::InvalidSentence);
+    }
+
+    let value =
+        |idx: usize| (field.as_bytes()[idx] - b'0') * 10 + (field.as_bytes()[idx + 1] - b'0');
+
+    Ok(Some((value(0), value(2), value(4))))
+}
+
+// Parses a (d)ddmm.mmmm coordinate and its hemisphere into decimal degrees
+fn parse_coordinate(field: &str, hemisphere: &str, degree_digits: usize) -> Result<Option<f64>> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+
+    if field.len() < degree_digits
+        || !field.as_bytes()[..degree_digits]
+            .iter()
+            .all(u8::is_ascii_digit)
+    {
+        return Err(Error::InvalidSentence);
+    }
+
+    let degrees: f64 = field[..degree_digits]
+        .parse()
+        .map_err(|_| Error::InvalidSentence)?;
+    let minutes: f64 = field[degree_digits..]
+        .parse()
+        .map_err(|_| Error::InvalidSentence)?;
+
+    let value = degrees + minutes / 60.0;
+
+    match hemisphere {
+        "N" | "E" => Ok(Some(value)),
+        "S" | "W" => Ok(Some(-value)),
+        _ => Err(Error::InvalidSentence),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rmc() {
+        let sentence =
+            parse("$GPRMC,123456,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W").unwrap();
+
+        match sentence {
+            Sentence::Rmc(rmc) => {
+                assert_eq!(rmc.time().map(|time| time.second()), Some(56));
+                assert!((rmc.latitude().unwrap() - 48.1173).abs() < 1e-9);
+                assert!((rmc.longitude().unwrap() - 11.516_666_666).abs() < 1e-6);
+            }
+            _ => panic!("expected an RMC sentence"),
+        }
+    }
+
+    #[test]
+    fn reject_non_ascii_time() {
+        assert!(matches!(
+            parse("$GPRMC,1\u{e9}3456,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W"),
+            Err(Error::InvalidSentence)
+        ));
+    }
+
+    #[test]
+    fn reject_signed_degrees() {
+        assert!(matches!(
+            parse("$GPRMC,123456,A,+807.038,N,01131.000,E,022.4,084.4,230394,003.1,W"),
+            Err(Error::InvalidSentence)
+        ));
+        assert!(matches!(
+            parse("$GPRMC,123456,A,4807.038,N,-1131.000,E,022.4,084.4,230394,003.1,W"),
+            Err(Error::InvalidSentence)
+        ));
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::gpio::{InputPin, Trigger};
+use crate::gps::nmea::{self, Sentence};
+use crate::gps::Result;
+#[cfg(feature = "async")]
+use crate::gps::SentenceStream;
+use crate::uart::Uart;
+
+// NMEA 0183 limits sentences to 82 characters, but some receivers exceed that
+// for proprietary sentences
+pub(crate) const MAX_SENTENCE_LEN: usize = 256;
+
+/// NMEA 0183 GPS receiver connected through UART.
+///
+/// `Gps` reads incoming data from a [`Uart`], splits it into sentences, and
+/// parses them with [`parse`]. Most receivers default to 9600 Bd, 8 data bits,
+/// no parity and 1 stop bit.
+///
+/// If the receiver has a pulse-per-second (PPS) output, it can be connected to
+/// a GPIO pin with [`set_pps_pin`], after which [`pps_timestamp`] returns the
+/// kernel timestamp of the most recent pulse. The pulse marks the start of the
+/// second reported by the sentences that follow it.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gps::{Gps, Sentence};
+/// use rppal::uart::{Device, Parity, Uart};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut gps = Gps::new(Uart::new(Device::Uart0, 9600, Parity::None, 8, 1)?)?;
+///
+/// for sentence in gps.sentences() {
+///     if let Sentence::Rmc(rmc) = sentence? {
+///         println!("{:?} {:?}", rmc.latitude(), rmc.longitude());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Uart`]: ../uart/struct.Uart.html
+/// [`parse`]: fn.parse.html
+/// [`set_pps_pin`]: #method.set_pps_pin
+/// [`pps_timestamp`]: #method.pps_timestamp
+#[derive(Debug)]
+pub struct Gps {
+    uart: Uart,
+    buffer: Vec<u8>,
+    pps: Option<Pps>,
+}
+
+impl Gps {
+    /// Constructs a new `Gps`.
+    ///
+    /// The read mode of `uart` is changed to block until data is received.
+    pub fn new(mut uart: Uart) -> Result<Gps> {
+        uart.set_read_mode(1, Duration::default())?;
+
+        Ok(Gps {
+            uart,
+            buffer: Vec::new(),
+            pps: None,
+        })
+    }
+
+    /// Blocks until a complete sentence is received, and returns the parsed
+    /// sentence.
+    ///
+    /// Any partial sentence received before the first `$` is discarded.
+    pub fn read_sentence(&mut self) -> Result<Sentence> {
+        let mut buffer = [0u8; 64];
+
+        loop {
+            if let Some(line) = next_line(&mut self.buffer) {
+                return nmea::parse(&line);
+            }
+
+            let len = self.uart.read(&mut buffer)?;
+            self.buffer.extend_from_slice(&buffer[..len]);
+        }
+    }
+
+    /// Returns a blocking iterator over incoming sentences.
+    ///
+    /// The iterator never ends. Errors that occur while reading or parsing a
+    /// sentence are returned as `Some(Err(_))`, after which the next call to `next`
+    /// continues with the following sentence.
+    pub fn sentences(&mut self) -> Sentences<'_> {
+        Sentences { gps: self }
+    }
+
+    /// Configures `pin`, connected to the receiver's PPS output, with an
+    /// asynchronous interrupt trigger that records the timestamp of each rising
+    /// edge.
+    ///
+    /// Any previously configured PPS pin is returned.
+    pub fn set_pps_pin(&mut self, pin: InputPin) -> Result<Option<InputPin>> {
+        let previous = self.clear_pps_pin();
+        self.pps = Some(Pps::new(pin)?);
+
+        Ok(previous)
+    }
+
+    /// Removes the PPS pin, and returns it.
+    pub fn clear_pps_pin(&mut self) -> Option<InputPin> {
+        self.pps.take().map(Pps::into_pin)
+    }
+
+    /// Returns the kernel timestamp of the most recent PPS pulse, or `None` if
+    /// no pulse has been received, or no PPS pin is configured.
+    ///
+    /// The timestamp is taken from the GPIO interrupt [`Event`]. Only the
+    /// difference between timestamps should be relied upon.
+    ///
+    /// [`Event`]: ../gpio/struct.Event.html
+    pub fn pps_timestamp(&self) -> Option<Duration> {
+        self.pps.as_ref().and_then(Pps::timestamp)
+    }
+
+    /// Consumes the `Gps`, and returns a [`SentenceStream`] that yields every
+    /// incoming sentence.
+    ///
+    /// The stream waits for incoming data through tokio's `AsyncFd`, and must be
+    /// created from within a tokio runtime with IO enabled. Any configured PPS pin
+    /// is moved to the stream.
+    ///
+    /// `into_stream` is only available when the `async` feature is enabled.
+    ///
+    /// [`SentenceStream`]: struct.SentenceStream.html
+    #[cfg(feature = "async")]
+    pub fn into_stream(self) -> Result<SentenceStream> {
+        SentenceStream::new(self)
+    }
+
+    /// Returns a reference to the underlying `Uart`.
+    pub fn uart(&self) -> &Uart {
+        &self.uart
+    }
+
+    /// Consumes the `Gps`, and returns the underlying `Uart` instance.
+    pub fn into_uart(self) -> Uart {
+        self.uart
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn into_parts(self) -> (Uart, Vec<u8>, Option<Pps>) {
+        (self.uart, self.buffer, self.pps)
+    }
+}
+
+/// Blocking iterator over incoming sentences.
+///
+/// `Sentences` is returned by [`Gps::sentences`].
+///
+/// [`Gps::sentences`]: struct.Gps.html#method.sentences
+#[derive(Debug)]
+pub struct Sentences<'a> {
+    gps: &'a mut Gps,
+}
+
+impl<'a> Iterator for Sentences<'a> {
+    type Item = Result<Sentence>;
+
+    fn next(&mut self) -> Option<Result<Sentence>> {
+        Some(self.gps.read_sentence())
+    }
+}
+
+// Records the timestamp of the most recent rising edge on the PPS pin
+#[derive(Debug)]
+pub(crate) struct Pps {
+    pin: InputPin,
+    timestamp: Arc<Mutex<Option<Duration>>>,
+}
+
+impl Pps {
+    fn new(mut pin: InputPin) -> Result<Pps> {
+        let timestamp = Arc::new(Mutex::new(None));
+
+        let callback_timestamp = timestamp.clone();
+        pin.set_async_interrupt(Trigger::RisingEdge, move |event| {
+            if let Ok(mut timestamp) = callback_timestamp.lock() {
+                *timestamp = Some(event.timestamp());
+            }
+        })?;
+
+        Ok(Pps { pin, timestamp })
+    }
+
+    pub(crate) fn timestamp(&self) -> Option<Duration> {
+        self.timestamp.lock().ok().and_then(|timestamp| *timestamp)
+    }
+
+    fn into_pin(mut self) -> InputPin {
+        let _ = self.pin.clear_async_interrupt();
+
+        self.pin
+    }
+}
+
+// Removes the first complete sentence from buffer, and returns it. Data preceding
+// the first $ is discarded, as are sentences that exceed MAX_SENTENCE_LEN.
+pub(crate) fn next_line(buffer: &mut Vec<u8>) -> Option<String> {
+    loop {
+        let start = match buffer.iter().position(|&b| b == b'$') {
+            Some(start) => start,
+            None => {
+                buffer.clear();
+                return None;
+            }
+        };
+
+        let end = match buffer[start..].iter().position(|&b| b == b'\n') {
+            Some(end) => start + end,
+            None => {
+                buffer.drain(..start);
+                if buffer.len() > MAX_SENTENCE_LEN {
+                    buffer.clear();
+                }
+
+                return None;
+            }
+        };
+
+        let line = buffer[start..end].to_vec();
+        buffer.drain(..=end);
+
+        if line.len() <= MAX_SENTENCE_LEN {
+            if let Ok(line) = String::from_utf8(line) {
+                return Some(line);
+            }
+        }
+    }
+}
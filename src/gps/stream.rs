@@ -0,0 +1,106 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::future::poll_fn;
+use std::pin::Pin as StdPin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::io::unix::AsyncFd;
+
+use crate::gps::nmea::{self, Sentence};
+use crate::gps::receiver::{next_line, Pps};
+use crate::gps::{Error, Gps, Result};
+use crate::uart::{self, Uart};
+
+/// Asynchronous stream of incoming sentences.
+///
+/// `SentenceStream` owns the [`Uart`] used by a [`Gps`], and yields a parsed
+/// [`Sentence`] for every complete sentence. Incoming data is awaited through
+/// tokio's `AsyncFd`, so no additional threads are needed.
+///
+/// `SentenceStream` is returned by [`Gps::into_stream`], and is only available
+/// when the `async` feature is enabled. It must be created and used from within
+/// a tokio runtime with IO enabled.
+///
+/// [`Uart`]: ../uart/struct.Uart.html
+/// [`Gps`]: struct.Gps.html
+/// [`Sentence`]: enum.Sentence.html
+/// [`Gps::into_stream`]: struct.Gps.html#method.into_stream
+#[derive(Debug)]
+pub struct SentenceStream {
+    uart: AsyncFd<Uart>,
+    buffer: Vec<u8>,
+    pps: Option<Pps>,
+}
+
+impl SentenceStream {
+    pub(crate) fn new(gps: Gps) -> Result<SentenceStream> {
+        let (uart, buffer, pps) = gps.into_parts();
+
+        uart.set_nonblocking(true)?;
+        let uart = AsyncFd::new(uart).map_err(uart::Error::Io)?;
+
+        Ok(SentenceStream { uart, buffer, pps })
+    }
+
+    /// Waits for the next complete sentence.
+    pub async fn next_sentence(&mut self) -> Result<Sentence> {
+        poll_fn(|cx| self.poll_sentence(cx)).await
+    }
+
+    /// Returns the kernel timestamp of the most recent PPS pulse, or `None` if
+    /// no pulse has been received, or no PPS pin is configured.
+    pub fn pps_timestamp(&self) -> Option<Duration> {
+        self.pps.as_ref().and_then(Pps::timestamp)
+    }
+
+    fn poll_sentence(&mut self, cx: &mut Context<'_>) -> Poll<Result<Sentence>> {
+        let mut buffer = [0u8; 64];
+
+        loop {
+            if let Some(line) = next_line(&mut self.buffer) {
+                return Poll::Ready(nmea::parse(&line));
+            }
+
+            let mut guard = match self.uart.poll_read_ready_mut(cx) {
+                Poll::Ready(Ok(guard)) => guard,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(Error::Uart(uart::Error::Io(e)))),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            match guard.get_inner_mut().read(&mut buffer) {
+                // Uart::read() returns Ok(0) when no data is available
+                Ok(0) => guard.clear_ready(),
+                Ok(len) => self.buffer.extend_from_slice(&buffer[..len]),
+                Err(e) => return Poll::Ready(Err(e.into())),
+            }
+        }
+    }
+}
+
+impl Stream for SentenceStream {
+    type Item = Result<Sentence>;
+
+    fn poll_next(self: StdPin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_sentence(cx).map(Some)
+    }
+}
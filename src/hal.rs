@@ -0,0 +1,85 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Delays, timers and bus sharing helpers for `embedded-hal` drivers.
+//!
+//! [`Delay`] implements the `embedded-hal` delay traits, and [`Timer`] provides
+//! a monotonic, periodic count-down timer that implements the `embedded-hal`
+//! 0.2 `CountDown` and `Periodic` traits.
+//!
+//! Both use `clock_nanosleep` to sleep until shortly before the requested
+//! deadline, and busy-wait for the remaining time. The last ~100 µs of every
+//! delay are spent busy-waiting, because the Linux scheduler doesn't reliably
+//! wake up a sleeping thread with that level of accuracy. This results in much
+//! shorter and more consistent delays than `thread::sleep`, at the cost of
+//! keeping a CPU core busy for short delays. Delays can still take longer than
+//! requested when the thread is preempted.
+//!
+//! `embedded-hal` 1.0 separates SPI buses (`SpiBus`) from SPI devices
+//! (`SpiDevice`). A bus only shifts data in and out, while a device also
+//! manages its own Slave Select (chip select) line, and gets exclusive access
+//! to the bus for the duration of a transaction. Most driver crates expect an
+//! `SpiDevice`.
+//!
+//! [`Spi`] implements `SpiDevice` directly using its hardware Slave Select
+//! line. [`ExclusiveDevice`] combines any `SpiBus`, such as [`SoftSpi`] or
+//! [`Spi`] configured with [`SpiConfig::with_ss_disabled`], with a GPIO pin
+//! that's used as Slave Select. [`SharedBus`] lets multiple drivers use the
+//! same SPI or I2C bus, by handing out device handles that lock the bus for
+//! each transaction.
+//!
+//! This module is available when either the optional `hal` or `hal-1` feature
+//! is enabled. [`ExclusiveDevice`] and [`SharedBus`] require the `hal-1` feature.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rppal::hal::Timer;
+//!
+//! let mut timer = Timer::new();
+//!
+//! // Run a control loop at 1 kHz
+//! timer.start(Duration::from_millis(1));
+//! loop {
+//!     // ...
+//!
+//!     timer.wait_for_expiry();
+//! }
+//! ```
+//!
+//! [`Delay`]: struct.Delay.html
+//! [`Timer`]: struct.Timer.html
+//! [`Spi`]: ../spi/struct.Spi.html
+//! [`SoftSpi`]: ../spi/struct.SoftSpi.html
+//! [`SpiConfig::with_ss_disabled`]: ../spi/struct.SpiConfig.html#method.with_ss_disabled
+//! [`ExclusiveDevice`]: struct.ExclusiveDevice.html
+//! [`SharedBus`]: struct.SharedBus.html
+
+#[cfg(feature = "hal-1")]
+mod bus;
+mod delay;
+mod timer;
+
+#[cfg(feature = "hal-1")]
+pub use self::bus::{DeviceError, ExclusiveDevice, SharedBus, SharedSpiDevice};
+pub use self::delay::Delay;
+pub use self::timer::Timer;
@@ -0,0 +1,337 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::error;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use embedded_hal_1::digital::OutputPin;
+use embedded_hal_1::i2c::{self, I2c};
+use embedded_hal_1::spi::{self, SpiBus, SpiDevice};
+
+use crate::delay::delay_ns;
+
+/// Errors that can occur during an SPI device transaction.
+#[derive(Debug)]
+pub enum DeviceError<B, C> {
+    /// SPI bus error.
+    Spi(B),
+    /// Slave Select pin error.
+    Cs(C),
+}
+
+impl<B: fmt::Display, C: fmt::Display> fmt::Display for DeviceError<B, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceError::Spi(err) => write!(f, "SPI bus error: {}", err),
+            DeviceError::Cs(err) => write!(f, "Slave Select pin error: {}", err),
+        }
+    }
+}
+
+impl<B, C> error::Error for DeviceError<B, C>
+where
+    B: fmt::Debug + fmt::Display,
+    C: fmt::Debug + fmt::Display,
+{
+}
+
+impl<B: spi::Error, C: fmt::Debug> spi::Error for DeviceError<B, C> {
+    fn kind(&self) -> spi::ErrorKind {
+        match self {
+            DeviceError::Spi(err) => err.kind(),
+            DeviceError::Cs(_) => spi::ErrorKind::ChipSelectFault,
+        }
+    }
+}
+
+// Executes a transaction on bus with cs active. cs is always set back to
+// inactive, even if one of the operations fails.
+fn device_transaction<BUS, CS>(
+    bus: &mut BUS,
+    cs: &mut CS,
+    operations: &mut [spi::Operation<'_, u8>],
+) -> Result<(), DeviceError<BUS::Error, CS::Error>>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+{
+    cs.set_low().map_err(DeviceError::Cs)?;
+
+    let result = operations
+        .iter_mut()
+        .try_for_each(|operation| match operation {
+            spi::Operation::Read(words) => bus.read(words),
+            spi::Operation::Write(words) => bus.write(words),
+            spi::Operation::Transfer(read, write) => bus.transfer(read, write),
+            spi::Operation::TransferInPlace(words) => bus.transfer_in_place(words),
+            spi::Operation::DelayNs(ns) => {
+                bus.flush()?;
+                delay_ns(u64::from(*ns));
+
+                Ok(())
+            }
+        });
+
+    // Wait until all data has been shifted out before deactivating Slave Select
+    let flush_result = bus.flush();
+    let cs_result = cs.set_high();
+
+    result.map_err(DeviceError::Spi)?;
+    flush_result.map_err(DeviceError::Spi)?;
+    cs_result.map_err(DeviceError::Cs)?;
+
+    Ok(())
+}
+
+/// An SPI device with exclusive access to an SPI bus.
+///
+/// `ExclusiveDevice` implements [`SpiDevice`] by combining an [`SpiBus`] with
+/// a GPIO pin that's used as Slave Select. Slave Select is set to active (low)
+/// at the start of each transaction, and inactive (high) when the transaction
+/// completes.
+///
+/// When using [`Spi`] as the bus, make sure its hardware Slave Select line is
+/// disabled with [`SpiConfig::with_ss_disabled`], or isn't connected to any
+/// device. When using [`SoftSpi`], don't configure a Slave Select pin for
+/// the bus itself.
+///
+/// [`SpiBus`]: https://docs.rs/embedded-hal/1/embedded_hal/spi/trait.SpiBus.html
+/// [`SpiDevice`]: https://docs.rs/embedded-hal/1/embedded_hal/spi/trait.SpiDevice.html
+/// [`Spi`]: ../spi/struct.Spi.html
+/// [`SoftSpi`]: ../spi/struct.SoftSpi.html
+/// [`SpiConfig::with_ss_disabled`]: ../spi/struct.SpiConfig.html#method.with_ss_disabled
+#[derive(Debug)]
+pub struct ExclusiveDevice<BUS, CS> {
+    bus: BUS,
+    cs: CS,
+}
+
+impl<BUS, CS> ExclusiveDevice<BUS, CS>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+{
+    /// Constructs a new `ExclusiveDevice`.
+    ///
+    /// `cs` is set to inactive (high) before `new` returns.
+    pub fn new(bus: BUS, mut cs: CS) -> Result<ExclusiveDevice<BUS, CS>, CS::Error> {
+        cs.set_high()?;
+
+        Ok(ExclusiveDevice { bus, cs })
+    }
+
+    /// Returns a reference to the underlying SPI bus.
+    pub fn bus(&self) -> &BUS {
+        &self.bus
+    }
+
+    /// Returns a mutable reference to the underlying SPI bus.
+    ///
+    /// Slave Select isn't changed when the bus is accessed directly.
+    pub fn bus_mut(&mut self) -> &mut BUS {
+        &mut self.bus
+    }
+
+    /// Consumes the `ExclusiveDevice`, and returns the SPI bus and Slave
+    /// Select pin.
+    pub fn release(self) -> (BUS, CS) {
+        (self.bus, self.cs)
+    }
+}
+
+impl<BUS, CS> spi::ErrorType for ExclusiveDevice<BUS, CS>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+{
+    type Error = DeviceError<BUS::Error, CS::Error>;
+}
+
+impl<BUS, CS> SpiDevice<u8> for ExclusiveDevice<BUS, CS>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+{
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        device_transaction(&mut self.bus, &mut self.cs, operations)
+    }
+}
+
+/// A bus that can be shared between multiple drivers.
+///
+/// `SharedBus` wraps an SPI or I2C bus in an `Arc<Mutex>`. Cloning a
+/// `SharedBus` returns a new handle to the same bus, which makes it possible
+/// to pass a separate handle to each driver, including drivers that run on
+/// different threads.
+///
+/// When the underlying bus implements [`I2c`], every `SharedBus` handle
+/// implements [`I2c`] as well. Use [`spi_device`] to create an [`SpiDevice`]
+/// with its own Slave Select pin for an underlying [`SpiBus`].
+///
+/// The bus is locked for the duration of each transaction, so transactions
+/// from different handles are never interleaved.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::Gpio;
+/// use rppal::hal::SharedBus;
+/// use rppal::i2c::I2c;
+/// use rppal::spi::{Mode, SoftSpi};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gpio = Gpio::new()?;
+///
+/// // Two SPI devices sharing a software-based SPI bus, each with its own Slave Select pin
+/// let spi = SoftSpi::new(
+///     gpio.get(11).unwrap().into_output(),
+///     Some(gpio.get(10).unwrap().into_output()),
+///     Some(gpio.get(9).unwrap().into_input()),
+///     None,
+///     1_000_000,
+///     Mode::Mode0,
+/// )?;
+/// let spi_bus = SharedBus::new(spi);
+/// let _display = spi_bus.spi_device(gpio.get(8).unwrap().into_output_high())?;
+/// let _sensor = spi_bus.spi_device(gpio.get(7).unwrap().into_output_high())?;
+///
+/// // Two I2C device handles using the same I2C bus
+/// let i2c_bus = SharedBus::new(I2c::new()?);
+/// let _rtc = i2c_bus.clone();
+/// let _imu = i2c_bus.clone();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`I2c`]: https://docs.rs/embedded-hal/1/embedded_hal/i2c/trait.I2c.html
+/// [`SpiBus`]: https://docs.rs/embedded-hal/1/embedded_hal/spi/trait.SpiBus.html
+/// [`SpiDevice`]: https://docs.rs/embedded-hal/1/embedded_hal/spi/trait.SpiDevice.html
+/// [`spi_device`]: #method.spi_device
+#[derive(Debug)]
+pub struct SharedBus<T> {
+    bus: Arc<Mutex<T>>,
+}
+
+impl<T> SharedBus<T> {
+    /// Constructs a new `SharedBus`.
+    pub fn new(bus: T) -> SharedBus<T> {
+        SharedBus {
+            bus: Arc::new(Mutex::new(bus)),
+        }
+    }
+
+    /// Creates an [`SpiDevice`] that uses `cs` as its Slave Select pin.
+    ///
+    /// `cs` is set to inactive (high) before `spi_device` returns.
+    ///
+    /// [`SpiDevice`]: https://docs.rs/embedded-hal/1/embedded_hal/spi/trait.SpiDevice.html
+    pub fn spi_device<CS>(&self, mut cs: CS) -> Result<SharedSpiDevice<T, CS>, CS::Error>
+    where
+        T: SpiBus<u8>,
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+
+        Ok(SharedSpiDevice {
+            bus: self.clone(),
+            cs,
+        })
+    }
+
+    // A poisoned lock only means another thread panicked during a transaction,
+    // which doesn't leave the bus in an invalid state.
+    fn lock(&self) -> MutexGuard<'_, T> {
+        self.bus.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<T> Clone for SharedBus<T> {
+    fn clone(&self) -> SharedBus<T> {
+        SharedBus {
+            bus: Arc::clone(&self.bus),
+        }
+    }
+}
+
+impl<T: i2c::ErrorType> i2c::ErrorType for SharedBus<T> {
+    type Error = T::Error;
+}
+
+impl<A, T> I2c<A> for SharedBus<T>
+where
+    A: i2c::AddressMode,
+    T: I2c<A>,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.lock().transaction(address, operations)
+    }
+}
+
+/// An SPI device on a [`SharedBus`].
+///
+/// `SharedSpiDevice` is created by [`SharedBus::spi_device`]. The bus is
+/// locked, and Slave Select is set to active (low), for the duration of each
+/// transaction.
+///
+/// [`SharedBus`]: struct.SharedBus.html
+/// [`SharedBus::spi_device`]: struct.SharedBus.html#method.spi_device
+#[derive(Debug)]
+pub struct SharedSpiDevice<BUS, CS> {
+    bus: SharedBus<BUS>,
+    cs: CS,
+}
+
+impl<BUS, CS> SharedSpiDevice<BUS, CS> {
+    /// Consumes the `SharedSpiDevice`, and returns its Slave Select pin.
+    pub fn release(self) -> CS {
+        self.cs
+    }
+}
+
+impl<BUS, CS> spi::ErrorType for SharedSpiDevice<BUS, CS>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+{
+    type Error = DeviceError<BUS::Error, CS::Error>;
+}
+
+impl<BUS, CS> SpiDevice<u8> for SharedSpiDevice<BUS, CS>
+where
+    BUS: SpiBus<u8>,
+    CS: OutputPin,
+{
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        let mut bus = self.bus.lock();
+
+        device_transaction(&mut *bus, &mut self.cs, operations)
+    }
+}
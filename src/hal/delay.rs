@@ -0,0 +1,119 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+#[cfg(feature = "hal")]
+use embedded_hal::blocking::delay;
+#[cfg(feature = "hal-1")]
+use embedded_hal_1::delay as delay_1;
+
+use crate::delay::delay_ns;
+
+/// Provides accurate blocking delays.
+///
+/// `Delay` implements the `embedded-hal` 0.2 `DelayUs` and `DelayMs` traits
+/// (`hal` feature), and the `embedded-hal` 1.0 `DelayNs` trait (`hal-1`
+/// feature), which makes it suitable for driver crates that need accurate short
+/// delays.
+///
+/// Delays sleep until shortly before the requested duration has elapsed, and
+/// busy-wait for the remaining time, using the same implementation as the
+/// [`delay`] module.
+///
+/// [`delay`]: ../delay/index.html
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Delay;
+
+impl Delay {
+    /// Constructs a new `Delay`.
+    pub fn new() -> Delay {
+        Delay
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl delay_1::DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        delay_ns(u64::from(ns));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        delay_ns(u64::from(us) * 1_000);
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        delay_ns(u64::from(ms) * 1_000_000);
+    }
+}
+
+#[cfg(feature = "hal")]
+impl delay::DelayUs<u8> for Delay {
+    fn delay_us(&mut self, us: u8) {
+        delay_ns(u64::from(us) * 1_000);
+    }
+}
+
+#[cfg(feature = "hal")]
+impl delay::DelayUs<u16> for Delay {
+    fn delay_us(&mut self, us: u16) {
+        delay_ns(u64::from(us) * 1_000);
+    }
+}
+
+#[cfg(feature = "hal")]
+impl delay::DelayUs<u32> for Delay {
+    fn delay_us(&mut self, us: u32) {
+        delay_ns(u64::from(us) * 1_000);
+    }
+}
+
+#[cfg(feature = "hal")]
+impl delay::DelayUs<u64> for Delay {
+    fn delay_us(&mut self, us: u64) {
+        delay_ns(us.saturating_mul(1_000));
+    }
+}
+
+#[cfg(feature = "hal")]
+impl delay::DelayMs<u8> for Delay {
+    fn delay_ms(&mut self, ms: u8) {
+        delay_ns(u64::from(ms) * 1_000_000);
+    }
+}
+
+#[cfg(feature = "hal")]
+impl delay::DelayMs<u16> for Delay {
+    fn delay_ms(&mut self, ms: u16) {
+        delay_ns(u64::from(ms) * 1_000_000);
+    }
+}
+
+#[cfg(feature = "hal")]
+impl delay::DelayMs<u32> for Delay {
+    fn delay_ms(&mut self, ms: u32) {
+        delay_ns(u64::from(ms) * 1_000_000);
+    }
+}
+
+#[cfg(feature = "hal")]
+impl delay::DelayMs<u64> for Delay {
+    fn delay_ms(&mut self, ms: u64) {
+        delay_ns(ms.saturating_mul(1_000_000));
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::Duration;
+
+#[cfg(feature = "hal")]
+use embedded_hal::timer;
+#[cfg(feature = "hal")]
+use void::Void;
+
+use crate::delay::{delay_until_ns, duration_to_ns, get_time_ns};
+
+/// A monotonic, periodic count-down timer.
+///
+/// `Timer` is based on `CLOCK_MONOTONIC`, so it isn't affected by changes to
+/// the system time. After [`start`] is called, the timer expires every time the
+/// specified period has elapsed. Each new period starts at the previous
+/// deadline rather than when the expiration is noticed, so the timer doesn't
+/// drift when it's polled late. If one or more periods were missed, they're
+/// reported as expired immediately.
+///
+/// With the `hal` feature enabled, `Timer` implements the `embedded-hal` 0.2
+/// `CountDown` and `Periodic` traits, using [`Duration`] as its time unit.
+///
+/// [`start`]: #method.start
+/// [`Duration`]: https://doc.rust-lang.org/std/time/struct.Duration.html
+#[derive(Debug, Default, Clone)]
+pub struct Timer {
+    // Deadline and period in nanoseconds, or None if the timer isn't running
+    state: Option<(i64, i64)>,
+}
+
+impl Timer {
+    /// Constructs a new `Timer`.
+    ///
+    /// The timer isn't running until [`start`] is called.
+    ///
+    /// [`start`]: #method.start
+    pub fn new() -> Timer {
+        Timer { state: None }
+    }
+
+    /// Starts the timer with the specified `period`.
+    ///
+    /// If the timer is already running, it's restarted with the new period.
+    pub fn start(&mut self, period: Duration) {
+        let period_ns = duration_to_ns(period);
+
+        self.state = Some((get_time_ns().saturating_add(period_ns), period_ns));
+    }
+
+    /// Stops the timer.
+    pub fn stop(&mut self) {
+        self.state = None;
+    }
+
+    /// Returns `true` if the timer is running.
+    pub fn is_running(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Returns the time remaining until the timer expires.
+    ///
+    /// Returns `None` if the timer isn't running.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.state.map(|(deadline_ns, _)| {
+            Duration::from_nanos((deadline_ns - get_time_ns()).max(0) as u64)
+        })
+    }
+
+    /// Returns `true` if the timer has expired, and starts the next period.
+    ///
+    /// `poll_expired` doesn't block. Returns `false` if the timer hasn't expired
+    /// yet, or isn't running.
+    pub fn poll_expired(&mut self) -> bool {
+        match self.state {
+            Some((deadline_ns, period_ns)) if get_time_ns() >= deadline_ns => {
+                self.state = Some((deadline_ns.saturating_add(period_ns), period_ns));
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Blocks until the timer expires, and starts the next period.
+    ///
+    /// Returns immediately if the timer isn't running.
+    pub fn wait_for_expiry(&mut self) {
+        if let Some((deadline_ns, period_ns)) = self.state {
+            delay_until_ns(deadline_ns);
+
+            self.state = Some((deadline_ns.saturating_add(period_ns), period_ns));
+        }
+    }
+}
+
+#[cfg(feature = "hal")]
+impl timer::CountDown for Timer {
+    type Time = Duration;
+
+    fn start<T>(&mut self, count: T)
+    where
+        T: Into<Self::Time>,
+    {
+        Timer::start(self, count.into());
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Void> {
+        if self.poll_expired() {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+#[cfg(feature = "hal")]
+impl timer::Periodic for Timer {}
@@ -39,7 +39,9 @@
 //! In addition to the hardware I2C buses, it's possible to configure a
 //! bit-banged software I2C bus on any available GPIO pins through the `i2c-gpio`
 //! device tree overlay. More details on enabling and configuring `i2c-gpio`
-//! can be found in `/boot/overlays/README`.
+//! can be found in `/boot/overlays/README`. Alternatively, [`SoftI2c`] can be
+//! used to bit-bang the I2C protocol from user space without any additional
+//! configuration.
 //!
 //! ## Transmission speed
 //!
@@ -52,10 +54,21 @@
 //! clock frequency in herz (Hz). Remember to reboot
 //! the Raspberry Pi afterwards.
 //!
+//! ## Embedded HAL trait implementations
+//!
+//! When the optional `hal` feature is enabled, [`I2c`] and [`SoftI2c`] implement
+//! the `embedded-hal` 0.2 blocking I2C traits (`Read`, `Write` and `WriteRead`).
+//! The `hal-1` feature adds implementations of the `embedded-hal` 1.0 `I2c`
+//! trait for both 7-bit and 10-bit addresses. The slave address and addressing
+//! mode are only reconfigured when they differ from the current settings.
+//!
+//! To share a single bus between multiple drivers, wrap it in a
+//! [`SharedBus`](../hal/struct.SharedBus.html).
+//!
 //! ## Not supported
 //!
 //! Some I2C and SMBus features aren't fully supported by the i2cdev interface, the underlying driver or
-//! the BCM283x SoC: 10-bit slave addresses, SMBus Block Read, SMBus Block Process Call, SMBus Host Notify,
+//! the BCM283x SoC: 10-bit slave addresses (supported by [`SoftI2c`]), SMBus Block Read, SMBus Block Process Call, SMBus Host Notify,
 //! SMBus Read/Write 32/64, and the SMBus Address Resolution Protocol.
 //!
 //! While clock stretching is supported, a bug exists in the implementation on the BCM283x SoC that will result
@@ -81,6 +94,7 @@
 //!
 //! [`new`]: struct.I2c.html#method.new
 //! [`set_timeout`]: struct.I2c.html#method.set_timeout
+//! [`SoftI2c`]: struct.SoftI2c.html
 
 #![allow(dead_code)]
 
@@ -97,9 +111,13 @@ use quick_error::quick_error;
 use crate::system;
 use crate::system::{DeviceInfo, Model};
 
+#[cfg(any(feature = "hal", feature = "hal-1"))]
+mod hal;
 mod ioctl;
+mod soft;
 
-pub use self::ioctl::Capabilities;
+pub use self::ioctl::{Capabilities, Operation};
+pub use self::soft::SoftI2c;
 
 quick_error! {
 /// Errors that can occur when accessing the I2C peripheral.
@@ -240,6 +258,13 @@ impl I2c {
         self.bus
     }
 
+    /// Returns the slave address that was set with [`set_slave_address`].
+    ///
+    /// [`set_slave_address`]: #method.set_slave_address
+    pub fn slave_address(&self) -> u16 {
+        self.address
+    }
+
     /// Returns the clock frequency in herz (Hz).
     pub fn clock_speed(&self) -> Result<u32> {
         let mut buffer = [0u8; 4];
@@ -373,6 +398,56 @@ impl I2c {
         Ok(())
     }
 
+    /// Transfers multiple read and write operations as a single combined
+    /// transaction.
+    ///
+    /// Each [`Operation`] is preceded by a START condition for the first operation,
+    /// or a repeated START for every subsequent operation, followed by the slave
+    /// address and the R/W bit. No STOP condition is issued until all operations
+    /// have been transferred, which is required by some devices that would otherwise
+    /// reset their internal state, or release the bus to another master.
+    ///
+    /// Adjacent operations of the same type are merged, without a repeated START
+    /// and address in between, following the transaction model of embedded-hal 1.0.
+    /// For instance, a register address and the data that should be written to it
+    /// can be passed as two separate write operations, without having to copy them
+    /// into a single buffer first. The merged operations are transferred through an
+    /// intermediate buffer.
+    ///
+    /// All operations are transferred with a single `I2C_RDWR` request. After
+    /// merging, a maximum of 42 operations can be transferred, and each operation
+    /// can contain a maximum of 8192 bytes. Note that the BCM283x I2C driver only
+    /// supports a single read operation, which has to be the final operation.
+    ///
+    /// Sequence: START → Address + R/W Bit → Operation → Repeated START → Address +
+    /// R/W Bit → Operation → ... → STOP
+    ///
+    /// [`Operation`]: enum.Operation.html
+    pub fn transaction(&self, operations: &mut [Operation<'_>]) -> Result<()> {
+        ioctl::i2c_transaction(
+            self.i2cdev.as_raw_fd(),
+            self.address,
+            self.addr_10bit,
+            operations,
+        )?;
+
+        Ok(())
+    }
+
+    // Writes to the general call address (0x00), regardless of the configured slave
+    // address, which set_slave_address() doesn't accept. Some devices, like the
+    // ATECC508A and ATECC608, wake up when SDA is held low long enough.
+    pub(crate) fn write_general_call(&self, buffer: &[u8]) -> Result<()> {
+        ioctl::i2c_transaction(
+            self.i2cdev.as_raw_fd(),
+            0x00,
+            false,
+            &mut [Operation::Write(buffer)],
+        )?;
+
+        Ok(())
+    }
+
     /// Sends an 8-bit `command`, and then fills a multi-byte `buffer` with
     /// incoming data.
     ///
@@ -610,6 +685,42 @@ impl I2c {
         Ok(())
     }
 
+    /// Sends an 8-bit `command` and an 8-bit byte count along with a multi-byte
+    /// `write_buffer`, and then receives an 8-bit byte count along with a multi-byte
+    /// response, which is stored in `read_buffer`.
+    ///
+    /// `smbus_block_process_call` currently isn't supported on the Raspberry Pi, and
+    /// returns [`Err(Error::FeatureNotSupported)`] unless underlying driver support is
+    /// detected.
+    ///
+    /// `smbus_block_process_call` can write and read a maximum of 32 bytes. Any
+    /// additional data contained in `write_buffer` is ignored.
+    ///
+    /// Sequence: START → Address + Write Bit → Command → Outgoing Byte Count →
+    /// Outgoing Bytes → Repeated START → Address + Read Bit → Incoming Byte Count →
+    /// Incoming Bytes → STOP
+    ///
+    /// Returns how many bytes were read.
+    ///
+    /// [`Err(Error::FeatureNotSupported)`]: enum.Error.html#variant.FeatureNotSupported
+    pub fn smbus_block_process_call(
+        &self,
+        command: u8,
+        write_buffer: &[u8],
+        read_buffer: &mut [u8],
+    ) -> Result<usize> {
+        if !self.capabilities().smbus_block_process_call() {
+            return Err(Error::FeatureNotSupported);
+        }
+
+        Ok(ioctl::smbus_block_process_call(
+            self.i2cdev.as_raw_fd(),
+            command,
+            write_buffer,
+            read_buffer,
+        )?)
+    }
+
     /// Enables or disables SMBus Packet Error Checking.
     ///
     /// Packet Error Checking inserts a CRC-8 Packet Error Code (PEC) byte before each STOP
@@ -0,0 +1,220 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+#[cfg(feature = "hal")]
+use embedded_hal::blocking::i2c;
+#[cfg(feature = "hal-1")]
+use embedded_hal_1::i2c as i2c_1;
+
+#[cfg(feature = "hal-1")]
+use crate::i2c::Operation;
+use crate::i2c::{Error, I2c, SoftI2c};
+
+impl I2c {
+    // Only reconfigures the addressing mode and slave address when they differ
+    // from the current settings, to avoid unnecessary ioctl() calls.
+    fn select_address(&mut self, address: u16, addr_10bit: bool) -> Result<(), Error> {
+        if self.addr_10bit != addr_10bit {
+            self.set_addr_10bit(addr_10bit)?;
+        }
+
+        if self.address != address {
+            self.set_slave_address(address)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SoftI2c {
+    fn select_address(&mut self, address: u16, addr_10bit: bool) -> Result<(), Error> {
+        self.set_addr_10bit(addr_10bit);
+
+        if self.slave_address() != address {
+            self.set_slave_address(address)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl i2c::Write for I2c {
+    type Error = Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.select_address(u16::from(address), false)?;
+        I2c::write(self, bytes)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl i2c::Read for I2c {
+    type Error = Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.select_address(u16::from(address), false)?;
+        I2c::read(self, buffer)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl i2c::WriteRead for I2c {
+    type Error = Error;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.select_address(u16::from(address), false)?;
+        I2c::write_read(self, bytes, buffer)
+    }
+}
+
+#[cfg(feature = "hal")]
+impl i2c::Write for SoftI2c {
+    type Error = Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.select_address(u16::from(address), false)?;
+        SoftI2c::write(self, bytes)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl i2c::Read for SoftI2c {
+    type Error = Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.select_address(u16::from(address), false)?;
+        SoftI2c::read(self, buffer)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl i2c::WriteRead for SoftI2c {
+    type Error = Error;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.select_address(u16::from(address), false)?;
+        SoftI2c::write_read(self, bytes, buffer)
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl i2c_1::Error for Error {
+    fn kind(&self) -> i2c_1::ErrorKind {
+        match self {
+            Error::Io(e) => match e.raw_os_error() {
+                Some(libc::EREMOTEIO) | Some(libc::ENXIO) => {
+                    i2c_1::ErrorKind::NoAcknowledge(i2c_1::NoAcknowledgeSource::Unknown)
+                }
+                Some(libc::EAGAIN) => i2c_1::ErrorKind::ArbitrationLoss,
+                _ => i2c_1::ErrorKind::Other,
+            },
+            _ => i2c_1::ErrorKind::Other,
+        }
+    }
+}
+
+// Converts embedded-hal operations to the equivalent RPPAL operations
+#[cfg(feature = "hal-1")]
+fn hal_operations<'a>(operations: &'a mut [i2c_1::Operation<'_>]) -> Vec<Operation<'a>> {
+    operations
+        .iter_mut()
+        .map(|operation| match operation {
+            i2c_1::Operation::Read(buffer) => Operation::Read(buffer),
+            i2c_1::Operation::Write(buffer) => Operation::Write(buffer),
+        })
+        .collect()
+}
+
+#[cfg(feature = "hal-1")]
+impl i2c_1::ErrorType for I2c {
+    type Error = Error;
+}
+
+#[cfg(feature = "hal-1")]
+impl i2c_1::I2c<i2c_1::SevenBitAddress> for I2c {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c_1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.select_address(u16::from(address), false)?;
+        I2c::transaction(self, &mut hal_operations(operations))
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl i2c_1::I2c<i2c_1::TenBitAddress> for I2c {
+    fn transaction(
+        &mut self,
+        address: u16,
+        operations: &mut [i2c_1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.select_address(address, true)?;
+        I2c::transaction(self, &mut hal_operations(operations))
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl i2c_1::ErrorType for SoftI2c {
+    type Error = Error;
+}
+
+#[cfg(feature = "hal-1")]
+impl i2c_1::I2c<i2c_1::SevenBitAddress> for SoftI2c {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c_1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.select_address(u16::from(address), false)?;
+        SoftI2c::transaction(self, &mut hal_operations(operations))
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl i2c_1::I2c<i2c_1::TenBitAddress> for SoftI2c {
+    fn transaction(
+        &mut self,
+        address: u16,
+        operations: &mut [i2c_1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.select_address(address, true)?;
+        SoftI2c::transaction(self, &mut hal_operations(operations))
+    }
+}
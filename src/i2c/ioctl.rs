@@ -194,6 +194,47 @@ impl fmt::Debug for Capabilities {
     }
 }
 
+/// Part of a combined I2C transaction.
+///
+/// `Operation`s are transferred using the [`transaction`] method, which maps to a
+/// single `I2C_RDWR` request. Each operation starts with a (repeated) START
+/// condition, followed by the slave address and the R/W bit, unless it directly
+/// follows an operation of the same type, in which case both are merged. A single
+/// STOP condition is sent after the final operation. This matches the transaction
+/// model of embedded-hal 1.0.
+///
+/// [`transaction`]: struct.I2c.html#method.transaction
+#[derive(Debug, PartialEq)]
+pub enum Operation<'a> {
+    /// Fills the buffer with incoming data.
+    Read(&'a mut [u8]),
+    /// Sends the outgoing data contained in the buffer.
+    Write(&'a [u8]),
+}
+
+impl<'a> Operation<'a> {
+    /// Returns the length of the operation's buffer.
+    pub fn len(&self) -> usize {
+        match self {
+            Operation::Read(buffer) => buffer.len(),
+            Operation::Write(buffer) => buffer.len(),
+        }
+    }
+
+    /// Returns `true` if the operation's buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if this is a read operation.
+    pub fn is_read(&self) -> bool {
+        match self {
+            Operation::Read(_) => true,
+            Operation::Write(_) => false,
+        }
+    }
+}
+
 // ioctl() requests supported by i2cdev
 const REQ_RETRIES: IoctlLong = 0x0701; // How many retries when waiting for an ACK
 const REQ_TIMEOUT: IoctlLong = 0x0702; // Timeout in 10ms units
@@ -212,6 +253,7 @@ const RDWR_FLAG_RD: u16 = 0x0001; // Read operation
 const RDWR_FLAG_TEN: u16 = 0x0010; // 10-bit slave address
 
 const RDWR_MSG_MAX: usize = 42; // Maximum messages per RDWR operation
+const RDWR_BUFFER_MAX: usize = 8192; // Maximum bytes per RDWR message
 const SMBUS_BLOCK_MAX: usize = 32; // Maximum bytes per block transfer
 
 // SMBus read or write request
@@ -230,6 +272,7 @@ enum SmbusSize {
     WordData = 3,
     ProcCall = 4,
     BlockData = 5,
+    BlockProcCall = 7,
     I2cBlockData = 8,
 }
 
@@ -280,7 +323,7 @@ impl SmbusBuffer {
             buffer.data[1..=SMBUS_BLOCK_MAX].copy_from_slice(&value[..SMBUS_BLOCK_MAX]);
             SMBUS_BLOCK_MAX as u8
         } else {
-            buffer.data[1..=value.len()].copy_from_slice(&value);
+            buffer.data[1..=value.len()].copy_from_slice(value);
             value.len() as u8
         };
 
@@ -459,6 +502,36 @@ pub fn smbus_block_write(fd: c_int, command: u8, value: &[u8]) -> Result<()> {
     )
 }
 
+pub fn smbus_block_process_call(
+    fd: c_int,
+    command: u8,
+    write_buffer: &[u8],
+    read_buffer: &mut [u8],
+) -> Result<usize> {
+    // The outgoing data is replaced with the incoming data in the same buffer
+    let mut buffer = SmbusBuffer::with_buffer(write_buffer);
+    smbus_request(
+        fd,
+        SmbusReadWrite::Write,
+        command,
+        SmbusSize::BlockProcCall,
+        Some(&mut buffer),
+    )?;
+
+    // Verify the length in case we're receiving corrupted data
+    let incoming_length = if buffer.data[0] as usize > SMBUS_BLOCK_MAX {
+        SMBUS_BLOCK_MAX
+    } else {
+        buffer.data[0] as usize
+    };
+
+    // Make sure the incoming data fits in the read buffer
+    let read_length = incoming_length.min(read_buffer.len());
+    read_buffer[..read_length].copy_from_slice(&buffer.data[1..=read_length]);
+
+    Ok(incoming_length)
+}
+
 pub fn i2c_block_read(fd: c_int, command: u8, value: &mut [u8]) -> Result<()> {
     let mut buffer = SmbusBuffer::new();
     buffer.data[0] = if value.len() > SMBUS_BLOCK_MAX {
@@ -510,11 +583,28 @@ struct RdwrSegment {
 #[derive(Debug, PartialEq, Copy, Clone)]
 struct RdwrRequest {
     // Pointer to an array of segments
-    segments: *mut [RdwrSegment],
+    segments: *mut RdwrSegment,
     // Number of segments
     nmsgs: u32,
 }
 
+fn rdwr_segment(
+    address: u16,
+    addr_10bit: bool,
+    read: bool,
+    buffer: *mut u8,
+    len: usize,
+) -> RdwrSegment {
+    let flags = if addr_10bit { RDWR_FLAG_TEN } else { 0 };
+
+    RdwrSegment {
+        addr: address,
+        flags: if read { flags | RDWR_FLAG_RD } else { flags },
+        len: len as u16,
+        data: buffer as usize,
+    }
+}
+
 pub fn i2c_write_read(
     fd: c_int,
     address: u16,
@@ -527,32 +617,104 @@ pub fn i2c_write_read(
         return Ok(());
     }
 
-    let segment_write = RdwrSegment {
-        addr: address,
-        flags: if addr_10bit { RDWR_FLAG_TEN } else { 0 },
-        len: write_buffer.len() as u16,
-        data: write_buffer.as_ptr() as usize,
-    };
+    i2c_transaction(
+        fd,
+        address,
+        addr_10bit,
+        &mut [Operation::Write(write_buffer), Operation::Read(read_buffer)],
+    )
+}
 
-    let segment_read = RdwrSegment {
-        addr: address,
-        flags: if addr_10bit {
-            RDWR_FLAG_RD | RDWR_FLAG_TEN
-        } else {
-            RDWR_FLAG_RD
-        },
-        len: read_buffer.len() as u16,
-        data: read_buffer.as_mut_ptr() as usize,
-    };
+// A group of adjacent operations of the same type, which is transferred as a
+// single RDWR message. Groups containing multiple operations use an
+// intermediate buffer.
+struct Group {
+    start: usize,
+    end: usize,
+    read: bool,
+    len: usize,
+    buffer: Vec<u8>,
+}
+
+pub fn i2c_transaction(
+    fd: c_int,
+    address: u16,
+    addr_10bit: bool,
+    operations: &mut [Operation<'_>],
+) -> Result<()> {
+    let mut groups: Vec<Group> = Vec::new();
+    for (index, operation) in operations.iter().enumerate() {
+        match groups.last_mut() {
+            Some(group) if group.read == operation.is_read() => {
+                group.end = index + 1;
+                group.len += operation.len();
+            }
+            _ => groups.push(Group {
+                start: index,
+                end: index + 1,
+                read: operation.is_read(),
+                len: operation.len(),
+                buffer: Vec::new(),
+            }),
+        }
+    }
+
+    if groups.len() > RDWR_MSG_MAX || groups.iter().any(|group| group.len > RDWR_BUFFER_MAX) {
+        return Err(io::Error::from_raw_os_error(libc::EINVAL));
+    }
+
+    if groups.is_empty() {
+        return Ok(());
+    }
+
+    let mut segments: Vec<RdwrSegment> = Vec::with_capacity(groups.len());
+    for group in groups.iter_mut() {
+        let ops = &mut operations[group.start..group.end];
+
+        let buffer = match ops {
+            [Operation::Read(buffer)] => buffer.as_mut_ptr(),
+            [Operation::Write(buffer)] => buffer.as_ptr() as *mut u8,
+            _ => {
+                group.buffer.reserve_exact(group.len);
+                for operation in ops.iter() {
+                    match operation {
+                        Operation::Read(buffer) => {
+                            group.buffer.resize(group.buffer.len() + buffer.len(), 0)
+                        }
+                        Operation::Write(buffer) => group.buffer.extend_from_slice(buffer),
+                    }
+                }
+
+                group.buffer.as_mut_ptr()
+            }
+        };
+
+        segments.push(rdwr_segment(
+            address, addr_10bit, group.read, buffer, group.len,
+        ));
+    }
 
-    let mut segments: [RdwrSegment; 2] = [segment_write, segment_read];
     let mut request = RdwrRequest {
-        segments: &mut segments,
-        nmsgs: 2,
+        segments: segments.as_mut_ptr(),
+        nmsgs: segments.len() as u32,
     };
 
     parse_retval!(unsafe { ioctl(fd, REQ_RDWR, &mut request) })?;
 
+    // Copy merged reads back to their original buffers
+    for group in groups
+        .iter()
+        .filter(|group| group.read && !group.buffer.is_empty())
+    {
+        let mut offset = 0;
+        for operation in operations[group.start..group.end].iter_mut() {
+            if let Operation::Read(buffer) = operation {
+                buffer.copy_from_slice(&group.buffer[offset..offset + buffer.len()]);
+                offset += buffer.len();
+            }
+        }
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,533 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::io;
+
+use crate::delay::{delay_until_ns, get_time_ns};
+use crate::gpio::{IoPin, Level, Mode, PullUpDown};
+use crate::i2c::{Error, Operation, Result};
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+const NANOS_PER_MILLI: i64 = 1_000_000;
+
+// Default clock stretching timeout, similar to the i2c-bcm2835 driver
+const DEFAULT_TIMEOUT_MS: u32 = 1000;
+
+// Returned on a NACK, the same error code used by the i2c-bcm2835 driver
+#[inline]
+fn nack_error() -> Error {
+    Error::Io(io::Error::from_raw_os_error(libc::EREMOTEIO))
+}
+
+/// Software-based I2C master.
+///
+/// `SoftI2c` emulates an I2C master by bit-banging any two available GPIO pins.
+/// It can be used when the hardware I2C bus is already in use, when you need
+/// multiple I2C buses, or when a slave device relies on clock stretching at
+/// arbitrary points during the transfer, which isn't handled properly by the
+/// BCM283x SoC.
+///
+/// I2C uses open-drain outputs. `SoftI2c` emulates this behavior by switching
+/// a pin to [`Mode::Output`] to pull the line low, and switching it back to
+/// [`Mode::Input`] to release the line, which is then pulled high by a pull-up
+/// resistor. The built-in pull-up resistors are enabled for both pins, but their
+/// high resistance may not be sufficient for higher clock speeds or longer wires.
+/// Connecting external pull-up resistors is recommended.
+///
+/// `SoftI2c` takes ownership of two [`IoPin`]s. When `SoftI2c` goes out of scope,
+/// the pins are reset to their original modes and the pull-up resistors are
+/// disabled, unless [`reset_on_drop`] was set to `false` on the `IoPin`s.
+///
+/// The clock speed is an upper limit. Timing is controlled by sleeping and busy
+/// waiting on the current thread, so the actual clock speed may be lower. Slave
+/// devices are allowed to hold the clock line low to stretch the clock, up to the
+/// configured timeout. Multi-master arbitration isn't supported.
+///
+/// When a slave device doesn't acknowledge its address or any of the outgoing
+/// bytes, an `io::Error` with the same `EREMOTEIO` error code returned by the
+/// hardware I2C driver is returned. A clock stretching timeout returns an
+/// `io::ErrorKind::TimedOut` error.
+///
+/// [`Mode::Output`]: ../gpio/enum.Mode.html#variant.Output
+/// [`Mode::Input`]: ../gpio/enum.Mode.html#variant.Input
+/// [`IoPin`]: ../gpio/struct.IoPin.html
+/// [`reset_on_drop`]: ../gpio/struct.IoPin.html#method.set_reset_on_drop
+#[derive(Debug)]
+pub struct SoftI2c {
+    sda: IoPin,
+    scl: IoPin,
+    clock_speed: u32,
+    half_period_ns: i64,
+    timeout_ns: i64,
+    addr_10bit: bool,
+    address: u16,
+}
+
+impl SoftI2c {
+    /// Constructs a new `SoftI2c`.
+    ///
+    /// `sda` is used as the data line, and `scl` as the clock line. `clock_speed`
+    /// defines the maximum clock frequency in hertz (Hz). Typical values are
+    /// 100_000 (Standard-mode) and 400_000 (Fast-mode).
+    ///
+    /// Both pins are configured as inputs with their built-in pull-up resistors
+    /// enabled, which keeps the bus idle.
+    pub fn new(sda: IoPin, scl: IoPin, clock_speed: u32) -> Result<SoftI2c> {
+        let mut soft_i2c = SoftI2c {
+            sda,
+            scl,
+            clock_speed: 0,
+            half_period_ns: 0,
+            timeout_ns: i64::from(DEFAULT_TIMEOUT_MS) * NANOS_PER_MILLI,
+            addr_10bit: false,
+            address: 0,
+        };
+
+        soft_i2c.set_clock_speed(clock_speed)?;
+
+        for pin in [&mut soft_i2c.sda, &mut soft_i2c.scl].iter_mut() {
+            pin.set_mode(Mode::Input);
+            pin.set_pullupdown(PullUpDown::PullUp);
+            // Once a pin is switched to output mode, it should always pull the line low
+            pin.set_low();
+        }
+
+        Ok(soft_i2c)
+    }
+
+    /// Consumes the `SoftI2c`, and returns the SDA and SCL pins.
+    pub fn into_pins(self) -> (IoPin, IoPin) {
+        (self.sda, self.scl)
+    }
+
+    /// Returns the maximum clock frequency in hertz (Hz).
+    pub fn clock_speed(&self) -> u32 {
+        self.clock_speed
+    }
+
+    /// Sets the maximum clock frequency in hertz (Hz).
+    ///
+    /// Returns `Err(Error::Io)` with `io::ErrorKind::InvalidInput` if
+    /// `clock_speed` is set to 0.
+    pub fn set_clock_speed(&mut self, clock_speed: u32) -> Result<()> {
+        if clock_speed == 0 {
+            return Err(Error::Io(io::Error::from(io::ErrorKind::InvalidInput)));
+        }
+
+        self.clock_speed = clock_speed;
+        self.half_period_ns = NANOS_PER_SEC / (2 * i64::from(clock_speed));
+
+        Ok(())
+    }
+
+    /// Sets the maximum duration in milliseconds (ms) a slave device is allowed
+    /// to stretch the clock.
+    ///
+    /// By default, `timeout` is set to 1000ms.
+    pub fn set_timeout(&mut self, timeout: u32) {
+        self.timeout_ns = i64::from(timeout) * NANOS_PER_MILLI;
+    }
+
+    /// Returns the slave address that was set with [`set_slave_address`].
+    ///
+    /// [`set_slave_address`]: #method.set_slave_address
+    pub fn slave_address(&self) -> u16 {
+        self.address
+    }
+
+    /// Sets a 7-bit or 10-bit slave address.
+    ///
+    /// `slave_address` refers to the slave device you're communicating with.
+    /// The specified address shouldn't include the R/W bit.
+    ///
+    /// By default, 10-bit addressing is disabled, which means
+    /// `set_slave_address` only accepts 7-bit addresses. 10-bit addressing
+    /// can be enabled with [`set_addr_10bit`].
+    ///
+    /// [`set_addr_10bit`]: #method.set_addr_10bit
+    pub fn set_slave_address(&mut self, slave_address: u16) -> Result<()> {
+        // Filter out reserved, invalid and unsupported addresses
+        if (!self.addr_10bit
+            && (slave_address < 8 || (slave_address >> 3) == 0b1111 || slave_address > 0x7F))
+            || (self.addr_10bit && slave_address > 0x03FF)
+        {
+            return Err(Error::InvalidSlaveAddress(slave_address));
+        }
+
+        self.address = slave_address;
+
+        Ok(())
+    }
+
+    /// Enables or disables 10-bit addressing.
+    ///
+    /// Unlike the hardware I2C bus, `SoftI2c` always supports 10-bit addressing.
+    /// The slave address is sent as a reserved `11110XX` address byte containing
+    /// the two most-significant address bits, followed by the remaining 8 bits.
+    ///
+    /// By default, `addr_10bit` is set to `false`.
+    pub fn set_addr_10bit(&mut self, addr_10bit: bool) {
+        self.addr_10bit = addr_10bit;
+    }
+
+    /// Receives incoming data from the slave device and writes it to `buffer`.
+    ///
+    /// `read` reads as many bytes as can fit in `buffer`.
+    ///
+    /// Sequence: START → Address + Read Bit → Incoming Bytes → STOP
+    ///
+    /// Returns how many bytes were read.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        self.transaction_write_read(&[], buffer)?;
+
+        Ok(buffer.len())
+    }
+
+    /// Sends the outgoing data contained in `buffer` to the slave device.
+    ///
+    /// Sequence: START → Address + Write Bit → Outgoing Bytes → STOP
+    ///
+    /// Returns how many bytes were written.
+    pub fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        self.transaction_write_read(buffer, &mut [])?;
+
+        Ok(buffer.len())
+    }
+
+    /// Sends the outgoing data contained in `write_buffer` to the slave device, and
+    /// then fills `read_buffer` with incoming data.
+    ///
+    /// Compared to calling [`write`] and [`read`] separately, `write_read` doesn't
+    /// issue a STOP condition in between the write and read operation. A repeated
+    /// START is sent instead.
+    ///
+    /// Sequence: START → Address + Write Bit → Outgoing Bytes → Repeated START →
+    /// Address + Read Bit → Incoming Bytes → STOP
+    ///
+    /// [`write`]: #method.write
+    /// [`read`]: #method.read
+    pub fn write_read(&mut self, write_buffer: &[u8], read_buffer: &mut [u8]) -> Result<()> {
+        self.transaction_write_read(write_buffer, read_buffer)
+    }
+
+    /// Transfers multiple read and write operations as a single combined
+    /// transaction.
+    ///
+    /// Each [`Operation`] is preceded by a START condition for the first operation,
+    /// or a repeated START for every subsequent operation, followed by the slave
+    /// address and the R/W bit. No STOP condition is issued until all operations
+    /// have been transferred. Adjacent operations of the same type are merged,
+    /// without a repeated START and address in between, following the transaction
+    /// model of embedded-hal 1.0.
+    ///
+    /// Unlike the hardware I2C bus, `SoftI2c` supports any number of read and
+    /// write operations in any order. If `operations` is empty, nothing is sent.
+    ///
+    /// Sequence: START → Address + R/W Bit → Operation → Repeated START → Address +
+    /// R/W Bit → Operation → ... → STOP
+    ///
+    /// [`Operation`]: enum.Operation.html
+    pub fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result<()> {
+        // Don't generate a START and STOP condition without anything in between
+        if operations.is_empty() {
+            return Ok(());
+        }
+
+        let mut result = Ok(());
+
+        for index in 0..operations.len() {
+            let continues =
+                index > 0 && operations[index - 1].is_read() == operations[index].is_read();
+            // Empty operations don't transfer any bytes, so the final byte of a
+            // read is NACKed unless a merged read that follows has data to receive
+            let continued = operations[index + 1..]
+                .iter()
+                .take_while(|next| next.is_read() == operations[index].is_read())
+                .any(|next| !next.is_empty());
+
+            result = match &mut operations[index] {
+                Operation::Read(buffer) => self.read_bytes(buffer, !continues, !continued),
+                Operation::Write(buffer) => self.write_bytes(buffer, !continues),
+            };
+
+            if result.is_err() {
+                break;
+            }
+        }
+
+        // Always try to release the bus, even if the transfer failed
+        let stop_result = self.stop();
+
+        result.and(stop_result)
+    }
+
+    /// Sends an 8-bit `command`, and then fills a multi-byte `buffer` with
+    /// incoming data.
+    ///
+    /// Sequence: START → Address + Write Bit → Command → Repeated START
+    /// → Address + Read Bit → Incoming Bytes → STOP
+    pub fn block_read(&mut self, command: u8, buffer: &mut [u8]) -> Result<()> {
+        self.transaction_write_read(&[command], buffer)
+    }
+
+    /// Sends an 8-bit `command` followed by a multi-byte `buffer`.
+    ///
+    /// Sequence: START → Address + Write Bit → Command → Outgoing Bytes → STOP
+    pub fn block_write(&mut self, command: u8, buffer: &[u8]) -> Result<()> {
+        let mut write_buffer = Vec::with_capacity(buffer.len() + 1);
+        write_buffer.push(command);
+        write_buffer.extend_from_slice(buffer);
+
+        self.transaction_write_read(&write_buffer, &mut [])
+    }
+
+    /// Sends an 8-bit `command`, and receives an 8-bit value.
+    ///
+    /// Sequence: START → Address + Write Bit → Command → Repeated START
+    /// → Address + Read Bit → Incoming Byte → STOP
+    pub fn smbus_read_byte(&mut self, command: u8) -> Result<u8> {
+        let mut buffer = [0u8; 1];
+        self.transaction_write_read(&[command], &mut buffer)?;
+
+        Ok(buffer[0])
+    }
+
+    /// Sends an 8-bit `command` and an 8-bit `value`.
+    ///
+    /// Sequence: START → Address + Write Bit → Command → Outgoing Byte → STOP
+    pub fn smbus_write_byte(&mut self, command: u8, value: u8) -> Result<()> {
+        self.transaction_write_read(&[command, value], &mut [])
+    }
+
+    /// Sends an 8-bit `command`, and receives a 16-bit value.
+    ///
+    /// Based on the SMBus protocol definition, the first byte received is
+    /// stored as the low byte of the 16-bit value, and the second byte as
+    /// the high byte.
+    ///
+    /// Sequence: START → Address + Write Bit → Command → Repeated START
+    /// → Address + Read Bit → Incoming Byte Low → Incoming Byte High → STOP
+    pub fn smbus_read_word(&mut self, command: u8) -> Result<u16> {
+        let mut buffer = [0u8; 2];
+        self.transaction_write_read(&[command], &mut buffer)?;
+
+        Ok(u16::from(buffer[0]) | (u16::from(buffer[1]) << 8))
+    }
+
+    /// Sends an 8-bit `command` and a 16-bit `value`.
+    ///
+    /// Based on the SMBus protocol definition, the first byte sent is the low byte
+    /// of the 16-bit value, and the second byte is the high byte.
+    ///
+    /// Sequence: START → Address + Write Bit → Command → Outgoing Byte Low → Outgoing Byte High → STOP
+    pub fn smbus_write_word(&mut self, command: u8, value: u16) -> Result<()> {
+        self.transaction_write_read(
+            &[command, (value & 0xFF) as u8, (value >> 8) as u8],
+            &mut [],
+        )
+    }
+
+    fn transaction_write_read(
+        &mut self,
+        write_buffer: &[u8],
+        read_buffer: &mut [u8],
+    ) -> Result<()> {
+        let result = if read_buffer.is_empty() {
+            self.transfer_write(write_buffer)
+        } else if write_buffer.is_empty() {
+            self.transfer_read(read_buffer)
+        } else {
+            self.transfer_write(write_buffer)
+                .and_then(|_| self.transfer_read(read_buffer))
+        };
+
+        // Always try to release the bus, even if the transfer failed
+        let stop_result = self.stop();
+
+        result.and(stop_result)
+    }
+
+    // Sends a (repeated) START, followed by the slave address and the R/W bit.
+    fn start_address(&mut self, read: bool) -> Result<()> {
+        self.start()?;
+
+        if self.addr_10bit {
+            // 11110 + two most-significant address bits + write bit
+            let high_byte = 0xF0 | ((self.address >> 7) & 0x06) as u8;
+
+            if !self.write_byte(high_byte)? || !self.write_byte(self.address as u8)? {
+                return Err(nack_error());
+            }
+
+            // A read requires a repeated START, followed by only the first address
+            // byte with the read bit set
+            if read {
+                self.start()?;
+
+                if !self.write_byte(high_byte | 0x01)? {
+                    return Err(nack_error());
+                }
+            }
+        } else if !self.write_byte(((self.address << 1) as u8) | read as u8)? {
+            return Err(nack_error());
+        }
+
+        Ok(())
+    }
+
+    fn transfer_write(&mut self, buffer: &[u8]) -> Result<()> {
+        self.write_bytes(buffer, true)
+    }
+
+    fn transfer_read(&mut self, buffer: &mut [u8]) -> Result<()> {
+        self.read_bytes(buffer, true, true)
+    }
+
+    // Sends the outgoing bytes, preceded by a (repeated) START and the slave
+    // address if `start` is set.
+    fn write_bytes(&mut self, buffer: &[u8], start: bool) -> Result<()> {
+        if start {
+            self.start_address(false)?;
+        }
+
+        for &byte in buffer {
+            if !self.write_byte(byte)? {
+                return Err(nack_error());
+            }
+        }
+
+        Ok(())
+    }
+
+    // Receives incoming bytes, preceded by a (repeated) START and the slave
+    // address if `start` is set. The final byte is NACKed if `last` is set.
+    fn read_bytes(&mut self, buffer: &mut [u8], start: bool, last: bool) -> Result<()> {
+        if start {
+            self.start_address(true)?;
+        }
+
+        let len = buffer.len();
+        for (idx, byte) in buffer.iter_mut().enumerate() {
+            // NACK the final byte to let the slave know we're done
+            *byte = self.read_byte(!last || idx + 1 != len)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn delay(&self) {
+        delay_until_ns(get_time_ns().saturating_add(self.half_period_ns));
+    }
+
+    #[inline]
+    fn drive_low(pin: &mut IoPin) {
+        pin.set_mode(Mode::Output);
+    }
+
+    #[inline]
+    fn release(pin: &mut IoPin) {
+        pin.set_mode(Mode::Input);
+    }
+
+    // Releases SCL, and waits until the line is high to support clock stretching.
+    fn scl_high(&mut self) -> Result<()> {
+        SoftI2c::release(&mut self.scl);
+
+        let start_ns = get_time_ns();
+        while self.scl.read() == Level::Low {
+            if get_time_ns() - start_ns > self.timeout_ns {
+                return Err(Error::Io(io::Error::from(io::ErrorKind::TimedOut)));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Sends a START condition, or a repeated START if SCL is currently low.
+    fn start(&mut self) -> Result<()> {
+        SoftI2c::release(&mut self.sda);
+        self.delay();
+        self.scl_high()?;
+        self.delay();
+        SoftI2c::drive_low(&mut self.sda);
+        self.delay();
+        SoftI2c::drive_low(&mut self.scl);
+
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        SoftI2c::drive_low(&mut self.sda);
+        self.delay();
+        self.scl_high()?;
+        self.delay();
+        SoftI2c::release(&mut self.sda);
+        self.delay();
+
+        Ok(())
+    }
+
+    fn write_bit(&mut self, bit: bool) -> Result<()> {
+        if bit {
+            SoftI2c::release(&mut self.sda);
+        } else {
+            SoftI2c::drive_low(&mut self.sda);
+        }
+
+        self.delay();
+        self.scl_high()?;
+        self.delay();
+        SoftI2c::drive_low(&mut self.scl);
+
+        Ok(())
+    }
+
+    fn read_bit(&mut self) -> Result<bool> {
+        SoftI2c::release(&mut self.sda);
+        self.delay();
+        self.scl_high()?;
+        let bit = self.sda.read() == Level::High;
+        self.delay();
+        SoftI2c::drive_low(&mut self.scl);
+
+        Ok(bit)
+    }
+
+    // Returns true if the slave device acknowledged the byte.
+    fn write_byte(&mut self, byte: u8) -> Result<bool> {
+        for idx in 0..8 {
+            self.write_bit(byte & (0x80 >> idx) != 0)?;
+        }
+
+        Ok(!self.read_bit()?)
+    }
+
+    fn read_byte(&mut self, ack: bool) -> Result<u8> {
+        let mut byte = 0u8;
+        for _ in 0..8 {
+            byte = (byte << 1) | self.read_bit()? as u8;
+        }
+
+        self.write_bit(!ack)?;
+
+        Ok(byte)
+    }
+}
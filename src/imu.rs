@@ -0,0 +1,75 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Drivers for inertial measurement units.
+//!
+//! * [`Imu`] supports the InvenSense MPU-6050 and ICM-20948 6-axis motion trackers,
+//!   connected through I2C. It reads accelerometer, gyroscope and temperature
+//!   measurements, scaled based on the configured full-scale ranges.
+//!
+//! Instead of polling, samples can be collected as soon as they're available
+//! by connecting the IMU's INT pin to a GPIO pin. [`Imu::into_stream`] configures
+//! the IMU's data-ready interrupt and an asynchronous interrupt trigger on the
+//! [`InputPin`], and sends every new sample through a channel, which can be
+//! read from [`SampleStream`] on any thread.
+//!
+//! [`Imu`]: struct.Imu.html
+//! [`Imu::into_stream`]: struct.Imu.html#method.into_stream
+//! [`SampleStream`]: struct.SampleStream.html
+//! [`InputPin`]: ../gpio/struct.InputPin.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::gpio;
+use crate::i2c;
+
+mod invensense;
+
+pub use self::invensense::{AccelRange, GyroRange, Imu, ImuModel, Sample, SampleStream};
+
+quick_error! {
+/// Errors that can occur when accessing an IMU.
+    #[derive(Debug)]
+    pub enum Error {
+/// I2C error.
+        I2c(err: i2c::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Unknown device.
+///
+/// The device's WHO_AM_I register doesn't contain the value expected for the
+/// selected model. Contains the value that was read instead.
+        UnknownDevice(id: u8) { description("unknown device") }
+/// Timeout.
+///
+/// No sample was received before the timeout elapsed.
+        Timeout { description("no sample received before the timeout elapsed") }
+/// Sample stream disconnected.
+///
+/// The asynchronous interrupt trigger that sends samples to the
+/// `SampleStream` is no longer active.
+        Disconnected { description("sample stream disconnected") }
+    }
+}
+
+/// Result type returned from methods that can have `imu::Error`s.
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,607 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::delay::get_time_ns;
+use crate::gpio::{InputPin, Trigger};
+use crate::i2c::I2c;
+use crate::imu::{Error, Result};
+
+// MPU-6050 registers
+const MPU_SMPLRT_DIV: u8 = 0x19;
+const MPU_CONFIG: u8 = 0x1a;
+const MPU_GYRO_CONFIG: u8 = 0x1b;
+const MPU_ACCEL_CONFIG: u8 = 0x1c;
+const MPU_INT_PIN_CFG: u8 = 0x37;
+const MPU_INT_ENABLE: u8 = 0x38;
+const MPU_INT_STATUS: u8 = 0x3a;
+const MPU_ACCEL_XOUT_H: u8 = 0x3b;
+const MPU_PWR_MGMT_1: u8 = 0x6b;
+const MPU_WHO_AM_I: u8 = 0x75;
+
+// ICM-20948 user bank 0 registers
+const ICM_WHO_AM_I: u8 = 0x00;
+const ICM_PWR_MGMT_1: u8 = 0x06;
+const ICM_PWR_MGMT_2: u8 = 0x07;
+const ICM_INT_PIN_CFG: u8 = 0x0f;
+const ICM_INT_ENABLE_1: u8 = 0x11;
+const ICM_INT_STATUS_1: u8 = 0x1a;
+const ICM_ACCEL_XOUT_H: u8 = 0x2d;
+// ICM-20948 user bank 2 registers
+const ICM_GYRO_SMPLRT_DIV: u8 = 0x00;
+const ICM_GYRO_CONFIG_1: u8 = 0x01;
+const ICM_ACCEL_SMPLRT_DIV_1: u8 = 0x10;
+const ICM_ACCEL_SMPLRT_DIV_2: u8 = 0x11;
+const ICM_ACCEL_CONFIG: u8 = 0x14;
+// Available in every user bank
+const ICM_REG_BANK_SEL: u8 = 0x7f;
+
+const WHO_AM_I_MPU6050: u8 = 0x68;
+const WHO_AM_I_ICM20948: u8 = 0xea;
+
+// PWR_MGMT_1 flags, identical for both models
+const PWR_DEVICE_RESET: u8 = 0x80;
+const PWR_CLKSEL_AUTO: u8 = 0x01;
+
+// Data-ready interrupt enable, bit 0 of INT_ENABLE (MPU-6050) and INT_ENABLE_1 (ICM-20948)
+const INT_DATA_READY: u8 = 0x01;
+
+// Digital low-pass filter setting, ~44 Hz (MPU-6050) and ~50 Hz (ICM-20948) bandwidth
+const DLPF_CFG: u8 = 3;
+// Enables the ICM-20948's digital low-pass filter and sample rate divider
+const ICM_FCHOICE: u8 = 0x01;
+
+const RESET_DELAY: Duration = Duration::from_millis(100);
+const WAKE_DELAY: Duration = Duration::from_millis(10);
+
+const DEFAULT_SAMPLE_RATE: f64 = 100.0;
+
+/// IMU models.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ImuModel {
+    /// MPU-6050. Accelerometer and gyroscope with a 1 kHz internal sample rate.
+    Mpu6050,
+    /// ICM-20948. Accelerometer and gyroscope with a 1.125 kHz internal sample rate.
+    ///
+    /// The integrated AK09916 magnetometer isn't supported.
+    Icm20948,
+}
+
+impl ImuModel {
+    fn who_am_i(self) -> u8 {
+        match self {
+            ImuModel::Mpu6050 => WHO_AM_I_MPU6050,
+            ImuModel::Icm20948 => WHO_AM_I_ICM20948,
+        }
+    }
+
+    // Internal sample rate in Hz, divided by 1 + the sample rate divider
+    fn base_sample_rate(self) -> f64 {
+        match self {
+            ImuModel::Mpu6050 => 1000.0,
+            ImuModel::Icm20948 => 1125.0,
+        }
+    }
+}
+
+/// Accelerometer full-scale ranges.
+///
+/// A smaller range results in a higher resolution.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum AccelRange {
+    /// ±2 g.
+    G2 = 0,
+    /// ±4 g.
+    G4 = 1,
+    /// ±8 g.
+    G8 = 2,
+    /// ±16 g.
+    G16 = 3,
+}
+
+impl AccelRange {
+    /// Returns the full-scale range in standard gravity (g).
+    pub fn full_scale_range(self) -> f64 {
+        match self {
+            AccelRange::G2 => 2.0,
+            AccelRange::G4 => 4.0,
+            AccelRange::G8 => 8.0,
+            AccelRange::G16 => 16.0,
+        }
+    }
+}
+
+/// Gyroscope full-scale ranges.
+///
+/// A smaller range results in a higher resolution.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum GyroRange {
+    /// ±250 °/s.
+    Dps250 = 0,
+    /// ±500 °/s.
+    Dps500 = 1,
+    /// ±1000 °/s.
+    Dps1000 = 2,
+    /// ±2000 °/s.
+    Dps2000 = 3,
+}
+
+impl GyroRange {
+    /// Returns the full-scale range in degrees per second (°/s).
+    pub fn full_scale_range(self) -> f64 {
+        match self {
+            GyroRange::Dps250 => 250.0,
+            GyroRange::Dps500 => 500.0,
+            GyroRange::Dps1000 => 1000.0,
+            GyroRange::Dps2000 => 2000.0,
+        }
+    }
+}
+
+/// Scaled accelerometer, gyroscope and temperature measurements.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Sample {
+    accel: [f64; 3],
+    gyro: [f64; 3],
+    temperature: f64,
+    timestamp: Duration,
+}
+
+impl Sample {
+    /// Returns the X, Y and Z acceleration in standard gravity (g).
+    pub fn accel(&self) -> [f64; 3] {
+        self.accel
+    }
+
+    /// Returns the X, Y and Z angular velocity in degrees per second (°/s).
+    pub fn gyro(&self) -> [f64; 3] {
+        self.gyro
+    }
+
+    /// Returns the die temperature in degrees Celsius (°C).
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    /// Returns the time at which the sample was taken.
+    ///
+    /// For samples received through a [`SampleStream`], this is the timestamp
+    /// of the data-ready interrupt, as set by the kernel. Otherwise, it's the time
+    /// the measurements were read, based on `CLOCK_MONOTONIC`. Only the difference
+    /// between timestamps from the same source should be relied upon.
+    ///
+    /// [`SampleStream`]: struct.SampleStream.html
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+}
+
+/// MPU-6050 and ICM-20948 IMU driver.
+///
+/// The AD0 pin selects the slave address. Connecting it to GND or VDD results in
+/// address 0x68 or 0x69 respectively.
+///
+/// During construction, the IMU is reset, woken up from sleep mode, and
+/// configured with a ±2 g accelerometer range, a ±250 °/s gyroscope range and
+/// a sample rate of 100 Hz. The digital low-pass filter is set to a bandwidth
+/// of roughly 45 Hz.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::Gpio;
+/// use rppal::i2c::I2c;
+/// use rppal::imu::{AccelRange, Imu, ImuModel};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut imu = Imu::new(I2c::new()?, ImuModel::Mpu6050, 0x68)?;
+/// imu.set_accel_range(AccelRange::G4)?;
+///
+/// let sample = imu.read()?;
+/// println!("accel {:?} g, gyro {:?} °/s", sample.accel(), sample.gyro());
+///
+/// // Receive samples as soon as the IMU signals new data on GPIO 17
+/// let pin = Gpio::new()?.get(17).unwrap().into_input();
+/// let stream = imu.into_stream(pin, 64)?;
+///
+/// for _ in 0..100 {
+///     let sample = stream.recv()?;
+///     println!("{:?}", sample.accel());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Imu {
+    i2c: I2c,
+    model: ImuModel,
+    accel_range: AccelRange,
+    gyro_range: GyroRange,
+    sample_rate_div: u8,
+    // Currently selected ICM-20948 user bank
+    bank: u8,
+}
+
+impl Imu {
+    /// Constructs a new `Imu` for the device at the specified slave address.
+    ///
+    /// Returns [`Error::UnknownDevice`] if the device doesn't identify as the
+    /// selected model.
+    ///
+    /// [`Error::UnknownDevice`]: enum.Error.html#variant.UnknownDevice
+    pub fn new(mut i2c: I2c, model: ImuModel, address: u16) -> Result<Imu> {
+        i2c.set_slave_address(address)?;
+
+        let mut imu = Imu {
+            i2c,
+            model,
+            accel_range: AccelRange::G2,
+            gyro_range: GyroRange::Dps250,
+            sample_rate_div: 0,
+            bank: 0,
+        };
+
+        if model == ImuModel::Icm20948 {
+            // A previously selected bank is retained until the IMU is reset
+            imu.write_register(ICM_REG_BANK_SEL, 0)?;
+        }
+
+        let id = imu.read_register(imu.who_am_i_register())?;
+        if id != model.who_am_i() {
+            return Err(Error::UnknownDevice(id));
+        }
+
+        imu.reset()?;
+        imu.set_accel_range(AccelRange::G2)?;
+        imu.set_gyro_range(GyroRange::Dps250)?;
+        imu.set_sample_rate(DEFAULT_SAMPLE_RATE)?;
+
+        Ok(imu)
+    }
+
+    /// Returns the model.
+    pub fn model(&self) -> ImuModel {
+        self.model
+    }
+
+    /// Returns the accelerometer full-scale range.
+    pub fn accel_range(&self) -> AccelRange {
+        self.accel_range
+    }
+
+    /// Sets the accelerometer full-scale range.
+    pub fn set_accel_range(&mut self, range: AccelRange) -> Result<()> {
+        match self.model {
+            ImuModel::Mpu6050 => self.write_register(MPU_ACCEL_CONFIG, (range as u8) << 3)?,
+            ImuModel::Icm20948 => {
+                self.select_bank(2)?;
+                self.write_register(
+                    ICM_ACCEL_CONFIG,
+                    (DLPF_CFG << 3) | ((range as u8) << 1) | ICM_FCHOICE,
+                )?;
+                self.select_bank(0)?;
+            }
+        }
+
+        self.accel_range = range;
+
+        Ok(())
+    }
+
+    /// Returns the gyroscope full-scale range.
+    pub fn gyro_range(&self) -> GyroRange {
+        self.gyro_range
+    }
+
+    /// Sets the gyroscope full-scale range.
+    pub fn set_gyro_range(&mut self, range: GyroRange) -> Result<()> {
+        match self.model {
+            ImuModel::Mpu6050 => self.write_register(MPU_GYRO_CONFIG, (range as u8) << 3)?,
+            ImuModel::Icm20948 => {
+                self.select_bank(2)?;
+                self.write_register(
+                    ICM_GYRO_CONFIG_1,
+                    (DLPF_CFG << 3) | ((range as u8) << 1) | ICM_FCHOICE,
+                )?;
+                self.select_bank(0)?;
+            }
+        }
+
+        self.gyro_range = range;
+
+        Ok(())
+    }
+
+    /// Returns the sample rate in herz (Hz).
+    pub fn sample_rate(&self) -> f64 {
+        self.model.base_sample_rate() / (1.0 + f64::from(self.sample_rate_div))
+    }
+
+    /// Sets the sample rate in herz (Hz).
+    ///
+    /// The sample rate is derived from the internal sample rate of 1 kHz (MPU-6050)
+    /// or 1.125 kHz (ICM-20948) through an 8-bit divider, and the closest
+    /// supported rate is selected. The data-ready interrupt fires at the
+    /// configured sample rate.
+    pub fn set_sample_rate(&mut self, rate: f64) -> Result<()> {
+        let div = if rate > 0.0 {
+            (self.model.base_sample_rate() / rate).round() - 1.0
+        } else {
+            255.0
+        };
+        let div = div.clamp(0.0, 255.0) as u8;
+
+        match self.model {
+            ImuModel::Mpu6050 => self.write_register(MPU_SMPLRT_DIV, div)?,
+            ImuModel::Icm20948 => {
+                self.select_bank(2)?;
+                self.write_register(ICM_GYRO_SMPLRT_DIV, div)?;
+                self.write_register(ICM_ACCEL_SMPLRT_DIV_1, 0)?;
+                self.write_register(ICM_ACCEL_SMPLRT_DIV_2, div)?;
+                self.select_bank(0)?;
+            }
+        }
+
+        self.sample_rate_div = div;
+
+        Ok(())
+    }
+
+    /// Reads the most recent accelerometer, gyroscope and temperature
+    /// measurements.
+    pub fn read(&mut self) -> Result<Sample> {
+        let timestamp = Duration::from_nanos(get_time_ns() as u64);
+
+        self.read_sample(timestamp)
+    }
+
+    /// Consumes the `Imu` and `pin`, and returns a [`SampleStream`] that receives
+    /// every new sample.
+    ///
+    /// The IMU's INT pin should be connected to `pin`. The data-ready interrupt is
+    /// configured as an active-high, push-pull, 50 µs pulse, and `pin` is
+    /// configured with an asynchronous interrupt trigger on the rising edge.
+    /// Each time the interrupt fires, the measurements are read on the interrupt
+    /// polling thread, and sent through a channel that holds up to `capacity`
+    /// samples. Samples that arrive while the channel is full are dropped.
+    ///
+    /// [`SampleStream`]: struct.SampleStream.html
+    pub fn into_stream(self, mut pin: InputPin, capacity: usize) -> Result<SampleStream> {
+        let (int_pin_cfg, int_enable, int_status) = match self.model {
+            ImuModel::Mpu6050 => (MPU_INT_PIN_CFG, MPU_INT_ENABLE, MPU_INT_STATUS),
+            ImuModel::Icm20948 => (ICM_INT_PIN_CFG, ICM_INT_ENABLE_1, ICM_INT_STATUS_1),
+        };
+
+        // Active high, push-pull, 50 µs pulse
+        self.write_register(int_pin_cfg, 0)?;
+        self.write_register(int_enable, INT_DATA_READY)?;
+        self.read_register(int_status)?;
+
+        let imu = Arc::new(Mutex::new(self));
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+
+        let callback_imu = imu.clone();
+        let callback_dropped = dropped.clone();
+        pin.set_async_interrupt(Trigger::RisingEdge, move |event| {
+            let sample = match callback_imu.lock() {
+                Ok(mut imu) => imu.read_sample(event.timestamp()),
+                Err(_) => return,
+            };
+
+            if let Err(TrySendError::Full(_)) = sender.try_send(sample) {
+                callback_dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        })?;
+
+        Ok(SampleStream {
+            imu,
+            pin,
+            receiver,
+            dropped,
+        })
+    }
+
+    /// Consumes the `Imu`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        let pwr_mgmt_1 = match self.model {
+            ImuModel::Mpu6050 => MPU_PWR_MGMT_1,
+            ImuModel::Icm20948 => ICM_PWR_MGMT_1,
+        };
+
+        self.write_register(pwr_mgmt_1, PWR_DEVICE_RESET)?;
+        sleep(RESET_DELAY);
+        // A reset selects user bank 0
+        self.bank = 0;
+
+        // Clear the sleep bit, and select the best available clock source
+        self.write_register(pwr_mgmt_1, PWR_CLKSEL_AUTO)?;
+        match self.model {
+            ImuModel::Mpu6050 => self.write_register(MPU_CONFIG, DLPF_CFG)?,
+            // Enable all accelerometer and gyroscope axes
+            ImuModel::Icm20948 => self.write_register(ICM_PWR_MGMT_2, 0)?,
+        }
+        sleep(WAKE_DELAY);
+
+        Ok(())
+    }
+
+    fn read_sample(&mut self, timestamp: Duration) -> Result<Sample> {
+        let mut buffer = [0u8; 14];
+
+        // The MPU-6050 stores the temperature in between the accelerometer and
+        // gyroscope measurements, while the ICM-20948 stores it at the end.
+        let (accel, gyro, temp) = match self.model {
+            ImuModel::Mpu6050 => {
+                self.i2c.write_read(&[MPU_ACCEL_XOUT_H], &mut buffer)?;
+                (0, 8, 6)
+            }
+            ImuModel::Icm20948 => {
+                self.i2c.write_read(&[ICM_ACCEL_XOUT_H], &mut buffer)?;
+                (0, 6, 12)
+            }
+        };
+
+        let raw =
+            |offset: usize| f64::from(i16::from_be_bytes([buffer[offset], buffer[offset + 1]]));
+
+        let accel_scale = self.accel_range.full_scale_range() / 32768.0;
+        let gyro_scale = self.gyro_range.full_scale_range() / 32768.0;
+
+        let temperature = match self.model {
+            ImuModel::Mpu6050 => raw(temp) / 340.0 + 36.53,
+            ImuModel::Icm20948 => raw(temp) / 333.87 + 21.0,
+        };
+
+        Ok(Sample {
+            accel: [
+                raw(accel) * accel_scale,
+                raw(accel + 2) * accel_scale,
+                raw(accel + 4) * accel_scale,
+            ],
+            gyro: [
+                raw(gyro) * gyro_scale,
+                raw(gyro + 2) * gyro_scale,
+                raw(gyro + 4) * gyro_scale,
+            ],
+            temperature,
+            timestamp,
+        })
+    }
+
+    fn disable_interrupt(&mut self) -> Result<()> {
+        let int_enable = match self.model {
+            ImuModel::Mpu6050 => MPU_INT_ENABLE,
+            ImuModel::Icm20948 => ICM_INT_ENABLE_1,
+        };
+
+        self.write_register(int_enable, 0)
+    }
+
+    fn who_am_i_register(&self) -> u8 {
+        match self.model {
+            ImuModel::Mpu6050 => MPU_WHO_AM_I,
+            ImuModel::Icm20948 => ICM_WHO_AM_I,
+        }
+    }
+
+    fn select_bank(&mut self, bank: u8) -> Result<()> {
+        if self.bank != bank {
+            self.write_register(ICM_REG_BANK_SEL, bank << 4)?;
+            self.bank = bank;
+        }
+
+        Ok(())
+    }
+
+    fn read_register(&self, register: u8) -> Result<u8> {
+        Ok(self.i2c.smbus_read_byte(register)?)
+    }
+
+    fn write_register(&self, register: u8, value: u8) -> Result<()> {
+        Ok(self.i2c.smbus_write_byte(register, value)?)
+    }
+}
+
+/// Receives samples from an [`Imu`] as soon as they're available.
+///
+/// `SampleStream` is returned by [`Imu::into_stream`]. Samples are read on the
+/// interrupt polling thread of the [`InputPin`] connected to the IMU's INT pin.
+/// Any errors that occur while reading a sample are passed along instead.
+///
+/// Dropping the `SampleStream` stops the interrupt trigger. Use [`into_inner`]
+/// to stop receiving samples and get the `Imu` and `InputPin` back.
+///
+/// [`Imu`]: struct.Imu.html
+/// [`Imu::into_stream`]: struct.Imu.html#method.into_stream
+/// [`InputPin`]: ../gpio/struct.InputPin.html
+/// [`into_inner`]: #method.into_inner
+#[derive(Debug)]
+pub struct SampleStream {
+    imu: Arc<Mutex<Imu>>,
+    pin: InputPin,
+    receiver: Receiver<Result<Sample>>,
+    dropped: Arc<AtomicUsize>,
+}
+
+impl SampleStream {
+    /// Blocks until a new sample is received.
+    pub fn recv(&self) -> Result<Sample> {
+        self.receiver.recv().map_err(|_| Error::Disconnected)?
+    }
+
+    /// Blocks until a new sample is received, or `timeout` elapses.
+    ///
+    /// Returns [`Error::Timeout`] if no sample was received in time.
+    ///
+    /// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Sample> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(sample) => sample,
+            Err(RecvTimeoutError::Timeout) => Err(Error::Timeout),
+            Err(RecvTimeoutError::Disconnected) => Err(Error::Disconnected),
+        }
+    }
+
+    /// Returns the oldest pending sample without blocking, or `None` if no
+    /// sample is pending.
+    pub fn try_recv(&self) -> Result<Option<Sample>> {
+        match self.receiver.try_recv() {
+            Ok(sample) => sample.map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(Error::Disconnected),
+        }
+    }
+
+    /// Returns the number of samples that were dropped because the channel was
+    /// full.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Stops receiving samples, disables the IMU's data-ready interrupt, and
+    /// returns the `Imu` and `InputPin`.
+    ///
+    /// Any pending samples are discarded.
+    pub fn into_inner(self) -> Result<(Imu, InputPin)> {
+        let SampleStream { imu, mut pin, .. } = self;
+
+        // Stopping the interrupt trigger drops the callback's reference to imu
+        pin.clear_async_interrupt()?;
+
+        let mut imu = match Arc::try_unwrap(imu) {
+            Ok(imu) => imu.into_inner().unwrap_or_else(|err| err.into_inner()),
+            Err(_) => return Err(Error::Disconnected),
+        };
+
+        imu.disable_interrupt()?;
+
+        Ok((imu, pin))
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Infrared remote control receiver and transmitter.
+//!
+//! [`Receiver`] decodes NEC and RC-5 frames from the output of a demodulating
+//! infrared receiver module, such as the TSOP38238 or VS1838B, connected to any
+//! available GPIO pin. These modules pull their output low while they detect a
+//! modulated carrier (a mark), and release it otherwise (a space). Edges are timed
+//! by the kernel using synchronous interrupts, so decoding doesn't rely on busy
+//! waiting.
+//!
+//! [`Transmitter`] sends NEC and RC-5 codes through an infrared LED, modulated with
+//! a 38 kHz (NEC) or 36 kHz (RC-5) carrier. The carrier can be generated by the
+//! hardware PWM peripheral, or bit-banged on any available GPIO pin. Most GPIO pins
+//! can't supply enough current to drive an infrared LED directly, so a transistor
+//! is usually required.
+//!
+//! Both types work with raw mark/space durations as well, which are represented
+//! as alternating mark and space durations, starting with a mark. [`decode`]
+//! and [`encode`] convert between [`Code`]s and raw durations.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::gpio::Gpio;
+//! use rppal::ir::Receiver;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let gpio = Gpio::new()?;
+//! let mut receiver = Receiver::new(gpio.get(17).unwrap().into_input())?;
+//!
+//! loop {
+//!     if let Some(code) = receiver.receive(None)? {
+//!         println!("{:?}", code);
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! [`Receiver`]: struct.Receiver.html
+//! [`Transmitter`]: struct.Transmitter.html
+//! [`decode`]: fn.decode.html
+//! [`encode`]: fn.encode.html
+//! [`Code`]: enum.Code.html
+
+use std::result;
+use std::time::Duration;
+
+use quick_error::quick_error;
+
+use crate::gpio;
+use crate::pwm;
+
+mod nec;
+mod rc5;
+mod receiver;
+mod transmitter;
+
+pub use self::receiver::Receiver;
+pub use self::transmitter::Transmitter;
+
+quick_error! {
+/// Errors that can occur when receiving or transmitting infrared codes.
+    #[derive(Debug)]
+    pub enum Error {
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// PWM error.
+        Pwm(err: pwm::Error) { description(err.description()) from() }
+    }
+}
+
+/// Result type returned from methods that can have `ir::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// Infrared remote control protocols.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Protocol {
+    /// NEC, including extended NEC with 16-bit addresses. 38 kHz carrier.
+    Nec,
+    /// Philips RC-5, including RC-5X with 7-bit commands. 36 kHz carrier.
+    Rc5,
+}
+
+impl Protocol {
+    /// Returns the carrier frequency in herz (Hz).
+    pub fn carrier_frequency(self) -> f64 {
+        match self {
+            Protocol::Nec => 38_000.0,
+            Protocol::Rc5 => 36_000.0,
+        }
+    }
+}
+
+/// Decoded infrared remote control code.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Code {
+    /// NEC frame.
+    ///
+    /// Standard NEC frames contain an 8-bit address followed by its inverse. If the
+    /// second address byte isn't the inverse of the first, the frame is treated as
+    /// extended NEC, and `address` contains the full 16-bit address, with the first
+    /// byte in the least significant bits.
+    Nec {
+        /// Device address.
+        address: u16,
+        /// Command.
+        command: u8,
+    },
+    /// NEC repeat code, which is sent roughly every 108 ms while a button is held down.
+    NecRepeat,
+    /// RC-5 frame.
+    ///
+    /// The `toggle` bit changes every time a button is pressed, which distinguishes
+    /// a new button press from a held down button.
+    Rc5 {
+        /// Device address (0-31).
+        address: u8,
+        /// Command (0-127).
+        command: u8,
+        /// Toggle bit.
+        toggle: bool,
+    },
+}
+
+impl Code {
+    /// Returns the protocol used by this code.
+    pub fn protocol(&self) -> Protocol {
+        match self {
+            Code::Nec { .. } | Code::NecRepeat => Protocol::Nec,
+            Code::Rc5 { .. } => Protocol::Rc5,
+        }
+    }
+}
+
+/// Decodes a frame of alternating mark and space durations, starting with a mark.
+///
+/// Returns `None` if the durations don't match any of the supported protocols.
+pub fn decode(pulses: &[Duration]) -> Option<Code> {
+    nec::decode(pulses).or_else(|| rc5::decode(pulses))
+}
+
+/// Encodes `code` into alternating mark and space durations, starting with a mark.
+///
+/// The last duration is always a mark.
+pub fn encode(code: &Code) -> Vec<Duration> {
+    match code {
+        Code::Nec { .. } | Code::NecRepeat => nec::encode(code),
+        Code::Rc5 { .. } => rc5::encode(code),
+    }
+}
+
+// Returns true if duration is within 35% of expected_us. Receiver modules tend to
+// stretch marks and shorten spaces by up to 100-150 µs, and remotes aren't all that
+// accurate either.
+pub(crate) fn matches(duration: Duration, expected_us: u64) -> bool {
+    let duration_us = duration.as_micros() as u64;
+    let tolerance_us = expected_us * 35 / 100;
+
+    duration_us + tolerance_us >= expected_us && duration_us <= expected_us + tolerance_us
+}
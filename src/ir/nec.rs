@@ -0,0 +1,122 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::Duration;
+
+use crate::ir::{matches, Code};
+
+const LEADER_MARK: u64 = 9000;
+const LEADER_SPACE: u64 = 4500;
+const REPEAT_SPACE: u64 = 2250;
+const BIT_MARK: u64 = 562;
+const ZERO_SPACE: u64 = 562;
+const ONE_SPACE: u64 = 1687;
+
+pub(crate) fn decode(pulses: &[Duration]) -> Option<Code> {
+    if pulses.len() < 3 || !matches(pulses[0], LEADER_MARK) {
+        return None;
+    }
+
+    if pulses.len() < 67 {
+        if matches(pulses[1], REPEAT_SPACE) && matches(pulses[2], BIT_MARK) {
+            return Some(Code::NecRepeat);
+        }
+
+        return None;
+    }
+
+    if !matches(pulses[1], LEADER_SPACE) {
+        return None;
+    }
+
+    // 32 data bits, LSB first, each consisting of a mark followed by a space
+    let mut data: u32 = 0;
+    for bit in 0..32 {
+        let mark = pulses[2 + bit * 2];
+        let space = pulses[3 + bit * 2];
+
+        if !matches(mark, BIT_MARK) {
+            return None;
+        }
+
+        if matches(space, ONE_SPACE) {
+            data |= 1 << bit;
+        } else if !matches(space, ZERO_SPACE) {
+            return None;
+        }
+    }
+
+    let address = data as u8;
+    let address_inv = (data >> 8) as u8;
+    let command = (data >> 16) as u8;
+    let command_inv = (data >> 24) as u8;
+
+    if command != !command_inv {
+        return None;
+    }
+
+    Some(Code::Nec {
+        address: if address == !address_inv {
+            u16::from(address)
+        } else {
+            data as u16
+        },
+        command,
+    })
+}
+
+pub(crate) fn encode(code: &Code) -> Vec<Duration> {
+    let mut pulses: Vec<u64> = Vec::with_capacity(67);
+
+    match *code {
+        Code::Nec { address, command } => {
+            let address = if address <= 0xff {
+                address | (u16::from(!(address as u8)) << 8)
+            } else {
+                address
+            };
+
+            let data =
+                u32::from(address) | (u32::from(command) << 16) | (u32::from(!command) << 24);
+
+            pulses.push(LEADER_MARK);
+            pulses.push(LEADER_SPACE);
+
+            for bit in 0..32 {
+                pulses.push(BIT_MARK);
+                pulses.push(if data & (1 << bit) > 0 {
+                    ONE_SPACE
+                } else {
+                    ZERO_SPACE
+                });
+            }
+        }
+        Code::NecRepeat => {
+            pulses.push(LEADER_MARK);
+            pulses.push(REPEAT_SPACE);
+        }
+        _ => return Vec::new(),
+    }
+
+    // Final mark, which ends the last space
+    pulses.push(BIT_MARK);
+
+    pulses.into_iter().map(Duration::from_micros).collect()
+}
@@ -0,0 +1,131 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::Duration;
+
+use crate::ir::{matches, Code};
+
+// Each bit is 1.778 ms, split into two halves. A 1 bit is a space followed by a
+// mark, and a 0 bit is a mark followed by a space.
+const HALF_BIT: u64 = 889;
+const BITS: usize = 14;
+
+pub(crate) fn decode(pulses: &[Duration]) -> Option<Code> {
+    if pulses.is_empty() || pulses.len() > BITS * 2 {
+        return None;
+    }
+
+    // The first start bit is always 1, so its first half is a space that can't be
+    // detected by the receiver
+    let mut halves = Vec::with_capacity(BITS * 2);
+    halves.push(false);
+
+    for (idx, pulse) in pulses.iter().enumerate() {
+        let mark = idx % 2 == 0;
+
+        let count = if matches(*pulse, HALF_BIT) {
+            1
+        } else if matches(*pulse, HALF_BIT * 2) {
+            2
+        } else {
+            return None;
+        };
+
+        for _ in 0..count {
+            halves.push(mark);
+        }
+    }
+
+    // If the last bit is 0, its second half is a space that ends the frame
+    if halves.len() == BITS * 2 - 1 {
+        halves.push(false);
+    }
+
+    if halves.len() != BITS * 2 {
+        return None;
+    }
+
+    let mut data: u16 = 0;
+    for bit in halves.chunks(2) {
+        data <<= 1;
+
+        match (bit[0], bit[1]) {
+            (false, true) => data |= 1,
+            (true, false) => (),
+            _ => return None,
+        }
+    }
+
+    // The second start bit is the inverted 7th command bit in RC-5X
+    let field = data & (1 << 12) == 0;
+
+    Some(Code::Rc5 {
+        address: ((data >> 6) & 0x1f) as u8,
+        command: (data & 0x3f) as u8 | if field { 0x40 } else { 0 },
+        toggle: data & (1 << 11) > 0,
+    })
+}
+
+pub(crate) fn encode(code: &Code) -> Vec<Duration> {
+    let (address, command, toggle) = match *code {
+        Code::Rc5 {
+            address,
+            command,
+            toggle,
+        } => (address, command, toggle),
+        _ => return Vec::new(),
+    };
+
+    let data: u16 = (1 << 13)
+        | if command & 0x40 > 0 { 0 } else { 1 << 12 }
+        | if toggle { 1 << 11 } else { 0 }
+        | (u16::from(address & 0x1f) << 6)
+        | u16::from(command & 0x3f);
+
+    // Convert the bits into half bits, and merge consecutive halves of the same
+    // kind into a single mark or space.
+    let mut pulses: Vec<(bool, u64)> = Vec::with_capacity(BITS * 2);
+    for bit in (0..BITS).rev() {
+        let halves = if data & (1 << bit) > 0 {
+            [false, true]
+        } else {
+            [true, false]
+        };
+
+        for &mark in &halves {
+            match pulses.last_mut() {
+                Some(last) if last.0 == mark => last.1 += HALF_BIT,
+                _ => pulses.push((mark, HALF_BIT)),
+            }
+        }
+    }
+
+    // Skip the leading space of the first start bit, and the trailing space of
+    // the last bit
+    if let Some(&(false, _)) = pulses.last() {
+        pulses.pop();
+    }
+
+    pulses
+        .into_iter()
+        .skip_while(|pulse| !pulse.0)
+        .map(|pulse| Duration::from_micros(pulse.1))
+        .collect()
+}
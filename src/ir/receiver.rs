@@ -0,0 +1,120 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::{Duration, Instant};
+
+use crate::gpio::{InputPin, Trigger};
+use crate::ir::{decode, Code, Result};
+
+// Maximum time between two edges within a frame. The longest mark or space
+// is the 9 ms NEC leader mark.
+const FRAME_GAP: Duration = Duration::from_millis(15);
+// A full NEC frame contains 68 edges
+const QUEUE_CAPACITY: usize = 128;
+
+/// Infrared remote control receiver.
+///
+/// `Receiver` configures a synchronous interrupt trigger on both edges for
+/// the pin connected to the receiver module's output. Any previously configured
+/// synchronous interrupt trigger is replaced.
+///
+/// A frame ends when no edges are detected for 15 ms.
+#[derive(Debug)]
+pub struct Receiver {
+    pin: InputPin,
+}
+
+impl Receiver {
+    /// Constructs a new `Receiver`.
+    pub fn new(mut pin: InputPin) -> Result<Receiver> {
+        pin.set_interrupt(Trigger::Both)?;
+        pin.set_interrupt_queue_capacity(QUEUE_CAPACITY);
+
+        Ok(Receiver { pin })
+    }
+
+    /// Blocks until a supported frame is received, and returns the decoded code.
+    ///
+    /// Frames that can't be decoded are skipped. `timeout` can be set to `None`
+    /// to wait indefinitely. Returns `Ok(None)` if no supported frame was received
+    /// before the timeout elapsed. Any frame that's still being received when the
+    /// timeout elapses is completed first.
+    pub fn receive(&mut self, timeout: Option<Duration>) -> Result<Option<Code>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            let remaining =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+            match self.receive_raw(remaining)? {
+                Some(pulses) => {
+                    if let Some(code) = decode(&pulses) {
+                        return Ok(Some(code));
+                    }
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Blocks until a frame is received, and returns its alternating mark and
+    /// space durations, starting with a mark.
+    ///
+    /// `timeout` can be set to `None` to wait indefinitely. Returns `Ok(None)` if
+    /// no frame was received before the timeout elapsed.
+    pub fn receive_raw(&mut self, timeout: Option<Duration>) -> Result<Option<Vec<Duration>>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        // A mark starts with a falling edge, since the receiver module's output
+        // is active-low
+        let start = loop {
+            let remaining =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+            match self.pin.poll_interrupt(false, remaining)? {
+                Some(event) if event.trigger() == Trigger::FallingEdge => break event,
+                Some(_) => (),
+                None => return Ok(None),
+            }
+        };
+
+        let mut pulses = Vec::new();
+        let mut last = start;
+
+        while let Some(event) = self.pin.poll_interrupt(false, Some(FRAME_GAP))? {
+            // Skip duplicate edges caused by missed events
+            if event.trigger() == last.trigger() {
+                continue;
+            }
+
+            pulses.push(event.timestamp().saturating_sub(last.timestamp()));
+            last = event;
+        }
+
+        Ok(Some(pulses))
+    }
+
+    /// Returns the underlying `InputPin`, after clearing its interrupt trigger.
+    pub fn into_pin(mut self) -> Result<InputPin> {
+        self.pin.clear_interrupt()?;
+
+        Ok(self.pin)
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::Duration;
+
+use crate::delay::{delay_until_ns, duration_to_ns, get_time_ns};
+use crate::gpio::OutputPin;
+use crate::ir::{encode, Code, Result};
+use crate::pwm::Pwm;
+
+const NANOS_PER_SEC: f64 = 1_000_000_000.0;
+// Most receiver modules expect a carrier with a duty cycle between 25% and 50%
+const CARRIER_DUTY_CYCLE: f64 = 0.33;
+
+#[derive(Debug)]
+enum Carrier {
+    Pin(OutputPin),
+    Pwm(Pwm),
+}
+
+/// Infrared remote control transmitter.
+///
+/// The carrier is either bit-banged on an `OutputPin`, or generated by the
+/// hardware PWM peripheral, which is switched on and off for every mark.
+///
+/// Frames are timed by busy waiting on the current thread, which can be
+/// interrupted by the scheduler. Running the transmitting thread with real-time
+/// priority improves accuracy.
+#[derive(Debug)]
+pub struct Transmitter {
+    carrier: Carrier,
+}
+
+impl Transmitter {
+    /// Constructs a new `Transmitter` that bit-bangs the carrier on `pin`.
+    pub fn new(mut pin: OutputPin) -> Transmitter {
+        pin.set_low();
+
+        Transmitter {
+            carrier: Carrier::Pin(pin),
+        }
+    }
+
+    /// Constructs a new `Transmitter` that uses a hardware PWM channel to generate
+    /// the carrier.
+    pub fn with_pwm(pwm: Pwm) -> Result<Transmitter> {
+        pwm.disable()?;
+
+        Ok(Transmitter {
+            carrier: Carrier::Pwm(pwm),
+        })
+    }
+
+    /// Sends `code` using the carrier frequency of its protocol.
+    ///
+    /// A single NEC frame or RC-5 frame is sent. Call `send` repeatedly to
+    /// emulate a held down button, sending [`Code::NecRepeat`] after the initial
+    /// NEC frame every 108 ms.
+    ///
+    /// [`Code::NecRepeat`]: enum.Code.html#variant.NecRepeat
+    pub fn send(&mut self, code: &Code) -> Result<()> {
+        self.send_raw(code.protocol().carrier_frequency(), &encode(code))
+    }
+
+    /// Sends alternating mark and space durations, starting with a mark, using
+    /// the specified carrier frequency in herz (Hz).
+    pub fn send_raw(&mut self, frequency: f64, pulses: &[Duration]) -> Result<()> {
+        let period_ns = (NANOS_PER_SEC / frequency) as i64;
+        let high_ns = (period_ns as f64 * CARRIER_DUTY_CYCLE) as i64;
+
+        if let Carrier::Pwm(ref pwm) = self.carrier {
+            pwm.set_frequency(frequency, CARRIER_DUTY_CYCLE)?;
+        }
+
+        let mut start_ns = get_time_ns();
+
+        for (idx, pulse) in pulses.iter().enumerate() {
+            let end_ns = start_ns + duration_to_ns(*pulse);
+
+            if idx % 2 == 0 {
+                match self.carrier {
+                    Carrier::Pin(ref mut pin) => {
+                        let mut cycle_ns = start_ns;
+                        while cycle_ns < end_ns {
+                            pin.set_high();
+                            delay_until_ns((cycle_ns + high_ns).min(end_ns));
+                            pin.set_low();
+                            delay_until_ns((cycle_ns + period_ns).min(end_ns));
+                            cycle_ns += period_ns;
+                        }
+                    }
+                    Carrier::Pwm(ref pwm) => {
+                        pwm.enable()?;
+                        delay_until_ns(end_ns);
+                        pwm.disable()?;
+                    }
+                }
+            } else {
+                delay_until_ns(end_ns);
+            }
+
+            start_ns = end_ns;
+        }
+
+        Ok(())
+    }
+}
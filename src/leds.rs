@@ -0,0 +1,392 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Interface for LEDs controlled by the kernel's LED class driver.
+//!
+//! The Raspberry Pi's green activity (ACT) LED and red power (PWR) LED are
+//! connected to GPIO pins that are claimed by the kernel, as are any LEDs
+//! configured through the `gpio-led` overlay. Rather than stealing those pins
+//! with [`Gpio::steal`], [`Led`] controls them through `/sys/class/leds`, which
+//! lets the kernel keep track of their state.
+//!
+//! Each LED has a trigger, which determines what drives it. By default, the ACT
+//! LED is driven by SD card activity (`mmc0`), and the PWR LED is either always
+//! on (`default-on`) or indicates undervoltage (`input`). Setting the brightness
+//! while a trigger is active might be overridden by the trigger, so
+//! [`set_brightness`] and related methods switch the trigger to
+//! [`Trigger::None`] first. When `Led` goes out of scope, the LED's original
+//! trigger and brightness are restored.
+//!
+//! Changing the trigger or brightness requires superuser privileges.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rppal::leds::{Led, Trigger};
+//!
+//! # fn main() -> rppal::leds::Result<()> {
+//! let mut act = Led::act()?;
+//!
+//! act.set_trigger(&Trigger::Heartbeat)?;
+//! // ...
+//! act.blink(Duration::from_millis(100), Duration::from_millis(900))?;
+//! // ...
+//! act.on()?;
+//!
+//! // The ACT LED indicates SD card activity again once act goes out of scope
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Gpio::steal`]: ../gpio/struct.Gpio.html#method.steal
+//! [`Led`]: struct.Led.html
+//! [`set_brightness`]: struct.Led.html#method.set_brightness
+//! [`Trigger::None`]: enum.Trigger.html#variant.None
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::result;
+use std::time::Duration;
+
+use libc;
+use quick_error::quick_error;
+
+const LEDS_PATH: &str = "/sys/class/leds";
+
+// The onboard LEDs were renamed from led0/led1 to ACT/PWR in Linux 5.10
+const ACT_NAMES: [&str; 2] = ["ACT", "led0"];
+const PWR_NAMES: [&str; 2] = ["PWR", "led1"];
+
+quick_error! {
+/// Errors that can occur when accessing an LED.
+    #[derive(Debug)]
+    pub enum Error {
+/// I/O error.
+        Io(err: io::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// LED not found.
+///
+/// No LED with the specified name exists in `/sys/class/leds`.
+        NotFound(name: String) { description("LED not found") display("LED {} not found", name) }
+/// Permission denied.
+///
+/// Changing the trigger or brightness of an LED requires superuser privileges.
+        PermissionDenied(path: PathBuf) { description("LED insufficient permissions") display("{} insufficient permissions", path.display()) }
+/// Trigger isn't available.
+///
+/// The trigger isn't supported by the kernel, or its module isn't loaded. The
+/// available triggers can be retrieved with [`Led::triggers`].
+///
+/// [`Led::triggers`]: struct.Led.html#method.triggers
+        TriggerNotAvailable(trigger: String) { description("trigger isn't available") display("trigger {} isn't available", trigger) }
+    }
+}
+
+/// Result type returned from methods that can have `leds::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// LED triggers.
+///
+/// Only the most common triggers have their own variant. Any other trigger
+/// listed by [`Led::triggers`], such as `mmc0`, `cpu0` or `input`, is
+/// represented by `Other`.
+///
+/// [`Led::triggers`]: struct.Led.html#method.triggers
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Trigger {
+    /// No trigger. The LED is controlled through its brightness.
+    None,
+    /// Always on.
+    DefaultOn,
+    /// Blinks in a heartbeat pattern, which speeds up with the system load.
+    Heartbeat,
+    /// Blinks with a configurable on and off time. See [`Led::blink`].
+    ///
+    /// [`Led::blink`]: struct.Led.html#method.blink
+    Timer,
+    /// Any other trigger.
+    Other(String),
+}
+
+impl Trigger {
+    fn from_name(name: &str) -> Trigger {
+        match name {
+            "none" => Trigger::None,
+            "default-on" => Trigger::DefaultOn,
+            "heartbeat" => Trigger::Heartbeat,
+            "timer" => Trigger::Timer,
+            name => Trigger::Other(name.to_owned()),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Trigger::None => "none",
+            Trigger::DefaultOn => "default-on",
+            Trigger::Heartbeat => "heartbeat",
+            Trigger::Timer => "timer",
+            Trigger::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Provides access to an LED through `/sys/class/leds`.
+///
+/// The LED's trigger and brightness are recorded when `Led` is constructed, and
+/// restored when it goes out of scope. Use [`set_restore_on_drop(false)`] to
+/// disable this behavior.
+///
+/// More information can be found in the [module documentation].
+///
+/// [`set_restore_on_drop(false)`]: #method.set_restore_on_drop
+/// [module documentation]: index.html
+#[derive(Debug)]
+pub struct Led {
+    name: String,
+    path: PathBuf,
+    max_brightness: u32,
+    prev_trigger: Trigger,
+    prev_brightness: u32,
+    restore_on_drop: bool,
+}
+
+impl Led {
+    /// Constructs a new `Led` for the LED with the specified name in
+    /// `/sys/class/leds`.
+    pub fn new(name: &str) -> Result<Led> {
+        let path = Path::new(LEDS_PATH).join(name);
+        if name.is_empty() || name.contains('/') || !path.exists() {
+            return Err(Error::NotFound(name.to_owned()));
+        }
+
+        let mut led = Led {
+            name: name.to_owned(),
+            path,
+            max_brightness: 0,
+            prev_trigger: Trigger::None,
+            prev_brightness: 0,
+            restore_on_drop: true,
+        };
+
+        led.max_brightness = led.read_u32("max_brightness")?;
+        led.prev_trigger = led.trigger()?;
+        led.prev_brightness = led.brightness()?;
+
+        Ok(led)
+    }
+
+    /// Constructs a new `Led` for the green activity (ACT) LED.
+    ///
+    /// The ACT LED isn't available on the Raspberry Pi 3 B and Compute Modules,
+    /// where it's controlled by the firmware.
+    pub fn act() -> Result<Led> {
+        Led::find(&ACT_NAMES)
+    }
+
+    /// Constructs a new `Led` for the red power (PWR) LED.
+    ///
+    /// The PWR LED isn't available on models without a controllable power LED,
+    /// such as the Raspberry Pi Zero.
+    pub fn pwr() -> Result<Led> {
+        Led::find(&PWR_NAMES)
+    }
+
+    fn find(names: &[&str]) -> Result<Led> {
+        for name in names {
+            match Led::new(name) {
+                Err(Error::NotFound(_)) => continue,
+                result => return result,
+            }
+        }
+
+        Err(Error::NotFound(names[0].to_owned()))
+    }
+
+    /// Returns the names of all LEDs in `/sys/class/leds`, in alphabetical order.
+    pub fn list() -> Result<Vec<String>> {
+        let mut names = fs::read_dir(LEDS_PATH)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect::<Vec<_>>();
+
+        names.sort();
+
+        Ok(names)
+    }
+
+    /// Returns the LED's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the maximum brightness supported by the LED.
+    ///
+    /// LEDs connected to a GPIO pin only support a maximum brightness of `1`.
+    pub fn max_brightness(&self) -> u32 {
+        self.max_brightness
+    }
+
+    /// Returns the LED's current brightness.
+    ///
+    /// While a trigger is active, the brightness reflects the trigger's most recent
+    /// change, or the brightness the LED is set to when it's turned on.
+    pub fn brightness(&self) -> Result<u32> {
+        self.read_u32("brightness")
+    }
+
+    /// Switches the trigger to [`Trigger::None`], and sets the LED's brightness.
+    ///
+    /// `brightness` is limited to [`max_brightness`].
+    ///
+    /// [`Trigger::None`]: enum.Trigger.html#variant.None
+    /// [`max_brightness`]: #method.max_brightness
+    pub fn set_brightness(&mut self, brightness: u32) -> Result<()> {
+        if self.trigger()? != Trigger::None {
+            self.set_trigger(&Trigger::None)?;
+        }
+
+        self.write(
+            "brightness",
+            &brightness.min(self.max_brightness).to_string(),
+        )
+    }
+
+    /// Switches the trigger to [`Trigger::None`], and turns the LED on at its
+    /// maximum brightness.
+    ///
+    /// [`Trigger::None`]: enum.Trigger.html#variant.None
+    pub fn on(&mut self) -> Result<()> {
+        self.set_brightness(self.max_brightness)
+    }
+
+    /// Switches the trigger to [`Trigger::None`], and turns the LED off.
+    ///
+    /// [`Trigger::None`]: enum.Trigger.html#variant.None
+    pub fn off(&mut self) -> Result<()> {
+        self.set_brightness(0)
+    }
+
+    /// Returns the LED's active trigger.
+    pub fn trigger(&self) -> Result<Trigger> {
+        let triggers = self.read("trigger")?;
+
+        // The active trigger is enclosed in brackets
+        Ok(triggers
+            .split_whitespace()
+            .find(|name| name.starts_with('['))
+            .map(|name| Trigger::from_name(name.trim_start_matches('[').trim_end_matches(']')))
+            .unwrap_or(Trigger::None))
+    }
+
+    /// Returns the triggers that are available for the LED.
+    pub fn triggers(&self) -> Result<Vec<Trigger>> {
+        Ok(self
+            .read("trigger")?
+            .split_whitespace()
+            .map(|name| Trigger::from_name(name.trim_start_matches('[').trim_end_matches(']')))
+            .collect())
+    }
+
+    /// Sets the LED's trigger.
+    ///
+    /// If the trigger isn't available, `set_trigger` returns
+    /// [`Error::TriggerNotAvailable`].
+    ///
+    /// [`Error::TriggerNotAvailable`]: enum.Error.html#variant.TriggerNotAvailable
+    pub fn set_trigger(&mut self, trigger: &Trigger) -> Result<()> {
+        match self.write("trigger", trigger.name()) {
+            Err(Error::Io(ref err)) if err.raw_os_error() == Some(libc::EINVAL) => {
+                Err(Error::TriggerNotAvailable(trigger.name().to_owned()))
+            }
+            result => result,
+        }
+    }
+
+    /// Switches the trigger to [`Trigger::Timer`], and blinks the LED with the
+    /// specified on and off times.
+    ///
+    /// The times have a resolution of one millisecond.
+    ///
+    /// [`Trigger::Timer`]: enum.Trigger.html#variant.Timer
+    pub fn blink(&mut self, on: Duration, off: Duration) -> Result<()> {
+        self.set_trigger(&Trigger::Timer)?;
+
+        // Setting the timer trigger creates delay_on and delay_off
+        self.write("delay_on", &on.as_millis().to_string())?;
+        self.write("delay_off", &off.as_millis().to_string())
+    }
+
+    /// Returns the value of `restore_on_drop`.
+    pub fn restore_on_drop(&self) -> bool {
+        self.restore_on_drop
+    }
+
+    /// When enabled, restores the LED's original trigger and brightness when
+    /// `Led` goes out of scope.
+    ///
+    /// By default, `restore_on_drop` is set to `true`.
+    pub fn set_restore_on_drop(&mut self, restore_on_drop: bool) {
+        self.restore_on_drop = restore_on_drop;
+    }
+
+    fn read(&self, attribute: &str) -> Result<String> {
+        Ok(fs::read_to_string(self.path.join(attribute))?)
+    }
+
+    fn read_u32(&self, attribute: &str) -> Result<u32> {
+        self.read(attribute)?
+            .trim()
+            .parse()
+            .map_err(|_| Error::Io(io::Error::new(io::ErrorKind::InvalidData, attribute)))
+    }
+
+    fn write(&self, attribute: &str, value: &str) -> Result<()> {
+        let path = self.path.join(attribute);
+
+        fs::write(&path, value).map_err(|err| match err.raw_os_error() {
+            Some(libc::EACCES) | Some(libc::EPERM) => Error::PermissionDenied(path),
+            _ => Error::Io(err),
+        })
+    }
+}
+
+impl Drop for Led {
+    fn drop(&mut self) {
+        if !self.restore_on_drop {
+            return;
+        }
+
+        let prev_trigger = self.prev_trigger.clone();
+        if prev_trigger == Trigger::None {
+            let _ = self.set_brightness(self.prev_brightness);
+        } else {
+            let _ = self.set_trigger(&prev_trigger);
+        }
+    }
+}
@@ -18,10 +18,10 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-//! RPPAL is a Rust library that provides access to the Raspberry Pi's GPIO, I2C and SPI peripherals.
+//! RPPAL is a Rust library that provides access to the Raspberry Pi's GPIO, I2C, PWM, SPI and UART peripherals.
 //! Support for additional peripherals will be added in future updates. The library is
-//! compatible with the Raspberry Pi A, A+, B, B+, 2B, 3A+, 3B, 3B+, Compute, Compute 3,
-//! Zero and Zero W.
+//! compatible with the Raspberry Pi A, A+, B, B+, 2B, 3A+, 3B, 3B+, 4B, 400, Compute, Compute 3,
+//! Compute 3+, Compute 4, Zero, Zero W and Zero 2 W.
 
 // Used by rustdoc to link other crates to rppal's docs
 #![doc(html_root_url = "https://docs.rs/rppal/0.10.0")]
@@ -29,14 +29,47 @@
 #![recursion_limit = "128"]
 #![allow(clippy::new_ret_no_self)]
 
+mod error;
 mod user;
 
 #[macro_use]
 mod macros;
 
+pub mod adc;
+pub mod bootloader;
+pub mod camera;
+pub mod can;
+pub mod clock;
+pub mod config;
+pub mod crypto;
+pub mod dac;
+pub mod debugprobe;
+pub mod delay;
+pub mod display;
+pub mod eeprom;
+pub mod expander;
+pub mod fan;
 pub mod gpio;
+pub mod gps;
+#[cfg(any(feature = "hal", feature = "hal-1"))]
+pub mod hal;
 pub mod i2c;
+pub mod imu;
+pub mod ir;
+pub mod leds;
+pub mod modbus;
+pub mod motor;
+pub mod onewire;
+pub mod pcm;
+pub mod power;
 pub mod pwm;
+pub mod rfid;
+pub mod rtc;
+pub mod sdi12;
 pub mod spi;
 pub mod system;
-// pub mod uart;
+pub mod tone;
+pub mod uart;
+pub mod watchdog;
+
+pub use crate::error::{Error, Result};
@@ -0,0 +1,88 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Modbus RTU client.
+//!
+//! [`RtuClient`] communicates with Modbus RTU slave devices, such as energy
+//! meters, solar inverters and PLCs, connected through [`Uart`]. It implements
+//! the RTU framing, including the CRC-16 checksum and the silent interval of
+//! 3.5 character times that separates frames, and supports the following
+//! function codes:
+//!
+//! * 0x03 Read Holding Registers
+//! * 0x04 Read Input Registers
+//! * 0x06 Write Single Register
+//! * 0x10 Write Multiple Registers
+//!
+//! ## RS-485
+//!
+//! Modbus RTU is typically used on an RS-485 bus. Most RS-485 transceivers, such
+//! as the MAX485, need a driver enable (DE) and receiver enable (RE) signal to
+//! switch between sending and receiving. Connect DE and /RE to a GPIO pin, and
+//! configure it with [`RtuClient::set_direction_pin`]. The pin is set high while
+//! a request is sent, and low while waiting for a response.
+//!
+//! [`RtuClient`]: struct.RtuClient.html
+//! [`Uart`]: ../uart/struct.Uart.html
+//! [`RtuClient::set_direction_pin`]: struct.RtuClient.html#method.set_direction_pin
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::uart;
+
+mod rtu;
+
+pub use self::rtu::RtuClient;
+
+quick_error! {
+/// Errors that can occur when communicating with a Modbus device.
+    #[derive(Debug)]
+    pub enum Error {
+/// UART error.
+        Uart(err: uart::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Timeout.
+///
+/// The slave didn't respond in time.
+        Timeout { description("slave didn't respond in time") }
+/// CRC mismatch.
+        Crc { description("CRC mismatch") }
+/// Exception response.
+///
+/// The slave rejected the request. Contains the exception code, for instance
+/// 0x01 (illegal function), 0x02 (illegal data address), 0x03 (illegal data
+/// value) or 0x04 (slave device failure).
+        Exception(code: u8) { description("exception response") }
+/// Unexpected response.
+///
+/// The response was sent by a different slave, contains a different function
+/// code, or doesn't have the expected length or contents.
+        UnexpectedResponse { description("unexpected response") }
+/// Invalid value.
+///
+/// The number of registers is out of range, or a broadcast address was used
+/// for a read request.
+        InvalidValue { description("invalid value") }
+    }
+}
+
+/// Result type returned from methods that can have `modbus::Error`s.
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,366 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::gpio::OutputPin;
+use crate::modbus::{Error, Result};
+use crate::uart::{Queue, Uart};
+
+const FN_READ_HOLDING_REGISTERS: u8 = 0x03;
+const FN_READ_INPUT_REGISTERS: u8 = 0x04;
+const FN_WRITE_SINGLE_REGISTER: u8 = 0x06;
+const FN_WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+
+// Set in the function code of an exception response
+const EXCEPTION_FLAG: u8 = 0x80;
+
+const BROADCAST_ADDRESS: u8 = 0;
+
+const MAX_READ_REGISTERS: usize = 125;
+const MAX_WRITE_REGISTERS: usize = 123;
+
+// Start bit, 8 data bits, parity or second stop bit, and stop bit
+const BITS_PER_CHAR: u64 = 11;
+// Above 19200 Bd, the specification recommends a fixed silent interval
+const FIXED_FRAME_DELAY_LINE_SPEED: u32 = 19200;
+const FIXED_FRAME_DELAY: Duration = Duration::from_micros(1750);
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Modbus RTU client.
+///
+/// `RtuClient` sends requests to slave devices, and waits for their response.
+/// Slave addresses range from 1 through 247. Write requests can be broadcast
+/// to all slaves through address 0, in which case no response is expected.
+///
+/// The silent interval between frames is calculated from the line speed
+/// configured on the [`Uart`] when the `RtuClient` is constructed.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::Gpio;
+/// use rppal::modbus::RtuClient;
+/// use rppal::uart::{Device, Parity, Uart};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let uart = Uart::new(Device::Uart0, 9600, Parity::Even, 8, 1)?;
+/// let mut client = RtuClient::new(uart)?;
+///
+/// // RS-485 transceiver DE and /RE connected to GPIO 18
+/// client.set_direction_pin(Gpio::new()?.get(18).unwrap().into_output_low());
+///
+/// let mut registers = [0u16; 4];
+/// client.read_holding_registers(1, 0x0000, &mut registers)?;
+/// client.write_single_register(1, 0x0010, 500)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Uart`]: ../uart/struct.Uart.html
+#[derive(Debug)]
+pub struct RtuClient {
+    uart: Uart,
+    direction_pin: Option<OutputPin>,
+    timeout: Duration,
+    frame_delay: Duration,
+    // End of the most recent frame, used to enforce the silent interval
+    last_frame: Option<Instant>,
+}
+
+impl RtuClient {
+    /// Constructs a new `RtuClient`.
+    ///
+    /// The read mode of `uart` is changed to wait up to 100 ms for incoming data.
+    /// The response timeout defaults to 1 second.
+    pub fn new(mut uart: Uart) -> Result<RtuClient> {
+        uart.set_read_mode(0, Duration::from_millis(100))?;
+
+        let line_speed = uart.line_speed()?;
+        let frame_delay = if line_speed == 0 || line_speed > FIXED_FRAME_DELAY_LINE_SPEED {
+            FIXED_FRAME_DELAY
+        } else {
+            // 3.5 character times
+            Duration::from_nanos(BITS_PER_CHAR * 3_500_000_000 / u64::from(line_speed))
+        };
+
+        Ok(RtuClient {
+            uart,
+            direction_pin: None,
+            timeout: DEFAULT_TIMEOUT,
+            frame_delay,
+            last_frame: None,
+        })
+    }
+
+    /// Sets the GPIO pin that controls the direction of an RS-485 transceiver.
+    ///
+    /// The pin is set high while a request is sent, and set low afterwards. Any
+    /// previously configured direction pin is returned.
+    pub fn set_direction_pin(&mut self, mut pin: OutputPin) -> Option<OutputPin> {
+        pin.set_low();
+
+        self.direction_pin.replace(pin)
+    }
+
+    /// Removes the direction pin, and returns it.
+    pub fn clear_direction_pin(&mut self) -> Option<OutputPin> {
+        self.direction_pin.take()
+    }
+
+    /// Returns the response timeout.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Sets how long to wait for a complete response before returning
+    /// [`Error::Timeout`].
+    ///
+    /// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Reads `buffer.len()` (1-125) consecutive holding registers starting at
+    /// `address` (function code 0x03).
+    pub fn read_holding_registers(
+        &mut self,
+        slave: u8,
+        address: u16,
+        buffer: &mut [u16],
+    ) -> Result<()> {
+        self.read_registers(FN_READ_HOLDING_REGISTERS, slave, address, buffer)
+    }
+
+    /// Reads `buffer.len()` (1-125) consecutive input registers starting at
+    /// `address` (function code 0x04).
+    pub fn read_input_registers(
+        &mut self,
+        slave: u8,
+        address: u16,
+        buffer: &mut [u16],
+    ) -> Result<()> {
+        self.read_registers(FN_READ_INPUT_REGISTERS, slave, address, buffer)
+    }
+
+    /// Writes `value` to the holding register at `address` (function code 0x06).
+    pub fn write_single_register(&mut self, slave: u8, address: u16, value: u16) -> Result<()> {
+        let request = [
+            slave,
+            FN_WRITE_SINGLE_REGISTER,
+            (address >> 8) as u8,
+            address as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ];
+
+        self.send(&request)?;
+        if slave == BROADCAST_ADDRESS {
+            return Ok(());
+        }
+
+        // The response echoes the request
+        let response = self.receive(slave, FN_WRITE_SINGLE_REGISTER, 8)?;
+        if response[2..6] != request[2..6] {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        Ok(())
+    }
+
+    /// Writes `values` (1-123 registers) to consecutive holding registers starting
+    /// at `address` (function code 0x10).
+    pub fn write_multiple_registers(
+        &mut self,
+        slave: u8,
+        address: u16,
+        values: &[u16],
+    ) -> Result<()> {
+        if values.is_empty() || values.len() > MAX_WRITE_REGISTERS {
+            return Err(Error::InvalidValue);
+        }
+
+        let count = values.len() as u16;
+        let mut request = Vec::with_capacity(7 + values.len() * 2);
+        request.extend_from_slice(&[
+            slave,
+            FN_WRITE_MULTIPLE_REGISTERS,
+            (address >> 8) as u8,
+            address as u8,
+            (count >> 8) as u8,
+            count as u8,
+            (values.len() * 2) as u8,
+        ]);
+        for value in values {
+            request.extend_from_slice(&value.to_be_bytes());
+        }
+
+        self.send(&request)?;
+        if slave == BROADCAST_ADDRESS {
+            return Ok(());
+        }
+
+        // The response contains the starting address and the number of registers
+        let response = self.receive(slave, FN_WRITE_MULTIPLE_REGISTERS, 8)?;
+        if response[2..6] != request[2..6] {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        Ok(())
+    }
+
+    /// Consumes the `RtuClient`, and returns the underlying `Uart` instance.
+    pub fn into_uart(self) -> Uart {
+        self.uart
+    }
+
+    fn read_registers(
+        &mut self,
+        function: u8,
+        slave: u8,
+        address: u16,
+        buffer: &mut [u16],
+    ) -> Result<()> {
+        if slave == BROADCAST_ADDRESS || buffer.is_empty() || buffer.len() > MAX_READ_REGISTERS {
+            return Err(Error::InvalidValue);
+        }
+
+        let count = buffer.len() as u16;
+        self.send(&[
+            slave,
+            function,
+            (address >> 8) as u8,
+            address as u8,
+            (count >> 8) as u8,
+            count as u8,
+        ])?;
+
+        // Slave address, function code, byte count, register values and CRC
+        let response = self.receive(slave, function, 5 + buffer.len() * 2)?;
+        if usize::from(response[2]) != buffer.len() * 2 {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        for (value, bytes) in buffer.iter_mut().zip(response[3..].chunks_exact(2)) {
+            *value = u16::from_be_bytes([bytes[0], bytes[1]]);
+        }
+
+        Ok(())
+    }
+
+    // Sends a request frame after appending the CRC
+    fn send(&mut self, request: &[u8]) -> Result<()> {
+        let mut frame = Vec::with_capacity(request.len() + 2);
+        frame.extend_from_slice(request);
+        frame.extend_from_slice(&crc16(request).to_le_bytes());
+
+        // Wait for the silent interval that marks the end of the previous frame
+        if let Some(last_frame) = self.last_frame {
+            let elapsed = last_frame.elapsed();
+            if elapsed < self.frame_delay {
+                sleep(self.frame_delay - elapsed);
+            }
+        }
+
+        // Discard any leftovers from a previous response
+        self.uart.flush(Queue::Input)?;
+
+        if let Some(pin) = self.direction_pin.as_mut() {
+            pin.set_high();
+        }
+
+        let result = self.uart.write(&frame).and_then(|_| self.uart.drain());
+
+        if let Some(pin) = self.direction_pin.as_mut() {
+            pin.set_low();
+        }
+
+        self.last_frame = Some(Instant::now());
+
+        Ok(result?)
+    }
+
+    // Waits for a response of len bytes, and checks the slave address, function
+    // code and CRC. Exception responses are returned as Error::Exception.
+    fn receive(&mut self, slave: u8, function: u8, len: usize) -> Result<Vec<u8>> {
+        let mut response = vec![0u8; len];
+        let mut received = 0;
+        let deadline = Instant::now() + self.timeout;
+
+        // Exception responses only contain 5 bytes, so check the function code
+        // before waiting for the remainder of the response.
+        let mut expected = 5.min(len);
+        while received < expected {
+            received += self.uart.read(&mut response[received..expected])?;
+
+            if received == expected && expected < len {
+                if response[1] == function | EXCEPTION_FLAG {
+                    break;
+                }
+
+                expected = len;
+            }
+
+            if received < expected && Instant::now() > deadline {
+                return Err(Error::Timeout);
+            }
+        }
+
+        self.last_frame = Some(Instant::now());
+
+        let response = &response[..received];
+        let (frame, crc) = response.split_at(received - 2);
+        if crc16(frame).to_le_bytes() != crc {
+            return Err(Error::Crc);
+        }
+
+        if frame[0] != slave {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        if frame[1] == function | EXCEPTION_FLAG {
+            return Err(Error::Exception(frame[2]));
+        } else if frame[1] != function {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        Ok(response.to_vec())
+    }
+}
+
+// CRC-16/MODBUS (polynomial 0x8005 reflected, initial value 0xffff)
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xffffu16;
+
+    for &byte in data {
+        crc ^= u16::from(byte);
+        for _ in 0..8 {
+            if crc & 0x0001 != 0 {
+                crc = (crc >> 1) ^ 0xa001;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+
+    crc
+}
@@ -0,0 +1,60 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Motor control helpers.
+//!
+//! [`Stepper`] generates step sequences for 4-wire unipolar stepper motors
+//! connected through a driver like the ULN2003, as well as step pulses for
+//! bipolar stepper motor drivers with a STEP/DIR interface, such as the A4988,
+//! DRV8825 or TMC2208. Steps are generated on a background thread, with
+//! acceleration and deceleration ramps, while the current position is tracked.
+//!
+//! [`DcMotor`] controls the speed and direction of DC motors through an H-bridge
+//! driver like the L298N or TB6612FNG, using either hardware or software-based PWM.
+//!
+//! [`Stepper`]: struct.Stepper.html
+//! [`DcMotor`]: struct.DcMotor.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::gpio;
+use crate::pwm;
+
+mod dc;
+mod stepper;
+
+pub use self::dc::{DcMotor, DcMotorState};
+pub use self::stepper::{StepMode, Stepper};
+
+quick_error! {
+/// Errors that can occur when controlling motors.
+    #[derive(Debug)]
+    pub enum Error {
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// PWM error.
+        Pwm(err: pwm::Error) { description(err.description()) from() }
+    }
+}
+
+/// Result type returned from methods that can have `motor::Error`s.
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,257 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::gpio::OutputPin;
+use crate::motor::Result;
+use crate::pwm::Pwm;
+
+const DEFAULT_PWM_FREQUENCY: f64 = 1000.0;
+// Software-based PWM busy waits for most of each cycle, so it's kept lower
+const DEFAULT_SOFT_PWM_FREQUENCY: f64 = 100.0;
+
+#[derive(Debug)]
+enum SpeedChannel {
+    Pwm(Pwm),
+    Soft(OutputPin),
+}
+
+/// Direction and mode of a DC motor.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum DcMotorState {
+    /// Spinning forward.
+    Forward,
+    /// Spinning in reverse.
+    Reverse,
+    /// Both motor terminals are shorted, which actively stops the motor.
+    Brake,
+    /// Both motor terminals are disconnected, which lets the motor spin freely.
+    Coast,
+}
+
+/// H-bridge DC motor driver.
+///
+/// `DcMotor` controls a DC motor through an H-bridge driver with two direction
+/// inputs and an enable input, such as the L298N, L293D or TB6612FNG. The speed
+/// is controlled by a PWM signal on the enable input, which is generated by either
+/// a hardware PWM channel, or software-based PWM on any available GPIO pin.
+///
+/// | State | IN1 | IN2 | EN |
+/// |-------|-----|-----|----|
+/// | Forward | High | Low | PWM |
+/// | Reverse | Low | High | PWM |
+/// | Brake | High | High | High |
+/// | Coast | Low | Low | Low |
+///
+/// The motor coasts when `DcMotor` goes out of scope.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use std::thread::sleep;
+/// use std::time::Duration;
+///
+/// use rppal::gpio::Gpio;
+/// use rppal::motor::DcMotor;
+/// use rppal::pwm::{Channel, Pwm};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gpio = Gpio::new()?;
+/// let mut motor = DcMotor::new(
+///     gpio.get(23).unwrap().into_output(),
+///     gpio.get(24).unwrap().into_output(),
+///     Pwm::new(Channel::Pwm0)?,
+/// )?;
+///
+/// motor.forward(0.75)?;
+/// sleep(Duration::from_secs(2));
+/// motor.brake()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DcMotor {
+    in1: OutputPin,
+    in2: OutputPin,
+    channel: SpeedChannel,
+    frequency: f64,
+    state: DcMotorState,
+    speed: f64,
+}
+
+impl DcMotor {
+    /// Constructs a new `DcMotor`, using a hardware PWM channel connected to the
+    /// driver's enable input.
+    ///
+    /// The PWM frequency defaults to 1 kHz.
+    pub fn new(in1: OutputPin, in2: OutputPin, pwm: Pwm) -> Result<DcMotor> {
+        let mut motor = DcMotor {
+            in1,
+            in2,
+            channel: SpeedChannel::Pwm(pwm),
+            frequency: DEFAULT_PWM_FREQUENCY,
+            state: DcMotorState::Coast,
+            speed: 0.0,
+        };
+
+        motor.coast()?;
+
+        Ok(motor)
+    }
+
+    /// Constructs a new `DcMotor`, using software-based PWM on the pin connected
+    /// to the driver's enable input.
+    ///
+    /// The PWM frequency defaults to 100 Hz.
+    pub fn with_soft_pwm(in1: OutputPin, in2: OutputPin, enable: OutputPin) -> Result<DcMotor> {
+        let mut motor = DcMotor {
+            in1,
+            in2,
+            channel: SpeedChannel::Soft(enable),
+            frequency: DEFAULT_SOFT_PWM_FREQUENCY,
+            state: DcMotorState::Coast,
+            speed: 0.0,
+        };
+
+        motor.coast()?;
+
+        Ok(motor)
+    }
+
+    /// Returns the current state.
+    pub fn state(&self) -> DcMotorState {
+        self.state
+    }
+
+    /// Returns the current speed as a value between `0.0` and `1.0`.
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Returns the PWM frequency in herz (Hz).
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    /// Sets the PWM frequency in herz (Hz).
+    ///
+    /// The new frequency is applied immediately if the motor is spinning.
+    pub fn set_frequency(&mut self, frequency: f64) -> Result<()> {
+        self.frequency = frequency;
+
+        match self.state {
+            DcMotorState::Forward | DcMotorState::Reverse => self.set_duty_cycle(self.speed),
+            _ => Ok(()),
+        }
+    }
+
+    /// Spins the motor forward at the specified speed.
+    ///
+    /// `speed` is specified as a floating point value between `0.0` (stopped) and
+    /// `1.0` (full speed).
+    pub fn forward(&mut self, speed: f64) -> Result<()> {
+        self.in2.set_low();
+        self.in1.set_high();
+        self.run(DcMotorState::Forward, speed)
+    }
+
+    /// Spins the motor in reverse at the specified speed.
+    ///
+    /// `speed` is specified as a floating point value between `0.0` (stopped) and
+    /// `1.0` (full speed).
+    pub fn reverse(&mut self, speed: f64) -> Result<()> {
+        self.in1.set_low();
+        self.in2.set_high();
+        self.run(DcMotorState::Reverse, speed)
+    }
+
+    /// Spins the motor in the direction indicated by the sign of `speed`.
+    ///
+    /// `speed` is specified as a floating point value between `-1.0` (full speed
+    /// in reverse) and `1.0` (full speed forward).
+    pub fn set_speed(&mut self, speed: f64) -> Result<()> {
+        if speed < 0.0 {
+            self.reverse(-speed)
+        } else {
+            self.forward(speed)
+        }
+    }
+
+    /// Actively stops the motor by shorting its terminals.
+    pub fn brake(&mut self) -> Result<()> {
+        self.in1.set_high();
+        self.in2.set_high();
+        self.set_duty_cycle(1.0)?;
+        self.state = DcMotorState::Brake;
+        self.speed = 0.0;
+
+        Ok(())
+    }
+
+    /// Disconnects the motor's terminals, which lets the motor spin freely until
+    /// it comes to a stop.
+    pub fn coast(&mut self) -> Result<()> {
+        self.set_duty_cycle(0.0)?;
+        self.in1.set_low();
+        self.in2.set_low();
+        self.state = DcMotorState::Coast;
+        self.speed = 0.0;
+
+        Ok(())
+    }
+
+    fn run(&mut self, state: DcMotorState, speed: f64) -> Result<()> {
+        let speed = speed.clamp(0.0, 1.0);
+
+        self.set_duty_cycle(speed)?;
+        self.state = state;
+        self.speed = speed;
+
+        Ok(())
+    }
+
+    fn set_duty_cycle(&mut self, duty_cycle: f64) -> Result<()> {
+        match self.channel {
+            SpeedChannel::Pwm(ref pwm) => {
+                pwm.set_frequency(self.frequency, duty_cycle)?;
+                pwm.enable()?;
+            }
+            SpeedChannel::Soft(ref mut pin) => {
+                // Avoid running the PWM thread when the output is constant
+                if duty_cycle <= 0.0 {
+                    pin.clear_pwm()?;
+                    pin.set_low();
+                } else if duty_cycle >= 1.0 {
+                    pin.clear_pwm()?;
+                    pin.set_high();
+                } else {
+                    pin.set_pwm_frequency(self.frequency, duty_cycle)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for DcMotor {
+    fn drop(&mut self) {
+        let _ = self.coast();
+    }
+}
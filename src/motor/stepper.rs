@@ -0,0 +1,532 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::delay::{delay_until_ns, get_time_ns};
+use crate::gpio::soft_pwm::set_realtime_priority;
+use crate::gpio::{Level, OutputPin};
+
+const NANOS_PER_SEC: f64 = 1_000_000_000.0;
+// Minimum STEP pulse width and DIR setup time, which covers the A4988 (1 µs
+// and 200 ns), DRV8825 (1.9 µs and 650 ns) and TMC2208 (100 ns and 20 ns)
+const STEP_PULSE_NS: i64 = 2_000;
+const DIR_SETUP_NS: i64 = 1_000;
+
+const DEFAULT_MAX_SPEED: f64 = 200.0;
+const DEFAULT_ACCELERATION: f64 = 400.0;
+
+const WAVE_SEQUENCE: [u8; 4] = [0b1000, 0b0100, 0b0010, 0b0001];
+const FULL_SEQUENCE: [u8; 4] = [0b1100, 0b0110, 0b0011, 0b1001];
+const HALF_SEQUENCE: [u8; 8] = [
+    0b1000, 0b1100, 0b0100, 0b0110, 0b0010, 0b0011, 0b0001, 0b1001,
+];
+
+/// Step sequences for unipolar stepper motors.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum StepMode {
+    /// Wave drive. A single coil is energized at a time, which results in the
+    /// lowest power consumption and torque.
+    Wave,
+    /// Full step. Two coils are energized at a time, which results in the highest
+    /// torque.
+    Full,
+    /// Half step. Alternates between energizing one and two coils, which doubles
+    /// the number of steps per revolution.
+    Half,
+}
+
+impl StepMode {
+    fn sequence(self) -> &'static [u8] {
+        match self {
+            StepMode::Wave => &WAVE_SEQUENCE,
+            StepMode::Full => &FULL_SEQUENCE,
+            StepMode::Half => &HALF_SEQUENCE,
+        }
+    }
+}
+
+// Each Stepper owns a single Driver, so the size difference between variants is irrelevant
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+enum Driver {
+    Unipolar {
+        pins: [OutputPin; 4],
+        mode: StepMode,
+    },
+    StepDir {
+        step: OutputPin,
+        dir: OutputPin,
+        direction: i64,
+    },
+}
+
+impl Driver {
+    // Moves a single step in the specified direction, where position is the new
+    // position after the step
+    fn step(&mut self, direction: i64, position: i64) {
+        match self {
+            Driver::Unipolar { pins, mode } => {
+                Driver::energize(pins, *mode, position);
+            }
+            Driver::StepDir {
+                step,
+                dir,
+                direction: current,
+            } => {
+                if *current != direction {
+                    dir.write(if direction > 0 {
+                        Level::High
+                    } else {
+                        Level::Low
+                    });
+                    *current = direction;
+                    delay_until_ns(get_time_ns() + DIR_SETUP_NS);
+                }
+
+                let start_ns = get_time_ns();
+                step.set_high();
+                delay_until_ns(start_ns + STEP_PULSE_NS);
+                step.set_low();
+            }
+        }
+    }
+
+    fn energize(pins: &mut [OutputPin; 4], mode: StepMode, position: i64) {
+        let sequence = mode.sequence();
+        let phase = sequence[position.rem_euclid(sequence.len() as i64) as usize];
+
+        for (idx, pin) in pins.iter_mut().enumerate() {
+            pin.write(if phase & (0b1000 >> idx) > 0 {
+                Level::High
+            } else {
+                Level::Low
+            });
+        }
+    }
+
+    fn release(&mut self) {
+        if let Driver::Unipolar { pins, .. } = self {
+            for pin in pins.iter_mut() {
+                pin.set_low();
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Motion {
+    position: i64,
+    target: i64,
+    // Signed speed in steps per second
+    speed: f64,
+    max_speed: f64,
+    acceleration: f64,
+    released: bool,
+    shutdown: bool,
+}
+
+impl Motion {
+    // Lowest speed that can be reached within a single step from standstill
+    fn min_speed(&self) -> f64 {
+        if self.acceleration > 0.0 {
+            (2.0 * self.acceleration).sqrt().min(self.max_speed)
+        } else {
+            self.max_speed
+        }
+    }
+
+    fn is_moving(&self) -> bool {
+        self.position != self.target || self.speed != 0.0
+    }
+
+    // Calculates the direction and speed for the next step, or returns None if
+    // no step is needed
+    fn next_step(&mut self) -> Option<(i64, f64)> {
+        let distance = self.target - self.position;
+        let speed = self.speed.abs();
+
+        let direction = if self.speed > 0.0 {
+            1
+        } else if self.speed < 0.0 {
+            -1
+        } else if distance != 0 {
+            distance.signum()
+        } else {
+            return None;
+        };
+
+        // Remaining steps in the current direction. Negative if we have to reverse.
+        let remaining = distance * direction;
+
+        if self.acceleration <= 0.0 {
+            if remaining <= 0 {
+                self.speed = 0.0;
+                return self.next_step_reversed(distance);
+            }
+
+            return Some((direction, self.max_speed));
+        }
+
+        let stopping_distance = speed * speed / (2.0 * self.acceleration);
+
+        let new_speed = if remaining <= 0 || stopping_distance >= remaining as f64 {
+            (speed * speed - 2.0 * self.acceleration).max(0.0).sqrt()
+        } else {
+            (speed * speed + 2.0 * self.acceleration)
+                .sqrt()
+                .min(self.max_speed)
+        };
+
+        if new_speed < self.min_speed() {
+            if remaining > 0 {
+                return Some((direction, self.min_speed()));
+            }
+
+            // We've come to a stop, either at the target, or past it after the
+            // target was changed while moving
+            self.speed = 0.0;
+            return self.next_step_reversed(distance);
+        }
+
+        Some((direction, new_speed))
+    }
+
+    fn next_step_reversed(&mut self, distance: i64) -> Option<(i64, f64)> {
+        if distance == 0 {
+            None
+        } else {
+            Some((distance.signum(), self.min_speed()))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    motion: Mutex<Motion>,
+    driver: Mutex<Driver>,
+    changed: Condvar,
+    idle: Condvar,
+}
+
+/// Stepper motor driver.
+///
+/// `Stepper` supports 4-wire unipolar stepper motors, where the four coils
+/// are driven directly through a transistor array like the ULN2003, and bipolar
+/// stepper motor drivers with a STEP/DIR interface.
+///
+/// Steps are generated on a background thread, which is assigned real-time
+/// scheduling when the application runs with superuser privileges. Positions
+/// are counted in steps relative to the position at construction, and speeds
+/// are specified in steps per second. The motor accelerates up to the maximum
+/// speed, and decelerates in time to stop at the target position. Changing the
+/// target position while the motor is moving is handled smoothly, including
+/// direction reversals.
+///
+/// For STEP/DIR drivers, the micro-stepping resolution is configured through the
+/// driver's mode select pins, which can be controlled with regular `OutputPin`s.
+/// Positions and speeds are counted in micro-steps in that case.
+///
+/// Changes to the target position or speed are picked up after the current step
+/// has completed. Keep this in mind when using very low speeds.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::Gpio;
+/// use rppal::motor::{StepMode, Stepper};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gpio = Gpio::new()?;
+/// let pins = [
+///     gpio.get(17).unwrap().into_output(),
+///     gpio.get(18).unwrap().into_output(),
+///     gpio.get(27).unwrap().into_output(),
+///     gpio.get(22).unwrap().into_output(),
+/// ];
+///
+/// // 28BYJ-48 with a ULN2003 driver board, 4096 half steps per revolution
+/// let mut stepper = Stepper::unipolar(pins, StepMode::Half);
+/// stepper.set_max_speed(800.0);
+/// stepper.set_acceleration(1600.0);
+///
+/// stepper.move_to(4096);
+/// stepper.wait();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Stepper {
+    shared: Arc<Shared>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Stepper {
+    /// Constructs a new `Stepper` for a 4-wire unipolar stepper motor.
+    ///
+    /// `pins` are connected to the four coils, in the order they're energized
+    /// during a full rotation. For the common ULN2003 driver boards, these are
+    /// IN1, IN2, IN3 and IN4.
+    pub fn unipolar(pins: [OutputPin; 4], mode: StepMode) -> Stepper {
+        let mut driver = Driver::Unipolar { pins, mode };
+        driver.release();
+
+        Stepper::new(driver)
+    }
+
+    /// Constructs a new `Stepper` for a stepper motor driver with a STEP/DIR
+    /// interface.
+    ///
+    /// A step is taken on every rising edge of the `step` pin. The `dir` pin is
+    /// set high for positive steps, and low for negative steps.
+    pub fn step_dir(mut step: OutputPin, mut dir: OutputPin) -> Stepper {
+        step.set_low();
+        dir.set_high();
+
+        Stepper::new(Driver::StepDir {
+            step,
+            dir,
+            direction: 1,
+        })
+    }
+
+    fn new(driver: Driver) -> Stepper {
+        let shared = Arc::new(Shared {
+            motion: Mutex::new(Motion {
+                position: 0,
+                target: 0,
+                speed: 0.0,
+                max_speed: DEFAULT_MAX_SPEED,
+                acceleration: DEFAULT_ACCELERATION,
+                released: true,
+                shutdown: false,
+            }),
+            driver: Mutex::new(driver),
+            changed: Condvar::new(),
+            idle: Condvar::new(),
+        });
+
+        let thread_shared = shared.clone();
+        let thread = thread::spawn(move || {
+            set_realtime_priority();
+            Stepper::run(&thread_shared);
+        });
+
+        Stepper {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    fn run(shared: &Shared) {
+        let mut last_step_ns = get_time_ns();
+
+        loop {
+            let (direction, speed, position) = {
+                let mut motion = shared.motion.lock().unwrap();
+
+                loop {
+                    if motion.shutdown {
+                        return;
+                    }
+
+                    if let Some((direction, speed)) = motion.next_step() {
+                        motion.position += direction;
+                        motion.speed = speed * direction as f64;
+                        motion.released = false;
+                        break (direction, speed, motion.position);
+                    }
+
+                    shared.idle.notify_all();
+                    motion = shared.changed.wait(motion).unwrap();
+                    last_step_ns = get_time_ns();
+                }
+            };
+
+            // Wait until the next step is due, based on the time of the previous step
+            let step_ns = last_step_ns + (NANOS_PER_SEC / speed) as i64;
+            delay_until_ns(step_ns);
+            last_step_ns = step_ns.max(get_time_ns() - (NANOS_PER_SEC / speed) as i64);
+
+            shared.driver.lock().unwrap().step(direction, position);
+        }
+    }
+
+    fn update<F: FnOnce(&mut Motion)>(&self, f: F) {
+        f(&mut self.shared.motion.lock().unwrap());
+        self.shared.changed.notify_all();
+    }
+
+    /// Returns the current position in steps.
+    pub fn position(&self) -> i64 {
+        self.shared.motion.lock().unwrap().position
+    }
+
+    /// Sets the current position without moving the motor, and stops any movement
+    /// in progress immediately.
+    pub fn set_position(&mut self, position: i64) {
+        self.update(|motion| {
+            motion.position = position;
+            motion.target = position;
+            motion.speed = 0.0;
+        });
+    }
+
+    /// Returns the target position in steps.
+    pub fn target(&self) -> i64 {
+        self.shared.motion.lock().unwrap().target
+    }
+
+    /// Returns the current speed in steps per second. Negative speeds indicate
+    /// the motor is moving in the negative direction.
+    pub fn speed(&self) -> f64 {
+        self.shared.motion.lock().unwrap().speed
+    }
+
+    /// Returns the maximum speed in steps per second.
+    pub fn max_speed(&self) -> f64 {
+        self.shared.motion.lock().unwrap().max_speed
+    }
+
+    /// Sets the maximum speed in steps per second.
+    ///
+    /// `max_speed` is set to at least 1 step per second. Defaults to 200 steps per
+    /// second.
+    pub fn set_max_speed(&mut self, max_speed: f64) {
+        self.update(|motion| motion.max_speed = max_speed.max(1.0));
+    }
+
+    /// Returns the acceleration in steps per second per second.
+    pub fn acceleration(&self) -> f64 {
+        self.shared.motion.lock().unwrap().acceleration
+    }
+
+    /// Sets the acceleration and deceleration in steps per second per second.
+    ///
+    /// If `acceleration` is set to `0.0`, the motor immediately starts and stops
+    /// at its maximum speed. Defaults to 400 steps per second per second.
+    pub fn set_acceleration(&mut self, acceleration: f64) {
+        self.update(|motion| motion.acceleration = acceleration.max(0.0));
+    }
+
+    /// Starts moving to the specified absolute position.
+    ///
+    /// `move_to` returns immediately. Use [`wait`] to block until the target
+    /// position has been reached.
+    ///
+    /// [`wait`]: #method.wait
+    pub fn move_to(&mut self, position: i64) {
+        self.update(|motion| motion.target = position);
+    }
+
+    /// Starts moving the specified number of steps, relative to the current
+    /// target position.
+    ///
+    /// `move_by` returns immediately. Use [`wait`] to block until the target
+    /// position has been reached.
+    ///
+    /// [`wait`]: #method.wait
+    pub fn move_by(&mut self, steps: i64) {
+        self.update(|motion| motion.target = motion.target.saturating_add(steps));
+    }
+
+    /// Decelerates to a stop as quickly as the configured acceleration allows.
+    ///
+    /// The target position is changed to the position where the motor will
+    /// come to a stop.
+    pub fn stop(&mut self) {
+        self.update(|motion| {
+            let stopping_distance = if motion.acceleration > 0.0 {
+                (motion.speed * motion.speed / (2.0 * motion.acceleration)) as i64
+            } else {
+                0
+            };
+
+            motion.target = if motion.speed < 0.0 {
+                motion.position - stopping_distance
+            } else if motion.speed > 0.0 {
+                motion.position + stopping_distance
+            } else {
+                motion.position
+            };
+        });
+    }
+
+    /// Returns `true` if the motor is moving.
+    pub fn is_moving(&self) -> bool {
+        self.shared.motion.lock().unwrap().is_moving()
+    }
+
+    /// Blocks until the target position has been reached.
+    pub fn wait(&self) {
+        let mut motion = self.shared.motion.lock().unwrap();
+
+        while motion.is_moving() && !motion.shutdown {
+            motion = self.shared.idle.wait(motion).unwrap();
+        }
+    }
+
+    /// Blocks until the target position has been reached, or the timeout
+    /// elapses.
+    ///
+    /// Returns `true` if the target position was reached.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let motion = self.shared.motion.lock().unwrap();
+
+        let (motion, _) = self
+            .shared
+            .idle
+            .wait_timeout_while(motion, timeout, |motion| motion.is_moving())
+            .unwrap();
+
+        !motion.is_moving()
+    }
+
+    /// De-energizes the coils of a unipolar stepper motor while it's not moving,
+    /// which reduces power consumption and heat, at the cost of holding torque.
+    ///
+    /// The coils are energized again on the next step. `release` has no effect
+    /// for STEP/DIR drivers, which usually have a separate enable pin for this
+    /// purpose.
+    pub fn release(&mut self) {
+        let mut motion = self.shared.motion.lock().unwrap();
+
+        if !motion.is_moving() && !motion.released {
+            self.shared.driver.lock().unwrap().release();
+            motion.released = true;
+        }
+    }
+}
+
+impl Drop for Stepper {
+    fn drop(&mut self) {
+        self.update(|motion| motion.shutdown = true);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        if let Ok(mut driver) = self.shared.driver.lock() {
+            driver.release();
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Interface for 1-Wire devices.
+//!
+//! 1-Wire is a single-wire bus developed by Dallas Semiconductor (now Maxim
+//! Integrated). Multiple devices can share the bus, each of which is addressed
+//! by its unique 64-bit [`RomCode`].
+//!
+//! RPPAL provides two ways to access 1-Wire devices.
+//!
+//! [`OneWire`] is a bit-banged bus master that can use any available GPIO pin.
+//! It implements the reset and presence detection sequence, individual time slots,
+//! ROM commands and the ROM search algorithm, and works together with device-specific
+//! drivers like [`Ds18b20`].
+//!
+//! [`W1`] uses the Linux kernel's w1 subsystem instead, which handles the bus
+//! timing in kernel space. The w1 subsystem can be enabled by adding
+//! `dtoverlay=w1-gpio` to `/boot/config.txt`, which uses GPIO 4 by default. [`W1`]
+//! can list the connected devices, and read the temperature from sensors that
+//! are supported by the kernel's `w1_therm` driver.
+//!
+//! Both options require an external 4.7 kΩ pull-up resistor on the data line.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::gpio::{Gpio, Mode};
+//! use rppal::onewire::{Ds18b20, OneWire};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let gpio = Gpio::new()?;
+//! let mut bus = OneWire::new(gpio.get(4).unwrap().into_io(Mode::Input));
+//!
+//! for rom_code in bus.search()? {
+//!     if let Ok(sensor) = Ds18b20::new(rom_code) {
+//!         println!("{}: {:.2} °C", rom_code, sensor.read_temperature(&mut bus)?);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`RomCode`]: struct.RomCode.html
+//! [`OneWire`]: struct.OneWire.html
+//! [`Ds18b20`]: struct.Ds18b20.html
+//! [`W1`]: struct.W1.html
+
+use std::fmt;
+use std::io;
+use std::result;
+
+use quick_error::quick_error;
+
+mod ds18b20;
+mod soft;
+mod w1;
+
+pub use self::ds18b20::Ds18b20;
+pub use self::soft::OneWire;
+pub use self::w1::W1;
+
+quick_error! {
+/// Errors that can occur when accessing 1-Wire devices.
+    #[derive(Debug)]
+    pub enum Error {
+/// IO error.
+        Io(err: io::Error) { description(err.description()) from() }
+/// No devices responded to the reset pulse.
+        NoPresence { description("no devices are present on the bus") }
+/// CRC mismatch.
+///
+/// The received data is corrupted, which is usually caused by the current
+/// thread getting interrupted during a time slot, or by a noisy connection.
+        Crc { description("CRC mismatch") }
+/// Unsupported device family.
+///
+/// The device's family code doesn't match any of the families supported by the
+/// driver.
+        UnsupportedFamily(family: u8) { description("unsupported device family") }
+/// Timeout.
+///
+/// The device didn't finish the requested operation in time.
+        Timeout { description("device didn't respond in time") }
+    }
+}
+
+/// Result type returned from methods that can have `onewire::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// Unique 64-bit device address.
+///
+/// The ROM code consists of an 8-bit family code, a 48-bit serial number and
+/// an 8-bit CRC. `RomCode` is displayed in the same format the kernel's w1
+/// subsystem uses for its device names, for instance `28-0000075a3b2c`.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct RomCode {
+    bytes: [u8; 8],
+}
+
+impl RomCode {
+    /// Constructs a new `RomCode` from the 8 bytes in the order they're sent on
+    /// the bus, starting with the family code.
+    ///
+    /// Returns [`Error::Crc`] if the last byte doesn't contain a valid CRC.
+    ///
+    /// [`Error::Crc`]: enum.Error.html#variant.Crc
+    pub fn from_bytes(bytes: [u8; 8]) -> Result<RomCode> {
+        if crc8(&bytes[..7]) != bytes[7] {
+            return Err(Error::Crc);
+        }
+
+        Ok(RomCode { bytes })
+    }
+
+    // Constructs a RomCode from a family code and serial number, and
+    // calculates the CRC
+    pub(crate) fn with_serial(family: u8, serial: u64) -> RomCode {
+        let mut bytes = [0u8; 8];
+
+        bytes[0] = family;
+        for (idx, byte) in bytes[1..7].iter_mut().enumerate() {
+            *byte = (serial >> (idx * 8)) as u8;
+        }
+        bytes[7] = crc8(&bytes[..7]);
+
+        RomCode { bytes }
+    }
+
+    /// Returns the 8 bytes in the order they're sent on the bus.
+    pub fn bytes(&self) -> [u8; 8] {
+        self.bytes
+    }
+
+    /// Returns the family code, which identifies the device type.
+    pub fn family(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    /// Returns the 48-bit serial number.
+    pub fn serial(&self) -> u64 {
+        self.bytes[1..7]
+            .iter()
+            .rev()
+            .fold(0, |acc, &byte| (acc << 8) | u64::from(byte))
+    }
+}
+
+impl fmt::Display for RomCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02x}-{:012x}", self.family(), self.serial())
+    }
+}
+
+/// Calculates the Dallas/Maxim CRC-8 (polynomial x^8 + x^5 + x^4 + 1), which is used
+/// to verify ROM codes and most device responses.
+///
+/// Calculating the CRC over a sequence of bytes that ends with its own CRC
+/// results in 0.
+pub fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+
+    for &byte in data {
+        let mut byte = byte;
+        for _ in 0..8 {
+            let mix = (crc ^ byte) & 0x01;
+            crc >>= 1;
+            if mix > 0 {
+                crc ^= 0x8c;
+            }
+            byte >>= 1;
+        }
+    }
+
+    crc
+}
@@ -0,0 +1,121 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::Duration;
+
+use crate::delay::get_time_ns;
+use crate::onewire::{crc8, Error, OneWire, Result, RomCode};
+
+const CMD_CONVERT_T: u8 = 0x44;
+const CMD_READ_SCRATCHPAD: u8 = 0xbe;
+
+// Supported family codes
+const FAMILY_DS18B20: u8 = 0x28;
+const FAMILY_DS1822: u8 = 0x22;
+const FAMILY_DS1825: u8 = 0x3b;
+const FAMILY_DS28EA00: u8 = 0x42;
+
+// Maximum conversion time at 12-bit resolution is 750 ms
+const CONVERSION_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// DS18B20 temperature sensor driver.
+///
+/// `Ds18b20` also supports the compatible DS1822, DS1825 and DS28EA00 sensors.
+///
+/// The sensor needs to be powered through its VDD pin. Parasite power mode isn't
+/// supported, because it requires a strong pull-up on the data line during the
+/// temperature conversion.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::{Gpio, Mode};
+/// use rppal::onewire::{Ds18b20, OneWire};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gpio = Gpio::new()?;
+/// let mut bus = OneWire::new(gpio.get(4).unwrap().into_io(Mode::Input));
+///
+/// let rom_code = bus.search()?[0];
+/// let sensor = Ds18b20::new(rom_code)?;
+/// println!("{:.2} °C", sensor.read_temperature(&mut bus)?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Ds18b20 {
+    rom_code: RomCode,
+}
+
+impl Ds18b20 {
+    /// Constructs a new `Ds18b20` for the device with the specified ROM code.
+    ///
+    /// Returns [`Error::UnsupportedFamily`] if the ROM code doesn't belong to a
+    /// supported sensor.
+    ///
+    /// [`Error::UnsupportedFamily`]: enum.Error.html#variant.UnsupportedFamily
+    pub fn new(rom_code: RomCode) -> Result<Ds18b20> {
+        match rom_code.family() {
+            FAMILY_DS18B20 | FAMILY_DS1822 | FAMILY_DS1825 | FAMILY_DS28EA00 => {
+                Ok(Ds18b20 { rom_code })
+            }
+            family => Err(Error::UnsupportedFamily(family)),
+        }
+    }
+
+    /// Returns the sensor's ROM code.
+    pub fn rom_code(&self) -> RomCode {
+        self.rom_code
+    }
+
+    /// Starts a temperature conversion, waits for it to complete, and returns
+    /// the temperature in degrees Celsius (°C).
+    ///
+    /// Returns [`Error::Timeout`] if the conversion doesn't complete within 1 second,
+    /// or [`Error::Crc`] if the received data is corrupted.
+    ///
+    /// [`Error::Timeout`]: enum.Error.html#variant.Timeout
+    /// [`Error::Crc`]: enum.Error.html#variant.Crc
+    pub fn read_temperature(&self, bus: &mut OneWire) -> Result<f32> {
+        bus.select(&self.rom_code)?;
+        bus.write_byte(CMD_CONVERT_T);
+
+        // The sensor responds with 0 bits while the conversion is in progress
+        let deadline = get_time_ns() + CONVERSION_TIMEOUT.as_nanos() as i64;
+        while !bus.read_bit() {
+            if get_time_ns() > deadline {
+                return Err(Error::Timeout);
+            }
+        }
+
+        let mut scratchpad = [0u8; 9];
+        bus.select(&self.rom_code)?;
+        bus.write_byte(CMD_READ_SCRATCHPAD);
+        bus.read(&mut scratchpad);
+
+        if crc8(&scratchpad) != 0 {
+            return Err(Error::Crc);
+        }
+
+        let raw = i16::from_le_bytes([scratchpad[0], scratchpad[1]]);
+
+        Ok(f32::from(raw) / 16.0)
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::delay::{delay_until_ns, get_time_ns};
+use crate::gpio::{IoPin, Level, Mode, PullUpDown};
+use crate::onewire::{crc8, Error, Result, RomCode};
+
+// Standard speed timing in µs, as recommended in Maxim application note 126
+const TIMING_A: i64 = 6;
+const TIMING_B: i64 = 64;
+const TIMING_C: i64 = 60;
+const TIMING_D: i64 = 10;
+const TIMING_E: i64 = 9;
+const TIMING_F: i64 = 55;
+const TIMING_H: i64 = 480;
+const TIMING_I: i64 = 70;
+const TIMING_J: i64 = 410;
+
+const CMD_SEARCH_ROM: u8 = 0xf0;
+const CMD_MATCH_ROM: u8 = 0x55;
+const CMD_SKIP_ROM: u8 = 0xcc;
+const CMD_ALARM_SEARCH: u8 = 0xec;
+
+/// Bit-banged 1-Wire bus master.
+///
+/// The data line can be connected to any available GPIO pin. The pin emulates
+/// an open-drain output by switching between output mode, where it pulls the line
+/// low, and input mode, where the line is pulled high by the pull-up resistors.
+/// The built-in pull-up resistor is enabled, but isn't strong enough on its own,
+/// so an external 4.7 kΩ pull-up resistor is required.
+///
+/// `OneWire` takes ownership of an [`IoPin`]. When `OneWire` goes out of scope,
+/// the pin is reset to its original mode and the pull-up resistor is disabled,
+/// unless [`reset_on_drop`] was set to `false` on the `IoPin`.
+///
+/// All time slots are timed by busy waiting on the current thread. A thread
+/// getting interrupted during a time slot can cause corrupted data, which is why
+/// every ROM code found during a search is verified using its CRC. Device drivers
+/// should verify any data that comes with a CRC as well.
+///
+/// [`Ds18b20`] is an example of a device driver that uses `OneWire`.
+///
+/// [`IoPin`]: ../gpio/struct.IoPin.html
+/// [`reset_on_drop`]: ../gpio/struct.IoPin.html#method.set_reset_on_drop
+/// [`Ds18b20`]: struct.Ds18b20.html
+#[derive(Debug)]
+pub struct OneWire {
+    pin: IoPin,
+}
+
+impl OneWire {
+    /// Constructs a new `OneWire`.
+    ///
+    /// The pin is configured as an input with its built-in pull-up resistor enabled,
+    /// which keeps the bus idle.
+    pub fn new(mut pin: IoPin) -> OneWire {
+        pin.set_mode(Mode::Input);
+        pin.set_pullupdown(PullUpDown::PullUp);
+        // Once the pin is switched to output mode, it should always pull the line low
+        pin.set_low();
+
+        OneWire { pin }
+    }
+
+    /// Consumes the `OneWire`, and returns the data pin.
+    pub fn into_pin(self) -> IoPin {
+        self.pin
+    }
+
+    /// Sends a reset pulse, and returns `true` if at least one device responded
+    /// with a presence pulse.
+    pub fn reset(&mut self) -> Result<bool> {
+        let start = get_time_ns();
+
+        // A line that's held low indicates a short circuit or a missing pull-up
+        // resistor, in which case we'll never see a presence pulse.
+        if self.pin.read() == Level::Low {
+            return Err(Error::NoPresence);
+        }
+
+        self.pin.set_mode(Mode::Output);
+        delay_until_ns(start + TIMING_H * 1000);
+        self.pin.set_mode(Mode::Input);
+
+        delay_until_ns(start + (TIMING_H + TIMING_I) * 1000);
+        let presence = self.pin.read() == Level::Low;
+        delay_until_ns(start + (TIMING_H + TIMING_I + TIMING_J) * 1000);
+
+        Ok(presence)
+    }
+
+    /// Writes a single bit.
+    pub fn write_bit(&mut self, bit: bool) {
+        let start = get_time_ns();
+
+        let (low, high) = if bit {
+            (TIMING_A, TIMING_B)
+        } else {
+            (TIMING_C, TIMING_D)
+        };
+
+        self.pin.set_mode(Mode::Output);
+        delay_until_ns(start + low * 1000);
+        self.pin.set_mode(Mode::Input);
+        delay_until_ns(start + (low + high) * 1000);
+    }
+
+    /// Reads a single bit.
+    pub fn read_bit(&mut self) -> bool {
+        let start = get_time_ns();
+
+        self.pin.set_mode(Mode::Output);
+        delay_until_ns(start + TIMING_A * 1000);
+        self.pin.set_mode(Mode::Input);
+        delay_until_ns(start + (TIMING_A + TIMING_E) * 1000);
+        let bit = self.pin.read() == Level::High;
+        delay_until_ns(start + (TIMING_A + TIMING_E + TIMING_F) * 1000);
+
+        bit
+    }
+
+    /// Writes a single byte, least significant bit first.
+    pub fn write_byte(&mut self, byte: u8) {
+        for bit in 0..8 {
+            self.write_bit(byte & (1 << bit) > 0);
+        }
+    }
+
+    /// Reads a single byte, least significant bit first.
+    pub fn read_byte(&mut self) -> u8 {
+        (0..8).fold(0, |byte, bit| {
+            if self.read_bit() {
+                byte | (1 << bit)
+            } else {
+                byte
+            }
+        })
+    }
+
+    /// Writes the contents of `buffer`.
+    pub fn write(&mut self, buffer: &[u8]) {
+        for byte in buffer {
+            self.write_byte(*byte);
+        }
+    }
+
+    /// Fills `buffer` with incoming data.
+    pub fn read(&mut self, buffer: &mut [u8]) {
+        for byte in buffer.iter_mut() {
+            *byte = self.read_byte();
+        }
+    }
+
+    /// Sends a reset pulse followed by a Match ROM command, which selects the
+    /// device with the specified ROM code for the next function command.
+    ///
+    /// Returns [`Error::NoPresence`] if no devices responded to the reset pulse.
+    ///
+    /// [`Error::NoPresence`]: enum.Error.html#variant.NoPresence
+    pub fn select(&mut self, rom_code: &RomCode) -> Result<()> {
+        self.reset_with_presence()?;
+
+        self.write_byte(CMD_MATCH_ROM);
+        self.write(&rom_code.bytes());
+
+        Ok(())
+    }
+
+    /// Sends a reset pulse followed by a Skip ROM command, which selects all
+    /// devices on the bus for the next function command.
+    ///
+    /// Returns [`Error::NoPresence`] if no devices responded to the reset pulse.
+    ///
+    /// [`Error::NoPresence`]: enum.Error.html#variant.NoPresence
+    pub fn skip_rom(&mut self) -> Result<()> {
+        self.reset_with_presence()?;
+
+        self.write_byte(CMD_SKIP_ROM);
+
+        Ok(())
+    }
+
+    /// Returns the ROM codes of all devices on the bus.
+    ///
+    /// Returns [`Error::Crc`] if any of the received ROM codes is corrupted,
+    /// in which case the search can be retried. An empty list is returned if no
+    /// devices are present.
+    ///
+    /// [`Error::Crc`]: enum.Error.html#variant.Crc
+    pub fn search(&mut self) -> Result<Vec<RomCode>> {
+        self.search_with(CMD_SEARCH_ROM)
+    }
+
+    /// Returns the ROM codes of all devices on the bus that have their alarm
+    /// flag set.
+    ///
+    /// Returns [`Error::Crc`] if any of the received ROM codes is corrupted.
+    ///
+    /// [`Error::Crc`]: enum.Error.html#variant.Crc
+    pub fn search_alarms(&mut self) -> Result<Vec<RomCode>> {
+        self.search_with(CMD_ALARM_SEARCH)
+    }
+
+    fn reset_with_presence(&mut self) -> Result<()> {
+        if self.reset()? {
+            Ok(())
+        } else {
+            Err(Error::NoPresence)
+        }
+    }
+
+    // ROM search algorithm, as described in Maxim application note 187. Each pass
+    // walks down the binary tree of ROM code bits, where all devices that match
+    // the path taken so far respond with their next bit and its complement.
+    fn search_with(&mut self, command: u8) -> Result<Vec<RomCode>> {
+        let mut rom_codes = Vec::new();
+        let mut rom = [0u8; 8];
+        let mut last_discrepancy = 0;
+
+        loop {
+            if !self.reset()? {
+                return Ok(rom_codes);
+            }
+
+            self.write_byte(command);
+
+            let mut discrepancy = 0;
+            for bit_index in 1..=64 {
+                let byte = (bit_index - 1) / 8;
+                let mask = 1 << ((bit_index - 1) % 8);
+
+                let bit = self.read_bit();
+                let complement = self.read_bit();
+
+                let direction = match (bit, complement) {
+                    // None of the devices responded
+                    (true, true) => return Ok(rom_codes),
+                    // All matching devices have a 0 or a 1 at this position
+                    (bit, complement) if bit != complement => bit,
+                    // Both 0 and 1 are present. Take the same path as last time,
+                    // unless we've reached the previous discrepancy, in which case
+                    // we'll take the 1 branch this time.
+                    _ => {
+                        let direction = if bit_index < last_discrepancy {
+                            rom[byte] & mask > 0
+                        } else {
+                            bit_index == last_discrepancy
+                        };
+
+                        if !direction {
+                            discrepancy = bit_index;
+                        }
+
+                        direction
+                    }
+                };
+
+                if direction {
+                    rom[byte] |= mask;
+                } else {
+                    rom[byte] &= !mask;
+                }
+
+                self.write_bit(direction);
+            }
+
+            if crc8(&rom) != 0 {
+                return Err(Error::Crc);
+            }
+
+            rom_codes.push(RomCode { bytes: rom });
+
+            if discrepancy == 0 {
+                return Ok(rom_codes);
+            }
+
+            last_discrepancy = discrepancy;
+        }
+    }
+}
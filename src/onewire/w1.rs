@@ -0,0 +1,145 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::onewire::{Error, Result, RomCode};
+
+const W1_PATH: &str = "/sys/bus/w1/devices";
+
+/// 1-Wire access through the Linux kernel's w1 subsystem.
+///
+/// The w1 subsystem detects connected devices automatically, and loads the
+/// appropriate kernel driver for each supported device family. It can be enabled
+/// by adding `dtoverlay=w1-gpio` to `/boot/config.txt`. By default, the bus uses
+/// GPIO 4, which can be changed using the overlay's `gpiopin` parameter.
+///
+/// Unlike [`OneWire`], `W1` doesn't rely on busy waiting in user space, which makes
+/// it more reliable on a heavily loaded system.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::onewire::W1;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let w1 = W1::new();
+///
+/// for rom_code in w1.devices()? {
+///     println!("{}: {:.3} °C", rom_code, w1.read_temperature(&rom_code)?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`OneWire`]: struct.OneWire.html
+#[derive(Debug, Clone)]
+pub struct W1 {
+    path: PathBuf,
+}
+
+impl W1 {
+    /// Constructs a new `W1`.
+    pub fn new() -> W1 {
+        W1 {
+            path: PathBuf::from(W1_PATH),
+        }
+    }
+
+    /// Returns the ROM codes of all devices detected by the w1 subsystem.
+    ///
+    /// Bus masters and any entries that can't be parsed as a ROM code are skipped.
+    pub fn devices(&self) -> Result<Vec<RomCode>> {
+        let mut rom_codes = Vec::new();
+
+        for entry in fs::read_dir(&self.path)? {
+            if let Some(rom_code) = entry?.file_name().to_str().and_then(parse_rom_code) {
+                rom_codes.push(rom_code);
+            }
+        }
+
+        rom_codes.sort_by_key(|rom_code| rom_code.bytes());
+
+        Ok(rom_codes)
+    }
+
+    /// Returns the temperature in degrees Celsius (°C) from a sensor that's
+    /// supported by the kernel's `w1_therm` driver, such as the DS18B20.
+    ///
+    /// Returns [`Error::Crc`] if the kernel reports a CRC mismatch.
+    ///
+    /// [`Error::Crc`]: enum.Error.html#variant.Crc
+    pub fn read_temperature(&self, rom_code: &RomCode) -> Result<f32> {
+        let contents = fs::read_to_string(self.device_path(rom_code).join("w1_slave"))?;
+        let mut lines = contents.lines();
+
+        // The first line ends with YES if the CRC matches, and the second line
+        // ends with t=<temperature in m°C>
+        match lines.next() {
+            Some(line) if line.trim_end().ends_with("YES") => (),
+            _ => return Err(Error::Crc),
+        }
+
+        lines
+            .next()
+            .and_then(|line| line.rsplit("t=").next())
+            .and_then(|temp| temp.trim().parse::<i32>().ok())
+            .map(|temp| temp as f32 / 1000.0)
+            .ok_or_else(|| {
+                Error::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid w1_slave format",
+                ))
+            })
+    }
+
+    /// Returns the sysfs directory for the specified device, which can be used to
+    /// access device-specific attributes that aren't directly supported by `W1`.
+    pub fn device_path(&self, rom_code: &RomCode) -> PathBuf {
+        self.path.join(rom_code.to_string())
+    }
+
+    /// Returns `true` if the w1 subsystem is available.
+    pub fn is_available(&self) -> bool {
+        Path::new(&self.path).is_dir()
+    }
+}
+
+impl Default for W1 {
+    fn default() -> W1 {
+        W1::new()
+    }
+}
+
+// Parses device names in the ff-sssssssssss format. The CRC isn't part of the
+// name, so it's recalculated here.
+fn parse_rom_code(name: &str) -> Option<RomCode> {
+    let mut parts = name.splitn(2, '-');
+
+    let family = parts.next().filter(|family| family.len() == 2)?;
+    let serial = parts.next().filter(|serial| serial.len() == 12)?;
+
+    Some(RomCode::with_serial(
+        u8::from_str_radix(family, 16).ok()?,
+        u64::from_str_radix(serial, 16).ok()?,
+    ))
+}
@@ -0,0 +1,731 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Interface for the PCM/I2S peripheral.
+//!
+//! The BCM283x and BCM2711 SoCs contain a PCM/I2S audio interface, which can
+//! exchange serial audio data with I2S devices such as MEMS microphones
+//! (SPH0645, INMP441, ICS-43434) and DACs (PCM5102A, MAX98357A), without going
+//! through ALSA or requiring a device tree overlay for the audio device.
+//!
+//! [`Pcm`] configures the bit clock and frame sync, and transfers samples
+//! through the peripheral's 64-sample transmit and receive FIFOs. The bit clock
+//! is generated by the PCM clock generator, which uses the same clock sources
+//! as the general-purpose clock generators in the [`clock`] module. Samples are
+//! exchanged as `i32` values, interleaved when 2 channels are used, and
+//! [`Pcm::read`] and [`Pcm::write`] block until all samples have been
+//! transferred.
+//!
+//! Because samples are transferred by the CPU rather than through DMA, the FIFOs
+//! run empty or overflow when the current thread doesn't keep up, which can be
+//! detected with [`Pcm::underrun`] and [`Pcm::overflow`]. A 64-sample FIFO holds
+//! roughly 0.7 ms of 48 kHz stereo audio, so consider running the thread with a
+//! real-time scheduling policy for continuous streams.
+//!
+//! ## Pins
+//!
+//! [`Pcm`] uses the following pins:
+//!
+//! * PCM_CLK (bit clock): GPIO 18 (physical pin 12)
+//! * PCM_FS (frame sync, LRCLK): GPIO 19 (physical pin 35)
+//! * PCM_DIN (capture): GPIO 20 (physical pin 38)
+//! * PCM_DOUT (playback): GPIO 21 (physical pin 40)
+//!
+//! PCM_DIN and PCM_DOUT are only used when the corresponding direction is
+//! enabled. Make sure any kernel audio overlays that use the PCM peripheral,
+//! such as `hifiberry-dac` or `googlevoicehat-soundcard`, are disabled.
+//!
+//! ## Permissions
+//!
+//! The PCM and clock manager registers are only accessible through `/dev/mem`,
+//! which requires superuser privileges. The Raspberry Pi 5 isn't supported,
+//! because its I2S peripherals are part of the RP1 I/O controller.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::pcm::{Direction, Pcm, PcmConfig};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Capture 32-bit stereo samples at 48 kHz from an I2S MEMS microphone
+//! let config = PcmConfig::new(Direction::Capture, 48_000, 32);
+//! let mut pcm = Pcm::new(&config)?;
+//!
+//! let mut samples = [0i32; 960];
+//! pcm.read(&mut samples)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Pcm`]: struct.Pcm.html
+//! [`Pcm::read`]: struct.Pcm.html#method.read
+//! [`Pcm::write`]: struct.Pcm.html#method.write
+//! [`Pcm::underrun`]: struct.Pcm.html#method.underrun
+//! [`Pcm::overflow`]: struct.Pcm.html#method.overflow
+//! [`clock`]: ../clock/index.html
+
+use std::fs::OpenOptions;
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+use std::result;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use libc;
+use quick_error::quick_error;
+
+use crate::clock::{self, ClockManager, Divisor};
+use crate::gpio::{self, AltPin, Function, Gpio};
+use crate::system::{DeviceInfo, SoC};
+
+// PCM offset from the peripheral base address
+const PCM_OFFSET: u64 = 0x20_3000;
+const PCM_MEM_SIZE: usize = 4096;
+
+// PCM clock generator control register
+const CM_PCMCTL: usize = 0x98 / std::mem::size_of::<u32>();
+
+// PCM registers
+const PCM_CS: usize = 0x00;
+const PCM_FIFO: usize = 0x04 / std::mem::size_of::<u32>();
+const PCM_MODE: usize = 0x08 / std::mem::size_of::<u32>();
+const PCM_RXC: usize = 0x0c / std::mem::size_of::<u32>();
+const PCM_TXC: usize = 0x10 / std::mem::size_of::<u32>();
+
+const CS_EN: u32 = 1;
+const CS_RXON: u32 = 1 << 1;
+const CS_TXON: u32 = 1 << 2;
+const CS_TXCLR: u32 = 1 << 3;
+const CS_RXCLR: u32 = 1 << 4;
+const CS_TXERR: u32 = 1 << 15;
+const CS_RXERR: u32 = 1 << 16;
+const CS_TXD: u32 = 1 << 19;
+const CS_RXD: u32 = 1 << 20;
+const CS_RXSEX: u32 = 1 << 23;
+const CS_SYNC: u32 = 1 << 24;
+const CS_STBY: u32 = 1 << 25;
+
+const MODE_FLEN_SHIFT: u32 = 10;
+const MODE_FSI: u32 = 1 << 20;
+const MODE_FSM: u32 = 1 << 21;
+const MODE_CLKI: u32 = 1 << 22;
+const MODE_CLKM: u32 = 1 << 23;
+
+const CH1_SHIFT: u32 = 16;
+const CH_WEX: u32 = 1 << 15;
+const CH_EN: u32 = 1 << 14;
+const CH_POS_SHIFT: u32 = 4;
+
+const PIN_CLK: u8 = 18;
+const PIN_FS: u8 = 19;
+const PIN_DIN: u8 = 20;
+const PIN_DOUT: u8 = 21;
+
+const SYNC_TIMEOUT: Duration = Duration::from_millis(10);
+// Maximum time read() and write() wait for the FIFO to make progress
+const FIFO_TIMEOUT: Duration = Duration::from_millis(100);
+
+quick_error! {
+/// Errors that can occur when accessing the PCM peripheral.
+    #[derive(Debug)]
+    pub enum Error {
+/// IO error.
+        Io(err: io::Error) { description(err.description()) from() }
+/// Permission denied when opening `/dev/mem` for read/write access.
+///
+/// The PCM registers can only be accessed through `/dev/mem`, which
+/// requires superuser privileges.
+        PermissionDenied { description("/dev/mem insufficient permissions") }
+/// Unknown model.
+///
+/// The Raspberry Pi model or SoC can't be identified, which is needed to
+/// determine the PCM peripheral's memory address.
+        UnknownModel { description("unknown Raspberry Pi model") }
+/// Unsupported SoC.
+///
+/// The PCM peripheral isn't supported on the BCM2712 (Raspberry Pi 5).
+        UnsupportedSoC(soc: SoC) { description("PCM isn't supported on this SoC") }
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Clock generator error.
+        Clock(err: clock::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Invalid configuration.
+///
+/// The sample width is outside the supported range of 8 to 32 bits, the number
+/// of channels isn't 1 or 2, or the sample rate is 0. Also returned when reading
+/// or writing samples in a direction that isn't enabled.
+        InvalidConfig { description("invalid configuration") }
+/// Invalid frequency.
+///
+/// The bit clock frequency needed for the requested sample rate can't be
+/// reached with any of the available clock sources.
+        InvalidFrequency(frequency: f64) { description("frequency out of range") }
+/// Timeout.
+///
+/// The FIFO didn't make any progress within the expected time, which usually
+/// means the bit clock isn't running.
+        Timeout { description("PCM timed out") }
+    }
+}
+
+/// Result type returned from methods that can have `pcm::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// Transfer directions.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Direction {
+    /// Receives samples on PCM_DIN.
+    Capture,
+    /// Sends samples on PCM_DOUT.
+    Playback,
+    /// Receives and sends samples at the same time.
+    Duplex,
+}
+
+impl Direction {
+    fn capture(self) -> bool {
+        self != Direction::Playback
+    }
+
+    fn playback(self) -> bool {
+        self != Direction::Capture
+    }
+}
+
+/// Serial audio formats.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Format {
+    /// Philips I2S. The frame sync is low for the left channel, and data is
+    /// delayed by one bit clock cycle.
+    I2s,
+    /// Left-justified. The frame sync is high for the left channel, and data
+    /// starts on the first bit clock cycle.
+    LeftJustified,
+}
+
+/// Bit clock and frame sync roles.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Role {
+    /// The Raspberry Pi generates the bit clock and frame sync.
+    Master,
+    /// An external device generates the bit clock and frame sync.
+    Slave,
+}
+
+/// Configuration settings for [`Pcm`].
+///
+/// [`Pcm`]: struct.Pcm.html
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct PcmConfig {
+    direction: Direction,
+    sample_rate: u32,
+    sample_width: u8,
+    slot_width: u8,
+    channels: u8,
+    format: Format,
+    role: Role,
+}
+
+impl PcmConfig {
+    /// Constructs a new `PcmConfig` with the specified direction, sample rate in
+    /// herz (Hz) and sample width in bits.
+    ///
+    /// The remaining settings default to 2 channels in the [`I2s`] format, with
+    /// the Raspberry Pi acting as [`Master`], and a slot width equal to the
+    /// sample width.
+    ///
+    /// [`I2s`]: enum.Format.html#variant.I2s
+    /// [`Master`]: enum.Role.html#variant.Master
+    pub fn new(direction: Direction, sample_rate: u32, sample_width: u8) -> PcmConfig {
+        PcmConfig {
+            direction,
+            sample_rate,
+            sample_width,
+            slot_width: sample_width,
+            channels: 2,
+            format: Format::I2s,
+            role: Role::Master,
+        }
+    }
+
+    /// Sets the number of channels.
+    ///
+    /// With a single channel, only the left channel is transferred.
+    pub fn with_channels(mut self, channels: u8) -> PcmConfig {
+        self.channels = channels;
+        self
+    }
+
+    /// Sets the serial audio format.
+    pub fn with_format(mut self, format: Format) -> PcmConfig {
+        self.format = format;
+        self
+    }
+
+    /// Sets the bit clock and frame sync role.
+    pub fn with_role(mut self, role: Role) -> PcmConfig {
+        self.role = role;
+        self
+    }
+
+    /// Sets the number of bit clock cycles for each channel.
+    ///
+    /// Some devices expect a fixed number of bit clock cycles per channel, for
+    /// instance 32 cycles for 24-bit samples. The slot width is increased to
+    /// the sample width if it's smaller.
+    pub fn with_slot_width(mut self, slot_width: u8) -> PcmConfig {
+        self.slot_width = slot_width;
+        self
+    }
+
+    /// Returns the transfer direction.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Returns the sample rate in herz (Hz).
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the sample width in bits.
+    pub fn sample_width(&self) -> u8 {
+        self.sample_width
+    }
+
+    /// Returns the slot width in bits.
+    pub fn slot_width(&self) -> u8 {
+        self.slot_width.max(self.sample_width)
+    }
+
+    /// Returns the number of channels.
+    pub fn channels(&self) -> u8 {
+        self.channels
+    }
+
+    /// Returns the serial audio format.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the bit clock and frame sync role.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    // Number of bit clock cycles per frame
+    fn frame_length(&self) -> u32 {
+        2 * u32::from(self.slot_width())
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(8..=32).contains(&self.sample_width)
+            || self.slot_width() > 32
+            || !(1..=2).contains(&self.channels)
+            || self.sample_rate == 0
+        {
+            return Err(Error::InvalidConfig);
+        }
+
+        Ok(())
+    }
+}
+
+// PCM register access
+struct Registers {
+    mem_ptr: *mut u32,
+}
+
+impl Registers {
+    fn open(device_info: &DeviceInfo) -> Result<Registers> {
+        let mem_file = match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_SYNC)
+            .open("/dev/mem")
+        {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                return Err(Error::PermissionDenied)
+            }
+            Err(e) => return Err(Error::Io(e)),
+        };
+
+        let mem_ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                PCM_MEM_SIZE,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                mem_file.as_raw_fd(),
+                (device_info.peripheral_base() + PCM_OFFSET) as libc::off_t,
+            )
+        };
+
+        if mem_ptr == libc::MAP_FAILED {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+
+        Ok(Registers {
+            mem_ptr: mem_ptr as *mut u32,
+        })
+    }
+
+    #[inline]
+    fn read(&self, offset: usize) -> u32 {
+        unsafe { ptr::read_volatile(self.mem_ptr.add(offset)) }
+    }
+
+    #[inline]
+    fn write(&self, offset: usize, value: u32) {
+        unsafe {
+            ptr::write_volatile(self.mem_ptr.add(offset), value);
+        }
+    }
+}
+
+impl Drop for Registers {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.mem_ptr as *mut libc::c_void, PCM_MEM_SIZE);
+        }
+    }
+}
+
+impl std::fmt::Debug for Registers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Registers")
+            .field("mem_ptr", &self.mem_ptr)
+            .finish()
+    }
+}
+
+/// Provides access to the PCM/I2S peripheral.
+///
+/// The peripheral and its clock generator are stopped, and the GPIO pins are
+/// reset to their original mode, when `Pcm` goes out of scope.
+#[derive(Debug)]
+pub struct Pcm {
+    config: PcmConfig,
+    registers: Registers,
+    manager: Option<ClockManager>,
+    divisor: Option<Divisor>,
+    tx_started: bool,
+    // Kept so the pins are reset to their original mode on drop
+    _pins: Vec<AltPin>,
+}
+
+impl Pcm {
+    /// Constructs a new `Pcm`, and starts the transfers.
+    ///
+    /// As [`Master`], the actual sample rate might differ slightly from the
+    /// requested sample rate, and can be retrieved with [`sample_rate`].
+    ///
+    /// [`Master`]: enum.Role.html#variant.Master
+    /// [`sample_rate`]: #method.sample_rate
+    pub fn new(config: &PcmConfig) -> Result<Pcm> {
+        config.validate()?;
+
+        let device_info = DeviceInfo::new().map_err(|_| Error::UnknownModel)?;
+        if device_info.soc() == SoC::Bcm2712 {
+            return Err(Error::UnsupportedSoC(SoC::Bcm2712));
+        }
+
+        let (manager, divisor) = if config.role == Role::Master {
+            let frequency = f64::from(config.sample_rate) * f64::from(config.frame_length());
+            let divisor = clock::calculate_divisor(&clock::clock_sources(&device_info)?, frequency)
+                .ok_or(Error::InvalidFrequency(frequency))?;
+
+            (Some(ClockManager::open(&device_info)?), Some(divisor))
+        } else {
+            (None, None)
+        };
+
+        let registers = Registers::open(&device_info)?;
+
+        let gpio = Gpio::new()?;
+        let mut pins = Vec::with_capacity(4);
+        let mut functions = vec![(PIN_CLK, Function::PcmClk), (PIN_FS, Function::PcmFs)];
+        if config.direction.capture() {
+            functions.push((PIN_DIN, Function::PcmDin));
+        }
+
+        if config.direction.playback() {
+            functions.push((PIN_DOUT, Function::PcmDout));
+        }
+
+        for (pin, function) in functions {
+            pins.push(
+                gpio.get(pin)
+                    .ok_or(gpio::Error::PinNotAvailable(pin))?
+                    .into_alt_function(function)?,
+            );
+        }
+
+        let pcm = Pcm {
+            config: *config,
+            registers,
+            manager,
+            divisor,
+            tx_started: false,
+            _pins: pins,
+        };
+
+        pcm.start()?;
+
+        Ok(pcm)
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &PcmConfig {
+        &self.config
+    }
+
+    /// Returns the actual sample rate in herz (Hz).
+    ///
+    /// As [`Slave`], this returns the configured sample rate, since the bit
+    /// clock is generated by the external device.
+    ///
+    /// [`Slave`]: enum.Role.html#variant.Slave
+    pub fn sample_rate(&self) -> f64 {
+        match self.divisor {
+            Some(divisor) => divisor.frequency() / f64::from(self.config.frame_length()),
+            None => f64::from(self.config.sample_rate),
+        }
+    }
+
+    /// Blocks until `samples` has been filled with received samples.
+    ///
+    /// Samples are sign-extended to `i32`, and interleaved when 2 channels are
+    /// used, starting with the left channel.
+    ///
+    /// Returns `Err(Error::Timeout)` if no samples are received for 100 ms, and
+    /// `Err(Error::InvalidConfig)` if capture isn't enabled.
+    pub fn read(&mut self, samples: &mut [i32]) -> Result<()> {
+        if !self.config.direction.capture() {
+            return Err(Error::InvalidConfig);
+        }
+
+        let mut last_progress = Instant::now();
+        let mut index = 0;
+
+        while index < samples.len() {
+            if self.registers.read(PCM_CS) & CS_RXD > 0 {
+                samples[index] = self.registers.read(PCM_FIFO) as i32;
+                index += 1;
+                last_progress = Instant::now();
+            } else if last_progress.elapsed() > FIFO_TIMEOUT {
+                return Err(Error::Timeout);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Blocks until all `samples` have been queued for transmission.
+    ///
+    /// Only the lower bits of each sample are sent, depending on the sample
+    /// width. Samples are interleaved when 2 channels are used, starting with
+    /// the left channel.
+    ///
+    /// Transmission starts once the FIFO is full, or all samples of the first
+    /// call have been queued, to avoid an immediate underrun.
+    ///
+    /// Returns `Err(Error::Timeout)` if the transmit FIFO doesn't accept any
+    /// samples for 100 ms, and `Err(Error::InvalidConfig)` if playback isn't
+    /// enabled.
+    pub fn write(&mut self, samples: &[i32]) -> Result<()> {
+        if !self.config.direction.playback() {
+            return Err(Error::InvalidConfig);
+        }
+
+        let mut last_progress = Instant::now();
+        let mut index = 0;
+
+        while index < samples.len() {
+            if self.registers.read(PCM_CS) & CS_TXD > 0 {
+                self.registers.write(PCM_FIFO, samples[index] as u32);
+                index += 1;
+                last_progress = Instant::now();
+            } else if !self.tx_started {
+                self.start_tx();
+            } else if last_progress.elapsed() > FIFO_TIMEOUT {
+                return Err(Error::Timeout);
+            }
+        }
+
+        if !self.tx_started {
+            self.start_tx();
+        }
+
+        Ok(())
+    }
+
+    fn start_tx(&mut self) {
+        let cs = self.registers.read(PCM_CS) & !(CS_TXERR | CS_RXERR);
+        self.registers.write(PCM_CS, cs | CS_TXON);
+        self.tx_started = true;
+    }
+
+    /// Returns `true` if the transmit FIFO ran empty since the last call,
+    /// causing a gap in the output.
+    pub fn underrun(&mut self) -> bool {
+        self.take_error(CS_TXERR)
+    }
+
+    /// Returns `true` if the receive FIFO overflowed since the last call,
+    /// causing received samples to be lost.
+    pub fn overflow(&mut self) -> bool {
+        self.take_error(CS_RXERR)
+    }
+
+    fn take_error(&mut self, flag: u32) -> bool {
+        let cs = self.registers.read(PCM_CS);
+        if cs & flag > 0 {
+            // Error flags are cleared by writing a 1
+            self.registers
+                .write(PCM_CS, (cs & !(CS_TXERR | CS_RXERR)) | flag);
+            true
+        } else {
+            false
+        }
+    }
+
+    // Waits for 2 PCM clock cycles, which is needed for some register changes
+    // to take effect.
+    fn sync(&self) -> Result<()> {
+        let cs = self.registers.read(PCM_CS) & !(CS_TXERR | CS_RXERR);
+        self.registers.write(PCM_CS, cs & !CS_SYNC);
+        self.registers.write(PCM_CS, cs | CS_SYNC);
+
+        let start = Instant::now();
+        while self.registers.read(PCM_CS) & CS_SYNC == 0 {
+            if start.elapsed() > SYNC_TIMEOUT {
+                return Err(Error::Timeout);
+            }
+
+            sleep(Duration::from_micros(10));
+        }
+
+        Ok(())
+    }
+
+    fn start(&self) -> Result<()> {
+        let config = &self.config;
+        let width = u32::from(config.sample_width);
+        let slot = u32::from(config.slot_width());
+
+        self.registers.write(PCM_CS, 0);
+
+        if let (Some(manager), Some(divisor)) = (&self.manager, &self.divisor) {
+            manager.start(CM_PCMCTL, divisor)?;
+        }
+
+        let mut mode = ((config.frame_length() - 1) << MODE_FLEN_SHIFT) | slot;
+        if config.role == Role::Slave {
+            mode |= MODE_CLKM | MODE_FSM;
+        }
+
+        // I2S devices change data on the falling edge and sample on the rising
+        // edge of the bit clock, while the PCM peripheral defaults to the
+        // opposite.
+        mode |= MODE_CLKI;
+
+        // The frame sync is high for the first half of each frame, which is the
+        // left channel for left-justified, and the right channel for I2S.
+        let position = if config.format == Format::I2s {
+            mode |= MODE_FSI;
+            1
+        } else {
+            0
+        };
+
+        self.registers.write(PCM_MODE, mode);
+
+        // Channel 1 is configured in the upper 16 bits, channel 2 in the lower
+        let channel = |position: u32| {
+            let wex = if width > 24 { CH_WEX } else { 0 };
+            let wid = if width > 24 { width - 24 } else { width - 8 };
+
+            CH_EN | wex | (position << CH_POS_SHIFT) | wid
+        };
+
+        let mut channels = channel(position) << CH1_SHIFT;
+        if config.channels == 2 {
+            channels |= channel(position + slot);
+        }
+
+        self.registers.write(
+            PCM_RXC,
+            if config.direction.capture() {
+                channels
+            } else {
+                0
+            },
+        );
+        self.registers.write(
+            PCM_TXC,
+            if config.direction.playback() {
+                channels
+            } else {
+                0
+            },
+        );
+
+        // Enable the peripheral, and take the FIFO RAM out of standby
+        self.registers.write(PCM_CS, CS_EN | CS_STBY | CS_RXSEX);
+        self.sync()?;
+
+        self.registers.write(
+            PCM_CS,
+            CS_EN | CS_STBY | CS_RXSEX | CS_TXCLR | CS_RXCLR | CS_TXERR | CS_RXERR,
+        );
+        self.sync()?;
+
+        // Transmission is started by write(), once the FIFO contains samples
+        if config.direction.capture() {
+            self.registers
+                .write(PCM_CS, CS_EN | CS_STBY | CS_RXSEX | CS_RXON);
+        }
+
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<()> {
+        self.registers.write(PCM_CS, 0);
+
+        if let Some(manager) = &self.manager {
+            manager.stop(CM_PCMCTL)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Pcm {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+// Required because of the raw pointers to our memory-mapped registers
+unsafe impl Send for Pcm {}
@@ -0,0 +1,132 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Drivers for current and power monitors.
+//!
+//! * [`Ina219`] supports the TI INA219, which measures the voltage across an
+//!   external shunt resistor and the bus voltage, connected through I2C. The
+//!   calibration register is calculated from the shunt resistance and the maximum
+//!   expected current, after which the INA219 reports current and power directly.
+//! * [`Ina260`] supports the TI INA260, which includes a precision 2 mΩ shunt
+//!   resistor and doesn't need to be calibrated, connected through I2C.
+//!
+//! Both drivers return measurements in volts (V), amperes (A) and watts (W).
+//!
+//! ## Battery and UPS HATs
+//!
+//! * [`Max1704x`] supports the Maxim MAX17040, MAX17041, MAX17048 and MAX17049
+//!   fuel gauges, which calculate a battery's state of charge from its voltage,
+//!   connected through I2C.
+//! * [`Ip5306`] supports the Injoinic IP5306 power bank SoC, which combines a
+//!   charger, a boost converter and a coarse battery level indicator, connected
+//!   through I2C.
+//!
+//! Both drivers report the state of charge as a percentage, and the
+//! [`ChargingStatus`] of the battery.
+//!
+//! Many UPS HATs also signal the loss of external power through a GPIO pin.
+//! [`PowerLoss`] monitors that pin and executes a callback when power is lost,
+//! which can call [`shutdown`] to shut down the Raspberry Pi cleanly.
+//!
+//! [`Ina219`]: struct.Ina219.html
+//! [`Ina260`]: struct.Ina260.html
+//! [`Max1704x`]: struct.Max1704x.html
+//! [`Ip5306`]: struct.Ip5306.html
+//! [`ChargingStatus`]: enum.ChargingStatus.html
+//! [`PowerLoss`]: struct.PowerLoss.html
+//! [`shutdown`]: fn.shutdown.html
+
+use std::io;
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::gpio;
+use crate::i2c;
+
+mod ina219;
+mod ina260;
+mod ip5306;
+mod loss;
+mod max1704x;
+
+pub use self::ina219::{BusVoltageRange, Ina219, ShuntVoltageRange};
+pub use self::ina260::Ina260;
+pub use self::ip5306::Ip5306;
+pub use self::loss::{shutdown, PowerLoss};
+pub use self::max1704x::{Max1704x, Max1704xModel};
+
+quick_error! {
+/// Errors that can occur when accessing a power monitor.
+    #[derive(Debug)]
+    pub enum Error {
+/// I2C error.
+        I2c(err: i2c::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// I/O error.
+        Io(err: io::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Unknown device.
+///
+/// The device's manufacturer ID register doesn't contain the expected value.
+/// Contains the value that was read instead.
+        UnknownDevice(id: u16) { description("unknown device") }
+/// Invalid calibration.
+///
+/// The shunt resistance and maximum expected current result in a calibration
+/// value that doesn't fit in the calibration register.
+        InvalidCalibration { description("invalid calibration") }
+/// Not calibrated.
+///
+/// Current and power measurements are unavailable until the calibration register
+/// has been programmed.
+        NotCalibrated { description("not calibrated") }
+/// Math overflow.
+///
+/// The current or power calculation overflowed, which means the measured
+/// current exceeds the configured maximum expected current.
+        Overflow { description("current or power calculation overflowed") }
+/// Invalid alert threshold.
+///
+/// The alert threshold is outside of the range supported by the device.
+        InvalidThreshold { description("invalid alert threshold") }
+/// Shutdown failed.
+///
+/// The shutdown command returned an error. Contains the command's exit code,
+/// if available.
+        ShutdownFailed(code: Option<i32>) { description("shutdown failed") }
+    }
+}
+
+/// Battery charging status.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ChargingStatus {
+    /// The battery is charging.
+    Charging,
+    /// The battery is discharging.
+    Discharging,
+    /// The battery is fully charged.
+    Full,
+    /// The charging status can't be determined.
+    Unknown,
+}
+
+/// Result type returned from methods that can have `power::Error`s.
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,303 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::i2c::I2c;
+use crate::power::{Error, Result};
+
+const REG_CONFIG: u8 = 0x00;
+const REG_SHUNT_VOLTAGE: u8 = 0x01;
+const REG_BUS_VOLTAGE: u8 = 0x02;
+const REG_POWER: u8 = 0x03;
+const REG_CURRENT: u8 = 0x04;
+const REG_CALIBRATION: u8 = 0x05;
+
+const CONFIG_RESET: u16 = 0x8000;
+// 12-bit bus and shunt ADC resolution, 532 µs conversion time
+const CONFIG_ADC_12BIT: u16 = (0b0011 << 7) | (0b0011 << 3);
+const CONFIG_MODE_CONTINUOUS: u16 = 0b111;
+const CONFIG_MODE_POWER_DOWN: u16 = 0b000;
+
+// Bus voltage register flags
+const BUS_VOLTAGE_OVF: u16 = 0x0001;
+
+const SHUNT_VOLTAGE_LSB: f64 = 0.000_01;
+const BUS_VOLTAGE_LSB: f64 = 0.004;
+// Fixed scaling factor used to calculate the calibration value
+const CALIBRATION_SCALE: f64 = 0.04096;
+// The power LSB is always 20 times the current LSB
+const POWER_LSB_FACTOR: f64 = 20.0;
+
+/// Bus voltage ranges.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum BusVoltageRange {
+    /// 0-16 V.
+    V16 = 0,
+    /// 0-32 V. The bus voltage can't exceed 26 V.
+    V32 = 1,
+}
+
+/// Shunt voltage ranges, set through the programmable gain amplifier.
+///
+/// The maximum current that can be measured is the shunt voltage range divided
+/// by the shunt resistance.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ShuntVoltageRange {
+    /// ±40 mV.
+    Mv40 = 0,
+    /// ±80 mV.
+    Mv80 = 1,
+    /// ±160 mV.
+    Mv160 = 2,
+    /// ±320 mV.
+    Mv320 = 3,
+}
+
+impl ShuntVoltageRange {
+    /// Returns the full-scale range in volts (V).
+    pub fn full_scale_range(self) -> f64 {
+        match self {
+            ShuntVoltageRange::Mv40 => 0.04,
+            ShuntVoltageRange::Mv80 => 0.08,
+            ShuntVoltageRange::Mv160 => 0.16,
+            ShuntVoltageRange::Mv320 => 0.32,
+        }
+    }
+}
+
+/// INA219 current and power monitor driver.
+///
+/// The INA219 measures the voltage across an external shunt resistor, and the
+/// bus voltage on its VIN- pin. Current and power measurements are only available
+/// after the INA219 has been calibrated through [`calibrate`].
+///
+/// The A0 and A1 pins select one of 16 slave addresses between 0x40 and 0x4F.
+/// Connecting both pins to GND results in address 0x40.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::i2c::I2c;
+/// use rppal::power::Ina219;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut ina219 = Ina219::new(I2c::new()?, 0x40)?;
+/// // 0.1 Ω shunt resistor, up to 2 A
+/// ina219.calibrate(0.1, 2.0)?;
+///
+/// println!(
+///     "{:.3} V, {:.3} A, {:.3} W",
+///     ina219.bus_voltage()?,
+///     ina219.current()?,
+///     ina219.power()?
+/// );
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`calibrate`]: #method.calibrate
+#[derive(Debug)]
+pub struct Ina219 {
+    i2c: I2c,
+    bus_voltage_range: BusVoltageRange,
+    shunt_voltage_range: ShuntVoltageRange,
+    power_down: bool,
+    // Current register LSB in A, set by calibrate()
+    current_lsb: Option<f64>,
+}
+
+impl Ina219 {
+    /// Constructs a new `Ina219` for the device at the specified slave address.
+    ///
+    /// The INA219 is reset, and configured with a 0-32 V bus voltage range, a
+    /// ±320 mV shunt voltage range, 12-bit ADC resolution and continuous
+    /// conversions.
+    pub fn new(mut i2c: I2c, address: u16) -> Result<Ina219> {
+        i2c.set_slave_address(address)?;
+
+        let mut ina219 = Ina219 {
+            i2c,
+            bus_voltage_range: BusVoltageRange::V32,
+            shunt_voltage_range: ShuntVoltageRange::Mv320,
+            power_down: false,
+            current_lsb: None,
+        };
+
+        ina219.write_register(REG_CONFIG, CONFIG_RESET)?;
+        ina219.write_config()?;
+
+        Ok(ina219)
+    }
+
+    /// Calculates and programs the calibration register based on the shunt
+    /// resistance in ohms (Ω) and the maximum expected current in amperes (A).
+    ///
+    /// The current resolution equals `max_current` divided by 32768. A lower
+    /// maximum expected current results in a higher resolution. Returns
+    /// [`Error::InvalidCalibration`] if the resulting calibration value doesn't fit
+    /// in the 16-bit calibration register.
+    ///
+    /// [`Error::InvalidCalibration`]: enum.Error.html#variant.InvalidCalibration
+    pub fn calibrate(&mut self, shunt_resistance: f64, max_current: f64) -> Result<()> {
+        if !(shunt_resistance > 0.0 && max_current > 0.0) {
+            return Err(Error::InvalidCalibration);
+        }
+
+        let current_lsb = max_current / 32768.0;
+        let calibration = (CALIBRATION_SCALE / (current_lsb * shunt_resistance)).trunc();
+
+        // Bit 0 is reserved and always reads 0
+        if calibration < 2.0 || calibration > f64::from(u16::MAX) {
+            return Err(Error::InvalidCalibration);
+        }
+        let calibration = calibration as u16 & !0x0001;
+
+        self.write_register(REG_CALIBRATION, calibration)?;
+
+        // Recalculate the LSB to account for the truncated calibration value
+        self.current_lsb = Some(CALIBRATION_SCALE / (f64::from(calibration) * shunt_resistance));
+
+        Ok(())
+    }
+
+    /// Returns the bus voltage range.
+    pub fn bus_voltage_range(&self) -> BusVoltageRange {
+        self.bus_voltage_range
+    }
+
+    /// Sets the bus voltage range.
+    pub fn set_bus_voltage_range(&mut self, range: BusVoltageRange) -> Result<()> {
+        self.bus_voltage_range = range;
+
+        self.write_config()
+    }
+
+    /// Returns the shunt voltage range.
+    pub fn shunt_voltage_range(&self) -> ShuntVoltageRange {
+        self.shunt_voltage_range
+    }
+
+    /// Sets the shunt voltage range.
+    ///
+    /// A smaller range results in a higher accuracy, but limits the maximum
+    /// current that can be measured.
+    pub fn set_shunt_voltage_range(&mut self, range: ShuntVoltageRange) -> Result<()> {
+        self.shunt_voltage_range = range;
+
+        self.write_config()
+    }
+
+    /// Returns `true` if the INA219 is in power-down mode.
+    pub fn power_down(&self) -> bool {
+        self.power_down
+    }
+
+    /// When enabled, stops all conversions and puts the INA219 in power-down
+    /// mode, which reduces the quiescent current to less than 15 µA.
+    ///
+    /// Measurements return the last converted values until conversions are
+    /// resumed.
+    pub fn set_power_down(&mut self, power_down: bool) -> Result<()> {
+        self.power_down = power_down;
+
+        self.write_config()
+    }
+
+    /// Returns the shunt voltage in volts (V).
+    pub fn shunt_voltage(&self) -> Result<f64> {
+        let value = self.read_register(REG_SHUNT_VOLTAGE)? as i16;
+
+        Ok(f64::from(value) * SHUNT_VOLTAGE_LSB)
+    }
+
+    /// Returns the bus voltage in volts (V).
+    ///
+    /// Returns [`Error::Overflow`] if the current or power calculation overflowed
+    /// during the last conversion.
+    ///
+    /// [`Error::Overflow`]: enum.Error.html#variant.Overflow
+    pub fn bus_voltage(&self) -> Result<f64> {
+        let value = self.read_register(REG_BUS_VOLTAGE)?;
+        if value & BUS_VOLTAGE_OVF != 0 {
+            return Err(Error::Overflow);
+        }
+
+        Ok(f64::from(value >> 3) * BUS_VOLTAGE_LSB)
+    }
+
+    /// Returns the current in amperes (A).
+    ///
+    /// Returns [`Error::NotCalibrated`] if [`calibrate`] hasn't been called.
+    ///
+    /// [`Error::NotCalibrated`]: enum.Error.html#variant.NotCalibrated
+    /// [`calibrate`]: #method.calibrate
+    pub fn current(&self) -> Result<f64> {
+        let current_lsb = self.current_lsb.ok_or(Error::NotCalibrated)?;
+        let value = self.read_register(REG_CURRENT)? as i16;
+
+        Ok(f64::from(value) * current_lsb)
+    }
+
+    /// Returns the power in watts (W).
+    ///
+    /// Returns [`Error::NotCalibrated`] if [`calibrate`] hasn't been called.
+    ///
+    /// [`Error::NotCalibrated`]: enum.Error.html#variant.NotCalibrated
+    /// [`calibrate`]: #method.calibrate
+    pub fn power(&self) -> Result<f64> {
+        let current_lsb = self.current_lsb.ok_or(Error::NotCalibrated)?;
+        let value = self.read_register(REG_POWER)?;
+
+        Ok(f64::from(value) * current_lsb * POWER_LSB_FACTOR)
+    }
+
+    /// Consumes the `Ina219`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+
+    fn write_config(&mut self) -> Result<()> {
+        let mode = if self.power_down {
+            CONFIG_MODE_POWER_DOWN
+        } else {
+            CONFIG_MODE_CONTINUOUS
+        };
+
+        let config = ((self.bus_voltage_range as u16) << 13)
+            | ((self.shunt_voltage_range as u16) << 11)
+            | CONFIG_ADC_12BIT
+            | mode;
+
+        self.write_register(REG_CONFIG, config)
+    }
+
+    fn read_register(&self, register: u8) -> Result<u16> {
+        let mut buffer = [0u8; 2];
+        self.i2c.write_read(&[register], &mut buffer)?;
+
+        Ok((u16::from(buffer[0]) << 8) | u16::from(buffer[1]))
+    }
+
+    fn write_register(&mut self, register: u8, value: u16) -> Result<()> {
+        self.i2c
+            .write(&[register, (value >> 8) as u8, value as u8])?;
+
+        Ok(())
+    }
+}
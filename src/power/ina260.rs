@@ -0,0 +1,170 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::i2c::I2c;
+use crate::power::{Error, Result};
+
+const REG_CONFIG: u8 = 0x00;
+const REG_CURRENT: u8 = 0x01;
+const REG_BUS_VOLTAGE: u8 = 0x02;
+const REG_POWER: u8 = 0x03;
+const REG_MANUFACTURER_ID: u8 = 0xfe;
+
+// "TI" in ASCII
+const MANUFACTURER_ID: u16 = 0x5449;
+
+const CONFIG_RESET: u16 = 0x8000;
+// No averaging, 1.1 ms bus voltage and shunt current conversion times
+const CONFIG_DEFAULT: u16 = 0x6120;
+const CONFIG_MODE_CONTINUOUS: u16 = 0b111;
+const CONFIG_MODE_POWER_DOWN: u16 = 0b000;
+
+const CURRENT_LSB: f64 = 0.001_25;
+const BUS_VOLTAGE_LSB: f64 = 0.001_25;
+const POWER_LSB: f64 = 0.01;
+
+/// INA260 current and power monitor driver.
+///
+/// The INA260 includes a precision 2 mΩ shunt resistor, and measures currents
+/// up to ±15 A and bus voltages up to 36 V. No calibration is needed.
+///
+/// The A0 and A1 pins select one of 16 slave addresses between 0x40 and 0x4F.
+/// Connecting both pins to GND results in address 0x40.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::i2c::I2c;
+/// use rppal::power::Ina260;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ina260 = Ina260::new(I2c::new()?, 0x40)?;
+///
+/// println!(
+///     "{:.3} V, {:.3} A, {:.3} W",
+///     ina260.bus_voltage()?,
+///     ina260.current()?,
+///     ina260.power()?
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Ina260 {
+    i2c: I2c,
+    power_down: bool,
+}
+
+impl Ina260 {
+    /// Constructs a new `Ina260` for the device at the specified slave address.
+    ///
+    /// The INA260 is reset, and configured for continuous conversions. Returns
+    /// [`Error::UnknownDevice`] if the manufacturer ID doesn't match.
+    ///
+    /// [`Error::UnknownDevice`]: enum.Error.html#variant.UnknownDevice
+    pub fn new(mut i2c: I2c, address: u16) -> Result<Ina260> {
+        i2c.set_slave_address(address)?;
+
+        let mut ina260 = Ina260 {
+            i2c,
+            power_down: false,
+        };
+
+        let id = ina260.read_register(REG_MANUFACTURER_ID)?;
+        if id != MANUFACTURER_ID {
+            return Err(Error::UnknownDevice(id));
+        }
+
+        ina260.write_register(REG_CONFIG, CONFIG_RESET)?;
+        ina260.write_config()?;
+
+        Ok(ina260)
+    }
+
+    /// Returns `true` if the INA260 is in power-down mode.
+    pub fn power_down(&self) -> bool {
+        self.power_down
+    }
+
+    /// When enabled, stops all conversions and puts the INA260 in power-down
+    /// mode, which reduces the quiescent current to less than 2 µA.
+    ///
+    /// Measurements return the last converted values until conversions are
+    /// resumed.
+    pub fn set_power_down(&mut self, power_down: bool) -> Result<()> {
+        self.power_down = power_down;
+
+        self.write_config()
+    }
+
+    /// Returns the voltage across the integrated shunt resistor in volts (V).
+    ///
+    /// The INA260 doesn't report the shunt voltage directly, so it's calculated
+    /// from the measured current.
+    pub fn shunt_voltage(&self) -> Result<f64> {
+        Ok(self.current()? * 0.002)
+    }
+
+    /// Returns the bus voltage in volts (V).
+    pub fn bus_voltage(&self) -> Result<f64> {
+        Ok(f64::from(self.read_register(REG_BUS_VOLTAGE)?) * BUS_VOLTAGE_LSB)
+    }
+
+    /// Returns the current in amperes (A).
+    pub fn current(&self) -> Result<f64> {
+        let value = self.read_register(REG_CURRENT)? as i16;
+
+        Ok(f64::from(value) * CURRENT_LSB)
+    }
+
+    /// Returns the power in watts (W).
+    pub fn power(&self) -> Result<f64> {
+        Ok(f64::from(self.read_register(REG_POWER)?) * POWER_LSB)
+    }
+
+    /// Consumes the `Ina260`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+
+    fn write_config(&mut self) -> Result<()> {
+        let mode = if self.power_down {
+            CONFIG_MODE_POWER_DOWN
+        } else {
+            CONFIG_MODE_CONTINUOUS
+        };
+
+        self.write_register(REG_CONFIG, CONFIG_DEFAULT | mode)
+    }
+
+    fn read_register(&self, register: u8) -> Result<u16> {
+        let mut buffer = [0u8; 2];
+        self.i2c.write_read(&[register], &mut buffer)?;
+
+        Ok((u16::from(buffer[0]) << 8) | u16::from(buffer[1]))
+    }
+
+    fn write_register(&mut self, register: u8, value: u16) -> Result<()> {
+        self.i2c
+            .write(&[register, (value >> 8) as u8, value as u8])?;
+
+        Ok(())
+    }
+}
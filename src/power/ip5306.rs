@@ -0,0 +1,162 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::i2c::I2c;
+use crate::power::{ChargingStatus, Result};
+
+const ADDRESS: u16 = 0x75;
+
+const REG_SYS_CTL0: u8 = 0x00;
+const REG_READ0: u8 = 0x70;
+const REG_READ1: u8 = 0x71;
+const REG_READ4: u8 = 0x78;
+
+const SYS_CTL0_BOOST_ENABLE: u8 = 0x20;
+const SYS_CTL0_CHARGER_ENABLE: u8 = 0x10;
+const READ0_CHARGER_CONNECTED: u8 = 0x08;
+const READ1_CHARGE_FULL: u8 = 0x08;
+
+/// IP5306 power bank SoC driver.
+///
+/// The IP5306 integrates a lithium-ion battery charger, a 5 V boost converter
+/// and a battery level indicator, and is used on several UPS HATs for the
+/// Raspberry Pi. Only the IP5306 variants with an I2C interface (usually marked
+/// IP5306_I2C) are supported.
+///
+/// The IP5306 doesn't measure the state of charge directly. Instead, it reports
+/// the battery level through its four LED indicator outputs, which results in
+/// a 25% resolution.
+///
+/// The IP5306 uses the fixed slave address 0x75.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::i2c::I2c;
+/// use rppal::power::Ip5306;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ip5306 = Ip5306::new(I2c::new()?)?;
+///
+/// println!(
+///     "{} %, {:?}",
+///     ip5306.state_of_charge()?,
+///     ip5306.charging_status()?
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Ip5306 {
+    i2c: I2c,
+}
+
+impl Ip5306 {
+    /// Constructs a new `Ip5306`.
+    pub fn new(mut i2c: I2c) -> Result<Ip5306> {
+        i2c.set_slave_address(ADDRESS)?;
+
+        let ip5306 = Ip5306 { i2c };
+
+        // Make sure the device responds
+        ip5306.read_register(REG_SYS_CTL0)?;
+
+        Ok(ip5306)
+    }
+
+    /// Returns the state of charge as a percentage.
+    ///
+    /// The IP5306 only reports the battery level in 25% increments. Returns
+    /// either 0, 25, 50, 75 or 100.
+    pub fn state_of_charge(&self) -> Result<u8> {
+        // Each cleared bit represents an active LED indicator
+        Ok(match self.read_register(REG_READ4)? & 0xf0 {
+            0x00 => 100,
+            0x80 => 75,
+            0xc0 => 50,
+            0xe0 => 25,
+            _ => 0,
+        })
+    }
+
+    /// Returns `true` if an external power source is connected to the charger
+    /// input.
+    pub fn is_charger_connected(&self) -> Result<bool> {
+        Ok(self.read_register(REG_READ0)? & READ0_CHARGER_CONNECTED > 0)
+    }
+
+    /// Returns the charging status.
+    pub fn charging_status(&self) -> Result<ChargingStatus> {
+        if !self.is_charger_connected()? {
+            Ok(ChargingStatus::Discharging)
+        } else if self.read_register(REG_READ1)? & READ1_CHARGE_FULL > 0 {
+            Ok(ChargingStatus::Full)
+        } else {
+            Ok(ChargingStatus::Charging)
+        }
+    }
+
+    /// Returns `true` if the boost converter is enabled.
+    pub fn boost_enabled(&self) -> Result<bool> {
+        Ok(self.read_register(REG_SYS_CTL0)? & SYS_CTL0_BOOST_ENABLE > 0)
+    }
+
+    /// Enables or disables the 5 V boost converter.
+    ///
+    /// Disabling the boost converter cuts power to the Raspberry Pi when it's
+    /// running on battery power.
+    pub fn set_boost_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.update_register(REG_SYS_CTL0, SYS_CTL0_BOOST_ENABLE, enabled)
+    }
+
+    /// Returns `true` if the charger is enabled.
+    pub fn charger_enabled(&self) -> Result<bool> {
+        Ok(self.read_register(REG_SYS_CTL0)? & SYS_CTL0_CHARGER_ENABLE > 0)
+    }
+
+    /// Enables or disables the battery charger.
+    pub fn set_charger_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.update_register(REG_SYS_CTL0, SYS_CTL0_CHARGER_ENABLE, enabled)
+    }
+
+    /// Consumes the `Ip5306`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+
+    fn update_register(&mut self, register: u8, mask: u8, set: bool) -> Result<()> {
+        let value = self.read_register(register)?;
+
+        self.write_register(register, if set { value | mask } else { value & !mask })
+    }
+
+    fn read_register(&self, register: u8) -> Result<u8> {
+        let mut buffer = [0u8; 1];
+        self.i2c.write_read(&[register], &mut buffer)?;
+
+        Ok(buffer[0])
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) -> Result<()> {
+        self.i2c.write(&[register, value])?;
+
+        Ok(())
+    }
+}
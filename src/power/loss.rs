@@ -0,0 +1,174 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::process::Command;
+use std::time::Duration;
+
+use crate::gpio::{InputPin, Level, Trigger};
+use crate::power::{Error, Result};
+
+/// Power-loss monitor.
+///
+/// Most UPS HATs signal the loss of external power through a GPIO pin, which
+/// gives the Raspberry Pi a chance to shut down cleanly before the battery
+/// runs out. `PowerLoss` watches the pin, and either calls a callback on a
+/// separate thread, or blocks until the power is lost.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::{Gpio, Level};
+/// use rppal::power::{self, PowerLoss};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let pin = Gpio::new()?.get(6).unwrap().into_input_pullup();
+/// let mut power_loss = PowerLoss::new(pin, Level::Low);
+///
+/// power_loss.on_power_loss(|| {
+///     let _ = power::shutdown();
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PowerLoss {
+    pin: InputPin,
+    active_level: Level,
+}
+
+impl PowerLoss {
+    /// Constructs a new `PowerLoss`.
+    ///
+    /// `active_level` is the pin's logic level while external power is lost.
+    pub fn new(pin: InputPin, active_level: Level) -> PowerLoss {
+        PowerLoss { pin, active_level }
+    }
+
+    /// Returns the pin's logic level while external power is lost.
+    pub fn active_level(&self) -> Level {
+        self.active_level
+    }
+
+    /// Returns `true` if external power is currently lost.
+    pub fn is_power_lost(&self) -> bool {
+        self.pin.read() == self.active_level
+    }
+
+    /// Configures a callback that's executed on a separate thread when external
+    /// power is lost.
+    ///
+    /// Any previously configured callback is replaced. The callback isn't called
+    /// if power was already lost when it's configured, so check [`is_power_lost`]
+    /// afterwards to avoid missing the event.
+    ///
+    /// [`is_power_lost`]: #method.is_power_lost
+    pub fn on_power_loss<C>(&mut self, mut callback: C) -> Result<()>
+    where
+        C: FnMut() + Send + 'static,
+    {
+        self.pin
+            .set_async_interrupt(self.trigger(), move |_| callback())?;
+
+        Ok(())
+    }
+
+    /// Configures a callback that's executed on a separate thread when external
+    /// power is lost, ignoring any additional events within `debounce` of the
+    /// previous one.
+    ///
+    /// Power-fail signals on some UPS HATs briefly toggle when the external
+    /// supply is connected or disconnected. Besides the debounce filter,
+    /// `on_power_loss_debounced` works the same as [`on_power_loss`].
+    ///
+    /// [`on_power_loss`]: #method.on_power_loss
+    pub fn on_power_loss_debounced<C>(&mut self, debounce: Duration, mut callback: C) -> Result<()>
+    where
+        C: FnMut() + Send + 'static,
+    {
+        self.pin
+            .set_async_interrupt_debounced(self.trigger(), debounce, move |_| callback())?;
+
+        Ok(())
+    }
+
+    /// Removes the callback configured with [`on_power_loss`] or
+    /// [`on_power_loss_debounced`].
+    ///
+    /// [`on_power_loss`]: #method.on_power_loss
+    /// [`on_power_loss_debounced`]: #method.on_power_loss_debounced
+    pub fn clear(&mut self) -> Result<()> {
+        self.pin.clear_async_interrupt()?;
+
+        Ok(())
+    }
+
+    /// Blocks until external power is lost, or the timeout expires.
+    ///
+    /// Returns immediately if power is already lost. Returns `Ok(true)` if power is
+    /// lost, or `Ok(false)` if the timeout expired first. `timeout` can be set to
+    /// `None` to wait indefinitely.
+    ///
+    /// Any callback configured with [`on_power_loss`] is cleared.
+    ///
+    /// [`on_power_loss`]: #method.on_power_loss
+    pub fn wait(&mut self, timeout: Option<Duration>) -> Result<bool> {
+        self.pin.set_interrupt(self.trigger())?;
+
+        // Check after the interrupt is configured, so an edge in between isn't missed
+        let lost = self.is_power_lost() || self.pin.poll_interrupt(true, timeout)?.is_some();
+
+        self.pin.clear_interrupt()?;
+
+        Ok(lost)
+    }
+
+    /// Consumes the `PowerLoss`, and returns the underlying `InputPin`.
+    pub fn into_pin(mut self) -> InputPin {
+        let _ = self.pin.clear_async_interrupt();
+
+        self.pin
+    }
+
+    fn trigger(&self) -> Trigger {
+        match self.active_level {
+            Level::High => Trigger::RisingEdge,
+            Level::Low => Trigger::FallingEdge,
+        }
+    }
+}
+
+/// Initiates a clean shutdown of the Raspberry Pi.
+///
+/// `shutdown` runs `shutdown -h now`, which requires root privileges, and
+/// returns once the command has completed. Returns [`Error::Io`] if the
+/// command couldn't be executed, or [`Error::ShutdownFailed`] if it returned
+/// an error.
+///
+/// [`Error::Io`]: enum.Error.html#variant.Io
+/// [`Error::ShutdownFailed`]: enum.Error.html#variant.ShutdownFailed
+pub fn shutdown() -> Result<()> {
+    let status = Command::new("shutdown").args(["-h", "now"]).status()?;
+
+    if !status.success() {
+        return Err(Error::ShutdownFailed(status.code()));
+    }
+
+    Ok(())
+}
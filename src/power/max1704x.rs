@@ -0,0 +1,264 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::i2c::I2c;
+use crate::power::{ChargingStatus, Error, Result};
+
+const ADDRESS: u16 = 0x36;
+
+const REG_VCELL: u8 = 0x02;
+const REG_SOC: u8 = 0x04;
+const REG_MODE: u8 = 0x06;
+const REG_VERSION: u8 = 0x08;
+const REG_CONFIG: u8 = 0x0c;
+const REG_CRATE: u8 = 0x16;
+const REG_COMMAND: u8 = 0xfe;
+
+const MODE_QUICK_START: u16 = 0x4000;
+const COMMAND_POR: u16 = 0x5400;
+
+const CONFIG_ALRT: u16 = 0x0020;
+const CONFIG_ATHD_MASK: u16 = 0x001f;
+
+// 0.208 %/h per LSB
+const CRATE_LSB: f64 = 0.208;
+
+// Charge rates within this range (in %/h) are considered idle
+const CRATE_IDLE: f64 = 0.5;
+
+/// Supported MAX1704x fuel gauge models.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Max1704xModel {
+    /// MAX17040, single cell.
+    Max17040,
+    /// MAX17041, two cells in series.
+    Max17041,
+    /// MAX17048, single cell.
+    Max17048,
+    /// MAX17049, two cells in series.
+    Max17049,
+}
+
+impl Max1704xModel {
+    // Converts a raw VCELL register value to volts (V)
+    fn voltage(self, value: u16) -> f64 {
+        match self {
+            Max1704xModel::Max17040 => f64::from(value >> 4) * 0.001_25,
+            Max1704xModel::Max17041 => f64::from(value >> 4) * 0.002_5,
+            Max1704xModel::Max17048 => f64::from(value) * 0.000_078_125,
+            Max1704xModel::Max17049 => f64::from(value) * 0.000_156_25,
+        }
+    }
+
+    fn has_charge_rate(self) -> bool {
+        self == Max1704xModel::Max17048 || self == Max1704xModel::Max17049
+    }
+}
+
+/// MAX17040/MAX17041/MAX17048/MAX17049 fuel gauge driver.
+///
+/// The MAX1704x fuel gauges calculate the state of charge of a lithium-ion or
+/// lithium-polymer battery from its voltage, without the need for a sense resistor.
+/// They're found on many UPS HATs for the Raspberry Pi.
+///
+/// The MAX1704x uses the fixed slave address 0x36.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::i2c::I2c;
+/// use rppal::power::{Max1704x, Max1704xModel};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let gauge = Max1704x::new(I2c::new()?, Max1704xModel::Max17048)?;
+///
+/// println!(
+///     "{:.3} V, {:.1} %, {:?}",
+///     gauge.voltage()?,
+///     gauge.state_of_charge()?,
+///     gauge.charging_status()?
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Max1704x {
+    i2c: I2c,
+    model: Max1704xModel,
+}
+
+impl Max1704x {
+    /// Constructs a new `Max1704x` for the specified model.
+    pub fn new(mut i2c: I2c, model: Max1704xModel) -> Result<Max1704x> {
+        i2c.set_slave_address(ADDRESS)?;
+
+        let gauge = Max1704x { i2c, model };
+
+        // Make sure the device responds
+        gauge.version()?;
+
+        Ok(gauge)
+    }
+
+    /// Returns the model selected when the `Max1704x` was constructed.
+    pub fn model(&self) -> Max1704xModel {
+        self.model
+    }
+
+    /// Returns the production version of the IC.
+    pub fn version(&self) -> Result<u16> {
+        self.read_register(REG_VERSION)
+    }
+
+    /// Returns the battery voltage in volts (V).
+    ///
+    /// For the two-cell MAX17041 and MAX17049, this is the voltage across both
+    /// cells.
+    pub fn voltage(&self) -> Result<f64> {
+        Ok(self.model.voltage(self.read_register(REG_VCELL)?))
+    }
+
+    /// Returns the state of charge as a percentage.
+    ///
+    /// The MAX1704x may report values slightly above 100% for a fully charged
+    /// battery.
+    pub fn state_of_charge(&self) -> Result<f64> {
+        Ok(f64::from(self.read_register(REG_SOC)?) / 256.0)
+    }
+
+    /// Returns the approximate charge or discharge rate in percent per hour (%/h).
+    ///
+    /// Positive values indicate the battery is charging, and negative values
+    /// indicate it's discharging.
+    ///
+    /// Only the MAX17048 and MAX17049 measure the charge rate. Returns `Ok(None)`
+    /// for the MAX17040 and MAX17041.
+    pub fn charge_rate(&self) -> Result<Option<f64>> {
+        if !self.model.has_charge_rate() {
+            return Ok(None);
+        }
+
+        let value = self.read_register(REG_CRATE)? as i16;
+
+        Ok(Some(f64::from(value) * CRATE_LSB))
+    }
+
+    /// Returns the charging status.
+    ///
+    /// The status is derived from the charge rate and the state of charge.
+    /// Because the MAX17040 and MAX17041 don't measure the charge rate,
+    /// `charging_status` always returns [`ChargingStatus::Unknown`] for those models.
+    ///
+    /// [`ChargingStatus::Unknown`]: enum.ChargingStatus.html#variant.Unknown
+    pub fn charging_status(&self) -> Result<ChargingStatus> {
+        let rate = match self.charge_rate()? {
+            Some(rate) => rate,
+            None => return Ok(ChargingStatus::Unknown),
+        };
+
+        if rate > CRATE_IDLE {
+            Ok(ChargingStatus::Charging)
+        } else if rate < -CRATE_IDLE {
+            Ok(ChargingStatus::Discharging)
+        } else if self.state_of_charge()? >= 99.5 {
+            Ok(ChargingStatus::Full)
+        } else {
+            Ok(ChargingStatus::Unknown)
+        }
+    }
+
+    /// Returns the low state of charge alert threshold as a percentage.
+    pub fn alert_threshold(&self) -> Result<u8> {
+        let config = self.read_register(REG_CONFIG)?;
+
+        Ok(32 - (config & CONFIG_ATHD_MASK) as u8)
+    }
+
+    /// Sets the low state of charge alert threshold as a percentage.
+    ///
+    /// When the state of charge drops below `threshold`, the MAX1704x sets its
+    /// alert flag and pulls the ALRT pin low. `threshold` must be between 1
+    /// and 32.
+    ///
+    /// Returns [`Error::InvalidThreshold`] if `threshold` is out of range.
+    ///
+    /// [`Error::InvalidThreshold`]: enum.Error.html#variant.InvalidThreshold
+    pub fn set_alert_threshold(&mut self, threshold: u8) -> Result<()> {
+        if !(1..=32).contains(&threshold) {
+            return Err(Error::InvalidThreshold);
+        }
+
+        let config = self.read_register(REG_CONFIG)?;
+
+        self.write_register(
+            REG_CONFIG,
+            (config & !CONFIG_ATHD_MASK) | u16::from(32 - threshold),
+        )
+    }
+
+    /// Returns `true` if the state of charge has dropped below the alert
+    /// threshold.
+    pub fn alert(&self) -> Result<bool> {
+        Ok(self.read_register(REG_CONFIG)? & CONFIG_ALRT > 0)
+    }
+
+    /// Clears the alert flag, which releases the ALRT pin.
+    pub fn clear_alert(&mut self) -> Result<()> {
+        let config = self.read_register(REG_CONFIG)?;
+
+        self.write_register(REG_CONFIG, config & !CONFIG_ALRT)
+    }
+
+    /// Restarts the fuel gauge calculations.
+    ///
+    /// A quick start can be used to fix an inaccurate initial state of charge
+    /// caused by a noisy battery voltage during power-up.
+    pub fn quick_start(&mut self) -> Result<()> {
+        self.write_register(REG_MODE, MODE_QUICK_START)
+    }
+
+    /// Resets the MAX1704x, as if it was power-cycled.
+    pub fn reset(&mut self) -> Result<()> {
+        // The MAX1704x resets before it acknowledges the command, so the
+        // resulting I2C error is ignored.
+        let _ = self.write_register(REG_COMMAND, COMMAND_POR);
+
+        Ok(())
+    }
+
+    /// Consumes the `Max1704x`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+
+    fn read_register(&self, register: u8) -> Result<u16> {
+        let mut buffer = [0u8; 2];
+        self.i2c.write_read(&[register], &mut buffer)?;
+
+        Ok((u16::from(buffer[0]) << 8) | u16::from(buffer[1]))
+    }
+
+    fn write_register(&mut self, register: u8, value: u16) -> Result<()> {
+        self.i2c
+            .write(&[register, (value >> 8) as u8, value as u8])?;
+
+        Ok(())
+    }
+}
@@ -106,6 +106,7 @@ pub type Result<T> = result::Result<T, Error>;
 ///
 /// [here]: index.html
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Channel {
     Pwm0 = 0,
     Pwm1 = 1,
@@ -113,6 +114,7 @@ pub enum Channel {
 
 /// Output polarities.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Polarity {
     Normal,
     Inverse,
@@ -124,8 +126,10 @@ pub enum Polarity {
 /// and activated. More information can be found [here].
 ///
 /// [here]: index.html
+#[derive(Debug)]
 pub struct Pwm {
     channel: Channel,
+    reset_on_drop: bool,
 }
 
 impl Pwm {
@@ -133,7 +137,10 @@ impl Pwm {
     pub fn new(channel: Channel) -> Result<Pwm> {
         sysfs::export(channel as u8)?;
 
-        let pwm = Pwm { channel };
+        let pwm = Pwm {
+            channel,
+            reset_on_drop: true,
+        };
 
         // Always reset "enable" to 0. The sysfs interface has a bug where a previous
         // export may have left "enable" as 1 after unexporting. On the next export,
@@ -172,7 +179,10 @@ impl Pwm {
     ) -> Result<Pwm> {
         sysfs::export(channel as u8)?;
 
-        let pwm = Pwm { channel };
+        let pwm = Pwm {
+            channel,
+            reset_on_drop: true,
+        };
 
         // Always reset "enable" to 0. The sysfs pwm interface has a bug where a previous
         // export may have left "enable" as 1 after unexporting. On the next export,
@@ -216,7 +226,10 @@ impl Pwm {
     ) -> Result<Pwm> {
         sysfs::export(channel as u8)?;
 
-        let pwm = Pwm { channel };
+        let pwm = Pwm {
+            channel,
+            reset_on_drop: true,
+        };
 
         // Always reset "enable" to 0. The sysfs pwm interface has a bug where a previous
         // export may have left "enable" as 1 after unexporting. On the next export,
@@ -341,10 +354,37 @@ impl Pwm {
 
         Ok(())
     }
+
+    /// Returns the value of `reset_on_drop`.
+    pub fn reset_on_drop(&self) -> bool {
+        self.reset_on_drop
+    }
+
+    /// When enabled, disables and unexports the PWM channel when the `Pwm`
+    /// instance goes out of scope. By default, this is set to `true`.
+    ///
+    /// Disabling `reset_on_drop` keeps the PWM signal active after your
+    /// application exits, which can be useful for servos or LEDs that need
+    /// to stay in their current state.
+    ///
+    /// ## Note
+    ///
+    /// Drop methods aren't called when a program is abnormally terminated, for
+    /// instance when a user presses <kbd>Ctrl + C</kbd>, and the `SIGINT` signal
+    /// isn't caught. You catch those using crates such as [`simple_signal`].
+    ///
+    /// [`simple_signal`]: https://crates.io/crates/simple-signal
+    pub fn set_reset_on_drop(&mut self, reset_on_drop: bool) {
+        self.reset_on_drop = reset_on_drop;
+    }
 }
 
 impl Drop for Pwm {
     fn drop(&mut self) {
+        if !self.reset_on_drop {
+            return;
+        }
+
         let _ = sysfs::set_enabled(self.channel as u8, false);
         let _ = sysfs::unexport(self.channel as u8);
     }
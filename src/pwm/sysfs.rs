@@ -73,11 +73,7 @@ pub fn export(channel: u8) -> Result<()> {
     // manually adding rules, since they don't seem to be part of the latest release yet. The
     // patched drivers/pwm/sysfs.c was included in raspberrypi-kernel_1.20180417-1 (4.14.34).
     // See: https://github.com/raspberrypi/linux/issues/1983
-    let gid_gpio = if let Some(gid) = user::group_to_gid("gpio") {
-        gid
-    } else {
-        0
-    };
+    let gid_gpio = user::group_to_gid("gpio").unwrap_or_default();
 
     let paths = &[
         format!("/sys/class/pwm/pwmchip0/pwm{}", channel),
@@ -117,7 +113,7 @@ pub fn unexport(channel: u8) -> Result<()> {
 pub fn period(channel: u8) -> Result<u64> {
     let period = fs::read_to_string(format!("/sys/class/pwm/pwmchip0/pwm{}/period", channel))?;
 
-    if let Ok(period_u64) = u64::from_str_radix(&period, 10) {
+    if let Ok(period_u64) = period.trim().parse::<u64>() {
         Ok(period_u64)
     } else {
         Ok(0)
@@ -135,7 +131,7 @@ pub fn duty_cycle(channel: u8) -> Result<u64> {
     let duty_cycle =
         fs::read_to_string(format!("/sys/class/pwm/pwmchip0/pwm{}/duty_cycle", channel))?;
 
-    if let Ok(duty_cycle_u64) = u64::from_str_radix(&duty_cycle, 10) {
+    if let Ok(duty_cycle_u64) = duty_cycle.trim().parse::<u64>() {
         Ok(duty_cycle_u64)
     } else {
         Ok(0)
@@ -152,7 +148,7 @@ pub fn set_duty_cycle(channel: u8, duty_cycle: u64) -> Result<()> {
 pub fn polarity(channel: u8) -> Result<Polarity> {
     let polarity = fs::read_to_string(format!("/sys/class/pwm/pwmchip0/pwm{}/polarity", channel))?;
 
-    match &polarity[..] {
+    match polarity.trim() {
         "normal" => Ok(Polarity::Normal),
         _ => Ok(Polarity::Inverse),
     }
@@ -173,7 +169,7 @@ pub fn set_polarity(channel: u8, polarity: Polarity) -> Result<()> {
 pub fn enabled(channel: u8) -> Result<bool> {
     let enabled = fs::read_to_string(format!("/sys/class/pwm/pwmchip0/pwm{}/enable", channel))?;
 
-    match &enabled[..] {
+    match enabled.trim() {
         "0" => Ok(false),
         _ => Ok(true),
     }
@@ -0,0 +1,77 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Drivers for RFID readers.
+//!
+//! * [`Mfrc522`] supports the NXP MFRC522, found on the common RC522 modules,
+//!   connected through SPI. It detects ISO/IEC 14443 A cards, reads their UID
+//!   through the anticollision loop, and reads and writes MIFARE Classic blocks.
+//!
+//! [`Mfrc522`]: struct.Mfrc522.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::spi;
+
+mod mfrc522;
+
+pub use self::mfrc522::{Card, CardType, KeyType, Mfrc522};
+
+quick_error! {
+/// Errors that can occur when accessing an RFID reader.
+    #[derive(Debug)]
+    pub enum Error {
+/// SPI error.
+        Spi(err: spi::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Timeout.
+///
+/// The card didn't respond in time. This usually means there's no card in range.
+        Timeout { description("card didn't respond in time") }
+/// Communication error.
+///
+/// The reader detected a buffer overflow, parity error or protocol error. Contains
+/// the value of the reader's error register.
+        Communication(error: u8) { description("communication error") }
+/// CRC mismatch.
+        Crc { description("CRC mismatch") }
+/// Unresolved collision.
+///
+/// Multiple cards responded, and the collision couldn't be resolved.
+        Collision { description("unresolved collision") }
+/// Card didn't acknowledge the command.
+///
+/// Contains the 4-bit response sent by the card instead of an ACK.
+        Nack(response: u8) { description("card didn't acknowledge the command") }
+/// Authentication failed.
+///
+/// The key doesn't match the key stored in the sector trailer for the
+/// requested block.
+        AuthenticationFailed { description("authentication failed") }
+/// Unexpected response.
+///
+/// The card's response didn't have the expected length.
+        UnexpectedResponse { description("unexpected response") }
+    }
+}
+
+/// Result type returned from methods that can have `rfid::Error`s.
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,722 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fmt;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::rfid::{Error, Result};
+use crate::spi::Spi;
+
+// Registers (datasheet @ 9.2)
+const REG_COMMAND: u8 = 0x01;
+const REG_COM_IRQ: u8 = 0x04;
+const REG_ERROR: u8 = 0x06;
+const REG_STATUS2: u8 = 0x08;
+const REG_FIFO_DATA: u8 = 0x09;
+const REG_FIFO_LEVEL: u8 = 0x0a;
+const REG_CONTROL: u8 = 0x0c;
+const REG_BIT_FRAMING: u8 = 0x0d;
+const REG_COLL: u8 = 0x0e;
+const REG_MODE: u8 = 0x11;
+const REG_TX_MODE: u8 = 0x12;
+const REG_RX_MODE: u8 = 0x13;
+const REG_TX_CONTROL: u8 = 0x14;
+const REG_TX_ASK: u8 = 0x15;
+const REG_MOD_WIDTH: u8 = 0x24;
+const REG_T_MODE: u8 = 0x2a;
+const REG_T_PRESCALER: u8 = 0x2b;
+const REG_T_RELOAD_H: u8 = 0x2c;
+const REG_T_RELOAD_L: u8 = 0x2d;
+const REG_VERSION: u8 = 0x37;
+
+// Reader commands (datasheet @ 10.3)
+const CMD_IDLE: u8 = 0x00;
+const CMD_MF_AUTHENT: u8 = 0x0e;
+const CMD_TRANSCEIVE: u8 = 0x0c;
+const CMD_SOFT_RESET: u8 = 0x0f;
+
+const COMMAND_POWER_DOWN: u8 = 0x10;
+const COM_IRQ_TIMER: u8 = 0x01;
+const COM_IRQ_IDLE: u8 = 0x10;
+const COM_IRQ_RX: u8 = 0x20;
+const COM_IRQ_ALL: u8 = 0x7f;
+// BufferOvfl, ParityErr and ProtocolErr
+const ERROR_MASK: u8 = 0x13;
+const ERROR_COLL: u8 = 0x08;
+const STATUS2_CRYPTO1_ON: u8 = 0x08;
+const FIFO_FLUSH: u8 = 0x80;
+const BIT_FRAMING_START_SEND: u8 = 0x80;
+const COLL_VALUES_AFTER_COLL: u8 = 0x80;
+const COLL_POS_NOT_VALID: u8 = 0x20;
+const TX_CONTROL_ANTENNA: u8 = 0x03;
+
+// Card commands (ISO/IEC 14443-3 and MIFARE Classic)
+const PICC_REQA: u8 = 0x26;
+const PICC_WUPA: u8 = 0x52;
+const PICC_HLTA: u8 = 0x50;
+const PICC_SEL: [u8; 3] = [0x93, 0x95, 0x97];
+const PICC_CASCADE_TAG: u8 = 0x88;
+const PICC_MF_READ: u8 = 0x30;
+const PICC_MF_WRITE: u8 = 0xa0;
+const PICC_MF_ACK: u8 = 0x0a;
+const SAK_CASCADE: u8 = 0x04;
+
+// Software timeout, in case the reader's own timer doesn't fire
+const COMMAND_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// MIFARE Classic key types.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum KeyType {
+    /// Key A.
+    A = 0x60,
+    /// Key B.
+    B = 0x61,
+}
+
+/// Card types, based on the SAK (Select Acknowledge) byte.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum CardType {
+    /// MIFARE Classic Mini (320 bytes).
+    MifareMini,
+    /// MIFARE Classic 1K.
+    MifareClassic1K,
+    /// MIFARE Classic 4K.
+    MifareClassic4K,
+    /// MIFARE Ultralight or NTAG.
+    MifareUltralight,
+    /// ISO/IEC 14443-4 compliant card, such as MIFARE DESFire.
+    Iso14443_4,
+    /// Unknown card type. Contains the SAK byte.
+    Unknown(u8),
+}
+
+impl CardType {
+    fn from_sak(sak: u8) -> CardType {
+        match sak & 0x7f {
+            0x09 => CardType::MifareMini,
+            0x08 | 0x88 => CardType::MifareClassic1K,
+            0x18 => CardType::MifareClassic4K,
+            0x00 => CardType::MifareUltralight,
+            0x20 => CardType::Iso14443_4,
+            _ => CardType::Unknown(sak),
+        }
+    }
+}
+
+impl fmt::Display for CardType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CardType::MifareMini => write!(f, "MIFARE Classic Mini"),
+            CardType::MifareClassic1K => write!(f, "MIFARE Classic 1K"),
+            CardType::MifareClassic4K => write!(f, "MIFARE Classic 4K"),
+            CardType::MifareUltralight => write!(f, "MIFARE Ultralight"),
+            CardType::Iso14443_4 => write!(f, "ISO/IEC 14443-4"),
+            CardType::Unknown(sak) => write!(f, "Unknown (SAK 0x{:02x})", sak),
+        }
+    }
+}
+
+/// A selected card.
+///
+/// Returned by [`Mfrc522::select`] and [`Mfrc522::read_card`].
+///
+/// [`Mfrc522::select`]: struct.Mfrc522.html#method.select
+/// [`Mfrc522::read_card`]: struct.Mfrc522.html#method.read_card
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Card {
+    uid: Vec<u8>,
+    sak: u8,
+}
+
+impl Card {
+    /// Returns the card's 4, 7 or 10-byte UID.
+    pub fn uid(&self) -> &[u8] {
+        &self.uid
+    }
+
+    /// Returns the SAK (Select Acknowledge) byte.
+    pub fn sak(&self) -> u8 {
+        self.sak
+    }
+
+    /// Returns the card type, based on the SAK byte.
+    pub fn card_type(&self) -> CardType {
+        CardType::from_sak(self.sak)
+    }
+}
+
+/// MFRC522 RFID reader driver.
+///
+/// The MFRC522 communicates with ISO/IEC 14443 A cards at 13.56 MHz, and is used
+/// on the common RC522 modules. The maximum SPI clock speed is 10 MHz. The reader
+/// uses SPI [`Mode0`]. The SPI interface is selected by the module's wiring, and
+/// the RST pin has to be pulled high for the reader to be active.
+///
+/// A card is detected with [`request`], after which [`select`] runs the
+/// anticollision loop to retrieve its UID. [`read_card`] combines both steps. MIFARE
+/// Classic blocks can be read and written after authenticating with the sector's key
+/// through [`authenticate`]. Call [`stop_crypto`] when you're done, before
+/// communicating with a different card.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::rfid::{KeyType, Mfrc522};
+/// use rppal::spi::{Bus, Mode, SlaveSelect, Spi};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let spi = Spi::new(Bus::Spi0, SlaveSelect::Ss0, 1_000_000, Mode::Mode0)?;
+/// let mut reader = Mfrc522::new(spi)?;
+///
+/// if let Some(card) = reader.read_card()? {
+///     println!("UID: {:02x?} ({})", card.uid(), card.card_type());
+///
+///     reader.authenticate(KeyType::A, 4, &[0xff; 6], &card)?;
+///     println!("Block 4: {:02x?}", reader.read_block(4)?);
+///     reader.halt()?;
+///     reader.stop_crypto()?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Mode0`]: ../spi/enum.Mode.html#variant.Mode0
+/// [`request`]: #method.request
+/// [`select`]: #method.select
+/// [`read_card`]: #method.read_card
+/// [`authenticate`]: #method.authenticate
+/// [`stop_crypto`]: #method.stop_crypto
+#[derive(Debug)]
+pub struct Mfrc522 {
+    spi: Spi,
+}
+
+impl Mfrc522 {
+    /// Constructs a new `Mfrc522`, resets the reader, and enables the antenna.
+    pub fn new(spi: Spi) -> Result<Mfrc522> {
+        let mut mfrc522 = Mfrc522 { spi };
+
+        mfrc522.reset()?;
+
+        Ok(mfrc522)
+    }
+
+    /// Performs a soft reset, and restores the default configuration.
+    pub fn reset(&mut self) -> Result<()> {
+        self.write_register(REG_COMMAND, CMD_SOFT_RESET)?;
+
+        // The oscillator needs at least 37.74 µs to start up after a reset
+        let start = Instant::now();
+        sleep(Duration::from_millis(1));
+        while self.read_register(REG_COMMAND)? & COMMAND_POWER_DOWN != 0 {
+            if start.elapsed() > COMMAND_TIMEOUT {
+                return Err(Error::Timeout);
+            }
+
+            sleep(Duration::from_millis(1));
+        }
+
+        self.write_register(REG_TX_MODE, 0x00)?;
+        self.write_register(REG_RX_MODE, 0x00)?;
+        self.write_register(REG_MOD_WIDTH, 0x26)?;
+
+        // Start the timer automatically at the end of each transmission. With a
+        // 13.56 MHz / (2 * 0xa9 + 1) prescaler and a 1000 reload value, commands time
+        // out after 25 ms.
+        self.write_register(REG_T_MODE, 0x80)?;
+        self.write_register(REG_T_PRESCALER, 0xa9)?;
+        self.write_register(REG_T_RELOAD_H, 0x03)?;
+        self.write_register(REG_T_RELOAD_L, 0xe8)?;
+
+        // 100% ASK modulation, and a CRC preset value of 0x6363 (ISO/IEC 14443-3)
+        self.write_register(REG_TX_ASK, 0x40)?;
+        self.write_register(REG_MODE, 0x3d)?;
+
+        self.set_antenna(true)
+    }
+
+    /// Returns the contents of the version register.
+    ///
+    /// Genuine MFRC522 chips return `0x91` (version 1.0) or `0x92` (version 2.0).
+    /// Clones commonly return `0x12` or `0x88`.
+    pub fn version(&mut self) -> Result<u8> {
+        self.read_register(REG_VERSION)
+    }
+
+    /// Enables or disables the antenna drivers (TX1 and TX2).
+    pub fn set_antenna(&mut self, enabled: bool) -> Result<()> {
+        let reg_value = self.read_register(REG_TX_CONTROL)?;
+
+        if enabled {
+            self.write_register(REG_TX_CONTROL, reg_value | TX_CONTROL_ANTENNA)
+        } else {
+            self.write_register(REG_TX_CONTROL, reg_value & !TX_CONTROL_ANTENNA)
+        }
+    }
+
+    /// Sends a REQA command, which wakes up any cards in the idle state.
+    ///
+    /// Returns the ATQA (Answer To Request) bytes sent by the card, or `None` if
+    /// no card responded. Cards that have been halted with [`halt`] only respond
+    /// to [`wake_up`].
+    ///
+    /// [`halt`]: #method.halt
+    /// [`wake_up`]: #method.wake_up
+    pub fn request(&mut self) -> Result<Option<[u8; 2]>> {
+        self.request_a(PICC_REQA)
+    }
+
+    /// Sends a WUPA command, which wakes up any cards in the idle or halt state.
+    ///
+    /// Returns the ATQA (Answer To Request) bytes sent by the card, or `None` if
+    /// no card responded.
+    pub fn wake_up(&mut self) -> Result<Option<[u8; 2]>> {
+        self.request_a(PICC_WUPA)
+    }
+
+    /// Runs the anticollision loop and selects a card.
+    ///
+    /// Call [`request`] or [`wake_up`] first. If multiple cards are in range, the
+    /// collisions are resolved, and one of the cards is selected. The other
+    /// cards remain in the ready state until the next request.
+    ///
+    /// [`request`]: #method.request
+    /// [`wake_up`]: #method.wake_up
+    pub fn select(&mut self) -> Result<Card> {
+        let mut uid = Vec::with_capacity(10);
+
+        for &sel in PICC_SEL.iter() {
+            let uid_cl = self.anticollision(sel)?;
+
+            // Select the card, and retrieve SAK
+            let mut frame = [sel, 0x70, 0, 0, 0, 0, 0, 0, 0];
+            frame[2..7].copy_from_slice(&uid_cl);
+            let crc = crc_a(&frame[..7]);
+            frame[7..].copy_from_slice(&crc);
+
+            let response = self.transceive_crc(&frame)?;
+            if response.len() != 1 {
+                return Err(Error::UnexpectedResponse);
+            }
+
+            let sak = response[0];
+
+            if sak & SAK_CASCADE == 0 {
+                uid.extend_from_slice(&uid_cl[..4]);
+
+                return Ok(Card { uid, sak });
+            }
+
+            // The first UID byte is a cascade tag, and the rest of the UID
+            // follows on the next cascade level
+            if uid_cl[0] != PICC_CASCADE_TAG {
+                return Err(Error::UnexpectedResponse);
+            }
+
+            uid.extend_from_slice(&uid_cl[1..4]);
+        }
+
+        Err(Error::UnexpectedResponse)
+    }
+
+    /// Detects and selects a card.
+    ///
+    /// `read_card` is a convenience method that calls [`request`] followed by
+    /// [`select`]. Returns `None` if no card is in range.
+    ///
+    /// [`request`]: #method.request
+    /// [`select`]: #method.select
+    pub fn read_card(&mut self) -> Result<Option<Card>> {
+        if self.request()?.is_none() {
+            return Ok(None);
+        }
+
+        self.select().map(Some)
+    }
+
+    /// Puts the selected card in the halt state.
+    ///
+    /// A halted card only responds to [`wake_up`].
+    ///
+    /// [`wake_up`]: #method.wake_up
+    pub fn halt(&mut self) -> Result<()> {
+        let crc = crc_a(&[PICC_HLTA, 0x00]);
+
+        // The card acknowledges HLTA by not responding
+        match self.communicate(CMD_TRANSCEIVE, &[PICC_HLTA, 0x00, crc[0], crc[1]], 0, 0) {
+            Err(Error::Timeout) => Ok(()),
+            Err(e) => Err(e),
+            Ok(_) => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Authenticates with a MIFARE Classic card to access the sector containing
+    /// `block`.
+    ///
+    /// `key` is checked against key A or key B stored in the sector trailer,
+    /// depending on `key_type`. New cards use `[0xff; 6]` for both keys. After
+    /// a successful authentication, all communication with the card is encrypted
+    /// until [`stop_crypto`] is called.
+    ///
+    /// [`stop_crypto`]: #method.stop_crypto
+    pub fn authenticate(
+        &mut self,
+        key_type: KeyType,
+        block: u8,
+        key: &[u8; 6],
+        card: &Card,
+    ) -> Result<()> {
+        let mut frame = [0u8; 12];
+        frame[0] = key_type as u8;
+        frame[1] = block;
+        frame[2..8].copy_from_slice(key);
+
+        // Authentication uses the last 4 bytes of the UID
+        let uid = card.uid();
+        frame[8..].copy_from_slice(&uid[uid.len() - 4..]);
+
+        match self.communicate(CMD_MF_AUTHENT, &frame, 0, 0) {
+            Ok(_) | Err(Error::Timeout) => (),
+            Err(e) => return Err(e),
+        }
+
+        if self.read_register(REG_STATUS2)? & STATUS2_CRYPTO1_ON == 0 {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        Ok(())
+    }
+
+    /// Ends the encrypted communication started by [`authenticate`].
+    ///
+    /// [`authenticate`]: #method.authenticate
+    pub fn stop_crypto(&mut self) -> Result<()> {
+        let reg_value = self.read_register(REG_STATUS2)?;
+
+        self.write_register(REG_STATUS2, reg_value & !STATUS2_CRYPTO1_ON)
+    }
+
+    /// Reads a 16-byte MIFARE Classic block.
+    ///
+    /// The sector containing `block` needs to be authenticated first through
+    /// [`authenticate`].
+    ///
+    /// [`authenticate`]: #method.authenticate
+    pub fn read_block(&mut self, block: u8) -> Result<[u8; 16]> {
+        let crc = crc_a(&[PICC_MF_READ, block]);
+        let response = self.transceive_crc(&[PICC_MF_READ, block, crc[0], crc[1]])?;
+
+        if response.len() != 16 {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        let mut data = [0u8; 16];
+        data.copy_from_slice(&response);
+
+        Ok(data)
+    }
+
+    /// Writes a 16-byte MIFARE Classic block.
+    ///
+    /// The sector containing `block` needs to be authenticated first through
+    /// [`authenticate`]. Be careful when writing to a sector trailer (the last block
+    /// of each sector), as invalid access bits permanently lock the sector.
+    ///
+    /// [`authenticate`]: #method.authenticate
+    pub fn write_block(&mut self, block: u8, data: &[u8; 16]) -> Result<()> {
+        let crc = crc_a(&[PICC_MF_WRITE, block]);
+        self.transceive_ack(&[PICC_MF_WRITE, block, crc[0], crc[1]])?;
+
+        let mut frame = [0u8; 18];
+        frame[..16].copy_from_slice(data);
+        let crc = crc_a(data);
+        frame[16..].copy_from_slice(&crc);
+
+        self.transceive_ack(&frame)
+    }
+
+    /// Consumes the `Mfrc522`, and returns the underlying `Spi` instance.
+    pub fn into_spi(self) -> Spi {
+        self.spi
+    }
+
+    fn request_a(&mut self, command: u8) -> Result<Option<[u8; 2]>> {
+        // REQA and WUPA are sent as 7-bit short frames
+        let (response, valid_bits) = match self.communicate(CMD_TRANSCEIVE, &[command], 7, 0) {
+            Ok(response) => response,
+            Err(Error::Timeout) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        if response.len() != 2 || valid_bits != 0 {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        Ok(Some([response[0], response[1]]))
+    }
+
+    // Retrieves the 4 UID bytes and BCC for a single cascade level, resolving any
+    // collisions by selecting the card with a 1 at the collision position
+    fn anticollision(&mut self, sel: u8) -> Result<[u8; 5]> {
+        let mut uid_cl = [0u8; 5];
+        let mut known_bits = 0;
+
+        // Clear all received bits after a collision
+        let coll = self.read_register(REG_COLL)?;
+        self.write_register(REG_COLL, coll & !COLL_VALUES_AFTER_COLL)?;
+
+        loop {
+            let known_bytes = known_bits / 8;
+            let extra_bits = (known_bits % 8) as u8;
+            let tx_bytes = known_bytes + if extra_bits > 0 { 1 } else { 0 };
+
+            // NVB contains the number of valid bytes (including SEL and NVB) in the
+            // upper nibble, and the number of extra valid bits in the lower nibble
+            let mut frame = [0u8; 7];
+            frame[0] = sel;
+            frame[1] = (((2 + known_bytes) as u8) << 4) | extra_bits;
+            frame[2..2 + tx_bytes].copy_from_slice(&uid_cl[..tx_bytes]);
+
+            // The first received bit is aligned with the first unknown bit
+            let result = self.communicate(
+                CMD_TRANSCEIVE,
+                &frame[..2 + tx_bytes],
+                extra_bits,
+                extra_bits,
+            );
+
+            // On a collision, the bits received up to the collision are left in the FIFO
+            let (response, collision) = match result {
+                Ok((response, _)) => (response, false),
+                Err(Error::Collision) => (self.read_fifo()?, true),
+                Err(e) => return Err(e),
+            };
+
+            for (i, byte) in response.iter().enumerate() {
+                let index = known_bytes + i;
+                if index >= uid_cl.len() {
+                    break;
+                }
+
+                if i == 0 && extra_bits > 0 {
+                    // The lower bits of the first byte were sent by us
+                    let mask = (1 << extra_bits) - 1;
+                    uid_cl[index] = (uid_cl[index] & mask) | (byte & !mask);
+                } else {
+                    uid_cl[index] = *byte;
+                }
+            }
+
+            if !collision {
+                if response.len() + known_bytes < uid_cl.len() {
+                    return Err(Error::UnexpectedResponse);
+                }
+
+                if uid_cl[..4].iter().fold(0, |bcc, b| bcc ^ b) != uid_cl[4] {
+                    return Err(Error::Crc);
+                }
+
+                return Ok(uid_cl);
+            }
+
+            let coll = self.read_register(REG_COLL)?;
+            if coll & COLL_POS_NOT_VALID != 0 {
+                return Err(Error::Collision);
+            }
+
+            // CollPos is 1-based, and 0 indicates a collision in bit 32
+            let position = match (coll & 0x1f) as usize {
+                0 => 32,
+                position => position,
+            };
+
+            if position <= known_bits {
+                return Err(Error::Collision);
+            }
+
+            // Pick the card that sent a 1 at the collision position
+            uid_cl[(position - 1) / 8] |= 1 << ((position - 1) % 8);
+            known_bits = position;
+        }
+    }
+
+    // Sends frame, and verifies and strips the CRC_A from the response
+    fn transceive_crc(&mut self, frame: &[u8]) -> Result<Vec<u8>> {
+        let (mut response, valid_bits) = self.communicate(CMD_TRANSCEIVE, frame, 0, 0)?;
+
+        if response.len() == 1 && valid_bits == 4 {
+            return Err(Error::Nack(response[0] & 0x0f));
+        }
+
+        if response.len() < 3 || valid_bits != 0 {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        let data_len = response.len() - 2;
+        if crc_a(&response[..data_len]) != response[data_len..] {
+            return Err(Error::Crc);
+        }
+
+        response.truncate(data_len);
+
+        Ok(response)
+    }
+
+    // Sends frame, and expects a 4-bit MIFARE ACK
+    fn transceive_ack(&mut self, frame: &[u8]) -> Result<()> {
+        let (response, valid_bits) = self.communicate(CMD_TRANSCEIVE, frame, 0, 0)?;
+
+        if response.len() != 1 || valid_bits != 4 {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        if response[0] & 0x0f != PICC_MF_ACK {
+            return Err(Error::Nack(response[0] & 0x0f));
+        }
+
+        Ok(())
+    }
+
+    // Executes a command that sends data to the card, and returns the received
+    // bytes and the number of valid bits in the last byte (0 if all 8 bits are
+    // valid). tx_last_bits sets the number of bits to send from the last byte, and
+    // rx_align the bit position in the first byte where received bits are stored.
+    //
+    // Error::Collision is returned after the received data has been left in the
+    // FIFO, so the anticollision loop can retrieve it.
+    fn communicate(
+        &mut self,
+        command: u8,
+        data: &[u8],
+        tx_last_bits: u8,
+        rx_align: u8,
+    ) -> Result<(Vec<u8>, u8)> {
+        let wait_irq = if command == CMD_TRANSCEIVE {
+            COM_IRQ_RX | COM_IRQ_IDLE
+        } else {
+            COM_IRQ_IDLE
+        };
+
+        self.write_register(REG_COMMAND, CMD_IDLE)?;
+        self.write_register(REG_COM_IRQ, COM_IRQ_ALL)?;
+        self.write_register(REG_FIFO_LEVEL, FIFO_FLUSH)?;
+        self.write_fifo(data)?;
+        self.write_register(REG_BIT_FRAMING, (rx_align << 4) | tx_last_bits)?;
+        self.write_register(REG_COMMAND, command)?;
+
+        if command == CMD_TRANSCEIVE {
+            self.write_register(
+                REG_BIT_FRAMING,
+                BIT_FRAMING_START_SEND | (rx_align << 4) | tx_last_bits,
+            )?;
+        }
+
+        let start = Instant::now();
+        loop {
+            let irq = self.read_register(REG_COM_IRQ)?;
+
+            if irq & wait_irq != 0 {
+                break;
+            }
+
+            if irq & COM_IRQ_TIMER != 0 || start.elapsed() > COMMAND_TIMEOUT {
+                self.write_register(REG_COMMAND, CMD_IDLE)?;
+                return Err(Error::Timeout);
+            }
+        }
+
+        self.write_register(REG_BIT_FRAMING, 0)?;
+
+        let error = self.read_register(REG_ERROR)?;
+        if error & ERROR_MASK != 0 {
+            return Err(Error::Communication(error));
+        }
+
+        if error & ERROR_COLL != 0 {
+            return Err(Error::Collision);
+        }
+
+        if command != CMD_TRANSCEIVE {
+            return Ok((Vec::new(), 0));
+        }
+
+        let response = self.read_fifo()?;
+        let valid_bits = self.read_register(REG_CONTROL)? & 0x07;
+
+        Ok((response, valid_bits))
+    }
+
+    fn read_register(&mut self, register: u8) -> Result<u8> {
+        let mut buffer = [0u8; 2];
+        self.spi
+            .transfer(&mut buffer, &[0x80 | (register << 1), 0])?;
+
+        Ok(buffer[1])
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) -> Result<()> {
+        self.spi.write(&[register << 1, value])?;
+
+        Ok(())
+    }
+
+    fn read_fifo(&mut self) -> Result<Vec<u8>> {
+        let len = self.read_register(REG_FIFO_LEVEL)? as usize & 0x7f;
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Each byte shifted out addresses the FIFO again, and the response to each
+        // address is shifted in during the next byte
+        let mut write_buffer = vec![0x80 | (REG_FIFO_DATA << 1); len + 1];
+        write_buffer[len] = 0;
+        let mut read_buffer = vec![0u8; len + 1];
+        self.spi.transfer(&mut read_buffer, &write_buffer)?;
+
+        read_buffer.remove(0);
+
+        Ok(read_buffer)
+    }
+
+    fn write_fifo(&mut self, data: &[u8]) -> Result<()> {
+        let mut buffer = Vec::with_capacity(data.len() + 1);
+        buffer.push(REG_FIFO_DATA << 1);
+        buffer.extend_from_slice(data);
+
+        self.spi.write(&buffer)?;
+
+        Ok(())
+    }
+}
+
+// Calculates the CRC_A checksum (ISO/IEC 14443-3, annex B), which is appended to
+// most card commands, and returns it in transmission order (LSB first)
+fn crc_a(data: &[u8]) -> [u8; 2] {
+    let mut crc: u16 = 0x6363;
+
+    for &byte in data {
+        let mut b = byte ^ (crc as u8);
+        b ^= b << 4;
+        crc = (crc >> 8) ^ (u16::from(b) << 8) ^ (u16::from(b) << 3) ^ (u16::from(b) >> 4);
+    }
+
+    [crc as u8, (crc >> 8) as u8]
+}
@@ -0,0 +1,94 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Drivers for real-time clocks.
+//!
+//! * [`Ds3231`] supports the Maxim DS3231 temperature-compensated RTC, connected
+//!   through I2C. Besides keeping time, it reads out the temperature measured by
+//!   its integrated sensor.
+//! * [`Pcf8523`] supports the NXP PCF8523 RTC, connected through I2C, which is
+//!   found on many Raspberry Pi RTC add-on boards.
+//!
+//! Both drivers read and set the date and time as a [`DateTime`], and configure
+//! an [`Alarm`] that pulls the RTC's interrupt output low when it triggers. By
+//! connecting the interrupt output to a GPIO pin, you can block until the alarm
+//! triggers with `wait_for_alarm`, or handle it with an asynchronous interrupt
+//! trigger on the [`InputPin`]. The interrupt output is open-drain, so the pin
+//! needs a pull-up resistor.
+//!
+//! ## Optional features
+//!
+//! With the `chrono` feature enabled, [`DateTime`] converts from and to
+//! `chrono::NaiveDateTime`. With the `time` feature enabled, [`DateTime`] converts
+//! from and to `time::PrimitiveDateTime`.
+//!
+//! [`Ds3231`]: struct.Ds3231.html
+//! [`Pcf8523`]: struct.Pcf8523.html
+//! [`DateTime`]: struct.DateTime.html
+//! [`Alarm`]: enum.Alarm.html
+//! [`InputPin`]: ../gpio/struct.InputPin.html
+
+use std::result;
+use std::time::Duration;
+
+use quick_error::quick_error;
+
+use crate::gpio::{self, InputPin, Trigger};
+use crate::i2c;
+
+mod datetime;
+mod ds3231;
+mod pcf8523;
+
+pub use self::datetime::{Alarm, DateTime};
+pub use self::ds3231::Ds3231;
+pub use self::pcf8523::Pcf8523;
+
+quick_error! {
+/// Errors that can occur when accessing a real-time clock.
+    #[derive(Debug)]
+    pub enum Error {
+/// I2C error.
+        I2c(err: i2c::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Invalid date or time.
+///
+/// One of the fields is out of range, or the year is outside of the supported
+/// 2000-2099 range.
+        InvalidDateTime { description("invalid date or time") }
+/// Invalid alarm.
+///
+/// One of the alarm's fields is out of range.
+        InvalidAlarm { description("invalid alarm") }
+    }
+}
+
+/// Result type returned from methods that can have `rtc::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+// Waits for a falling edge on the RTC's open-drain interrupt output
+fn wait_for_interrupt(pin: &mut InputPin, timeout: Option<Duration>) -> Result<bool> {
+    pin.set_interrupt(Trigger::FallingEdge)?;
+    let event = pin.poll_interrupt(true, timeout);
+    pin.clear_interrupt()?;
+
+    Ok(event?.is_some())
+}
@@ -0,0 +1,278 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fmt;
+
+use crate::rtc::{Error, Result};
+
+/// A calendar date and time, without a time zone.
+///
+/// The supported years range from 2000 through 2099, which matches the range
+/// both RTCs keep track of, including leap years.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct DateTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl DateTime {
+    /// Constructs a new `DateTime`.
+    ///
+    /// `month` ranges from 1 through 12, `day` from 1 through the number of days
+    /// in the month, `hour` from 0 through 23, and `minute` and `second` from 0
+    /// through 59. Returns [`Error::InvalidDateTime`] if any of the fields are
+    /// out of range.
+    ///
+    /// [`Error::InvalidDateTime`]: enum.Error.html#variant.InvalidDateTime
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> Result<DateTime> {
+        if !(2000..=2099).contains(&year)
+            || !(1..=12).contains(&month)
+            || !(1..=days_in_month(year, month)).contains(&day)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(Error::InvalidDateTime);
+        }
+
+        Ok(DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    /// Returns the year.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Returns the month (1-12).
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Returns the day of the month (1-31).
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Returns the hour (0-23).
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Returns the minute (0-59).
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Returns the second (0-59).
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Returns the day of the week, where 0 is Sunday and 6 is Saturday.
+    pub fn weekday(&self) -> u8 {
+        // Sakamoto's method
+        const OFFSETS: [u16; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+
+        let year = if self.month < 3 {
+            self.year - 1
+        } else {
+            self.year
+        };
+
+        ((year + year / 4 - year / 100
+            + year / 400
+            + OFFSETS[usize::from(self.month - 1)]
+            + u16::from(self.day))
+            % 7) as u8
+    }
+
+    // Decodes the BCD-encoded seconds, minutes, hours, day, month and year
+    // registers shared by both RTCs. Any control bits should already be masked.
+    pub(crate) fn from_bcd(registers: [u8; 6]) -> Result<DateTime> {
+        DateTime::new(
+            2000 + u16::from(bcd_to_dec(registers[5])),
+            bcd_to_dec(registers[4]),
+            bcd_to_dec(registers[3]),
+            bcd_to_dec(registers[2]),
+            bcd_to_dec(registers[1]),
+            bcd_to_dec(registers[0]),
+        )
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// Alarm match conditions.
+///
+/// An alarm triggers at the start of the minute when the current time matches
+/// all of its fields.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Alarm {
+    /// Triggers every hour, when the minute (0-59) matches.
+    Hourly { minute: u8 },
+    /// Triggers every day, when the hour (0-23) and the minute (0-59) match.
+    Daily { hour: u8, minute: u8 },
+    /// Triggers every month, when the day of the month (1-31), the hour (0-23)
+    /// and the minute (0-59) match.
+    Monthly { day: u8, hour: u8, minute: u8 },
+}
+
+impl Alarm {
+    // Returns the minute, and optionally the hour and day, after checking
+    // whether the fields are in range
+    pub(crate) fn fields(self) -> Result<(u8, Option<u8>, Option<u8>)> {
+        let (minute, hour, day) = match self {
+            Alarm::Hourly { minute } => (minute, None, None),
+            Alarm::Daily { hour, minute } => (minute, Some(hour), None),
+            Alarm::Monthly { day, hour, minute } => (minute, Some(hour), Some(day)),
+        };
+
+        if minute > 59 || hour.unwrap_or(0) > 23 || day.is_some_and(|day| !(1..=31).contains(&day))
+        {
+            return Err(Error::InvalidAlarm);
+        }
+
+        Ok((minute, hour, day))
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        // Every year from 2000 through 2099 that's divisible by 4 is a leap year
+        2 if year & 0b11 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+pub(crate) fn bcd_to_dec(value: u8) -> u8 {
+    (value >> 4) * 10 + (value & 0x0f)
+}
+
+pub(crate) fn dec_to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+#[cfg(feature = "chrono")]
+impl From<DateTime> for chrono::NaiveDateTime {
+    fn from(datetime: DateTime) -> chrono::NaiveDateTime {
+        // DateTime only holds valid dates and times
+        chrono::NaiveDate::from_ymd_opt(
+            i32::from(datetime.year),
+            u32::from(datetime.month),
+            u32::from(datetime.day),
+        )
+        .and_then(|date| {
+            date.and_hms_opt(
+                u32::from(datetime.hour),
+                u32::from(datetime.minute),
+                u32::from(datetime.second),
+            )
+        })
+        .unwrap()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl std::convert::TryFrom<chrono::NaiveDateTime> for DateTime {
+    type Error = Error;
+
+    fn try_from(datetime: chrono::NaiveDateTime) -> Result<DateTime> {
+        use chrono::{Datelike, Timelike};
+
+        if datetime.year() < 2000 || datetime.year() > 2099 {
+            return Err(Error::InvalidDateTime);
+        }
+
+        DateTime::new(
+            datetime.year() as u16,
+            datetime.month() as u8,
+            datetime.day() as u8,
+            datetime.hour() as u8,
+            datetime.minute() as u8,
+            datetime.second() as u8,
+        )
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<DateTime> for time::PrimitiveDateTime {
+    fn from(datetime: DateTime) -> time::PrimitiveDateTime {
+        use std::convert::TryFrom;
+
+        // DateTime only holds valid dates and times
+        let date = time::Month::try_from(datetime.month)
+            .ok()
+            .and_then(|month| {
+                time::Date::from_calendar_date(i32::from(datetime.year), month, datetime.day).ok()
+            })
+            .unwrap();
+        let time = time::Time::from_hms(datetime.hour, datetime.minute, datetime.second).unwrap();
+
+        time::PrimitiveDateTime::new(date, time)
+    }
+}
+
+#[cfg(feature = "time")]
+impl std::convert::TryFrom<time::PrimitiveDateTime> for DateTime {
+    type Error = Error;
+
+    fn try_from(datetime: time::PrimitiveDateTime) -> Result<DateTime> {
+        if datetime.year() < 2000 || datetime.year() > 2099 {
+            return Err(Error::InvalidDateTime);
+        }
+
+        DateTime::new(
+            datetime.year() as u16,
+            datetime.month() as u8,
+            datetime.day(),
+            datetime.hour(),
+            datetime.minute(),
+            datetime.second(),
+        )
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::Duration;
+
+use crate::gpio::InputPin;
+use crate::i2c::I2c;
+use crate::rtc::datetime::{bcd_to_dec, dec_to_bcd};
+use crate::rtc::{wait_for_interrupt, Alarm, DateTime, Result};
+
+const REG_SECONDS: u8 = 0x00;
+const REG_ALARM2_MINUTES: u8 = 0x0b;
+const REG_CONTROL: u8 = 0x0e;
+const REG_STATUS: u8 = 0x0f;
+const REG_TEMP_MSB: u8 = 0x11;
+
+// Alarm mask bit, set to ignore a field
+const ALARM_MASK: u8 = 0x80;
+
+const CONTROL_INTCN: u8 = 0x04;
+const CONTROL_A2IE: u8 = 0x02;
+
+const STATUS_OSF: u8 = 0x80;
+const STATUS_A2F: u8 = 0x02;
+
+// Hours register 12-hour mode flag, and century flag in the month register
+const HOURS_12H: u8 = 0x40;
+const MONTH_CENTURY: u8 = 0x80;
+
+/// DS3231 real-time clock driver.
+///
+/// The DS3231 uses slave address 0x68. Alarms are configured through alarm 2,
+/// which has a resolution of one minute. The INT/SQW output is switched to
+/// interrupt mode when an alarm is set.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::Gpio;
+/// use rppal::i2c::I2c;
+/// use rppal::rtc::{Alarm, DateTime, Ds3231};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut rtc = Ds3231::new(I2c::new()?)?;
+///
+/// if rtc.lost_power()? {
+///     rtc.set_datetime(&DateTime::new(2019, 1, 1, 12, 0, 0)?)?;
+/// }
+///
+/// println!("{} ({:.2} °C)", rtc.read_datetime()?, rtc.temperature()?);
+///
+/// // INT/SQW is connected to GPIO 4
+/// let mut pin = Gpio::new()?.get(4).unwrap().into_input_pullup();
+/// rtc.set_alarm(Alarm::Daily { hour: 7, minute: 30 })?;
+/// rtc.wait_for_alarm(&mut pin, None)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Ds3231 {
+    i2c: I2c,
+}
+
+impl Ds3231 {
+    /// Constructs a new `Ds3231`.
+    pub fn new(mut i2c: I2c) -> Result<Ds3231> {
+        i2c.set_slave_address(0x68)?;
+
+        Ok(Ds3231 { i2c })
+    }
+
+    /// Reads the current date and time.
+    pub fn read_datetime(&self) -> Result<DateTime> {
+        let mut buffer = [0u8; 7];
+        self.i2c.write_read(&[REG_SECONDS], &mut buffer)?;
+
+        let hours = if buffer[2] & HOURS_12H != 0 {
+            twelve_to_24(buffer[2])
+        } else {
+            buffer[2] & 0x3f
+        };
+
+        // Skip the day of the week register
+        DateTime::from_bcd([
+            buffer[0] & 0x7f,
+            buffer[1] & 0x7f,
+            hours,
+            buffer[4] & 0x3f,
+            buffer[5] & 0x1f,
+            buffer[6],
+        ])
+    }
+
+    /// Sets the date and time, and clears the oscillator stop flag.
+    pub fn set_datetime(&mut self, datetime: &DateTime) -> Result<()> {
+        self.i2c.write(&[
+            REG_SECONDS,
+            dec_to_bcd(datetime.second()),
+            dec_to_bcd(datetime.minute()),
+            dec_to_bcd(datetime.hour()),
+            // The DS3231 counts days of the week from 1 through 7
+            datetime.weekday() + 1,
+            dec_to_bcd(datetime.day()),
+            dec_to_bcd(datetime.month()) & !MONTH_CENTURY,
+            dec_to_bcd((datetime.year() - 2000) as u8),
+        ])?;
+
+        let status = self.read_register(REG_STATUS)?;
+        self.write_register(REG_STATUS, status & !STATUS_OSF)
+    }
+
+    /// Returns `true` if the oscillator stopped since the date and time were
+    /// last set, which happens when both the main and the backup power supply
+    /// were lost, and means the date and time are no longer valid.
+    pub fn lost_power(&self) -> Result<bool> {
+        Ok(self.read_register(REG_STATUS)? & STATUS_OSF != 0)
+    }
+
+    /// Returns the temperature in degrees Celsius (°C), with a resolution of
+    /// 0.25 °C.
+    ///
+    /// The DS3231 measures the temperature every 64 seconds to compensate for
+    /// crystal frequency drift.
+    pub fn temperature(&self) -> Result<f64> {
+        let mut buffer = [0u8; 2];
+        self.i2c.write_read(&[REG_TEMP_MSB], &mut buffer)?;
+
+        Ok(f64::from(buffer[0] as i8) + f64::from(buffer[1] >> 6) * 0.25)
+    }
+
+    /// Sets an alarm, and enables the alarm interrupt on the INT/SQW output.
+    ///
+    /// Any previously triggered alarm is cleared.
+    pub fn set_alarm(&mut self, alarm: Alarm) -> Result<()> {
+        let (minute, hour, day) = alarm.fields()?;
+
+        self.i2c.write(&[
+            REG_ALARM2_MINUTES,
+            dec_to_bcd(minute),
+            hour.map_or(ALARM_MASK, dec_to_bcd),
+            day.map_or(ALARM_MASK, dec_to_bcd),
+        ])?;
+
+        self.clear_alarm()?;
+
+        let control = self.read_register(REG_CONTROL)?;
+        self.write_register(REG_CONTROL, control | CONTROL_INTCN | CONTROL_A2IE)
+    }
+
+    /// Disables the alarm interrupt.
+    pub fn disable_alarm(&mut self) -> Result<()> {
+        let control = self.read_register(REG_CONTROL)?;
+        self.write_register(REG_CONTROL, control & !CONTROL_A2IE)?;
+
+        self.clear_alarm()
+    }
+
+    /// Returns `true` if the alarm has triggered, and hasn't been cleared yet.
+    pub fn alarm_triggered(&self) -> Result<bool> {
+        Ok(self.read_register(REG_STATUS)? & STATUS_A2F != 0)
+    }
+
+    /// Clears the alarm flag, which releases the INT/SQW output.
+    pub fn clear_alarm(&mut self) -> Result<()> {
+        let status = self.read_register(REG_STATUS)?;
+        self.write_register(REG_STATUS, status & !STATUS_A2F)
+    }
+
+    /// Blocks until the alarm triggers, or `timeout` elapses, and then clears
+    /// the alarm.
+    ///
+    /// `pin` should be connected to the INT/SQW output, and have a pull-up
+    /// resistor. Returns `Ok(true)` if the alarm triggered, or `Ok(false)` if
+    /// `timeout` elapsed. `timeout` can be set to `None` to wait indefinitely.
+    pub fn wait_for_alarm(
+        &mut self,
+        pin: &mut InputPin,
+        timeout: Option<Duration>,
+    ) -> Result<bool> {
+        if !self.alarm_triggered()? && !wait_for_interrupt(pin, timeout)? {
+            return Ok(false);
+        }
+
+        self.clear_alarm()?;
+
+        Ok(true)
+    }
+
+    /// Consumes the `Ds3231`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+
+    fn read_register(&self, register: u8) -> Result<u8> {
+        Ok(self.i2c.smbus_read_byte(register)?)
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) -> Result<()> {
+        Ok(self.i2c.smbus_write_byte(register, value)?)
+    }
+}
+
+// Converts a BCD-encoded 12-hour value with AM/PM flag to a BCD-encoded 24-hour value
+fn twelve_to_24(value: u8) -> u8 {
+    let hour = bcd_to_dec(value & 0x1f) % 12;
+    let pm = value & 0x20 != 0;
+
+    dec_to_bcd(if pm { hour + 12 } else { hour })
+}
@@ -0,0 +1,241 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::Duration;
+
+use crate::gpio::InputPin;
+use crate::i2c::I2c;
+use crate::rtc::datetime::{bcd_to_dec, dec_to_bcd};
+use crate::rtc::{wait_for_interrupt, Alarm, DateTime, Result};
+
+const REG_CONTROL_1: u8 = 0x00;
+const REG_CONTROL_2: u8 = 0x01;
+const REG_CONTROL_3: u8 = 0x02;
+const REG_SECONDS: u8 = 0x03;
+const REG_MINUTE_ALARM: u8 = 0x0a;
+const REG_TMR_CLKOUT_CTRL: u8 = 0x0f;
+
+// Alarm enable bit, cleared to enable a field
+const ALARM_DISABLE: u8 = 0x80;
+
+const CONTROL_1_12_24: u8 = 0x08;
+const CONTROL_1_AIE: u8 = 0x02;
+const CONTROL_2_AF: u8 = 0x08;
+// Battery switch-over in standard mode, with battery low detection enabled
+const CONTROL_3_SWITCHOVER: u8 = 0x00;
+// Disables CLKOUT, so INT1 only signals interrupts
+const TMR_CLKOUT_DISABLE: u8 = 0x38;
+
+// Oscillator stop flag in the seconds register
+const SECONDS_OS: u8 = 0x80;
+
+/// PCF8523 real-time clock driver.
+///
+/// The PCF8523 uses slave address 0x68. During construction, battery switch-over
+/// is enabled, so the PCF8523 keeps time on its backup battery when the main
+/// power supply is lost. Alarms pull the INT1 output low, which is switched
+/// from CLKOUT to interrupt mode when an alarm is set.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::i2c::I2c;
+/// use rppal::rtc::{DateTime, Pcf8523};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut rtc = Pcf8523::new(I2c::new()?)?;
+///
+/// if rtc.lost_power()? {
+///     rtc.set_datetime(&DateTime::new(2019, 1, 1, 12, 0, 0)?)?;
+/// }
+///
+/// println!("{}", rtc.read_datetime()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Pcf8523 {
+    i2c: I2c,
+}
+
+impl Pcf8523 {
+    /// Constructs a new `Pcf8523`.
+    pub fn new(mut i2c: I2c) -> Result<Pcf8523> {
+        i2c.set_slave_address(0x68)?;
+
+        let mut rtc = Pcf8523 { i2c };
+        rtc.write_register(REG_CONTROL_3, CONTROL_3_SWITCHOVER)?;
+
+        // Use 24-hour mode
+        let control_1 = rtc.read_register(REG_CONTROL_1)?;
+        if control_1 & CONTROL_1_12_24 != 0 {
+            let datetime = rtc.read_datetime_12h()?;
+            rtc.write_register(REG_CONTROL_1, control_1 & !CONTROL_1_12_24)?;
+            if let Some(datetime) = datetime {
+                rtc.set_datetime(&datetime)?;
+            }
+        }
+
+        Ok(rtc)
+    }
+
+    /// Reads the current date and time.
+    pub fn read_datetime(&self) -> Result<DateTime> {
+        let buffer = self.read_time_registers()?;
+
+        // Skip the weekday register
+        DateTime::from_bcd([
+            buffer[0] & 0x7f,
+            buffer[1] & 0x7f,
+            buffer[2] & 0x3f,
+            buffer[3] & 0x3f,
+            buffer[5] & 0x1f,
+            buffer[6],
+        ])
+    }
+
+    /// Sets the date and time, and clears the oscillator stop flag.
+    pub fn set_datetime(&mut self, datetime: &DateTime) -> Result<()> {
+        // Writing the seconds register clears the oscillator stop flag
+        self.i2c.write(&[
+            REG_SECONDS,
+            dec_to_bcd(datetime.second()),
+            dec_to_bcd(datetime.minute()),
+            dec_to_bcd(datetime.hour()),
+            dec_to_bcd(datetime.day()),
+            datetime.weekday(),
+            dec_to_bcd(datetime.month()),
+            dec_to_bcd((datetime.year() - 2000) as u8),
+        ])?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if the oscillator stopped since the date and time were
+    /// last set, which happens when both the main and the backup power supply
+    /// were lost, and means the date and time are no longer valid.
+    pub fn lost_power(&self) -> Result<bool> {
+        Ok(self.read_register(REG_SECONDS)? & SECONDS_OS != 0)
+    }
+
+    /// Sets an alarm, and enables the alarm interrupt on the INT1 output.
+    ///
+    /// Any previously triggered alarm is cleared.
+    pub fn set_alarm(&mut self, alarm: Alarm) -> Result<()> {
+        let (minute, hour, day) = alarm.fields()?;
+
+        self.i2c.write(&[
+            REG_MINUTE_ALARM,
+            dec_to_bcd(minute),
+            hour.map_or(ALARM_DISABLE, dec_to_bcd),
+            day.map_or(ALARM_DISABLE, dec_to_bcd),
+            // Weekday alarm
+            ALARM_DISABLE,
+        ])?;
+
+        self.clear_alarm()?;
+
+        self.write_register(REG_TMR_CLKOUT_CTRL, TMR_CLKOUT_DISABLE)?;
+        let control_1 = self.read_register(REG_CONTROL_1)?;
+        self.write_register(REG_CONTROL_1, control_1 | CONTROL_1_AIE)
+    }
+
+    /// Disables the alarm interrupt.
+    pub fn disable_alarm(&mut self) -> Result<()> {
+        let control_1 = self.read_register(REG_CONTROL_1)?;
+        self.write_register(REG_CONTROL_1, control_1 & !CONTROL_1_AIE)?;
+
+        self.clear_alarm()
+    }
+
+    /// Returns `true` if the alarm has triggered, and hasn't been cleared yet.
+    pub fn alarm_triggered(&self) -> Result<bool> {
+        Ok(self.read_register(REG_CONTROL_2)? & CONTROL_2_AF != 0)
+    }
+
+    /// Clears the alarm flag, which releases the INT1 output.
+    pub fn clear_alarm(&mut self) -> Result<()> {
+        let control_2 = self.read_register(REG_CONTROL_2)?;
+        self.write_register(REG_CONTROL_2, control_2 & !CONTROL_2_AF)
+    }
+
+    /// Blocks until the alarm triggers, or `timeout` elapses, and then clears
+    /// the alarm.
+    ///
+    /// `pin` should be connected to the INT1 output, and have a pull-up resistor.
+    /// Returns `Ok(true)` if the alarm triggered, or `Ok(false)` if `timeout`
+    /// elapsed. `timeout` can be set to `None` to wait indefinitely.
+    pub fn wait_for_alarm(
+        &mut self,
+        pin: &mut InputPin,
+        timeout: Option<Duration>,
+    ) -> Result<bool> {
+        if !self.alarm_triggered()? && !wait_for_interrupt(pin, timeout)? {
+            return Ok(false);
+        }
+
+        self.clear_alarm()?;
+
+        Ok(true)
+    }
+
+    /// Consumes the `Pcf8523`, and returns the underlying `I2c` instance.
+    pub fn into_i2c(self) -> I2c {
+        self.i2c
+    }
+
+    // Reads the current date and time while the PCF8523 is in 12-hour mode,
+    // or None if the time registers don't contain a valid date and time
+    fn read_datetime_12h(&self) -> Result<Option<DateTime>> {
+        let buffer = self.read_time_registers()?;
+
+        let hour = bcd_to_dec(buffer[2] & 0x1f) % 12;
+        let hour = if buffer[2] & 0x20 != 0 {
+            hour + 12
+        } else {
+            hour
+        };
+
+        Ok(DateTime::new(
+            2000 + u16::from(bcd_to_dec(buffer[6])),
+            bcd_to_dec(buffer[5] & 0x1f),
+            bcd_to_dec(buffer[3] & 0x3f),
+            hour,
+            bcd_to_dec(buffer[1] & 0x7f),
+            bcd_to_dec(buffer[0] & 0x7f),
+        )
+        .ok())
+    }
+
+    fn read_time_registers(&self) -> Result<[u8; 7]> {
+        let mut buffer = [0u8; 7];
+        self.i2c.write_read(&[REG_SECONDS], &mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    fn read_register(&self, register: u8) -> Result<u8> {
+        Ok(self.i2c.smbus_read_byte(register)?)
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) -> Result<()> {
+        Ok(self.i2c.smbus_write_byte(register, value)?)
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! SDI-12 data recorder.
+//!
+//! SDI-12 is a single-wire serial protocol used by many environmental sensors,
+//! such as soil moisture probes, weather stations and water level loggers.
+//! Sensors share a single data line, and communicate at 1200 Bd, with 7 data
+//! bits, even parity and 1 stop bit, using negative logic. Every exchange is
+//! started by the data recorder, which wakes up the sensors with a break before
+//! sending a command.
+//!
+//! [`Sdi12`] is a data recorder that can use one of two backends:
+//!
+//! * [`Sdi12::new`] bit-bangs the protocol on a single GPIO pin through
+//!   [`SoftUart`], with inverted logic, so the data line can be connected
+//!   through a level shifter without an inverter.
+//! * [`Sdi12::with_uart`] uses a [`Uart`] with an external SDI-12 or RS-485 style
+//!   transceiver, which needs to invert the logic levels. The direction of the
+//!   transceiver is controlled through an optional GPIO pin, which is set high
+//!   while a command is sent.
+//!
+//! [`Sdi12`]: struct.Sdi12.html
+//! [`Sdi12::new`]: struct.Sdi12.html#method.new
+//! [`Sdi12::with_uart`]: struct.Sdi12.html#method.with_uart
+//! [`SoftUart`]: ../uart/struct.SoftUart.html
+//! [`Uart`]: ../uart/struct.Uart.html
+
+use std::result;
+
+use quick_error::quick_error;
+
+use crate::uart;
+
+mod recorder;
+
+pub use self::recorder::Sdi12;
+
+quick_error! {
+/// Errors that can occur when communicating with SDI-12 sensors.
+    #[derive(Debug)]
+    pub enum Error {
+/// UART error.
+        Uart(err: uart::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Timeout.
+///
+/// The sensor didn't respond in time. This usually means there's no sensor
+/// with the specified address.
+        Timeout { description("sensor didn't respond in time") }
+/// Invalid address.
+///
+/// SDI-12 addresses are the characters 0-9, a-z and A-Z.
+        InvalidAddress(address: char) { description("invalid address") }
+/// Invalid response.
+///
+/// The response was sent by a different sensor, or couldn't be parsed.
+        InvalidResponse { description("invalid response") }
+    }
+}
+
+/// Result type returned from methods that can have `sdi12::Error`s.
+pub type Result<T> = result::Result<T, Error>;
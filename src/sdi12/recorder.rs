@@ -0,0 +1,382 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::gpio::{Mode, OutputPin, Pin};
+use crate::sdi12::{Error, Result};
+use crate::uart::{Parity, Queue, SoftUart, Uart};
+
+const LINE_SPEED: u32 = 1200;
+const DATA_BITS: u8 = 7;
+const STOP_BITS: u8 = 1;
+
+// The break needs to last at least 12 ms, followed by at least 8.33 ms of marking
+const BREAK_DURATION: Duration = Duration::from_millis(13);
+const MARK_DURATION: Duration = Duration::from_micros(9000);
+// Sensors should start responding within 15 ms after the command was sent
+const RESPONSE_TIMEOUT: Duration = Duration::from_millis(20);
+// Maximum marking between characters is 1.66 ms
+const CHAR_TIMEOUT: Duration = Duration::from_millis(10);
+// Retry commands that aren't answered, as recommended by the specification
+const RETRIES: usize = 3;
+// Responses are limited to 75 characters for D commands, plus CR LF
+const MAX_RESPONSE_LEN: usize = 82;
+
+#[derive(Debug)]
+enum Bus {
+    Soft(SoftUart),
+    Uart {
+        uart: Uart,
+        direction_pin: Option<OutputPin>,
+    },
+}
+
+/// SDI-12 data recorder.
+///
+/// Sensor addresses are the characters 0-9, a-z and A-Z. Most sensors default
+/// to address `'0'`.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::gpio::Gpio;
+/// use rppal::sdi12::Sdi12;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut sdi12 = Sdi12::new(Gpio::new()?.get(27).unwrap())?;
+///
+/// println!("{}", sdi12.identify('0')?);
+/// println!("{:?}", sdi12.measure('0')?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Sdi12 {
+    bus: Bus,
+}
+
+impl Sdi12 {
+    /// Constructs a new `Sdi12` that bit-bangs the protocol on `pin`.
+    ///
+    /// The pin is switched between input and output mode through a [`SoftUart`]
+    /// with inverted logic.
+    ///
+    /// [`SoftUart`]: ../uart/struct.SoftUart.html
+    pub fn new(pin: Pin) -> Result<Sdi12> {
+        let mut soft_uart = SoftUart::new(
+            pin.into_io(Mode::Input),
+            LINE_SPEED,
+            Parity::Even,
+            DATA_BITS,
+            STOP_BITS,
+        )?;
+        soft_uart.set_inverted(true);
+
+        Ok(Sdi12 {
+            bus: Bus::Soft(soft_uart),
+        })
+    }
+
+    /// Constructs a new `Sdi12` that uses `uart`, connected through an external
+    /// inverting transceiver.
+    ///
+    /// `uart` is configured for 1200 Bd, 7 data bits, even parity and 1 stop bit.
+    /// If `direction_pin` is specified, it's set high while a command is sent, and
+    /// low while waiting for a response.
+    pub fn with_uart(mut uart: Uart, mut direction_pin: Option<OutputPin>) -> Result<Sdi12> {
+        uart.set_line_speed(LINE_SPEED)?;
+        uart.set_parity(Parity::Even)?;
+        uart.set_data_bits(DATA_BITS)?;
+        uart.set_stop_bits(STOP_BITS)?;
+        uart.set_read_mode(0, Duration::from_millis(100))?;
+
+        if let Some(pin) = direction_pin.as_mut() {
+            pin.set_low();
+        }
+
+        Ok(Sdi12 {
+            bus: Bus::Uart {
+                uart,
+                direction_pin,
+            },
+        })
+    }
+
+    /// Sends a break followed by `command`, and returns the response without
+    /// the trailing CR LF.
+    ///
+    /// `command` should include the address and the trailing `!`, for instance
+    /// `"0I!"`. Commands that aren't answered are retried up to 3 times.
+    pub fn command(&mut self, command: &str) -> Result<String> {
+        for _ in 0..RETRIES {
+            self.send(command)?;
+
+            match self.receive(RESPONSE_TIMEOUT) {
+                Err(Error::Timeout) => (),
+                result => return result,
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    /// Returns `true` if a sensor responds at `address` (command `a!`).
+    pub fn acknowledge(&mut self, address: char) -> Result<bool> {
+        check_address(address)?;
+
+        match self.command(&format!("{}!", address)) {
+            Ok(response) => Ok(response.starts_with(address)),
+            Err(Error::Timeout) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the address of the only sensor connected to the bus (command `?!`).
+    ///
+    /// If multiple sensors are connected, their responses will collide.
+    pub fn query_address(&mut self) -> Result<char> {
+        let response = self.command("?!")?;
+
+        response
+            .chars()
+            .next()
+            .filter(|&c| check_address(c).is_ok())
+            .ok_or(Error::InvalidResponse)
+    }
+
+    /// Changes the address of the sensor at `address` to `new_address` (command
+    /// `aAb!`).
+    pub fn change_address(&mut self, address: char, new_address: char) -> Result<()> {
+        check_address(address)?;
+        check_address(new_address)?;
+
+        let response = self.command(&format!("{}A{}!", address, new_address))?;
+        if !response.starts_with(new_address) {
+            return Err(Error::InvalidResponse);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the identification of the sensor at `address` (command `aI!`),
+    /// which includes the SDI-12 version, vendor, model and sensor version.
+    pub fn identify(&mut self, address: char) -> Result<String> {
+        let response = self.addressed_command(address, "I")?;
+
+        Ok(response[1..].to_owned())
+    }
+
+    /// Starts a measurement on the sensor at `address` (command `aM!`).
+    ///
+    /// Returns the time until the measurement is available, and the number of
+    /// values it contains.
+    pub fn start_measurement(&mut self, address: char) -> Result<(Duration, u8)> {
+        let response = self.addressed_command(address, "M")?;
+
+        // atttn
+        if response.len() != 5 || !response[1..].bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::InvalidResponse);
+        }
+
+        let seconds: u64 = response[1..4].parse().map_err(|_| Error::InvalidResponse)?;
+        let count: u8 = response[4..].parse().map_err(|_| Error::InvalidResponse)?;
+
+        Ok((Duration::from_secs(seconds), count))
+    }
+
+    /// Reads the values returned by data command `index` (0-9) from the sensor
+    /// at `address` (command `aDn!`).
+    pub fn read_data(&mut self, address: char, index: u8) -> Result<Vec<f64>> {
+        if index > 9 {
+            return Err(Error::InvalidResponse);
+        }
+
+        let response = self.addressed_command(address, &format!("D{}", index))?;
+
+        parse_values(&response[1..])
+    }
+
+    /// Starts a measurement on the sensor at `address`, waits until it's
+    /// available, and returns all values.
+    ///
+    /// When using a `Uart`, the sensor may signal that the measurement is
+    /// available earlier than announced by sending a service request, which ends
+    /// the wait.
+    pub fn measure(&mut self, address: char) -> Result<Vec<f64>> {
+        let (wait, count) = self.start_measurement(address)?;
+
+        match self.bus {
+            // Polling the pin for a service request would keep a CPU core busy
+            // for the entire measurement, so wait for the announced time instead
+            Bus::Soft(_) => sleep(wait),
+            // A service request consists of the address followed by CR LF
+            Bus::Uart { .. } if wait > Duration::default() => {
+                match self.receive(wait + RESPONSE_TIMEOUT) {
+                    Ok(_) | Err(Error::Timeout) => (),
+                    Err(e) => return Err(e),
+                }
+            }
+            Bus::Uart { .. } => (),
+        }
+
+        let mut values = Vec::with_capacity(usize::from(count));
+        for index in 0..=9 {
+            if values.len() >= usize::from(count) {
+                break;
+            }
+
+            let data = self.read_data(address, index)?;
+            if data.is_empty() {
+                return Err(Error::InvalidResponse);
+            }
+
+            values.extend(data);
+        }
+
+        Ok(values)
+    }
+
+    fn addressed_command(&mut self, address: char, command: &str) -> Result<String> {
+        check_address(address)?;
+
+        let response = self.command(&format!("{}{}!", address, command))?;
+        if !response.starts_with(address) {
+            return Err(Error::InvalidResponse);
+        }
+
+        Ok(response)
+    }
+
+    fn send(&mut self, command: &str) -> Result<()> {
+        match &mut self.bus {
+            Bus::Soft(soft_uart) => {
+                soft_uart.send_break(BREAK_DURATION, MARK_DURATION);
+                soft_uart.write(command.as_bytes())?;
+            }
+            Bus::Uart {
+                uart,
+                direction_pin,
+            } => {
+                if let Some(pin) = direction_pin.as_mut() {
+                    pin.set_high();
+                }
+
+                uart.set_break(true)?;
+                sleep(BREAK_DURATION);
+                uart.set_break(false)?;
+                sleep(MARK_DURATION);
+
+                let result = uart.write(command.as_bytes()).and_then(|_| uart.drain());
+
+                if let Some(pin) = direction_pin.as_mut() {
+                    pin.set_low();
+                }
+
+                // Discard anything that was echoed while sending
+                uart.flush(Queue::Input)?;
+
+                result?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Waits up to timeout for the start of a response, and returns it without
+    // the trailing CR LF
+    fn receive(&mut self, timeout: Duration) -> Result<String> {
+        let mut response = Vec::with_capacity(MAX_RESPONSE_LEN);
+        let mut byte = [0u8];
+
+        let deadline = Instant::now() + timeout;
+        while !response.ends_with(b"\r\n") {
+            if response.len() >= MAX_RESPONSE_LEN {
+                return Err(Error::InvalidResponse);
+            }
+
+            let len = match &mut self.bus {
+                Bus::Soft(soft_uart) => {
+                    let char_timeout = if response.is_empty() {
+                        timeout
+                    } else {
+                        CHAR_TIMEOUT
+                    };
+
+                    soft_uart.read(&mut byte, char_timeout)?
+                }
+                Bus::Uart { uart, .. } => uart.read(&mut byte)?,
+            };
+
+            if len == 0 {
+                if !response.is_empty() || Instant::now() > deadline {
+                    return Err(Error::Timeout);
+                }
+
+                continue;
+            }
+
+            // Strip the parity bit in case the device doesn't
+            response.push(byte[0] & 0x7f);
+        }
+
+        response.truncate(response.len() - 2);
+
+        String::from_utf8(response).map_err(|_| Error::InvalidResponse)
+    }
+}
+
+fn check_address(address: char) -> Result<()> {
+    if address.is_ascii_alphanumeric() {
+        Ok(())
+    } else {
+        Err(Error::InvalidAddress(address))
+    }
+}
+
+// Parses values formatted as a sign followed by up to 7 digits and an optional
+// decimal point, for instance +1.23-45.6+7
+fn parse_values(data: &str) -> Result<Vec<f64>> {
+    let mut values = Vec::new();
+    let mut start = None;
+
+    for (idx, c) in data.char_indices() {
+        if c == '+' || c == '-' {
+            if let Some(start) = start {
+                values.push(parse_value(&data[start..idx])?);
+            }
+
+            start = Some(idx);
+        } else if start.is_none() {
+            return Err(Error::InvalidResponse);
+        }
+    }
+
+    if let Some(start) = start {
+        values.push(parse_value(&data[start..])?);
+    }
+
+    Ok(values)
+}
+
+fn parse_value(value: &str) -> Result<f64> {
+    value.parse().map_err(|_| Error::InvalidResponse)
+}
@@ -73,7 +73,17 @@
 //! transfer. You can increase this limit to a maximum of 65536 bytes by adding
 //! `spidev.bufsiz=65536` to the single line of parameters in `/boot/cmdline.txt`.
 //! Remember to reboot the Raspberry Pi afterwards. The current value of bufsiz
-//! can be checked with `cat /sys/module/spidev/parameters/bufsiz`.
+//! can be checked with `cat /sys/module/spidev/parameters/bufsiz`. The limit
+//! can't be changed while spidev is loaded.
+//!
+//! [`Spi::read`], [`Spi::write`] and [`Spi::transfer`] automatically split
+//! larger buffers into chunks of the maximum size, which is read from sysfs
+//! when `Spi` is constructed, and can be retrieved with [`Spi::buffer_size`].
+//! The chunks are transferred directly from and to the provided buffers, without
+//! any intermediate copies in user space. A GPIO Slave Select stays active until
+//! all chunks have been transferred, while the hardware Slave Select is briefly
+//! set to inactive between chunks. Segments passed to [`Spi::transfer_segments`]
+//! aren't split, and their combined length is limited to bufsiz.
 //!
 //! ## Not supported
 //!
@@ -82,7 +92,9 @@
 //! first bit order), SPI_3WIRE (bidirectional mode), SPI_LOOP (loopback mode),
 //! SPI_NO_CS (no Slave Select), SPI_READY (slave ready signal),
 //! SPI_TX_DUAL/SPI_RX_DUAL (dual SPI), SPI_TX_QUAD/SPI_RX_QUAD (quad SPI),
-//! and any number of bits per word other than 8.
+//! and any number of bits per word other than 8. [`SpiConfig`] still passes
+//! the LSB first, 3-wire, loopback and no Slave Select flags on to spidev, which
+//! may be accepted by newer kernels or other SPI controller drivers.
 //!
 //! If your slave device requires SPI_LSB_FIRST, you can use the
 //! [`reverse_bits`] function instead to reverse the bit order in software.
@@ -90,9 +102,40 @@
 //! SPI_LOOP mode can be achieved by connecting the MOSI and MISO pins
 //! together.
 //!
-//! SPI_NO_CS can be implemented by connecting the Slave Select pin on your
-//! slave device to any other available GPIO pin on the Pi, and manually
-//! changing it to high and low as needed.
+//! ## GPIO Slave Select
+//!
+//! If you need more slave devices than the available Slave Select pins, or
+//! SPI_NO_CS isn't supported, connect the Slave Select pin on your slave device to
+//! any other available GPIO pin, and configure it with [`Spi::set_gpio_ss`]. `Spi`
+//! sets the pin to active before every transfer, and inactive when the transfer
+//! completes. The hardware Slave Select pin is still toggled by the SPI controller,
+//! so select one that's not connected to any other device.
+//!
+//! ## Software SPI
+//!
+//! If the hardware SPI buses are already in use, or your slave device is
+//! connected to different pins, [`SoftSpi`] can be used instead. `SoftSpi`
+//! bit-bangs the SPI protocol on any available GPIO pins, at the cost of a
+//! lower maximum clock speed and less accurate timing.
+//!
+//! ## Embedded HAL trait implementations
+//!
+//! When the optional `hal` feature is enabled, [`Spi`] and [`SoftSpi`] implement
+//! the `embedded-hal` 0.2 blocking SPI traits (`Transfer` and `Write`).
+//!
+//! The `hal-1` feature adds implementations of the `embedded-hal` 1.0 traits.
+//! [`Spi`] and [`SoftSpi`] implement `SpiBus`. [`Spi`] also implements
+//! `SpiDevice` using its hardware Slave Select pin, by combining all operations
+//! of a transaction into a single multi-segment transfer, so a transaction
+//! can't exceed spidev's buffer size limit. For devices that use a GPIO pin as
+//! Slave Select, or to share a bus between multiple devices, use
+//! [`ExclusiveDevice`](../hal/struct.ExclusiveDevice.html) or
+//! [`SharedBus`](../hal/struct.SharedBus.html).
+//!
+//! ## WS2812 LEDs
+//!
+//! The [`ws2812`] module uses the MOSI pin to generate the waveform needed to
+//! control WS2812 (NeoPixel) RGB LEDs.
 //!
 //! [`Ss0`]: enum.SlaveSelect.html
 //! [`Ss1`]: enum.SlaveSelect.html
@@ -100,20 +143,47 @@
 //! [`Mode1`]: enum.Mode.html
 //! [`Mode3`]: enum.Mode.html
 //! [`reverse_bits`]: fn.reverse_bits.html
+//! [`SoftSpi`]: struct.SoftSpi.html
+//! [`SpiConfig`]: struct.SpiConfig.html
+//! [`Spi::set_gpio_ss`]: struct.Spi.html#method.set_gpio_ss
+//! [`Spi::read`]: struct.Spi.html#method.read
+//! [`Spi::write`]: struct.Spi.html#method.write
+//! [`Spi::transfer`]: struct.Spi.html#method.transfer
+//! [`Spi::transfer_segments`]: struct.Spi.html#method.transfer_segments
+//! [`Spi::buffer_size`]: struct.Spi.html#method.buffer_size
+//! [`ws2812`]: ws2812/index.html
 
+use std::cell::RefCell;
 use std::fmt;
-use std::fs::{File, OpenOptions};
+use std::fs::{self, File, OpenOptions};
 use std::io;
-use std::io::{Read, Write};
 use std::marker::PhantomData;
 use std::os::unix::io::AsRawFd;
 use std::result;
 
 use quick_error::quick_error;
 
+use crate::gpio::OutputPin;
+
+#[cfg(any(feature = "hal", feature = "hal-1"))]
+mod hal;
 mod ioctl;
+mod soft;
+pub mod ws2812;
 
-pub use self::ioctl::TransferSegment;
+pub use self::ioctl::Segment;
+pub use self::soft::SoftSpi;
+
+// spidev's default buffer size, used when bufsiz can't be read from sysfs
+const DEFAULT_BUFFER_SIZE: usize = 4096;
+
+/// Part of a multi-segment transfer.
+///
+/// `TransferSegment` has been renamed to [`Segment`].
+///
+/// [`Segment`]: struct.Segment.html
+#[deprecated(since = "0.10.0", note = "TransferSegment has been renamed to Segment")]
+pub type TransferSegment<'a, 'b> = Segment<'a, 'b>;
 
 quick_error! {
 /// Errors that can occur when accessing the SPI peripheral.
@@ -144,6 +214,12 @@ quick_error! {
         ModeNotSupported(mode: Mode) { description("mode value not supported") }
 /// The specified Slave Select polarity is not supported.
         PolarityNotSupported(polarity: Polarity) { description("polarity value not supported") }
+/// 3-wire (SISO) mode is not supported.
+        ThreeWireNotSupported { description("3-wire mode not supported") }
+/// Loopback mode is not supported.
+        LoopbackNotSupported { description("loopback mode not supported") }
+/// Disabling Slave Select is not supported.
+        SsDisabledNotSupported { description("disabling Slave Select not supported") }
     }
 }
 
@@ -187,6 +263,7 @@ pub fn reverse_bits(buffer: &mut [u8]) {
 ///
 /// [here]: index.html
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Bus {
     Spi0 = 0,
     Spi1 = 1,
@@ -208,6 +285,7 @@ pub enum Bus {
 ///
 /// [here]: index.html
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SlaveSelect {
     Ss0 = 0,
     Ss1 = 1,
@@ -216,6 +294,7 @@ pub enum SlaveSelect {
 
 /// Slave Select polarities.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Polarity {
     ActiveLow = 0,
     ActiveHigh = 1,
@@ -241,6 +320,7 @@ pub enum Polarity {
 /// [`Spi2`]: enum.Bus.html
 /// [Wikipedia]: https://en.wikipedia.org/wiki/Serial_Peripheral_Interface_Bus#Clock_polarity_and_phase
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
     Mode0 = 0,
     Mode1 = 1,
@@ -265,11 +345,171 @@ pub enum Mode {
 ///
 /// [`reverse_bits`]: fn.reverse_bits.html
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BitOrder {
     MsbFirst = 0,
     LsbFirst = 1,
 }
 
+/// SPI bus configuration.
+///
+/// `SpiConfig` combines the clock speed, mode and optional spidev mode flags,
+/// which are applied in a single step by [`Spi::with_config`]. Several displays
+/// and sensors require the less common 3-wire, LSB first or no Slave Select modes.
+///
+/// Whether a mode flag is supported depends on the SPI controller's driver. If
+/// a flag is rejected, [`Spi::with_config`] returns the appropriate `*NotSupported`
+/// error.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use rppal::spi::{Bus, Mode, SlaveSelect, Spi, SpiConfig};
+///
+/// # fn main() -> rppal::spi::Result<()> {
+/// let config = SpiConfig::new(8_000_000, Mode::Mode0).with_three_wire(true);
+/// let spi = Spi::with_config(Bus::Spi0, SlaveSelect::Ss0, &config)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Spi::with_config`]: struct.Spi.html#method.with_config
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpiConfig {
+    clock_speed: u32,
+    mode: Mode,
+    bit_order: BitOrder,
+    ss_polarity: Polarity,
+    three_wire: bool,
+    loopback: bool,
+    ss_disabled: bool,
+}
+
+impl SpiConfig {
+    /// Constructs a new `SpiConfig` with the specified clock speed in herz (Hz)
+    /// and mode.
+    ///
+    /// The remaining settings default to [`MsbFirst`], an [`ActiveLow`] Slave Select
+    /// polarity, and full-duplex transfers with Slave Select enabled.
+    ///
+    /// [`MsbFirst`]: enum.BitOrder.html
+    /// [`ActiveLow`]: enum.Polarity.html
+    pub fn new(clock_speed: u32, mode: Mode) -> SpiConfig {
+        SpiConfig {
+            clock_speed,
+            mode,
+            bit_order: BitOrder::MsbFirst,
+            ss_polarity: Polarity::ActiveLow,
+            three_wire: false,
+            loopback: false,
+            ss_disabled: false,
+        }
+    }
+
+    /// Sets the bit order.
+    pub fn with_bit_order(mut self, bit_order: BitOrder) -> SpiConfig {
+        self.bit_order = bit_order;
+        self
+    }
+
+    /// Sets the Slave Select polarity.
+    pub fn with_ss_polarity(mut self, polarity: Polarity) -> SpiConfig {
+        self.ss_polarity = polarity;
+        self
+    }
+
+    /// Enables or disables 3-wire mode, where MOSI is used as a bidirectional
+    /// data line (SISO).
+    pub fn with_three_wire(mut self, three_wire: bool) -> SpiConfig {
+        self.three_wire = three_wire;
+        self
+    }
+
+    /// Enables or disables loopback mode, where MISO is internally connected to MOSI.
+    pub fn with_loopback(mut self, loopback: bool) -> SpiConfig {
+        self.loopback = loopback;
+        self
+    }
+
+    /// Disables or enables the Slave Select line, for devices that don't use one
+    /// or share the bus with a single slave.
+    pub fn with_ss_disabled(mut self, ss_disabled: bool) -> SpiConfig {
+        self.ss_disabled = ss_disabled;
+        self
+    }
+
+    /// Returns the clock speed in herz (Hz).
+    pub fn clock_speed(&self) -> u32 {
+        self.clock_speed
+    }
+
+    /// Returns the SPI mode.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Returns the bit order.
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Returns the Slave Select polarity.
+    pub fn ss_polarity(&self) -> Polarity {
+        self.ss_polarity
+    }
+
+    /// Returns `true` if 3-wire mode is enabled.
+    pub fn three_wire(&self) -> bool {
+        self.three_wire
+    }
+
+    /// Returns `true` if loopback mode is enabled.
+    pub fn loopback(&self) -> bool {
+        self.loopback
+    }
+
+    /// Returns `true` if the Slave Select line is disabled.
+    pub fn ss_disabled(&self) -> bool {
+        self.ss_disabled
+    }
+}
+
+// A GPIO pin that's used as a software-controlled Slave Select. The pin is wrapped
+// in a RefCell, because Spi's transfer methods only borrow &self. Spi is !Sync, so
+// the pin is never accessed from multiple threads at the same time.
+#[derive(Debug)]
+struct GpioSs {
+    pin: RefCell<OutputPin>,
+    polarity: Polarity,
+}
+
+impl GpioSs {
+    fn set_active(&self, active: bool) {
+        let mut pin = self.pin.borrow_mut();
+
+        if active == (self.polarity == Polarity::ActiveHigh) {
+            pin.set_high();
+        } else {
+            pin.set_low();
+        }
+    }
+
+    // Sets Slave Select to active while f is running
+    fn select<T, F: FnOnce() -> T>(gpio_ss: &Option<GpioSs>, f: F) -> T {
+        match gpio_ss {
+            Some(gpio_ss) => {
+                gpio_ss.set_active(true);
+                let result = f();
+                gpio_ss.set_active(false);
+
+                result
+            }
+            None => f(),
+        }
+    }
+}
+
 /// Provides access to the Raspberry Pi's SPI peripherals.
 ///
 /// Before using `Spi`, make sure your Raspberry Pi has the necessary SPI buses
@@ -278,6 +518,10 @@ pub enum BitOrder {
 /// [here]: index.html
 pub struct Spi {
     spidev: File,
+    bus: Bus,
+    slave_select: SlaveSelect,
+    gpio_ss: Option<GpioSs>,
+    buffer_size: usize,
     // The not_sync field is a workaround to force !Sync. Spi isn't safe for
     // Sync because of ioctl() and the underlying drivers. This avoids needing
     // #![feature(optin_builtin_traits)] to manually add impl !Sync for Spi.
@@ -295,6 +539,17 @@ impl Spi {
     ///
     /// `mode` selects the clock polarity and phase.
     pub fn new(bus: Bus, slave_select: SlaveSelect, clock_speed: u32, mode: Mode) -> Result<Spi> {
+        Spi::with_config(bus, slave_select, &SpiConfig::new(clock_speed, mode))
+    }
+
+    /// Constructs a new `Spi` with the settings specified in `config`.
+    ///
+    /// `bus` and `slave_select` specify the selected SPI bus and one of its
+    /// associated Slave Select pins. More information on the available settings
+    /// can be found at [`SpiConfig`].
+    ///
+    /// [`SpiConfig`]: struct.SpiConfig.html
+    pub fn with_config(bus: Bus, slave_select: SlaveSelect, config: &SpiConfig) -> Result<Spi> {
         // The following options currently aren't supported by spidev in Raspbian Stretch on the Pi:
         //
         // LSB_FIRST - ioctl() returns EINVAL when set
@@ -304,6 +559,11 @@ impl Spi {
         // READY - ioctl() returns EINVAL when set
         // TX_DUAL/TX_QUAD/RX_DUAL/RX_QUAD - Not supported by BCM283x
         // bits per word - any value other than 0 or 8 returns EINVAL when set
+        //
+        // Newer kernels and other SPI controller drivers may accept some of these flags,
+        // so SpiConfig passes them on, and reports an error if they're rejected.
+
+        let mode = config.mode;
 
         let spidev = OpenOptions::new()
             .read(true)
@@ -321,16 +581,66 @@ impl Spi {
 
         let spi = Spi {
             spidev,
+            bus,
+            slave_select,
+            gpio_ss: None,
+            buffer_size: buffer_size(),
             not_sync: PhantomData,
         };
 
         // Set defaults and user-specified settings
         spi.set_bits_per_word(8)?;
-        spi.set_clock_speed(clock_speed)?;
+        spi.set_clock_speed(config.clock_speed)?;
+
+        // Only touch the optional mode flags if they differ from the defaults,
+        // so drivers that reject them can still be used with the default settings
+        if config.bit_order != BitOrder::MsbFirst {
+            spi.set_bit_order(config.bit_order)?;
+        }
+
+        if config.ss_polarity != Polarity::ActiveLow {
+            spi.set_ss_polarity(config.ss_polarity)?;
+        }
+
+        if config.three_wire {
+            spi.set_three_wire(true)?;
+        }
+
+        if config.loopback {
+            spi.set_loopback(true)?;
+        }
+
+        if config.ss_disabled {
+            spi.set_ss_disabled(true)?;
+        }
 
         Ok(spi)
     }
 
+    /// Returns the selected SPI bus.
+    pub fn bus(&self) -> Bus {
+        self.bus
+    }
+
+    /// Returns the selected Slave Select pin.
+    pub fn slave_select(&self) -> SlaveSelect {
+        self.slave_select
+    }
+
+    /// Returns the maximum number of bytes spidev can handle in a single
+    /// transfer.
+    ///
+    /// Larger buffers passed to [`read`], [`write`] and [`transfer`] are split
+    /// into chunks of this size. More information can be found [here].
+    ///
+    /// [`read`]: #method.read
+    /// [`write`]: #method.write
+    /// [`transfer`]: #method.transfer
+    /// [here]: index.html#buffer-size-limits
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
     /// Gets the bit order.
     pub fn bit_order(&self) -> Result<BitOrder> {
         let mut bit_order: u8 = 0;
@@ -476,6 +786,119 @@ impl Spi {
         }
     }
 
+    /// Returns `true` if 3-wire mode is enabled.
+    pub fn three_wire(&self) -> Result<bool> {
+        self.mode_flag(ioctl::MODE_3WIRE)
+    }
+
+    /// Enables or disables 3-wire mode.
+    ///
+    /// In 3-wire mode, MOSI is used as a bidirectional data line (SISO), and the
+    /// direction depends on whether data is read or written. Use [`read`] and
+    /// [`write`], or half-duplex [`Segment`]s, as full-duplex transfers aren't
+    /// possible.
+    ///
+    /// By default, 3-wire mode is disabled.
+    ///
+    /// [`read`]: #method.read
+    /// [`write`]: #method.write
+    /// [`Segment`]: struct.Segment.html
+    pub fn set_three_wire(&self, three_wire: bool) -> Result<()> {
+        self.set_mode_flag(ioctl::MODE_3WIRE, three_wire, Error::ThreeWireNotSupported)
+    }
+
+    /// Returns `true` if loopback mode is enabled.
+    pub fn loopback(&self) -> Result<bool> {
+        self.mode_flag(ioctl::MODE_LOOP)
+    }
+
+    /// Enables or disables loopback mode.
+    ///
+    /// In loopback mode, MISO is internally connected to MOSI, which can be
+    /// used for testing.
+    ///
+    /// By default, loopback mode is disabled.
+    pub fn set_loopback(&self, loopback: bool) -> Result<()> {
+        self.set_mode_flag(ioctl::MODE_LOOP, loopback, Error::LoopbackNotSupported)
+    }
+
+    /// Returns `true` if the Slave Select line is disabled.
+    pub fn ss_disabled(&self) -> Result<bool> {
+        self.mode_flag(ioctl::MODE_NO_CS)
+    }
+
+    /// Disables or enables the Slave Select line.
+    ///
+    /// When disabled, Slave Select isn't set to active during transfers. Some SPI
+    /// controller drivers ignore this flag.
+    ///
+    /// By default, Slave Select is enabled.
+    pub fn set_ss_disabled(&self, ss_disabled: bool) -> Result<()> {
+        self.set_mode_flag(
+            ioctl::MODE_NO_CS,
+            ss_disabled,
+            Error::SsDisabledNotSupported,
+        )
+    }
+
+    fn mode_flag(&self, flag: u8) -> Result<bool> {
+        let mut mode: u8 = 0;
+        ioctl::mode(self.spidev.as_raw_fd(), &mut mode)?;
+
+        Ok((mode & flag) != 0)
+    }
+
+    fn set_mode_flag(&self, flag: u8, enabled: bool, not_supported: Error) -> Result<()> {
+        let mut new_mode: u8 = 0;
+        ioctl::mode(self.spidev.as_raw_fd(), &mut new_mode)?;
+
+        if enabled {
+            new_mode |= flag;
+        } else {
+            new_mode &= !flag;
+        }
+
+        match ioctl::set_mode(self.spidev.as_raw_fd(), new_mode) {
+            Ok(_) => Ok(()),
+            Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => Err(not_supported),
+            Err(e) => Err(Error::Io(e)),
+        }
+    }
+
+    /// Configures a GPIO pin as a software-controlled Slave Select.
+    ///
+    /// `pin` is set to inactive immediately, set to active at the start of every
+    /// read, write and transfer, and set to inactive when it completes. A
+    /// multi-segment transfer through [`transfer_segments`] keeps the pin active
+    /// until all segments have been transferred.
+    ///
+    /// `polarity` determines the pin's active logic level. It's independent of the
+    /// hardware Slave Select polarity configured through [`set_ss_polarity`]. Any
+    /// previously configured GPIO Slave Select pin is dropped. More information can
+    /// be found [here].
+    ///
+    /// [`transfer_segments`]: #method.transfer_segments
+    /// [`set_ss_polarity`]: #method.set_ss_polarity
+    /// [here]: index.html#gpio-slave-select
+    pub fn set_gpio_ss(&mut self, pin: OutputPin, polarity: Polarity) {
+        let gpio_ss = GpioSs {
+            pin: RefCell::new(pin),
+            polarity,
+        };
+
+        gpio_ss.set_active(false);
+
+        self.gpio_ss = Some(gpio_ss);
+    }
+
+    /// Stops using the GPIO Slave Select pin configured through [`set_gpio_ss`],
+    /// and returns it.
+    ///
+    /// [`set_gpio_ss`]: #method.set_gpio_ss
+    pub fn clear_gpio_ss(&mut self) -> Option<OutputPin> {
+        self.gpio_ss.take().map(|gpio_ss| gpio_ss.pin.into_inner())
+    }
+
     /// Receives incoming data from the slave device and writes it to `buffer`.
     ///
     /// The SPI protocol doesn't indicate how much incoming data is waiting,
@@ -486,11 +909,25 @@ impl Spi {
     /// line.
     ///
     /// Slave Select is set to active at the start of the read, and inactive
-    /// when the read completes.
+    /// when the read completes. Buffers that exceed spidev's buffer size limit
+    /// are split into multiple transfers. More information can be found [here].
     ///
     /// Returns how many bytes were read.
+    ///
+    /// [here]: index.html#buffer-size-limits
     pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
-        Ok(self.spidev.read(buffer)?)
+        let fd = self.spidev.as_raw_fd();
+        let chunk_size = self.buffer_size;
+
+        GpioSs::select(&self.gpio_ss, || -> io::Result<()> {
+            for chunk in buffer.chunks_mut(chunk_size) {
+                ioctl::transfer(fd, &[Segment::with_read(chunk)])?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(buffer.len())
     }
 
     /// Sends the outgoing data contained in `buffer` to the slave device.
@@ -498,11 +935,25 @@ impl Spi {
     /// Any data received on the MISO line from the slave is ignored.
     ///
     /// Slave Select is set to active at the start of the write, and inactive
-    /// when the write completes.
+    /// when the write completes. Buffers that exceed spidev's buffer size limit
+    /// are split into multiple transfers. More information can be found [here].
     ///
     /// Returns how many bytes were written.
+    ///
+    /// [here]: index.html#buffer-size-limits
     pub fn write(&mut self, buffer: &[u8]) -> Result<usize> {
-        Ok(self.spidev.write(buffer)?)
+        let fd = self.spidev.as_raw_fd();
+        let chunk_size = self.buffer_size;
+
+        GpioSs::select(&self.gpio_ss, || -> io::Result<()> {
+            for chunk in buffer.chunks(chunk_size) {
+                ioctl::transfer(fd, &[Segment::with_write(chunk)])?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(buffer.len())
     }
 
     /// Sends and receives data at the same time.
@@ -516,39 +967,74 @@ impl Spi {
     /// transfer as many bytes as the shortest of the two buffers contains.
     ///
     /// Slave Select is set to active at the start of the transfer, and inactive
-    /// when the transfer completes.
+    /// when the transfer completes. Buffers that exceed spidev's buffer size
+    /// limit are split into multiple transfers. More information can be found
+    /// [here].
     ///
     /// Returns how many bytes were transferred.
+    ///
+    /// [here]: index.html#buffer-size-limits
     pub fn transfer(&self, read_buffer: &mut [u8], write_buffer: &[u8]) -> Result<usize> {
-        let segment = TransferSegment::new(Some(read_buffer), Some(write_buffer));
+        let fd = self.spidev.as_raw_fd();
+        let len = read_buffer.len().min(write_buffer.len());
 
-        ioctl::transfer(self.spidev.as_raw_fd(), &[segment])?;
+        GpioSs::select(&self.gpio_ss, || -> io::Result<()> {
+            for (read_chunk, write_chunk) in read_buffer[..len]
+                .chunks_mut(self.buffer_size)
+                .zip(write_buffer[..len].chunks(self.buffer_size))
+            {
+                ioctl::transfer(fd, &[Segment::with_transfer(read_chunk, write_chunk)])?;
+            }
 
-        Ok(segment.len())
+            Ok(())
+        })?;
+
+        Ok(len)
     }
 
     /// Transfers multiple half-duplex or full-duplex segments.
     ///
     /// `transfer_segments` transfers multiple segments in
-    /// a single call. Each [`TransferSegment`] contains a reference
+    /// a single call. Each [`Segment`] contains a reference
     /// to a read buffer, a write buffer, or both, and optional
     /// settings that override the SPI bus settings for that
     /// specific segment.
     ///
-    /// [`TransferSegment`]: struct.TransferSegment.html
-    pub fn transfer_segments(&self, segments: &[TransferSegment<'_, '_>]) -> Result<()> {
-        ioctl::transfer(self.spidev.as_raw_fd(), segments)?;
+    /// Segments aren't split into chunks, so their combined length can't exceed
+    /// spidev's buffer size limit. More information can be found [here].
+    ///
+    /// [`Segment`]: struct.Segment.html
+    /// [here]: index.html#buffer-size-limits
+    pub fn transfer_segments(&self, segments: &[Segment<'_, '_>]) -> Result<()> {
+        GpioSs::select(&self.gpio_ss, || {
+            ioctl::transfer(self.spidev.as_raw_fd(), segments)
+        })?;
 
         Ok(())
     }
 }
 
+// Reads spidev's buffer size limit from sysfs
+fn buffer_size() -> usize {
+    fs::read_to_string("/sys/module/spidev/parameters/bufsiz")
+        .ok()
+        .and_then(|bufsiz| bufsiz.trim().parse().ok())
+        .filter(|&bufsiz| bufsiz > 0)
+        .unwrap_or(DEFAULT_BUFFER_SIZE)
+}
+
 // Send is safe for Spi, but we're marked !Send because of the dummy pointer that's
 // needed to force !Sync.
 unsafe impl Send for Spi {}
 
 impl fmt::Debug for Spi {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Spi").field("spidev", &self.spidev).finish()
+        f.debug_struct("Spi")
+            .field("spidev", &self.spidev)
+            .field("bus", &self.bus)
+            .field("slave_select", &self.slave_select)
+            .field("gpio_ss", &self.gpio_ss)
+            .field("buffer_size", &self.buffer_size)
+            .finish()
     }
 }
@@ -0,0 +1,243 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::convert::Infallible;
+
+#[cfg(feature = "hal")]
+use embedded_hal::blocking::spi;
+#[cfg(feature = "hal-1")]
+use embedded_hal_1::spi as spi_1;
+
+#[cfg(feature = "hal-1")]
+use crate::spi::Segment;
+use crate::spi::{Error, SoftSpi, Spi};
+
+#[cfg(feature = "hal")]
+impl spi::Transfer<u8> for Spi {
+    type Error = Error;
+
+    fn transfer<'a>(&mut self, buffer: &'a mut [u8]) -> Result<&'a [u8], Self::Error> {
+        let write_buffer = buffer.to_vec();
+        Spi::transfer(self, buffer, &write_buffer)?;
+
+        Ok(buffer)
+    }
+}
+
+#[cfg(feature = "hal")]
+impl spi::Write<u8> for Spi {
+    type Error = Error;
+
+    fn write(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+        Spi::write(self, buffer)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl spi::Transfer<u8> for SoftSpi {
+    type Error = Infallible;
+
+    fn transfer<'a>(&mut self, buffer: &'a mut [u8]) -> Result<&'a [u8], Self::Error> {
+        let write_buffer = buffer.to_vec();
+        SoftSpi::transfer(self, buffer, &write_buffer);
+
+        Ok(buffer)
+    }
+}
+
+#[cfg(feature = "hal")]
+impl spi::Write<u8> for SoftSpi {
+    type Error = Infallible;
+
+    fn write(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+        SoftSpi::write(self, buffer);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl spi_1::Error for Error {
+    fn kind(&self) -> spi_1::ErrorKind {
+        spi_1::ErrorKind::Other
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl spi_1::ErrorType for Spi {
+    type Error = Error;
+}
+
+#[cfg(feature = "hal-1")]
+impl spi_1::SpiBus<u8> for Spi {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        Spi::read(self, words)?;
+
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        Spi::write(self, words)?;
+
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        if read.len() == write.len() {
+            Spi::transfer(self, read, write)?;
+        } else {
+            // embedded-hal expects the shorter buffer to be padded, so the
+            // transfer length matches the longest of the two buffers.
+            let len = read.len().max(write.len());
+            let mut read_buffer = vec![0u8; len];
+            let mut write_buffer = vec![0u8; len];
+            write_buffer[..write.len()].copy_from_slice(write);
+
+            Spi::transfer(self, &mut read_buffer, &write_buffer)?;
+
+            read.copy_from_slice(&read_buffer[..read.len()]);
+        }
+
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let write_buffer = words.to_vec();
+        Spi::transfer(self, words, &write_buffer)?;
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+// The maximum delay in microseconds (µs) that fits in a single segment
+#[cfg(feature = "hal-1")]
+const MAX_SEGMENT_DELAY: u32 = u16::MAX as u32;
+
+#[cfg(feature = "hal-1")]
+impl spi_1::SpiDevice<u8> for Spi {
+    fn transaction(
+        &mut self,
+        operations: &mut [spi_1::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        // TransferInPlace needs a separate copy of the outgoing data, which has
+        // to outlive the segments that reference it.
+        let in_place_buffers: Vec<Vec<u8>> = operations
+            .iter()
+            .filter_map(|operation| match operation {
+                spi_1::Operation::TransferInPlace(words) => Some(words.to_vec()),
+                _ => None,
+            })
+            .collect();
+        let mut in_place_buffers = in_place_buffers.iter();
+
+        let mut segments: Vec<Segment<'_, '_>> = Vec::with_capacity(operations.len());
+
+        for operation in operations.iter_mut() {
+            match operation {
+                spi_1::Operation::Read(words) => segments.push(Segment::with_read(words)),
+                spi_1::Operation::Write(words) => segments.push(Segment::with_write(words)),
+                spi_1::Operation::Transfer(read, write) => {
+                    let len = read.len().min(write.len());
+                    let (read, read_remainder) = read.split_at_mut(len);
+                    let (write, write_remainder) = write.split_at(len);
+
+                    segments.push(Segment::with_transfer(read, write));
+
+                    // Pad the shorter buffer by adding a half-duplex segment
+                    // for the remaining bytes.
+                    if !read_remainder.is_empty() {
+                        segments.push(Segment::with_read(read_remainder));
+                    } else if !write_remainder.is_empty() {
+                        segments.push(Segment::with_write(write_remainder));
+                    }
+                }
+                spi_1::Operation::TransferInPlace(words) => {
+                    if let Some(write_buffer) = in_place_buffers.next() {
+                        segments.push(Segment::with_transfer(words, write_buffer));
+                    }
+                }
+                spi_1::Operation::DelayNs(ns) => {
+                    // Delays are added as empty segments, rounded up to the
+                    // nearest microsecond.
+                    let mut delay = ns.div_ceil(1000);
+                    while delay > 0 {
+                        let mut segment = Segment::new(None, None);
+                        segment.set_delay(delay.min(MAX_SEGMENT_DELAY) as u16);
+                        segments.push(segment);
+
+                        delay = delay.saturating_sub(MAX_SEGMENT_DELAY);
+                    }
+                }
+            }
+        }
+
+        self.transfer_segments(&segments)
+    }
+}
+
+#[cfg(feature = "hal-1")]
+impl spi_1::ErrorType for SoftSpi {
+    type Error = Infallible;
+}
+
+#[cfg(feature = "hal-1")]
+impl spi_1::SpiBus<u8> for SoftSpi {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        SoftSpi::read(self, words);
+
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        SoftSpi::write(self, words);
+
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        let len = read.len().max(write.len());
+        let mut read_buffer = vec![0u8; len];
+        let mut write_buffer = vec![0u8; len];
+        write_buffer[..write.len()].copy_from_slice(write);
+
+        SoftSpi::transfer(self, &mut read_buffer, &write_buffer);
+
+        read.copy_from_slice(&read_buffer[..read.len()]);
+
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let write_buffer = words.to_vec();
+        SoftSpi::transfer(self, words, &write_buffer);
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
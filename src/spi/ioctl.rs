@@ -40,9 +40,9 @@ const SIZEBITS: u8 = 14;
 const DIRBITS: u8 = 2;
 
 const NRSHIFT: u8 = 0;
-const TYPESHIFT: u8 = (NRSHIFT + NRBITS);
-const SIZESHIFT: u8 = (TYPESHIFT + TYPEBITS);
-const DIRSHIFT: u8 = (SIZESHIFT + SIZEBITS);
+const TYPESHIFT: u8 = NRSHIFT + NRBITS;
+const SIZESHIFT: u8 = TYPESHIFT + TYPEBITS;
+const DIRSHIFT: u8 = SIZESHIFT + SIZEBITS;
 
 const NR_MESSAGE: IoctlLong = 0 << NRSHIFT;
 const NR_MODE: IoctlLong = 1 << NRSHIFT;
@@ -60,18 +60,18 @@ const DIR_NONE: IoctlLong = 0;
 const DIR_WRITE: IoctlLong = 1 << DIRSHIFT;
 const DIR_READ: IoctlLong = 2 << DIRSHIFT;
 
-const REQ_RD_MODE: IoctlLong = (DIR_READ | TYPE_SPI | NR_MODE | SIZE_U8);
-const REQ_RD_LSB_FIRST: IoctlLong = (DIR_READ | TYPE_SPI | NR_LSB_FIRST | SIZE_U8);
-const REQ_RD_BITS_PER_WORD: IoctlLong = (DIR_READ | TYPE_SPI | NR_BITS_PER_WORD | SIZE_U8);
-const REQ_RD_MAX_SPEED_HZ: IoctlLong = (DIR_READ | TYPE_SPI | NR_MAX_SPEED_HZ | SIZE_U32);
-const REQ_RD_MODE_32: IoctlLong = (DIR_READ | TYPE_SPI | NR_MODE32 | SIZE_U32);
+const REQ_RD_MODE: IoctlLong = DIR_READ | TYPE_SPI | NR_MODE | SIZE_U8;
+const REQ_RD_LSB_FIRST: IoctlLong = DIR_READ | TYPE_SPI | NR_LSB_FIRST | SIZE_U8;
+const REQ_RD_BITS_PER_WORD: IoctlLong = DIR_READ | TYPE_SPI | NR_BITS_PER_WORD | SIZE_U8;
+const REQ_RD_MAX_SPEED_HZ: IoctlLong = DIR_READ | TYPE_SPI | NR_MAX_SPEED_HZ | SIZE_U32;
+const REQ_RD_MODE_32: IoctlLong = DIR_READ | TYPE_SPI | NR_MODE32 | SIZE_U32;
 
-const REQ_WR_MESSAGE: IoctlLong = (DIR_WRITE | TYPE_SPI | NR_MESSAGE);
-const REQ_WR_MODE: IoctlLong = (DIR_WRITE | TYPE_SPI | NR_MODE | SIZE_U8);
-const REQ_WR_LSB_FIRST: IoctlLong = (DIR_WRITE | TYPE_SPI | NR_LSB_FIRST | SIZE_U8);
-const REQ_WR_BITS_PER_WORD: IoctlLong = (DIR_WRITE | TYPE_SPI | NR_BITS_PER_WORD | SIZE_U8);
-const REQ_WR_MAX_SPEED_HZ: IoctlLong = (DIR_WRITE | TYPE_SPI | NR_MAX_SPEED_HZ | SIZE_U32);
-const REQ_WR_MODE_32: IoctlLong = (DIR_WRITE | TYPE_SPI | NR_MODE32 | SIZE_U32);
+const REQ_WR_MESSAGE: IoctlLong = DIR_WRITE | TYPE_SPI | NR_MESSAGE;
+const REQ_WR_MODE: IoctlLong = DIR_WRITE | TYPE_SPI | NR_MODE | SIZE_U8;
+const REQ_WR_LSB_FIRST: IoctlLong = DIR_WRITE | TYPE_SPI | NR_LSB_FIRST | SIZE_U8;
+const REQ_WR_BITS_PER_WORD: IoctlLong = DIR_WRITE | TYPE_SPI | NR_BITS_PER_WORD | SIZE_U8;
+const REQ_WR_MAX_SPEED_HZ: IoctlLong = DIR_WRITE | TYPE_SPI | NR_MAX_SPEED_HZ | SIZE_U32;
+const REQ_WR_MODE_32: IoctlLong = DIR_WRITE | TYPE_SPI | NR_MODE32 | SIZE_U32;
 
 pub const MODE_CPHA: u8 = 0x01;
 pub const MODE_CPOL: u8 = 0x02;
@@ -94,12 +94,16 @@ pub const MODE_RX_QUAD: u32 = 0x0800; // Receive on 4 incoming lines
 
 /// Part of a multi-segment transfer.
 ///
-/// `TransferSegment`s are transferred using the [`transfer_segments`] method.
+/// `Segment`s are transferred using the [`transfer_segments`] method, which maps to
+/// a single `SPI_IOC_MESSAGE` request. All segments are transferred as one atomic
+/// operation, so no other transfers can occur on the bus in between. Each segment
+/// can override the clock speed and bits per word, and control the delay and Slave
+/// Select behavior before the next segment.
 ///
 /// [`transfer_segments`]: struct.Spi.html#method.transfer_segments
 #[derive(PartialEq, Copy, Clone)]
 #[repr(C)]
-pub struct TransferSegment<'a, 'b> {
+pub struct Segment<'a, 'b> {
     // Pointer to write buffer, or 0.
     tx_buf: u64,
     // Pointer to read buffer, or 0.
@@ -126,8 +130,8 @@ pub struct TransferSegment<'a, 'b> {
     write_buffer_lifetime: marker::PhantomData<&'b [u8]>,
 }
 
-impl<'a, 'b> TransferSegment<'a, 'b> {
-    /// Constructs a new `TransferSegment` with the default settings.
+impl<'a, 'b> Segment<'a, 'b> {
+    /// Constructs a new `Segment` with the default settings.
     ///
     /// If `read_buffer` is set to `None`, any incoming data is discarded.
     ///
@@ -145,11 +149,29 @@ impl<'a, 'b> TransferSegment<'a, 'b> {
     pub fn new(
         read_buffer: Option<&'a mut [u8]>,
         write_buffer: Option<&'b [u8]>,
-    ) -> TransferSegment<'a, 'b> {
-        TransferSegment::with_settings(read_buffer, write_buffer, 0, 0, 0, false)
+    ) -> Segment<'a, 'b> {
+        Segment::with_settings(read_buffer, write_buffer, 0, 0, 0, false)
     }
 
-    /// Constructs a new `TransferSegment` with the specified settings.
+    /// Constructs a new `Segment` with the default settings, and configures it
+    /// for a half-duplex read.
+    pub fn with_read(read_buffer: &'a mut [u8]) -> Segment<'a, 'b> {
+        Segment::new(Some(read_buffer), None)
+    }
+
+    /// Constructs a new `Segment` with the default settings, and configures it
+    /// for a half-duplex write.
+    pub fn with_write(write_buffer: &'b [u8]) -> Segment<'a, 'b> {
+        Segment::new(None, Some(write_buffer))
+    }
+
+    /// Constructs a new `Segment` with the default settings, and configures it
+    /// for a full-duplex transfer.
+    pub fn with_transfer(read_buffer: &'a mut [u8], write_buffer: &'b [u8]) -> Segment<'a, 'b> {
+        Segment::new(Some(read_buffer), Some(write_buffer))
+    }
+
+    /// Constructs a new `Segment` with the specified settings.
     ///
     /// These settings override the values set for [`Spi`], and are only used
     /// for this specific segment.
@@ -179,7 +201,7 @@ impl<'a, 'b> TransferSegment<'a, 'b> {
         delay: u16,
         bits_per_word: u8,
         ss_change: bool,
-    ) -> TransferSegment<'a, 'b> {
+    ) -> Segment<'a, 'b> {
         // Len will contain the length of the shortest of the supplied buffers
         let mut len: u32 = 0;
 
@@ -199,7 +221,7 @@ impl<'a, 'b> TransferSegment<'a, 'b> {
             0
         };
 
-        TransferSegment {
+        Segment {
             tx_buf,
             rx_buf,
             len,
@@ -298,9 +320,9 @@ impl<'a, 'b> TransferSegment<'a, 'b> {
     }
 }
 
-impl<'a, 'b> fmt::Debug for TransferSegment<'a, 'b> {
+impl<'a, 'b> fmt::Debug for Segment<'a, 'b> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("TransferSegment")
+        f.debug_struct("Segment")
             .field("tx_buf", &self.tx_buf)
             .field("rx_buf", &self.rx_buf)
             .field("len", &self.len)
@@ -355,12 +377,12 @@ pub fn set_mode32(fd: c_int, value: u32) -> Result<i32> {
     parse_retval!(unsafe { ioctl(fd, REQ_WR_MODE_32, &value) })
 }
 
-pub fn transfer(fd: c_int, segments: &[TransferSegment<'_, '_>]) -> Result<i32> {
+pub fn transfer(fd: c_int, segments: &[Segment<'_, '_>]) -> Result<i32> {
     parse_retval!(unsafe {
         ioctl(
             fd,
             REQ_WR_MESSAGE
-                | (((segments.len() * size_of::<TransferSegment<'_, '_>>()) as IoctlLong)
+                | ((std::mem::size_of_val(segments) as IoctlLong)
                     << SIZESHIFT),
             segments,
         )
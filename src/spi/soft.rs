@@ -0,0 +1,323 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::delay::{delay_until_ns, get_time_ns};
+use crate::gpio::{InputPin, Level, OutputPin};
+use crate::spi::{BitOrder, Error, Mode, Polarity, Result};
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// Software-based SPI master.
+///
+/// `SoftSpi` emulates an SPI master by bit-banging any available GPIO pins.
+/// It can be used when the hardware SPI buses are already in use, or when your
+/// slave device is connected to pins that aren't associated with any of the SPI
+/// peripherals.
+///
+/// Unlike [`Spi`], `SoftSpi` supports the [`LsbFirst`] bit order, and doesn't require
+/// the MOSI, MISO or Slave Select pins to be configured. When no MISO pin is
+/// configured, any incoming data is read as zeroes.
+///
+/// The clock speed is an upper limit. Timing is controlled by sleeping and busy
+/// waiting on the current thread, so the actual clock speed may be lower, and
+/// exact timing can't be guaranteed. Most slave devices don't depend on a fixed
+/// clock speed, as all data is sampled relative to the clock edges.
+///
+/// [`Spi`]: struct.Spi.html
+/// [`LsbFirst`]: enum.BitOrder.html
+#[derive(Debug)]
+pub struct SoftSpi {
+    sclk: OutputPin,
+    mosi: Option<OutputPin>,
+    miso: Option<InputPin>,
+    ss: Option<OutputPin>,
+    mode: Mode,
+    bit_order: BitOrder,
+    ss_polarity: Polarity,
+    clock_speed: u32,
+    half_period_ns: i64,
+}
+
+impl SoftSpi {
+    /// Constructs a new `SoftSpi`.
+    ///
+    /// `sclk` is used as the clock pin. `mosi`, `miso` and `ss` are optional, and
+    /// can be set to `None` for write-only or read-only devices, or for devices
+    /// that don't use a Slave Select pin.
+    ///
+    /// `clock_speed` defines the maximum clock frequency in hertz (Hz).
+    ///
+    /// `mode` selects the clock polarity and phase.
+    ///
+    /// By default, data is transferred [`MsbFirst`], and Slave Select is
+    /// [`ActiveLow`].
+    ///
+    /// [`MsbFirst`]: enum.BitOrder.html
+    /// [`ActiveLow`]: enum.Polarity.html
+    pub fn new(
+        sclk: OutputPin,
+        mosi: Option<OutputPin>,
+        miso: Option<InputPin>,
+        ss: Option<OutputPin>,
+        clock_speed: u32,
+        mode: Mode,
+    ) -> Result<SoftSpi> {
+        let mut soft_spi = SoftSpi {
+            sclk,
+            mosi,
+            miso,
+            ss,
+            mode,
+            bit_order: BitOrder::MsbFirst,
+            ss_polarity: Polarity::ActiveLow,
+            clock_speed: 0,
+            half_period_ns: 0,
+        };
+
+        soft_spi.set_clock_speed(clock_speed)?;
+        soft_spi.sclk.write(soft_spi.idle_level());
+        soft_spi.deselect();
+
+        Ok(soft_spi)
+    }
+
+    /// Gets the bit order.
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Sets the order in which bits are shifted out and in.
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) {
+        self.bit_order = bit_order;
+    }
+
+    /// Gets the maximum clock frequency in hertz (Hz).
+    pub fn clock_speed(&self) -> u32 {
+        self.clock_speed
+    }
+
+    /// Sets the maximum clock frequency in hertz (Hz).
+    ///
+    /// Returns `Err(ClockSpeedNotSupported)` if `clock_speed` is set to 0.
+    pub fn set_clock_speed(&mut self, clock_speed: u32) -> Result<()> {
+        if clock_speed == 0 {
+            return Err(Error::ClockSpeedNotSupported(clock_speed));
+        }
+
+        self.clock_speed = clock_speed;
+        self.half_period_ns = NANOS_PER_SEC / (2 * i64::from(clock_speed));
+
+        Ok(())
+    }
+
+    /// Gets the SPI mode.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Sets the SPI mode, and changes the clock pin to the new idle level.
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.sclk.write(self.idle_level());
+    }
+
+    /// Gets the Slave Select polarity.
+    pub fn ss_polarity(&self) -> Polarity {
+        self.ss_polarity
+    }
+
+    /// Sets Slave Select polarity, and changes the Slave Select pin to the new
+    /// inactive level.
+    pub fn set_ss_polarity(&mut self, polarity: Polarity) {
+        self.ss_polarity = polarity;
+        self.deselect();
+    }
+
+    /// Receives incoming data from the slave device and writes it to `buffer`.
+    ///
+    /// The MOSI pin is kept low while data is shifted in.
+    ///
+    /// Slave Select is set to active at the start of the read, and inactive
+    /// when the read completes.
+    ///
+    /// Returns how many bytes were read.
+    pub fn read(&mut self, buffer: &mut [u8]) -> usize {
+        self.select();
+        for byte in buffer.iter_mut() {
+            *byte = self.transfer_byte(0);
+        }
+        self.deselect();
+
+        buffer.len()
+    }
+
+    /// Sends the outgoing data contained in `buffer` to the slave device.
+    ///
+    /// Any data received on the MISO pin is discarded.
+    ///
+    /// Slave Select is set to active at the start of the write, and inactive
+    /// when the write completes.
+    ///
+    /// Returns how many bytes were written.
+    pub fn write(&mut self, buffer: &[u8]) -> usize {
+        self.select();
+        for &byte in buffer {
+            self.transfer_byte(byte);
+        }
+        self.deselect();
+
+        buffer.len()
+    }
+
+    /// Sends and receives data at the same time.
+    ///
+    /// `transfer` stores the incoming data in `read_buffer`, and sends the
+    /// outgoing data contained in `write_buffer`. Because data is sent and
+    /// received simultaneously, `transfer` can only transfer as many bytes as
+    /// the shortest of the two buffers contains.
+    ///
+    /// Slave Select is set to active at the start of the transfer, and inactive
+    /// when the transfer completes.
+    ///
+    /// Returns how many bytes were transferred.
+    pub fn transfer(&mut self, read_buffer: &mut [u8], write_buffer: &[u8]) -> usize {
+        self.select();
+        for (read_byte, &write_byte) in read_buffer.iter_mut().zip(write_buffer) {
+            *read_byte = self.transfer_byte(write_byte);
+        }
+        self.deselect();
+
+        read_buffer.len().min(write_buffer.len())
+    }
+
+    /// Consumes the `SoftSpi`, and returns the SCLK, MOSI, MISO and Slave Select
+    /// pins.
+    pub fn into_pins(
+        self,
+    ) -> (
+        OutputPin,
+        Option<OutputPin>,
+        Option<InputPin>,
+        Option<OutputPin>,
+    ) {
+        (self.sclk, self.mosi, self.miso, self.ss)
+    }
+
+    fn idle_level(&self) -> Level {
+        match self.mode {
+            Mode::Mode0 | Mode::Mode1 => Level::Low,
+            Mode::Mode2 | Mode::Mode3 => Level::High,
+        }
+    }
+
+    fn active_level(&self) -> Level {
+        match self.mode {
+            Mode::Mode0 | Mode::Mode1 => Level::High,
+            Mode::Mode2 | Mode::Mode3 => Level::Low,
+        }
+    }
+
+    fn select(&mut self) {
+        let level = match self.ss_polarity {
+            Polarity::ActiveLow => Level::Low,
+            Polarity::ActiveHigh => Level::High,
+        };
+
+        if let Some(ref mut ss) = self.ss {
+            ss.write(level);
+        }
+    }
+
+    fn deselect(&mut self) {
+        let level = match self.ss_polarity {
+            Polarity::ActiveLow => Level::High,
+            Polarity::ActiveHigh => Level::Low,
+        };
+
+        if let Some(ref mut ss) = self.ss {
+            ss.write(level);
+        }
+    }
+
+    fn transfer_byte(&mut self, write_byte: u8) -> u8 {
+        // CPHA=0 samples on the leading clock edge, and shifts out on the
+        // trailing edge. CPHA=1 shifts out on the leading edge, and samples
+        // on the trailing edge.
+        let cpha = match self.mode {
+            Mode::Mode0 | Mode::Mode2 => false,
+            Mode::Mode1 | Mode::Mode3 => true,
+        };
+
+        let idle_level = self.idle_level();
+        let active_level = self.active_level();
+        let mut read_byte = 0u8;
+        let mut deadline_ns = get_time_ns();
+
+        for idx in 0..8 {
+            let mask = match self.bit_order {
+                BitOrder::MsbFirst => 0x80 >> idx,
+                BitOrder::LsbFirst => 0x01 << idx,
+            };
+
+            if !cpha {
+                self.write_mosi(write_byte & mask != 0);
+            }
+
+            deadline_ns = deadline_ns.saturating_add(self.half_period_ns);
+            delay_until_ns(deadline_ns);
+            self.sclk.write(active_level);
+
+            if cpha {
+                self.write_mosi(write_byte & mask != 0);
+            } else if self.read_miso() {
+                read_byte |= mask;
+            }
+
+            deadline_ns = deadline_ns.saturating_add(self.half_period_ns);
+            delay_until_ns(deadline_ns);
+            self.sclk.write(idle_level);
+
+            if cpha && self.read_miso() {
+                read_byte |= mask;
+            }
+        }
+
+        read_byte
+    }
+
+    #[inline]
+    fn write_mosi(&mut self, high: bool) {
+        if let Some(ref mut mosi) = self.mosi {
+            if high {
+                mosi.set_high();
+            } else {
+                mosi.set_low();
+            }
+        }
+    }
+
+    #[inline]
+    fn read_miso(&self) -> bool {
+        match self.miso {
+            Some(ref miso) => miso.is_high(),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! WS2812 (NeoPixel) LED driver.
+//!
+//! WS2812, WS2812B and SK6812 RGB LEDs are controlled through a single data line
+//! using an 800 kHz self-clocked waveform, where each bit is encoded by the length
+//! of a high pulse. The timing tolerances are too tight for reliable bit-banging
+//! from user space, so [`Ws2812`] generates the waveform on the MOSI pin of an SPI
+//! bus instead. Every data bit is encoded as three SPI bits sent at 2.4 MHz, which
+//! results in a 417 ns high pulse for a 0 bit, and an 833 ns high pulse for a 1 bit.
+//!
+//! Only the MOSI pin needs to be connected to the first LED's data input. Because
+//! the Raspberry Pi's GPIO pins use 3.3 V logic levels, a level shifter might be
+//! needed for LEDs powered at 5 V.
+//!
+//! ## Clock speed
+//!
+//! SPI0 derives its clock from the VPU core clock, which may be scaled dynamically
+//! depending on the system load. A changing core clock results in a corrupted
+//! waveform. On the Raspberry Pi 3 and earlier models, add `core_freq=250` to
+//! `/boot/config.txt`. On the Raspberry Pi 4, add `core_freq_min=500` instead.
+//!
+//! SPI1 and SPI2 don't support DMA, and their small FIFOs can cause gaps in
+//! longer transfers, which the LEDs interpret as the end of a frame. Only SPI0 is
+//! recommended.
+//!
+//! ## Buffer size
+//!
+//! Each LED requires 9 bytes, and each frame is followed by 90 bytes of reset
+//! time. An entire frame has to be sent in a single transfer, which limits the
+//! default spidev buffer size of 4096 bytes to 445 LEDs. See the [`spi`] module
+//! documentation on how to increase the buffer size.
+//!
+//! ## PWM
+//!
+//! Driving the LEDs through the PWM peripheral requires DMA, which isn't
+//! available through the sysfs PWM interface used by [`pwm`]. As a result, PWM
+//! output isn't supported.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use rppal::spi::ws2812::{Color, Ws2812};
+//! use rppal::spi::Bus;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut strip = Ws2812::new(Bus::Spi0, 8)?;
+//!
+//! strip.set_brightness(0.25);
+//! strip.write(&[Color::rgb(255, 0, 0); 8])?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Ws2812`]: struct.Ws2812.html
+//! [`spi`]: ../index.html#buffer-size-limits
+//! [`pwm`]: ../../pwm/index.html
+
+use crate::spi::{Bus, Mode, Result, Segment, SlaveSelect, Spi};
+
+// Each WS2812 bit is encoded as 3 SPI bits, which results in a bit period of 1.25 µs
+const CLOCK_SPEED: u32 = 2_400_000;
+const BYTES_PER_LED: usize = 9;
+// The reset time needs to be at least 280 µs for newer WS2812B revisions. 90 bytes
+// of zeroes at 2.4 MHz results in 300 µs.
+const RESET_BYTES: usize = 90;
+
+/// RGB color.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct Color {
+    /// Red component.
+    pub red: u8,
+    /// Green component.
+    pub green: u8,
+    /// Blue component.
+    pub blue: u8,
+}
+
+impl Color {
+    /// Constructs a new `Color` from its red, green and blue components.
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Color {
+        Color { red, green, blue }
+    }
+
+    /// Black, which turns an LED off.
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    /// White.
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+}
+
+impl From<(u8, u8, u8)> for Color {
+    fn from(rgb: (u8, u8, u8)) -> Color {
+        Color::rgb(rgb.0, rgb.1, rgb.2)
+    }
+}
+
+/// WS2812 LED strip driver.
+///
+/// `Ws2812` keeps a pixel buffer, which can be modified with [`set_pixel`] and
+/// [`fill`], and sent to the LEDs with [`show`]. [`write`] replaces the pixel buffer
+/// and sends it in a single call.
+///
+/// Colors are scaled by the configured brightness right before they're sent,
+/// which keeps the original colors intact in the pixel buffer.
+///
+/// More information on the hardware configuration can be found in the module
+/// documentation.
+///
+/// [`set_pixel`]: #method.set_pixel
+/// [`fill`]: #method.fill
+/// [`show`]: #method.show
+/// [`write`]: #method.write
+#[derive(Debug)]
+pub struct Ws2812 {
+    spi: Spi,
+    pixels: Vec<Color>,
+    brightness: f64,
+    buffer: Vec<u8>,
+}
+
+impl Ws2812 {
+    /// Constructs a new `Ws2812` for a strip containing `len` LEDs, connected to
+    /// the MOSI pin of the specified SPI bus.
+    ///
+    /// The Slave Select pin isn't used, but spidev claims [`Ss0`] for the
+    /// duration of the transfer.
+    ///
+    /// [`Ss0`]: ../enum.SlaveSelect.html#variant.Ss0
+    pub fn new(bus: Bus, len: usize) -> Result<Ws2812> {
+        Ok(Ws2812::with_spi(
+            Spi::new(bus, SlaveSelect::Ss0, CLOCK_SPEED, Mode::Mode0)?,
+            len,
+        ))
+    }
+
+    /// Constructs a new `Ws2812` using an existing `Spi` instance.
+    ///
+    /// The clock speed is changed to 2.4 MHz when the pixel buffer is sent.
+    pub fn with_spi(spi: Spi, len: usize) -> Ws2812 {
+        Ws2812 {
+            spi,
+            pixels: vec![Color::BLACK; len],
+            brightness: 1.0,
+            buffer: Vec::with_capacity(len * BYTES_PER_LED + RESET_BYTES),
+        }
+    }
+
+    /// Returns the number of LEDs.
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    /// Returns `true` if the strip doesn't contain any LEDs.
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    /// Returns the brightness as a value between `0.0` and `1.0`.
+    pub fn brightness(&self) -> f64 {
+        self.brightness
+    }
+
+    /// Sets the brightness as a value between `0.0` (off) and `1.0` (full brightness).
+    ///
+    /// The new brightness is applied the next time the pixel buffer is sent.
+    pub fn set_brightness(&mut self, brightness: f64) {
+        self.brightness = brightness.clamp(0.0, 1.0);
+    }
+
+    /// Returns the color of the LED at the specified index, or `None` if the index
+    /// is out of range.
+    pub fn pixel(&self, index: usize) -> Option<Color> {
+        self.pixels.get(index).copied()
+    }
+
+    /// Returns the pixel buffer.
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    /// Sets the color of the LED at the specified index.
+    ///
+    /// Indexes that are out of range are ignored. The change is sent to the LEDs
+    /// the next time [`show`] is called.
+    ///
+    /// [`show`]: #method.show
+    pub fn set_pixel(&mut self, index: usize, color: Color) {
+        if let Some(pixel) = self.pixels.get_mut(index) {
+            *pixel = color;
+        }
+    }
+
+    /// Sets all LEDs to the same color.
+    ///
+    /// The change is sent to the LEDs the next time [`show`] is called.
+    ///
+    /// [`show`]: #method.show
+    pub fn fill(&mut self, color: Color) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = color;
+        }
+    }
+
+    /// Turns off all LEDs.
+    pub fn clear(&mut self) -> Result<()> {
+        self.fill(Color::BLACK);
+        self.show()
+    }
+
+    /// Replaces the pixel buffer with `colors`, and sends it to the LEDs.
+    ///
+    /// If `colors` contains fewer colors than the number of LEDs, the remaining
+    /// LEDs keep their current color. Any additional colors are ignored.
+    pub fn write(&mut self, colors: &[Color]) -> Result<()> {
+        for (pixel, color) in self.pixels.iter_mut().zip(colors) {
+            *pixel = *color;
+        }
+
+        self.show()
+    }
+
+    /// Sends the pixel buffer to the LEDs.
+    pub fn show(&mut self) -> Result<()> {
+        self.spi.set_clock_speed(CLOCK_SPEED)?;
+
+        self.buffer.clear();
+
+        for pixel in &self.pixels {
+            // WS2812 LEDs expect green first, followed by red and blue
+            for &component in &[pixel.green, pixel.red, pixel.blue] {
+                let component = (f64::from(component) * self.brightness).round() as u8;
+                encode(component, &mut self.buffer);
+            }
+        }
+
+        self.buffer.resize(self.buffer.len() + RESET_BYTES, 0);
+
+        // Spi::write splits large buffers into chunks, and the gap between chunks
+        // would be interpreted as the end of a frame
+        self.spi
+            .transfer_segments(&[Segment::with_write(&self.buffer)])?;
+
+        Ok(())
+    }
+
+    /// Consumes the `Ws2812`, and returns the underlying `Spi` instance.
+    pub fn into_spi(self) -> Spi {
+        self.spi
+    }
+}
+
+// Encodes each bit in the specified byte into 3 SPI bits, 100 for a 0 bit and
+// 110 for a 1 bit, MSB first, and appends the resulting 3 bytes to buffer.
+#[inline]
+fn encode(byte: u8, buffer: &mut Vec<u8>) {
+    let mut bits: u32 = 0;
+
+    for bit in (0..8).rev() {
+        bits = (bits << 3) | if byte & (1 << bit) > 0 { 0b110 } else { 0b100 };
+    }
+
+    buffer.extend_from_slice(&[(bits >> 16) as u8, (bits >> 8) as u8, bits as u8]);
+}
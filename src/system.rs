@@ -21,21 +21,49 @@
 //! Raspberry Pi system-related tools.
 //!
 //! Use [`DeviceInfo`] to identify what Raspberry Pi model and SoC the software is
-//! running on.
+//! running on, and how much RAM is installed. Applications can use this information
+//! to select the appropriate peripherals and pins for the current board, the same
+//! way RPPAL does internally.
+//!
+//! [`Health`] reports the SoC temperature, core voltage and throttling status, which
+//! is useful for daemons that control cooling fans or monitor the power supply.
+//!
+//! [`HatInfo`] identifies the attached HAT based on the contents of its ID EEPROM,
+//! and [`overlays`] and [`boot_overlays`] list the loaded Device Tree overlays.
+//!
+//! [`SystemTimer`] reads the SoC's free-running 1 MHz system timer, which provides
+//! cheap microsecond timestamps for protocol timing.
 //!
 //! [`DeviceInfo`]: struct.DeviceInfo.html
+//! [`Health`]: struct.Health.html
+//! [`HatInfo`]: struct.HatInfo.html
+//! [`overlays`]: fn.overlays.html
+//! [`boot_overlays`]: fn.boot_overlays.html
+//! [`SystemTimer`]: struct.SystemTimer.html
 
 use std::fmt;
 use std::fs;
 use std::fs::File;
+use std::io;
 use std::io::{BufRead, BufReader};
 use std::result;
 
 use quick_error::quick_error;
 
+use self::mailbox::Mailbox;
+
+mod mailbox;
+mod timer;
+
+pub use self::timer::SystemTimer;
+
 const PERIPHERAL_BASE_RPI: u32 = 0x2000_0000;
 const PERIPHERAL_BASE_RPI2: u32 = 0x3f00_0000;
+const PERIPHERAL_BASE_RPI4: u32 = 0xfe00_0000;
+// The Raspberry Pi 5's GPIO pins are controlled by the RP1 I/O controller
+const PERIPHERAL_BASE_RPI5: u64 = 0x1f_0000_0000;
 const GPIO_OFFSET: u32 = 0x20_0000;
+const GPIO_OFFSET_RP1: u32 = 0x0d_0000;
 
 quick_error! {
 /// Errors that can occur when trying to identify the Raspberry Pi hardware.
@@ -55,6 +83,17 @@ quick_error! {
 /// doesn't provide any of the common user-accessible system files
 /// that are used to identify the model and SoC.
         UnknownModel { description("unknown Raspberry Pi model") }
+/// IO error.
+///
+/// Reading a system health value failed. This is usually caused by missing
+/// permissions for `/dev/vcio`, or a kernel that doesn't provide the relevant
+/// sysfs entries.
+        Io(err: io::Error) { description(err.description()) from() }
+/// Permission denied when opening `/dev/mem` for read access.
+///
+/// The system timer registers can only be accessed through `/dev/mem`, which
+/// requires superuser privileges.
+        PermissionDenied { description("/dev/mem insufficient permissions") }
     }
 }
 
@@ -73,10 +112,16 @@ pub enum Model {
     RaspberryPi3B,
     RaspberryPi3BPlus,
     RaspberryPi3APlus,
+    RaspberryPi4B,
+    RaspberryPi400,
+    RaspberryPi5,
     RaspberryPiComputeModule,
     RaspberryPiComputeModule3,
+    RaspberryPiComputeModule3Plus,
+    RaspberryPiComputeModule4,
     RaspberryPiZero,
     RaspberryPiZeroW,
+    RaspberryPiZero2W,
 }
 
 impl fmt::Display for Model {
@@ -91,21 +136,34 @@ impl fmt::Display for Model {
             Model::RaspberryPi3B => write!(f, "Raspberry Pi 3 B"),
             Model::RaspberryPi3BPlus => write!(f, "Raspberry Pi 3 B+"),
             Model::RaspberryPi3APlus => write!(f, "Raspberry Pi 3 A+"),
+            Model::RaspberryPi4B => write!(f, "Raspberry Pi 4 B"),
+            Model::RaspberryPi400 => write!(f, "Raspberry Pi 400"),
+            Model::RaspberryPi5 => write!(f, "Raspberry Pi 5"),
             Model::RaspberryPiComputeModule => write!(f, "Raspberry Pi Compute Module"),
             Model::RaspberryPiComputeModule3 => write!(f, "Raspberry Pi Compute Module 3"),
+            Model::RaspberryPiComputeModule3Plus => {
+                write!(f, "Raspberry Pi Compute Module 3+")
+            }
+            Model::RaspberryPiComputeModule4 => write!(f, "Raspberry Pi Compute Module 4"),
             Model::RaspberryPiZero => write!(f, "Raspberry Pi Zero"),
             Model::RaspberryPiZeroW => write!(f, "Raspberry Pi Zero W"),
+            Model::RaspberryPiZero2W => write!(f, "Raspberry Pi Zero 2 W"),
         }
     }
 }
 
 /// Identifiable Raspberry Pi SoCs.
+///
+/// The Raspberry Pi Zero 2 W's RP3A0 contains the same BCM2710A1 die as the
+/// Raspberry Pi 3 B, and is identified as `Bcm2837A1`.
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum SoC {
     Bcm2835,
     Bcm2836,
     Bcm2837A1,
     Bcm2837B0,
+    Bcm2711,
+    Bcm2712,
 }
 
 impl fmt::Display for SoC {
@@ -115,6 +173,8 @@ impl fmt::Display for SoC {
             SoC::Bcm2836 => write!(f, "BCM2836"),
             SoC::Bcm2837A1 => write!(f, "BCM2837A1"),
             SoC::Bcm2837B0 => write!(f, "BCM2837B0"),
+            SoC::Bcm2711 => write!(f, "BCM2711"),
+            SoC::Bcm2712 => write!(f, "BCM2712"),
         }
     }
 }
@@ -128,13 +188,11 @@ fn parse_proc_cpuinfo() -> Result<Model> {
 
     let mut hardware: String = String::new();
     let mut revision: String = String::new();
-    for line_result in proc_cpuinfo.lines() {
-        if let Ok(line) = line_result {
-            if line.starts_with("Hardware\t: ") {
-                hardware = String::from(&line[11..]);
-            } else if line.starts_with("Revision\t: ") {
-                revision = String::from(&line[11..]).to_lowercase();
-            }
+    for line in proc_cpuinfo.lines().map_while(result::Result::ok) {
+        if let Some(line_value) = line.strip_prefix("Hardware\t: ") {
+            hardware = String::from(line_value);
+        } else if let Some(line_value) = line.strip_prefix("Revision\t: ") {
+            revision = String::from(line_value).to_lowercase();
         }
     }
 
@@ -143,7 +201,7 @@ fn parse_proc_cpuinfo() -> Result<Model> {
     // solely based on the revision field.
     match &hardware[..] {
         "BCM2708" | "BCM2835" | "BCM2709" | "BCM2836" | "BCM2710" | "BCM2837" | "BCM2837A1"
-        | "BCM2837B0" => {}
+        | "BCM2837B0" | "BCM2711" | "BCM2712" => {}
         _ => return Err(Error::UnknownModel),
     }
 
@@ -168,8 +226,18 @@ fn parse_proc_cpuinfo() -> Result<Model> {
             "900092" | "920092" | "900093" | "920093" => Model::RaspberryPiZero,
             "a020a0" => Model::RaspberryPiComputeModule3,
             "9000c1" => Model::RaspberryPiZeroW,
-            "a020d3" => Model::RaspberryPi3BPlus,
-            "9020e0" => Model::RaspberryPi3APlus,
+            "a020d3" | "a020d4" => Model::RaspberryPi3BPlus,
+            "9020e0" | "9020e1" => Model::RaspberryPi3APlus,
+            "a02100" => Model::RaspberryPiComputeModule3Plus,
+            "a03111" | "b03111" | "b03112" | "b03114" | "b03115" | "c03111" | "c03112"
+            | "c03114" | "c03115" | "d03114" | "d03115" => Model::RaspberryPi4B,
+            "902120" => Model::RaspberryPiZero2W,
+            "c03130" | "c03131" => Model::RaspberryPi400,
+            "a03140" | "b03140" | "c03140" | "d03140" | "a03141" | "b03141" | "c03141"
+            | "d03141" => Model::RaspberryPiComputeModule4,
+            "b04170" | "c04170" | "d04170" | "b04171" | "c04171" | "d04171" | "e04171" => {
+                Model::RaspberryPi5
+            }
             _ => return Err(Error::UnknownModel),
         }
     } else {
@@ -203,6 +271,12 @@ fn parse_base_compatible() -> Result<Model> {
             "raspberrypi,model-zero-w" => Model::RaspberryPiZeroW,
             "raspberrypi,3-model-b-plus" => Model::RaspberryPi3BPlus,
             "raspberrypi,3-model-a-plus" => Model::RaspberryPi3APlus,
+            "raspberrypi,4-model-b" => Model::RaspberryPi4B,
+            "raspberrypi,3-compute-module-plus" => Model::RaspberryPiComputeModule3Plus,
+            "raspberrypi,model-zero-2-w" => Model::RaspberryPiZero2W,
+            "raspberrypi,400" => Model::RaspberryPi400,
+            "raspberrypi,4-compute-module" => Model::RaspberryPiComputeModule4,
+            "raspberrypi,5-model-b" => Model::RaspberryPi5,
             _ => continue,
         };
 
@@ -258,19 +332,68 @@ fn parse_base_model() -> Result<Model> {
         "Raspberry Pi 3 Model B+" => Model::RaspberryPi3BPlus,
         "Raspberry Pi 3 Model B Plus" => Model::RaspberryPi3BPlus,
         "Raspberry Pi 3 Model A Plus" => Model::RaspberryPi3APlus,
+        "Raspberry Pi 4 Model B" => Model::RaspberryPi4B,
+        "Raspberry Pi Compute Module 3 Plus" => Model::RaspberryPiComputeModule3Plus,
+        "Raspberry Pi Zero 2 W" => Model::RaspberryPiZero2W,
+        "Raspberry Pi 400" => Model::RaspberryPi400,
+        "Raspberry Pi Compute Module 4" => Model::RaspberryPiComputeModule4,
+        "Raspberry Pi 5 Model B" => Model::RaspberryPi5,
         _ => return Err(Error::UnknownModel),
     };
 
     Ok(model)
 }
 
+// Read the board revision code from /proc/cpuinfo, or /sys/firmware/devicetree/base/system/linux,revision
+// on 64-bit kernels that don't include the revision in /proc/cpuinfo
+fn parse_revision() -> Option<u32> {
+    if let Ok(file) = File::open("/proc/cpuinfo") {
+        for line in BufReader::new(file).lines().map_while(result::Result::ok) {
+            if let Some(line_value) = line.strip_prefix("Revision\t: ") {
+                return u32::from_str_radix(line_value.trim(), 16).ok();
+            }
+        }
+    }
+
+    match fs::read("/sys/firmware/devicetree/base/system/linux,revision") {
+        Ok(ref buffer) if buffer.len() == 4 => Some(
+            (u32::from(buffer[0]) << 24)
+                | (u32::from(buffer[1]) << 16)
+                | (u32::from(buffer[2]) << 8)
+                | u32::from(buffer[3]),
+        ),
+        _ => None,
+    }
+}
+
+// Identify the installed RAM in megabytes (MB) based on the board revision code
+fn parse_ram_size() -> Option<u32> {
+    let revision = parse_revision()?;
+
+    if revision & (1 << 23) > 0 {
+        // New-style revision codes store the memory size in bits 20-22
+        match (revision >> 20) & 0b111 {
+            size @ 0..=6 => Some(256 << size),
+            _ => None,
+        }
+    } else {
+        // Old-style revision codes, ignoring the over-voltage bits
+        match revision & 0xffff {
+            0x0002..=0x000c | 0x0011 | 0x0012 | 0x0015 => Some(256),
+            0x000d..=0x0010 | 0x0013 | 0x0014 => Some(512),
+            _ => None,
+        }
+    }
+}
+
 /// Retrieves Raspberry Pi device information.
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct DeviceInfo {
     model: Model,
     soc: SoC,
-    peripheral_base: u32,
+    peripheral_base: u64,
     gpio_offset: u32,
+    ram_size: Option<u32>,
 }
 
 impl DeviceInfo {
@@ -285,7 +408,7 @@ impl DeviceInfo {
             .or_else(|_| parse_base_compatible().or_else(|_| parse_base_model()))?;
 
         // Set SoC and memory offsets based on model
-        match model {
+        let (soc, peripheral_base, gpio_offset) = match model {
             Model::RaspberryPiA
             | Model::RaspberryPiAPlus
             | Model::RaspberryPiBRev1
@@ -293,31 +416,29 @@ impl DeviceInfo {
             | Model::RaspberryPiBPlus
             | Model::RaspberryPiComputeModule
             | Model::RaspberryPiZero
-            | Model::RaspberryPiZeroW => Ok(DeviceInfo {
-                model,
-                soc: SoC::Bcm2835,
-                peripheral_base: PERIPHERAL_BASE_RPI,
-                gpio_offset: GPIO_OFFSET,
-            }),
-            Model::RaspberryPi2B => Ok(DeviceInfo {
-                model,
-                soc: SoC::Bcm2836,
-                peripheral_base: PERIPHERAL_BASE_RPI2,
-                gpio_offset: GPIO_OFFSET,
-            }),
-            Model::RaspberryPi3B | Model::RaspberryPiComputeModule3 => Ok(DeviceInfo {
-                model,
-                soc: SoC::Bcm2837A1,
-                peripheral_base: PERIPHERAL_BASE_RPI2,
-                gpio_offset: GPIO_OFFSET,
-            }),
-            Model::RaspberryPi3BPlus | Model::RaspberryPi3APlus => Ok(DeviceInfo {
-                model,
-                soc: SoC::Bcm2837B0,
-                peripheral_base: PERIPHERAL_BASE_RPI2,
-                gpio_offset: GPIO_OFFSET,
-            }),
-        }
+            | Model::RaspberryPiZeroW => (SoC::Bcm2835, PERIPHERAL_BASE_RPI.into(), GPIO_OFFSET),
+            Model::RaspberryPi2B => (SoC::Bcm2836, PERIPHERAL_BASE_RPI2.into(), GPIO_OFFSET),
+            Model::RaspberryPi3B | Model::RaspberryPiComputeModule3 | Model::RaspberryPiZero2W => {
+                (SoC::Bcm2837A1, PERIPHERAL_BASE_RPI2.into(), GPIO_OFFSET)
+            }
+            Model::RaspberryPi3BPlus
+            | Model::RaspberryPi3APlus
+            | Model::RaspberryPiComputeModule3Plus => {
+                (SoC::Bcm2837B0, PERIPHERAL_BASE_RPI2.into(), GPIO_OFFSET)
+            }
+            Model::RaspberryPi4B | Model::RaspberryPi400 | Model::RaspberryPiComputeModule4 => {
+                (SoC::Bcm2711, PERIPHERAL_BASE_RPI4.into(), GPIO_OFFSET)
+            }
+            Model::RaspberryPi5 => (SoC::Bcm2712, PERIPHERAL_BASE_RPI5, GPIO_OFFSET_RP1),
+        };
+
+        Ok(DeviceInfo {
+            model,
+            soc,
+            peripheral_base,
+            gpio_offset,
+            ram_size: parse_ram_size(),
+        })
     }
 
     /// Returns the Raspberry Pi's model.
@@ -330,8 +451,12 @@ impl DeviceInfo {
         self.soc
     }
 
-    /// Returns the base memory address for the BCM283x peripherals.
-    pub fn peripheral_base(&self) -> u32 {
+    /// Returns the base memory address for the BCM283x/BCM2711 peripherals.
+    ///
+    /// On the Raspberry Pi 5, the GPIO pins are controlled by the RP1 I/O
+    /// controller, and `peripheral_base` returns the base address for the RP1
+    /// peripherals instead.
+    pub fn peripheral_base(&self) -> u64 {
         self.peripheral_base
     }
 
@@ -339,4 +464,295 @@ impl DeviceInfo {
     pub fn gpio_offset(&self) -> u32 {
         self.gpio_offset
     }
+
+    /// Returns the amount of installed RAM in megabytes (MB).
+    ///
+    /// The RAM size is based on the board revision code, and doesn't depend on
+    /// how much memory is available to Linux. Returns `None` if the revision code
+    /// can't be read, or doesn't contain the RAM size.
+    pub fn ram_size(&self) -> Option<u32> {
+        self.ram_size
+    }
+}
+
+// Read a null-terminated string property from the device tree. Returns None if the
+// property doesn't exist or is empty.
+fn read_dt_string(path: &str) -> Option<String> {
+    let buffer = fs::read(path).ok()?;
+    let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    let value = String::from_utf8_lossy(&buffer[..len]).trim().to_owned();
+
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+// Parse a hexadecimal device tree string property like "0x0001"
+fn read_dt_hex(path: &str) -> Option<u16> {
+    let value = read_dt_string(path)?;
+    let value = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(&value);
+
+    u16::from_str_radix(value, 16).ok()
+}
+
+/// Retrieves information on the attached HAT.
+///
+/// HATs store their identity in an ID EEPROM, which is read by the firmware at
+/// boot and exposed through `/proc/device-tree/hat/`. Applications can use this
+/// information to verify the expected HAT is attached before driving any pins.
+///
+/// ```no_run
+/// use rppal::system::HatInfo;
+///
+/// match HatInfo::new() {
+///     Some(ref hat) if hat.vendor() == "Acme" && hat.product() == "Relay HAT" => {
+///         println!("Found {} v{:?}", hat.product(), hat.product_version());
+///     }
+///     _ => panic!("Relay HAT not attached"),
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HatInfo {
+    vendor: String,
+    product: String,
+    uuid: String,
+    product_id: Option<u16>,
+    product_version: Option<u16>,
+}
+
+impl HatInfo {
+    /// Constructs a new `HatInfo`.
+    ///
+    /// Returns `None` if no HAT is attached, the HAT doesn't have an ID EEPROM,
+    /// or the firmware didn't expose its contents through the device tree.
+    pub fn new() -> Option<HatInfo> {
+        Some(HatInfo {
+            vendor: read_dt_string("/proc/device-tree/hat/vendor")?,
+            product: read_dt_string("/proc/device-tree/hat/product")?,
+            uuid: read_dt_string("/proc/device-tree/hat/uuid").unwrap_or_default(),
+            product_id: read_dt_hex("/proc/device-tree/hat/product_id"),
+            product_version: read_dt_hex("/proc/device-tree/hat/product_ver"),
+        })
+    }
+
+    /// Returns the vendor name.
+    pub fn vendor(&self) -> &str {
+        &self.vendor
+    }
+
+    /// Returns the product name.
+    pub fn product(&self) -> &str {
+        &self.product
+    }
+
+    /// Returns the UUID that uniquely identifies this product, or an empty
+    /// string if the EEPROM doesn't contain a UUID.
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    /// Returns the vendor-assigned product ID.
+    pub fn product_id(&self) -> Option<u16> {
+        self.product_id
+    }
+
+    /// Returns the vendor-assigned product version.
+    pub fn product_version(&self) -> Option<u16> {
+        self.product_version
+    }
+}
+
+/// Returns the names of all Device Tree overlays that were loaded at runtime.
+///
+/// Overlays loaded with `dtoverlay` are listed in `/sys/kernel/config/device-tree/overlays/`,
+/// which requires configfs to be mounted. The names are returned in the order
+/// in which the overlays were loaded. Overlays that were applied by the firmware
+/// at boot are merged into the base device tree, and are listed by [`boot_overlays`]
+/// instead.
+///
+/// [`boot_overlays`]: fn.boot_overlays.html
+pub fn overlays() -> Vec<String> {
+    let entries = match fs::read_dir("/sys/kernel/config/device-tree/overlays") {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    // dtoverlay names each directory <index>_<overlay>
+    let mut overlays: Vec<(u32, String)> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .map(|name| match name.find('_') {
+            Some(idx) => match name[..idx].parse() {
+                Ok(index) => (index, name[idx + 1..].to_owned()),
+                Err(_) => (u32::MAX, name),
+            },
+            None => (u32::MAX, name),
+        })
+        .collect();
+
+    overlays.sort();
+    overlays.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Returns the names of all Device Tree overlays that are loaded at boot.
+///
+/// The overlays are parsed from the `dtoverlay` entries in `/boot/firmware/config.txt`,
+/// or `/boot/config.txt` on older distributions, in the order in which they're listed.
+/// Any overlay parameters are omitted. Conditional filters like `[pi4]` aren't
+/// evaluated, so overlays in sections that don't apply to the current model
+/// are included as well.
+pub fn boot_overlays() -> Vec<String> {
+    let file =
+        match File::open("/boot/firmware/config.txt").or_else(|_| File::open("/boot/config.txt")) {
+            Ok(file) => file,
+            Err(_) => return Vec::new(),
+        };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(result::Result::ok)
+        .filter_map(|line| {
+            let value = line.trim().strip_prefix("dtoverlay")?.trim_start();
+            let name = value.strip_prefix('=')?.split(',').next()?.trim();
+
+            if name.is_empty() {
+                None
+            } else {
+                Some(name.to_owned())
+            }
+        })
+        .collect()
+}
+
+/// Throttling and under-voltage status flags.
+///
+/// The `is_*` methods report the current state, while the `has_*` methods report
+/// whether the condition has occurred at any time since boot.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct Throttled(u32);
+
+impl Throttled {
+    /// Constructs a new `Throttled` from the raw bitmask returned by the firmware.
+    pub fn from_bits(bits: u32) -> Throttled {
+        Throttled(bits)
+    }
+
+    /// Returns the raw bitmask, as reported by `vcgencmd get_throttled`.
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` if the supply voltage is currently too low.
+    pub fn is_under_voltage(self) -> bool {
+        self.0 & (1 << 0) > 0
+    }
+
+    /// Returns `true` if the ARM frequency is currently capped.
+    pub fn is_frequency_capped(self) -> bool {
+        self.0 & (1 << 1) > 0
+    }
+
+    /// Returns `true` if the SoC is currently throttled.
+    pub fn is_throttled(self) -> bool {
+        self.0 & (1 << 2) > 0
+    }
+
+    /// Returns `true` if the soft temperature limit is currently active.
+    pub fn is_soft_temperature_limited(self) -> bool {
+        self.0 & (1 << 3) > 0
+    }
+
+    /// Returns `true` if under-voltage has occurred since boot.
+    pub fn has_under_voltage_occurred(self) -> bool {
+        self.0 & (1 << 16) > 0
+    }
+
+    /// Returns `true` if ARM frequency capping has occurred since boot.
+    pub fn has_frequency_capping_occurred(self) -> bool {
+        self.0 & (1 << 17) > 0
+    }
+
+    /// Returns `true` if throttling has occurred since boot.
+    pub fn has_throttling_occurred(self) -> bool {
+        self.0 & (1 << 18) > 0
+    }
+
+    /// Returns `true` if the soft temperature limit has been active since boot.
+    pub fn has_soft_temperature_limit_occurred(self) -> bool {
+        self.0 & (1 << 19) > 0
+    }
+}
+
+/// Retrieves system health readings.
+///
+/// Values are read through sysfs when available, and otherwise requested from
+/// the VideoCore firmware through the mailbox interface at `/dev/vcio`, which
+/// requires the current user to be a member of the `video` group. The core
+/// voltage is only available through the mailbox interface.
+///
+/// ```no_run
+/// use rppal::system::Health;
+///
+/// # fn main() -> rppal::system::Result<()> {
+/// let health = Health::new();
+///
+/// println!("Temperature: {:.1} °C", health.temperature()?);
+/// println!("Core voltage: {:.3} V", health.core_voltage()?);
+///
+/// if health.throttled()?.is_under_voltage() {
+///     println!("Under-voltage detected");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Health {}
+
+impl Health {
+    /// Constructs a new `Health`.
+    pub fn new() -> Health {
+        Health {}
+    }
+
+    /// Returns the SoC temperature in degrees Celsius (°C).
+    ///
+    /// The CPU and GPU share the same die, and the same temperature sensor.
+    pub fn temperature(&self) -> Result<f64> {
+        if let Ok(value) = fs::read_to_string("/sys/class/thermal/thermal_zone0/temp") {
+            if let Ok(millidegrees) = value.trim().parse::<i32>() {
+                return Ok(f64::from(millidegrees) / 1000.0);
+            }
+        }
+
+        let millidegrees = Mailbox::new()?.property(mailbox::TAG_GET_TEMPERATURE, 0)?;
+
+        Ok(f64::from(millidegrees) / 1000.0)
+    }
+
+    /// Returns the SoC core voltage in volts (V).
+    pub fn core_voltage(&self) -> Result<f64> {
+        let microvolts =
+            Mailbox::new()?.property(mailbox::TAG_GET_VOLTAGE, mailbox::VOLTAGE_ID_CORE)?;
+
+        Ok(f64::from(microvolts) / 1_000_000.0)
+    }
+
+    /// Returns the throttling and under-voltage status flags.
+    pub fn throttled(&self) -> Result<Throttled> {
+        if let Ok(value) =
+            fs::read_to_string("/sys/devices/platform/soc/soc:firmware/get_throttled")
+        {
+            if let Ok(bits) = u32::from_str_radix(value.trim().trim_start_matches("0x"), 16) {
+                return Ok(Throttled(bits));
+            }
+        }
+
+        Ok(Throttled(
+            Mailbox::new()?.property(mailbox::TAG_GET_THROTTLED, 0xffff)?,
+        ))
+    }
 }
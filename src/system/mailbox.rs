@@ -0,0 +1,104 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+// VideoCore mailbox property interface, accessed through /dev/vcio. Based on
+// https://github.com/raspberrypi/firmware/wiki/Mailbox-property-interface and
+// drivers/char/broadcom/vcio.c.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::mem::size_of;
+use std::os::unix::io::AsRawFd;
+
+use libc::{self, c_char, ioctl};
+
+#[cfg(target_env = "gnu")]
+type IoctlLong = libc::c_ulong;
+#[cfg(target_env = "musl")]
+type IoctlLong = libc::c_long;
+
+// _IOWR(100, 0, char *)
+const REQ_MBOX_PROPERTY: IoctlLong =
+    (3 << 30) | ((size_of::<*mut c_char>() as IoctlLong) << 16) | (100 << 8);
+
+const PROCESS_REQUEST: u32 = 0;
+const REQUEST_SUCCESSFUL: u32 = 0x8000_0000;
+const RESPONSE_BIT: u32 = 0x8000_0000;
+
+pub const TAG_GET_VOLTAGE: u32 = 0x0003_0003;
+pub const TAG_GET_TEMPERATURE: u32 = 0x0003_0006;
+pub const TAG_GET_THROTTLED: u32 = 0x0003_0046;
+
+pub const VOLTAGE_ID_CORE: u32 = 1;
+
+// The firmware requires a 16-byte aligned buffer
+#[repr(C, align(16))]
+struct Message {
+    buffer: [u32; 8],
+}
+
+pub struct Mailbox {
+    vcio: File,
+}
+
+impl Mailbox {
+    pub fn new() -> io::Result<Mailbox> {
+        Ok(Mailbox {
+            vcio: OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/vcio")?,
+        })
+    }
+
+    // Sends a property tag with a single u32 argument, and returns the second
+    // u32 of the response value, which is where all supported tags store their result.
+    pub fn property(&self, tag: u32, id: u32) -> io::Result<u32> {
+        let mut message = Message {
+            buffer: [
+                size_of::<Message>() as u32,
+                PROCESS_REQUEST,
+                tag,
+                (2 * size_of::<u32>()) as u32, // Value buffer size
+                0,                             // Request code
+                id,
+                0,
+                0, // End tag
+            ],
+        };
+
+        parse_retval!(unsafe {
+            ioctl(
+                self.vcio.as_raw_fd(),
+                REQ_MBOX_PROPERTY,
+                message.buffer.as_mut_ptr(),
+            )
+        })?;
+
+        if message.buffer[1] != REQUEST_SUCCESSFUL || message.buffer[4] & RESPONSE_BIT == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "mailbox property request failed",
+            ));
+        }
+
+        Ok(message.buffer[6])
+    }
+}
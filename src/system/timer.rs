@@ -0,0 +1,179 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+
+use libc;
+
+use crate::delay;
+use crate::system::{DeviceInfo, Error, Result, SoC};
+
+// The system timer is located at the same offset from the peripheral base on
+// BCM2835, BCM2836, BCM2837 and BCM2711. On the BCM2712, the peripheral base
+// reported by DeviceInfo belongs to the RP1, so the system timer has its own
+// address.
+const ST_OFFSET: u64 = 0x3000;
+const ST_BASE_BCM2712: u64 = 0x10_7c00_3000;
+const ST_MEM_SIZE: usize = 4096;
+
+// Register offsets (in words)
+const ST_CLO: usize = 1;
+const ST_CHI: usize = 2;
+
+/// Provides access to the BCM283x free-running system timer.
+///
+/// The system timer is a 64-bit counter that's incremented at a fixed
+/// frequency of 1 MHz, independent of the CPU and core clock frequencies.
+/// Reading the counter only requires a memory access, which makes it a cheap
+/// and jitter-free source of microsecond timestamps for protocol timing.
+///
+/// The system timer registers are only accessible through `/dev/mem`, which
+/// requires superuser privileges.
+///
+/// ```no_run
+/// use rppal::system::SystemTimer;
+///
+/// # fn main() -> rppal::system::Result<()> {
+/// let timer = SystemTimer::new()?;
+///
+/// let start = timer.ticks();
+/// timer.wait_until(start + 500);
+///
+/// println!("Elapsed: {} µs", timer.ticks() - start);
+/// # Ok(())
+/// # }
+/// ```
+pub struct SystemTimer {
+    mem_ptr: *mut u32,
+}
+
+impl SystemTimer {
+    /// The frequency of the system timer in hertz (Hz).
+    pub const FREQUENCY: u32 = 1_000_000;
+
+    /// Constructs a new `SystemTimer`.
+    pub fn new() -> Result<SystemTimer> {
+        let device_info = DeviceInfo::new()?;
+        let address = match device_info.soc() {
+            SoC::Bcm2712 => ST_BASE_BCM2712,
+            _ => device_info.peripheral_base() + ST_OFFSET,
+        };
+
+        // The BCM2712 system timer is located above 4 GiB, which doesn't fit
+        // in a 32-bit off_t.
+        let offset = libc::off_t::try_from(address).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "system timer address out of range",
+            )
+        })?;
+
+        let mem_file = match OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_SYNC)
+            .open("/dev/mem")
+        {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                return Err(Error::PermissionDenied)
+            }
+            Err(e) => return Err(Error::Io(e)),
+        };
+
+        let mem_ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                ST_MEM_SIZE,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                mem_file.as_raw_fd(),
+                offset,
+            )
+        };
+
+        if mem_ptr == libc::MAP_FAILED {
+            return Err(Error::Io(io::Error::last_os_error()));
+        }
+
+        Ok(SystemTimer {
+            mem_ptr: mem_ptr as *mut u32,
+        })
+    }
+
+    #[inline(always)]
+    fn read(&self, offset: usize) -> u32 {
+        unsafe { ptr::read_volatile(self.mem_ptr.add(offset)) }
+    }
+
+    /// Returns the current value of the system timer's counter.
+    ///
+    /// The counter is incremented once every microsecond (µs).
+    #[inline]
+    pub fn ticks(&self) -> u64 {
+        // The upper and lower 32 bits are stored in separate registers. If the
+        // upper 32 bits changed while reading the lower 32 bits, the lower
+        // 32 bits wrapped around, and need to be read again.
+        let mut high = self.read(ST_CHI);
+        loop {
+            let low = self.read(ST_CLO);
+            let high_check = self.read(ST_CHI);
+
+            if high == high_check {
+                return (u64::from(high) << 32) | u64::from(low);
+            }
+
+            high = high_check;
+        }
+    }
+
+    /// Blocks until the system timer's counter reaches `ticks`.
+    ///
+    /// `wait_until` sleeps if enough time remains, and busy-waits for the
+    /// final ~100 µs. Returns immediately if `ticks` has already passed.
+    pub fn wait_until(&self, ticks: u64) {
+        delay::delay_until_ticks(self, ticks);
+    }
+}
+
+impl fmt::Debug for SystemTimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SystemTimer")
+            .field("mem_ptr", &self.mem_ptr)
+            .finish()
+    }
+}
+
+impl Drop for SystemTimer {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.mem_ptr as *mut libc::c_void, ST_MEM_SIZE);
+        }
+    }
+}
+
+// Required because of the raw pointer to our memory-mapped file
+unsafe impl Send for SystemTimer {}
+unsafe impl Sync for SystemTimer {}
@@ -0,0 +1,461 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Tone generation for piezo buzzers and speakers.
+//!
+//! [`Buzzer`] plays square-wave [`Tone`]s with a 50% duty cycle, using either
+//! a hardware PWM channel, or software-based PWM on any available GPIO pin.
+//! Tones are played on a background thread, so [`Buzzer::enqueue`] and
+//! [`Buzzer::enqueue_melody`] return immediately, while [`Buzzer::play`] and
+//! [`Buzzer::play_melody`] block until playback has finished.
+//!
+//! Passive piezo buzzers and small speakers (through a transistor) need to be
+//! driven by a square wave. Active buzzers contain their own oscillator, and
+//! only need a constant high level, so they can be controlled with a regular
+//! [`OutputPin`] instead.
+//!
+//! Software-based PWM is limited to a few kHz, and its timing might be affected
+//! by other processes running on the Raspberry Pi, which results in audible
+//! jitter. Use hardware PWM if you need clean tones.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rppal::pwm::{Channel, Pwm};
+//! use rppal::tone::{Buzzer, Tone};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut buzzer = Buzzer::new(Pwm::new(Channel::Pwm0)?);
+//!
+//! let quarter = Duration::from_millis(250);
+//! let melody = [
+//!     Tone::note("C5", quarter)?,
+//!     Tone::note("E5", quarter)?,
+//!     Tone::note("G5", quarter)?,
+//!     Tone::rest(quarter),
+//!     Tone::note("C6", quarter * 2)?,
+//! ];
+//!
+//! // Returns immediately, while the melody plays in the background
+//! buzzer.enqueue_melody(&melody);
+//!
+//! // ...
+//!
+//! buzzer.wait()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Buzzer`]: struct.Buzzer.html
+//! [`Tone`]: struct.Tone.html
+//! [`Buzzer::enqueue`]: struct.Buzzer.html#method.enqueue
+//! [`Buzzer::enqueue_melody`]: struct.Buzzer.html#method.enqueue_melody
+//! [`Buzzer::play`]: struct.Buzzer.html#method.play
+//! [`Buzzer::play_melody`]: struct.Buzzer.html#method.play_melody
+//! [`OutputPin`]: ../gpio/struct.OutputPin.html
+
+use std::collections::VecDeque;
+use std::result;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use quick_error::quick_error;
+
+use crate::gpio::{self, OutputPin};
+use crate::pwm::{self, Pwm};
+
+const DUTY_CYCLE: f64 = 0.5;
+const A4_FREQUENCY: f64 = 440.0;
+
+quick_error! {
+/// Errors that can occur when generating tones.
+    #[derive(Debug)]
+    pub enum Error {
+/// GPIO error.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// PWM error.
+        Pwm(err: pwm::Error) { description(err.description()) from() }
+/// Invalid note.
+///
+/// The note name couldn't be parsed. Notes are specified as a letter from `A`
+/// to `G`, an optional `#` or `b`, and an octave from `0` to `9`, for instance
+/// `A4`, `C#5` or `Eb3`.
+        InvalidNote(note: String) { description("invalid note") }
+    }
+}
+
+/// Result type returned from methods that can have `tone::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// A tone with a frequency and duration.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Tone {
+    frequency: f64,
+    duration: Duration,
+}
+
+impl Tone {
+    /// Constructs a new `Tone` with the specified frequency in herz (Hz) and
+    /// duration.
+    ///
+    /// A frequency of `0.0` or less is played as a rest.
+    pub fn new(frequency: f64, duration: Duration) -> Tone {
+        Tone {
+            frequency: if frequency.is_finite() {
+                frequency.max(0.0)
+            } else {
+                0.0
+            },
+            duration,
+        }
+    }
+
+    /// Constructs a new `Tone` for the specified note and duration.
+    ///
+    /// `note` consists of a letter from `A` to `G`, an optional `#` (sharp) or
+    /// `b` (flat), and an octave from `0` to `9`, for instance `A4` (440 Hz),
+    /// `C#5` or `Eb3`. Frequencies are based on twelve-tone equal temperament.
+    pub fn note(note: &str, duration: Duration) -> Result<Tone> {
+        Ok(Tone::new(
+            note_frequency(note).ok_or_else(|| Error::InvalidNote(note.to_owned()))?,
+            duration,
+        ))
+    }
+
+    /// Constructs a new `Tone` that stays silent for the specified duration.
+    pub fn rest(duration: Duration) -> Tone {
+        Tone {
+            frequency: 0.0,
+            duration,
+        }
+    }
+
+    /// Returns the frequency in herz (Hz), or `0.0` for a rest.
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    /// Returns the duration.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns `true` if this tone is a rest.
+    pub fn is_rest(&self) -> bool {
+        self.frequency == 0.0
+    }
+}
+
+// Converts a note name like A4, C#5 or Eb3 to its frequency
+fn note_frequency(note: &str) -> Option<f64> {
+    let mut chars = note.chars();
+
+    let semitone: i32 = match chars.next()?.to_ascii_uppercase() {
+        'C' => -9,
+        'D' => -7,
+        'E' => -5,
+        'F' => -4,
+        'G' => -2,
+        'A' => 0,
+        'B' => 2,
+        _ => return None,
+    };
+
+    let rest = chars.as_str();
+    let (semitone, octave) = if let Some(octave) = rest.strip_prefix('#') {
+        (semitone + 1, octave)
+    } else if let Some(octave) = rest.strip_prefix('b') {
+        (semitone - 1, octave)
+    } else {
+        (semitone, rest)
+    };
+
+    if octave.len() != 1 {
+        return None;
+    }
+
+    let octave = octave.parse::<i32>().ok()?;
+    let offset = (octave - 4) * 12 + semitone;
+
+    Some(A4_FREQUENCY * 2f64.powf(f64::from(offset) / 12.0))
+}
+
+#[derive(Debug)]
+enum Output {
+    Pwm(Pwm),
+    Soft(OutputPin),
+}
+
+impl Output {
+    fn start(&mut self, frequency: f64) -> Result<()> {
+        match self {
+            Output::Pwm(pwm) => {
+                pwm.set_frequency(frequency, DUTY_CYCLE)?;
+                pwm.enable()?;
+            }
+            Output::Soft(pin) => pin.set_pwm_frequency(frequency, DUTY_CYCLE)?,
+        }
+
+        Ok(())
+    }
+
+    fn silence(&mut self) -> Result<()> {
+        match self {
+            Output::Pwm(pwm) => pwm.disable()?,
+            Output::Soft(pin) => {
+                pin.clear_pwm()?;
+                pin.set_low();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    queue: VecDeque<Tone>,
+    playing: bool,
+    // Incremented by stop(), so the current tone is cut short
+    generation: u64,
+    error: Option<Error>,
+    shutdown: bool,
+}
+
+impl State {
+    fn is_busy(&self) -> bool {
+        self.playing || !self.queue.is_empty()
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+    idle: Condvar,
+}
+
+/// Square-wave tone generator for piezo buzzers.
+///
+/// `Buzzer` plays queued tones on a background thread, using either a hardware
+/// PWM channel or software-based PWM. The output is silenced when `Buzzer` goes
+/// out of scope.
+#[derive(Debug)]
+pub struct Buzzer {
+    shared: Arc<Shared>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Buzzer {
+    /// Constructs a new `Buzzer` using a hardware PWM channel.
+    pub fn new(pwm: Pwm) -> Buzzer {
+        Buzzer::with_output(Output::Pwm(pwm))
+    }
+
+    /// Constructs a new `Buzzer` using software-based PWM on the specified pin.
+    pub fn with_soft_pwm(pin: OutputPin) -> Buzzer {
+        Buzzer::with_output(Output::Soft(pin))
+    }
+
+    fn with_output(mut output: Output) -> Buzzer {
+        let _ = output.silence();
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                queue: VecDeque::new(),
+                playing: false,
+                generation: 0,
+                error: None,
+                shutdown: false,
+            }),
+            changed: Condvar::new(),
+            idle: Condvar::new(),
+        });
+
+        let thread_shared = shared.clone();
+        let thread = thread::spawn(move || {
+            Buzzer::run(&thread_shared, output);
+        });
+
+        Buzzer {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    fn run(shared: &Shared, mut output: Output) {
+        let mut state = shared.state.lock().unwrap();
+
+        loop {
+            if state.shutdown {
+                break;
+            }
+
+            let tone = match state.queue.pop_front() {
+                Some(tone) => tone,
+                None => {
+                    if state.playing {
+                        state.playing = false;
+                        if let Err(e) = output.silence() {
+                            state.error.get_or_insert(e);
+                        }
+                    }
+
+                    shared.idle.notify_all();
+                    state = shared.changed.wait(state).unwrap();
+                    continue;
+                }
+            };
+
+            state.playing = true;
+
+            let result = if tone.is_rest() {
+                output.silence()
+            } else {
+                output.start(tone.frequency)
+            };
+
+            if let Err(e) = result {
+                state.error.get_or_insert(e);
+            }
+
+            // Wait for the tone to finish, unless stop() or drop() cut it short
+            let generation = state.generation;
+            let deadline = Instant::now() + tone.duration;
+            loop {
+                let now = Instant::now();
+                if state.shutdown || state.generation != generation || now >= deadline {
+                    break;
+                }
+
+                state = shared
+                    .changed
+                    .wait_timeout(state, deadline - now)
+                    .unwrap()
+                    .0;
+            }
+        }
+
+        let _ = output.silence();
+    }
+
+    fn update<F: FnOnce(&mut State)>(&self, f: F) {
+        f(&mut self.shared.state.lock().unwrap());
+        self.shared.changed.notify_all();
+    }
+
+    /// Adds a tone to the end of the queue.
+    ///
+    /// `enqueue` returns immediately. Use [`wait`] to block until all queued
+    /// tones have been played.
+    ///
+    /// [`wait`]: #method.wait
+    pub fn enqueue(&mut self, tone: Tone) {
+        self.update(|state| state.queue.push_back(tone));
+    }
+
+    /// Adds a sequence of tones to the end of the queue.
+    ///
+    /// `enqueue_melody` returns immediately. Use [`wait`] to block until all
+    /// queued tones have been played.
+    ///
+    /// [`wait`]: #method.wait
+    pub fn enqueue_melody(&mut self, melody: &[Tone]) {
+        self.update(|state| state.queue.extend(melody.iter().copied()));
+    }
+
+    /// Plays a tone, and blocks until all queued tones, including this one,
+    /// have been played.
+    pub fn play(&mut self, tone: Tone) -> Result<()> {
+        self.enqueue(tone);
+        self.wait()
+    }
+
+    /// Plays a sequence of tones, and blocks until all queued tones, including
+    /// these, have been played.
+    pub fn play_melody(&mut self, melody: &[Tone]) -> Result<()> {
+        self.enqueue_melody(melody);
+        self.wait()
+    }
+
+    /// Returns the number of tones waiting in the queue, not including the tone
+    /// that's currently playing.
+    pub fn queued(&self) -> usize {
+        self.shared.state.lock().unwrap().queue.len()
+    }
+
+    /// Returns `true` if a tone is playing, or tones are waiting in the queue.
+    pub fn is_playing(&self) -> bool {
+        self.shared.state.lock().unwrap().is_busy()
+    }
+
+    /// Stops the current tone immediately, and clears the queue.
+    pub fn stop(&mut self) {
+        self.update(|state| {
+            state.queue.clear();
+            state.generation = state.generation.wrapping_add(1);
+        });
+    }
+
+    /// Blocks until all queued tones have been played.
+    ///
+    /// Returns the first error that occurred while configuring the PWM output
+    /// since the previous call, if any.
+    pub fn wait(&self) -> Result<()> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        while state.is_busy() && !state.shutdown {
+            state = self.shared.idle.wait(state).unwrap();
+        }
+
+        match state.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Blocks until all queued tones have been played, or the timeout elapses.
+    ///
+    /// Returns `true` if playback finished.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let state = self.shared.state.lock().unwrap();
+
+        let (state, _) = self
+            .shared
+            .idle
+            .wait_timeout_while(state, timeout, |state| state.is_busy())
+            .unwrap();
+
+        !state.is_busy()
+    }
+}
+
+impl Drop for Buzzer {
+    fn drop(&mut self) {
+        self.update(|state| state.shutdown = true);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -78,31 +78,87 @@
 //!
 //! ## USB serial devices
 //!
+//! In addition to the built-in UARTs, `Uart` supports USB serial adapters and other
+//! USB devices with a serial interface. Depending on the driver, these are exposed
+//! as `/dev/ttyUSBx` ([`Device::Usb`]) or `/dev/ttyACMx` ([`Device::Acm`]). Any other
+//! serial device can be opened by its path with [`Uart::with_path`].
+//!
+//! ## Hardware flow control
+//!
+//! RTS/CTS hardware flow control can be enabled with [`set_hardware_flow_control`].
+//! For UART0 and UART1, this also configures the associated GPIO pins listed above to
+//! the appropriate alternate function. The pins are reset to their original mode when
+//! hardware flow control is disabled, or `Uart` goes out of scope.
+//!
+//! XON/XOFF software flow control can be enabled with [`set_software_flow_control`].
+//!
+//! ## Software UART
+//!
+//! [`SoftUart`] emulates a half-duplex UART on a single GPIO pin, which is used
+//! for both sending and receiving data. It supports inverted logic and break
+//! generation, which are needed for single-wire protocols such as SDI-12.
+//!
 //! ## Troubleshooting
 //!
 //! ### Permission denied
 //!
+//! If [`new`] returns an `io::ErrorKind::PermissionDenied` error, make sure the file
+//! permissions for the serial device are correct, and the current user is a member
+//! of the `dialout` group.
+//!
+//! [`SoftUart`]: struct.SoftUart.html
+//! [`Device::Usb`]: enum.Device.html#variant.Usb
+//! [`Device::Acm`]: enum.Device.html#variant.Acm
+//! [`Uart::with_path`]: struct.Uart.html#method.with_path
+//! [`set_hardware_flow_control`]: struct.Uart.html#method.set_hardware_flow_control
+//! [`set_software_flow_control`]: struct.Uart.html#method.set_software_flow_control
+//! [`new`]: struct.Uart.html#method.new
 //! [documentation]: https://www.raspberrypi.org/documentation/configuration/uart.md
 
 use std::fs::{File, OpenOptions};
 use std::io;
 use std::io::{Read, Write};
 use std::os::unix::fs::OpenOptionsExt;
-use std::os::unix::io::AsRawFd;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::Path;
 use std::result;
+use std::thread;
+use std::time::Duration;
 
 use libc::{O_NDELAY, O_NOCTTY, O_NONBLOCK};
+use quick_error::quick_error;
+
+use crate::gpio::{self, AltPin, Gpio, Mode};
 
+mod soft;
 mod termios;
 
+pub use self::soft::SoftUart;
+
+// GPIO pins used for CTS and RTS
+const GPIO_CTS: u8 = 16;
+const GPIO_RTS: u8 = 17;
+
 quick_error! {
 /// Errors that can occur when accessing the UART peripheral.
     #[derive(Debug)]
     pub enum Error {
 /// IO error.
         Io(err: io::Error) { description(err.description()) from() }
+/// GPIO error.
+///
+/// Configuring the RTS/CTS pins for hardware flow control failed.
+        Gpio(err: gpio::Error) { description(err.description()) display("{}", err) cause(err) from() }
 /// Invalid value.
         InvalidValue { description("invalid value") }
+/// Parity error.
+///
+/// A byte was received with an incorrect parity bit. Only returned by `SoftUart`.
+        Parity { description("parity error") }
+/// Framing error.
+///
+/// A byte was received without a valid stop bit. Only returned by `SoftUart`.
+        Framing { description("framing error") }
     }
 }
 
@@ -128,10 +184,15 @@ pub type Result<T> = result::Result<T, Error>;
 ///
 /// [here]: index.html
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Device {
+    /// PL011 UART (`/dev/ttyAMA0`).
     Uart0,
+    /// Mini UART (`/dev/ttyS0`).
     Uart1,
+    /// USB CDC ACM device (`/dev/ttyACMx`).
     Acm(u8),
+    /// USB serial adapter (`/dev/ttyUSBx`).
     Usb(u8),
 }
 
@@ -141,6 +202,7 @@ pub enum Device {
 /// 1-bits in the data bits. `Mark` and `Space` always set the parity
 /// bit to `1` or `0` respectively.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Parity {
     None,
     Even,
@@ -149,14 +211,87 @@ pub enum Parity {
     Space,
 }
 
+/// Queue types.
+///
+/// `Input` refers to the buffer containing incoming data that hasn't been read
+/// yet. `Output` refers to the buffer containing outgoing data that hasn't been
+/// transmitted yet.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Queue {
+    Input,
+    Output,
+    Both,
+}
+
+/// Line error counters.
+///
+/// The counters are maintained by the kernel for as long as the serial device
+/// exists, and can be compared against a previous snapshot to detect new errors.
+/// Support is device-dependent.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct ErrorCounts {
+    breaks: u32,
+    frame: u32,
+    parity: u32,
+    overrun: u32,
+    buffer_overrun: u32,
+}
+
+impl ErrorCounts {
+    /// Returns the number of received break conditions.
+    ///
+    /// A break condition occurs when the RX line is held low for longer than
+    /// the time it takes to transfer a single frame.
+    pub fn breaks(&self) -> u32 {
+        self.breaks
+    }
+
+    /// Returns the number of framing errors.
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Returns the number of parity errors.
+    pub fn parity(&self) -> u32 {
+        self.parity
+    }
+
+    /// Returns the number of hardware FIFO overruns.
+    pub fn overrun(&self) -> u32 {
+        self.overrun
+    }
+
+    /// Returns the number of input buffer overruns.
+    pub fn buffer_overrun(&self) -> u32 {
+        self.buffer_overrun
+    }
+}
+
 /// Provides access to the Raspberry Pi's UART peripherals, and USB serial devices.
 #[derive(Debug)]
 pub struct Uart {
     device: File,
+    // Alternate function mode for the RTS/CTS pins, only used by UART0 and UART1
+    rts_cts_mode: Option<Mode>,
+    rts_cts_pins: Option<(AltPin, AltPin)>,
+    blocking_write: bool,
 }
 
 impl Uart {
     /// Constructs a new `Uart`.
+    ///
+    /// `line_speed` specifies the baud rate, `parity` the parity bit, `data_bits`
+    /// the number of data bits (5-8), and `stop_bits` the number of stop bits (1-2).
+    ///
+    /// By default, [`read`] doesn't block, and [`write`] blocks until all data has
+    /// been transferred to the output queue. This can be changed with
+    /// [`set_read_mode`] and [`set_write_mode`]. Hardware and software flow control
+    /// are disabled.
+    ///
+    /// [`read`]: #method.read
+    /// [`write`]: #method.write
+    /// [`set_read_mode`]: #method.set_read_mode
+    /// [`set_write_mode`]: #method.set_write_mode
     pub fn new(
         device: Device,
         line_speed: u32,
@@ -164,31 +299,80 @@ impl Uart {
         data_bits: u8,
         stop_bits: u8,
     ) -> Result<Uart> {
+        let rts_cts_mode = match device {
+            Device::Uart0 => Some(Mode::Alt3),
+            Device::Uart1 => Some(Mode::Alt5),
+            _ => None,
+        };
+
+        let path = match device {
+            Device::Uart0 => "/dev/ttyAMA0".to_owned(),
+            Device::Uart1 => "/dev/ttyS0".to_owned(),
+            Device::Acm(idx) => format!("/dev/ttyACM{}", idx),
+            Device::Usb(idx) => format!("/dev/ttyUSB{}", idx),
+        };
+
+        Uart::open(path, rts_cts_mode, line_speed, parity, data_bits, stop_bits)
+    }
+
+    /// Constructs a new `Uart` connected to the serial device specified by `path`.
+    ///
+    /// `with_path` can be used to access serial devices that aren't covered by
+    /// [`Device`], or to select a device based on its path in `/dev/serial/by-id`.
+    ///
+    /// Unlike [`new`], enabling hardware flow control doesn't configure any GPIO pins.
+    ///
+    /// [`Device`]: enum.Device.html
+    /// [`new`]: #method.new
+    pub fn with_path<P: AsRef<Path>>(
+        path: P,
+        line_speed: u32,
+        parity: Parity,
+        data_bits: u8,
+        stop_bits: u8,
+    ) -> Result<Uart> {
+        Uart::open(path, None, line_speed, parity, data_bits, stop_bits)
+    }
+
+    fn open<P: AsRef<Path>>(
+        path: P,
+        rts_cts_mode: Option<Mode>,
+        line_speed: u32,
+        parity: Parity,
+        data_bits: u8,
+        stop_bits: u8,
+    ) -> Result<Uart> {
+        // O_NONBLOCK prevents open() from blocking while waiting for a carrier
+        // detect signal, after which we go back to blocking mode, so read() can
+        // be configured through VMIN and VTIME.
         let device = OpenOptions::new()
             .read(true)
             .write(true)
             .custom_flags(O_NOCTTY | O_NDELAY | O_NONBLOCK)
-            .open(match device {
-                Device::Uart0 => "/dev/ttyAMA0".to_owned(),
-                Device::Uart1 => "/dev/ttyS0".to_owned(),
-                Device::Acm(idx) => format!("/dev/ttyACM{}", idx),
-                Device::Usb(idx) => format!("/dev/ttyUSB{}", idx),
-            })?;
+            .open(path)?;
 
+        termios::set_nonblocking(device.as_raw_fd(), false)?;
         termios::set_raw_mode(device.as_raw_fd())?;
         termios::ignore_carrier_detect(device.as_raw_fd())?;
         termios::enable_read(device.as_raw_fd())?;
+        termios::set_hardware_flow_control(device.as_raw_fd(), false)?;
+        termios::set_software_flow_control(device.as_raw_fd(), false)?;
         termios::set_line_speed(device.as_raw_fd(), line_speed)?;
         termios::set_parity(device.as_raw_fd(), parity)?;
         termios::set_data_bits(device.as_raw_fd(), data_bits)?;
         termios::set_stop_bits(device.as_raw_fd(), stop_bits)?;
 
-        Ok(Uart { device })
+        Ok(Uart {
+            device,
+            rts_cts_mode,
+            rts_cts_pins: None,
+            blocking_write: true,
+        })
     }
 
     /// Gets the line speed in baud (Bd).
     pub fn line_speed(&self) -> Result<u32> {
-        Ok(termios::line_speed(self.device.as_raw_fd())?)
+        termios::line_speed(self.device.as_raw_fd())
     }
 
     /// Sets the line speed in baud (Bd).
@@ -207,7 +391,7 @@ impl Uart {
 
     /// Gets the parity bit.
     pub fn parity(&self) -> Result<Parity> {
-        Ok(termios::parity(self.device.as_raw_fd())?)
+        termios::parity(self.device.as_raw_fd())
     }
 
     /// Sets the parity bit.
@@ -221,7 +405,7 @@ impl Uart {
 
     /// Gets the number of data bits.
     pub fn data_bits(&self) -> Result<u8> {
-        Ok(termios::data_bits(self.device.as_raw_fd())?)
+        termios::data_bits(self.device.as_raw_fd())
     }
 
     /// Sets the number of data bits.
@@ -235,7 +419,7 @@ impl Uart {
 
     /// Gets the number of stop bits.
     pub fn stop_bits(&self) -> Result<u8> {
-        Ok(termios::stop_bits(self.device.as_raw_fd())?)
+        termios::stop_bits(self.device.as_raw_fd())
     }
 
     /// Sets the number of stop bits.
@@ -249,7 +433,7 @@ impl Uart {
 
     /// Returns the status of the RTS/CTS hardware flow control setting.
     pub fn hardware_flow_control(&self) -> Result<bool> {
-        unimplemented!()
+        termios::hardware_flow_control(self.device.as_raw_fd())
     }
 
     /// Enables or disables RTS/CTS hardware flow control.
@@ -261,13 +445,152 @@ impl Uart {
     /// Support for RTS/CTS is device-dependent.
     ///
     /// [here]: index.html
-    pub fn set_hardware_flow_control(&self, _enabled: bool) -> Result<()> {
-        unimplemented!()
+    pub fn set_hardware_flow_control(&mut self, enabled: bool) -> Result<()> {
+        if enabled {
+            if let (Some(mode), None) = (self.rts_cts_mode, &self.rts_cts_pins) {
+                let gpio = Gpio::new()?;
+                let pins = gpio.get_multiple(&[GPIO_CTS, GPIO_RTS])?;
+                let mut pins = pins.into_iter().map(|pin| pin.into_alt(mode));
+
+                // get_multiple() guarantees both pins are available
+                if let (Some(cts), Some(rts)) = (pins.next(), pins.next()) {
+                    self.rts_cts_pins = Some((cts, rts));
+                }
+            }
+        } else {
+            // Resets the pins to their original mode
+            self.rts_cts_pins = None;
+        }
+
+        termios::set_hardware_flow_control(self.device.as_raw_fd(), enabled)?;
+
+        Ok(())
+    }
+
+    /// Returns the status of the XON/XOFF software flow control setting.
+    pub fn software_flow_control(&self) -> Result<bool> {
+        termios::software_flow_control(self.device.as_raw_fd())
+    }
+
+    /// Enables or disables XON/XOFF software flow control.
+    ///
+    /// When enabled, outgoing data is paused after receiving an XOFF (`0x13`)
+    /// character, and resumed after receiving an XON (`0x11`) character. XON and
+    /// XOFF are sent automatically to the remote device when the input queue is
+    /// almost full or empty. Received XON and XOFF characters are removed from the
+    /// incoming data, which means software flow control shouldn't be used when
+    /// transferring binary data.
+    ///
+    /// By default, software flow control is disabled.
+    pub fn set_software_flow_control(&self, enabled: bool) -> Result<()> {
+        termios::set_software_flow_control(self.device.as_raw_fd(), enabled)
+    }
+
+    /// Sends a break condition for the specified `duration`.
+    ///
+    /// A break condition holds the TX line low for longer than the time it takes
+    /// to transfer a single frame. Any outgoing data is paused while the break
+    /// condition is active.
+    ///
+    /// `send_break` blocks while sleeping for `duration`, which determines the
+    /// minimum length of the break condition. DMX512 for example requires a break
+    /// of at least 92µs.
+    pub fn send_break(&self, duration: Duration) -> Result<()> {
+        termios::set_break(self.device.as_raw_fd(), true)?;
+        thread::sleep(duration);
+        termios::set_break(self.device.as_raw_fd(), false)?;
+
+        Ok(())
+    }
+
+    /// Starts or stops sending a break condition.
+    ///
+    /// While `set_break` is set to `true`, the TX line is held low. This allows
+    /// more control over the timing compared to [`send_break`].
+    ///
+    /// [`send_break`]: #method.send_break
+    pub fn set_break(&self, enabled: bool) -> Result<()> {
+        termios::set_break(self.device.as_raw_fd(), enabled)
+    }
+
+    /// Returns the number of received break conditions and line errors.
+    ///
+    /// Received break conditions are also stored as a `0x00` byte in the
+    /// input queue. `error_counts` can be used to distinguish between a break
+    /// condition and incoming data.
+    ///
+    /// Support for error counters is device-dependent. USB serial devices and UART1
+    /// might return an `io::Error` instead.
+    pub fn error_counts(&self) -> Result<ErrorCounts> {
+        termios::error_counts(self.device.as_raw_fd())
+    }
+
+    /// Returns the status of the blocking write setting.
+    pub fn is_write_blocking(&self) -> bool {
+        self.blocking_write
+    }
+
+    /// Sets the read mode for [`read`].
+    ///
+    /// `min_length` indicates how many bytes should be received before [`read`]
+    /// returns, and can be set to a value between 0 and 255. `timeout` indicates
+    /// how long [`read`] waits for incoming data, and is rounded to a multiple of
+    /// 100 milliseconds, with a maximum of 25.5 seconds.
+    ///
+    /// * `min_length` = 0, `timeout` = 0: Non-blocking. Returns any available data
+    ///   immediately (default).
+    /// * `min_length` > 0, `timeout` = 0: Blocks until at least `min_length` bytes are
+    ///   received.
+    /// * `min_length` = 0, `timeout` > 0: Blocks until any data is received, or `timeout`
+    ///   elapses.
+    /// * `min_length` > 0, `timeout` > 0: Blocks until `min_length` bytes are received,
+    ///   or `timeout` elapses after receiving the most recent byte.
+    ///
+    /// [`read`]: #method.read
+    pub fn set_read_mode(&mut self, min_length: u8, timeout: Duration) -> Result<()> {
+        // VTIME is specified in deciseconds
+        let timeout = timeout.as_secs() * 10 + u64::from(timeout.subsec_millis() / 100);
+        if timeout > 255 {
+            return Err(Error::InvalidValue);
+        }
+
+        termios::set_read_mode(self.device.as_raw_fd(), min_length, timeout as u8)?;
+
+        Ok(())
+    }
+
+    /// Sets the write mode for [`write`].
+    ///
+    /// If `blocking` is set to `true`, [`write`] blocks until all data has been
+    /// transferred to the output queue. If `blocking` is set to `false`, [`write`]
+    /// only copies as much data as fits in the output queue, and returns
+    /// immediately.
+    ///
+    /// By default, `blocking` is set to `true`.
+    ///
+    /// [`write`]: #method.write
+    pub fn set_write_mode(&mut self, blocking: bool) {
+        self.blocking_write = blocking;
+    }
+
+    /// Returns the number of bytes waiting in the input queue.
+    pub fn input_len(&self) -> Result<usize> {
+        termios::input_len(self.device.as_raw_fd())
+    }
+
+    /// Returns the number of bytes waiting in the output queue.
+    pub fn output_len(&self) -> Result<usize> {
+        termios::output_len(self.device.as_raw_fd())
     }
 
     /// Receives incoming data from the device and stores it in `buffer`.
     ///
+    /// `read` blocks, or returns immediately, based on the configured read
+    /// mode. More information can be found at [`set_read_mode`].
+    ///
     /// Returns how many bytes were read.
+    ///
+    /// [`set_read_mode`]: #method.set_read_mode
     pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
         match self.device.read(buffer) {
             Ok(bytes_read) => Ok(bytes_read),
@@ -278,24 +601,58 @@ impl Uart {
 
     /// Sends the contents of `buffer` to the device.
     ///
+    /// `write` blocks, or returns immediately, based on the configured write
+    /// mode. More information can be found at [`set_write_mode`].
+    ///
     /// Returns how many bytes were written.
+    ///
+    /// [`set_write_mode`]: #method.set_write_mode
     pub fn write(&mut self, buffer: &[u8]) -> Result<usize> {
-        match self.device.write(buffer) {
+        if !self.blocking_write {
+            termios::set_nonblocking(self.device.as_raw_fd(), true)?;
+        }
+
+        let result = if self.blocking_write {
+            self.device.write_all(buffer).map(|_| buffer.len())
+        } else {
+            self.device.write(buffer)
+        };
+
+        if !self.blocking_write {
+            termios::set_nonblocking(self.device.as_raw_fd(), false)?;
+        }
+
+        match result {
             Ok(bytes_written) => Ok(bytes_written),
             Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Ok(0),
             Err(e) => Err(Error::Io(e)),
         }
     }
 
-    /// .
-    pub fn flush(&self) {
-        unimplemented!()
+    /// Blocks until all data in the output queue has been transmitted.
+    pub fn drain(&self) -> Result<()> {
+        termios::drain(self.device.as_raw_fd())?;
+
+        Ok(())
+    }
+
+    /// Discards all data in the input and/or output queue.
+    pub fn flush(&self, queue: Queue) -> Result<()> {
+        termios::flush(self.device.as_raw_fd(), queue)?;
+
+        Ok(())
     }
 
-    /// .
-    pub fn drain(&self) {
-        unimplemented!()
+    // Used by async wrappers that wait for incoming data through tokio's reactor.
+    // Uart::read() returns Ok(0) instead of WouldBlock when no data is available.
+    #[cfg(feature = "async")]
+    pub(crate) fn set_nonblocking(&self, nonblocking: bool) -> Result<()> {
+        termios::set_nonblocking(self.device.as_raw_fd(), nonblocking)
     }
+}
 
-    // TODO: Enable available error detection (parity?) through termios
+impl AsRawFd for Uart {
+    fn as_raw_fd(&self) -> RawFd {
+        self.device.as_raw_fd()
+    }
 }
@@ -0,0 +1,291 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::Duration;
+
+use crate::delay::{delay_until_ns, duration_to_ns, get_time_ns};
+use crate::gpio::{IoPin, Level, Mode, PullUpDown};
+use crate::uart::{Error, Parity, Result};
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// Bit-banged half-duplex UART on a single GPIO pin.
+///
+/// `SoftUart` emulates a UART on any available GPIO pin, which is used for both
+/// sending and receiving data. The pin is switched to output mode while sending,
+/// and back to input mode afterwards, so only one side of the connection can send
+/// data at a time. This is used by single-wire protocols such as SDI-12, and
+/// devices with a combined TX/RX line.
+///
+/// With inverted logic enabled, the idle (mark) state and 1 bits are represented
+/// by a low level instead of a high level, which removes the need for an external
+/// inverter on lines that use negative logic.
+///
+/// Bits are timed by sleeping and busy waiting on the current thread, which
+/// limits `SoftUart` to lower line speeds. Incoming data is received by polling
+/// the pin, so [`read`] keeps a CPU core busy while it's waiting for data.
+///
+/// `SoftUart` takes ownership of an [`IoPin`]. When `SoftUart` goes out of scope,
+/// the pin is reset to its original mode and the pull-up/pull-down resistor is
+/// disabled, unless [`reset_on_drop`] was set to `false` on the `IoPin`.
+///
+/// [`read`]: #method.read
+/// [`IoPin`]: ../gpio/struct.IoPin.html
+/// [`reset_on_drop`]: ../gpio/struct.IoPin.html#method.set_reset_on_drop
+#[derive(Debug)]
+pub struct SoftUart {
+    pin: IoPin,
+    line_speed: u32,
+    parity: Parity,
+    data_bits: u8,
+    stop_bits: u8,
+    inverted: bool,
+    bit_ns: i64,
+}
+
+impl SoftUart {
+    /// Constructs a new `SoftUart`.
+    ///
+    /// `line_speed` specifies the baud rate, `parity` the parity bit, `data_bits`
+    /// the number of data bits (5-8), and `stop_bits` the number of stop bits (1-2).
+    ///
+    /// The pin is configured as an input, with its built-in pull-up resistor
+    /// enabled to keep the line idle. Inverted logic is disabled.
+    pub fn new(
+        pin: IoPin,
+        line_speed: u32,
+        parity: Parity,
+        data_bits: u8,
+        stop_bits: u8,
+    ) -> Result<SoftUart> {
+        if line_speed == 0 || !(5..=8).contains(&data_bits) || !(1..=2).contains(&stop_bits) {
+            return Err(Error::InvalidValue);
+        }
+
+        let mut soft_uart = SoftUart {
+            pin,
+            line_speed,
+            parity,
+            data_bits,
+            stop_bits,
+            inverted: false,
+            bit_ns: NANOS_PER_SEC / i64::from(line_speed),
+        };
+
+        soft_uart.pin.set_mode(Mode::Input);
+        soft_uart.set_inverted(false);
+
+        Ok(soft_uart)
+    }
+
+    /// Consumes the `SoftUart`, and returns the data pin.
+    pub fn into_pin(self) -> IoPin {
+        self.pin
+    }
+
+    /// Returns the line speed in baud (Bd).
+    pub fn line_speed(&self) -> u32 {
+        self.line_speed
+    }
+
+    /// Returns the parity bit.
+    pub fn parity(&self) -> Parity {
+        self.parity
+    }
+
+    /// Returns the number of data bits.
+    pub fn data_bits(&self) -> u8 {
+        self.data_bits
+    }
+
+    /// Returns the number of stop bits.
+    pub fn stop_bits(&self) -> u8 {
+        self.stop_bits
+    }
+
+    /// Returns `true` if inverted logic is enabled.
+    pub fn inverted(&self) -> bool {
+        self.inverted
+    }
+
+    /// Enables or disables inverted logic.
+    ///
+    /// The built-in pull-down resistor is enabled when inverted logic is enabled,
+    /// and the pull-up resistor when it's disabled, to keep the line idle.
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
+
+        self.pin.set_pullupdown(if inverted {
+            PullUpDown::PullDown
+        } else {
+            PullUpDown::PullUp
+        });
+        self.pin.write(self.level(true));
+    }
+
+    /// Sends the contents of `buffer`.
+    ///
+    /// `write` blocks until all data has been sent. Returns how many bytes were
+    /// written.
+    pub fn write(&mut self, buffer: &[u8]) -> Result<usize> {
+        self.pin.write(self.level(true));
+        self.pin.set_mode(Mode::Output);
+
+        let mut deadline_ns = get_time_ns();
+        for &byte in buffer {
+            for bit in self.frame(byte) {
+                self.pin.write(self.level(bit));
+                deadline_ns += self.bit_ns;
+                delay_until_ns(deadline_ns);
+            }
+        }
+
+        self.pin.set_mode(Mode::Input);
+
+        Ok(buffer.len())
+    }
+
+    /// Holds the line in the break (space) state for `duration`, followed by
+    /// the idle (mark) state for `mark`.
+    pub fn send_break(&mut self, duration: Duration, mark: Duration) {
+        let start_ns = get_time_ns();
+
+        self.pin.write(self.level(false));
+        self.pin.set_mode(Mode::Output);
+        delay_until_ns(start_ns + duration_to_ns(duration));
+
+        self.pin.write(self.level(true));
+        delay_until_ns(start_ns + duration_to_ns(duration) + duration_to_ns(mark));
+
+        self.pin.set_mode(Mode::Input);
+    }
+
+    /// Receives incoming data and stores it in `buffer`.
+    ///
+    /// `read` waits up to `timeout` for the start bit of each byte, and returns
+    /// when `buffer` is full, or no start bit was detected in time. Returns how
+    /// many bytes were read.
+    ///
+    /// Returns [`Error::Parity`] or [`Error::Framing`] if a byte was received with
+    /// an incorrect parity bit or a missing stop bit.
+    ///
+    /// [`Error::Parity`]: enum.Error.html#variant.Parity
+    /// [`Error::Framing`]: enum.Error.html#variant.Framing
+    pub fn read(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize> {
+        for (idx, byte) in buffer.iter_mut().enumerate() {
+            match self.read_byte(timeout)? {
+                Some(value) => *byte = value,
+                None => return Ok(idx),
+            }
+        }
+
+        Ok(buffer.len())
+    }
+
+    fn read_byte(&mut self, timeout: Duration) -> Result<Option<u8>> {
+        let idle = self.level(true);
+        let deadline_ns = get_time_ns() + duration_to_ns(timeout);
+
+        // Wait for the start bit
+        let start_ns = loop {
+            if self.pin.read() != idle {
+                break get_time_ns();
+            }
+
+            if get_time_ns() > deadline_ns {
+                return Ok(None);
+            }
+        };
+
+        // Sample each bit in the middle of its bit period
+        let mut sample_ns = start_ns + self.bit_ns + self.bit_ns / 2;
+        let mut sample = || {
+            delay_until_ns(sample_ns);
+            sample_ns += self.bit_ns;
+
+            self.pin.read() == idle
+        };
+
+        let mut value = 0u8;
+        let mut ones = 0;
+        for bit in 0..self.data_bits {
+            if sample() {
+                value |= 1 << bit;
+                ones += 1;
+            }
+        }
+
+        let parity_ok = match self.parity {
+            Parity::None => true,
+            Parity::Even => sample() == (ones % 2 == 1),
+            Parity::Odd => sample() == (ones % 2 == 0),
+            Parity::Mark => sample(),
+            Parity::Space => !sample(),
+        };
+
+        // Only check the first stop bit, so the next start bit isn't missed
+        let stop_ok = sample();
+
+        if !parity_ok {
+            return Err(Error::Parity);
+        } else if !stop_ok {
+            return Err(Error::Framing);
+        }
+
+        Ok(Some(value))
+    }
+
+    // Returns the bits for a single character, including the start, parity and
+    // stop bits. true represents the mark state.
+    fn frame(&self, byte: u8) -> Vec<bool> {
+        let mut bits = Vec::with_capacity(12);
+
+        bits.push(false);
+        let mut ones = 0;
+        for bit in 0..self.data_bits {
+            let value = byte & (1 << bit) != 0;
+            if value {
+                ones += 1;
+            }
+            bits.push(value);
+        }
+
+        match self.parity {
+            Parity::None => (),
+            Parity::Even => bits.push(ones % 2 == 1),
+            Parity::Odd => bits.push(ones % 2 == 0),
+            Parity::Mark => bits.push(true),
+            Parity::Space => bits.push(false),
+        }
+
+        bits.resize(bits.len() + usize::from(self.stop_bits), true);
+
+        bits
+    }
+
+    // Returns the pin level for the mark (true) or space (false) state
+    fn level(&self, mark: bool) -> Level {
+        if mark != self.inverted {
+            Level::High
+        } else {
+            Level::Low
+        }
+    }
+}
@@ -18,23 +18,44 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-#![allow(unused_imports)]
-
-use std::io;
-
 use libc::{c_int, termios};
 use libc::{cfgetospeed, cfmakeraw, cfsetispeed, cfsetospeed, tcgetattr, tcsetattr};
+use libc::{fcntl, ioctl, tcdrain, tcflush, F_GETFL, F_SETFL, O_NONBLOCK};
 use libc::{B0, B110, B134, B150, B200, B300, B50, B75};
 use libc::{B1000000, B1152000, B460800, B500000, B576000, B921600};
 use libc::{B115200, B19200, B230400, B38400, B57600};
 use libc::{B1200, B1800, B2400, B4800, B600, B9600};
 use libc::{B1500000, B2000000, B2500000, B3000000, B3500000, B4000000};
+use libc::{FIONREAD, TCIFLUSH, TCIOFLUSH, TCOFLUSH, TIOCCBRK, TIOCGICOUNT, TIOCOUTQ, TIOCSBRK};
 
 use libc::{CLOCAL, CMSPAR, CREAD, CRTSCTS, TCSANOW};
 use libc::{CS5, CS6, CS7, CS8, CSIZE, CSTOPB, PARENB, PARODD};
+use libc::{IXANY, IXOFF, IXON, VSTART, VSTOP};
 use libc::{VMIN, VTIME};
 
-use uart::{Error, Parity, Result};
+use crate::uart::{Error, ErrorCounts, Parity, Queue, Result};
+
+// XON/XOFF control characters
+const XON: u8 = 0x11;
+const XOFF: u8 = 0x13;
+
+// Matches serial_icounter_struct in serial.h
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+struct SerialCounters {
+    cts: c_int,
+    dsr: c_int,
+    rng: c_int,
+    dcd: c_int,
+    rx: c_int,
+    tx: c_int,
+    frame: c_int,
+    overrun: c_int,
+    parity: c_int,
+    brk: c_int,
+    buf_overrun: c_int,
+    reserved: [c_int; 9],
+}
 
 #[cfg(target_env = "gnu")]
 pub fn attributes(fd: c_int) -> Result<termios> {
@@ -162,25 +183,20 @@ pub fn set_line_speed(fd: c_int, line_speed: u32) -> Result<()> {
 pub fn parity(fd: c_int) -> Result<Parity> {
     let attr = attributes(fd)?;
 
-    if (attr.c_cflag & PARENB) == 0 {
-        return Ok(Parity::None);
-    } else if (attr.c_cflag & PARENB) > 0 && (attr.c_cflag & PARODD) == 0 {
-        return Ok(Parity::Even);
-    } else if (attr.c_cflag & PARENB) > 0 && (attr.c_cflag & PARODD) > 0 {
-        return Ok(Parity::Odd);
-    } else if (attr.c_cflag & PARENB) > 0
-        && (attr.c_cflag & CMSPAR) > 0
-        && (attr.c_cflag & PARODD) > 0
-    {
-        return Ok(Parity::Mark);
-    } else if (attr.c_cflag & PARENB) > 0
-        && (attr.c_cflag & CMSPAR) > 0
-        && (attr.c_cflag & PARODD) == 0
-    {
-        return Ok(Parity::Space);
-    }
-
-    Err(Error::InvalidValue)
+    // CMSPAR needs to be checked first, because Mark and Space also set PARENB
+    Ok(
+        match (
+            attr.c_cflag & PARENB > 0,
+            attr.c_cflag & CMSPAR > 0,
+            attr.c_cflag & PARODD > 0,
+        ) {
+            (false, _, _) => Parity::None,
+            (true, true, true) => Parity::Mark,
+            (true, true, false) => Parity::Space,
+            (true, false, true) => Parity::Odd,
+            (true, false, false) => Parity::Even,
+        },
+    )
 }
 
 pub fn set_parity(fd: c_int, parity: Parity) -> Result<()> {
@@ -188,15 +204,15 @@ pub fn set_parity(fd: c_int, parity: Parity) -> Result<()> {
 
     match parity {
         Parity::None => {
-            attr.c_cflag &= !PARENB;
-            attr.c_cflag &= !PARODD;
+            attr.c_cflag &= !(PARENB | PARODD | CMSPAR);
         }
         Parity::Even => {
             attr.c_cflag |= PARENB;
-            attr.c_cflag &= !PARODD;
+            attr.c_cflag &= !(PARODD | CMSPAR);
         }
         Parity::Odd => {
             attr.c_cflag |= PARENB | PARODD;
+            attr.c_cflag &= !CMSPAR;
         }
         Parity::Mark => {
             attr.c_cflag |= PARENB | PARODD | CMSPAR;
@@ -295,3 +311,132 @@ pub fn ignore_carrier_detect(fd: c_int) -> Result<()> {
 
     Ok(())
 }
+
+pub fn hardware_flow_control(fd: c_int) -> Result<bool> {
+    let attr = attributes(fd)?;
+
+    Ok((attr.c_cflag & CRTSCTS) > 0)
+}
+
+pub fn set_hardware_flow_control(fd: c_int, enabled: bool) -> Result<()> {
+    let mut attr = attributes(fd)?;
+
+    if enabled {
+        attr.c_cflag |= CRTSCTS;
+    } else {
+        attr.c_cflag &= !CRTSCTS;
+    }
+
+    set_attributes(fd, &attr)?;
+
+    Ok(())
+}
+
+// VMIN and VTIME are only used when O_NONBLOCK isn't set
+pub fn set_read_mode(fd: c_int, min_length: u8, timeout: u8) -> Result<()> {
+    let mut attr = attributes(fd)?;
+    attr.c_cc[VMIN] = min_length;
+    attr.c_cc[VTIME] = timeout;
+    set_attributes(fd, &attr)?;
+
+    Ok(())
+}
+
+pub fn set_nonblocking(fd: c_int, nonblocking: bool) -> Result<()> {
+    let flags = parse_retval!(unsafe { fcntl(fd, F_GETFL) })?;
+
+    let flags = if nonblocking {
+        flags | O_NONBLOCK
+    } else {
+        flags & !O_NONBLOCK
+    };
+
+    parse_retval!(unsafe { fcntl(fd, F_SETFL, flags) })?;
+
+    Ok(())
+}
+
+pub fn input_len(fd: c_int) -> Result<usize> {
+    let mut len: c_int = 0;
+
+    parse_retval!(unsafe { ioctl(fd, FIONREAD, &mut len) })?;
+
+    Ok(len as usize)
+}
+
+pub fn output_len(fd: c_int) -> Result<usize> {
+    let mut len: c_int = 0;
+
+    parse_retval!(unsafe { ioctl(fd, TIOCOUTQ, &mut len) })?;
+
+    Ok(len as usize)
+}
+
+pub fn drain(fd: c_int) -> Result<()> {
+    parse_retval!(unsafe { tcdrain(fd) })?;
+
+    Ok(())
+}
+
+pub fn flush(fd: c_int, queue: Queue) -> Result<()> {
+    parse_retval!(unsafe {
+        tcflush(
+            fd,
+            match queue {
+                Queue::Input => TCIFLUSH,
+                Queue::Output => TCOFLUSH,
+                Queue::Both => TCIOFLUSH,
+            },
+        )
+    })?;
+
+    Ok(())
+}
+
+pub fn software_flow_control(fd: c_int) -> Result<bool> {
+    let attr = attributes(fd)?;
+
+    Ok((attr.c_iflag & (IXON | IXOFF)) == (IXON | IXOFF))
+}
+
+pub fn set_software_flow_control(fd: c_int, enabled: bool) -> Result<()> {
+    let mut attr = attributes(fd)?;
+
+    if enabled {
+        // Only resume transmission after receiving XON, instead of any character
+        attr.c_iflag |= IXON | IXOFF;
+        attr.c_iflag &= !IXANY;
+        attr.c_cc[VSTART] = XON;
+        attr.c_cc[VSTOP] = XOFF;
+    } else {
+        attr.c_iflag &= !(IXON | IXOFF | IXANY);
+    }
+
+    set_attributes(fd, &attr)?;
+
+    Ok(())
+}
+
+pub fn set_break(fd: c_int, enabled: bool) -> Result<()> {
+    if enabled {
+        parse_retval!(unsafe { ioctl(fd, TIOCSBRK) })?;
+    } else {
+        parse_retval!(unsafe { ioctl(fd, TIOCCBRK) })?;
+    }
+
+    Ok(())
+}
+
+pub fn error_counts(fd: c_int) -> Result<ErrorCounts> {
+    let mut counters = SerialCounters::default();
+
+    parse_retval!(unsafe { ioctl(fd, TIOCGICOUNT, &mut counters) })?;
+
+    Ok(ErrorCounts {
+        breaks: counters.brk as u32,
+        frame: counters.frame as u32,
+        parity: counters.parity as u32,
+        overrun: counters.overrun as u32,
+        buffer_overrun: counters.buf_overrun as u32,
+    })
+}
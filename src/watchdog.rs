@@ -0,0 +1,434 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Interface for the hardware watchdog timer.
+//!
+//! The watchdog resets the Raspberry Pi if it isn't periodically notified that
+//! the system is still running, which allows unattended deployments to recover
+//! from a hung application or kernel. [`Watchdog`] controls the watchdog through
+//! the Linux watchdog device `/dev/watchdog`.
+//!
+//! The watchdog is armed as soon as [`Watchdog::new`] opens the device. From then
+//! on, [`keepalive`] needs to be called more often than the configured
+//! [`timeout`], or the Raspberry Pi reboots. Calling [`close`] disarms the
+//! watchdog by sending the magic close character. If the application exits
+//! or crashes without calling [`close`], the watchdog stays armed, and the
+//! Raspberry Pi reboots once the timeout expires.
+//!
+//! After a reboot, [`boot_status`] indicates whether the previous reset was
+//! caused by the watchdog.
+//!
+//! ## Device configuration
+//!
+//! The BCM283x/BCM2711 watchdog driver (`bcm2835_wdt`) is loaded on all Raspberry
+//! Pi OS images, and supports timeouts of up to 15 seconds. The RP1-based
+//! Raspberry Pi 5 uses the same driver. On older images, the driver can be enabled
+//! by adding `dtparam=watchdog=on` to `/boot/config.txt`.
+//!
+//! Only a single process can open `/dev/watchdog` at a time. If systemd is
+//! configured to use the watchdog (`RuntimeWatchdogSec` in
+//! `/etc/systemd/system.conf`), [`Watchdog::new`] returns [`Error::Busy`].
+//! Opening the device requires superuser privileges.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::thread;
+//! use std::time::Duration;
+//!
+//! use rppal::watchdog::Watchdog;
+//!
+//! # fn main() -> rppal::watchdog::Result<()> {
+//! let watchdog = Watchdog::new()?;
+//! watchdog.set_timeout(Duration::from_secs(10))?;
+//!
+//! if watchdog.boot_status()?.card_reset() {
+//!     println!("Recovered from a watchdog reset");
+//! }
+//!
+//! for _ in 0..60 {
+//!     // ...
+//!     watchdog.keepalive()?;
+//!     thread::sleep(Duration::from_secs(1));
+//! }
+//!
+//! // Disarm the watchdog before exiting
+//! watchdog.close()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Watchdog`]: struct.Watchdog.html
+//! [`Watchdog::new`]: struct.Watchdog.html#method.new
+//! [`keepalive`]: struct.Watchdog.html#method.keepalive
+//! [`timeout`]: struct.Watchdog.html#method.timeout
+//! [`close`]: struct.Watchdog.html#method.close
+//! [`boot_status`]: struct.Watchdog.html#method.boot_status
+//! [`Error::Busy`]: enum.Error.html#variant.Busy
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::result;
+use std::time::Duration;
+
+use libc;
+use quick_error::quick_error;
+
+mod ioctl;
+
+const WATCHDOG_PATH: &str = "/dev/watchdog";
+const MAGIC_CLOSE: u8 = b'V';
+
+// Status and capability flags
+const WDIOF_OVERHEAT: u32 = 0x0001;
+const WDIOF_FANFAULT: u32 = 0x0002;
+const WDIOF_EXTERN1: u32 = 0x0004;
+const WDIOF_EXTERN2: u32 = 0x0008;
+const WDIOF_POWERUNDER: u32 = 0x0010;
+const WDIOF_CARDRESET: u32 = 0x0020;
+const WDIOF_POWEROVER: u32 = 0x0040;
+const WDIOF_SETTIMEOUT: u32 = 0x0080;
+const WDIOF_MAGICCLOSE: u32 = 0x0100;
+const WDIOF_PRETIMEOUT: u32 = 0x0200;
+const WDIOF_KEEPALIVEPING: u32 = 0x8000;
+
+quick_error! {
+/// Errors that can occur when accessing the watchdog.
+    #[derive(Debug)]
+    pub enum Error {
+/// I/O error.
+        Io(err: io::Error) { description(err.description()) display("{}", err) cause(err) from() }
+/// Permission denied when opening the watchdog device.
+///
+/// Opening `/dev/watchdog` requires superuser privileges.
+        PermissionDenied(path: PathBuf) { description("watchdog device insufficient permissions") display("{} insufficient permissions", path.display()) }
+/// Watchdog device is already in use.
+///
+/// Only a single process can open the watchdog device at a time. This usually
+/// means systemd is configured to use the watchdog through `RuntimeWatchdogSec`.
+        Busy(path: PathBuf) { description("watchdog device is already in use") display("{} is already in use", path.display()) }
+/// Watchdog device not found.
+///
+/// The watchdog driver isn't loaded. On older Raspberry Pi OS images, it can be
+/// enabled by adding `dtparam=watchdog=on` to `/boot/config.txt`.
+        NotFound(path: PathBuf) { description("watchdog device not found") display("{} not found", path.display()) }
+/// Magic close isn't supported.
+///
+/// The watchdog driver can't be disarmed once it's opened.
+        MagicCloseNotSupported { description("magic close isn't supported") }
+    }
+}
+
+/// Result type returned from methods that can have `watchdog::Error`s.
+pub type Result<T> = result::Result<T, Error>;
+
+/// Watchdog status flags.
+///
+/// `Status` is returned by [`Watchdog::status`] and [`Watchdog::boot_status`].
+/// Most drivers only report a subset of the flags. The Raspberry Pi's watchdog
+/// driver only reports [`card_reset`].
+///
+/// [`Watchdog::status`]: struct.Watchdog.html#method.status
+/// [`Watchdog::boot_status`]: struct.Watchdog.html#method.boot_status
+/// [`card_reset`]: #method.card_reset
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct Status {
+    flags: u32,
+}
+
+impl Status {
+    /// Indicates the system was reset by the watchdog.
+    pub fn card_reset(self) -> bool {
+        (self.flags & WDIOF_CARDRESET) > 0
+    }
+
+    /// Indicates the CPU overheated.
+    pub fn overheat(self) -> bool {
+        (self.flags & WDIOF_OVERHEAT) > 0
+    }
+
+    /// Indicates a fan failed.
+    pub fn fan_fault(self) -> bool {
+        (self.flags & WDIOF_FANFAULT) > 0
+    }
+
+    /// Indicates external relay 1 was triggered.
+    pub fn extern1(self) -> bool {
+        (self.flags & WDIOF_EXTERN1) > 0
+    }
+
+    /// Indicates external relay 2 was triggered.
+    pub fn extern2(self) -> bool {
+        (self.flags & WDIOF_EXTERN2) > 0
+    }
+
+    /// Indicates the power supply voltage dropped too low.
+    pub fn power_under(self) -> bool {
+        (self.flags & WDIOF_POWERUNDER) > 0
+    }
+
+    /// Indicates the power supply voltage rose too high.
+    pub fn power_over(self) -> bool {
+        (self.flags & WDIOF_POWEROVER) > 0
+    }
+
+    /// Indicates a keepalive was received since the status was last read.
+    pub fn keepalive_ping(self) -> bool {
+        (self.flags & WDIOF_KEEPALIVEPING) > 0
+    }
+}
+
+impl fmt::Debug for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Status")
+            .field("card_reset", &self.card_reset())
+            .field("overheat", &self.overheat())
+            .field("fan_fault", &self.fan_fault())
+            .field("extern1", &self.extern1())
+            .field("extern2", &self.extern2())
+            .field("power_under", &self.power_under())
+            .field("power_over", &self.power_over())
+            .field("keepalive_ping", &self.keepalive_ping())
+            .finish()
+    }
+}
+
+/// Watchdog driver information.
+///
+/// `Info` is returned by [`Watchdog::info`].
+///
+/// [`Watchdog::info`]: struct.Watchdog.html#method.info
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Info {
+    identity: String,
+    firmware_version: u32,
+    options: u32,
+}
+
+impl Info {
+    /// Returns the driver's identity, for instance `Broadcom BCM2835 Watchdog timer`.
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// Returns the watchdog's firmware version, if reported by the driver.
+    pub fn firmware_version(&self) -> u32 {
+        self.firmware_version
+    }
+
+    /// Indicates whether the timeout can be changed.
+    pub fn supports_set_timeout(&self) -> bool {
+        (self.options & WDIOF_SETTIMEOUT) > 0
+    }
+
+    /// Indicates whether the watchdog can be disarmed through magic close.
+    pub fn supports_magic_close(&self) -> bool {
+        (self.options & WDIOF_MAGICCLOSE) > 0
+    }
+
+    /// Indicates whether a pretimeout can be configured.
+    pub fn supports_pretimeout(&self) -> bool {
+        (self.options & WDIOF_PRETIMEOUT) > 0
+    }
+
+    /// Indicates whether the driver reports keepalives through [`Status::keepalive_ping`].
+    ///
+    /// [`Status::keepalive_ping`]: struct.Status.html#method.keepalive_ping
+    pub fn supports_keepalive_ping(&self) -> bool {
+        (self.options & WDIOF_KEEPALIVEPING) > 0
+    }
+
+    /// Indicates whether the driver reports resets through [`Status::card_reset`].
+    ///
+    /// [`Status::card_reset`]: struct.Status.html#method.card_reset
+    pub fn supports_card_reset(&self) -> bool {
+        (self.options & WDIOF_CARDRESET) > 0
+    }
+}
+
+/// Provides access to the hardware watchdog timer.
+///
+/// The watchdog is armed when `Watchdog` is constructed, and stays armed when
+/// `Watchdog` goes out of scope, unless it's disarmed by calling [`close`].
+///
+/// More information can be found in the [module documentation].
+///
+/// [`close`]: #method.close
+/// [module documentation]: index.html
+#[derive(Debug)]
+pub struct Watchdog {
+    file: File,
+    path: PathBuf,
+}
+
+impl Watchdog {
+    /// Opens `/dev/watchdog`, and arms the watchdog.
+    pub fn new() -> Result<Watchdog> {
+        Watchdog::with_path(WATCHDOG_PATH)
+    }
+
+    /// Opens the specified watchdog device, for instance `/dev/watchdog1`, and
+    /// arms the watchdog.
+    pub fn with_path<P: AsRef<Path>>(path: P) -> Result<Watchdog> {
+        let path = path.as_ref().to_path_buf();
+
+        let file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_CLOEXEC)
+            .open(&path)
+            .map_err(|err| match err.raw_os_error() {
+                Some(libc::EACCES) | Some(libc::EPERM) => Error::PermissionDenied(path.clone()),
+                Some(libc::EBUSY) => Error::Busy(path.clone()),
+                Some(libc::ENOENT) => Error::NotFound(path.clone()),
+                _ => Error::Io(err),
+            })?;
+
+        Ok(Watchdog { file, path })
+    }
+
+    /// Returns the path of the watchdog device.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns information about the watchdog driver and its capabilities.
+    pub fn info(&self) -> Result<Info> {
+        let info = ioctl::support(self.file.as_raw_fd())?;
+        let len = info
+            .identity
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(info.identity.len());
+
+        Ok(Info {
+            identity: String::from_utf8_lossy(&info.identity[..len]).into_owned(),
+            firmware_version: info.firmware_version,
+            options: info.options,
+        })
+    }
+
+    /// Resets the watchdog timer, which postpones the reboot by the configured
+    /// timeout.
+    pub fn keepalive(&self) -> Result<()> {
+        ioctl::keepalive(self.file.as_raw_fd())?;
+
+        Ok(())
+    }
+
+    /// Returns the watchdog timeout.
+    pub fn timeout(&self) -> Result<Duration> {
+        Ok(seconds_to_duration(ioctl::timeout(self.file.as_raw_fd())?))
+    }
+
+    /// Sets the watchdog timeout, and returns the timeout that was actually set.
+    ///
+    /// The timeout has a resolution of one second, and is rounded up. The driver
+    /// might clamp the timeout to the range supported by the hardware, which is
+    /// 1-15 seconds on the Raspberry Pi. Setting the timeout also resets the
+    /// watchdog timer.
+    pub fn set_timeout(&self, timeout: Duration) -> Result<Duration> {
+        let seconds = timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0);
+        let seconds = seconds.clamp(1, libc::c_int::MAX as u64) as libc::c_int;
+
+        Ok(seconds_to_duration(ioctl::set_timeout(
+            self.file.as_raw_fd(),
+            seconds,
+        )?))
+    }
+
+    /// Returns the time left before the watchdog resets the system.
+    ///
+    /// Not all drivers report the time left, in which case an [`Error::Io`]
+    /// is returned.
+    ///
+    /// [`Error::Io`]: enum.Error.html#variant.Io
+    pub fn time_left(&self) -> Result<Duration> {
+        Ok(seconds_to_duration(ioctl::time_left(
+            self.file.as_raw_fd(),
+        )?))
+    }
+
+    /// Returns the watchdog's current status.
+    pub fn status(&self) -> Result<Status> {
+        Ok(Status {
+            flags: ioctl::status(self.file.as_raw_fd())?,
+        })
+    }
+
+    /// Returns the watchdog's status at the last reboot.
+    ///
+    /// [`Status::card_reset`] indicates whether the previous reset was caused by
+    /// the watchdog.
+    ///
+    /// [`Status::card_reset`]: struct.Status.html#method.card_reset
+    pub fn boot_status(&self) -> Result<Status> {
+        Ok(Status {
+            flags: ioctl::boot_status(self.file.as_raw_fd())?,
+        })
+    }
+
+    /// Temporarily disarms the watchdog, without closing the device.
+    ///
+    /// Use [`enable`] to arm the watchdog again. Not all drivers support this.
+    ///
+    /// [`enable`]: #method.enable
+    pub fn disable(&self) -> Result<()> {
+        ioctl::set_enabled(self.file.as_raw_fd(), false)?;
+
+        Ok(())
+    }
+
+    /// Arms the watchdog after it was disarmed with [`disable`].
+    ///
+    /// [`disable`]: #method.disable
+    pub fn enable(&self) -> Result<()> {
+        ioctl::set_enabled(self.file.as_raw_fd(), true)?;
+
+        Ok(())
+    }
+
+    /// Disarms the watchdog by sending the magic close character, and closes
+    /// the device.
+    ///
+    /// If the driver doesn't support magic close, the device is closed, but the
+    /// watchdog stays armed, and `close` returns [`Error::MagicCloseNotSupported`].
+    /// Kernels built with `CONFIG_WATCHDOG_NOWAYOUT` never disarm the watchdog.
+    ///
+    /// [`Error::MagicCloseNotSupported`]: enum.Error.html#variant.MagicCloseNotSupported
+    pub fn close(mut self) -> Result<()> {
+        let info = self.info()?;
+
+        self.file.write_all(&[MAGIC_CLOSE])?;
+
+        if info.supports_magic_close() {
+            Ok(())
+        } else {
+            Err(Error::MagicCloseNotSupported)
+        }
+    }
+}
+
+fn seconds_to_duration(seconds: libc::c_int) -> Duration {
+    Duration::from_secs(seconds.max(0) as u64)
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2017-2018 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::io;
+use std::result;
+
+use libc::{self, c_int, ioctl};
+
+#[cfg(target_env = "gnu")]
+type IoctlLong = libc::c_ulong;
+#[cfg(target_env = "musl")]
+type IoctlLong = libc::c_long;
+
+pub type Result<T> = result::Result<T, io::Error>;
+
+// Based on watchdog.h and the documentation at
+// https://www.kernel.org/doc/Documentation/watchdog/watchdog-api.rst
+
+// ioctl() requests supported by the watchdog core
+const WDIOC_GETSUPPORT: IoctlLong = 0x8028_5700; // Read watchdog_info
+const WDIOC_GETSTATUS: IoctlLong = 0x8004_5701; // Read the current status flags
+const WDIOC_GETBOOTSTATUS: IoctlLong = 0x8004_5702; // Read the status flags at the last reboot
+const WDIOC_SETOPTIONS: IoctlLong = 0x8004_5704; // Enable or disable the watchdog
+const WDIOC_KEEPALIVE: IoctlLong = 0x8004_5705; // Reset the timer
+const WDIOC_SETTIMEOUT: IoctlLong = 0xC004_5706; // Set the timeout in seconds
+const WDIOC_GETTIMEOUT: IoctlLong = 0x8004_5707; // Read the timeout in seconds
+const WDIOC_GETTIMELEFT: IoctlLong = 0x8004_570A; // Read the time left before a reboot in seconds
+
+// Options for WDIOC_SETOPTIONS
+const WDIOS_DISABLECARD: c_int = 0x0001;
+const WDIOS_ENABLECARD: c_int = 0x0002;
+
+#[repr(C)]
+pub struct WatchdogInfo {
+    pub options: u32,
+    pub firmware_version: u32,
+    pub identity: [u8; 32],
+}
+
+pub fn support(fd: c_int) -> Result<WatchdogInfo> {
+    let mut info = WatchdogInfo {
+        options: 0,
+        firmware_version: 0,
+        identity: [0u8; 32],
+    };
+
+    parse_retval!(unsafe { ioctl(fd, WDIOC_GETSUPPORT, &mut info) })?;
+
+    Ok(info)
+}
+
+pub fn status(fd: c_int) -> Result<u32> {
+    let mut flags: c_int = 0;
+
+    parse_retval!(unsafe { ioctl(fd, WDIOC_GETSTATUS, &mut flags) })?;
+
+    Ok(flags as u32)
+}
+
+pub fn boot_status(fd: c_int) -> Result<u32> {
+    let mut flags: c_int = 0;
+
+    parse_retval!(unsafe { ioctl(fd, WDIOC_GETBOOTSTATUS, &mut flags) })?;
+
+    Ok(flags as u32)
+}
+
+pub fn set_enabled(fd: c_int, enabled: bool) -> Result<()> {
+    let mut options = if enabled {
+        WDIOS_ENABLECARD
+    } else {
+        WDIOS_DISABLECARD
+    };
+
+    parse_retval!(unsafe { ioctl(fd, WDIOC_SETOPTIONS, &mut options) })?;
+
+    Ok(())
+}
+
+pub fn keepalive(fd: c_int) -> Result<()> {
+    let mut dummy: c_int = 0;
+
+    parse_retval!(unsafe { ioctl(fd, WDIOC_KEEPALIVE, &mut dummy) })?;
+
+    Ok(())
+}
+
+// Returns the timeout that was actually set, which is rounded or clamped
+// by the driver.
+pub fn set_timeout(fd: c_int, seconds: c_int) -> Result<c_int> {
+    let mut seconds = seconds;
+
+    parse_retval!(unsafe { ioctl(fd, WDIOC_SETTIMEOUT, &mut seconds) })?;
+
+    Ok(seconds)
+}
+
+pub fn timeout(fd: c_int) -> Result<c_int> {
+    let mut seconds: c_int = 0;
+
+    parse_retval!(unsafe { ioctl(fd, WDIOC_GETTIMEOUT, &mut seconds) })?;
+
+    Ok(seconds)
+}
+
+pub fn time_left(fd: c_int) -> Result<c_int> {
+    let mut seconds: c_int = 0;
+
+    parse_retval!(unsafe { ioctl(fd, WDIOC_GETTIMELEFT, &mut seconds) })?;
+
+    Ok(seconds)
+}